@@ -0,0 +1,86 @@
+-- =============== SUPPLIERS =================
+CREATE TABLE IF NOT EXISTS suppliers (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  name VARCHAR(150) NOT NULL,
+  contact_name VARCHAR(150),
+  telp VARCHAR(30),
+  email VARCHAR(150),
+  address TEXT,
+  lead_time_days INT NOT NULL DEFAULT 1,
+  notes TEXT,
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT suppliers_lead_time_nonneg CHECK (lead_time_days >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS suppliers_name_idx ON suppliers (name) WHERE deleted_at = 0;
+
+-- =============== INGREDIENT SUPPLIERS =================
+-- ID: Bahan mana yang bisa dibeli dari supplier mana, beserta aturan pembeliannya
+-- EN: Which ingredients can be bought from which supplier, with purchasing rules
+CREATE TABLE IF NOT EXISTS ingredient_suppliers (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  ingredient_catalog_uuid UUID NOT NULL REFERENCES ingredient_catalog(uuid),
+  supplier_uuid UUID NOT NULL REFERENCES suppliers(uuid),
+  lead_time_days INT,
+  min_order_qty NUMERIC(12,4),
+  pack_size NUMERIC(12,4),
+  last_price NUMERIC(12,2),
+  is_preferred BOOLEAN NOT NULL DEFAULT FALSE,
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT ingredient_suppliers_lead_time_nonneg CHECK (lead_time_days IS NULL OR lead_time_days >= 0),
+  CONSTRAINT ingredient_suppliers_min_order_nonneg CHECK (min_order_qty IS NULL OR min_order_qty >= 0),
+  CONSTRAINT ingredient_suppliers_pack_size_pos CHECK (pack_size IS NULL OR pack_size > 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ingredient_suppliers_active_uniq
+  ON ingredient_suppliers (ingredient_catalog_uuid, supplier_uuid)
+  WHERE deleted_at = 0;
+CREATE INDEX IF NOT EXISTS ingredient_suppliers_supplier_idx
+  ON ingredient_suppliers (supplier_uuid)
+  WHERE deleted_at = 0;
+
+-- =============== PURCHASE ORDERS =================
+CREATE TABLE IF NOT EXISTS purchase_orders (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  po_no VARCHAR(30) NOT NULL UNIQUE,
+  supplier_uuid UUID NOT NULL REFERENCES suppliers(uuid),
+  store_uuid UUID REFERENCES stores(uuid),
+  status VARCHAR(20) NOT NULL DEFAULT 'DRAFT',
+  expected_at BIGINT,
+  notes TEXT,
+  total_estimated NUMERIC(14,2) NOT NULL DEFAULT 0,
+  created_by UUID REFERENCES users(uuid),
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT purchase_orders_status_valid CHECK (status IN ('DRAFT','SUBMITTED','RECEIVED','CANCELLED'))
+);
+
+CREATE INDEX IF NOT EXISTS purchase_orders_supplier_idx ON purchase_orders (supplier_uuid);
+CREATE INDEX IF NOT EXISTS purchase_orders_status_idx ON purchase_orders (status);
+CREATE INDEX IF NOT EXISTS purchase_orders_created_idx ON purchase_orders (created_at);
+
+-- =============== PURCHASE ORDER ITEMS =================
+CREATE TABLE IF NOT EXISTS purchase_order_items (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  purchase_order_uuid UUID NOT NULL REFERENCES purchase_orders(uuid) ON DELETE CASCADE,
+  ingredient_catalog_uuid UUID NOT NULL REFERENCES ingredient_catalog(uuid),
+  quantity NUMERIC(12,4) NOT NULL,
+  unit_price NUMERIC(12,2),
+  line_total NUMERIC(14,2) NOT NULL DEFAULT 0,
+  unit_of_measure_code VARCHAR(32),
+  unit_of_measure_name VARCHAR(64),
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT purchase_order_items_qty_pos CHECK (quantity > 0)
+);
+
+CREATE INDEX IF NOT EXISTS purchase_order_items_po_idx
+  ON purchase_order_items (purchase_order_uuid);
+CREATE INDEX IF NOT EXISTS purchase_order_items_ingredient_idx
+  ON purchase_order_items (ingredient_catalog_uuid);
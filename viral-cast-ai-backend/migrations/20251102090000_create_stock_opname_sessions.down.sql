@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS stock_opname_items;
+DROP TABLE IF EXISTS stock_opname_sessions;
@@ -0,0 +1,49 @@
+-- =============== STOCK OPNAME SESSIONS =================
+-- ID: Sesi hitung fisik stok; qty sistem di-snapshot saat sesi dibuat
+-- EN: Physical stock count sessions; system quantities are snapshotted on creation
+CREATE TABLE IF NOT EXISTS stock_opname_sessions (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  session_no VARCHAR(30) NOT NULL UNIQUE,
+  store_uuid UUID REFERENCES stores(uuid),
+  status VARCHAR(20) NOT NULL DEFAULT 'OPEN',
+  notes TEXT,
+  snapshot_at BIGINT NOT NULL,
+  created_by UUID REFERENCES users(uuid),
+  approved_by UUID REFERENCES users(uuid),
+  approved_at BIGINT,
+  cancelled_by UUID REFERENCES users(uuid),
+  cancelled_at BIGINT,
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT stock_opname_sessions_status_valid CHECK (status IN ('OPEN','APPROVED','CANCELLED'))
+);
+
+CREATE INDEX IF NOT EXISTS stock_opname_sessions_status_idx ON stock_opname_sessions (status);
+CREATE INDEX IF NOT EXISTS stock_opname_sessions_created_idx ON stock_opname_sessions (created_at);
+
+-- =============== STOCK OPNAME ITEMS =================
+CREATE TABLE IF NOT EXISTS stock_opname_items (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  session_uuid UUID NOT NULL REFERENCES stock_opname_sessions(uuid) ON DELETE CASCADE,
+  ingredient_catalog_uuid UUID NOT NULL REFERENCES ingredient_catalog(uuid),
+  ingredient_name VARCHAR(150),
+  unit_of_measure_code VARCHAR(32),
+  unit_of_measure_name VARCHAR(64),
+  system_qty NUMERIC(12,4) NOT NULL DEFAULT 0,
+  avg_cost NUMERIC(12,4),
+  counted_qty NUMERIC(12,4),
+  counted_by UUID REFERENCES users(uuid),
+  counted_at BIGINT,
+  notes TEXT,
+  adjustment_move_uuid UUID REFERENCES ingredient_stock_moves(uuid),
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT stock_opname_items_counted_nonneg CHECK (counted_qty IS NULL OR counted_qty >= 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS stock_opname_items_session_ingredient_uniq
+  ON stock_opname_items (session_uuid, ingredient_catalog_uuid);
+CREATE INDEX IF NOT EXISTS stock_opname_items_ingredient_idx
+  ON stock_opname_items (ingredient_catalog_uuid);
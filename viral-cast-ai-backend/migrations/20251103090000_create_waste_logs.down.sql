@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS waste_log_items;
+DROP TABLE IF EXISTS waste_logs;
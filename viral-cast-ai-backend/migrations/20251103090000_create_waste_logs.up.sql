@@ -0,0 +1,44 @@
+-- =============== WASTE LOGS =================
+-- ID: Catatan bahan/produk yang dibuang beserta alasannya; setiap item memposting move WASTE
+-- EN: Records of discarded ingredients/products with a reason; every item posts a WASTE move
+CREATE TABLE IF NOT EXISTS waste_logs (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  store_uuid UUID REFERENCES stores(uuid),
+  reason_code VARCHAR(30) NOT NULL,
+  product_uuid UUID REFERENCES products(uuid),
+  product_qty NUMERIC(12,4),
+  notes TEXT,
+  wasted_at BIGINT NOT NULL,
+  total_cost NUMERIC(14,2) NOT NULL DEFAULT 0,
+  logged_by UUID REFERENCES users(uuid),
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT waste_logs_reason_valid CHECK (
+    reason_code IN ('EXPIRED','SPOILED','SPILLED','CUSTOMER_RETURN','OVERPRODUCTION','DAMAGED','OTHER')
+  ),
+  CONSTRAINT waste_logs_product_qty_pos CHECK (product_qty IS NULL OR product_qty > 0)
+);
+
+CREATE INDEX IF NOT EXISTS waste_logs_wasted_at_idx ON waste_logs (wasted_at);
+CREATE INDEX IF NOT EXISTS waste_logs_reason_idx ON waste_logs (reason_code);
+
+-- =============== WASTE LOG ITEMS =================
+CREATE TABLE IF NOT EXISTS waste_log_items (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  waste_log_uuid UUID NOT NULL REFERENCES waste_logs(uuid) ON DELETE CASCADE,
+  ingredient_catalog_uuid UUID NOT NULL REFERENCES ingredient_catalog(uuid),
+  quantity NUMERIC(12,4) NOT NULL,
+  unit_cost NUMERIC(12,4),
+  total_cost NUMERIC(14,2) NOT NULL DEFAULT 0,
+  unit_of_measure_code VARCHAR(32),
+  unit_of_measure_name VARCHAR(64),
+  stock_move_uuid UUID REFERENCES ingredient_stock_moves(uuid),
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT waste_log_items_qty_pos CHECK (quantity > 0)
+);
+
+CREATE INDEX IF NOT EXISTS waste_log_items_log_idx ON waste_log_items (waste_log_uuid);
+CREATE INDEX IF NOT EXISTS waste_log_items_ingredient_idx ON waste_log_items (ingredient_catalog_uuid);
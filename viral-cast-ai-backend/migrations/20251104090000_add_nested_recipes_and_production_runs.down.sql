@@ -0,0 +1,15 @@
+DROP TABLE IF EXISTS production_run_items;
+DROP TABLE IF EXISTS production_runs;
+
+DROP INDEX IF EXISTS recipe_items_component_idx;
+DROP INDEX IF EXISTS recipe_items_component_unique_active;
+ALTER TABLE recipe_items DROP CONSTRAINT IF EXISTS recipe_items_no_self_component;
+ALTER TABLE recipe_items DROP CONSTRAINT IF EXISTS recipe_items_single_source;
+
+-- Komponen resep tidak dapat direpresentasikan tanpa kolom ini
+DELETE FROM recipe_items WHERE ingredient_stocks_uuid IS NULL;
+ALTER TABLE recipe_items ALTER COLUMN ingredient_stocks_uuid SET NOT NULL;
+ALTER TABLE recipe_items DROP COLUMN IF EXISTS component_recipe_sets_uuid;
+
+DROP INDEX IF EXISTS recipe_sets_output_ingredient_uniq;
+ALTER TABLE recipe_sets DROP COLUMN IF EXISTS output_ingredient_catalog_uuid;
@@ -0,0 +1,84 @@
+-- =============== NESTED RECIPES =================
+-- ID: Recipe set dapat menghasilkan bahan setengah jadi (sirup, saus, adonan) yang distok,
+-- dan recipe item dapat merujuk recipe set lain sebagai komponen.
+-- EN: A recipe set may produce a stocked semi-finished ingredient (syrup, sauce, dough),
+-- and a recipe item may reference another recipe set as a component.
+ALTER TABLE recipe_sets
+  ADD COLUMN IF NOT EXISTS output_ingredient_catalog_uuid UUID REFERENCES ingredient_catalog(uuid);
+
+CREATE UNIQUE INDEX IF NOT EXISTS recipe_sets_output_ingredient_uniq
+  ON recipe_sets (output_ingredient_catalog_uuid)
+  WHERE deleted_at = 0 AND output_ingredient_catalog_uuid IS NOT NULL;
+
+ALTER TABLE recipe_items
+  ADD COLUMN IF NOT EXISTS component_recipe_sets_uuid UUID REFERENCES recipe_sets(uuid);
+
+ALTER TABLE recipe_items
+  ALTER COLUMN ingredient_stocks_uuid DROP NOT NULL;
+
+ALTER TABLE recipe_items DROP CONSTRAINT IF EXISTS recipe_items_single_source;
+ALTER TABLE recipe_items ADD CONSTRAINT recipe_items_single_source CHECK (
+  (ingredient_stocks_uuid IS NULL) <> (component_recipe_sets_uuid IS NULL)
+);
+
+ALTER TABLE recipe_items DROP CONSTRAINT IF EXISTS recipe_items_no_self_component;
+ALTER TABLE recipe_items ADD CONSTRAINT recipe_items_no_self_component CHECK (
+  component_recipe_sets_uuid IS NULL OR component_recipe_sets_uuid <> recipe_sets_uuid
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS recipe_items_component_unique_active
+  ON recipe_items (recipe_sets_uuid, component_recipe_sets_uuid)
+  WHERE deleted_at = 0 AND component_recipe_sets_uuid IS NOT NULL;
+CREATE INDEX IF NOT EXISTS recipe_items_component_idx
+  ON recipe_items (component_recipe_sets_uuid);
+
+-- =============== PRODUCTION RUNS =================
+-- ID: Produksi batch: konsumsi bahan (move PRODUCTION negatif) dan hasil setengah jadi
+-- (move PRODUCTION positif, ditandai lewat output_move_uuid)
+-- EN: Batch production: consumes ingredients (negative PRODUCTION moves) and produces the
+-- semi-finished item (positive PRODUCTION move, identified by output_move_uuid)
+CREATE TABLE IF NOT EXISTS production_runs (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  run_no VARCHAR(30) NOT NULL UNIQUE,
+  recipe_sets_uuid UUID NOT NULL REFERENCES recipe_sets(uuid),
+  output_ingredient_catalog_uuid UUID NOT NULL REFERENCES ingredient_catalog(uuid),
+  output_quantity NUMERIC(12,4) NOT NULL,
+  unit_cost NUMERIC(12,4),
+  total_cost NUMERIC(14,2) NOT NULL DEFAULT 0,
+  output_move_uuid UUID REFERENCES ingredient_stock_moves(uuid),
+  store_uuid UUID REFERENCES stores(uuid),
+  notes TEXT,
+  produced_at BIGINT NOT NULL,
+  produced_by UUID REFERENCES users(uuid),
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT production_runs_output_qty_pos CHECK (output_quantity > 0)
+);
+
+CREATE INDEX IF NOT EXISTS production_runs_recipe_idx ON production_runs (recipe_sets_uuid);
+CREATE INDEX IF NOT EXISTS production_runs_produced_at_idx ON production_runs (produced_at);
+CREATE UNIQUE INDEX IF NOT EXISTS production_runs_output_move_uniq
+  ON production_runs (output_move_uuid)
+  WHERE output_move_uuid IS NOT NULL;
+
+-- =============== PRODUCTION RUN ITEMS =================
+CREATE TABLE IF NOT EXISTS production_run_items (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  production_run_uuid UUID NOT NULL REFERENCES production_runs(uuid) ON DELETE CASCADE,
+  ingredient_catalog_uuid UUID NOT NULL REFERENCES ingredient_catalog(uuid),
+  quantity NUMERIC(12,4) NOT NULL,
+  unit_cost NUMERIC(12,4),
+  total_cost NUMERIC(14,2) NOT NULL DEFAULT 0,
+  unit_of_measure_code VARCHAR(32),
+  unit_of_measure_name VARCHAR(64),
+  stock_move_uuid UUID REFERENCES ingredient_stock_moves(uuid),
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT production_run_items_qty_pos CHECK (quantity > 0)
+);
+
+CREATE INDEX IF NOT EXISTS production_run_items_run_idx ON production_run_items (production_run_uuid);
+CREATE INDEX IF NOT EXISTS production_run_items_ingredient_idx
+  ON production_run_items (ingredient_catalog_uuid);
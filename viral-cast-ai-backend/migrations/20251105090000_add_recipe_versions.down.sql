@@ -0,0 +1,10 @@
+DROP FUNCTION IF EXISTS resolve_product_recipe_set(UUID, BIGINT);
+
+DROP INDEX IF EXISTS recipe_sets_product_version_uniq;
+
+ALTER TABLE recipe_sets DROP CONSTRAINT IF EXISTS recipe_sets_version_no_pos;
+
+ALTER TABLE recipe_sets
+  DROP COLUMN IF EXISTS previous_version_uuid,
+  DROP COLUMN IF EXISTS version_no,
+  DROP COLUMN IF EXISTS product_uuid;
@@ -0,0 +1,53 @@
+-- =============== RECIPE VERSIONS =================
+-- ID: Versi resep per produk. Mengedit resep membuat recipe set baru (version_no + 1) yang
+-- berlaku mulai effective_from; versi sebelumnya ditutup (effective_to = effective_from baru).
+-- Rentang berlaku adalah [effective_from, effective_to).
+-- EN: Recipe versions per product. Editing a recipe creates a new recipe set (version_no + 1)
+-- effective from effective_from; the previous version is closed (effective_to = new
+-- effective_from). The effective range is [effective_from, effective_to).
+ALTER TABLE recipe_sets
+  ADD COLUMN IF NOT EXISTS product_uuid UUID REFERENCES products(uuid),
+  ADD COLUMN IF NOT EXISTS version_no INTEGER NOT NULL DEFAULT 1,
+  ADD COLUMN IF NOT EXISTS previous_version_uuid UUID REFERENCES recipe_sets(uuid);
+
+ALTER TABLE recipe_sets DROP CONSTRAINT IF EXISTS recipe_sets_version_no_pos;
+ALTER TABLE recipe_sets ADD CONSTRAINT recipe_sets_version_no_pos CHECK (version_no > 0);
+
+-- Backfill: resep yang dipakai tepat satu produk menjadi versi 1 produk tersebut
+UPDATE recipe_sets rs
+SET product_uuid = owner.product_uuid
+FROM (
+  SELECT recipe_sets_uuid, (ARRAY_AGG(uuid))[1] AS product_uuid
+  FROM products
+  WHERE recipe_sets_uuid IS NOT NULL AND deleted_at = 0
+  GROUP BY recipe_sets_uuid
+  HAVING COUNT(*) = 1
+) owner
+WHERE rs.uuid = owner.recipe_sets_uuid
+  AND rs.product_uuid IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS recipe_sets_product_version_uniq
+  ON recipe_sets (product_uuid, version_no)
+  WHERE deleted_at = 0 AND product_uuid IS NOT NULL;
+
+-- ID: Versi resep produk yang berlaku pada waktu p_at (ms). Jika tidak ada versi yang cocok,
+-- fallback ke products.recipe_sets_uuid (versi terkini).
+-- EN: Product recipe version effective at p_at (ms). Falls back to products.recipe_sets_uuid
+-- (the current version) when no version matches.
+CREATE OR REPLACE FUNCTION resolve_product_recipe_set(p_product_uuid UUID, p_at BIGINT)
+RETURNS UUID AS $$
+  SELECT COALESCE(
+    (
+      SELECT rs.uuid
+      FROM recipe_sets rs
+      WHERE rs.product_uuid = p_product_uuid
+        AND COALESCE(rs.deleted_at, 0) = 0
+        AND rs.is_active
+        AND (rs.effective_from IS NULL OR rs.effective_from <= p_at)
+        AND (rs.effective_to IS NULL OR rs.effective_to > p_at)
+      ORDER BY rs.version_no DESC
+      LIMIT 1
+    ),
+    (SELECT p.recipe_sets_uuid FROM products p WHERE p.uuid = p_product_uuid)
+  );
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,7 @@
+ALTER TABLE stores DROP COLUMN IF EXISTS brand_variants;
+
+ALTER TABLE profiles
+  DROP COLUMN IF EXISTS background_profile_variants,
+  DROP COLUMN IF EXISTS photo_profile_variants;
+
+ALTER TABLE products DROP COLUMN IF EXISTS image_variants;
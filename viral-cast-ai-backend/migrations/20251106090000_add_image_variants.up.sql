@@ -0,0 +1,15 @@
+-- =============== IMAGE VARIANTS =================
+-- ID: URL varian hasil pipeline gambar (original diperkecil, WebP, thumbnail persegi) disimpan
+-- pada entitas agar grid POS dapat memuat thumbnail kecil.
+-- Bentuk: {"original_url", "webp_url", "thumbnails": [{"size", "url", "webp_url"}]}
+-- EN: Image pipeline variant URLs (downscaled original, WebP, square thumbnails) stored on the
+-- entity so the POS grid can load small thumbnails.
+ALTER TABLE products
+  ADD COLUMN IF NOT EXISTS image_variants JSONB;
+
+ALTER TABLE profiles
+  ADD COLUMN IF NOT EXISTS photo_profile_variants JSONB,
+  ADD COLUMN IF NOT EXISTS background_profile_variants JSONB;
+
+ALTER TABLE stores
+  ADD COLUMN IF NOT EXISTS brand_variants JSONB;
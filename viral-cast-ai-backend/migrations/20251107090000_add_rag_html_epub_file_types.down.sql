@@ -0,0 +1,14 @@
+ALTER TABLE rag_configurations
+    ALTER COLUMN supported_file_types
+    SET DEFAULT ARRAY['pdf', 'txt', 'docx', 'md', 'csv', 'xlsx', 'xls', 'xlsm'];
+
+UPDATE rag_configurations
+SET supported_file_types = (
+    SELECT ARRAY(
+        SELECT val
+        FROM unnest(supported_file_types) AS expanded(val)
+        WHERE lower(val) NOT IN ('html', 'htm', 'epub')
+        ORDER BY 1
+    )
+)
+WHERE deleted_at = 0;
@@ -0,0 +1,14 @@
+-- Accept HTML and EPUB uploads for RAG ingestion (PDF was already listed, now actually extracted)
+ALTER TABLE rag_configurations
+    ALTER COLUMN supported_file_types
+    SET DEFAULT ARRAY['pdf', 'txt', 'docx', 'md', 'csv', 'xlsx', 'xls', 'xlsm', 'html', 'htm', 'epub'];
+
+UPDATE rag_configurations
+SET supported_file_types = (
+    SELECT ARRAY(
+        SELECT DISTINCT lower(val)
+        FROM unnest(supported_file_types || ARRAY['pdf', 'html', 'htm', 'epub']) AS expanded(val)
+        ORDER BY 1
+    )
+)
+WHERE deleted_at = 0;
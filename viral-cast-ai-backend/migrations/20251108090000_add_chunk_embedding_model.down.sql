@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_document_chunks_embedding_model;
+
+ALTER TABLE IF EXISTS document_chunks
+    DROP COLUMN IF EXISTS embedding_model;
@@ -0,0 +1,9 @@
+-- Model yang menghasilkan embedding tiap chunk; dipakai untuk mendeteksi chunk yang perlu di-embed ulang
+ALTER TABLE IF EXISTS document_chunks
+    ADD COLUMN IF NOT EXISTS embedding_model VARCHAR(100);
+
+-- Chunk lama dibuat oleh hashing embedder lokal
+UPDATE document_chunks SET embedding_model = 'local-hash' WHERE embedding_model IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_document_chunks_embedding_model
+    ON document_chunks (embedding_model);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_documents_tags;
+DROP INDEX IF EXISTS idx_document_chunks_content_tsv;
+
+ALTER TABLE IF EXISTS document_chunks
+    DROP COLUMN IF EXISTS content_tsv;
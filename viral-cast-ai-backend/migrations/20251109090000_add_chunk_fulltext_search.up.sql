@@ -0,0 +1,13 @@
+-- Full-text search untuk retrieval hybrid RAG (Indonesia + Inggris)
+ALTER TABLE IF EXISTS document_chunks
+    ADD COLUMN IF NOT EXISTS content_tsv tsvector
+    GENERATED ALWAYS AS (
+        to_tsvector('indonesian', coalesce(content, '')) || to_tsvector('english', coalesce(content, ''))
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_document_chunks_content_tsv
+    ON document_chunks USING GIN (content_tsv);
+
+-- Filter tag dokumen (operator &&)
+CREATE INDEX IF NOT EXISTS idx_documents_tags
+    ON documents USING GIN (tags);
@@ -0,0 +1,6 @@
+ALTER TABLE IF EXISTS rag_configurations
+    DROP CONSTRAINT IF EXISTS chk_rag_configurations_chunk_size_unit,
+    DROP CONSTRAINT IF EXISTS chk_rag_configurations_chunking_strategy,
+    DROP COLUMN IF EXISTS context_token_budget,
+    DROP COLUMN IF EXISTS chunk_size_unit,
+    DROP COLUMN IF EXISTS chunking_strategy;
@@ -0,0 +1,13 @@
+-- Strategi chunking RAG: fixed (jendela karakter lama) | sentence | heading
+ALTER TABLE IF EXISTS rag_configurations
+    ADD COLUMN IF NOT EXISTS chunking_strategy VARCHAR(20) NOT NULL DEFAULT 'sentence',
+    ADD COLUMN IF NOT EXISTS chunk_size_unit VARCHAR(10) NOT NULL DEFAULT 'chars',
+    ADD COLUMN IF NOT EXISTS context_token_budget INTEGER NOT NULL DEFAULT 3000;
+
+ALTER TABLE rag_configurations
+    DROP CONSTRAINT IF EXISTS chk_rag_configurations_chunking_strategy,
+    ADD CONSTRAINT chk_rag_configurations_chunking_strategy
+        CHECK (chunking_strategy IN ('fixed', 'sentence', 'heading')),
+    DROP CONSTRAINT IF EXISTS chk_rag_configurations_chunk_size_unit,
+    ADD CONSTRAINT chk_rag_configurations_chunk_size_unit
+        CHECK (chunk_size_unit IN ('chars', 'tokens'));
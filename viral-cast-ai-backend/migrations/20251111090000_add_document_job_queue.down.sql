@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_processing_jobs_queue;
+
+ALTER TABLE IF EXISTS document_processing_jobs
+    DROP COLUMN IF EXISTS locked_by,
+    DROP COLUMN IF EXISTS next_attempt_at;
@@ -0,0 +1,13 @@
+-- Antrian ingest RAG berbasis Postgres: worker klaim job via FOR UPDATE SKIP LOCKED,
+-- job gagal dijadwalkan ulang dengan backoff lewat next_attempt_at
+ALTER TABLE IF EXISTS document_processing_jobs
+    ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    ADD COLUMN IF NOT EXISTS locked_by VARCHAR(100);
+
+UPDATE document_processing_jobs
+SET next_attempt_at = COALESCE(created_at, NOW())
+WHERE next_attempt_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_processing_jobs_queue
+    ON document_processing_jobs(priority DESC, next_attempt_at)
+    WHERE status = 'pending' AND deleted_at = 0;
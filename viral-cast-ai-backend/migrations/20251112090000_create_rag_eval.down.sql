@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS rag_eval_runs;
+DROP TABLE IF EXISTS rag_eval_questions;
+DROP TABLE IF EXISTS rag_eval_sets;
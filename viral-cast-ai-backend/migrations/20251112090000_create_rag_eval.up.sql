@@ -0,0 +1,38 @@
+-- Set evaluasi RAG: pertanyaan emas dengan dokumen/chunk yang diharapkan, plus riwayat run
+CREATE TABLE IF NOT EXISTS rag_eval_sets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS rag_eval_questions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    eval_set_id UUID NOT NULL REFERENCES rag_eval_sets(id) ON DELETE CASCADE,
+    question TEXT NOT NULL,
+    expected_document_ids UUID[] NOT NULL DEFAULT '{}',
+    expected_chunk_ids UUID[] NOT NULL DEFAULT '{}',
+    reference_answer TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at BIGINT NOT NULL DEFAULT 0,
+    CONSTRAINT chk_rag_eval_questions_expectation
+        CHECK (cardinality(expected_document_ids) > 0 OR cardinality(expected_chunk_ids) > 0)
+);
+
+CREATE TABLE IF NOT EXISTS rag_eval_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    eval_set_id UUID NOT NULL REFERENCES rag_eval_sets(id) ON DELETE CASCADE,
+    k INTEGER NOT NULL,
+    question_count INTEGER NOT NULL,
+    recall_at_k REAL NOT NULL,
+    mrr REAL NOT NULL,
+    answer_similarity REAL,
+    config_snapshot JSONB NOT NULL DEFAULT '{}',
+    results JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rag_eval_questions_set ON rag_eval_questions(eval_set_id) WHERE deleted_at = 0;
+CREATE INDEX IF NOT EXISTS idx_rag_eval_runs_set_created ON rag_eval_runs(eval_set_id, created_at DESC);
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_documents_store_uuid;
+
+ALTER TABLE IF EXISTS documents
+    DROP CONSTRAINT IF EXISTS chk_documents_visibility,
+    DROP CONSTRAINT IF EXISTS documents_store_uuid_fk,
+    DROP COLUMN IF EXISTS visibility,
+    DROP COLUMN IF EXISTS store_uuid;
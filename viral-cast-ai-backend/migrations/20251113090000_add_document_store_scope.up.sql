@@ -0,0 +1,32 @@
+-- Basis pengetahuan RAG per toko: dokumen dimiliki satu toko (visibility 'store') atau
+-- dibagikan ke seluruh platform oleh Super Admin (visibility 'platform', store_uuid NULL)
+ALTER TABLE IF EXISTS documents
+    ADD COLUMN IF NOT EXISTS store_uuid UUID,
+    ADD COLUMN IF NOT EXISTS visibility VARCHAR(20) NOT NULL DEFAULT 'store';
+
+ALTER TABLE documents
+  ADD CONSTRAINT documents_store_uuid_fk
+    FOREIGN KEY (store_uuid)
+    REFERENCES stores(uuid)
+    ON UPDATE CASCADE
+    ON DELETE RESTRICT;
+
+-- Dokumen lama mengikuti toko pengunggahnya; yang tidak bisa dilacak tetap terlihat oleh
+-- semua pengguna seperti sebelumnya, sebagai dokumen platform
+UPDATE documents d
+SET store_uuid = p.store_uuid
+FROM profiles p
+WHERE p.user_uuid = d.uploaded_by
+  AND p.deleted_at = 0
+  AND p.store_uuid IS NOT NULL
+  AND d.store_uuid IS NULL;
+
+UPDATE documents SET visibility = 'platform' WHERE store_uuid IS NULL;
+
+ALTER TABLE documents
+  ADD CONSTRAINT chk_documents_visibility CHECK (
+    (visibility = 'platform' AND store_uuid IS NULL)
+    OR (visibility = 'store' AND store_uuid IS NOT NULL)
+  );
+
+CREATE INDEX IF NOT EXISTS idx_documents_store_uuid ON documents(store_uuid) WHERE deleted_at = 0;
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_auth_security_events_user_uuid;
+DROP TABLE IF EXISTS auth_security_events;
+DROP INDEX IF EXISTS idx_auth_refresh_tokens_family_uuid;
+DROP TABLE IF EXISTS auth_refresh_tokens;
+DROP INDEX IF EXISTS idx_auth_token_families_user_uuid;
+DROP TABLE IF EXISTS auth_token_families;
@@ -0,0 +1,43 @@
+-- Keluarga refresh token: satu keluarga per login, setiap refresh merotasi token di dalamnya.
+-- Token yang sudah dirotasi dipakai lagi => seluruh keluarga dicabut (reuse detection).
+CREATE TABLE IF NOT EXISTS auth_token_families (
+    uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+    user_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    rotation_count INTEGER NOT NULL DEFAULT 0,
+    revoked_at BIGINT,
+    revoked_reason VARCHAR(50),
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT,
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_token_families_user_uuid
+    ON auth_token_families(user_uuid)
+    WHERE revoked_at IS NULL;
+
+-- Setiap refresh token yang pernah diterbitkan (token_uuid = klaim JWT / key Redis)
+-- beserta access token yang diterbitkan bersamanya
+CREATE TABLE IF NOT EXISTS auth_refresh_tokens (
+    token_uuid UUID NOT NULL PRIMARY KEY,
+    family_uuid UUID NOT NULL REFERENCES auth_token_families(uuid) ON DELETE CASCADE,
+    access_token_uuid UUID,
+    rotated_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_refresh_tokens_family_uuid
+    ON auth_refresh_tokens(family_uuid);
+
+-- Catatan kejadian keamanan autentikasi (mis. refresh_token_reuse)
+CREATE TABLE IF NOT EXISTS auth_security_events (
+    uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+    user_uuid UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    family_uuid UUID REFERENCES auth_token_families(uuid) ON DELETE SET NULL,
+    event_type VARCHAR(50) NOT NULL,
+    ip_address VARCHAR(64),
+    user_agent TEXT,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_security_events_user_uuid
+    ON auth_security_events(user_uuid, created_at DESC);
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_auth_refresh_tokens_access_token_uuid;
+
+ALTER TABLE auth_token_families
+    DROP COLUMN IF EXISTS last_seen_at,
+    DROP COLUMN IF EXISTS ip_address,
+    DROP COLUMN IF EXISTS user_agent,
+    DROP COLUMN IF EXISTS device_name;
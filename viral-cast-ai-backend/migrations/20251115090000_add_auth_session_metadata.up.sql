@@ -0,0 +1,12 @@
+-- Keluarga token = sesi login: simpan perangkat, user agent, IP dan waktu terakhir aktif
+ALTER TABLE auth_token_families
+    ADD COLUMN IF NOT EXISTS device_name VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS user_agent TEXT,
+    ADD COLUMN IF NOT EXISTS ip_address VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS last_seen_at BIGINT;
+
+UPDATE auth_token_families SET last_seen_at = updated_at WHERE last_seen_at IS NULL;
+
+-- Middleware auth mencari sesi dari access token untuk last_seen_at dan sesi "current"
+CREATE INDEX IF NOT EXISTS idx_auth_refresh_tokens_access_token_uuid
+    ON auth_refresh_tokens(access_token_uuid);
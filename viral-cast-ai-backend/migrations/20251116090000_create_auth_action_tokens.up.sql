@@ -0,0 +1,19 @@
+-- Email terverifikasi (epoch ms); NULL = belum diverifikasi
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at BIGINT;
+
+-- Token sekali pakai untuk verifikasi email dan reset password.
+-- Yang disimpan hanya hash SHA-256; token mentah hanya ada di email.
+CREATE TABLE IF NOT EXISTS auth_action_tokens (
+    uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+    user_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    purpose VARCHAR(30) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    -- Alamat email tujuan saat token diterbitkan (verifikasi batal bila email diganti)
+    email VARCHAR(255),
+    expires_at BIGINT NOT NULL,
+    used_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_action_tokens_user_purpose
+    ON auth_action_tokens(user_uuid, purpose, created_at DESC);
@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS idx_auth_token_families_pos_device_uuid;
+
+ALTER TABLE auth_token_families
+    DROP COLUMN IF EXISTS expires_at,
+    DROP COLUMN IF EXISTS pos_device_uuid;
+
+DROP TABLE IF EXISTS cashier_pins;
+DROP TABLE IF EXISTS pos_devices;
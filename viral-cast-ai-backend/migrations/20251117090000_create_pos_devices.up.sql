@@ -0,0 +1,34 @@
+-- Terminal POS bersama (mis. tablet kasir) yang didaftarkan owner/admin ke satu toko.
+-- Yang disimpan hanya hash SHA-256 dari kredensial perangkat.
+CREATE TABLE IF NOT EXISTS pos_devices (
+    uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+    store_uuid UUID NOT NULL REFERENCES stores(uuid) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    credential_hash VARCHAR(64) NOT NULL UNIQUE,
+    enrolled_by UUID REFERENCES users(uuid) ON DELETE SET NULL,
+    last_used_at BIGINT,
+    revoked_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT,
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_pos_devices_store_uuid
+    ON pos_devices(store_uuid)
+    WHERE revoked_at IS NULL;
+
+-- PIN kasir (hash Argon2) untuk membuka terminal POS di toko sendiri
+CREATE TABLE IF NOT EXISTS cashier_pins (
+    user_uuid UUID NOT NULL PRIMARY KEY REFERENCES users(uuid) ON DELETE CASCADE,
+    pin_hash TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT,
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+-- Sesi PIN: terikat ke satu perangkat, tanpa refresh token, berakhir pada expires_at
+ALTER TABLE auth_token_families
+    ADD COLUMN IF NOT EXISTS pos_device_uuid UUID REFERENCES pos_devices(uuid) ON DELETE CASCADE,
+    ADD COLUMN IF NOT EXISTS expires_at BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_auth_token_families_pos_device_uuid
+    ON auth_token_families(pos_device_uuid)
+    WHERE pos_device_uuid IS NOT NULL AND revoked_at IS NULL;
@@ -0,0 +1,23 @@
+-- Secret TOTP (base32) untuk autentikasi dua faktor; confirmed_at NULL = pendaftaran belum dikonfirmasi.
+-- last_used_step mencegah kode yang sama dipakai dua kali.
+CREATE TABLE IF NOT EXISTS auth_totp_secrets (
+    user_uuid UUID NOT NULL PRIMARY KEY REFERENCES users(uuid) ON DELETE CASCADE,
+    secret VARCHAR(64) NOT NULL,
+    confirmed_at BIGINT,
+    last_used_step BIGINT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT,
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+-- Kode pemulihan sekali pakai (hash Argon2) bila aplikasi authenticator hilang
+CREATE TABLE IF NOT EXISTS auth_recovery_codes (
+    uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+    user_uuid UUID NOT NULL REFERENCES users(uuid) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    used_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM NOW()) * 1000)::BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_recovery_codes_user_uuid
+    ON auth_recovery_codes(user_uuid)
+    WHERE used_at IS NULL;
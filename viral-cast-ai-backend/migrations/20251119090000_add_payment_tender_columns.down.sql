@@ -0,0 +1,5 @@
+ALTER TABLE payments DROP CONSTRAINT IF EXISTS payments_change_nonneg;
+
+ALTER TABLE payments
+  DROP COLUMN IF EXISTS change_amount,
+  DROP COLUMN IF EXISTS tendered_amount;
@@ -0,0 +1,11 @@
+-- =============== PAYMENT TENDER ===============
+-- ID: `amount` = porsi yang dialokasikan ke tagihan pesanan; `tendered_amount` = uang yang
+--     diserahkan pelanggan; `change_amount` = kembalian (hanya untuk CASH).
+-- EN: `amount` = portion allocated to the order bill; `tendered_amount` = what the customer
+--     handed over; `change_amount` = change given back (CASH only).
+ALTER TABLE payments
+  ADD COLUMN IF NOT EXISTS tendered_amount NUMERIC(12,2),
+  ADD COLUMN IF NOT EXISTS change_amount NUMERIC(12,2) NOT NULL DEFAULT 0;
+
+ALTER TABLE payments
+  ADD CONSTRAINT payments_change_nonneg CHECK (change_amount >= 0);
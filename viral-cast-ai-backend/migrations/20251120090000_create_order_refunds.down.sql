@@ -0,0 +1,16 @@
+DROP INDEX IF EXISTS payments_refunded_payment_idx;
+DROP INDEX IF EXISTS payments_refund_idx;
+
+ALTER TABLE payments DROP CONSTRAINT IF EXISTS payments_amount_sign;
+
+DELETE FROM payments WHERE refund_uuid IS NOT NULL;
+
+ALTER TABLE payments
+  DROP COLUMN IF EXISTS refunded_payment_uuid,
+  DROP COLUMN IF EXISTS refund_uuid;
+
+ALTER TABLE payments
+  ADD CONSTRAINT payments_amount_pos CHECK (amount >= 0);
+
+DROP TABLE IF EXISTS order_refund_items;
+DROP TABLE IF EXISTS order_refunds;
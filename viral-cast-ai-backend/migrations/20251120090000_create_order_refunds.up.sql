@@ -0,0 +1,75 @@
+-- =============== ORDER REFUNDS =================
+-- ID: Refund penuh/sebagian per baris pesanan dan void pesanan. Permintaan berstatus PENDING
+--     sampai disetujui supervisor; saat disetujui dibuat pembayaran negatif per tender dan,
+--     bila restock, move stok RETURN.
+-- EN: Full/partial line-item refunds and order voids. Requests stay PENDING until a
+--     supervisor approves them; approval books negative payments per tender and, when
+--     restocking, RETURN stock moves.
+CREATE TABLE IF NOT EXISTS order_refunds (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  refund_no VARCHAR(30) NOT NULL UNIQUE,
+  order_uuid UUID NOT NULL REFERENCES orders(uuid),
+  kind VARCHAR(10) NOT NULL DEFAULT 'REFUND',
+  status VARCHAR(10) NOT NULL DEFAULT 'PENDING',
+  reason TEXT NOT NULL,
+  amount NUMERIC(12,2) NOT NULL DEFAULT 0,
+  cost_amount NUMERIC(14,2) NOT NULL DEFAULT 0,
+  -- Laba yang dibatalkan = amount - biaya bahan yang kembali ke stok
+  profit_reversed NUMERIC(12,2) NOT NULL DEFAULT 0,
+  restock BOOLEAN NOT NULL DEFAULT FALSE,
+  qris_sandbox BOOLEAN NOT NULL DEFAULT FALSE,
+  requested_by UUID REFERENCES users(uuid),
+  -- Supervisor yang menyetujui atau menolak
+  approved_by UUID REFERENCES users(uuid),
+  approved_at BIGINT,
+  rejected_reason TEXT,
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  updated_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  deleted_at BIGINT DEFAULT 0,
+  CONSTRAINT order_refunds_kind_valid CHECK (kind IN ('REFUND','VOID')),
+  CONSTRAINT order_refunds_status_valid CHECK (status IN ('PENDING','APPROVED','REJECTED')),
+  CONSTRAINT order_refunds_reason_present CHECK (length(btrim(reason)) > 0),
+  CONSTRAINT order_refunds_amount_nonneg CHECK (amount >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS order_refunds_order_idx ON order_refunds (order_uuid);
+CREATE INDEX IF NOT EXISTS order_refunds_status_idx ON order_refunds (status);
+
+-- =============== ORDER REFUND ITEMS =================
+CREATE TABLE IF NOT EXISTS order_refund_items (
+  uuid UUID DEFAULT gen_uuid_v7() NOT NULL PRIMARY KEY,
+  refund_uuid UUID NOT NULL REFERENCES order_refunds(uuid) ON DELETE CASCADE,
+  order_item_uuid UUID NOT NULL REFERENCES order_items(uuid),
+  product_uuid UUID NOT NULL REFERENCES products(uuid),
+  qty NUMERIC(12,4) NOT NULL,
+  unit_price NUMERIC(12,2) NOT NULL,
+  -- Porsi total pesanan (setelah diskon/pajak) yang dikembalikan untuk baris ini
+  amount NUMERIC(12,2) NOT NULL,
+  unit_cost NUMERIC(12,2),
+  cost_amount NUMERIC(14,2) NOT NULL DEFAULT 0,
+  created_at BIGINT DEFAULT EXTRACT(EPOCH FROM NOW()) * 1000,
+  CONSTRAINT order_refund_items_qty_pos CHECK (qty > 0)
+);
+
+CREATE INDEX IF NOT EXISTS order_refund_items_refund_idx ON order_refund_items (refund_uuid);
+CREATE INDEX IF NOT EXISTS order_refund_items_order_item_idx ON order_refund_items (order_item_uuid);
+
+-- =============== PAYMENTS: REFUND ROWS =================
+-- ID: Refund dicatat sebagai pembayaran negatif yang menunjuk ke refund dan tender asalnya.
+-- EN: Refunds are booked as negative payments pointing at the refund and the original tender.
+ALTER TABLE payments DROP CONSTRAINT IF EXISTS payments_amount_pos;
+
+ALTER TABLE payments
+  ADD COLUMN IF NOT EXISTS refund_uuid UUID REFERENCES order_refunds(uuid),
+  ADD COLUMN IF NOT EXISTS refunded_payment_uuid UUID REFERENCES payments(uuid);
+
+ALTER TABLE payments
+  ADD CONSTRAINT payments_amount_sign CHECK (
+    (refund_uuid IS NULL AND amount >= 0)
+    OR (refund_uuid IS NOT NULL AND amount < 0)
+  );
+
+CREATE INDEX IF NOT EXISTS payments_refund_idx
+  ON payments (refund_uuid) WHERE refund_uuid IS NOT NULL;
+CREATE INDEX IF NOT EXISTS payments_refunded_payment_idx
+  ON payments (refunded_payment_uuid) WHERE refunded_payment_uuid IS NOT NULL;
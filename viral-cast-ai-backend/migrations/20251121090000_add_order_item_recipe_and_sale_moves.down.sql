@@ -0,0 +1,18 @@
+DROP INDEX IF EXISTS idx_ingredient_stock_moves_sale_ref;
+
+-- Move SALE (selalu negatif) menjadi ADJUSTMENT agar saldo stok tidak berubah
+UPDATE ingredient_stock_moves
+SET ref_type = 'ADJUSTMENT', quantity = -ABS(quantity)
+WHERE UPPER(ref_type) = 'SALE';
+
+ALTER TABLE ingredient_stock_moves
+  DROP CONSTRAINT IF EXISTS ingredient_stock_moves_ref_type_allowed;
+ALTER TABLE ingredient_stock_moves
+  ADD CONSTRAINT ingredient_stock_moves_ref_type_allowed
+  CHECK (
+    ref_type IS NULL
+    OR UPPER(ref_type) IN ('PURCHASE','PRODUCTION','ADJUSTMENT','WASTE','RETURN')
+  );
+
+DROP INDEX IF EXISTS order_items_recipe_sets_idx;
+ALTER TABLE order_items DROP COLUMN IF EXISTS recipe_sets_uuid;
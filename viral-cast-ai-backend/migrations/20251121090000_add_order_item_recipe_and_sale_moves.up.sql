@@ -0,0 +1,34 @@
+-- =============== SALE CONSUMPTION =================
+-- ID: Versi resep yang dipakai tiap baris pesanan disimpan saat pesanan dibuat, sehingga
+-- biaya dan konsumsi stok tetap mengikuti versi yang berlaku pada created_at pesanan.
+-- Saat pesanan lunas (PAID) bahan dikurangi lewat pergerakan SALE (ref_uuid = pesanan).
+-- EN: The recipe version used by each order line is stored when the order is created, so
+-- costing and stock consumption keep following the version effective at the order's
+-- created_at. Once the order is PAID its ingredients are deducted through SALE moves
+-- (ref_uuid = order).
+ALTER TABLE order_items
+  ADD COLUMN IF NOT EXISTS recipe_sets_uuid UUID REFERENCES recipe_sets(uuid);
+
+-- Backfill: versi yang berlaku saat pesanan lama dibuat
+UPDATE order_items oi
+SET recipe_sets_uuid = resolve_product_recipe_set(oi.product_uuid, o.created_at)
+FROM orders o
+WHERE o.uuid = oi.order_uuid
+  AND oi.recipe_sets_uuid IS NULL;
+
+CREATE INDEX IF NOT EXISTS order_items_recipe_sets_idx
+  ON order_items (recipe_sets_uuid)
+  WHERE recipe_sets_uuid IS NOT NULL;
+
+ALTER TABLE ingredient_stock_moves
+  DROP CONSTRAINT IF EXISTS ingredient_stock_moves_ref_type_allowed;
+ALTER TABLE ingredient_stock_moves
+  ADD CONSTRAINT ingredient_stock_moves_ref_type_allowed
+  CHECK (
+    ref_type IS NULL
+    OR UPPER(ref_type) IN ('PURCHASE','PRODUCTION','ADJUSTMENT','WASTE','RETURN','SALE')
+  );
+
+CREATE INDEX IF NOT EXISTS idx_ingredient_stock_moves_sale_ref
+  ON ingredient_stock_moves (ref_uuid)
+  WHERE ref_type = 'SALE' AND deleted_at = 0;
@@ -0,0 +1,7 @@
+-- Dimensi vektor yang sedang dipakai tiap koleksi Milvus. Worker re-embed membuat ulang
+-- koleksi bila embedding_dimensions berubah (Milvus tidak bisa mengubah dim secara in-place).
+CREATE TABLE IF NOT EXISTS rag_vector_collections (
+    collection_name VARCHAR(255) PRIMARY KEY,
+    dimensions INTEGER NOT NULL CHECK (dimensions > 0),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS document_chunks_active_chunk_index_unique;
+
+-- Chunk versi lama bertabrakan dengan constraint penuh; vektornya sudah dihapus saat reindex
+DELETE FROM document_chunks WHERE deleted_at <> 0;
+
+ALTER TABLE IF EXISTS document_chunks
+    ADD CONSTRAINT document_chunks_document_id_chunk_index_key UNIQUE (document_id, chunk_index);
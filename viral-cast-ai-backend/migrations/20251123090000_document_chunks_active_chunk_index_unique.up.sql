@@ -0,0 +1,8 @@
+-- Reindex menandai chunk lama deleted_at lalu menyisipkan chunk baru dengan chunk_index yang
+-- sama dalam satu transaksi; keunikan (document_id, chunk_index) hanya berlaku untuk chunk aktif.
+ALTER TABLE IF EXISTS document_chunks
+    DROP CONSTRAINT IF EXISTS document_chunks_document_id_chunk_index_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS document_chunks_active_chunk_index_unique
+    ON document_chunks (document_id, chunk_index)
+    WHERE deleted_at = 0;
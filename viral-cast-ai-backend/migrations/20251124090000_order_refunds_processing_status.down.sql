@@ -0,0 +1,14 @@
+-- Refund yang masih PROCESSING kembali ke PENDING; cadangan pembayaran negatifnya dihapus
+DELETE FROM payments
+WHERE paid_at IS NULL
+  AND refund_uuid IN (SELECT uuid FROM order_refunds WHERE status = 'PROCESSING');
+
+UPDATE order_refunds
+SET status = 'PENDING', approved_by = NULL
+WHERE status = 'PROCESSING';
+
+ALTER TABLE order_refunds DROP CONSTRAINT IF EXISTS order_refunds_status_valid;
+
+ALTER TABLE order_refunds
+  ADD CONSTRAINT order_refunds_status_valid
+  CHECK (status IN ('PENDING','APPROVED','REJECTED'));
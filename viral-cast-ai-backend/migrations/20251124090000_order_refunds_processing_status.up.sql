@@ -0,0 +1,12 @@
+-- =============== ORDER REFUNDS: PROCESSING =================
+-- ID: Approval refund berjalan dalam dua transaksi dengan panggilan Xendit di antaranya.
+--     PROCESSING = pembayaran negatif sudah dicadangkan (paid_at NULL), menunggu gateway;
+--     approval yang diulang melanjutkan refund dari status ini.
+-- EN: Refund approval runs in two transactions with the Xendit calls in between.
+--     PROCESSING = negative payments are reserved (paid_at NULL) and wait on the gateway;
+--     retrying the approval resumes the refund from this state.
+ALTER TABLE order_refunds DROP CONSTRAINT IF EXISTS order_refunds_status_valid;
+
+ALTER TABLE order_refunds
+  ADD CONSTRAINT order_refunds_status_valid
+  CHECK (status IN ('PENDING','PROCESSING','APPROVED','REJECTED'));
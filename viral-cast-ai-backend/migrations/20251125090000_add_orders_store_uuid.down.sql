@@ -0,0 +1,9 @@
+-- Remove store_uuid foreign key and column from orders
+
+ALTER TABLE orders
+  DROP CONSTRAINT IF EXISTS orders_store_uuid_fk;
+
+DROP INDEX IF EXISTS orders_store_uuid_idx;
+
+ALTER TABLE orders
+  DROP COLUMN IF EXISTS store_uuid;
@@ -0,0 +1,23 @@
+-- =============== ORDERS: STORE =================
+-- ID: Toko tempat pesanan dibuat; dipakai untuk membatasi siapa yang boleh menyetujui refund.
+--     Pesanan lama diisi dari toko profil kasirnya.
+-- EN: The store an order was placed in; used to scope who may approve its refunds.
+--     Existing orders are backfilled from their cashier's profile store.
+ALTER TABLE orders
+  ADD COLUMN IF NOT EXISTS store_uuid UUID;
+
+CREATE INDEX IF NOT EXISTS orders_store_uuid_idx ON orders(store_uuid);
+
+ALTER TABLE orders
+  ADD CONSTRAINT orders_store_uuid_fk
+    FOREIGN KEY (store_uuid)
+    REFERENCES stores(uuid)
+    ON DELETE SET NULL
+    ON UPDATE CASCADE;
+
+UPDATE orders o
+SET store_uuid = p.store_uuid
+FROM profiles p
+WHERE o.store_uuid IS NULL
+  AND p.user_uuid = o.cashier_uuid
+  AND p.deleted_at = 0;
@@ -0,0 +1,4 @@
+-- Remove the gateway claim column from order_refunds
+
+ALTER TABLE order_refunds
+  DROP COLUMN IF EXISTS gateway_started_at;
@@ -0,0 +1,9 @@
+-- =============== ORDER REFUNDS: GATEWAY CLAIM =================
+-- ID: Waktu approver mengklaim refund PROCESSING untuk memanggil Xendit. Klaim di-commit
+--     sebelum panggilan gateway, sehingga tidak ada transaksi atau kunci baris yang ditahan;
+--     penolakan dan approver lain menghormatinya sampai masa klaim habis.
+-- EN: When an approver claimed a PROCESSING refund to call Xendit. The claim commits before
+--     the gateway call, so no transaction or row lock is held; rejections and other approvers
+--     respect it until the claim expires.
+ALTER TABLE order_refunds
+  ADD COLUMN IF NOT EXISTS gateway_started_at BIGINT;
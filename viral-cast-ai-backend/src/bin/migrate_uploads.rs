@@ -0,0 +1,203 @@
+//! ID: Pindahkan file upload lama dari disk lokal ke object storage S3-compatible,
+//!     lalu (opsional) tulis ulang URL yang tersimpan di database.
+//! EN: Move legacy uploads from local disk to S3-compatible object storage,
+//!     then (optionally) rewrite the URLs stored in the database.
+//!
+//! Usage: cargo run --bin migrate_uploads -- [--dry-run] [--skip-db]
+use anyhow::{anyhow, Result};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Pool, Postgres};
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[allow(dead_code)]
+#[path = "../services/blob_store.rs"]
+mod blob_store;
+
+use blob_store::{content_type_for_key, BlobStore, BlobStoreConfig};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let skip_db = args.iter().any(|a| a == "--skip-db");
+
+    let config = BlobStoreConfig::from_env();
+    let target = config
+        .build_s3()
+        .map_err(|e| anyhow!("Target storage: {}", e))?;
+    let root = PathBuf::from(&config.local_root);
+    if !root.is_dir() {
+        return Err(anyhow!("Local upload root {:?} does not exist", root));
+    }
+
+    // Sumber dokumen RAG (documents/) ada di root privat terpisah
+    let private_root = PathBuf::from(&config.local_private_root);
+    let mut files = Vec::new();
+    for dir in [&root, &private_root] {
+        let mut found = Vec::new();
+        if dir.is_dir() {
+            collect_files(dir, &mut found)?;
+        }
+        files.extend(found.into_iter().map(|path| (dir.clone(), path)));
+    }
+    println!(
+        "📦 Found {} file(s) under {:?} and {:?}{}",
+        files.len(),
+        root,
+        private_root,
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let mut copied = 0usize;
+    let mut skipped = 0usize;
+    let mut failed = 0usize;
+    for (root, path) in files {
+        let key = match storage_key(&root, &path) {
+            Some(key) => key,
+            None => {
+                println!("⚠️ Skipping {:?}: not a valid storage key", path);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        if dry_run {
+            println!("→ {} => {}", key, target.public_url(&key));
+            continue;
+        }
+
+        // Idempotent: file yang sudah ada di bucket tidak diupload ulang
+        match target.exists(&key).await {
+            Ok(true) => {
+                skipped += 1;
+                continue;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                println!("🔥 {}: {}", key, e);
+                failed += 1;
+                continue;
+            }
+        }
+
+        let bytes = tokio::fs::read(&path).await?;
+        match target
+            .put(&key, bytes, Some(content_type_for_key(&key)))
+            .await
+        {
+            Ok(()) => copied += 1,
+            Err(e) => {
+                println!("🔥 {}: {}", key, e);
+                failed += 1;
+            }
+        }
+    }
+    println!(
+        "✅ Upload copy done: {} copied, {} skipped, {} failed",
+        copied, skipped, failed
+    );
+
+    if skip_db || dry_run {
+        return Ok(());
+    }
+    if failed > 0 {
+        return Err(anyhow!(
+            "Not rewriting database URLs because {} file(s) failed to copy",
+            failed
+        ));
+    }
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await?;
+
+    let old_prefix = format!("{}/", config.local_public_base_url.trim_end_matches('/'));
+    let new_prefix = target.public_url("");
+    rewrite_urls(&pool, &old_prefix, &new_prefix).await?;
+    println!(
+        "✅ Database URLs rewritten: {} -> {}",
+        old_prefix, new_prefix
+    );
+    Ok(())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn storage_key(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let key = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?
+        .join("/");
+    blob_store::validate_key(&key).ok()?;
+    Some(key)
+}
+
+async fn rewrite_urls(db: &Pool<Postgres>, old_prefix: &str, new_prefix: &str) -> Result<()> {
+    const TEXT_COLUMNS: &[(&str, &str)] = &[
+        ("products", "image_url"),
+        ("stores", "brand_url"),
+        ("profiles", "photo_profile"),
+        ("profiles", "background_profile"),
+    ];
+    const JSON_COLUMNS: &[(&str, &str)] = &[
+        ("products", "image_variants"),
+        ("stores", "brand_variants"),
+        ("profiles", "photo_profile_variants"),
+        ("profiles", "background_profile_variants"),
+    ];
+
+    let mut tx = db.begin().await?;
+    for (table, column) in TEXT_COLUMNS {
+        let sql = format!(
+            "UPDATE {table} SET {column} = $2 || substr({column}, length($1) + 1) \
+             WHERE {column} LIKE $1 || '%'"
+        );
+        let result = sqlx::query(&sql)
+            .bind(old_prefix)
+            .bind(new_prefix)
+            .execute(&mut *tx)
+            .await?;
+        println!("  {}.{}: {} row(s)", table, column, result.rows_affected());
+    }
+    for (table, column) in JSON_COLUMNS {
+        // Variants hanya berisi URL, jadi replace teks aman dilakukan
+        let sql = format!(
+            "UPDATE {table} SET {column} = replace({column}::text, $1, $2)::jsonb \
+             WHERE {column} IS NOT NULL AND {column}::text LIKE '%' || $1 || '%'"
+        );
+        let result = sqlx::query(&sql)
+            .bind(old_prefix)
+            .bind(new_prefix)
+            .execute(&mut *tx)
+            .await?;
+        println!("  {}.{}: {} row(s)", table, column, result.rows_affected());
+    }
+
+    // Dokumen RAG lama menyimpan path disk, sekarang file_path adalah key blob store
+    let result = sqlx::query(
+        "UPDATE documents SET file_path = substr(file_path, length('uploads/') + 1) \
+         WHERE file_path LIKE 'uploads/documents/%'",
+    )
+    .execute(&mut *tx)
+    .await?;
+    println!("  documents.file_path: {} row(s)", result.rows_affected());
+
+    tx.commit().await?;
+    Ok(())
+}
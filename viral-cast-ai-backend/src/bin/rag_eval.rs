@@ -0,0 +1,140 @@
+//! ID: Jalankan set evaluasi RAG lewat API server yang sedang berjalan dan tampilkan
+//!     recall@k, MRR, kemiripan jawaban serta perubahan dibanding run sebelumnya.
+//!     Exit code 2 bila ada pertanyaan yang memburuk (berguna untuk CI).
+//! EN: Run a RAG evaluation set through the running server API and print recall@k,
+//!     MRR, answer similarity and the change since the previous run.
+//!     Exits with code 2 when any question regressed (useful for CI).
+//!
+//! Usage: cargo run --bin rag_eval -- --set <uuid> [--k N] [--answers] [--base-url URL]
+//!        cargo run --bin rag_eval -- --list
+//! Token akses (Bearer) diambil dari --token atau RAG_EVAL_TOKEN; retrieval mengikuti
+//! cakupan dokumen toko pemilik token.
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use std::env;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let arg_value = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+
+    let base_url = arg_value("--base-url")
+        .or_else(|| env::var("RAG_EVAL_BASE_URL").ok())
+        .unwrap_or_else(|| {
+            let port = env::var("APP_PORT").unwrap_or_else(|_| "4000".to_string());
+            format!("http://localhost:{}", port)
+        });
+    let base_url = base_url.trim_end_matches('/');
+    let token = arg_value("--token")
+        .or_else(|| env::var("RAG_EVAL_TOKEN").ok())
+        .ok_or_else(|| anyhow!("--token or RAG_EVAL_TOKEN is required"))?;
+    let client = reqwest::Client::new();
+
+    if args.iter().any(|a| a == "--list") {
+        let body = get_json(&client, &token, &format!("{}/api/rag/eval/sets", base_url)).await?;
+        for set in body["data"].as_array().cloned().unwrap_or_default() {
+            println!(
+                "📋 {}  {} ({} question(s), last run: {})",
+                set["id"].as_str().unwrap_or("-"),
+                set["name"].as_str().unwrap_or("-"),
+                set["question_count"],
+                set["last_run_at"].as_str().unwrap_or("never")
+            );
+        }
+        return Ok(());
+    }
+
+    let set_id = arg_value("--set").ok_or_else(|| anyhow!("--set <uuid> is required"))?;
+    let k = arg_value("--k").and_then(|v| v.parse::<i32>().ok());
+    let include_answers = args.iter().any(|a| a == "--answers");
+
+    let res = client
+        .post(format!("{}/api/rag/eval/sets/{}/runs", base_url, set_id))
+        .bearer_auth(&token)
+        .json(&json!({ "k": k, "include_answers": include_answers }))
+        .send()
+        .await?;
+    if !res.status().is_success() {
+        bail!("Evaluation run failed: HTTP {}", res.status());
+    }
+    let body: Value = res.json().await?;
+    let run = &body["data"];
+    let summary = &run["summary"];
+
+    println!(
+        "🧪 Run {} (k = {}, {} question(s))",
+        run["run_id"].as_str().unwrap_or("-"),
+        run["k"],
+        summary["question_count"]
+    );
+    println!(
+        "   recall@k          : {}",
+        format_score(&summary["recall_at_k"])
+    );
+    println!("   MRR               : {}", format_score(&summary["mrr"]));
+    println!(
+        "   answer similarity : {}",
+        format_score(&summary["answer_similarity"])
+    );
+
+    let comparison = &run["comparison"];
+    if comparison.is_null() {
+        println!("ℹ️  First run of this set; nothing to compare against");
+        return Ok(());
+    }
+    println!(
+        "📈 vs previous run {}: recall@k {}, MRR {}, answer similarity {}",
+        run["previous_run_id"].as_str().unwrap_or("-"),
+        format_delta(&comparison["recall_delta"]),
+        format_delta(&comparison["mrr_delta"]),
+        format_delta(&comparison["answer_similarity_delta"])
+    );
+    let improved = comparison["improved_questions"]
+        .as_array()
+        .map_or(0, |ids| ids.len());
+    let regressed = comparison["regressed_questions"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    println!(
+        "   {} question(s) improved, {} regressed",
+        improved,
+        regressed.len()
+    );
+    if regressed.is_empty() {
+        println!("✅ No regressions");
+        return Ok(());
+    }
+    for id in &regressed {
+        println!("   ⚠️  regressed: {}", id.as_str().unwrap_or("-"));
+    }
+    std::process::exit(2);
+}
+
+async fn get_json(client: &reqwest::Client, token: &str, url: &str) -> Result<Value> {
+    let res = client.get(url).bearer_auth(token).send().await?;
+    if !res.status().is_success() {
+        bail!("GET {} failed: HTTP {}", url, res.status());
+    }
+    Ok(res.json().await?)
+}
+
+fn format_score(value: &Value) -> String {
+    value
+        .as_f64()
+        .map(|v| format!("{:.3}", v))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_delta(value: &Value) -> String {
+    value
+        .as_f64()
+        .map(|v| format!("{:+.3}", v))
+        .unwrap_or_else(|| "-".to_string())
+}
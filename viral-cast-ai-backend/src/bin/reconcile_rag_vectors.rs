@@ -0,0 +1,211 @@
+//! ID: Samakan isi koleksi Milvus dengan `document_chunks` aktif di Postgres:
+//!     - chunk aktif milik dokumen yang sudah dihapus di-soft-delete,
+//!     - vektor Milvus tanpa chunk aktif (yatim) dihapus,
+//!     - chunk aktif tanpa vektor di Milvus di-upsert ulang dari kolom `embedding`;
+//!       chunk tanpa embedding ditandai agar di-embed ulang oleh worker rag_reembed.
+//! EN: Reconcile the Milvus collection with live `document_chunks` in Postgres:
+//!     - live chunks of deleted documents are soft-deleted,
+//!     - Milvus vectors without a live chunk (orphans) are deleted,
+//!     - live chunks missing from Milvus are re-upserted from the `embedding` column;
+//!       chunks without an embedding are flagged for the rag_reembed worker.
+//!
+//! Usage: cargo run --bin reconcile_rag_vectors -- [--dry-run] [--buckets N]
+use anyhow::{anyhow, Result};
+use milvus::{Client as MilvusClient, Endpoint};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Pool, Postgres, Row};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use uuid::Uuid;
+
+#[allow(dead_code)]
+#[path = "../services/milvus.rs"]
+mod milvus_store;
+
+const UPSERT_BATCH_SIZE: usize = 256;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    // Jumlah rentang PK yang dipindai; naikkan untuk koleksi > ~4 juta vektor
+    let buckets = args
+        .iter()
+        .position(|a| a == "--buckets")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(256);
+
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let milvus_uri = env::var("MILVUS_URI").map_err(|_| anyhow!("MILVUS_URI must be set"))?;
+    let collection = env::var("MILVUS_COLLECTION").unwrap_or_else(|_| "rag_chunks".to_string());
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await?;
+    let endpoint = Endpoint::from_shared(milvus_uri)?;
+    let mut client = MilvusClient::connect("default".to_string(), endpoint).await?;
+
+    let retired = retire_chunks_of_deleted_documents(&pool, dry_run).await?;
+    println!(
+        "🧹 {} live chunk(s) belonged to deleted documents{}",
+        retired,
+        if dry_run { " (dry run)" } else { "" }
+    );
+
+    let live = load_live_chunks(&pool).await?;
+    let live_ids: HashSet<i64> = live.keys().copied().collect();
+
+    let mut milvus_ids = HashSet::new();
+    for expr in milvus_store::id_range_exprs(buckets) {
+        let ids = milvus_store::query_vector_ids(&mut client, &collection, &expr).await?;
+        milvus_ids.extend(ids);
+    }
+    println!(
+        "📦 Postgres: {} live chunk(s); Milvus '{}': {} vector(s)",
+        live_ids.len(),
+        collection,
+        milvus_ids.len()
+    );
+
+    let orphans: Vec<i64> = milvus_ids.difference(&live_ids).copied().collect();
+    let missing: Vec<Uuid> = live_ids
+        .difference(&milvus_ids)
+        .filter_map(|id| live.get(id).copied())
+        .collect();
+    println!(
+        "🔎 {} orphan vector(s) in Milvus, {} chunk(s) missing from Milvus",
+        orphans.len(),
+        missing.len()
+    );
+    if dry_run {
+        return Ok(());
+    }
+
+    milvus_store::delete_vectors(&mut client, &collection, &orphans).await?;
+    println!("✅ Deleted {} orphan vector(s)", orphans.len());
+
+    let (restored, flagged) = restore_missing(&pool, &mut client, &collection, &missing).await?;
+    println!(
+        "✅ Re-upserted {} chunk(s); {} chunk(s) without embedding flagged for re-embedding",
+        restored, flagged
+    );
+    Ok(())
+}
+
+async fn retire_chunks_of_deleted_documents(pool: &Pool<Postgres>, dry_run: bool) -> Result<u64> {
+    if dry_run {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM document_chunks dc
+            JOIN documents d ON d.id = dc.document_id
+            WHERE dc.deleted_at = 0 AND (d.deleted_at <> 0 OR d.status = 'deleted')
+            "#,
+        )
+        .fetch_one(pool)
+        .await?;
+        return Ok(count as u64);
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE document_chunks dc
+        SET deleted_at = $1
+        FROM documents d
+        WHERE d.id = dc.document_id
+          AND dc.deleted_at = 0
+          AND (d.deleted_at <> 0 OR d.status = 'deleted')
+        "#,
+    )
+    .bind(chrono::Utc::now().timestamp_millis())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// PK Milvus -> chunk aktif; milvus_vector_id kosong (data lama) dihitung dari UUID chunk
+async fn load_live_chunks(pool: &Pool<Postgres>) -> Result<HashMap<i64, Uuid>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT dc.id, dc.milvus_vector_id
+        FROM document_chunks dc
+        JOIN documents d ON d.id = dc.document_id
+        WHERE dc.deleted_at = 0 AND d.deleted_at = 0
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let id: Uuid = row.get("id");
+            let vector_id = row
+                .get::<Option<i64>, _>("milvus_vector_id")
+                .unwrap_or_else(|| milvus_store::uuid_to_i64(&id));
+            (vector_id, id)
+        })
+        .collect())
+}
+
+async fn restore_missing(
+    pool: &Pool<Postgres>,
+    client: &mut MilvusClient,
+    collection: &str,
+    missing: &[Uuid],
+) -> Result<(usize, usize)> {
+    let mut restored = 0usize;
+    let mut flagged = 0usize;
+    for batch in missing.chunks(UPSERT_BATCH_SIZE) {
+        let rows = sqlx::query(
+            r#"
+            SELECT dc.id, dc.embedding, d.store_uuid
+            FROM document_chunks dc
+            JOIN documents d ON d.id = dc.document_id
+            WHERE dc.id = ANY($1)
+            "#,
+        )
+        .bind(batch)
+        .fetch_all(pool)
+        .await?;
+
+        // Vektor dipulihkan ke partisi basis pengetahuan pemilik dokumen
+        let mut by_partition: HashMap<String, (Vec<Uuid>, Vec<Vec<f32>>)> = HashMap::new();
+        let mut without_embedding = Vec::new();
+        for row in rows {
+            let id: Uuid = row.get("id");
+            let embedding: Option<Vec<f32>> = row.get("embedding");
+            match embedding.filter(|e| !e.is_empty()) {
+                Some(embedding) => {
+                    let partition = milvus_store::document_partition(row.get("store_uuid"));
+                    let entry = by_partition.entry(partition).or_default();
+                    entry.0.push(id);
+                    entry.1.push(embedding);
+                }
+                None => without_embedding.push(id),
+            }
+        }
+
+        // upsert_chunk_embeddings memakai uuid_to_i64(chunk_id), sama dengan milvus_vector_id
+        for (partition, (chunk_ids, embeddings)) in &by_partition {
+            milvus_store::upsert_chunk_embeddings(
+                client, collection, partition, chunk_ids, embeddings,
+            )
+            .await?;
+            restored += chunk_ids.len();
+        }
+
+        // embedding_model NULL => dipilih worker rag_reembed saat server start berikutnya
+        if !without_embedding.is_empty() {
+            sqlx::query("UPDATE document_chunks SET embedding_model = NULL WHERE id = ANY($1)")
+                .bind(&without_embedding)
+                .execute(pool)
+                .await?;
+            flagged += without_embedding.len();
+        }
+    }
+    Ok((restored, flagged))
+}
@@ -145,6 +145,33 @@ pub struct Config {
     pub milvus_token: Option<String>,
     pub milvus_collection: String,
     pub openai_api_key: Option<String>,
+    // OpenAI-compatible embeddings endpoint (OpenAI / TEI / Ollama); unset = hashing fallback
+    pub embedding_api_url: Option<String>,
+    pub embedding_api_key: Option<String>,
+    pub embedding_batch_size: Option<usize>,
+    // Cross-encoder rerank endpoint (TEI `/rerank`); unset = LLM-scored rerank via Groq
+    pub rerank_api_url: Option<String>,
+    pub rerank_api_key: Option<String>,
+    pub rerank_top_n: Option<usize>,
+    // Number of RAG ingestion queue workers (default 2)
+    pub rag_ingest_workers: usize,
+    // Login throttling: failures per username / per IP within the window before a temporary lockout
+    pub login_max_failures: u32,
+    pub login_ip_max_failures: u32,
+    pub login_failure_window_seconds: u64,
+    pub login_lockout_seconds: u64,
+    // Reverse proxies whose X-Forwarded-For / X-Real-IP is trusted; empty = use the peer address
+    pub trusted_proxies: Vec<std::net::IpAddr>,
+    // Lifetime of emailed single-use tokens (verify email / reset password), in minutes
+    pub email_verification_ttl_minutes: i64,
+    pub password_reset_ttl_minutes: i64,
+    // Lifetime of a cashier PIN session on a shared POS terminal, in minutes
+    pub pos_pin_session_minutes: i64,
+    // Two-factor (TOTP): roles_number values that must enrol, issuer shown in the authenticator
+    // app, and how long the second login step may take
+    pub two_factor_required_roles: Vec<i32>,
+    pub two_factor_issuer: String,
+    pub two_factor_challenge_minutes: i64,
     pub allow_mock_dependencies: bool,
     pub serper_api_key: Option<String>,
     pub serper_base_url: Option<String>,
@@ -156,6 +183,7 @@ pub struct Config {
     pub xendit_callback_token_sandbox: Option<String>,
     pub xendit_callback_token_live: Option<String>,
     pub xendit_qris_callback_url: Option<String>,
+    pub xendit_base_url: String,
     // Google Ads API config
     pub google_ads_client_id: Option<String>,
     pub google_ads_client_secret: Option<String>,
@@ -307,6 +335,72 @@ impl Config {
         let milvus_collection =
             std::env::var("MILVUS_COLLECTION").unwrap_or_else(|_| "rag_chunks".to_string());
         let openai_api_key = std::env::var("OPENAI_API_KEY").ok();
+        let embedding_api_url = std::env::var("EMBEDDING_API_URL").ok();
+        // ID: Tanpa EMBEDDING_API_KEY, pakai OPENAI_API_KEY (server lokal biasanya tanpa key).
+        // EN: Without EMBEDDING_API_KEY, fall back to OPENAI_API_KEY (local servers need none).
+        let embedding_api_key = std::env::var("EMBEDDING_API_KEY")
+            .ok()
+            .or_else(|| openai_api_key.clone());
+        let embedding_batch_size = std::env::var("EMBEDDING_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok());
+        let rerank_api_url = std::env::var("RERANK_API_URL").ok();
+        let rerank_api_key = std::env::var("RERANK_API_KEY").ok();
+        let rerank_top_n = std::env::var("RERANK_TOP_N")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok());
+        let rag_ingest_workers = std::env::var("RAG_INGEST_WORKERS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(2);
+        let login_max_failures = std::env::var("LOGIN_MAX_FAILURES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(5);
+        let login_ip_max_failures = std::env::var("LOGIN_IP_MAX_FAILURES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(20);
+        let login_failure_window_seconds = std::env::var("LOGIN_FAILURE_WINDOW_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(900);
+        let login_lockout_seconds = std::env::var("LOGIN_LOCKOUT_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(900);
+        let trusted_proxies = std::env::var("TRUSTED_PROXIES")
+            .map(|v| {
+                v.split(',')
+                    .filter_map(|ip| ip.trim().parse::<std::net::IpAddr>().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let email_verification_ttl_minutes = std::env::var("EMAIL_VERIFICATION_TTL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(24 * 60);
+        let password_reset_ttl_minutes = std::env::var("PASSWORD_RESET_TTL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(30);
+        let pos_pin_session_minutes = std::env::var("POS_PIN_SESSION_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(60);
+        let two_factor_required_roles = std::env::var("TWO_FACTOR_REQUIRED_ROLES")
+            .map(|v| {
+                v.split(',')
+                    .filter_map(|role| role.trim().parse::<i32>().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let two_factor_issuer =
+            std::env::var("TWO_FACTOR_ISSUER").unwrap_or_else(|_| "Viral Cast AI".to_string());
+        let two_factor_challenge_minutes = std::env::var("TWO_FACTOR_CHALLENGE_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(5);
         // ID: Saat AI mode, default izinkan dependency mock agar Milvus optional.
         // EN: In AI mode, default allow mock dependencies so Milvus is optional.
         let allow_mock_dependencies = std::env::var("ALLOW_MOCK_DEPENDENCIES")
@@ -328,6 +422,13 @@ impl Config {
         let xendit_callback_token_sandbox = std::env::var("XENDIT_CALLBACK_TOKEN_SANDBOX").ok();
         let xendit_callback_token_live = std::env::var("XENDIT_CALLBACK_TOKEN_LIVE").ok();
         let xendit_qris_callback_url = std::env::var("XENDIT_QRIS_CALLBACK_URL").ok();
+        // ID: Base URL API Xendit (QR code & refund); bisa diarahkan ke mock saat pengujian.
+        // EN: Xendit API base URL (QR codes & refunds); can point at a mock for testing.
+        let xendit_base_url = std::env::var("XENDIT_BASE_URL")
+            .ok()
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| "https://api.xendit.co".to_string());
 
         // Google Ads API environment
         let google_ads_client_id = std::env::var("GOOGLE_ADS_CLIENT_ID").ok();
@@ -362,6 +463,24 @@ impl Config {
             milvus_token,
             milvus_collection,
             openai_api_key,
+            embedding_api_url,
+            embedding_api_key,
+            embedding_batch_size,
+            rerank_api_url,
+            rerank_api_key,
+            rerank_top_n,
+            rag_ingest_workers,
+            login_max_failures,
+            login_ip_max_failures,
+            login_failure_window_seconds,
+            login_lockout_seconds,
+            trusted_proxies,
+            email_verification_ttl_minutes,
+            password_reset_ttl_minutes,
+            pos_pin_session_minutes,
+            two_factor_required_roles,
+            two_factor_issuer,
+            two_factor_challenge_minutes,
             allow_mock_dependencies,
             serper_api_key,
             serper_base_url,
@@ -372,6 +491,7 @@ impl Config {
             xendit_callback_token_sandbox,
             xendit_callback_token_live,
             xendit_qris_callback_url,
+            xendit_base_url,
             google_ads_client_id,
             google_ads_client_secret,
             google_ads_refresh_token,
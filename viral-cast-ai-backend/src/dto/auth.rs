@@ -10,6 +10,12 @@ pub struct ProcessedRegister {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ProcessedLogin {
     pub access_token: Option<String>,
+    // Refresh token hasil rotasi (hanya pada /auth/refresh) untuk klien yang tidak memakai cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    // Kode pemulihan 2FA, hanya saat pendaftaran 2FA diselesaikan di langkah kedua login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -19,6 +25,41 @@ pub struct FilteredRegisterResponse {
     pub email: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SessionResponse {
+    pub uuid: Uuid,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: i64,
+    pub last_seen_at: Option<i64>,
+    // Sesi milik access token yang sedang dipakai
+    pub current: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct RevokeSessionsQuery {
+    // "Sign out everywhere" tetap mempertahankan sesi ini bila true
+    pub keep_current: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RevokedSessionsResponse {
+    pub revoked_sessions: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EmailVerificationResponse {
+    pub email: String,
+    pub email_verified_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AccountUnlockedResponse {
+    pub user_uuid: Uuid,
+    pub username: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct FilteredUserResponse {
     pub uuid: Uuid,
@@ -9,6 +9,8 @@ pub struct TokenDetails {
     pub token_uuid: uuid::Uuid,
     pub user_uuid: uuid::Uuid,
     pub expires_in: Option<i64>,
+    /// Terminal POS tempat sesi PIN kasir dibuka (None untuk login biasa)
+    pub pos_device_uuid: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +20,8 @@ pub struct TokenClaims {
     pub exp: i64,
     pub iat: i64,
     pub nbf: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pos_device_uuid: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,6 +33,27 @@ pub fn generate_jwt_token(
     user_uuid: uuid::Uuid,
     ttl: i64,
     private_key: String,
+) -> Result<TokenDetails, jsonwebtoken::errors::Error> {
+    encode_jwt_token(user_uuid, None, ttl, private_key)
+}
+
+/// ID: Access token sesi PIN kasir; klaim `pos_device_uuid` mengikat token ke satu terminal POS.
+/// EN: Access token for a cashier PIN session; the `pos_device_uuid` claim binds it to one POS
+///     terminal.
+pub fn generate_pos_jwt_token(
+    user_uuid: uuid::Uuid,
+    pos_device_uuid: uuid::Uuid,
+    ttl: i64,
+    private_key: String,
+) -> Result<TokenDetails, jsonwebtoken::errors::Error> {
+    encode_jwt_token(user_uuid, Some(pos_device_uuid), ttl, private_key)
+}
+
+fn encode_jwt_token(
+    user_uuid: uuid::Uuid,
+    pos_device_uuid: Option<uuid::Uuid>,
+    ttl: i64,
+    private_key: String,
 ) -> Result<TokenDetails, jsonwebtoken::errors::Error> {
     // Determine format: PEM ascii vs base64-encoded PEM and decode with padding
     let pem_bytes = if private_key.contains("-----BEGIN") {
@@ -71,6 +96,7 @@ pub fn generate_jwt_token(
         token_uuid: Uuid::new_v4(),
         expires_in: Some((now + chrono::Duration::minutes(ttl)).timestamp()),
         token: None,
+        pos_device_uuid,
     };
 
     let claims = TokenClaims {
@@ -79,6 +105,7 @@ pub fn generate_jwt_token(
         exp: token_details.expires_in.unwrap(),
         iat: now.timestamp(),
         nbf: now.timestamp(),
+        pos_device_uuid: pos_device_uuid.map(|uuid| uuid.to_string()),
     };
 
     let header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256);
@@ -233,15 +260,38 @@ pub fn verify_jwt_token(
     let token_uuid = Uuid::parse_str(decoded.claims.token_uuid.as_str()).map_err(|_| {
         jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidToken)
     })?;
+    let pos_device_uuid = decoded
+        .claims
+        .pos_device_uuid
+        .as_deref()
+        .map(Uuid::parse_str)
+        .transpose()
+        .map_err(|_| {
+            jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidToken)
+        })?;
 
     Ok(TokenDetails {
         token: None,
         token_uuid,
         user_uuid,
         expires_in: None,
+        pos_device_uuid,
     })
 }
 
+/// ID: Token acak 256-bit (base64url) untuk tautan email beserta hash SHA-256 (hex) yang disimpan.
+/// EN: Random 256-bit token (base64url) for emailed links plus the SHA-256 hex hash that is stored.
+pub fn generate_action_token() -> (String, String) {
+    let token = general_purpose::URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>());
+    let token_hash = hash_action_token(&token);
+    (token, token_hash)
+}
+
+pub fn hash_action_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
@@ -265,6 +315,18 @@ mod unit_tests {
         std::env::set_var("REFRESH_TOKEN_MAXAGE", "60");
     }
 
+    #[test]
+    fn action_tokens_are_random_and_hashed() {
+        let (token, token_hash) = generate_action_token();
+        let (other, _) = generate_action_token();
+        assert_ne!(token, other);
+        assert_eq!(token.len(), 43);
+        assert_eq!(token_hash.len(), 64);
+        assert_eq!(hash_action_token(&token), token_hash);
+        assert_eq!(hash_action_token(&format!(" {}\n", token)), token_hash);
+        assert_ne!(hash_action_token(&other), token_hash);
+    }
+
     #[test]
     fn generate_and_verify_access_ok() {
         ensure_env();
@@ -280,6 +342,27 @@ mod unit_tests {
             verified.token_uuid.to_string(),
             details.token_uuid.to_string()
         );
+        assert_eq!(verified.pos_device_uuid, None);
+    }
+
+    #[test]
+    fn pos_token_carries_device_claim() {
+        ensure_env();
+        let cfg = Config::init();
+        let user_uuid = Uuid::new_v4();
+        let device_uuid = Uuid::new_v4();
+        let details = generate_pos_jwt_token(
+            user_uuid,
+            device_uuid,
+            1,
+            cfg.access_token_private_key.clone(),
+        )
+        .expect("generate pos token");
+        let token = details.token.as_ref().expect("token present");
+        let verified =
+            verify_jwt_token(cfg.access_token_public_key.clone(), token).expect("verify pos token");
+        assert_eq!(verified.user_uuid, user_uuid);
+        assert_eq!(verified.pos_device_uuid, Some(device_uuid));
     }
 
     #[test]
@@ -0,0 +1,59 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+// DTO permintaan refund. Tanpa `items` = refund penuh atas semua qty yang tersisa.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateOrderRefundRequest {
+    #[validate(custom(function = "validate_reason"))]
+    pub reason: String,
+    pub items: Option<Vec<OrderRefundItemInput>>,
+    // ID: Kembalikan bahan ke stok (move RETURN) saat disetujui
+    // EN: Put the ingredients back into stock (RETURN moves) on approval
+    pub restock: Option<bool>,
+    // ID: Pembayaran QRIS dibuat lewat endpoint sandbox
+    // EN: QRIS payments were taken through the sandbox endpoints
+    pub qris_sandbox: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct OrderRefundItemInput {
+    pub order_item_uuid: Uuid,
+    #[validate(custom(function = "validate_positive"))]
+    pub qty: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct VoidOrderRequest {
+    #[validate(custom(function = "validate_reason"))]
+    pub reason: String,
+    pub restock: Option<bool>,
+    pub qris_sandbox: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RejectOrderRefundRequest {
+    #[validate(custom(function = "validate_reason"))]
+    pub reason: String,
+}
+
+fn validate_reason(value: &str) -> Result<(), ValidationError> {
+    let length = value.trim().chars().count();
+    if length == 0 {
+        return Err(ValidationError::new("reason is required"));
+    }
+    if length > 500 {
+        return Err(ValidationError::new(
+            "reason must not exceed 500 characters",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_positive(value: &Decimal) -> Result<(), ValidationError> {
+    if *value <= Decimal::ZERO {
+        return Err(ValidationError::new("qty must be greater than 0"));
+    }
+    Ok(())
+}
@@ -32,6 +32,7 @@ pub struct CreateOrderItemRequest {
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct UpdateOrderRequest {
     pub cashier_uuid: Option<Uuid>,
+    #[validate(custom = "validate_order_status")]
     pub status: Option<String>,
     pub subtotal: Option<rust_decimal::Decimal>,
     pub discount: Option<rust_decimal::Decimal>,
@@ -56,6 +57,7 @@ pub struct OrderResponse {
     pub uuid: Uuid,
     pub order_no: String,
     pub cashier_uuid: Option<Uuid>,
+    pub store_uuid: Option<Uuid>,
     pub status: String,
     pub subtotal: rust_decimal::Decimal,
     pub discount: rust_decimal::Decimal,
@@ -76,6 +78,8 @@ pub struct OrderItemResponse {
     pub unit_price: rust_decimal::Decimal,
     pub unit_cost: Option<rust_decimal::Decimal>,
     pub line_total: rust_decimal::Decimal,
+    // Versi resep yang dipatok saat order dibuat
+    pub recipe_sets_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
     pub product_name: Option<String>,
@@ -115,7 +119,11 @@ pub struct OrderStatsRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrderStatsResponse {
     pub total_orders: i64,
+    // Total pesanan sebelum refund; total_revenue & total_profit sudah dikurangi refund/void
+    pub gross_revenue: rust_decimal::Decimal,
     pub total_revenue: rust_decimal::Decimal,
+    pub total_refunded: rust_decimal::Decimal,
+    pub refund_count: i64,
     pub total_profit: rust_decimal::Decimal,
     pub avg_order_value: rust_decimal::Decimal,
     pub orders_by_status: Vec<OrderStatusCount>,
@@ -134,19 +142,22 @@ pub struct DailyOrderStats {
     pub date: String,
     pub orders_count: i64,
     pub total_revenue: rust_decimal::Decimal,
+    pub total_refunded: rust_decimal::Decimal,
     pub total_profit: rust_decimal::Decimal,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct UpdateOrderStatusRequest {
+    #[validate(custom = "validate_order_status")]
     pub status: String,
 }
 
+// REFUNDED hanya diisi alur refund (approve), tidak boleh diset manual
 fn validate_order_status(status: &str) -> Result<(), ValidationError> {
     match status.to_uppercase().as_str() {
-        "DRAFT" | "PAID" | "CANCELLED" | "REFUNDED" => Ok(()),
+        "DRAFT" | "PAID" | "CANCELLED" => Ok(()),
         _ => Err(ValidationError::new(
-            "Invalid order status. Must be one of: DRAFT, PAID, CANCELLED, REFUNDED",
+            "Invalid order status. Must be one of: DRAFT, PAID, CANCELLED; REFUNDED is set by the refund workflow",
         )),
     }
 }
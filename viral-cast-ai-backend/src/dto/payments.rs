@@ -6,6 +6,7 @@ use validator::{Validate, ValidationError};
 pub struct CreatePaymentRequest {
     pub order_uuid: Uuid,
     pub method: String,
+    // Uang yang diserahkan pelanggan; kelebihan CASH dicatat sebagai kembalian
     pub amount: rust_decimal::Decimal,
     pub paid_at: Option<i64>,
     #[validate(length(
@@ -45,10 +46,19 @@ pub struct PaymentResponse {
     pub order_no: Option<String>,
     pub method: String,
     pub amount: rust_decimal::Decimal,
+    pub tendered_amount: Option<rust_decimal::Decimal>,
+    pub change_amount: rust_decimal::Decimal,
     pub paid_at: Option<i64>,
     pub external_ref: Option<String>,
+    // Terisi untuk baris refund (amount negatif)
+    pub refund_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
+    // Sisa tagihan & status pesanan setelah pembayaran ini (hanya pada create/update)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_amount: Option<rust_decimal::Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_status: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,8 +68,12 @@ pub struct PaymentSummaryResponse {
     pub order_no: String,
     pub method: String,
     pub amount: rust_decimal::Decimal,
+    pub tendered_amount: Option<rust_decimal::Decimal>,
+    pub change_amount: rust_decimal::Decimal,
     pub paid_at: Option<i64>,
     pub external_ref: Option<String>,
+    // Terisi untuk baris refund (amount negatif)
+    pub refund_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
 }
 
@@ -82,9 +96,14 @@ pub struct PaymentStatsRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentStatsResponse {
+    // Hanya pembayaran masuk; refund dihitung terpisah
     pub total_payments: i64,
     pub total_amount: rust_decimal::Decimal,
     pub avg_payment_amount: rust_decimal::Decimal,
+    pub refund_count: i64,
+    pub refunded_amount: rust_decimal::Decimal,
+    // total_amount - refunded_amount
+    pub net_amount: rust_decimal::Decimal,
     pub payments_by_method: Vec<PaymentMethodCount>,
     pub daily_stats: Vec<DailyPaymentStats>,
 }
@@ -94,6 +113,8 @@ pub struct PaymentMethodCount {
     pub method: String,
     pub count: i64,
     pub total_amount: rust_decimal::Decimal,
+    pub refunded_amount: rust_decimal::Decimal,
+    pub net_amount: rust_decimal::Decimal,
     pub percentage: f64,
 }
 
@@ -103,6 +124,8 @@ pub struct DailyPaymentStats {
     pub payments_count: i64,
     pub total_amount: rust_decimal::Decimal,
     pub avg_amount: rust_decimal::Decimal,
+    pub refunded_amount: rust_decimal::Decimal,
+    pub net_amount: rust_decimal::Decimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,9 +137,15 @@ pub struct PaymentsByOrderRequest {
 pub struct PaymentsByOrderResponse {
     pub order_uuid: Uuid,
     pub order_no: String,
+    pub order_status: String,
     pub order_total: rust_decimal::Decimal,
     pub total_paid: rust_decimal::Decimal,
+    // QRIS yang belum selesai tetap memesan sebagian tagihan
+    pub pending_amount: rust_decimal::Decimal,
     pub remaining_amount: rust_decimal::Decimal,
+    pub change_given: rust_decimal::Decimal,
+    // Total pembayaran negatif dari refund/void yang disetujui
+    pub total_refunded: rust_decimal::Decimal,
     pub payments: Vec<PaymentResponse>,
 }
 
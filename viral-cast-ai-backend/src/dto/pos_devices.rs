@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::pos_devices::PosDevice;
+
+#[derive(Deserialize, Debug)]
+pub struct EnrollPosDeviceRequest {
+    pub name: String,
+    // Wajib untuk Super Admin; Admin/Owner selalu memakai toko profilnya
+    pub store_uuid: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct PosDeviceListQuery {
+    pub store_uuid: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SetPinRequest {
+    pub current_password: String,
+    pub pin: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PinUnlockRequest {
+    pub user_uuid: Uuid,
+    pub pin: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PosDeviceResponse {
+    pub uuid: Uuid,
+    pub store_uuid: Uuid,
+    pub name: String,
+    pub enrolled_by: Option<Uuid>,
+    pub last_used_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl From<PosDevice> for PosDeviceResponse {
+    fn from(device: PosDevice) -> Self {
+        Self {
+            uuid: device.uuid,
+            store_uuid: device.store_uuid,
+            name: device.name,
+            enrolled_by: device.enrolled_by,
+            last_used_at: device.last_used_at,
+            created_at: device.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PosDeviceEnrolledResponse {
+    pub device: PosDeviceResponse,
+    // Kredensial perangkat hanya ditampilkan sekali; kirim di header X-Device-Token
+    pub device_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PinSessionResponse {
+    pub access_token: String,
+    pub expires_at: i64,
+    pub user_uuid: Uuid,
+    pub username: String,
+    pub store_uuid: Uuid,
+    pub device_uuid: Uuid,
+}
+
+/// ID: PIN kasir berupa 4-6 digit angka.
+/// EN: A cashier PIN is 4-6 ASCII digits.
+pub fn is_valid_pin(pin: &str) -> bool {
+    (4..=6).contains(&pin.len()) && pin.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_are_four_to_six_digits() {
+        assert!(is_valid_pin("0420"));
+        assert!(is_valid_pin("123456"));
+        assert!(!is_valid_pin("123"));
+        assert!(!is_valid_pin("1234567"));
+        assert!(!is_valid_pin("12a4"));
+        assert!(!is_valid_pin("12 4"));
+    }
+}
@@ -0,0 +1,35 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+// DTO untuk produksi batch bahan setengah jadi dari sebuah recipe set
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateProductionRunRequest {
+    pub recipe_sets_uuid: Uuid,
+    // ID: Qty hasil dalam satuan bahan output; default = yield_quantity resep (1 batch)
+    // EN: Output quantity in the output ingredient's unit; defaults to the recipe yield (1 batch)
+    #[validate(custom(function = "validate_positive"))]
+    pub output_quantity: Option<Decimal>,
+    pub notes: Option<String>,
+    pub produced_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductionRunListRequest {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    pub recipe_sets_uuid: Option<Uuid>,
+    pub output_ingredient_catalog_uuid: Option<Uuid>,
+    pub date_from: Option<i64>,
+    pub date_to: Option<i64>,
+}
+
+fn validate_positive(value: &Decimal) -> Result<(), ValidationError> {
+    if *value <= Decimal::ZERO {
+        return Err(ValidationError::new(
+            "output_quantity must be greater than 0",
+        ));
+    }
+    Ok(())
+}
@@ -53,6 +53,7 @@ pub struct ProcessedProductSchema {
     pub current_recipe_yield_qty: Option<Decimal>, // Recipe yield quantity if assigned
     pub status: String,
     pub image_url: Option<String>,
+    pub image_variants: Option<serde_json::Value>, // Thumbnail & WebP untuk grid POS
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
 }
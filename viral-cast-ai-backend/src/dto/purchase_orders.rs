@@ -0,0 +1,92 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO untuk query saran reorder
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReorderSuggestionQuery {
+    // ID: Jumlah hari ke depan untuk membaca forecast (default 7)
+    // EN: Number of days ahead used to read forecasts (default 7)
+    #[validate(range(min = 1, max = 90, message = "horizon_days must be between 1 and 90"))]
+    pub horizon_days: Option<i64>,
+    // ID: Periode review hingga pemesanan berikutnya (default 7 hari)
+    // EN: Review period until the next order cycle (default 7 days)
+    #[validate(range(min = 0, max = 90, message = "review_days must be between 0 and 90"))]
+    pub review_days: Option<i32>,
+    pub supplier_uuid: Option<Uuid>,
+    // 'FORECAST' | 'PREDICTION' | 'AUTO'
+    pub source: Option<String>,
+    pub include_zero: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ReorderSuggestionItem {
+    pub ingredient_catalog_uuid: Uuid,
+    pub ingredient_name: Option<String>,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+    pub on_hand_qty: Decimal,
+    pub on_order_qty: Decimal,
+    pub minimum_stock: Decimal,
+    pub daily_usage: Decimal,
+    pub usage_source: String,
+    pub lead_time_days: i32,
+    pub reorder_point: Decimal,
+    pub target_stock: Decimal,
+    pub suggested_qty: Decimal,
+    pub unit_price: Option<Decimal>,
+    pub estimated_cost: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SupplierReorderSuggestion {
+    pub supplier_uuid: Option<Uuid>,
+    pub supplier_name: Option<String>,
+    pub lead_time_days: Option<i32>,
+    pub items: Vec<ReorderSuggestionItem>,
+    pub total_estimated: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReorderSuggestionResponse {
+    pub generated_at: i64,
+    pub horizon_days: i64,
+    pub review_days: i32,
+    pub suppliers: Vec<SupplierReorderSuggestion>,
+    // ID: Bahan yang perlu dipesan tetapi belum punya supplier
+    // EN: Ingredients that need ordering but have no linked supplier
+    pub unassigned: Vec<ReorderSuggestionItem>,
+}
+
+// DTO untuk membuat draft PO langsung dari saran reorder
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreatePurchaseOrdersFromSuggestionsRequest {
+    #[validate(range(min = 1, max = 90, message = "horizon_days must be between 1 and 90"))]
+    pub horizon_days: Option<i64>,
+    #[validate(range(min = 0, max = 90, message = "review_days must be between 0 and 90"))]
+    pub review_days: Option<i32>,
+    pub source: Option<String>,
+    // ID: Batasi ke supplier tertentu; kosong berarti semua supplier
+    // EN: Restrict to these suppliers; empty means every supplier
+    pub supplier_uuids: Option<Vec<Uuid>>,
+    pub notes: Option<String>,
+}
+
+// DTO untuk query daftar PO
+#[derive(Debug, Deserialize)]
+pub struct PurchaseOrderListRequest {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    pub status: Option<String>,
+    pub supplier_uuid: Option<Uuid>,
+    pub date_from: Option<i64>,
+    pub date_to: Option<i64>,
+    pub search: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdatePurchaseOrderStatusRequest {
+    #[validate(length(min = 1, message = "Status is required"))]
+    pub status: String,
+}
@@ -2,6 +2,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::models::rag::RagEvalQuestion;
+use crate::services::rag_eval::{EvalComparison, EvalSummary, QuestionOutcome};
+
 // Document upload request
 #[derive(Debug, Deserialize)]
 pub struct DocumentUploadRequest {
@@ -24,6 +27,8 @@ pub struct DocumentUploadResponse {
     pub file_type: String,
     pub status: String, // processing, ready, error
     pub uploaded_at: DateTime<Utc>,
+    pub store_uuid: Option<Uuid>,
+    pub visibility: String, // store, platform
 }
 
 // Ingest text document request (no file upload)
@@ -34,6 +39,7 @@ pub struct DocumentTextIngestRequest {
     pub content: String,
     pub tags: Option<Vec<String>>, // optional tags
     pub description: Option<String>,
+    pub visibility: Option<String>, // store (default) | platform (Super Admin only)
 }
 
 // Ingest text document response
@@ -51,6 +57,7 @@ pub struct RagQueryRequest {
     pub query: String,
     pub document_ids: Option<Vec<Uuid>>, // Specific documents to search in
     pub category_filter: Option<String>, // Filter by category
+    pub tags_filter: Option<Vec<String>>, // Documents sharing any of these tags
     pub max_results: Option<i32>,
     pub similarity_threshold: Option<f32>,
 }
@@ -73,6 +80,8 @@ pub struct DocumentSource {
     pub similarity_score: f32,
     pub page_number: Option<i32>,
     pub chunk_index: i32,
+    pub retrieval_score: f32,      // RRF score from hybrid retrieval
+    pub rerank_score: Option<f32>, // 0..1, only when reranking ran for this chunk
 }
 
 // Document list request
@@ -109,6 +118,8 @@ pub struct DocumentSummary {
     pub chunk_count: i32,
     pub uploaded_at: DateTime<Utc>,
     pub last_accessed: Option<DateTime<Utc>>,
+    pub store_uuid: Option<Uuid>,
+    pub visibility: String,
 }
 
 // Document processing status
@@ -122,6 +133,18 @@ pub struct DocumentProcessingStatus {
     pub chunks_processed: i32,
     pub total_chunks: i32,
     pub estimated_completion: Option<DateTime<Utc>>,
+    pub job_status: Option<String>, // pending, processing, completed, failed
+    pub retry_count: i32,
+    pub max_retries: i32,
+    pub next_retry_at: Option<DateTime<Utc>>,
+}
+
+// Reindex document response
+#[derive(Debug, Serialize)]
+pub struct DocumentReindexResponse {
+    pub document_id: Uuid,
+    pub job_id: Uuid,
+    pub status: String,
 }
 
 // Document chunk for internal processing
@@ -144,9 +167,14 @@ pub struct RagConfig {
     pub chunk_size: i32,
     pub chunk_overlap: i32,
     pub embedding_model: String,
+    pub embedding_dimensions: i32,
     pub similarity_threshold: f32,
     pub max_results: i32,
     pub enable_reranking: bool,
+    pub reranking_model: Option<String>,
+    pub chunking_strategy: String,
+    pub chunk_size_unit: String,
+    pub context_token_budget: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -157,9 +185,14 @@ pub struct UpdateRagConfigRequest {
     pub chunk_size: Option<i32>,
     pub chunk_overlap: Option<i32>,
     pub embedding_model: Option<String>,
+    pub embedding_dimensions: Option<i32>,
     pub similarity_threshold: Option<f32>,
     pub max_results: Option<i32>,
     pub enable_reranking: Option<bool>,
+    pub reranking_model: Option<String>, // cross-encoder name, or "llm" / "llm:<groq model>"
+    pub chunking_strategy: Option<String>, // fixed | sentence | heading
+    pub chunk_size_unit: Option<String>, // chars | tokens
+    pub context_token_budget: Option<i32>,
 }
 
 // Document analytics
@@ -214,6 +247,7 @@ pub struct RagAnswerRequest {
     pub query: String,
     pub document_ids: Option<Vec<Uuid>>, // Specific documents to search in
     pub category_filter: Option<String>, // Filter by category
+    pub tags_filter: Option<Vec<String>>, // Documents sharing any of these tags
     pub max_results: Option<i32>,
     pub similarity_threshold: Option<f32>,
     pub prompt_instructions: Option<String>, // Optional extra instructions for the LLM
@@ -230,6 +264,19 @@ pub struct RagAnswerResponse {
     pub processing_time_ms: i64,
     pub llm_model: Option<String>,
     pub tokens_used: Option<u32>,
+    pub citations: Vec<AnswerCitation>,
+    pub groundedness_score: f32, // 0..1, token overlap of claims with their cited sources
+    pub refused: bool,           // true = "not in knowledge base", no LLM answer generated
+}
+
+// Inline citation marker [n] in an answer, mapped to sources[n - 1]
+#[derive(Debug, Serialize)]
+pub struct AnswerCitation {
+    pub marker: usize,
+    pub source_index: usize,
+    pub document_id: Uuid,
+    pub claim: String,
+    pub support: Option<f32>,
 }
 
 // Simple RAG answer request (minimal payload: only the user query)
@@ -239,3 +286,58 @@ pub struct RagAnswerResponse {
 pub struct SimpleRagRequest {
     pub query: String,
 }
+
+// RAG evaluation: golden question with the documents/chunks that should be retrieved
+#[derive(Debug, Deserialize)]
+pub struct RagEvalQuestionInput {
+    pub question: String,
+    pub expected_document_ids: Option<Vec<Uuid>>,
+    pub expected_chunk_ids: Option<Vec<Uuid>>, // takes precedence over document ids when set
+    pub reference_answer: Option<String>,      // enables answer similarity scoring
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRagEvalSetRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub questions: Vec<RagEvalQuestionInput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RagEvalSetResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub questions: Vec<RagEvalQuestion>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RagEvalSetSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub question_count: i64,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RunRagEvalRequest {
+    pub k: Option<i32>,                // defaults to rag_configuration.max_results
+    pub include_answers: Option<bool>, // also generate LLM answers for answer similarity
+}
+
+#[derive(Debug, Serialize)]
+pub struct RagEvalRunResponse {
+    pub run_id: Uuid,
+    pub eval_set_id: Uuid,
+    pub k: i32,
+    pub summary: EvalSummary,
+    pub previous_run_id: Option<Uuid>,
+    pub comparison: Option<EvalComparison>, // None on the first run of a set
+    pub config_snapshot: serde_json::Value,
+    pub results: Vec<QuestionOutcome>,
+    pub created_at: DateTime<Utc>,
+}
@@ -5,7 +5,9 @@ use uuid::Uuid;
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CreateRecipeItemSchema {
     pub recipe_sets_uuid: Uuid,
-    pub ingredient_stocks_uuid: Uuid,
+    // Isi salah satu: bahan (ingredient_stocks_uuid) atau recipe set komponen
+    pub ingredient_stocks_uuid: Option<Uuid>,
+    pub component_recipe_sets_uuid: Option<Uuid>,
     pub quantity: Decimal,
     pub waste_percent: Option<Decimal>, // defaults to 0
 }
@@ -22,13 +24,15 @@ pub struct GetRecipeItemSchema {
     pub limit: Option<usize>,
     pub recipe_sets_uuid: Option<Uuid>,
     pub ingredient_stocks_uuid: Option<Uuid>,
+    pub component_recipe_sets_uuid: Option<Uuid>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ProcessedRecipeItemSchema {
     pub uuid: Uuid,
     pub recipe_sets_uuid: Uuid,
-    pub ingredient_stocks_uuid: Uuid,
+    pub ingredient_stocks_uuid: Option<Uuid>,
+    pub component_recipe_sets_uuid: Option<Uuid>,
     pub quantity: Decimal,
     pub waste_percent: Option<Decimal>,
     pub created_at: Option<i64>,
@@ -9,6 +9,8 @@ pub struct CreateRecipeSetSchema {
     pub effective_from: Option<i64>,
     pub effective_to: Option<i64>,
     pub is_active: Option<bool>, // defaults to true
+    pub output_ingredient_catalog_uuid: Option<Uuid>, // stocked semi-finished item produced by this recipe
+    pub product_uuid: Option<Uuid>, // product whose recipe versions this set belongs to
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -18,6 +20,7 @@ pub struct UpdateRecipeSetSchema {
     pub effective_from: Option<i64>,
     pub effective_to: Option<i64>,
     pub is_active: Option<bool>,
+    pub output_ingredient_catalog_uuid: Option<Uuid>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -39,6 +42,23 @@ pub struct ProcessedRecipeSetSchema {
     pub effective_from: Option<i64>,
     pub effective_to: Option<i64>,
     pub is_active: bool,
+    pub output_ingredient_catalog_uuid: Option<Uuid>,
+    pub product_uuid: Option<Uuid>,
+    pub version_no: i32,
+    pub previous_version_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
 }
+
+// Create a new version of a recipe set (items are copied, then edited on the new version)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateRecipeVersionSchema {
+    pub effective_from: Option<i64>, // defaults to now
+    pub name: Option<String>,
+    pub yield_quantity: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecipeVersionDiffQuery {
+    pub against: Uuid, // the other version to compare with
+}
@@ -0,0 +1,61 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+// DTO untuk membuka sesi stock opname
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateStockOpnameSessionRequest {
+    pub notes: Option<String>,
+    // ID: Kosongkan untuk menghitung semua bahan di katalog
+    // EN: Leave empty to count every ingredient in the catalog
+    pub ingredient_catalog_uuids: Option<Vec<Uuid>>,
+}
+
+// DTO untuk input hasil hitung (boleh sebagian, boleh dari beberapa perangkat)
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SubmitStockOpnameCountsRequest {
+    #[validate(length(min = 1, message = "At least one count is required"))]
+    pub counts: Vec<StockOpnameCountInput>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct StockOpnameCountInput {
+    pub ingredient_catalog_uuid: Uuid,
+    #[validate(custom(function = "validate_non_negative"))]
+    pub counted_qty: Decimal,
+    // 'SET' (default) menimpa hasil hitung, 'ADD' menambahkan (mis. hitung per rak dari perangkat berbeda)
+    #[validate(custom(function = "validate_count_mode"))]
+    pub mode: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StockOpnameListRequest {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    pub status: Option<String>,
+    pub date_from: Option<i64>,
+    pub date_to: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StockOpnameDetailQuery {
+    // ID: Hanya tampilkan item yang memiliki selisih
+    // EN: Only return items that have a variance
+    pub only_variance: Option<bool>,
+}
+
+fn validate_non_negative(value: &Decimal) -> Result<(), ValidationError> {
+    if value.is_sign_negative() {
+        return Err(ValidationError::new("counted_qty must not be negative"));
+    }
+    Ok(())
+}
+
+fn validate_count_mode(value: &str) -> Result<(), ValidationError> {
+    match value.to_ascii_uppercase().as_str() {
+        "SET" | "ADD" => Ok(()),
+        _ => Err(ValidationError::new("Invalid mode. Allowed: SET, ADD")),
+    }
+}
@@ -44,6 +44,7 @@ pub struct GetStoreSchema {
     pub uuid: Uuid,
     pub name: String,
     pub brand_url: Option<String>,
+    pub brand_variants: Option<serde_json::Value>, // URL varian gambar brand (thumbnail, WebP)
     pub province_code: Option<String>,
     pub regency_code: Option<String>,
     pub district_code: Option<String>,
@@ -66,6 +67,7 @@ pub struct ProcessedStore {
     pub uuid: Uuid,
     pub name: String,
     pub brand_url: Option<String>,
+    pub brand_variants: Option<serde_json::Value>, // URL varian gambar brand (thumbnail, WebP)
     pub province_code: Option<String>,
     pub regency_code: Option<String>,
     pub district_code: Option<String>,
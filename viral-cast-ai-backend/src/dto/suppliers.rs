@@ -0,0 +1,74 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO untuk membuat supplier
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateSupplierSchema {
+    #[validate(length(
+        min = 1,
+        max = 150,
+        message = "Name must be between 1 and 150 characters"
+    ))]
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub telp: Option<String>,
+    #[validate(email(message = "Invalid email"))]
+    pub email: Option<String>,
+    pub address: Option<String>,
+    #[validate(range(
+        min = 0,
+        max = 365,
+        message = "Lead time must be between 0 and 365 days"
+    ))]
+    pub lead_time_days: Option<i32>,
+    pub notes: Option<String>,
+}
+
+// DTO untuk memperbarui supplier
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateSupplierSchema {
+    #[validate(length(
+        min = 1,
+        max = 150,
+        message = "Name must be between 1 and 150 characters"
+    ))]
+    pub name: Option<String>,
+    pub contact_name: Option<String>,
+    pub telp: Option<String>,
+    #[validate(email(message = "Invalid email"))]
+    pub email: Option<String>,
+    pub address: Option<String>,
+    #[validate(range(
+        min = 0,
+        max = 365,
+        message = "Lead time must be between 0 and 365 days"
+    ))]
+    pub lead_time_days: Option<i32>,
+    pub notes: Option<String>,
+}
+
+// DTO untuk query parameter
+#[derive(Debug, Deserialize)]
+pub struct GetSuppliersSchema {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    pub search: Option<String>,
+}
+
+// DTO untuk menghubungkan bahan ke supplier (upsert)
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpsertIngredientSupplierSchema {
+    pub ingredient_catalog_uuid: Uuid,
+    #[validate(range(
+        min = 0,
+        max = 365,
+        message = "Lead time must be between 0 and 365 days"
+    ))]
+    pub lead_time_days: Option<i32>,
+    pub min_order_qty: Option<Decimal>,
+    pub pack_size: Option<Decimal>,
+    pub last_price: Option<Decimal>,
+    pub is_preferred: Option<bool>,
+}
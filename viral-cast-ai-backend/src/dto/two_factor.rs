@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Debug)]
+pub struct TwoFactorSetupRequest {
+    pub current_password: String,
+}
+
+// Kode dari aplikasi authenticator, atau salah satu kode pemulihan
+#[derive(Deserialize, Debug, Default)]
+pub struct TwoFactorCodeRequest {
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DisableTwoFactorRequest {
+    pub current_password: String,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TwoFactorChallengeRequest {
+    pub challenge_token: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub recovery_code: Option<String>,
+    // Sama seperti login: nama perangkat untuk daftar sesi; fallback header X-Device-Name
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TwoFactorSetupResponse {
+    pub secret: String,
+    // Tampilkan sebagai QR code untuk dipindai aplikasi authenticator
+    pub otpauth_uri: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecoveryCodesResponse {
+    // Hanya ditampilkan sekali; yang disimpan server hanya hash-nya
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TwoFactorStatusResponse {
+    pub enabled: bool,
+    // Pendaftaran sudah dimulai tetapi belum dikonfirmasi dengan kode
+    pub pending: bool,
+    // Role pengguna wajib memakai 2FA (TWO_FACTOR_REQUIRED_ROLES)
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    // Role wajib 2FA tetapi belum mendaftar: panggil /auth/login/2fa/setup lebih dulu
+    pub setup_required: bool,
+    pub challenge_token: String,
+    pub expires_at: i64,
+}
@@ -0,0 +1,68 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::models::waste_logs::WASTE_REASON_CODES;
+
+// DTO untuk mencatat waste. Isi `items` (bahan) dan/atau `product_uuid` + `product_qty`
+// (produk jadi, dipecah menjadi bahan lewat resep).
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateWasteLogRequest {
+    #[validate(custom(function = "validate_reason_code"))]
+    pub reason_code: String,
+    pub notes: Option<String>,
+    // ID: Waktu kejadian (unix ms); default sekarang
+    // EN: When the waste happened (unix ms); defaults to now
+    pub wasted_at: Option<i64>,
+    pub product_uuid: Option<Uuid>,
+    #[validate(custom(function = "validate_positive"))]
+    pub product_qty: Option<Decimal>,
+    pub items: Option<Vec<WasteLogItemInput>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct WasteLogItemInput {
+    pub ingredient_catalog_uuid: Uuid,
+    #[validate(custom(function = "validate_positive"))]
+    pub quantity: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WasteLogListRequest {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+    pub reason_code: Option<String>,
+    pub product_uuid: Option<Uuid>,
+    pub ingredient_catalog_uuid: Option<Uuid>,
+    pub date_from: Option<i64>,
+    pub date_to: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WasteReportQuery {
+    pub date_from: Option<i64>,
+    pub date_to: Option<i64>,
+    pub ingredient_catalog_uuid: Option<Uuid>,
+    // ID: Zona waktu untuk pengelompokan bulan (default Asia/Jakarta)
+    // EN: Time zone used to bucket months (defaults to Asia/Jakarta)
+    pub timezone: Option<String>,
+}
+
+fn validate_reason_code(value: &str) -> Result<(), ValidationError> {
+    let upper = value.trim().to_ascii_uppercase();
+    if WASTE_REASON_CODES.contains(&upper.as_str()) {
+        Ok(())
+    } else {
+        Err(ValidationError::new(
+            "Invalid reason_code. Allowed: EXPIRED, SPOILED, SPILLED, CUSTOMER_RETURN, OVERPRODUCTION, DAMAGED, OTHER",
+        ))
+    }
+}
+
+fn validate_positive(value: &Decimal) -> Result<(), ValidationError> {
+    if *value <= Decimal::ZERO {
+        return Err(ValidationError::new("quantity must be greater than 0"));
+    }
+    Ok(())
+}
@@ -18,6 +18,7 @@ use redis::AsyncCommands;
 use serde_json::{json, to_string};
 
 use crate::repository::auth as auth_repository;
+use crate::repository::auth_tokens::{self as auth_tokens_repository, SessionMetadata};
 use crate::{
     dto::{
         api::ApiResponse,
@@ -25,8 +26,15 @@ use crate::{
         auth_token::{self, TokenDetails},
         users::FilteredUser,
     },
+    handlers::auth_account::send_verification_email,
+    handlers::auth_two_factor::two_factor_challenge,
     middleware::jwt::JWTAuthMiddleware,
+    models::auth_tokens::{
+        RefreshTokenRecord, EVENT_ACCOUNT_LOCKED, EVENT_LOGIN_FAILED, EVENT_REFRESH_TOKEN_REUSE,
+        REVOKED_REASON_LOGOUT, REVOKED_REASON_REUSE,
+    },
     models::user::{LoginUserSchema, RegisterUserSchema, User},
+    services::login_throttle::{FailedLogin, LoginThrottle, LoginThrottlePolicy},
     AppState,
 };
 
@@ -44,6 +52,7 @@ pub async fn health_checker_handler() -> impl IntoResponse {
 
 pub async fn register_user_handler(
     State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Json(body): Json<RegisterUserSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     let username_exists = auth_repository::username_exists(&data.db, &body.username)
@@ -144,6 +153,19 @@ pub async fn register_user_handler(
             (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
         })?;
 
+    // Email verifikasi dikirim best-effort; pendaftaran tetap berhasil bila gagal
+    if let Some(email) = user
+        .email
+        .as_deref()
+        .filter(|email| !email.trim().is_empty())
+    {
+        if let Err((_, Json(e))) =
+            send_verification_email(&data, user.uuid, &user.username, email).await
+        {
+            tracing::warn!("Failed to issue email verification: {}", e["message"]);
+        }
+    }
+
     let access_token_details = generate_token(
         user.uuid,
         data.env.access_token_max_age,
@@ -162,6 +184,13 @@ pub async fn register_user_handler(
         data.env.refresh_token_max_age,
     )
     .await?;
+    start_token_family(
+        &data,
+        &access_token_details,
+        &refresh_token_details,
+        &session_metadata(&request_headers, None),
+    )
+    .await?;
 
     let access_cookie = Cookie::build((
         "access_token",
@@ -265,8 +294,23 @@ pub async fn register_user_handler(
 
 pub async fn login_user_handler(
     State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Json(body): Json<LoginUserSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let (ip_address, user_agent) = client_metadata(&request_headers);
+    let throttle = LoginThrottle::new(
+        &data.redis_client,
+        &data.session_store,
+        LoginThrottlePolicy::from_config(&data.env),
+    );
+    // Kuncian dicek sebelum password agar username yang tidak ada pun ikut terkunci
+    if let Some(retry_after) = throttle
+        .locked_for(&body.username, ip_address.as_deref())
+        .await
+    {
+        return Err(login_locked_response(retry_after));
+    }
+
     let user = auth_repository::find_user_by_username(&data.db, &body.username)
         .await
         .map_err(|e| {
@@ -278,8 +322,35 @@ pub async fn login_user_handler(
                 "errors": {},
             });
             (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
-        })?
-        .ok_or_else(|| {
+        })?;
+
+    let is_valid = match user.as_ref().map(|user| PasswordHash::new(&user.password)) {
+        Some(Ok(parsed_hash)) => Argon2::default()
+            .verify_password(body.password.as_bytes(), &parsed_hash)
+            .map_or(false, |_| true),
+        _ => false,
+    };
+
+    let user = match user {
+        Some(user) if is_valid => user,
+        user => {
+            let failed = throttle
+                .record_failure(&body.username, ip_address.as_deref())
+                .await;
+            record_failed_login(
+                &data,
+                user.map(|user| user.uuid),
+                &body.username,
+                ip_address.as_deref(),
+                user_agent.as_deref(),
+                &failed,
+            )
+            .await;
+            tokio::time::sleep(failed.delay).await;
+            if failed.username_locked || failed.ip_locked {
+                return Err(login_locked_response(data.env.login_lockout_seconds));
+            }
+
             let error_response = serde_json::json!({
                 "code": 400,
                 "status": "BAD_REQUEST",
@@ -287,27 +358,39 @@ pub async fn login_user_handler(
                 "data": {},
                 "errors": {},
             });
-            (StatusCode::BAD_REQUEST, Json(error_response))
-        })?;
-
-    let is_valid = match PasswordHash::new(&user.password) {
-        Ok(parsed_hash) => Argon2::default()
-            .verify_password(body.password.as_bytes(), &parsed_hash)
-            .map_or(false, |_| true),
-        Err(_) => false,
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
     };
-
-    if !is_valid {
-        let error_response = serde_json::json!({
-            "code": 400,
-            "status": "BAD_REQUEST",
-            "message": "Username atau password salah",
-            "data": {},
-            "errors": {},
-        });
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    throttle.record_success(&body.username).await;
+
+    // 2FA aktif (atau diwajibkan untuk role pengguna): token baru terbit setelah kode diverifikasi
+    if let Some(challenge) = two_factor_challenge(&data, &user).await? {
+        let challenge_response = ApiResponse {
+            code: 200,
+            status: "OK".to_string(),
+            message: "Two-factor authentication required".to_string(),
+            data: challenge,
+            errors: json!({}),
+        };
+        return Ok(Json(challenge_response).into_response());
     }
 
+    issue_login_session(&data, &user, &request_headers, body.device_name, None)
+        .await
+        .map(IntoResponse::into_response)
+}
+
+/// ID: Terbitkan access + refresh token untuk login yang sudah lolos semua faktor, mulai keluarga
+///     token baru dan pasang cookie sesi.
+/// EN: Issue access + refresh tokens for a sign-in that passed every factor, start a new token
+///     family and set the session cookies.
+pub(crate) async fn issue_login_session(
+    data: &Arc<AppState>,
+    user: &User,
+    request_headers: &HeaderMap,
+    device_name: Option<String>,
+    recovery_codes: Option<Vec<String>>,
+) -> Result<Response<String>, (StatusCode, Json<serde_json::Value>)> {
     let access_token_details = generate_token(
         user.uuid,
         data.env.access_token_max_age,
@@ -319,13 +402,20 @@ pub async fn login_user_handler(
         data.env.refresh_token_private_key.to_owned(),
     )?;
 
-    save_token_data_to_redis(&data, &access_token_details, data.env.access_token_max_age).await?;
+    save_token_data_to_redis(data, &access_token_details, data.env.access_token_max_age).await?;
     save_token_data_to_redis(
-        &data,
+        data,
         &refresh_token_details,
         data.env.refresh_token_max_age * 60,
     )
     .await?;
+    start_token_family(
+        data,
+        &access_token_details,
+        &refresh_token_details,
+        &session_metadata(request_headers, device_name),
+    )
+    .await?;
 
     let access_cookie = Cookie::build((
         "access_token",
@@ -353,6 +443,8 @@ pub async fn login_user_handler(
 
     let processed_login = ProcessedLogin {
         access_token: access_token_details.token.clone(),
+        refresh_token: None,
+        recovery_codes,
     };
 
     let login_response = ApiResponse {
@@ -416,9 +508,14 @@ pub async fn login_user_handler(
     Ok(response)
 }
 
+/// ID: Terbitkan access token baru dan rotasi refresh token. Refresh token lama yang
+///     dipakai ulang mencabut seluruh keluarga token (login) dan dicatat sebagai kejadian keamanan.
+/// EN: Issue a new access token and rotate the refresh token. Presenting an already rotated
+///     refresh token revokes the whole token family (login) and records a security event.
 pub async fn refresh_access_token_handler(
     cookie_jar: CookieJar,
     State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     let message = "Could not refresh access token";
 
@@ -453,6 +550,24 @@ pub async fn refresh_access_token_handler(
         }
     };
 
+    // Reuse detection dijalankan sebelum cek Redis karena key token yang sudah dirotasi
+    // memang sudah dihapus dari Redis
+    let token_record =
+        auth_tokens_repository::find_refresh_token(&data.db, refresh_token_details.token_uuid)
+            .await
+            .map_err(db_error)?;
+    if let Some(record) = &token_record {
+        if record.family_revoked_at.is_some() {
+            return Err(unauthorized_response(
+                "Token is invalid or session has expired",
+            ));
+        }
+        if record.rotated_at.is_some() {
+            revoke_reused_token_family(&data, record, &request_headers).await;
+            return Err(unauthorized_response(REFRESH_TOKEN_REUSED_MESSAGE));
+        }
+    }
+
     // Try Redis, fallback to in-memory session store
     let redis_token_user_uuid_opt = match data.redis_client.get_multiplexed_async_connection().await
     {
@@ -529,11 +644,39 @@ pub async fn refresh_access_token_handler(
         (StatusCode::UNAUTHORIZED, Json(error_response))
     })?;
 
+    let family_uuid = match &token_record {
+        Some(record) => {
+            let rotated =
+                auth_tokens_repository::mark_refresh_token_rotated(&data.db, record.token_uuid)
+                    .await
+                    .map_err(db_error)?;
+            // Request lain merotasi token yang sama lebih dulu
+            if !rotated {
+                revoke_reused_token_family(&data, record, &request_headers).await;
+                return Err(unauthorized_response(REFRESH_TOKEN_REUSED_MESSAGE));
+            }
+            record.family_uuid
+        }
+        // Refresh token lama (diterbitkan sebelum rotasi aktif) memulai keluarga baru
+        None => auth_tokens_repository::create_token_family(
+            &data.db,
+            user.uuid,
+            &session_metadata(&request_headers, None),
+        )
+        .await
+        .map_err(db_error)?,
+    };
+
     let access_token_details = generate_token(
         user.uuid,
         data.env.access_token_max_age,
         data.env.access_token_private_key.to_owned(),
     )?;
+    let new_refresh_token_details = generate_token(
+        user.uuid,
+        data.env.refresh_token_max_age,
+        data.env.refresh_token_private_key.to_owned(),
+    )?;
 
     save_token_data_to_redis(
         &data,
@@ -541,6 +684,22 @@ pub async fn refresh_access_token_handler(
         data.env.access_token_max_age * 60,
     )
     .await?;
+    save_token_data_to_redis(
+        &data,
+        &new_refresh_token_details,
+        data.env.refresh_token_max_age * 60,
+    )
+    .await?;
+    auth_tokens_repository::insert_refresh_token(
+        &data.db,
+        family_uuid,
+        new_refresh_token_details.token_uuid,
+        access_token_details.token_uuid,
+    )
+    .await
+    .map_err(db_error)?;
+    // Refresh token lama tidak lagi valid; pemakaian berikutnya terdeteksi lewat rotated_at
+    remove_session_keys(&data, &[refresh_token_details.token_uuid]).await;
 
     let access_cookie = Cookie::build((
         "access_token",
@@ -551,6 +710,15 @@ pub async fn refresh_access_token_handler(
     .same_site(SameSite::Lax)
     .http_only(true);
 
+    let refresh_cookie = Cookie::build((
+        "refresh_token",
+        new_refresh_token_details.token.clone().unwrap_or_default(),
+    ))
+    .path("/")
+    .max_age(time::Duration::minutes(data.env.refresh_token_max_age * 60))
+    .same_site(SameSite::Lax)
+    .http_only(true);
+
     let logged_in_cookie = Cookie::build(("logged_in", "true"))
         .path("/")
         .max_age(time::Duration::minutes(data.env.access_token_max_age * 60))
@@ -559,6 +727,8 @@ pub async fn refresh_access_token_handler(
 
     let processed_refresh = ProcessedLogin {
         access_token: access_token_details.token.clone(),
+        refresh_token: new_refresh_token_details.token,
+        recovery_codes: None,
     };
 
     let refresh_response = ApiResponse {
@@ -586,6 +756,19 @@ pub async fn refresh_access_token_handler(
     })?;
     headers.append(header::SET_COOKIE, access_header);
 
+    let refresh_header =
+        header::HeaderValue::from_str(&refresh_cookie.to_string()).map_err(|e| {
+            let error_response = serde_json::json!({
+                "code": 500,
+                "status": "INTERNAL_SERVER_ERROR",
+                "message": format!("Failed to set refresh_token cookie: {}", e),
+                "data": {},
+                "errors": {},
+            });
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+        })?;
+    headers.append(header::SET_COOKIE, refresh_header);
+
     let logged_in_header =
         header::HeaderValue::from_str(&logged_in_cookie.to_string()).map_err(|e| {
             let error_response = serde_json::json!({
@@ -647,6 +830,22 @@ pub async fn logout_handler(
         }
     };
 
+    // Logout mengakhiri seluruh keluarga token login ini (termasuk token hasil rotasi)
+    if let Ok(Some(record)) =
+        auth_tokens_repository::find_refresh_token(&data.db, refresh_token_details.token_uuid).await
+    {
+        match auth_tokens_repository::revoke_token_family(
+            &data.db,
+            record.family_uuid,
+            REVOKED_REASON_LOGOUT,
+        )
+        .await
+        {
+            Ok(token_uuids) => remove_session_keys(&data, &token_uuids).await,
+            Err(e) => tracing::warn!("Failed to revoke token family on logout: {:?}", e),
+        }
+    }
+
     // Try to delete from Redis, fallback to in-memory store
     if let Ok(mut conn) = data.redis_client.get_multiplexed_async_connection().await {
         let _ = conn
@@ -818,7 +1017,7 @@ fn generate_token(
     })
 }
 
-async fn save_token_data_to_redis(
+pub(crate) async fn save_token_data_to_redis(
     data: &Arc<AppState>,
     token_details: &TokenDetails,
     max_age: i64,
@@ -855,3 +1054,211 @@ async fn save_token_data_to_redis(
         }
     }
 }
+
+const REFRESH_TOKEN_REUSED_MESSAGE: &str =
+    "Refresh token has already been used; this login has been revoked, please sign in again";
+
+fn login_locked_response(retry_after_seconds: u64) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = serde_json::json!({
+        "code": 429,
+        "status": "TOO_MANY_REQUESTS",
+        "message": "Terlalu banyak percobaan login gagal, coba lagi nanti",
+        "data": { "retry_after_seconds": retry_after_seconds },
+        "errors": {},
+    });
+    (StatusCode::TOO_MANY_REQUESTS, Json(error_response))
+}
+
+// Catat login gagal (dan kuncian yang dipicunya) ke auth_security_events
+async fn record_failed_login(
+    data: &Arc<AppState>,
+    user_uuid: Option<uuid::Uuid>,
+    username: &str,
+    ip_address: Option<&str>,
+    user_agent: Option<&str>,
+    failed: &FailedLogin,
+) {
+    let mut events = vec![(
+        EVENT_LOGIN_FAILED,
+        json!({
+            "username": username,
+            "username_failures": failed.username_failures,
+            "ip_failures": failed.ip_failures,
+        }),
+    )];
+    for (locked, scope) in [
+        (failed.username_locked, "username"),
+        (failed.ip_locked, "ip"),
+    ] {
+        if locked {
+            events.push((
+                EVENT_ACCOUNT_LOCKED,
+                json!({
+                    "username": username,
+                    "scope": scope,
+                    "lockout_seconds": data.env.login_lockout_seconds,
+                }),
+            ));
+        }
+    }
+    for (event_type, details) in events {
+        if let Err(e) = auth_tokens_repository::insert_security_event(
+            &data.db, user_uuid, None, event_type, ip_address, user_agent, details,
+        )
+        .await
+        {
+            tracing::error!("Failed to record security event: {:?}", e);
+        }
+    }
+}
+
+pub(crate) fn unauthorized_response(message: &str) -> (StatusCode, Json<serde_json::Value>) {
+    error_response(StatusCode::UNAUTHORIZED, message)
+}
+
+pub(crate) fn db_error(e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &format!("Database error: {}", e),
+    )
+}
+
+pub(crate) fn error_response(
+    status: StatusCode,
+    message: &str,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let status_text = status
+        .canonical_reason()
+        .unwrap_or("ERROR")
+        .to_uppercase()
+        .replace(' ', "_");
+    let error_response = serde_json::json!({
+        "code": status.as_u16(),
+        "status": status_text,
+        "message": message,
+        "data": {},
+        "errors": {},
+    });
+    (status, Json(error_response))
+}
+
+// Catat refresh token pertama sebuah login sebagai awal keluarga token baru
+async fn start_token_family(
+    data: &Arc<AppState>,
+    access_token_details: &TokenDetails,
+    refresh_token_details: &TokenDetails,
+    metadata: &SessionMetadata,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let family_uuid = auth_tokens_repository::create_token_family(
+        &data.db,
+        refresh_token_details.user_uuid,
+        metadata,
+    )
+    .await
+    .map_err(db_error)?;
+    auth_tokens_repository::insert_refresh_token(
+        &data.db,
+        family_uuid,
+        refresh_token_details.token_uuid,
+        access_token_details.token_uuid,
+    )
+    .await
+    .map_err(db_error)
+}
+
+// ID: Refresh token yang sudah dirotasi dipakai lagi: kemungkinan cookie dicuri. Cabut
+//     seluruh keluarga (pengguna asli dan penyerang sama-sama harus login ulang) dan catat.
+// EN: An already rotated refresh token was presented again, likely a stolen cookie. Revoke
+//     the whole family (both the real user and the attacker must sign in again) and log it.
+async fn revoke_reused_token_family(
+    data: &Arc<AppState>,
+    record: &RefreshTokenRecord,
+    request_headers: &HeaderMap,
+) {
+    tracing::warn!(
+        "Refresh token reuse detected: user={}, family={}, token={}",
+        record.user_uuid,
+        record.family_uuid,
+        record.token_uuid
+    );
+    match auth_tokens_repository::revoke_token_family(
+        &data.db,
+        record.family_uuid,
+        REVOKED_REASON_REUSE,
+    )
+    .await
+    {
+        Ok(token_uuids) => remove_session_keys(data, &token_uuids).await,
+        Err(e) => tracing::error!(
+            "Failed to revoke token family {}: {:?}",
+            record.family_uuid,
+            e
+        ),
+    }
+
+    let (ip_address, user_agent) = client_metadata(request_headers);
+    if let Err(e) = auth_tokens_repository::insert_security_event(
+        &data.db,
+        Some(record.user_uuid),
+        Some(record.family_uuid),
+        EVENT_REFRESH_TOKEN_REUSE,
+        ip_address.as_deref(),
+        user_agent.as_deref(),
+        json!({ "token_uuid": record.token_uuid }),
+    )
+    .await
+    {
+        tracing::error!("Failed to record security event: {:?}", e);
+    }
+}
+
+// Hapus key sesi dari Redis dan session store in-memory (keduanya bisa berisi token)
+pub(crate) async fn remove_session_keys(data: &Arc<AppState>, token_uuids: &[uuid::Uuid]) {
+    if token_uuids.is_empty() {
+        return;
+    }
+    let keys: Vec<String> = token_uuids.iter().map(|uuid| uuid.to_string()).collect();
+    if let Ok(mut conn) = data.redis_client.get_multiplexed_async_connection().await {
+        if let Err(e) = conn.del::<_, ()>(&keys).await {
+            tracing::warn!("Redis del failed for session keys: {:?}", e);
+        }
+    }
+    let mut store = data.session_store.lock().await;
+    for key in &keys {
+        store.remove(key);
+    }
+}
+
+// Metadata sesi baru; nama perangkat dari body login atau header X-Device-Name
+fn session_metadata(headers: &HeaderMap, device_name: Option<String>) -> SessionMetadata {
+    let (ip_address, user_agent) = client_metadata(headers);
+    let device_name = device_name
+        .or_else(|| {
+            headers
+                .get("x-device-name")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        })
+        .map(|name| name.trim().chars().take(100).collect::<String>())
+        .filter(|name| !name.is_empty());
+    SessionMetadata {
+        device_name,
+        user_agent,
+        ip_address,
+    }
+}
+
+// IP klien dari header X-Real-IP dan User-Agent
+// X-Real-IP ditulis ulang oleh middleware::client_ip (alamat koneksi atau proxy tepercaya)
+pub(crate) fn client_metadata(headers: &HeaderMap) -> (Option<String>, Option<String>) {
+    let ip_address = headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+    (ip_address, user_agent)
+}
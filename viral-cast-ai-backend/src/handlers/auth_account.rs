@@ -0,0 +1,423 @@
+use std::sync::Arc;
+
+use argon2::{password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use rand_core::OsRng;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        api::ApiResponse,
+        auth::{EmailVerificationResponse, RevokedSessionsResponse},
+        auth_token::{generate_action_token, hash_action_token},
+    },
+    handlers::auth::{client_metadata, db_error, error_response},
+    handlers::auth_sessions::{current_session, revoke_all},
+    handlers::pos_devices::ensure_password_session,
+    middleware::jwt::JWTAuthMiddleware,
+    models::auth_tokens::{
+        AuthActionToken, EVENT_EMAIL_VERIFIED, EVENT_PASSWORD_CHANGED, EVENT_PASSWORD_RESET,
+        PURPOSE_RESET_PASSWORD, PURPOSE_VERIFY_EMAIL, REVOKED_REASON_PASSWORD_CHANGED,
+    },
+    models::user::{
+        ChangePasswordSchema, ForgotPasswordSchema, ResetPasswordSchema, VerifyEmailSchema,
+    },
+    repository::auth as auth_repository,
+    repository::auth_tokens as auth_tokens_repository,
+    services::login_throttle::{LoginThrottle, LoginThrottlePolicy},
+    services::mailer::MailMessage,
+    AppState,
+};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+// Jeda minimum sebelum email verifikasi/reset untuk akun yang sama boleh dikirim ulang
+const RESEND_COOLDOWN_MS: i64 = 60_000;
+const INVALID_TOKEN_MESSAGE: &str = "Token tidak valid atau sudah kedaluwarsa";
+
+// POST /api/v1/auth/verify-email/request: kirim ulang email verifikasi ke pengguna login
+pub async fn request_email_verification_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let email = jwt_auth
+        .user
+        .email
+        .clone()
+        .filter(|email| !email.trim().is_empty())
+        .ok_or_else(|| error_response(StatusCode::BAD_REQUEST, "Akun belum memiliki email"))?;
+    let verified_at = auth_repository::email_verified_at(&data.db, jwt_auth.user.uuid)
+        .await
+        .map_err(db_error)?;
+    if verified_at.is_some() {
+        return Err(error_response(
+            StatusCode::CONFLICT,
+            "Email sudah terverifikasi",
+        ));
+    }
+    if !send_verification_email(&data, jwt_auth.user.uuid, &jwt_auth.user.username, &email).await? {
+        return Err(error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Email verifikasi baru saja dikirim, coba lagi sebentar lagi",
+        ));
+    }
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Verification email sent".to_string(),
+        data: EmailVerificationResponse {
+            email,
+            email_verified_at: None,
+        },
+        errors: json!({}),
+    }))
+}
+
+// POST /api/v1/auth/verify-email
+pub async fn verify_email_handler(
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<VerifyEmailSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let token = consume_token(&data, PURPOSE_VERIFY_EMAIL, &body.token).await?;
+    let email = token.email.ok_or_else(invalid_token)?;
+    // Email yang sudah diganti sejak token diterbitkan tidak ikut terverifikasi
+    let verified_at = auth_repository::mark_email_verified(&data.db, token.user_uuid, &email)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(invalid_token)?;
+    record_account_event(
+        &data,
+        token.user_uuid,
+        EVENT_EMAIL_VERIFIED,
+        &request_headers,
+        json!({ "email": email }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Email verified successfully".to_string(),
+        data: EmailVerificationResponse {
+            email,
+            email_verified_at: Some(verified_at),
+        },
+        errors: json!({}),
+    }))
+}
+
+/// ID: Selalu membalas sukses agar endpoint tidak membocorkan email yang terdaftar.
+/// EN: Always answers success so the endpoint does not reveal which emails are registered.
+pub async fn forgot_password_handler(
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<ForgotPasswordSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = auth_repository::find_user_by_email(&data.db, body.email.trim())
+        .await
+        .map_err(db_error)?;
+    if let Some((user, email)) = user.and_then(|user| user.email.clone().map(|e| (user, e))) {
+        let token = issue_token(
+            &data,
+            user.uuid,
+            PURPOSE_RESET_PASSWORD,
+            Some(&email),
+            data.env.password_reset_ttl_minutes,
+        )
+        .await?;
+        if let Some(token) = token {
+            let link = frontend_link(&data, "reset-password", &token);
+            spawn_mail(
+                &data,
+                MailMessage {
+                    to: email,
+                    subject: "Reset password Viral Cast AI".to_string(),
+                    text_body: format!(
+                        "Halo {},\n\nKami menerima permintaan reset password untuk akun \
+                         Anda. Buka tautan berikut untuk membuat password baru (berlaku {} \
+                         menit):\n\n{}\n\nAbaikan email ini jika Anda tidak memintanya; \
+                         password Anda tidak berubah.",
+                        user.username, data.env.password_reset_ttl_minutes, link
+                    ),
+                },
+            );
+        }
+    }
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Jika email terdaftar, tautan reset password telah dikirim".to_string(),
+        data: json!({}),
+        errors: json!({}),
+    }))
+}
+
+// POST /api/v1/auth/reset-password: semua sesi dicabut setelah password diganti
+pub async fn reset_password_handler(
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<ResetPasswordSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    validate_new_password(&body.new_password)?;
+    let token = consume_token(&data, PURPOSE_RESET_PASSWORD, &body.token).await?;
+    let user = auth_repository::find_user_by_uuid(&data.db, token.user_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(invalid_token)?;
+
+    let hashed_password = hash_password(&body.new_password)?;
+    auth_repository::update_password(&data.db, user.uuid, &hashed_password)
+        .await
+        .map_err(db_error)?;
+    // Tautan reset yang sampai ke inbox juga membuktikan kepemilikan email
+    if let Some(email) = token.email.as_deref() {
+        if let Err(e) = auth_repository::mark_email_verified(&data.db, user.uuid, email).await {
+            tracing::warn!("Failed to mark email verified after reset: {:?}", e);
+        }
+    }
+    let revoked = revoke_all(&data, user.uuid, None, REVOKED_REASON_PASSWORD_CHANGED).await?;
+    LoginThrottle::new(
+        &data.redis_client,
+        &data.session_store,
+        LoginThrottlePolicy::from_config(&data.env),
+    )
+    .unlock(&user.username)
+    .await;
+    record_account_event(
+        &data,
+        user.uuid,
+        EVENT_PASSWORD_RESET,
+        &request_headers,
+        json!({ "revoked_sessions": revoked }),
+    )
+    .await;
+
+    Ok(password_changed_response(revoked))
+}
+
+// POST /api/v1/auth/change-password: sesi lain dicabut, sesi saat ini dipertahankan
+pub async fn change_password_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<ChangePasswordSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_password_session(&jwt_auth)?;
+    validate_new_password(&body.new_password)?;
+    if !verify_password(&jwt_auth.user.password, &body.current_password) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Password saat ini salah",
+        ));
+    }
+
+    let hashed_password = hash_password(&body.new_password)?;
+    auth_repository::update_password(&data.db, jwt_auth.user.uuid, &hashed_password)
+        .await
+        .map_err(db_error)?;
+    auth_tokens_repository::invalidate_action_tokens(
+        &data.db,
+        jwt_auth.user.uuid,
+        PURPOSE_RESET_PASSWORD,
+    )
+    .await
+    .map_err(db_error)?;
+    let keep_family = current_session(&data, &jwt_auth).await?;
+    let revoked = revoke_all(
+        &data,
+        jwt_auth.user.uuid,
+        keep_family,
+        REVOKED_REASON_PASSWORD_CHANGED,
+    )
+    .await?;
+    record_account_event(
+        &data,
+        jwt_auth.user.uuid,
+        EVENT_PASSWORD_CHANGED,
+        &request_headers,
+        json!({ "revoked_sessions": revoked }),
+    )
+    .await;
+
+    Ok(password_changed_response(revoked))
+}
+
+/// ID: Terbitkan token verifikasi dan kirim emailnya; `false` bila masih dalam jeda kirim ulang.
+/// EN: Issue a verification token and send its email; `false` while the resend cooldown runs.
+pub(crate) async fn send_verification_email(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    username: &str,
+    email: &str,
+) -> Result<bool, (StatusCode, Json<serde_json::Value>)> {
+    let token = issue_token(
+        data,
+        user_uuid,
+        PURPOSE_VERIFY_EMAIL,
+        Some(email),
+        data.env.email_verification_ttl_minutes,
+    )
+    .await?;
+    let Some(token) = token else {
+        return Ok(false);
+    };
+    let link = frontend_link(data, "verify-email", &token);
+    spawn_mail(
+        data,
+        MailMessage {
+            to: email.to_string(),
+            subject: "Verifikasi email Viral Cast AI".to_string(),
+            text_body: format!(
+                "Halo {},\n\nKonfirmasi alamat email Anda dengan membuka tautan berikut \
+                 (berlaku {} jam):\n\n{}\n\nAbaikan email ini jika Anda tidak mendaftar.",
+                username,
+                data.env.email_verification_ttl_minutes / 60,
+                link
+            ),
+        },
+    );
+    Ok(true)
+}
+
+// Token mentah dikembalikan untuk email; hanya hash-nya yang disimpan
+async fn issue_token(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    purpose: &str,
+    email: Option<&str>,
+    ttl_minutes: i64,
+) -> Result<Option<String>, (StatusCode, Json<serde_json::Value>)> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let last_created_at =
+        auth_tokens_repository::last_action_token_created_at(&data.db, user_uuid, purpose)
+            .await
+            .map_err(db_error)?;
+    if last_created_at.is_some_and(|created_at| now - created_at < RESEND_COOLDOWN_MS) {
+        return Ok(None);
+    }
+
+    let (token, token_hash) = generate_action_token();
+    let expires_at = now + ttl_minutes * 60 * 1000;
+    auth_tokens_repository::create_action_token(
+        &data.db,
+        user_uuid,
+        purpose,
+        &token_hash,
+        email,
+        expires_at,
+    )
+    .await
+    .map_err(db_error)?;
+    Ok(Some(token))
+}
+
+async fn consume_token(
+    data: &Arc<AppState>,
+    purpose: &str,
+    token: &str,
+) -> Result<AuthActionToken, (StatusCode, Json<serde_json::Value>)> {
+    auth_tokens_repository::consume_action_token(&data.db, purpose, &hash_action_token(token))
+        .await
+        .map_err(db_error)?
+        .ok_or_else(invalid_token)
+}
+
+// Pengiriman di background: respons tidak menunggu SMTP (dan tidak membocorkan waktunya)
+fn spawn_mail(data: &Arc<AppState>, message: MailMessage) {
+    let mailer = data.mailer.clone();
+    tokio::spawn(async move {
+        if let Err(e) = mailer.send(&message).await {
+            tracing::error!("Failed to send \"{}\" email: {}", message.subject, e);
+        }
+    });
+}
+
+fn frontend_link(data: &Arc<AppState>, path: &str, token: &str) -> String {
+    format!(
+        "{}/{}?token={}",
+        data.env.client_origin.trim_end_matches('/'),
+        path,
+        token
+    )
+}
+
+fn validate_new_password(password: &str) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Password minimal {} karakter", MIN_PASSWORD_LENGTH),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn hash_password(
+    password: &str,
+) -> Result<String, (StatusCode, Json<serde_json::Value>)> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Password hashing error: {}", e),
+            )
+        })
+}
+
+pub(crate) fn verify_password(password_hash: &str, password: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|parsed_hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed_hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+async fn record_account_event(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    event_type: &str,
+    request_headers: &HeaderMap,
+    details: serde_json::Value,
+) {
+    let (ip_address, user_agent) = client_metadata(request_headers);
+    if let Err(e) = auth_tokens_repository::insert_security_event(
+        &data.db,
+        Some(user_uuid),
+        None,
+        event_type,
+        ip_address.as_deref(),
+        user_agent.as_deref(),
+        details,
+    )
+    .await
+    {
+        tracing::error!("Failed to record security event: {:?}", e);
+    }
+}
+
+fn password_changed_response(
+    revoked_sessions: usize,
+) -> Json<ApiResponse<RevokedSessionsResponse>> {
+    Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Password changed successfully".to_string(),
+        data: RevokedSessionsResponse { revoked_sessions },
+        errors: json!({}),
+    })
+}
+
+fn invalid_token() -> (StatusCode, Json<serde_json::Value>) {
+    error_response(StatusCode::BAD_REQUEST, INVALID_TOKEN_MESSAGE)
+}
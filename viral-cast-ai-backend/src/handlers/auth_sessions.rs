@@ -0,0 +1,340 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        api::ApiResponse,
+        auth::{
+            AccountUnlockedResponse, RevokeSessionsQuery, RevokedSessionsResponse, SessionResponse,
+        },
+    },
+    handlers::auth::{client_metadata, db_error, error_response, remove_session_keys},
+    handlers::auth_two_factor::two_factor_throttle_key,
+    handlers::pos_devices::pin_throttle_key,
+    middleware::jwt::JWTAuthMiddleware,
+    models::auth_tokens::{
+        AuthSession, EVENT_ACCOUNT_UNLOCKED, EVENT_SESSIONS_REVOKED_BY_ADMIN, REVOKED_REASON_ADMIN,
+        REVOKED_REASON_SIGNED_OUT,
+    },
+    models::roles::{session_admin_rank, SESSION_ADMIN_ROLE_NUMBERS, SUPER_ADMIN_ROLE_NUMBER},
+    repository::auth as auth_repository,
+    repository::auth_tokens as auth_tokens_repository,
+    services::login_throttle::{LoginThrottle, LoginThrottlePolicy},
+    AppState,
+};
+
+// GET /api/v1/auth/sessions
+pub async fn list_sessions_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let sessions = load_sessions(&data, jwt_auth.user.uuid, Some(&jwt_auth)).await?;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Get active sessions successfully".to_string(),
+        data: sessions,
+        errors: json!({}),
+    }))
+}
+
+// DELETE /api/v1/auth/sessions/:id
+pub async fn revoke_session_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    Path(session_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    revoke_one(
+        &data,
+        jwt_auth.user.uuid,
+        session_uuid,
+        REVOKED_REASON_SIGNED_OUT,
+    )
+    .await?;
+    Ok(revoked_response(1))
+}
+
+/// ID: "Keluar dari semua perangkat". `?keep_current=true` mempertahankan sesi pemanggil.
+/// EN: "Sign out everywhere". `?keep_current=true` keeps the caller's own session.
+pub async fn revoke_all_sessions_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    Query(params): Query<RevokeSessionsQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let keep_family = if params.keep_current.unwrap_or(false) {
+        current_session(&data, &jwt_auth).await?
+    } else {
+        None
+    };
+    let revoked = revoke_all(
+        &data,
+        jwt_auth.user.uuid,
+        keep_family,
+        REVOKED_REASON_SIGNED_OUT,
+    )
+    .await?;
+    Ok(revoked_response(revoked))
+}
+
+// GET /api/v1/auth/users/:user_uuid/sessions (admin)
+pub async fn list_user_sessions_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    Path(user_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_session_admin(&data, &jwt_auth, user_uuid).await?;
+    let sessions = load_sessions(&data, user_uuid, None).await?;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Get active sessions successfully".to_string(),
+        data: sessions,
+        errors: json!({}),
+    }))
+}
+
+// DELETE /api/v1/auth/users/:user_uuid/sessions (admin): cabut semua sesi staf
+pub async fn revoke_user_sessions_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Path(user_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_session_admin(&data, &jwt_auth, user_uuid).await?;
+    let revoked = revoke_all(&data, user_uuid, None, REVOKED_REASON_ADMIN).await?;
+    record_admin_revocation(&data, &jwt_auth, &request_headers, user_uuid, None, revoked).await;
+    Ok(revoked_response(revoked))
+}
+
+// DELETE /api/v1/auth/users/:user_uuid/sessions/:id (admin): mis. tablet POS yang hilang
+pub async fn revoke_user_session_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Path((user_uuid, session_uuid)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_session_admin(&data, &jwt_auth, user_uuid).await?;
+    revoke_one(&data, user_uuid, session_uuid, REVOKED_REASON_ADMIN).await?;
+    record_admin_revocation(
+        &data,
+        &jwt_auth,
+        &request_headers,
+        user_uuid,
+        Some(session_uuid),
+        1,
+    )
+    .await;
+    Ok(revoked_response(1))
+}
+
+// POST /api/v1/auth/users/:user_uuid/unlock (admin): cabut kuncian login akun
+pub async fn unlock_user_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Path(user_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_session_admin(&data, &jwt_auth, user_uuid).await?;
+    let user = auth_repository::find_user_by_uuid(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "User not found"))?;
+
+    let throttle = LoginThrottle::new(
+        &data.redis_client,
+        &data.session_store,
+        LoginThrottlePolicy::from_config(&data.env),
+    );
+    throttle.unlock(&user.username).await;
+    throttle.unlock(&pin_throttle_key(user_uuid)).await;
+    throttle.unlock(&two_factor_throttle_key(user_uuid)).await;
+
+    let (ip_address, user_agent) = client_metadata(&request_headers);
+    if let Err(e) = auth_tokens_repository::insert_security_event(
+        &data.db,
+        Some(user_uuid),
+        None,
+        EVENT_ACCOUNT_UNLOCKED,
+        ip_address.as_deref(),
+        user_agent.as_deref(),
+        json!({ "unlocked_by": jwt_auth.user.uuid }),
+    )
+    .await
+    {
+        tracing::error!("Failed to record security event: {:?}", e);
+    }
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Account unlocked successfully".to_string(),
+        data: AccountUnlockedResponse {
+            user_uuid,
+            username: user.username,
+        },
+        errors: json!({}),
+    }))
+}
+
+async fn load_sessions(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    caller: Option<&JWTAuthMiddleware>,
+) -> Result<Vec<SessionResponse>, (StatusCode, Json<serde_json::Value>)> {
+    // Masa berlaku refresh token (menit, sama dengan TTL JWT refresh) dalam milidetik
+    let refresh_ttl_ms = data.env.refresh_token_max_age * 60 * 1000;
+    let sessions =
+        auth_tokens_repository::list_active_sessions(&data.db, user_uuid, refresh_ttl_ms)
+            .await
+            .map_err(db_error)?;
+    let current = match caller {
+        Some(jwt_auth) => current_session(data, jwt_auth).await?,
+        None => None,
+    };
+    Ok(sessions
+        .into_iter()
+        .map(|session| to_session_response(session, current))
+        .collect())
+}
+
+fn to_session_response(session: AuthSession, current: Option<Uuid>) -> SessionResponse {
+    SessionResponse {
+        uuid: session.uuid,
+        device_name: session.device_name,
+        user_agent: session.user_agent,
+        ip_address: session.ip_address,
+        created_at: session.created_at,
+        last_seen_at: session.last_seen_at,
+        current: current == Some(session.uuid),
+    }
+}
+
+pub(crate) async fn current_session(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+) -> Result<Option<Uuid>, (StatusCode, Json<serde_json::Value>)> {
+    auth_tokens_repository::find_family_by_access_token(&data.db, jwt_auth.access_token_uuid)
+        .await
+        .map_err(db_error)
+}
+
+async fn revoke_one(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    session_uuid: Uuid,
+    reason: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let token_uuids =
+        auth_tokens_repository::revoke_user_session(&data.db, user_uuid, session_uuid, reason)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "Session not found"))?;
+    remove_session_keys(data, &token_uuids).await;
+    Ok(())
+}
+
+pub(crate) async fn revoke_all(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    keep_family: Option<Uuid>,
+    reason: &str,
+) -> Result<usize, (StatusCode, Json<serde_json::Value>)> {
+    let (revoked, token_uuids) =
+        auth_tokens_repository::revoke_user_sessions(&data.db, user_uuid, keep_family, reason)
+            .await
+            .map_err(db_error)?;
+    remove_session_keys(data, &token_uuids).await;
+    Ok(revoked)
+}
+
+// ID: Super Admin boleh mengelola sesi/kuncian/2FA siapa pun; Owner/Admin hanya akun berperingkat
+//     lebih rendah di toko yang sama (Admin tidak boleh mengelola Owner atau Admin lain). Sesi PIN
+//     kasir di terminal POS tidak pernah boleh.
+// EN: Super Admin may manage anyone's sessions/lockout/2FA; Owner/Admin only lower-ranked accounts
+//     of their own store (an Admin cannot manage an Owner or another Admin). A cashier PIN session
+//     on a POS terminal never may.
+pub(crate) async fn ensure_session_admin(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    target_user_uuid: Uuid,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let forbidden = || {
+        error_response(
+            StatusCode::FORBIDDEN,
+            "You are not allowed to manage this user's account",
+        )
+    };
+    if jwt_auth.pos_device.is_some() {
+        return Err(forbidden());
+    }
+    let (caller_store, caller_role) =
+        auth_tokens_repository::find_profile_scope(&data.db, jwt_auth.user.uuid)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(forbidden)?;
+    let caller_role = caller_role.ok_or_else(forbidden)?;
+    if !SESSION_ADMIN_ROLE_NUMBERS.contains(&caller_role) {
+        return Err(forbidden());
+    }
+    if caller_role == SUPER_ADMIN_ROLE_NUMBER {
+        return Ok(());
+    }
+
+    let (target_store, target_role) =
+        auth_tokens_repository::find_profile_scope(&data.db, target_user_uuid)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "User not found"))?;
+    if session_admin_rank(target_role) >= session_admin_rank(Some(caller_role)) {
+        return Err(forbidden());
+    }
+    match (caller_store, target_store) {
+        (Some(caller_store), Some(target_store)) if caller_store == target_store => Ok(()),
+        _ => Err(forbidden()),
+    }
+}
+
+async fn record_admin_revocation(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    request_headers: &HeaderMap,
+    target_user_uuid: Uuid,
+    session_uuid: Option<Uuid>,
+    revoked_sessions: usize,
+) {
+    let (ip_address, user_agent) = client_metadata(request_headers);
+    if let Err(e) = auth_tokens_repository::insert_security_event(
+        &data.db,
+        Some(target_user_uuid),
+        session_uuid,
+        EVENT_SESSIONS_REVOKED_BY_ADMIN,
+        ip_address.as_deref(),
+        user_agent.as_deref(),
+        json!({
+            "revoked_by": jwt_auth.user.uuid,
+            "revoked_sessions": revoked_sessions,
+        }),
+    )
+    .await
+    {
+        tracing::error!("Failed to record security event: {:?}", e);
+    }
+}
+
+fn revoked_response(revoked_sessions: usize) -> Json<ApiResponse<RevokedSessionsResponse>> {
+    Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Sessions revoked successfully".to_string(),
+        data: RevokedSessionsResponse { revoked_sessions },
+        errors: json!({}),
+    })
+}
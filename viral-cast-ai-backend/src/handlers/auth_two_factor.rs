@@ -0,0 +1,653 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        api::ApiResponse,
+        auth_token::{generate_action_token, hash_action_token},
+        two_factor::{
+            DisableTwoFactorRequest, RecoveryCodesResponse, TwoFactorChallengeRequest,
+            TwoFactorChallengeResponse, TwoFactorCodeRequest, TwoFactorLoginRequest,
+            TwoFactorSetupRequest, TwoFactorSetupResponse, TwoFactorStatusResponse,
+        },
+    },
+    handlers::auth::{client_metadata, db_error, error_response, issue_login_session},
+    handlers::auth_account::{hash_password, verify_password},
+    handlers::auth_sessions::{current_session, ensure_session_admin, revoke_all},
+    handlers::pos_devices::ensure_password_session,
+    middleware::jwt::JWTAuthMiddleware,
+    models::auth_tokens::{PURPOSE_TWO_FACTOR_LOGIN, REVOKED_REASON_TWO_FACTOR_ENABLED},
+    models::two_factor::{
+        TotpSecret, EVENT_RECOVERY_CODES_REGENERATED, EVENT_RECOVERY_CODE_USED,
+        EVENT_TWO_FACTOR_DISABLED, EVENT_TWO_FACTOR_ENABLED, EVENT_TWO_FACTOR_FAILED,
+        EVENT_TWO_FACTOR_RESET_BY_ADMIN, RECOVERY_CODE_COUNT,
+    },
+    models::user::User,
+    repository::auth as auth_repository,
+    repository::auth_tokens as auth_tokens_repository,
+    repository::two_factor as two_factor_repository,
+    services::login_throttle::{LoginThrottle, LoginThrottlePolicy},
+    services::totp,
+    AppState,
+};
+
+const INVALID_CHALLENGE_MESSAGE: &str = "Sesi login 2FA tidak valid atau sudah kedaluwarsa";
+
+// GET /api/v1/auth/2fa
+pub async fn two_factor_status_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user_uuid = jwt_auth.user.uuid;
+    let secret = two_factor_repository::find_totp_secret(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?;
+    let recovery_codes_remaining =
+        two_factor_repository::count_unused_recovery_codes(&data.db, user_uuid)
+            .await
+            .map_err(db_error)?;
+    let required = two_factor_required(&data, user_uuid).await?;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Get two-factor status successfully".to_string(),
+        data: TwoFactorStatusResponse {
+            enabled: secret.as_ref().is_some_and(TotpSecret::is_confirmed),
+            pending: secret.as_ref().is_some_and(|secret| !secret.is_confirmed()),
+            required,
+            recovery_codes_remaining,
+        },
+        errors: json!({}),
+    }))
+}
+
+// POST /api/v1/auth/2fa/setup: mulai pendaftaran, secret baru aktif setelah /2fa/confirm
+pub async fn two_factor_setup_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<TwoFactorSetupRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_password_session(&jwt_auth)?;
+    if !verify_password(&jwt_auth.user.password, &body.current_password) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Password saat ini salah",
+        ));
+    }
+    let setup = start_enrolment(&data, &jwt_auth.user).await?;
+    Ok(setup_response(setup))
+}
+
+// POST /api/v1/auth/2fa/confirm: kode pertama dari aplikasi mengaktifkan 2FA
+pub async fn two_factor_confirm_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_password_session(&jwt_auth)?;
+    let user_uuid = jwt_auth.user.uuid;
+    let secret = two_factor_repository::find_totp_secret(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(setup_not_started)?;
+    if secret.is_confirmed() {
+        return Err(already_enabled());
+    }
+    check_second_factor(&data, &secret, body.code.as_deref(), None, &request_headers).await?;
+    let keep_family = current_session(&data, &jwt_auth).await?;
+    let recovery_codes = enable_two_factor(&data, user_uuid, keep_family, &request_headers).await?;
+    Ok(recovery_codes_response(
+        "Two-factor authentication enabled",
+        recovery_codes,
+    ))
+}
+
+// POST /api/v1/auth/2fa/recovery-codes: buat ulang kode pemulihan (kode lama hangus)
+pub async fn regenerate_recovery_codes_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_password_session(&jwt_auth)?;
+    let user_uuid = jwt_auth.user.uuid;
+    let secret = find_enabled_secret(&data, user_uuid).await?;
+    check_second_factor(
+        &data,
+        &secret,
+        body.code.as_deref(),
+        body.recovery_code.as_deref(),
+        &request_headers,
+    )
+    .await?;
+    let recovery_codes = issue_recovery_codes(&data, user_uuid).await?;
+    record_two_factor_event(
+        &data,
+        Some(user_uuid),
+        EVENT_RECOVERY_CODES_REGENERATED,
+        &request_headers,
+        json!({}),
+    )
+    .await;
+    Ok(recovery_codes_response(
+        "Recovery codes regenerated",
+        recovery_codes,
+    ))
+}
+
+// DELETE /api/v1/auth/2fa: matikan 2FA (tidak boleh bila role pengguna mewajibkannya)
+pub async fn disable_two_factor_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<DisableTwoFactorRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_password_session(&jwt_auth)?;
+    let user_uuid = jwt_auth.user.uuid;
+    if two_factor_required(&data, user_uuid).await? {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "Two-factor authentication is required for your role",
+        ));
+    }
+    if !verify_password(&jwt_auth.user.password, &body.current_password) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Password saat ini salah",
+        ));
+    }
+    let secret = find_enabled_secret(&data, user_uuid).await?;
+    check_second_factor(
+        &data,
+        &secret,
+        body.code.as_deref(),
+        body.recovery_code.as_deref(),
+        &request_headers,
+    )
+    .await?;
+
+    two_factor_repository::delete_two_factor(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?;
+    record_two_factor_event(
+        &data,
+        Some(user_uuid),
+        EVENT_TWO_FACTOR_DISABLED,
+        &request_headers,
+        json!({}),
+    )
+    .await;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Two-factor authentication disabled".to_string(),
+        data: json!({}),
+        errors: json!({}),
+    }))
+}
+
+// ID: DELETE /api/v1/auth/users/:user_uuid/2fa: admin mereset 2FA staf yang kehilangan perangkat
+//     dan kode pemulihannya; staf mendaftar ulang saat login berikutnya bila role mewajibkan.
+// EN: DELETE /api/v1/auth/users/:user_uuid/2fa: an admin resets 2FA for staff who lost both the
+//     device and the recovery codes; they re-enrol on next sign-in when their role requires it.
+pub async fn reset_user_two_factor_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Path(user_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_session_admin(&data, &jwt_auth, user_uuid).await?;
+    let deleted = two_factor_repository::delete_two_factor(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?;
+    if !deleted {
+        return Err(error_response(
+            StatusCode::NOT_FOUND,
+            "Two-factor authentication is not set up for this user",
+        ));
+    }
+    auth_tokens_repository::invalidate_action_tokens(&data.db, user_uuid, PURPOSE_TWO_FACTOR_LOGIN)
+        .await
+        .map_err(db_error)?;
+    record_two_factor_event(
+        &data,
+        Some(user_uuid),
+        EVENT_TWO_FACTOR_RESET_BY_ADMIN,
+        &request_headers,
+        json!({ "admin_uuid": jwt_auth.user.uuid }),
+    )
+    .await;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Two-factor authentication reset".to_string(),
+        data: json!({}),
+        errors: json!({}),
+    }))
+}
+
+// POST /api/v1/auth/login/2fa/setup: role wajib 2FA yang belum mendaftar, di tengah login
+pub async fn login_two_factor_setup_handler(
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<TwoFactorChallengeRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = challenge_user(&data, &body.challenge_token).await?;
+    let setup = start_enrolment(&data, &user).await?;
+    Ok(setup_response(setup))
+}
+
+/// ID: Langkah kedua login: tukar challenge_token + kode TOTP (atau kode pemulihan) dengan
+///     access/refresh token. Bila pendaftaran 2FA diselesaikan di sini, kode pemulihan ikut
+///     dikembalikan.
+/// EN: Second login step: exchange challenge_token + TOTP code (or a recovery code) for
+///     access/refresh tokens. When 2FA enrolment is completed here, recovery codes are returned
+///     as well.
+pub async fn login_two_factor_handler(
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<TwoFactorLoginRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = challenge_user(&data, &body.challenge_token).await?;
+    let secret = two_factor_repository::find_totp_secret(&data.db, user.uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(setup_not_started)?;
+    check_second_factor(
+        &data,
+        &secret,
+        body.code.as_deref(),
+        body.recovery_code.as_deref(),
+        &request_headers,
+    )
+    .await?;
+
+    // Tantangan dipakai sekali; request paralel dengan token yang sama kalah di sini
+    auth_tokens_repository::consume_action_token(
+        &data.db,
+        PURPOSE_TWO_FACTOR_LOGIN,
+        &hash_action_token(&body.challenge_token),
+    )
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| error_response(StatusCode::UNAUTHORIZED, INVALID_CHALLENGE_MESSAGE))?;
+
+    let recovery_codes = if secret.is_confirmed() {
+        None
+    } else {
+        Some(enable_two_factor(&data, user.uuid, None, &request_headers).await?)
+    };
+    issue_login_session(
+        &data,
+        &user,
+        &request_headers,
+        body.device_name,
+        recovery_codes,
+    )
+    .await
+}
+
+/// ID: Buat tantangan 2FA untuk login yang passwordnya benar, atau None bila pengguna tidak
+///     memakai 2FA dan role-nya tidak mewajibkannya.
+/// EN: Create a 2FA challenge for a sign-in with a correct password, or None when the user has
+///     no 2FA and their role does not require it.
+pub(crate) async fn two_factor_challenge(
+    data: &Arc<AppState>,
+    user: &User,
+) -> Result<Option<TwoFactorChallengeResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let enabled = two_factor_repository::find_totp_secret(&data.db, user.uuid)
+        .await
+        .map_err(db_error)?
+        .is_some_and(|secret| secret.is_confirmed());
+    if !enabled && !two_factor_required(data, user.uuid).await? {
+        return Ok(None);
+    }
+
+    let (challenge_token, token_hash) = generate_action_token();
+    let expires_at =
+        chrono::Utc::now().timestamp_millis() + data.env.two_factor_challenge_minutes * 60_000;
+    auth_tokens_repository::create_action_token(
+        &data.db,
+        user.uuid,
+        PURPOSE_TWO_FACTOR_LOGIN,
+        &token_hash,
+        None,
+        expires_at,
+    )
+    .await
+    .map_err(db_error)?;
+    Ok(Some(TwoFactorChallengeResponse {
+        two_factor_required: true,
+        setup_required: !enabled,
+        challenge_token,
+        expires_at,
+    }))
+}
+
+/// ID: Kunci throttle kode 2FA per pengguna, terpisah dari kuncian login password.
+/// EN: Per-user 2FA code throttle key, separate from the password login lockout.
+pub(crate) fn two_factor_throttle_key(user_uuid: Uuid) -> String {
+    format!("2fa:{}", user_uuid)
+}
+
+// Role profil pengguna termasuk TWO_FACTOR_REQUIRED_ROLES
+async fn two_factor_required(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+) -> Result<bool, (StatusCode, Json<serde_json::Value>)> {
+    if data.env.two_factor_required_roles.is_empty() {
+        return Ok(false);
+    }
+    let role = auth_tokens_repository::find_profile_scope(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?
+        .and_then(|(_, role)| role);
+    Ok(role.is_some_and(|role| data.env.two_factor_required_roles.contains(&role)))
+}
+
+/// ID: Login pengguna memakai 2FA (sudah aktif atau diwajibkan role-nya), sehingga PIN saja
+///     tidak cukup untuk membuka sesi.
+/// EN: The user's sign-in goes through 2FA (enabled, or required by their role), so a PIN alone
+///     must not open a session.
+pub(crate) async fn two_factor_in_use(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+) -> Result<bool, (StatusCode, Json<serde_json::Value>)> {
+    let enabled = two_factor_repository::find_totp_secret(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?
+        .as_ref()
+        .is_some_and(TotpSecret::is_confirmed);
+    Ok(enabled || two_factor_required(data, user_uuid).await?)
+}
+
+async fn challenge_user(
+    data: &Arc<AppState>,
+    challenge_token: &str,
+) -> Result<User, (StatusCode, Json<serde_json::Value>)> {
+    let invalid = || error_response(StatusCode::UNAUTHORIZED, INVALID_CHALLENGE_MESSAGE);
+    let challenge = auth_tokens_repository::find_action_token(
+        &data.db,
+        PURPOSE_TWO_FACTOR_LOGIN,
+        &hash_action_token(challenge_token),
+    )
+    .await
+    .map_err(db_error)?
+    .ok_or_else(invalid)?;
+    auth_repository::find_user_by_uuid(&data.db, challenge.user_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(invalid)
+}
+
+async fn find_enabled_secret(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+) -> Result<TotpSecret, (StatusCode, Json<serde_json::Value>)> {
+    two_factor_repository::find_totp_secret(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?
+        .filter(TotpSecret::is_confirmed)
+        .ok_or_else(|| {
+            error_response(
+                StatusCode::BAD_REQUEST,
+                "Two-factor authentication is not enabled",
+            )
+        })
+}
+
+async fn start_enrolment(
+    data: &Arc<AppState>,
+    user: &User,
+) -> Result<TwoFactorSetupResponse, (StatusCode, Json<serde_json::Value>)> {
+    let secret = totp::generate_secret();
+    let stored = two_factor_repository::upsert_pending_secret(&data.db, user.uuid, &secret)
+        .await
+        .map_err(db_error)?;
+    if !stored {
+        return Err(already_enabled());
+    }
+    let account = user.email.as_deref().unwrap_or(&user.username);
+    Ok(TwoFactorSetupResponse {
+        otpauth_uri: totp::provisioning_uri(&data.env.two_factor_issuer, account, &secret),
+        secret,
+    })
+}
+
+// ID: Dipanggil setelah kode pertama terverifikasi (confirmed_at sudah diisi mark_step_used).
+//     Sesi lain (termasuk sesi PIN POS) dicabut karena dibuat tanpa faktor kedua.
+// EN: Called once the first code is verified (confirmed_at already set by mark_step_used).
+//     Other sessions, POS PIN sessions included, are revoked since they were created without
+//     a second factor.
+async fn enable_two_factor(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    keep_family: Option<Uuid>,
+    request_headers: &HeaderMap,
+) -> Result<Vec<String>, (StatusCode, Json<serde_json::Value>)> {
+    let recovery_codes = issue_recovery_codes(data, user_uuid).await?;
+    let revoked = revoke_all(
+        data,
+        user_uuid,
+        keep_family,
+        REVOKED_REASON_TWO_FACTOR_ENABLED,
+    )
+    .await?;
+    record_two_factor_event(
+        data,
+        Some(user_uuid),
+        EVENT_TWO_FACTOR_ENABLED,
+        request_headers,
+        json!({ "revoked_sessions": revoked }),
+    )
+    .await;
+    Ok(recovery_codes)
+}
+
+async fn issue_recovery_codes(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+) -> Result<Vec<String>, (StatusCode, Json<serde_json::Value>)> {
+    let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+    let code_hashes = recovery_codes
+        .iter()
+        .map(|code| hash_password(&totp::normalize_recovery_code(code)))
+        .collect::<Result<Vec<_>, _>>()?;
+    two_factor_repository::replace_recovery_codes(&data.db, user_uuid, &code_hashes)
+        .await
+        .map_err(db_error)?;
+    Ok(recovery_codes)
+}
+
+// ID: Verifikasi kode TOTP atau kode pemulihan dengan throttle yang sama seperti login.
+//     Kode pemulihan hanya berlaku setelah 2FA aktif.
+// EN: Verify a TOTP code or a recovery code, throttled the same way as logins.
+//     Recovery codes only work once 2FA is active.
+async fn check_second_factor(
+    data: &Arc<AppState>,
+    secret: &TotpSecret,
+    code: Option<&str>,
+    recovery_code: Option<&str>,
+    request_headers: &HeaderMap,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let user_uuid = secret.user_uuid;
+    let (ip_address, user_agent) = client_metadata(request_headers);
+    let throttle = LoginThrottle::new(
+        &data.redis_client,
+        &data.session_store,
+        LoginThrottlePolicy::from_config(&data.env),
+    );
+    let throttle_key = two_factor_throttle_key(user_uuid);
+    if let Some(retry_after) = throttle
+        .locked_for(&throttle_key, ip_address.as_deref())
+        .await
+    {
+        return Err(two_factor_locked_response(retry_after));
+    }
+
+    let verified = match (code, recovery_code) {
+        (Some(code), _) => {
+            let now = chrono::Utc::now().timestamp().max(0) as u64;
+            let last_used_step = secret.last_used_step.map(|step| step.max(0) as u64);
+            match totp::verify(&secret.secret, code, now, last_used_step) {
+                Some(step) => two_factor_repository::mark_step_used(
+                    &data.db,
+                    user_uuid,
+                    step as i64,
+                    !secret.is_confirmed(),
+                )
+                .await
+                .map_err(db_error)?,
+                None => false,
+            }
+        }
+        (None, Some(recovery_code)) if secret.is_confirmed() => {
+            use_recovery_code(data, user_uuid, recovery_code, request_headers).await?
+        }
+        _ => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "Kode 2FA wajib diisi",
+            ))
+        }
+    };
+
+    if !verified {
+        let failed = throttle
+            .record_failure(&throttle_key, ip_address.as_deref())
+            .await;
+        if let Err(e) = auth_tokens_repository::insert_security_event(
+            &data.db,
+            Some(user_uuid),
+            None,
+            EVENT_TWO_FACTOR_FAILED,
+            ip_address.as_deref(),
+            user_agent.as_deref(),
+            json!({
+                "user_failures": failed.username_failures,
+                "ip_failures": failed.ip_failures,
+            }),
+        )
+        .await
+        {
+            tracing::error!("Failed to record security event: {:?}", e);
+        }
+        tokio::time::sleep(failed.delay).await;
+        if failed.username_locked || failed.ip_locked {
+            return Err(two_factor_locked_response(data.env.login_lockout_seconds));
+        }
+        return Err(error_response(StatusCode::BAD_REQUEST, "Kode 2FA salah"));
+    }
+    throttle.record_success(&throttle_key).await;
+    Ok(())
+}
+
+async fn use_recovery_code(
+    data: &Arc<AppState>,
+    user_uuid: Uuid,
+    recovery_code: &str,
+    request_headers: &HeaderMap,
+) -> Result<bool, (StatusCode, Json<serde_json::Value>)> {
+    let recovery_code = totp::normalize_recovery_code(recovery_code);
+    let unused = two_factor_repository::list_unused_recovery_codes(&data.db, user_uuid)
+        .await
+        .map_err(db_error)?;
+    let Some(matched) = unused
+        .iter()
+        .find(|candidate| verify_password(&candidate.code_hash, &recovery_code))
+    else {
+        return Ok(false);
+    };
+    if !two_factor_repository::mark_recovery_code_used(&data.db, matched.uuid)
+        .await
+        .map_err(db_error)?
+    {
+        return Ok(false);
+    }
+    record_two_factor_event(
+        data,
+        Some(user_uuid),
+        EVENT_RECOVERY_CODE_USED,
+        request_headers,
+        json!({ "recovery_codes_remaining": unused.len() - 1 }),
+    )
+    .await;
+    Ok(true)
+}
+
+async fn record_two_factor_event(
+    data: &Arc<AppState>,
+    user_uuid: Option<Uuid>,
+    event_type: &str,
+    request_headers: &HeaderMap,
+    details: serde_json::Value,
+) {
+    let (ip_address, user_agent) = client_metadata(request_headers);
+    if let Err(e) = auth_tokens_repository::insert_security_event(
+        &data.db,
+        user_uuid,
+        None,
+        event_type,
+        ip_address.as_deref(),
+        user_agent.as_deref(),
+        details,
+    )
+    .await
+    {
+        tracing::error!("Failed to record security event: {:?}", e);
+    }
+}
+
+fn setup_response(setup: TwoFactorSetupResponse) -> Json<ApiResponse<TwoFactorSetupResponse>> {
+    Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Scan the QR code, then confirm with a code from the app".to_string(),
+        data: setup,
+        errors: json!({}),
+    })
+}
+
+fn recovery_codes_response(
+    message: &str,
+    recovery_codes: Vec<String>,
+) -> Json<ApiResponse<RecoveryCodesResponse>> {
+    Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: message.to_string(),
+        data: RecoveryCodesResponse { recovery_codes },
+        errors: json!({}),
+    })
+}
+
+fn setup_not_started() -> (StatusCode, Json<serde_json::Value>) {
+    error_response(
+        StatusCode::BAD_REQUEST,
+        "Two-factor setup has not been started",
+    )
+}
+
+fn already_enabled() -> (StatusCode, Json<serde_json::Value>) {
+    error_response(
+        StatusCode::CONFLICT,
+        "Two-factor authentication is already enabled",
+    )
+}
+
+fn two_factor_locked_response(retry_after_seconds: u64) -> (StatusCode, Json<serde_json::Value>) {
+    let (status, Json(mut body)) = error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        "Terlalu banyak kode 2FA salah, coba lagi nanti",
+    );
+    body["data"] = json!({ "retry_after_seconds": retry_after_seconds });
+    (status, Json(body))
+}
@@ -1,335 +1,360 @@
-use std::path::Path;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::{
+    body::Bytes,
     extract::{Multipart, State},
     http::{HeaderMap, StatusCode},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use serde_json::{json, Value};
-use tokio::fs;
 use uuid::Uuid;
 
-use crate::{dto::api::ApiResponse, AppState};
+use crate::{
+    dto::api::ApiResponse,
+    middleware::jwt::JWTAuthMiddleware,
+    repository::{products as products_repository, stores as stores_repository},
+    services::blob_store::{content_type_for_key, BlobStore},
+    services::image_pipeline::{self, ImagePipelineError, ImageVariants},
+    AppState,
+};
 
-pub async fn upload_product_image_handler(
-    State(_data): State<Arc<AppState>>,
-    mut multipart: Multipart,
-) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
-    println!("🔥 Upload handler called!");
-    tracing::info!("Starting image upload process");
-    // Create uploads directory if it doesn't exist
-    let upload_dir = "uploads/products";
-    if let Err(_) = fs::create_dir_all(upload_dir).await {
-        let error_response = json!({
-            "status": "error",
-            "message": "Failed to create upload directory"
-        });
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+fn fail(status: StatusCode, message: String) -> (StatusCode, Json<Value>) {
+    let status_text = if status.is_server_error() {
+        "error"
+    } else {
+        "fail"
+    };
+    (
+        status,
+        Json(json!({
+            "status": status_text,
+            "message": message
+        })),
+    )
+}
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    fail(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("Database error: {}", e),
+    )
+}
+
+fn validate_multipart_content_type(headers: &HeaderMap) -> Result<(), (StatusCode, Json<Value>)> {
+    let Some(content_type) = headers.get("content-type") else {
+        return Err(fail(
+            StatusCode::BAD_REQUEST,
+            "Missing Content-Type header".to_string(),
+        ));
+    };
+    let content_type_str = content_type.to_str().unwrap_or("");
+    tracing::info!("Content-Type: {}", content_type_str);
+
+    if !content_type_str.starts_with("multipart/form-data") {
+        return Err(fail(
+            StatusCode::BAD_REQUEST,
+            "Content-Type must be multipart/form-data".to_string(),
+        ));
     }
+    // Check if boundary is present
+    if !content_type_str.contains("boundary=") {
+        return Err(fail(
+            StatusCode::BAD_REQUEST,
+            "Missing boundary in Content-Type header".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+struct ImageUpload {
+    original_filename: String,
+    data: Bytes,
+}
+
+struct StoredImage {
+    image_url: String,
+    filename: String,
+    file_size: usize,
+    variants: ImageVariants,
+}
+
+// ID: Baca seluruh field multipart dulu: urutan field (mis. product_uuid setelah file) tidak dijamin
+// EN: Read all multipart fields first: field order (e.g. product_uuid after the file) is not guaranteed
+async fn read_upload_fields(
+    mut multipart: Multipart,
+) -> Result<(ImageUpload, HashMap<String, String>), (StatusCode, Json<Value>)> {
+    let mut upload: Option<ImageUpload> = None;
+    let mut fields = HashMap::new();
 
     while let Some(field) = multipart.next_field().await.map_err(|e| {
-        println!("🔥 Error reading multipart field: {}", e);
         tracing::error!("Multipart field error: {}", e);
-        let error_response = json!({
-            "status": "error",
-            "message": format!("Invalid multipart data: {}", e)
-        });
-        (StatusCode::BAD_REQUEST, Json(error_response))
+        fail(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid multipart data: {}", e),
+        )
     })? {
         let name = field.name().unwrap_or("").to_string();
         tracing::debug!("Processing field: {}", name);
 
         if name == "image" || name == "file" {
-            let filename = field.file_name().unwrap_or("").to_string();
-
-            println!("🔥 Processing file: {}", filename);
-
-            if filename.is_empty() {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "No file provided"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            let original_filename = field.file_name().unwrap_or("").to_string();
+            if original_filename.is_empty() {
+                return Err(fail(
+                    StatusCode::BAD_REQUEST,
+                    "No file provided".to_string(),
+                ));
             }
 
-            // Validate file extension
-            let allowed_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
-            let file_extension = Path::new(&filename)
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-
-            if !allowed_extensions.contains(&file_extension.as_str()) {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "Invalid file type. Only JPG, JPEG, PNG, GIF, and WebP are allowed"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            // Treat parsing errors on file read as BAD_REQUEST to guide client
+            let data = field.bytes().await.map_err(|e| {
+                tracing::error!("Error reading file data: {}", e);
+                fail(
+                    StatusCode::BAD_REQUEST,
+                    format!("Error parsing multipart/form-data request: {}", e),
+                )
+            })?;
+
+            if data.len() > image_pipeline::MAX_UPLOAD_BYTES {
+                return Err(fail(
+                    StatusCode::BAD_REQUEST,
+                    "File size too large. Maximum size is 5MB".to_string(),
+                ));
             }
 
-            // Generate unique filename
-            let unique_filename = format!("{}.{}", Uuid::new_v4(), file_extension);
-            let file_path = format!("{}/{}", upload_dir, unique_filename);
-
-            // Get file data
-            let data = match field.bytes().await {
-                Ok(data) => {
-                    println!("🔥 File data read successfully, size: {} bytes", data.len());
-                    data
-                }
-                Err(e) => {
-                    println!("🔥 Error reading file data: {}", e);
-                    let error_response = json!({
-                        "status": "error",
-                        "message": format!("Failed to read file data: {}", e)
-                    });
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-                }
-            };
+            upload = Some(ImageUpload {
+                original_filename,
+                data,
+            });
+        } else if !name.is_empty() {
+            let value = field.text().await.unwrap_or_default();
+            fields.insert(name, value.trim().to_string());
+        }
+    }
 
-            // Validate file size (max 5MB)
-            if data.len() > 5 * 1024 * 1024 {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "File size too large. Maximum size is 5MB"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
+    let upload = upload.ok_or_else(|| {
+        fail(
+            StatusCode::BAD_REQUEST,
+            "No image field found in the request (expected 'image' or 'file')".to_string(),
+        )
+    })?;
 
-            // Save file
-            match fs::write(&file_path, &data).await {
-                Ok(_) => {
-                    // Return the URL path
-                    let image_url = format!("/uploads/products/{}", unique_filename);
-
-                    let json_response = ApiResponse {
-                        code: 200,
-                        status: "success".to_string(),
-                        message: "Image uploaded successfully".to_string(),
-                        data: json!({
-                            "image_url": image_url,
-                            "filename": unique_filename,
-                            "original_filename": filename,
-                            "file_size": data.len()
-                        }),
-                        errors: json!(null),
-                    };
-
-                    return Ok((StatusCode::OK, Json(json_response)));
-                }
-                Err(_) => {
-                    let error_response = json!({
-                        "status": "error",
-                        "message": "Failed to save file"
-                    });
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-                }
+    Ok((upload, fields))
+}
+
+// ID: Decode + verifikasi isi, buang EXIF, buat original/WebP/thumbnail, lalu simpan ke blob store
+// EN: Decode + verify content, strip EXIF, build original/WebP/thumbnails, then store in the blob store
+async fn process_and_store_image(
+    blob_store: &dyn BlobStore,
+    folder: &str,
+    upload: &ImageUpload,
+) -> Result<StoredImage, (StatusCode, Json<Value>)> {
+    let data = upload.data.clone();
+    let processed = tokio::task::spawn_blocking(move || image_pipeline::process_image(&data))
+        .await
+        .map_err(|e| {
+            fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Image processing failed: {}", e),
+            )
+        })?
+        .map_err(|e| match e {
+            ImagePipelineError::Encode(_) => fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            _ => fail(StatusCode::BAD_REQUEST, e.to_string()),
+        })?;
+
+    let stem = Uuid::new_v4().to_string();
+    let mut written = Vec::with_capacity(processed.files.len());
+    for file in &processed.files {
+        let key = format!("{}/{}", folder, file.file_name(&stem));
+        let content_type = content_type_for_key(&key);
+        if let Err(e) = blob_store
+            .put(&key, file.bytes.clone(), Some(content_type))
+            .await
+        {
+            tracing::error!("Failed to save file {}: {}", key, e);
+            for key in written {
+                let _ = blob_store.delete(&key).await;
             }
+            return Err(fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to save file: {}", e),
+            ));
         }
+        written.push(key);
     }
 
-    let error_response = json!({
-        "status": "fail",
-        "message": "No image field found in the request (expected 'image' atau 'file')"
-    });
-    Err((StatusCode::BAD_REQUEST, Json(error_response)))
+    let url_prefix = blob_store.public_url(folder);
+    let variants = processed.variants(&url_prefix, &stem).ok_or_else(|| {
+        fail(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to build image variants".to_string(),
+        )
+    })?;
+    tracing::info!(
+        "Image processed from {} into {} files under {} ({})",
+        processed.source_format,
+        processed.files.len(),
+        folder,
+        blob_store.backend()
+    );
+
+    Ok(StoredImage {
+        filename: variants
+            .original_url
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        image_url: variants.original_url.clone(),
+        file_size: processed
+            .files
+            .iter()
+            .find(|f| f.variant == "original" && f.extension != "webp")
+            .map(|f| f.bytes.len())
+            .unwrap_or_default(),
+        variants,
+    })
 }
 
-// Alternative handler with explicit content-type validation
-pub async fn upload_product_image_handler_v2(
-    State(_data): State<Arc<AppState>>,
-    headers: HeaderMap,
-    mut multipart: Multipart,
-) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
-    println!("🔥 Upload handler v2 called!");
-    tracing::info!("Starting image upload process v2");
-
-    // Validate content-type header
-    if let Some(content_type) = headers.get("content-type") {
-        let content_type_str = content_type.to_str().unwrap_or("");
-        tracing::info!("Content-Type: {}", content_type_str);
+fn variants_json(variants: &ImageVariants) -> Value {
+    serde_json::to_value(variants).unwrap_or(Value::Null)
+}
 
-        if !content_type_str.starts_with("multipart/form-data") {
-            let error_response = json!({
-                "status": "error",
-                "message": "Content-Type must be multipart/form-data"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+async fn remove_stored_image(blob_store: &dyn BlobStore, image_url: &str) {
+    let Some(variants) = image_pipeline::variants_for_url(image_url) else {
+        return;
+    };
+    let mut urls = vec![variants.webp_url];
+    for thumbnail in variants.thumbnails {
+        urls.push(thumbnail.url);
+        urls.push(thumbnail.webp_url);
+    }
+    for url in urls {
+        if let Some(key) = blob_store.key_from_url(&url) {
+            let _ = blob_store.delete(&key).await;
         }
+    }
+}
 
-        // Check if boundary is present
-        if !content_type_str.contains("boundary=") {
-            let error_response = json!({
-                "status": "error",
-                "message": "Missing boundary in Content-Type header"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+async fn handle_product_image_upload(
+    data: &AppState,
+    multipart: Multipart,
+) -> Result<(StatusCode, Json<ApiResponse<Value>>), (StatusCode, Json<Value>)> {
+    let (upload, fields) = read_upload_fields(multipart).await?;
+
+    // Opsional: product_uuid untuk langsung menyimpan gambar + varian ke produk
+    let product_uuid = match fields.get("product_uuid").filter(|v| !v.is_empty()) {
+        Some(raw) => {
+            let id = Uuid::parse_str(raw).map_err(|_| {
+                fail(
+                    StatusCode::BAD_REQUEST,
+                    "product_uuid must be a valid UUID".to_string(),
+                )
+            })?;
+            products_repository::get_product_by_uuid(&data.db, id)
+                .await
+                .map_err(internal_error)?
+                .ok_or_else(|| {
+                    fail(
+                        StatusCode::NOT_FOUND,
+                        format!("Product with ID: {} not found", id),
+                    )
+                })?;
+            Some(id)
         }
-    } else {
-        let error_response = json!({
-            "status": "error",
-            "message": "Missing Content-Type header"
-        });
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-    }
+        None => None,
+    };
 
-    // Create uploads directory if it doesn't exist
-    let upload_dir = "uploads/products";
-    if let Err(_) = fs::create_dir_all(upload_dir).await {
-        let error_response = json!({
-            "status": "error",
-            "message": "Failed to create upload directory"
-        });
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    let stored = process_and_store_image(data.blob_store.as_ref(), "products", &upload).await?;
+
+    if let Some(id) = product_uuid {
+        let current_time = chrono::Utc::now().timestamp_millis();
+        products_repository::update_product_image(
+            &data.db,
+            id,
+            &stored.image_url,
+            &variants_json(&stored.variants),
+            current_time,
+        )
+        .await
+        .map_err(internal_error)?;
     }
 
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        println!("🔥 Error reading multipart field: {}", e);
-        tracing::error!("Multipart field error: {}", e);
-        let error_response = json!({
-            "status": "error",
-            "message": format!("Invalid multipart data: {}", e)
-        });
-        (StatusCode::BAD_REQUEST, Json(error_response))
-    })? {
-        let name = field.name().unwrap_or("").to_string();
-        tracing::debug!("Processing field: {}", name);
-
-        if name == "image" || name == "file" {
-            let filename = field.file_name().unwrap_or("").to_string();
-
-            println!("🔥 Processing file: {}", filename);
-
-            if filename.is_empty() {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "No file provided"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
-
-            // Validate file extension
-            let allowed_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
-            let file_extension = Path::new(&filename)
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-
-            if !allowed_extensions.contains(&file_extension.as_str()) {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "Invalid file type. Only JPG, JPEG, PNG, GIF, and WebP are allowed"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
-
-            // Generate unique filename
-            let unique_filename = format!("{}.{}", Uuid::new_v4(), file_extension);
-            let file_path = format!("{}/{}", upload_dir, unique_filename);
-
-            // Get file data
-            let data = match field.bytes().await {
-                Ok(data) => {
-                    println!("🔥 File data read successfully, size: {} bytes", data.len());
-                    tracing::info!("File data read successfully, size: {} bytes", data.len());
-                    data
-                }
-                Err(e) => {
-                    println!("🔥 Error reading file data: {}", e);
-                    tracing::error!("Error reading file data: {}", e);
-                    let error_response = json!({
-                        "status": "error",
-                        "message": format!("Failed to read file data: {}", e)
-                    });
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-                }
-            };
+    let json_response = ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Image uploaded successfully".to_string(),
+        data: json!({
+            "image_url": stored.image_url,
+            "filename": stored.filename,
+            "original_filename": upload.original_filename,
+            "file_size": stored.file_size,
+            "variants": stored.variants,
+            "product_uuid": product_uuid
+        }),
+        errors: json!(null),
+    };
 
-            // Validate file size (max 5MB)
-            if data.len() > 5 * 1024 * 1024 {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "File size too large. Maximum size is 5MB"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
+    Ok((StatusCode::OK, Json(json_response)))
+}
 
-            // Save file
-            match fs::write(&file_path, &data).await {
-                Ok(_) => {
-                    // Return the URL path
-                    let image_url = format!("/uploads/products/{}", unique_filename);
-
-                    let json_response = ApiResponse {
-                        code: 200,
-                        status: "success".to_string(),
-                        message: "Image uploaded successfully".to_string(),
-                        data: json!({
-                            "image_url": image_url,
-                            "filename": unique_filename,
-                            "original_filename": filename,
-                            "file_size": data.len()
-                        }),
-                        errors: json!(null),
-                    };
-
-                    return Ok((StatusCode::OK, Json(json_response)));
-                }
-                Err(e) => {
-                    tracing::error!("Failed to save file: {}", e);
-                    let error_response = json!({
-                        "status": "error",
-                        "message": format!("Failed to save file: {}", e)
-                    });
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-                }
-            }
-        }
-    }
+pub async fn upload_product_image_handler(
+    State(data): State<Arc<AppState>>,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    tracing::info!("Starting image upload process");
+    handle_product_image_upload(&data, multipart).await
+}
 
-    let error_response = json!({
-        "status": "fail",
-        "message": "No image field found in the request (expected 'image' atau 'file')"
-    });
-    Err((StatusCode::BAD_REQUEST, Json(error_response)))
+// Alternative handler with explicit content-type validation
+pub async fn upload_product_image_handler_v2(
+    State(data): State<Arc<AppState>>,
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    tracing::info!("Starting image upload process v2");
+    validate_multipart_content_type(&headers)?;
+    handle_product_image_upload(&data, multipart).await
 }
 
 pub async fn delete_product_image_handler(
-    State(_data): State<Arc<AppState>>,
+    State(data): State<Arc<AppState>>,
     Json(body): Json<Value>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
     let image_url = match body.get("image_url").and_then(|v| v.as_str()) {
         Some(url) => url,
         None => {
-            let error_response = json!({
-                "status": "fail",
-                "message": "image_url is required"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+            return Err(fail(
+                StatusCode::BAD_REQUEST,
+                "image_url is required".to_string(),
+            ));
         }
     };
 
-    // Extract filename from URL
-    let filename = match image_url.strip_prefix("/uploads/products/") {
-        Some(name) => name,
-        None => {
-            let error_response = json!({
-                "status": "fail",
-                "message": "Invalid image URL format"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    // Extract blob key from URL
+    let key = match data.blob_store.key_from_url(image_url) {
+        Some(key)
+            if key
+                .strip_prefix("products/")
+                .is_some_and(|name| !name.contains('/')) =>
+        {
+            key
+        }
+        _ => {
+            return Err(fail(
+                StatusCode::BAD_REQUEST,
+                "Invalid image URL format".to_string(),
+            ));
         }
     };
 
-    let file_path = format!("uploads/products/{}", filename);
+    // Check if file exists and delete it, together with its WebP/thumbnail variants
+    match data.blob_store.exists(&key).await {
+        Ok(true) if data.blob_store.delete(&key).await.is_ok() => {
+            remove_stored_image(data.blob_store.as_ref(), image_url).await;
 
-    // Check if file exists and delete it
-    match fs::remove_file(&file_path).await {
-        Ok(_) => {
             let json_response = ApiResponse {
                 code: 200,
                 status: "success".to_string(),
@@ -342,247 +367,181 @@ pub async fn delete_product_image_handler(
 
             Ok((StatusCode::OK, Json(json_response)))
         }
-        Err(_) => {
-            let error_response = json!({
-                "status": "error",
-                "message": "Failed to delete image file or file not found"
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)))
-        }
+        _ => Err(fail(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to delete image file or file not found".to_string(),
+        )),
     }
 }
 
-// Upload user profile image
-pub async fn upload_user_image_handler(
-    State(_data): State<Arc<AppState>>,
-    headers: HeaderMap,
-    mut multipart: Multipart,
-) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
-    // Validate content-type header upfront
-    if let Some(content_type) = headers.get("content-type") {
-        let content_type_str = content_type.to_str().unwrap_or("");
-        if !content_type_str.starts_with("multipart/form-data") {
-            let error_response = json!({
-                "status": "error",
-                "message": "Content-Type must be multipart/form-data"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-        }
-        if !content_type_str.contains("boundary=") {
-            let error_response = json!({
-                "status": "error",
-                "message": "Missing boundary in Content-Type header"
-            });
-            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+#[derive(Clone, Copy)]
+enum ProfileImageTarget {
+    PhotoProfile,
+    BackgroundProfile,
+}
+
+impl ProfileImageTarget {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "photo_profile" | "profile_photo" => Some(Self::PhotoProfile),
+            "background_profile" | "background" => Some(Self::BackgroundProfile),
+            _ => None,
         }
-    } else {
-        let error_response = json!({
-            "status": "error",
-            "message": "Missing Content-Type header"
-        });
-        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
     }
 
-    let upload_dir = "uploads/users";
-    if let Err(_) = fs::create_dir_all(upload_dir).await {
-        let error_response = json!({
-            "status": "error",
-            "message": "Failed to create upload directory"
-        });
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    fn column(&self) -> &'static str {
+        match self {
+            Self::PhotoProfile => "photo_profile",
+            Self::BackgroundProfile => "background_profile",
+        }
     }
+}
 
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        let error_response = json!({
-            "status": "error",
-            "message": format!("Invalid multipart data: {}", e)
-        });
-        (StatusCode::BAD_REQUEST, Json(error_response))
-    })? {
-        let name = field.name().unwrap_or("").to_string();
-        if name == "image" || name == "file" {
-            let filename = field.file_name().unwrap_or("").to_string();
-            if filename.is_empty() {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "No file provided"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
+async fn handle_user_image_upload(
+    data: &AppState,
+    user_uuid: Uuid,
+    headers: &HeaderMap,
+    multipart: Multipart,
+    target: Option<ProfileImageTarget>,
+) -> Result<(StatusCode, Json<ApiResponse<Value>>), (StatusCode, Json<Value>)> {
+    // Validate content-type header upfront
+    validate_multipart_content_type(headers)?;
+
+    let (upload, fields) = read_upload_fields(multipart).await?;
+
+    // Endpoint generik /user: kolom profil opsional lewat field 'target'
+    let target = match target {
+        Some(target) => Some(target),
+        None => match fields.get("target").filter(|v| !v.is_empty()) {
+            Some(raw) => Some(ProfileImageTarget::parse(raw).ok_or_else(|| {
+                fail(
+                    StatusCode::BAD_REQUEST,
+                    "target must be 'photo_profile' or 'background_profile'".to_string(),
+                )
+            })?),
+            None => None,
+        },
+    };
 
-            let allowed_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
-            let file_extension = Path::new(&filename)
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-
-            if !allowed_extensions.contains(&file_extension.as_str()) {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "Invalid file type. Only JPG, JPEG, PNG, GIF, and WebP are allowed"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
+    let stored = process_and_store_image(data.blob_store.as_ref(), "users", &upload).await?;
+
+    let mut profile_updated = false;
+    if let Some(target) = target {
+        let column = target.column();
+        let query = format!(
+            "UPDATE profiles SET {column} = $1, {column}_variants = $2, updated_at = $3
+             WHERE user_uuid = $4 AND COALESCE(deleted_at, 0) = 0"
+        );
+        let result = sqlx::query(&query)
+            .bind(&stored.image_url)
+            .bind(variants_json(&stored.variants))
+            .bind(chrono::Utc::now().timestamp_millis())
+            .bind(user_uuid)
+            .execute(&data.db)
+            .await
+            .map_err(internal_error)?;
+        profile_updated = result.rows_affected() > 0;
+    }
 
-            let unique_filename = format!("{}.{}", Uuid::new_v4(), file_extension);
-            let file_path = format!("{}/{}", upload_dir, unique_filename);
-
-            let data = match field.bytes().await {
-                Ok(data) => data,
-                Err(e) => {
-                    // Treat parsing errors on file read as BAD_REQUEST to guide client
-                    let error_response = json!({
-                        "status": "error",
-                        "message": format!("Error parsing multipart/form-data request: {}", e)
-                    });
-                    return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-                }
-            };
+    let json_response = ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Image uploaded successfully".to_string(),
+        data: json!({
+            "image_url": stored.image_url,
+            "filename": stored.filename,
+            "original_filename": upload.original_filename,
+            "file_size": stored.file_size,
+            "variants": stored.variants,
+            "target": target.map(|t| t.column()),
+            "profile_updated": profile_updated
+        }),
+        errors: json!(null),
+    };
+    Ok((StatusCode::OK, Json(json_response)))
+}
 
-            if data.len() > 5 * 1024 * 1024 {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "File size too large. Maximum size is 5MB"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
+// Upload user profile image
+pub async fn upload_user_image_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    handle_user_image_upload(&data, jwt_auth.user.uuid, &headers, multipart, None).await
+}
 
-            match fs::write(&file_path, &data).await {
-                Ok(_) => {
-                    let image_url = format!("/uploads/users/{}", unique_filename);
-                    let json_response = ApiResponse {
-                        code: 200,
-                        status: "success".to_string(),
-                        message: "Image uploaded successfully".to_string(),
-                        data: json!({
-                            "image_url": image_url,
-                            "filename": unique_filename,
-                            "original_filename": filename,
-                            "file_size": data.len()
-                        }),
-                        errors: json!(null),
-                    };
-                    return Ok((StatusCode::OK, Json(json_response)));
-                }
-                Err(e) => {
-                    let error_response = json!({
-                        "status": "error",
-                        "message": format!("Failed to save file: {}", e)
-                    });
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-                }
-            }
-        }
-    }
+// Upload user profile photo and store it (with variants) on the profile
+pub async fn upload_user_profile_photo_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    handle_user_image_upload(
+        &data,
+        jwt_auth.user.uuid,
+        &headers,
+        multipart,
+        Some(ProfileImageTarget::PhotoProfile),
+    )
+    .await
+}
 
-    let error_response = json!({
-        "status": "fail",
-        "message": "No image field found in the request (expected 'image' or 'file')"
-    });
-    Err((StatusCode::BAD_REQUEST, Json(error_response)))
+// Upload user background image and store it (with variants) on the profile
+pub async fn upload_user_background_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    handle_user_image_upload(
+        &data,
+        jwt_auth.user.uuid,
+        &headers,
+        multipart,
+        Some(ProfileImageTarget::BackgroundProfile),
+    )
+    .await
 }
 
 // Upload store brand image
 pub async fn upload_store_brand_image_handler(
-    State(_data): State<Arc<AppState>>,
-    mut multipart: Multipart,
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    multipart: Multipart,
 ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
-    let upload_dir = "uploads/stores";
-    if let Err(_) = fs::create_dir_all(upload_dir).await {
-        let error_response = json!({
-            "status": "error",
-            "message": "Failed to create upload directory"
-        });
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
+    let (upload, _) = read_upload_fields(multipart).await?;
+    let stored = process_and_store_image(data.blob_store.as_ref(), "stores", &upload).await?;
+
+    // Toko milik user langsung diperbarui; jika belum punya toko, brand_url dipakai saat create store
+    let store = stores_repository::get_store_by_user_uuid(&data.db, jwt_auth.user.uuid)
+        .await
+        .map_err(internal_error)?;
+    if let Some(store) = &store {
+        stores_repository::update_store_brand_image(
+            &data.db,
+            store.uuid,
+            &stored.image_url,
+            &variants_json(&stored.variants),
+        )
+        .await
+        .map_err(internal_error)?;
     }
 
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        let error_response = json!({
-            "status": "error",
-            "message": format!("Invalid multipart data: {}", e)
-        });
-        (StatusCode::BAD_REQUEST, Json(error_response))
-    })? {
-        let name = field.name().unwrap_or("").to_string();
-        if name == "image" || name == "file" {
-            let filename = field.file_name().unwrap_or("").to_string();
-            if filename.is_empty() {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "No file provided"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
-
-            let allowed_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
-            let file_extension = Path::new(&filename)
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-
-            if !allowed_extensions.contains(&file_extension.as_str()) {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "Invalid file type. Only JPG, JPEG, PNG, GIF, and WebP are allowed"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
-
-            let unique_filename = format!("{}.{}", Uuid::new_v4(), file_extension);
-            let file_path = format!("{}/{}", upload_dir, unique_filename);
-
-            let data = match field.bytes().await {
-                Ok(data) => data,
-                Err(e) => {
-                    let error_response = json!({
-                        "status": "error",
-                        "message": format!("Failed to read file data: {}", e)
-                    });
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-                }
-            };
-
-            if data.len() > 5 * 1024 * 1024 {
-                let error_response = json!({
-                    "status": "fail",
-                    "message": "File size too large. Maximum size is 5MB"
-                });
-                return Err((StatusCode::BAD_REQUEST, Json(error_response)));
-            }
-
-            match fs::write(&file_path, &data).await {
-                Ok(_) => {
-                    let brand_url = format!("/uploads/stores/{}", unique_filename);
-                    let json_response = ApiResponse {
-                        code: 200,
-                        status: "success".to_string(),
-                        message: "Image uploaded successfully".to_string(),
-                        data: json!({
-                            "brand_url": brand_url,
-                            "filename": unique_filename,
-                            "original_filename": filename,
-                            "file_size": data.len()
-                        }),
-                        errors: json!(null),
-                    };
-                    return Ok((StatusCode::OK, Json(json_response)));
-                }
-                Err(_) => {
-                    let error_response = json!({
-                        "status": "error",
-                        "message": "Failed to save file"
-                    });
-                    return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)));
-                }
-            }
-        }
-    }
-
-    let error_response = json!({
-        "status": "fail",
-        "message": "No image field found in the request (expected 'image' atau 'file')"
-    });
-    Err((StatusCode::BAD_REQUEST, Json(error_response)))
+    let json_response = ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Image uploaded successfully".to_string(),
+        data: json!({
+            "brand_url": stored.image_url,
+            "filename": stored.filename,
+            "original_filename": upload.original_filename,
+            "file_size": stored.file_size,
+            "variants": stored.variants,
+            "store_uuid": store.map(|s| s.uuid)
+        }),
+        errors: json!(null),
+    };
+    Ok((StatusCode::OK, Json(json_response)))
 }
@@ -0,0 +1,1040 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use reqwest::Client;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::api::ApiResponse;
+use crate::dto::order_refunds::{
+    CreateOrderRefundRequest, OrderRefundItemInput, RejectOrderRefundRequest, VoidOrderRequest,
+};
+use crate::handlers::pos_devices::ensure_password_session;
+use crate::middleware::jwt::JWTAuthMiddleware;
+use crate::models::order_refunds::{
+    OrderRefund, OrderRefundWithItems, REFUND_KIND_REFUND, REFUND_KIND_VOID, REFUND_STATUS_PENDING,
+    REFUND_STATUS_PROCESSING,
+};
+use crate::models::orders::OrderStatus;
+use crate::models::roles::{REFUND_APPROVER_ROLE_NUMBERS, SUPER_ADMIN_ROLE_NUMBER};
+use crate::repository::auth_tokens as auth_tokens_repository;
+use crate::repository::ingredient_stocks;
+use crate::repository::order_refunds as order_refunds_repo;
+use crate::repository::order_refunds::{
+    NewOrderRefund, NewOrderRefundItem, OrderForRefund, PendingGatewayRefund, RefundTransition,
+};
+use crate::repository::recipe_items as recipe_items_repository;
+use crate::repository::recipe_sets as recipe_sets_repository;
+use crate::services::refunds::{
+    cap_restock, check_line_qty, line_refund_amount, profit_reversed, split_across_tenders,
+    RefundError, TenderBalance,
+};
+use crate::services::xendit as xnd;
+use crate::AppState;
+
+// Batas tiap panggilan Xendit; jauh di bawah REFUND_GATEWAY_CLAIM_TTL_MS
+const XENDIT_TIMEOUT_SECS: u64 = 30;
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({
+            "status": "error",
+            "message": format!("{:?}", e)
+        })),
+    )
+}
+
+fn validation_error(e: validator::ValidationErrors) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "fail",
+            "message": "Validation error",
+            "errors": e,
+        })),
+    )
+}
+
+fn fail(status: StatusCode, message: String) -> (StatusCode, Json<Value>) {
+    (
+        status,
+        Json(json!({
+            "status": "fail",
+            "message": message
+        })),
+    )
+}
+
+fn order_not_found(id: Uuid) -> (StatusCode, Json<Value>) {
+    fail(
+        StatusCode::NOT_FOUND,
+        format!("Pesanan dengan ID: {} tidak ditemukan", id),
+    )
+}
+
+fn refund_not_found(id: Uuid) -> (StatusCode, Json<Value>) {
+    fail(
+        StatusCode::NOT_FOUND,
+        format!("Refund dengan ID: {} tidak ditemukan", id),
+    )
+}
+
+fn refund_not_pending(status: &str) -> (StatusCode, Json<Value>) {
+    fail(
+        StatusCode::CONFLICT,
+        format!(
+            "Refund berstatus {}, hanya refund PENDING yang bisa diproses",
+            status
+        ),
+    )
+}
+
+// Qty/nominal yang melebihi sisa: 400 saat diminta, 409 saat approval (pesanan sudah berubah)
+fn refund_error(e: RefundError, status: StatusCode) -> (StatusCode, Json<Value>) {
+    fail(status, e.to_string())
+}
+
+// REFUND hanya untuk pesanan PAID; VOID untuk pesanan DRAFT atau PAID
+fn ensure_order_refundable(
+    order: &OrderForRefund,
+    kind: &str,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    let allowed: &[OrderStatus] = if kind == REFUND_KIND_VOID {
+        &[OrderStatus::Draft, OrderStatus::Paid]
+    } else {
+        &[OrderStatus::Paid]
+    };
+    if allowed.iter().any(|status| status.as_str() == order.status) {
+        return Ok(());
+    }
+    Err(fail(
+        StatusCode::CONFLICT,
+        format!(
+            "Pesanan {} berstatus {} dan tidak bisa di-{}",
+            order.order_no,
+            order.status,
+            kind.to_ascii_lowercase()
+        ),
+    ))
+}
+
+// Gabungkan qty per baris pesanan dengan urutan kemunculan pertama
+fn merge_item_inputs(items: &[OrderRefundItemInput]) -> Vec<(Uuid, Decimal)> {
+    let mut merged: Vec<(Uuid, Decimal)> = Vec::new();
+    for item in items {
+        match merged
+            .iter_mut()
+            .find(|(uuid, _)| *uuid == item.order_item_uuid)
+        {
+            Some((_, qty)) => *qty += item.qty,
+            None => merged.push((item.order_item_uuid, item.qty)),
+        }
+    }
+    merged
+}
+
+// ID: Buat permintaan refund/void berstatus PENDING. Qty dan nominal dicadangkan sampai
+//     disetujui atau ditolak.
+// EN: Create a PENDING refund/void request. Qty and amount stay reserved until it is
+//     approved or rejected.
+#[allow(clippy::too_many_arguments)]
+async fn request_refund(
+    data: &AppState,
+    jwt_auth: &JWTAuthMiddleware,
+    order_uuid: Uuid,
+    kind: &'static str,
+    reason: &str,
+    items: Option<&[OrderRefundItemInput]>,
+    restock: bool,
+    qris_sandbox: bool,
+) -> Result<Uuid, (StatusCode, Json<Value>)> {
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let order = order_refunds_repo::lock_order(&mut tx, order_uuid)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| order_not_found(order_uuid))?;
+    ensure_order_refundable(&order, kind)?;
+
+    let lines = order_refunds_repo::load_refundable_lines(&mut tx, order_uuid, None)
+        .await
+        .map_err(internal_error)?;
+
+    // Tanpa `items` (dan selalu untuk VOID) = semua qty yang tersisa
+    let requested: Vec<(Uuid, Decimal)> = match items {
+        Some(items) if kind == REFUND_KIND_REFUND && !items.is_empty() => merge_item_inputs(items),
+        _ => lines
+            .iter()
+            .filter(|line| line.qty > line.refunded_qty)
+            .map(|line| (line.order_item_uuid, line.qty - line.refunded_qty))
+            .collect(),
+    };
+    if requested.is_empty() && kind == REFUND_KIND_REFUND {
+        return Err(fail(
+            StatusCode::CONFLICT,
+            format!("Semua item pesanan {} sudah direfund", order.order_no),
+        ));
+    }
+
+    let mut new_items = Vec::with_capacity(requested.len());
+    for (order_item_uuid, qty) in &requested {
+        let line = lines
+            .iter()
+            .find(|line| line.order_item_uuid == *order_item_uuid)
+            .ok_or_else(|| {
+                fail(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Item pesanan dengan ID: {} bukan bagian dari pesanan {}",
+                        order_item_uuid, order.order_no
+                    ),
+                )
+            })?;
+        check_line_qty(*qty, line.qty, line.refunded_qty)
+            .map_err(|e| refund_error(e, StatusCode::BAD_REQUEST))?;
+
+        new_items.push(NewOrderRefundItem {
+            order_item_uuid: line.order_item_uuid,
+            product_uuid: line.product_uuid,
+            qty: *qty,
+            unit_price: line.unit_price,
+            amount: line_refund_amount(
+                *qty,
+                line.qty,
+                line.line_total,
+                order.subtotal,
+                order.total,
+            ),
+            unit_cost: line.unit_cost,
+            cost_amount: (*qty * line.unit_cost.unwrap_or(Decimal::ZERO)).round_dp(2),
+        });
+    }
+
+    let refundable = order_refunds_repo::refundable_amount(&mut tx, order_uuid, None)
+        .await
+        .map_err(internal_error)?
+        .max(Decimal::ZERO);
+    let completes_order = lines.iter().all(|line| {
+        let requested_qty = requested
+            .iter()
+            .find(|(uuid, _)| *uuid == line.order_item_uuid)
+            .map(|(_, qty)| *qty)
+            .unwrap_or(Decimal::ZERO);
+        line.refunded_qty + requested_qty >= line.qty
+    });
+
+    // Refund terakhir / void mengembalikan seluruh sisa pembayaran (menyerap selisih pembulatan)
+    let items_amount: Decimal = new_items.iter().map(|item| item.amount).sum();
+    let amount = if kind == REFUND_KIND_VOID || completes_order {
+        refundable
+    } else {
+        items_amount.min(refundable)
+    };
+    if kind == REFUND_KIND_REFUND && amount <= Decimal::ZERO {
+        return Err(fail(
+            StatusCode::CONFLICT,
+            format!(
+                "Pesanan {} tidak memiliki pembayaran yang bisa dikembalikan",
+                order.order_no
+            ),
+        ));
+    }
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let refund_uuid = order_refunds_repo::insert_refund(
+        &mut tx,
+        &NewOrderRefund {
+            order_uuid,
+            kind,
+            reason: reason.trim().to_string(),
+            amount,
+            cost_amount: new_items.iter().map(|item| item.cost_amount).sum(),
+            restock,
+            qris_sandbox,
+            requested_by: Some(jwt_auth.user.uuid),
+            items: new_items,
+        },
+        current_time,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+    Ok(refund_uuid)
+}
+
+async fn load_refund_with_items(
+    data: &AppState,
+    id: Uuid,
+) -> Result<OrderRefundWithItems, (StatusCode, Json<Value>)> {
+    let refund = order_refunds_repo::get_refund(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| refund_not_found(id))?;
+    let order_no = order_refunds_repo::get_order_no(&data.db, refund.order_uuid)
+        .await
+        .map_err(internal_error)?;
+    let items = order_refunds_repo::get_refund_items(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+    let payments = order_refunds_repo::get_refund_payments(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(OrderRefundWithItems {
+        refund,
+        order_no,
+        items,
+        payments,
+    })
+}
+
+// ID: Approver wajib login dengan password, berperan supervisor ke atas, bukan peminta
+//     refund, dan berada di toko tempat pesanan dibuat (kecuali Super Admin).
+// EN: The approver must be on a password session, hold a supervisor-or-above role, not be
+//     the requester, and belong to the store the order was placed in (Super Admin excepted).
+async fn ensure_refund_approver(
+    data: &AppState,
+    jwt_auth: &JWTAuthMiddleware,
+    refund: &OrderRefund,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    ensure_password_session(jwt_auth)?;
+
+    let forbidden = |message: &str| fail(StatusCode::FORBIDDEN, message.to_string());
+    let approver_uuid = jwt_auth.user.uuid;
+    let (approver_store, approver_role) =
+        auth_tokens_repository::find_profile_scope(&data.db, approver_uuid)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| forbidden("Profil approver tidak ditemukan"))?;
+    let approver_role = approver_role.unwrap_or_default();
+    if !REFUND_APPROVER_ROLE_NUMBERS.contains(&approver_role) {
+        return Err(forbidden(
+            "Hanya supervisor ke atas yang boleh memproses refund",
+        ));
+    }
+    if refund.requested_by == Some(approver_uuid) {
+        return Err(forbidden(
+            "Refund harus disetujui oleh orang lain selain peminta",
+        ));
+    }
+    if approver_role == SUPER_ADMIN_ROLE_NUMBER {
+        return Ok(());
+    }
+
+    // Dibandingkan dengan toko pesanan; pesanan tanpa toko hanya bisa diproses Super Admin
+    let order_store = order_refunds_repo::find_order_store(&data.db, refund.order_uuid)
+        .await
+        .map_err(internal_error)?;
+    match (approver_store, order_store) {
+        (Some(approver_store), Some(order_store)) if approver_store == order_store => Ok(()),
+        _ => Err(forbidden(
+            "Refund hanya bisa diproses oleh supervisor di toko yang sama",
+        )),
+    }
+}
+
+// Gabungkan qty per bahan dengan urutan kemunculan pertama
+fn add_line(lines: &mut Vec<(Uuid, Decimal)>, ingredient_catalog_uuid: Uuid, quantity: Decimal) {
+    match lines
+        .iter_mut()
+        .find(|(uuid, _)| *uuid == ingredient_catalog_uuid)
+    {
+        Some((_, total)) => *total += quantity,
+        None => lines.push((ingredient_catalog_uuid, quantity)),
+    }
+}
+
+// ID: Pecah item pesanan yang direfund menjadi bahan lewat versi resep yang dipatok di
+//     order_items (fallback: versi yang berlaku saat pesanan dibuat), sama dengan move SALE.
+//     Produk tanpa resep tidak mengembalikan bahan.
+// EN: Explode refunded order items into ingredients through the recipe version pinned on
+//     order_items (falling back to the version effective when the order was placed), the same
+//     one the SALE moves used. Products without a recipe return nothing.
+async fn restock_lines(
+    data: &AppState,
+    order_items: &[(Uuid, Decimal)],
+) -> Result<Vec<(Uuid, Decimal)>, (StatusCode, Json<Value>)> {
+    let mut recipes: Vec<(Uuid, Decimal)> = Vec::new();
+    for (order_item_uuid, qty) in order_items {
+        if let Some(recipe_sets_uuid) =
+            recipe_sets_repository::resolve_order_item_recipe_set(&data.db, *order_item_uuid)
+                .await
+                .map_err(internal_error)?
+        {
+            recipes.push((recipe_sets_uuid, *qty));
+        }
+    }
+    if recipes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Resep yang berubah (mis. dihapus) sejak dipatok berarti restock tidak bisa dihitung
+    let graph = recipe_items_repository::load_recipe_graph(&data.db)
+        .await
+        .map_err(internal_error)?;
+    let mut lines: Vec<(Uuid, Decimal)> = Vec::new();
+    for (recipe_sets_uuid, qty) in recipes {
+        let exploded = graph
+            .explode(recipe_sets_uuid, qty, true)
+            .map_err(|e| fail(StatusCode::CONFLICT, e.to_string()))?;
+        for (ingredient_catalog_uuid, quantity) in exploded {
+            add_line(&mut lines, ingredient_catalog_uuid, quantity.round_dp(4));
+        }
+    }
+    lines.retain(|(_, quantity)| *quantity > Decimal::ZERO);
+    Ok(lines)
+}
+
+fn qris_secret_key<'a>(
+    data: &'a AppState,
+    refund: &OrderRefund,
+) -> Result<&'a str, (StatusCode, Json<Value>)> {
+    if refund.qris_sandbox {
+        data.env.xendit_secret_key_sandbox.as_deref()
+    } else {
+        data.env.xendit_secret_key_live.as_deref()
+    }
+    .ok_or_else(|| {
+        fail(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Xendit secret key is not configured".to_string(),
+        )
+    })
+}
+
+fn qris_reference(
+    payment_uuid: Uuid,
+    qr_id: Option<&str>,
+) -> Result<&str, (StatusCode, Json<Value>)> {
+    qr_id.filter(|s| !s.is_empty()).ok_or_else(|| {
+        fail(
+            StatusCode::CONFLICT,
+            format!(
+                "Pembayaran QRIS {} tidak memiliki referensi Xendit",
+                payment_uuid
+            ),
+        )
+    })
+}
+
+// ID: Refund tender QRIS lewat Xendit; mengembalikan ID refund Xendit untuk external_ref.
+// EN: Refund a QRIS tender through Xendit; returns the Xendit refund ID for external_ref.
+async fn refund_qris_tender(
+    data: &AppState,
+    client: &Client,
+    refund: &OrderRefund,
+    part: &PendingGatewayRefund,
+) -> Result<String, (StatusCode, Json<Value>)> {
+    let secret_key = qris_secret_key(data, refund)?;
+    let qr_id = qris_reference(part.tender_uuid, part.qr_id.as_deref())?;
+    // Cadangan QRIS selalu rupiah bulat (split_across_tenders); nominal pecahan berarti data
+    // lama dan tidak boleh dibulatkan diam-diam karena harus sama dengan yang dibukukan
+    let amount_i64 = Some(part.amount)
+        .filter(|amount| amount.fract().is_zero())
+        .and_then(|amount| amount.to_i64())
+        .ok_or_else(|| {
+            fail(
+                StatusCode::CONFLICT,
+                format!(
+                    "Nominal refund QRIS {} harus rupiah bulat",
+                    part.amount.normalize()
+                ),
+            )
+        })?;
+    let full = part.refunded_elsewhere.is_zero() && part.amount == part.tender_amount;
+    let gateway_error = |e: anyhow::Error| {
+        fail(
+            StatusCode::BAD_GATEWAY,
+            format!("Refund QRIS ke Xendit gagal: {}", e),
+        )
+    };
+
+    let base_url = &data.env.xendit_base_url;
+    let qr_payment = xnd::list_qr_payments(client, base_url, secret_key, qr_id)
+        .await
+        .map_err(gateway_error)?
+        .into_iter()
+        .find(|p| p.status.eq_ignore_ascii_case("SUCCEEDED"))
+        .ok_or_else(|| {
+            fail(
+                StatusCode::BAD_GATEWAY,
+                format!(
+                    "Xendit tidak menemukan pembayaran berhasil untuk QR {}",
+                    qr_id
+                ),
+            )
+        })?;
+
+    // Kunci idempotensi per refund + tender: approval yang diulang tidak merefund dua kali
+    let idempotency_key = format!("{}-{}", refund.uuid, part.tender_uuid);
+    let xendit_refund = xnd::create_qr_refund(
+        client,
+        base_url,
+        secret_key,
+        &qr_payment.id,
+        &refund.refund_no,
+        &idempotency_key,
+        amount_i64,
+        full,
+        &refund.reason,
+    )
+    .await
+    .map_err(gateway_error)?;
+
+    Ok(xendit_refund.id)
+}
+
+// ID: Tahap 1 approval: validasi ulang terhadap pesanan terkunci, bagi nominal ke tender asal,
+//     cadangkan sebagai pembayaran negatif (paid_at NULL) lalu tandai refund PROCESSING.
+//     Transaksi di-commit sebelum Xendit dipanggil sehingga kunci pesanan tidak ditahan.
+// EN: Approval step 1: re-validate against the locked order, split the amount across the
+//     original tenders, reserve it as negative payments (paid_at NULL) and mark the refund
+//     PROCESSING. The transaction commits before Xendit is called, so no order lock is held.
+async fn reserve_refund(
+    data: &AppState,
+    jwt_auth: &JWTAuthMiddleware,
+    requested: &OrderRefund,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    let id = requested.uuid;
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    // Pesanan dikunci sebelum refund, sama seperti alur pembayaran
+    let order = order_refunds_repo::lock_order(&mut tx, requested.order_uuid)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| order_not_found(requested.order_uuid))?;
+    let refund = order_refunds_repo::lock_refund(&mut tx, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| refund_not_found(id))?;
+    match refund.status.as_str() {
+        REFUND_STATUS_PENDING => {}
+        // Approver lain baru saja mencadangkannya; lanjut ke gateway
+        REFUND_STATUS_PROCESSING => return Ok(()),
+        other => return Err(refund_not_pending(other)),
+    }
+    ensure_order_refundable(&order, &refund.kind)?;
+
+    // Validasi ulang qty terhadap refund lain yang disetujui setelah permintaan ini dibuat
+    let items = order_refunds_repo::get_refund_items(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+    let lines = order_refunds_repo::load_refundable_lines(&mut tx, order.uuid, Some(id))
+        .await
+        .map_err(internal_error)?;
+    for item in &items {
+        let (line_qty, refunded_qty) = lines
+            .iter()
+            .find(|line| line.order_item_uuid == item.order_item_uuid)
+            .map(|line| (line.qty, line.refunded_qty))
+            .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+        check_line_qty(item.qty, line_qty, refunded_qty)
+            .map_err(|e| refund_error(e, StatusCode::CONFLICT))?;
+    }
+
+    let refundable = order_refunds_repo::refundable_amount(&mut tx, order.uuid, Some(id))
+        .await
+        .map_err(internal_error)?
+        .max(Decimal::ZERO);
+    // Void mengembalikan semua pembayaran yang masuk sampai saat disetujui
+    let amount = if refund.kind == REFUND_KIND_VOID {
+        refundable
+    } else {
+        refund.amount
+    };
+
+    let tenders = order_refunds_repo::load_refundable_tenders(&mut tx, order.uuid)
+        .await
+        .map_err(internal_error)?;
+    let balances: Vec<TenderBalance> = tenders
+        .iter()
+        .map(|tender| TenderBalance {
+            payment_uuid: tender.uuid,
+            refundable: tender.amount - tender.refunded,
+            whole_units: tender.method == "QRIS",
+        })
+        .collect();
+    let parts = split_across_tenders(amount, &balances)
+        .map_err(|e| refund_error(e, StatusCode::CONFLICT))?;
+    // Porsi QRIS dibulatkan ke rupiah penuh; yang dicatat adalah total yang benar-benar dibagi
+    let amount: Decimal = parts.iter().map(|(_, part)| *part).sum();
+
+    // Semua yang bisa gagal sebelum uang bergerak dicek di sini, bukan setelah Xendit
+    let current_time = chrono::Utc::now().timestamp_millis();
+    if refund.restock {
+        let order_items: Vec<(Uuid, Decimal)> = items
+            .iter()
+            .map(|item| (item.order_item_uuid, item.qty))
+            .collect();
+        restock_lines(data, &order_items).await?;
+    }
+    for (payment_uuid, part) in &parts {
+        let tender = tenders
+            .iter()
+            .find(|tender| tender.uuid == *payment_uuid)
+            .ok_or_else(|| {
+                fail(
+                    StatusCode::CONFLICT,
+                    format!("Pembayaran {} tidak bisa direfund", payment_uuid),
+                )
+            })?;
+        if tender.method == "QRIS" {
+            qris_secret_key(data, &refund)?;
+            qris_reference(tender.uuid, tender.external_ref.as_deref())?;
+        }
+        order_refunds_repo::insert_refund_payment(
+            &mut tx,
+            order.uuid,
+            refund.uuid,
+            tender.uuid,
+            &tender.method,
+            *part,
+            current_time,
+        )
+        .await
+        .map_err(internal_error)?;
+    }
+
+    order_refunds_repo::mark_refund_processing(
+        &mut tx,
+        refund.uuid,
+        jwt_auth.user.uuid,
+        amount,
+        current_time,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)
+}
+
+// ID: Tahap 3 approval: setelah semua refund QRIS diterima Xendit, bukukan pembayaran negatif
+//     (paid_at), posting move RETURN bila restock, tandai APPROVED lalu perbarui status pesanan.
+//     Mengembalikan bahan yang di-restock dan status pesanan terbaru.
+// EN: Approval step 3: once Xendit accepted every QRIS refund, book the negative payments
+//     (paid_at), post RETURN moves when restocking, mark the refund APPROVED and update the
+//     order status. Returns the restocked ingredients and the resulting order status.
+async fn finalize_refund(
+    data: &AppState,
+    requested: &OrderRefund,
+) -> Result<(Vec<(Uuid, Decimal)>, String), (StatusCode, Json<Value>)> {
+    let id = requested.uuid;
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let order = order_refunds_repo::lock_order(&mut tx, requested.order_uuid)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| order_not_found(requested.order_uuid))?;
+    let refund = order_refunds_repo::lock_refund(&mut tx, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| refund_not_found(id))?;
+    if refund.status != REFUND_STATUS_PROCESSING {
+        return Err(refund_not_pending(&refund.status));
+    }
+
+    let items = order_refunds_repo::get_refund_items(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+    let lines = order_refunds_repo::load_refundable_lines(&mut tx, order.uuid, Some(id))
+        .await
+        .map_err(internal_error)?;
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    order_refunds_repo::complete_refund_payments(&mut tx, refund.uuid, current_time)
+        .await
+        .map_err(internal_error)?;
+
+    let mut restocked = Vec::new();
+    if refund.restock {
+        let order_items: Vec<(Uuid, Decimal)> = items
+            .iter()
+            .map(|item| (item.order_item_uuid, item.qty))
+            .collect();
+        let exploded = restock_lines(data, &order_items).await?;
+        let restockable = order_refunds_repo::restockable_ingredients(&mut tx, order.uuid)
+            .await
+            .map_err(internal_error)?;
+        restocked = cap_restock(&exploded, &restockable);
+        order_refunds_repo::post_restock_moves(
+            &mut tx,
+            refund.uuid,
+            &refund.refund_no,
+            &restocked,
+            current_time,
+        )
+        .await
+        .map_err(internal_error)?;
+    }
+
+    // Laba yang dibatalkan dihitung dari nilai baris (bukan uang yang kembali), sehingga void
+    // pesanan yang belum dibayar tetap menghapus labanya dari laporan
+    let items_amount: Decimal = items.iter().map(|item| item.amount).sum();
+    order_refunds_repo::mark_refund_approved(
+        &mut tx,
+        refund.uuid,
+        profit_reversed(items_amount, refund.cost_amount, refund.restock),
+        current_time,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    let fully_refunded = lines.iter().all(|line| {
+        let qty: Decimal = items
+            .iter()
+            .filter(|item| item.order_item_uuid == line.order_item_uuid)
+            .map(|item| item.qty)
+            .sum();
+        line.refunded_qty + qty >= line.qty
+    });
+    let new_status = if refund.kind == REFUND_KIND_VOID {
+        Some(OrderStatus::Cancelled)
+    } else if fully_refunded {
+        Some(OrderStatus::Refunded)
+    } else {
+        None
+    };
+    if let Some(status) = &new_status {
+        order_refunds_repo::set_order_status(&mut tx, order.uuid, status.as_str(), current_time)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    tx.commit().await.map_err(internal_error)?;
+
+    let order_status = new_status
+        .map(|status| status.as_str().to_string())
+        .unwrap_or(order.status);
+    Ok((restocked, order_status))
+}
+
+// Klaim refund PROCESSING untuk tahap gateway; 409 bila approver lain sedang memegangnya
+async fn claim_gateway(data: &AppState, id: Uuid) -> Result<i64, (StatusCode, Json<Value>)> {
+    let current_time = chrono::Utc::now().timestamp_millis();
+    if let Some(claimed_at) = order_refunds_repo::claim_refund_gateway(&data.db, id, current_time)
+        .await
+        .map_err(internal_error)?
+    {
+        return Ok(claimed_at);
+    }
+
+    let refund = order_refunds_repo::get_refund(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| refund_not_found(id))?;
+    if refund.status != REFUND_STATUS_PROCESSING {
+        return Err(refund_not_pending(&refund.status));
+    }
+    Err(fail(
+        StatusCode::CONFLICT,
+        format!(
+            "Refund {} sedang diproses ke Xendit oleh approver lain, coba lagi nanti",
+            refund.refund_no
+        ),
+    ))
+}
+
+// ID: Tahap 2 dan 3 approval: refund QRIS lewat Xendit (ID refund dicatat per porsi), lalu
+//     bukukan refund.
+// EN: Approval steps 2 and 3: refund QRIS through Xendit (recording each share's refund ID),
+//     then book the refund.
+async fn settle_through_gateway(
+    data: &AppState,
+    requested: &OrderRefund,
+) -> Result<(Vec<(Uuid, Decimal)>, String), (StatusCode, Json<Value>)> {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(XENDIT_TIMEOUT_SECS))
+        .build()
+        .unwrap_or_default();
+    let gateway_parts = order_refunds_repo::pending_gateway_refunds(&data.db, requested.uuid)
+        .await
+        .map_err(internal_error)?;
+    for part in &gateway_parts {
+        let xendit_refund_id = refund_qris_tender(data, &client, requested, part).await?;
+        order_refunds_repo::set_refund_payment_external_ref(
+            &data.db,
+            part.payment_uuid,
+            &xendit_refund_id,
+        )
+        .await
+        .map_err(internal_error)?;
+    }
+
+    finalize_refund(data, requested).await
+}
+
+// ID: Handler untuk meminta refund penuh/sebagian atas pesanan PAID
+// EN: Handler to request a full/partial refund of a PAID order
+pub async fn create_order_refund_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(order_uuid): Path<Uuid>,
+    Json(body): Json<CreateOrderRefundRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+    if let Some(items) = &body.items {
+        for item in items {
+            item.validate().map_err(validation_error)?;
+        }
+    }
+
+    let refund_uuid = request_refund(
+        &data,
+        &jwt_auth,
+        order_uuid,
+        REFUND_KIND_REFUND,
+        &body.reason,
+        body.items.as_deref(),
+        body.restock.unwrap_or(false),
+        body.qris_sandbox.unwrap_or(false),
+    )
+    .await?;
+
+    let refund = load_refund_with_items(&data, refund_uuid).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "success".to_string(),
+            message: "Permintaan refund dibuat, menunggu persetujuan supervisor".to_string(),
+            data: refund,
+            errors: json!({}),
+        }),
+    ))
+}
+
+// ID: Handler untuk meminta void (pembatalan) pesanan DRAFT/PAID
+// EN: Handler to request a void of a DRAFT/PAID order
+pub async fn void_order_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(order_uuid): Path<Uuid>,
+    Json(body): Json<VoidOrderRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+
+    let refund_uuid = request_refund(
+        &data,
+        &jwt_auth,
+        order_uuid,
+        REFUND_KIND_VOID,
+        &body.reason,
+        None,
+        body.restock.unwrap_or(false),
+        body.qris_sandbox.unwrap_or(false),
+    )
+    .await?;
+
+    let refund = load_refund_with_items(&data, refund_uuid).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "success".to_string(),
+            message: "Permintaan void dibuat, menunggu persetujuan supervisor".to_string(),
+            data: refund,
+            errors: json!({}),
+        }),
+    ))
+}
+
+// ID: Handler untuk daftar refund/void sebuah pesanan
+// EN: Handler to list the refunds/voids of an order
+pub async fn get_order_refunds_handler(
+    State(data): State<Arc<AppState>>,
+    Path(order_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    if !order_refunds_repo::order_exists(&data.db, order_uuid)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(order_not_found(order_uuid));
+    }
+
+    let refunds = order_refunds_repo::list_refunds_for_order(&data.db, order_uuid)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Daftar refund pesanan berhasil diambil".to_string(),
+        data: json!({ "refunds": refunds }),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk detail refund beserta item dan pembayaran negatifnya
+// EN: Handler to get a refund with its items and negative payments
+pub async fn get_order_refund_handler(
+    State(data): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let refund = load_refund_with_items(&data, id).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Refund berhasil diambil".to_string(),
+        data: refund,
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk menyetujui refund/void: cadangkan pembayaran negatif per tender, klaim
+//     tahap gateway, refund QRIS lewat Xendit di luar transaksi, lalu bukukan refund, move
+//     RETURN bila restock dan status pesanan. Gateway yang gagal melepas klaim dan
+//     meninggalkan refund PROCESSING; approval ulang melanjutkannya dengan kunci idempotensi
+//     yang sama.
+// EN: Handler to approve a refund/void: reserve negative payments per tender, claim the
+//     gateway step, refund QRIS through Xendit outside any transaction, then book the refund,
+//     RETURN moves when restocking and the order status. A gateway failure releases the claim
+//     and leaves the refund PROCESSING; approving again resumes it with the same idempotency
+//     keys.
+pub async fn approve_order_refund_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let requested = order_refunds_repo::get_refund(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| refund_not_found(id))?;
+    ensure_refund_approver(&data, &jwt_auth, &requested).await?;
+
+    match requested.status.as_str() {
+        REFUND_STATUS_PENDING => reserve_refund(&data, &jwt_auth, &requested).await?,
+        REFUND_STATUS_PROCESSING => {}
+        other => return Err(refund_not_pending(other)),
+    }
+
+    // Klaim gateway di-commit sebelum Xendit dipanggil: penolakan dan approver lain menunggu
+    // klaim ini tanpa transaksi atau kunci baris yang ditahan selama HTTP
+    let claimed_at = claim_gateway(&data, id).await?;
+    let (restocked, order_status) = match settle_through_gateway(&data, &requested).await {
+        Ok(settled) => settled,
+        Err(e) => {
+            // Approval ulang bisa langsung melanjutkan; bila gagal dilepas, klaim kedaluwarsa
+            if let Err(release_error) =
+                order_refunds_repo::release_refund_gateway(&data.db, id, claimed_at).await
+            {
+                tracing::error!(
+                    "Failed to release the gateway claim of refund {}: {}",
+                    id,
+                    release_error
+                );
+            }
+            return Err(e);
+        }
+    };
+
+    // Refund sudah tercatat; kegagalan hitung ulang stok hanya dicatat
+    for (ingredient_catalog_uuid, _) in &restocked {
+        if let Err(e) =
+            ingredient_stocks::recompute_stock_for_ingredient(&data.db, *ingredient_catalog_uuid)
+                .await
+        {
+            tracing::error!(
+                "Failed to recompute stock for ingredient {} after refund {}: {}",
+                ingredient_catalog_uuid,
+                id,
+                e
+            );
+        }
+    }
+
+    let approved = load_refund_with_items(&data, id).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: format!(
+            "Refund {} disetujui, status pesanan {}",
+            approved.refund.refund_no, order_status
+        ),
+        data: approved,
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk menolak refund/void PENDING atau PROCESSING; qty, nominal dan pembayaran
+//     negatif yang dicadangkan dilepas. PROCESSING yang sebagian sudah direfund Xendit hanya
+//     bisa diselesaikan lewat approval ulang.
+// EN: Handler to reject a PENDING or PROCESSING refund/void; the reserved qty, amount and
+//     negative payments are released. A PROCESSING refund Xendit already partly refunded can
+//     only be finished by approving it again.
+pub async fn reject_order_refund_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<RejectOrderRefundRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+
+    let requested = order_refunds_repo::get_refund(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| refund_not_found(id))?;
+    ensure_refund_approver(&data, &jwt_auth, &requested).await?;
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    match order_refunds_repo::reject_refund(
+        &data.db,
+        id,
+        jwt_auth.user.uuid,
+        body.reason.trim(),
+        current_time,
+    )
+    .await
+    .map_err(internal_error)?
+    {
+        RefundTransition::Done => {}
+        RefundTransition::NotFound => return Err(refund_not_found(id)),
+        RefundTransition::InvalidStatus(status) => {
+            return Err(fail(
+                StatusCode::CONFLICT,
+                format!(
+                    "Refund berstatus {}, hanya refund PENDING atau PROCESSING yang bisa ditolak",
+                    status
+                ),
+            ))
+        }
+        RefundTransition::GatewayClaimed => {
+            return Err(fail(
+                StatusCode::CONFLICT,
+                "Refund sedang diproses ke Xendit; tunggu hingga selesai sebelum menolaknya"
+                    .to_string(),
+            ))
+        }
+        RefundTransition::GatewayRefunded => {
+            return Err(fail(
+                StatusCode::CONFLICT,
+                "Sebagian refund QRIS sudah diterima Xendit; setujui ulang untuk menyelesaikannya"
+                    .to_string(),
+            ))
+        }
+    }
+
+    let refund = load_refund_with_items(&data, id).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Refund ditolak".to_string(),
+        data: refund,
+        errors: json!({}),
+    }))
+}
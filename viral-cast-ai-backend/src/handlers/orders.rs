@@ -2,23 +2,245 @@ use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     response::Json,
+    Extension,
 };
+use rust_decimal::Decimal;
 use serde_json::Value;
-use sqlx::{PgPool, Row};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::handlers::payments::lock_order_balance;
 use crate::models::orders::{Order, OrderItemWithProduct};
+use crate::models::roles::{CASHIER_OVERRIDE_ROLE_NUMBERS, SUPER_ADMIN_ROLE_NUMBER};
+use crate::repository::auth_tokens as auth_tokens_repository;
+use crate::repository::ingredient_stocks as ingredient_stocks_repository;
+use crate::repository::recipe_items as recipe_items_repository;
+use crate::repository::recipe_sets as recipe_sets_repository;
+use crate::repository::sale_consumption;
+use crate::services::payment_allocation::remaining_balance;
 use crate::{
     dto::{
         api::{ApiResponse, ErrorResponse},
         orders::*,
     },
+    middleware::jwt::JWTAuthMiddleware,
     AppState,
 };
 
+// Recipe version effective at `at_ms` per product, with its rolled-up unit cost when the
+// graph can be costed; products without a resolvable recipe are left out
+struct ResolvedRecipe {
+    recipe_sets_uuid: Uuid,
+    unit_cost: Option<Decimal>,
+}
+
+async fn resolve_recipe_versions(
+    db: &PgPool,
+    product_uuids: &[Uuid],
+    at_ms: i64,
+) -> Result<HashMap<Uuid, ResolvedRecipe>, sqlx::Error> {
+    let mut resolved = HashMap::new();
+    if product_uuids.is_empty() {
+        return Ok(resolved);
+    }
+
+    let graph = recipe_items_repository::load_recipe_graph(db).await?;
+    let prices = ingredient_stocks_repository::fetch_current_unit_costs(db).await?;
+
+    for product_uuid in product_uuids {
+        if resolved.contains_key(product_uuid) {
+            continue;
+        }
+        let Some(recipe_sets_uuid) =
+            recipe_sets_repository::resolve_product_recipe_set(db, *product_uuid, at_ms).await?
+        else {
+            continue;
+        };
+        let unit_cost = graph
+            .roll_up_cost(recipe_sets_uuid, &prices)
+            .ok()
+            .map(|breakdown| breakdown.unit_cost);
+        resolved.insert(
+            *product_uuid,
+            ResolvedRecipe {
+                recipe_sets_uuid,
+                unit_cost,
+            },
+        );
+    }
+
+    Ok(resolved)
+}
+
+// ID: Kasir pesanan diambil dari sesi. Di terminal POS selalu pemilik sesi PIN; di luar POS
+//     cashier_uuid lain hanya boleh diisi Supervisor ke atas untuk staf di tokonya sendiri.
+// EN: The order's cashier comes from the session. On a POS terminal it is always the PIN
+//     session owner; elsewhere only Supervisor-or-above may record another cashier, and only
+//     staff of their own store.
+async fn resolve_cashier(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    requested: Option<Uuid>,
+) -> Result<Uuid, (StatusCode, Json<ErrorResponse>)> {
+    let caller = jwt_auth.user.uuid;
+    let cashier = match requested {
+        Some(cashier) if jwt_auth.pos_device.is_none() && cashier != caller => cashier,
+        _ => return Ok(caller),
+    };
+
+    let forbidden = || {
+        (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: "cashier_uuid must be the signed-in user".to_string(),
+            }),
+        )
+    };
+    let db_error = |e: sqlx::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Database error: {}", e),
+            }),
+        )
+    };
+    let (caller_store, caller_role) = auth_tokens_repository::find_profile_scope(&data.db, caller)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(forbidden)?;
+    let caller_role = caller_role.ok_or_else(forbidden)?;
+    if !CASHIER_OVERRIDE_ROLE_NUMBERS.contains(&caller_role) {
+        return Err(forbidden());
+    }
+    let (cashier_store, _) = auth_tokens_repository::find_profile_scope(&data.db, cashier)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(forbidden)?;
+    match (caller_store, cashier_store) {
+        _ if caller_role == SUPER_ADMIN_ROLE_NUMBER => Ok(cashier),
+        (Some(caller_store), Some(cashier_store)) if caller_store == cashier_store => Ok(cashier),
+        _ => Err(forbidden()),
+    }
+}
+
+// Toko pesanan: toko terminal untuk sesi PIN, selain itu toko profil kasir
+async fn resolve_order_store(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    cashier: Uuid,
+) -> Result<Option<Uuid>, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(device) = &jwt_auth.pos_device {
+        return Ok(Some(device.store_uuid));
+    }
+    let scope = auth_tokens_repository::find_profile_scope(&data.db, cashier)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+    Ok(scope.and_then(|(store, _)| store))
+}
+
+// Pesanan yang ditandai PAID secara manual juga mengonsumsi bahan resepnya (idempoten)
+async fn consume_if_paid(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    status: Option<&str>,
+    now: i64,
+) -> Result<Vec<Uuid>, (StatusCode, Json<ErrorResponse>)> {
+    if !status.is_some_and(|status| status.eq_ignore_ascii_case("PAID")) {
+        return Ok(Vec::new());
+    }
+    sale_consumption::post_sale_consumption(tx, order_uuid, now)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: format!("Database error: {}", e),
+                }),
+            )
+        })
+}
+
+// ID: Perubahan status manual mengunci baris pesanan lebih dulu. Pesanan REFUNDED atau yang punya
+//     refund aktif hanya berubah lewat alur refund, dan PAID butuh pembayaran yang menutup total
+//     (total baru bila ikut diubah).
+// EN: Manual status changes lock the order row first. REFUNDED orders and orders with active
+//     refunds only move through the refund workflow, and PAID needs payments covering the total
+//     (the new total when it changes too).
+async fn ensure_status_change_allowed(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    status: &str,
+    new_total: Option<Decimal>,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let balance = lock_order_balance(tx, order_uuid, None)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: format!("Database error: {}", e),
+                }),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: "Order not found".to_string(),
+                }),
+            )
+        })?;
+
+    let conflict = if balance.status == "REFUNDED" {
+        Some(format!(
+            "Order {} is REFUNDED and its status can no longer change",
+            balance.order_no
+        ))
+    } else if balance.has_refunds {
+        Some(format!(
+            "Order {} has refunds; its status only changes through the refund workflow",
+            balance.order_no
+        ))
+    } else {
+        let remaining = remaining_balance(new_total.unwrap_or(balance.total), balance.allocated);
+        (status.eq_ignore_ascii_case("PAID") && remaining > Decimal::ZERO).then(|| {
+            format!(
+                "Order {} still has {} outstanding and cannot be marked PAID",
+                balance.order_no, remaining
+            )
+        })
+    };
+
+    match conflict {
+        Some(message) => Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message,
+            }),
+        )),
+        None => Ok(()),
+    }
+}
+
 pub async fn create_order(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     State(data): State<Arc<AppState>>,
     Json(payload): Json<CreateOrderRequest>,
 ) -> Result<Json<ApiResponse<OrderResponse>>, (StatusCode, Json<ErrorResponse>)> {
@@ -71,6 +293,25 @@ pub async fn create_order(
     let order_uuid = Uuid::new_v4();
     let now = chrono::Utc::now().timestamp_millis();
 
+    let cashier = resolve_cashier(&data, &jwt_auth, payload.cashier_uuid).await?;
+    let store_uuid = resolve_order_store(&data, &jwt_auth, cashier).await?;
+    let cashier_uuid = Some(cashier);
+
+    // Setiap item dipatok ke versi resep yang berlaku saat order dibuat; konsumsi stok saat
+    // order lunas memakai versi ini. Item tanpa unit_cost dihitung dari versi yang sama
+    let product_uuids: Vec<Uuid> = payload.items.iter().map(|item| item.product_uuid).collect();
+    let recipe_versions = resolve_recipe_versions(&data.db, &product_uuids, now)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: format!("Database error: {}", e),
+                }),
+            )
+        })?;
+
     // Create order
     let discount = payload
         .discount
@@ -79,13 +320,14 @@ pub async fn create_order(
 
     sqlx::query(
         r#"
-        INSERT INTO orders (uuid, order_no, cashier_uuid, status, subtotal, discount, tax, total, net_profit, created_at, updated_at)
-        VALUES ($1, $2, $3, 'DRAFT', $4, $5, $6, $7, $8, $9, $10)
+        INSERT INTO orders (uuid, order_no, cashier_uuid, store_uuid, status, subtotal, discount, tax, total, net_profit, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, 'DRAFT', $5, $6, $7, $8, $9, $10, $11)
         "#
     )
     .bind(order_uuid)
     .bind(&payload.order_no)
-    .bind(payload.cashier_uuid)
+    .bind(cashier_uuid)
+    .bind(store_uuid)
     .bind(payload.subtotal)
     .bind(discount)
     .bind(tax)
@@ -108,10 +350,11 @@ pub async fn create_order(
     // Create order items
     for item in &payload.items {
         let item_uuid = Uuid::new_v4();
+        let recipe = recipe_versions.get(&item.product_uuid);
         sqlx::query(
             r#"
-            INSERT INTO order_items (uuid, order_uuid, product_uuid, qty, unit_price, unit_cost, line_total, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO order_items (uuid, order_uuid, product_uuid, qty, unit_price, unit_cost, line_total, recipe_sets_uuid, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             "#
         )
         .bind(item_uuid)
@@ -119,8 +362,12 @@ pub async fn create_order(
         .bind(item.product_uuid)
         .bind(item.qty)
         .bind(item.unit_price)
-        .bind(item.unit_cost)
+        .bind(
+            item.unit_cost
+                .or_else(|| recipe.and_then(|resolved| resolved.unit_cost)),
+        )
         .bind(item.line_total)
+        .bind(recipe.map(|resolved| resolved.recipe_sets_uuid))
         .bind(now)
         .bind(now)
         .execute(&mut *tx)
@@ -239,6 +486,7 @@ async fn get_order_by_id_internal(
             unit_price: item.unit_price,
             unit_cost: item.unit_cost,
             line_total: item.line_total,
+            recipe_sets_uuid: item.recipe_sets_uuid,
             created_at: item.created_at.or(Some(0)),
             updated_at: item.updated_at.or(Some(0)),
             product_name: item.product_name,
@@ -251,6 +499,7 @@ async fn get_order_by_id_internal(
         uuid: order.uuid,
         order_no: order.order_no,
         cashier_uuid: order.cashier_uuid,
+        store_uuid: order.store_uuid,
         status: order.status,
         subtotal: order.subtotal,
         discount: order.discount,
@@ -405,30 +654,37 @@ pub async fn get_orders(
 
 pub async fn update_order(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateOrderRequest>,
 ) -> Result<Json<ApiResponse<OrderResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    if let Err(errors) = payload.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Validation error: {:?}", errors),
+            }),
+        ));
+    }
+
     let now = chrono::Utc::now().timestamp_millis();
 
+    // Mengganti kasir mengikuti aturan yang sama dengan pembuatan pesanan
+    let cashier_uuid = match payload.cashier_uuid {
+        Some(requested) => Some(resolve_cashier(&data, &jwt_auth, Some(requested)).await?),
+        None => None,
+    };
+
     let mut set_clauses = Vec::new();
     let mut bind_count = 0;
 
-    if payload.cashier_uuid.is_some() {
+    if cashier_uuid.is_some() {
         bind_count += 1;
         set_clauses.push(format!("cashier_uuid = ${}", bind_count));
     }
 
-    if let Some(status) = &payload.status {
-        // Validate status
-        if !["DRAFT", "PAID", "CANCELLED", "REFUNDED"].contains(&status.to_uppercase().as_str()) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    status: "error".to_string(),
-                    message: "Invalid status".to_string(),
-                }),
-            ));
-        }
+    if payload.status.is_some() {
         bind_count += 1;
         set_clauses.push(format!("status = ${}", bind_count));
     }
@@ -480,12 +736,12 @@ pub async fn update_order(
 
     let mut query_builder = sqlx::query(&query);
 
-    if let Some(cashier_uuid) = payload.cashier_uuid {
+    if let Some(cashier_uuid) = cashier_uuid {
         query_builder = query_builder.bind(cashier_uuid);
     }
 
     if let Some(status) = &payload.status {
-        query_builder = query_builder.bind(status);
+        query_builder = query_builder.bind(status.to_uppercase());
     }
 
     if let Some(subtotal) = payload.subtotal {
@@ -510,7 +766,21 @@ pub async fn update_order(
 
     query_builder = query_builder.bind(now).bind(id);
 
-    let result = query_builder.execute(&data.db).await.map_err(|e| {
+    let mut tx = data.db.begin().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    if let Some(status) = &payload.status {
+        ensure_status_change_allowed(&mut tx, id, status, payload.total).await?;
+    }
+
+    let result = query_builder.execute(&mut *tx).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -530,6 +800,20 @@ pub async fn update_order(
         ));
     }
 
+    let consumed = consume_if_paid(&mut tx, id, payload.status.as_deref(), now).await?;
+
+    tx.commit().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Failed to commit transaction: {}", e),
+            }),
+        )
+    })?;
+
+    sale_consumption::recompute_sale_stock(&data.db, &consumed).await;
+
     let order = get_order_by_id_internal(&data.db, id).await?;
 
     Ok(Json(ApiResponse {
@@ -557,14 +841,27 @@ pub async fn update_order_status(
     }
 
     let now = chrono::Utc::now().timestamp_millis();
+    let status = payload.status.to_uppercase();
+
+    let mut tx = data.db.begin().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Database error: {}", e),
+            }),
+        )
+    })?;
+
+    ensure_status_change_allowed(&mut tx, id, &status, None).await?;
 
     let result = sqlx::query(
         "UPDATE orders SET status = $1, updated_at = $2 WHERE uuid = $3 AND deleted_at = 0",
     )
-    .bind(payload.status.to_uppercase())
+    .bind(&status)
     .bind(now)
     .bind(id)
-    .execute(&data.db)
+    .execute(&mut *tx)
     .await
     .map_err(|e| {
         (
@@ -586,6 +883,20 @@ pub async fn update_order_status(
         ));
     }
 
+    let consumed = consume_if_paid(&mut tx, id, Some(&status), now).await?;
+
+    tx.commit().await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Failed to commit transaction: {}", e),
+            }),
+        )
+    })?;
+
+    sale_consumption::recompute_sale_stock(&data.db, &consumed).await;
+
     let order = get_order_by_id_internal(&data.db, id).await?;
 
     Ok(Json(ApiResponse {
@@ -675,6 +986,22 @@ pub async fn delete_order(
     }))
 }
 
+// ID: Refund/void yang disetujui per pesanan. Pendapatan dikurangi nilai baris yang direfund,
+//     laba dikurangi profit_reversed; total_refunded = uang yang benar-benar dikembalikan.
+// EN: Approved refunds/voids per order. Revenue is reduced by the refunded line value,
+//     profit by profit_reversed; total_refunded = money actually paid back.
+const APPROVED_REFUNDS_JOIN: &str = r#"
+        LEFT JOIN LATERAL (
+            SELECT COUNT(*) as refund_count,
+                   COALESCE(SUM(r.amount), 0) as refunded_amount,
+                   COALESCE(SUM(r.profit_reversed), 0) as profit_reversed,
+                   COALESCE(SUM((
+                       SELECT SUM(ri.amount) FROM order_refund_items ri WHERE ri.refund_uuid = r.uuid
+                   )), 0) as revenue_reversed
+            FROM order_refunds r
+            WHERE r.order_uuid = o.uuid AND r.status = 'APPROVED' AND r.deleted_at = 0
+        ) rf ON TRUE"#;
+
 pub async fn get_order_stats(
     State(data): State<Arc<AppState>>,
     Query(params): Query<OrderStatsRequest>,
@@ -709,13 +1036,17 @@ pub async fn get_order_stats(
         r#"
         SELECT 
             COUNT(*) as total_orders,
-            COALESCE(SUM(total), 0) as total_revenue,
-            COALESCE(SUM(net_profit), 0) as total_profit,
-            COALESCE(AVG(total), 0) as avg_order_value
+            COALESCE(SUM(o.total), 0) as gross_revenue,
+            COALESCE(SUM(o.total - rf.revenue_reversed), 0) as total_revenue,
+            COALESCE(SUM(rf.refunded_amount), 0) as total_refunded,
+            COALESCE(SUM(rf.refund_count), 0)::BIGINT as refund_count,
+            COALESCE(SUM(o.net_profit - rf.profit_reversed), 0) as total_profit,
+            COALESCE(AVG(o.total), 0) as avg_order_value
         FROM orders o
+        {}
         WHERE {}
         "#,
-        where_clause
+        APPROVED_REFUNDS_JOIN, where_clause
     );
 
     let mut stats_query_builder = sqlx::query(&stats_query);
@@ -747,7 +1078,10 @@ pub async fn get_order_stats(
     })?;
 
     let total_orders: i64 = stats_row.get("total_orders");
+    let gross_revenue: rust_decimal::Decimal = stats_row.get("gross_revenue");
     let total_revenue: rust_decimal::Decimal = stats_row.get("total_revenue");
+    let total_refunded: rust_decimal::Decimal = stats_row.get("total_refunded");
+    let refund_count: i64 = stats_row.get("refund_count");
     let total_profit: rust_decimal::Decimal = stats_row.get("total_profit");
     let avg_order_value: rust_decimal::Decimal = stats_row.get("avg_order_value");
 
@@ -810,17 +1144,19 @@ pub async fn get_order_stats(
     let daily_query = format!(
         r#"
         SELECT 
-            DATE(to_timestamp(created_at / 1000)) as date,
+            DATE(to_timestamp(o.created_at / 1000)) as date,
             COUNT(*) as orders_count,
-            COALESCE(SUM(total), 0) as total_revenue,
-            COALESCE(SUM(net_profit), 0) as total_profit
+            COALESCE(SUM(o.total - rf.revenue_reversed), 0) as total_revenue,
+            COALESCE(SUM(rf.refunded_amount), 0) as total_refunded,
+            COALESCE(SUM(o.net_profit - rf.profit_reversed), 0) as total_profit
         FROM orders o
+        {}
         WHERE {}
-        GROUP BY DATE(to_timestamp(created_at / 1000))
+        GROUP BY DATE(to_timestamp(o.created_at / 1000))
         ORDER BY date DESC
         LIMIT 30
         "#,
-        where_clause
+        APPROVED_REFUNDS_JOIN, where_clause
     );
 
     let mut daily_query_builder = sqlx::query(&daily_query);
@@ -857,6 +1193,7 @@ pub async fn get_order_stats(
             date: row.get::<chrono::NaiveDate, _>("date").to_string(),
             orders_count: row.get("orders_count"),
             total_revenue: row.get("total_revenue"),
+            total_refunded: row.get("total_refunded"),
             total_profit: row.get("total_profit"),
         })
         .collect();
@@ -867,7 +1204,10 @@ pub async fn get_order_stats(
         message: "Order statistics retrieved successfully".to_string(),
         data: OrderStatsResponse {
             total_orders,
+            gross_revenue,
             total_revenue,
+            total_refunded,
+            refund_count,
             total_profit,
             avg_order_value,
             orders_by_status,
@@ -6,8 +6,9 @@ use axum::{
 };
 use reqwest::Client;
 use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde_json::Value;
-use sqlx::{PgPool, Row};
+use sqlx::{PgPool, Postgres, Row, Transaction};
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
@@ -17,6 +18,9 @@ use crate::{
         api::{ApiResponse, ErrorResponse},
         payments::*,
     },
+    models::payments::PaymentMethod,
+    repository::sale_consumption,
+    services::payment_allocation::{allocate, remaining_balance, AllocationError},
     AppState,
 };
 
@@ -36,30 +40,27 @@ pub async fn create_payment(
         ));
     }
 
-    // Check if order exists (runtime query)
-    let order_exists = sqlx::query("SELECT 1 FROM orders WHERE uuid = $1 AND deleted_at = 0")
-        .bind(payload.order_uuid)
-        .fetch_optional(&data.db)
+    let method = parse_payment_method(&payload.method)?;
+
+    let mut tx = data.db.begin().await.map_err(database_error)?;
+
+    // Kunci baris pesanan agar dua kasir tidak mengalokasikan sisa tagihan yang sama
+    let balance = lock_order_balance(&mut tx, payload.order_uuid, None)
         .await
-        .map_err(|e| {
+        .map_err(database_error)?
+        .ok_or_else(|| {
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::NOT_FOUND,
                 Json(ErrorResponse {
                     status: "error".to_string(),
-                    message: format!("Database error: {}", e),
+                    message: "Order not found".to_string(),
                 }),
             )
         })?;
+    ensure_order_accepts_payments(&balance)?;
 
-    if order_exists.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                status: "error".to_string(),
-                message: "Order not found".to_string(),
-            }),
-        ));
-    }
+    let allocation =
+        allocate(&method, payload.amount, balance.remaining()).map_err(allocation_error)?;
 
     let payment_uuid = Uuid::new_v4();
     let now = chrono::Utc::now().timestamp_millis();
@@ -67,19 +68,21 @@ pub async fn create_payment(
 
     sqlx::query(
         r#"
-        INSERT INTO payments (uuid, order_uuid, method, amount, paid_at, external_ref, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        INSERT INTO payments (uuid, order_uuid, method, amount, tendered_amount, change_amount, paid_at, external_ref, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         "#
     )
     .bind(payment_uuid)
     .bind(payload.order_uuid)
-    .bind(payload.method.to_uppercase())
-    .bind(payload.amount)
+    .bind(method.as_str())
+    .bind(allocation.applied)
+    .bind(allocation.tendered)
+    .bind(allocation.change)
     .bind(paid_at)
     .bind(payload.external_ref)
     .bind(now)
     .bind(now)
-    .execute(&data.db)
+    .execute(&mut *tx)
     .await
     .map_err(|e| {
         (
@@ -91,7 +94,22 @@ pub async fn create_payment(
         )
     })?;
 
-    let payment = get_payment_by_id_internal(&data.db, payment_uuid).await?;
+    let consumed = settle_order_if_paid(&mut tx, payload.order_uuid)
+        .await
+        .map_err(database_error)?;
+
+    tx.commit().await.map_err(database_error)?;
+
+    let settled = consumed.is_some();
+    sale_consumption::recompute_sale_stock(&data.db, &consumed.unwrap_or_default()).await;
+
+    let mut payment = get_payment_by_id_internal(&data.db, payment_uuid).await?;
+    payment.remaining_amount = Some(allocation.remaining_after);
+    payment.order_status = Some(if settled {
+        "PAID".to_string()
+    } else {
+        balance.status
+    });
 
     Ok(Json(ApiResponse {
         code: 201,
@@ -102,6 +120,246 @@ pub async fn create_payment(
     }))
 }
 
+// ID: Saldo pesanan yang dibaca di dalam transaksi pembayaran atau perubahan status (baris
+//     pesanan terkunci).
+// EN: Order balance read inside a payment or status-change transaction (order row locked).
+pub(crate) struct OrderBalance {
+    pub(crate) order_no: String,
+    pub(crate) status: String,
+    pub(crate) total: Decimal,
+    // Semua pembayaran aktif, termasuk QRIS yang belum selesai
+    pub(crate) allocated: Decimal,
+    // Pesanan yang punya refund aktif: pembayarannya hanya berubah lewat alur refund
+    pub(crate) has_refunds: bool,
+}
+
+impl OrderBalance {
+    pub(crate) fn remaining(&self) -> Decimal {
+        remaining_balance(self.total, self.allocated)
+    }
+}
+
+pub(crate) async fn lock_order_balance(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    exclude_payment: Option<Uuid>,
+) -> Result<Option<OrderBalance>, sqlx::Error> {
+    let order_row = sqlx::query(
+        r#"
+        SELECT order_no, status, total,
+               EXISTS (
+                   SELECT 1 FROM order_refunds r
+                   WHERE r.order_uuid = orders.uuid AND r.deleted_at = 0 AND r.status <> 'REJECTED'
+               ) AS has_refunds
+        FROM orders
+        WHERE uuid = $1 AND deleted_at = 0
+        FOR UPDATE
+        "#,
+    )
+    .bind(order_uuid)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    let Some(order_row) = order_row else {
+        return Ok(None);
+    };
+
+    let allocated: Decimal = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(amount), 0)
+        FROM payments
+        WHERE order_uuid = $1 AND deleted_at = 0 AND refund_uuid IS NULL
+          AND ($2::uuid IS NULL OR uuid <> $2)
+        "#,
+    )
+    .bind(order_uuid)
+    .bind(exclude_payment)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(Some(OrderBalance {
+        order_no: order_row.get("order_no"),
+        status: order_row.get("status"),
+        total: order_row.get("total"),
+        allocated,
+        has_refunds: order_row.get("has_refunds"),
+    }))
+}
+
+// ID: Pesanan otomatis menjadi PAID begitu pembayaran yang sudah selesai menutup total, lalu
+//     bahan resepnya dikonsumsi. Some(bahan tersentuh) bila lunas; pemanggil menghitung ulang
+//     stok setelah commit.
+// EN: Flip the order to PAID once completed payments cover its total, then consume its recipe
+//     ingredients. Some(touched ingredients) when settled; the caller recomputes stock after
+//     commit.
+async fn settle_order_if_paid(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+) -> Result<Option<Vec<Uuid>>, sqlx::Error> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let settled = sqlx::query(
+        r#"
+        UPDATE orders
+        SET status = 'PAID', updated_at = $2
+        WHERE uuid = $1 AND deleted_at = 0 AND status = 'DRAFT'
+          AND total <= (
+            SELECT COALESCE(SUM(amount), 0)
+            FROM payments
+            WHERE order_uuid = $1 AND deleted_at = 0 AND paid_at IS NOT NULL
+              AND refund_uuid IS NULL
+          )
+        "#,
+    )
+    .bind(order_uuid)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if !settled {
+        return Ok(None);
+    }
+    sale_consumption::post_sale_consumption(tx, order_uuid, now)
+        .await
+        .map(Some)
+}
+
+// ID: Kebalikan settle_order_if_paid: pesanan PAID kembali ke DRAFT bila pembayaran yang sudah
+//     selesai tidak lagi menutup total (nominal diturunkan atau pembayaran dihapus). Konsumsi
+//     bahan yang sudah diposting tetap; saat lunas lagi tidak diposting ulang (idempoten).
+// EN: Inverse of settle_order_if_paid: a PAID order goes back to DRAFT once completed payments
+//     no longer cover its total (amount lowered or payment deleted). Ingredient consumption
+//     already posted stays; settling again does not post it twice (idempotent).
+async fn reopen_order_if_unpaid(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let reopened = sqlx::query(
+        r#"
+        UPDATE orders
+        SET status = 'DRAFT', updated_at = $2
+        WHERE uuid = $1 AND deleted_at = 0 AND status = 'PAID'
+          AND total > (
+            SELECT COALESCE(SUM(amount), 0)
+            FROM payments
+            WHERE order_uuid = $1 AND deleted_at = 0 AND paid_at IS NOT NULL
+              AND refund_uuid IS NULL
+          )
+        "#,
+    )
+    .bind(order_uuid)
+    .bind(now)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    Ok(reopened > 0)
+}
+
+// ID: Tandai pembayaran QRIS selesai lalu lunasi pesanan bila tagihannya sudah tertutup.
+//     Pesanan dikunci lebih dulu agar urutan kunci sama dengan create_payment.
+// EN: Mark a QRIS payment completed, then settle the order once its bill is covered.
+//     The order is locked first so lock order matches create_payment.
+async fn complete_qris_payment(
+    tx: &mut Transaction<'_, Postgres>,
+    payment_uuid: Uuid,
+    order_uuid: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query("SELECT 1 FROM orders WHERE uuid = $1 FOR UPDATE")
+        .bind(order_uuid)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let completed = sqlx::query(
+        "UPDATE payments SET paid_at = $1, updated_at = $1 WHERE uuid = $2 AND paid_at IS NULL",
+    )
+    .bind(now)
+    .bind(payment_uuid)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+
+    if completed == 0 {
+        return Ok(Vec::new());
+    }
+    Ok(settle_order_if_paid(tx, order_uuid)
+        .await?
+        .unwrap_or_default())
+}
+
+fn ensure_order_accepts_payments(
+    balance: &OrderBalance,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if matches!(balance.status.as_str(), "CANCELLED" | "REFUNDED") {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!(
+                    "Order {} is {} and cannot take payments",
+                    balance.order_no, balance.status
+                ),
+            }),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_payment_method(method: &str) -> Result<PaymentMethod, (StatusCode, Json<ErrorResponse>)> {
+    PaymentMethod::from_str(method).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: "Invalid payment method. Must be one of: CASH, CARD, QRIS, TRANSFER"
+                    .to_string(),
+            }),
+        )
+    })
+}
+
+fn allocation_error(err: AllocationError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match err {
+        AllocationError::AlreadySettled => StatusCode::CONFLICT,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    (
+        status,
+        Json(ErrorResponse {
+            status: "error".to_string(),
+            message: err.to_string(),
+        }),
+    )
+}
+
+// Baris refund dan pembayaran yang sudah direfund hanya berubah lewat alur refund
+const REFUND_LOCKED_SQL: &str = "(refund_uuid IS NOT NULL OR EXISTS (\
+     SELECT 1 FROM payments r WHERE r.refunded_payment_uuid = payments.uuid AND r.deleted_at = 0))";
+
+fn refund_locked_error() -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::CONFLICT,
+        Json(ErrorResponse {
+            status: "error".to_string(),
+            message:
+                "Payments of an order with refunds can only change through the refund workflow"
+                    .to_string(),
+        }),
+    )
+}
+
+fn database_error(e: sqlx::Error) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse {
+            status: "error".to_string(),
+            message: format!("Database error: {}", e),
+        }),
+    )
+}
+
 pub async fn get_payment_by_id(
     State(data): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
@@ -163,10 +421,15 @@ async fn get_payment_by_id_internal(
         order_no: row.get::<Option<String>, _>("order_no"),
         method: row.get("method"),
         amount: row.get("amount"),
+        tendered_amount: row.get("tendered_amount"),
+        change_amount: row.get("change_amount"),
         paid_at: row.get("paid_at"),
         external_ref: row.get("external_ref"),
+        refund_uuid: row.get("refund_uuid"),
         created_at: row.get::<Option<i64>, _>("created_at").or(Some(0)),
         updated_at: row.get::<Option<i64>, _>("updated_at").or(Some(0)),
+        remaining_amount: None,
+        order_status: None,
     })
 }
 
@@ -291,8 +554,11 @@ pub async fn get_payments(
             order_no: row.get("order_no"),
             method: row.get("method"),
             amount: row.get("amount"),
+            tendered_amount: row.get("tendered_amount"),
+            change_amount: row.get("change_amount"),
             paid_at: row.get("paid_at"),
             external_ref: row.get("external_ref"),
+            refund_uuid: row.get("refund_uuid"),
             created_at: row.get::<Option<i64>, _>("created_at").or(Some(0)),
         })
         .collect();
@@ -329,19 +595,106 @@ pub async fn update_payment(
         ));
     }
 
+    if payload.method.is_none()
+        && payload.amount.is_none()
+        && payload.paid_at.is_none()
+        && payload.external_ref.is_none()
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: "No fields to update".to_string(),
+            }),
+        ));
+    }
+
+    let new_method = payload
+        .method
+        .as_deref()
+        .map(parse_payment_method)
+        .transpose()?;
+
+    let payment_not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: "Payment not found".to_string(),
+            }),
+        )
+    };
+
+    let order_uuid: Uuid =
+        sqlx::query_scalar("SELECT order_uuid FROM payments WHERE uuid = $1 AND deleted_at = 0")
+            .bind(id)
+            .fetch_optional(&data.db)
+            .await
+            .map_err(database_error)?
+            .ok_or_else(payment_not_found)?;
+
+    let mut tx = data.db.begin().await.map_err(database_error)?;
+
+    // Pesanan selalu dikunci sebelum baris pembayaran, sama seperti create dan webhook QRIS
+    let balance = lock_order_balance(&mut tx, order_uuid, Some(id))
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: "Order not found".to_string(),
+                }),
+            )
+        })?;
+
+    let current = sqlx::query(&format!(
+        "SELECT method, amount, tendered_amount, {} AS refund_locked FROM payments WHERE uuid = $1 AND deleted_at = 0 FOR UPDATE",
+        REFUND_LOCKED_SQL
+    ))
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(database_error)?
+    .ok_or_else(payment_not_found)?;
+    if balance.has_refunds || current.get::<bool, _>("refund_locked") {
+        return Err(refund_locked_error());
+    }
+
+    // ID: Ubah metode/nominal = alokasi ulang terhadap sisa tagihan tanpa pembayaran ini.
+    // EN: Changing method/amount re-allocates against the balance excluding this payment.
+    let allocation = if new_method.is_some() || payload.amount.is_some() {
+        ensure_order_accepts_payments(&balance)?;
+        let method = match &new_method {
+            Some(method) => method.clone(),
+            None => parse_payment_method(current.get("method"))?,
+        };
+        let tendered = payload.amount.unwrap_or_else(|| {
+            current
+                .get::<Option<Decimal>, _>("tendered_amount")
+                .unwrap_or_else(|| current.get("amount"))
+        });
+        Some(allocate(&method, tendered, balance.remaining()).map_err(allocation_error)?)
+    } else {
+        None
+    };
+
     let now = chrono::Utc::now().timestamp_millis();
 
     let mut set_clauses = Vec::new();
     let mut bind_count = 0;
 
-    if payload.method.is_some() {
+    if new_method.is_some() {
         bind_count += 1;
         set_clauses.push(format!("method = ${}", bind_count));
     }
 
-    if payload.amount.is_some() {
-        bind_count += 1;
-        set_clauses.push(format!("amount = ${}", bind_count));
+    if allocation.is_some() {
+        set_clauses.push(format!("amount = ${}", bind_count + 1));
+        set_clauses.push(format!("tendered_amount = ${}", bind_count + 2));
+        set_clauses.push(format!("change_amount = ${}", bind_count + 3));
+        bind_count += 3;
     }
 
     if payload.paid_at.is_some() {
@@ -354,16 +707,6 @@ pub async fn update_payment(
         set_clauses.push(format!("external_ref = ${}", bind_count));
     }
 
-    if set_clauses.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                status: "error".to_string(),
-                message: "No fields to update".to_string(),
-            }),
-        ));
-    }
-
     bind_count += 1;
     set_clauses.push(format!("updated_at = ${}", bind_count));
 
@@ -376,12 +719,15 @@ pub async fn update_payment(
 
     let mut query_builder = sqlx::query(&query);
 
-    if let Some(method) = &payload.method {
-        query_builder = query_builder.bind(method.to_uppercase());
+    if let Some(method) = &new_method {
+        query_builder = query_builder.bind(method.as_str());
     }
 
-    if let Some(amount) = payload.amount {
-        query_builder = query_builder.bind(amount);
+    if let Some(allocation) = &allocation {
+        query_builder = query_builder
+            .bind(allocation.applied)
+            .bind(allocation.tendered)
+            .bind(allocation.change);
     }
 
     if let Some(paid_at) = payload.paid_at {
@@ -394,27 +740,41 @@ pub async fn update_payment(
 
     query_builder = query_builder.bind(now).bind(id);
 
-    let result = query_builder.execute(&data.db).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                status: "error".to_string(),
-                message: format!("Database error: {}", e),
-            }),
-        )
-    })?;
+    let result = query_builder
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
 
     if result.rows_affected() == 0 {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                status: "error".to_string(),
-                message: "Payment not found".to_string(),
-            }),
-        ));
+        return Err(payment_not_found());
     }
 
-    let payment = get_payment_by_id_internal(&data.db, id).await?;
+    let consumed = settle_order_if_paid(&mut tx, order_uuid)
+        .await
+        .map_err(database_error)?;
+    // Nominal/metode berubah: pesanan yang sudah PAID dievaluasi ulang
+    let reopened = consumed.is_none()
+        && allocation.is_some()
+        && reopen_order_if_unpaid(&mut tx, order_uuid)
+            .await
+            .map_err(database_error)?;
+
+    tx.commit().await.map_err(database_error)?;
+
+    let settled = consumed.is_some();
+    sale_consumption::recompute_sale_stock(&data.db, &consumed.unwrap_or_default()).await;
+
+    let mut payment = get_payment_by_id_internal(&data.db, id).await?;
+    if let Some(allocation) = allocation {
+        payment.remaining_amount = Some(allocation.remaining_after);
+    }
+    payment.order_status = Some(if settled {
+        "PAID".to_string()
+    } else if reopened {
+        "DRAFT".to_string()
+    } else {
+        balance.status
+    });
 
     Ok(Json(ApiResponse {
         code: 200,
@@ -429,39 +789,77 @@ pub async fn delete_payment(
     State(data): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<ErrorResponse>)> {
-    let now = chrono::Utc::now().timestamp_millis();
-
-    let result =
-        sqlx::query("UPDATE payments SET deleted_at = $1 WHERE uuid = $2 AND deleted_at = 0")
-            .bind(now)
-            .bind(id)
-            .execute(&data.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        status: "error".to_string(),
-                        message: format!("Database error: {}", e),
-                    }),
-                )
-            })?;
-
-    if result.rows_affected() == 0 {
-        return Err((
+    let payment_not_found = || {
+        (
             StatusCode::NOT_FOUND,
             Json(ErrorResponse {
                 status: "error".to_string(),
                 message: "Payment not found".to_string(),
             }),
-        ));
+        )
+    };
+
+    let order_uuid: Uuid =
+        sqlx::query_scalar("SELECT order_uuid FROM payments WHERE uuid = $1 AND deleted_at = 0")
+            .bind(id)
+            .fetch_optional(&data.db)
+            .await
+            .map_err(database_error)?
+            .ok_or_else(payment_not_found)?;
+
+    let mut tx = data.db.begin().await.map_err(database_error)?;
+
+    // Urutan kunci sama dengan update: pesanan dulu, lalu baris pembayaran
+    let balance = lock_order_balance(&mut tx, order_uuid, Some(id))
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: "Order not found".to_string(),
+                }),
+            )
+        })?;
+
+    let refund_locked: bool = sqlx::query_scalar(&format!(
+        "SELECT {} FROM payments WHERE uuid = $1 AND deleted_at = 0 FOR UPDATE",
+        REFUND_LOCKED_SQL
+    ))
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(database_error)?
+    .ok_or_else(payment_not_found)?;
+    if balance.has_refunds || refund_locked {
+        return Err(refund_locked_error());
     }
 
+    let now = chrono::Utc::now().timestamp_millis();
+
+    sqlx::query("UPDATE payments SET deleted_at = $1 WHERE uuid = $2 AND deleted_at = 0")
+        .bind(now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(database_error)?;
+
+    let reopened = reopen_order_if_unpaid(&mut tx, order_uuid)
+        .await
+        .map_err(database_error)?;
+
+    tx.commit().await.map_err(database_error)?;
+
     Ok(Json(ApiResponse {
         code: 200,
         status: "success".to_string(),
         message: "Payment deleted successfully".to_string(),
-        data: serde_json::json!({"deleted": true}),
+        data: serde_json::json!({
+            "deleted": true,
+            "remaining_amount": balance.remaining(),
+            "order_status": if reopened { "DRAFT".to_string() } else { balance.status },
+        }),
         errors: serde_json::json!(null),
     }))
 }
@@ -470,7 +868,11 @@ pub async fn get_payment_stats(
     State(data): State<Arc<AppState>>,
     Query(params): Query<PaymentStatsRequest>,
 ) -> Result<Json<ApiResponse<PaymentStatsResponse>>, (StatusCode, Json<ErrorResponse>)> {
-    let mut conditions = vec!["p.deleted_at = 0".to_string()];
+    // Refund yang masih PROCESSING (paid_at NULL) belum mengembalikan uang
+    let mut conditions = vec![
+        "p.deleted_at = 0".to_string(),
+        "(p.refund_uuid IS NULL OR p.paid_at IS NOT NULL)".to_string(),
+    ];
     let mut bind_count = 0;
 
     if params.date_from.is_some() {
@@ -499,9 +901,11 @@ pub async fn get_payment_stats(
     let stats_query = format!(
         r#"
         SELECT 
-            COUNT(*) as total_payments,
-            COALESCE(SUM(amount), 0) as total_amount,
-            COALESCE(AVG(amount), 0) as avg_payment_amount
+            COUNT(*) FILTER (WHERE refund_uuid IS NULL) as total_payments,
+            COALESCE(SUM(amount) FILTER (WHERE refund_uuid IS NULL), 0) as total_amount,
+            COALESCE(AVG(amount) FILTER (WHERE refund_uuid IS NULL), 0) as avg_payment_amount,
+            COUNT(DISTINCT refund_uuid) as refund_count,
+            COALESCE(-SUM(amount) FILTER (WHERE refund_uuid IS NOT NULL), 0) as refunded_amount
         FROM payments p
         WHERE {}
         "#,
@@ -539,14 +943,17 @@ pub async fn get_payment_stats(
     let total_payments: i64 = stats_row.get("total_payments");
     let total_amount: rust_decimal::Decimal = stats_row.get("total_amount");
     let avg_payment_amount: rust_decimal::Decimal = stats_row.get("avg_payment_amount");
+    let refund_count: i64 = stats_row.get("refund_count");
+    let refunded_amount: rust_decimal::Decimal = stats_row.get("refunded_amount");
 
     // Get payments by method
     let method_query = format!(
         r#"
         SELECT 
             method,
-            COUNT(*) as count,
-            COALESCE(SUM(amount), 0) as total_amount
+            COUNT(*) FILTER (WHERE refund_uuid IS NULL) as count,
+            COALESCE(SUM(amount) FILTER (WHERE refund_uuid IS NULL), 0) as total_amount,
+            COALESCE(-SUM(amount) FILTER (WHERE refund_uuid IS NOT NULL), 0) as refunded_amount
         FROM payments p
         WHERE {}
         GROUP BY method
@@ -590,6 +997,7 @@ pub async fn get_payment_stats(
         .into_iter()
         .map(|row| {
             let method_total: rust_decimal::Decimal = row.get("total_amount");
+            let method_refunded: rust_decimal::Decimal = row.get("refunded_amount");
             let percentage = if total_amount > rust_decimal::Decimal::ZERO {
                 (method_total / total_amount * rust_decimal::Decimal::from(100))
                     .to_string()
@@ -603,6 +1011,8 @@ pub async fn get_payment_stats(
                 method: row.get("method"),
                 count: row.get("count"),
                 total_amount: method_total,
+                refunded_amount: method_refunded,
+                net_amount: method_total - method_refunded,
                 percentage,
             }
         })
@@ -613,9 +1023,10 @@ pub async fn get_payment_stats(
         r#"
         SELECT 
             DATE(to_timestamp(created_at / 1000)) as date,
-            COUNT(*) as payments_count,
-            COALESCE(SUM(amount), 0) as total_amount,
-            COALESCE(AVG(amount), 0) as avg_amount
+            COUNT(*) FILTER (WHERE refund_uuid IS NULL) as payments_count,
+            COALESCE(SUM(amount) FILTER (WHERE refund_uuid IS NULL), 0) as total_amount,
+            COALESCE(AVG(amount) FILTER (WHERE refund_uuid IS NULL), 0) as avg_amount,
+            COALESCE(-SUM(amount) FILTER (WHERE refund_uuid IS NOT NULL), 0) as refunded_amount
         FROM payments p
         WHERE {}
         GROUP BY DATE(to_timestamp(created_at / 1000))
@@ -655,11 +1066,17 @@ pub async fn get_payment_stats(
 
     let daily_stats: Vec<DailyPaymentStats> = daily_rows
         .into_iter()
-        .map(|row| DailyPaymentStats {
-            date: row.get::<chrono::NaiveDate, _>("date").to_string(),
-            payments_count: row.get("payments_count"),
-            total_amount: row.get("total_amount"),
-            avg_amount: row.get("avg_amount"),
+        .map(|row| {
+            let total_amount: rust_decimal::Decimal = row.get("total_amount");
+            let refunded_amount: rust_decimal::Decimal = row.get("refunded_amount");
+            DailyPaymentStats {
+                date: row.get::<chrono::NaiveDate, _>("date").to_string(),
+                payments_count: row.get("payments_count"),
+                total_amount,
+                avg_amount: row.get("avg_amount"),
+                refunded_amount,
+                net_amount: total_amount - refunded_amount,
+            }
         })
         .collect();
 
@@ -671,6 +1088,9 @@ pub async fn get_payment_stats(
             total_payments,
             total_amount,
             avg_payment_amount,
+            refund_count,
+            refunded_amount,
+            net_amount: total_amount - refunded_amount,
             payments_by_method,
             daily_stats,
         },
@@ -683,20 +1103,21 @@ pub async fn get_payments_by_order(
     Path(order_uuid): Path<Uuid>,
 ) -> Result<Json<ApiResponse<PaymentsByOrderResponse>>, (StatusCode, Json<ErrorResponse>)> {
     // Get order details (runtime query)
-    let order_row_opt =
-        sqlx::query("SELECT uuid, order_no, total FROM orders WHERE uuid = $1 AND deleted_at = 0")
-            .bind(order_uuid)
-            .fetch_optional(&data.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        status: "error".to_string(),
-                        message: format!("Database error: {}", e),
-                    }),
-                )
-            })?;
+    let order_row_opt = sqlx::query(
+        "SELECT uuid, order_no, status, total FROM orders WHERE uuid = $1 AND deleted_at = 0",
+    )
+    .bind(order_uuid)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Database error: {}", e),
+            }),
+        )
+    })?;
 
     let order_row = match order_row_opt {
         Some(r) => r,
@@ -712,13 +1133,15 @@ pub async fn get_payments_by_order(
     };
 
     let order_no: String = order_row.get("order_no");
+    let order_status: String = order_row.get("status");
     let order_total: rust_decimal::Decimal = order_row.get("total");
 
     // Get payments for this order (runtime query)
     let payment_rows = sqlx::query(
         r#"
         SELECT 
-            uuid, order_uuid, method, amount, paid_at, external_ref, created_at, updated_at
+            uuid, order_uuid, method, amount, tendered_amount, change_amount,
+            paid_at, external_ref, refund_uuid, created_at, updated_at
         FROM payments 
         WHERE order_uuid = $1 AND deleted_at = 0
         ORDER BY created_at DESC
@@ -745,16 +1168,34 @@ pub async fn get_payments_by_order(
             order_no: Some(order_no.clone()),
             method: row.get("method"),
             amount: row.get("amount"),
+            tendered_amount: row.get("tendered_amount"),
+            change_amount: row.get("change_amount"),
             paid_at: row.get("paid_at"),
             external_ref: row.get("external_ref"),
+            refund_uuid: row.get("refund_uuid"),
             created_at: row.get::<Option<i64>, _>("created_at").or(Some(0)),
             updated_at: row.get::<Option<i64>, _>("updated_at").or(Some(0)),
+            remaining_amount: None,
+            order_status: None,
         })
         .collect();
 
-    let total_paid: rust_decimal::Decimal = payment_responses.iter().map(|p| p.amount).sum();
-
-    let remaining_amount = order_total - total_paid;
+    // Refund dijumlahkan terpisah agar total_paid tetap nominal yang pernah dibayar
+    let (refunds, tenders): (Vec<&PaymentResponse>, Vec<&PaymentResponse>) = payment_responses
+        .iter()
+        .partition(|p| p.refund_uuid.is_some());
+    let (completed, pending): (Vec<&PaymentResponse>, Vec<&PaymentResponse>) =
+        tenders.into_iter().partition(|p| p.paid_at.is_some());
+    let total_paid: rust_decimal::Decimal = completed.iter().map(|p| p.amount).sum();
+    let total_refunded: rust_decimal::Decimal = refunds
+        .iter()
+        .filter(|p| p.paid_at.is_some())
+        .map(|p| -p.amount)
+        .sum();
+    let pending_amount: rust_decimal::Decimal = pending.iter().map(|p| p.amount).sum();
+    let change_given: rust_decimal::Decimal = completed.iter().map(|p| p.change_amount).sum();
+
+    let remaining_amount = remaining_balance(order_total, total_paid + pending_amount);
 
     Ok(Json(ApiResponse {
         code: 200,
@@ -763,9 +1204,13 @@ pub async fn get_payments_by_order(
         data: PaymentsByOrderResponse {
             order_uuid,
             order_no: order_no,
+            order_status,
             order_total: order_total,
             total_paid,
+            pending_amount,
             remaining_amount,
+            change_given,
+            total_refunded,
             payments: payment_responses,
         },
         errors: serde_json::json!(null),
@@ -818,38 +1263,24 @@ async fn create_qris_payment_internal(
     })?;
 
     // Lock order row to prevent race conditions (runtime query)
-    let order_row_opt = sqlx::query(
-        "SELECT uuid, order_no, total FROM orders WHERE uuid = $1 AND deleted_at = 0 FOR UPDATE",
-    )
-    .bind(payload.order_uuid)
-    .fetch_optional(&mut *tx)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                status: "error".to_string(),
-                message: format!("Database error: {}", e),
-            }),
-        )
-    })?;
-
-    let order_row = match order_row_opt {
-        Some(r) => r,
-        None => {
-            return Err((
+    let balance = lock_order_balance(&mut tx, payload.order_uuid, None)
+        .await
+        .map_err(database_error)?
+        .ok_or_else(|| {
+            (
                 StatusCode::NOT_FOUND,
                 Json(ErrorResponse {
                     status: "error".to_string(),
                     message: "Order not found".to_string(),
                 }),
-            ))
-        }
-    };
+            )
+        })?;
+    ensure_order_accepts_payments(&balance)?;
 
-    // Check existing QRIS payment and lock it if exists
+    // Check existing pending QRIS payment and lock it if exists; a completed QRIS
+    // (split tender) does not block a new QR for the remaining balance
     let existing = sqlx::query(
-        r#"SELECT uuid, external_ref, paid_at FROM payments WHERE order_uuid = $1 AND method = 'QRIS' AND deleted_at = 0 ORDER BY created_at DESC LIMIT 1 FOR UPDATE"#
+        r#"SELECT uuid, external_ref, paid_at FROM payments WHERE order_uuid = $1 AND method = 'QRIS' AND paid_at IS NULL AND deleted_at = 0 ORDER BY created_at DESC LIMIT 1 FOR UPDATE"#
     )
     .bind(payload.order_uuid)
     .fetch_optional(&mut *tx)
@@ -863,11 +1294,12 @@ async fn create_qris_payment_internal(
 
     // Either reuse existing QR code or create a new one
     let (payment_uuid, external_ref, xendit_info) = if let Some(row) = existing {
-        // If already completed, just return status without creating new QR
+        // Pending QR already exists, return it instead of creating a new one
         let existing_ext_ref: Option<String> = row.get("external_ref");
         let existing_uuid: Uuid = row.get("uuid");
         let qr = xnd::get_qr_code(
             &client,
+            &data.env.xendit_base_url,
             secret_key,
             existing_ext_ref.as_deref().unwrap_or(""),
         )
@@ -893,9 +1325,10 @@ async fn create_qris_payment_internal(
 
         (existing_uuid, qr.id, info)
     } else {
-        // Create new QR code
-        let order_no: String = order_row.get("order_no");
-        let reference_id = format!("ORDER-{}", order_no);
+        // Create new QR code; QRIS tidak boleh melebihi sisa tagihan (tanpa kembalian)
+        allocate(&PaymentMethod::Qris, payload.amount, balance.remaining())
+            .map_err(allocation_error)?;
+        let reference_id = format!("ORDER-{}", balance.order_no);
         let amount_i64 = payload.amount.to_i64().ok_or_else(|| {
             (
                 StatusCode::BAD_REQUEST,
@@ -906,25 +1339,32 @@ async fn create_qris_payment_internal(
             )
         })?;
 
-        let qr = xnd::create_qr_code(&client, secret_key, &reference_id, amount_i64, callback_url)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        status: "error".to_string(),
-                        message: format!("Xendit create QR error: {}", e),
-                    }),
-                )
-            })?;
+        let qr = xnd::create_qr_code(
+            &client,
+            &data.env.xendit_base_url,
+            secret_key,
+            &reference_id,
+            amount_i64,
+            callback_url,
+        )
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    status: "error".to_string(),
+                    message: format!("Xendit create QR error: {}", e),
+                }),
+            )
+        })?;
 
         let now = chrono::Utc::now().timestamp_millis();
         let new_payment_uuid = Uuid::new_v4();
 
         sqlx::query(
             r#"
-            INSERT INTO payments (uuid, order_uuid, method, amount, paid_at, external_ref, created_at, updated_at)
-            VALUES ($1, $2, 'QRIS', $3, $4, $5, $6, $7)
+            INSERT INTO payments (uuid, order_uuid, method, amount, tendered_amount, paid_at, external_ref, created_at, updated_at)
+            VALUES ($1, $2, 'QRIS', $3, $3, $4, $5, $6, $7)
             "#
         )
         .bind(new_payment_uuid)
@@ -1014,17 +1454,22 @@ async fn get_qris_status_internal(
     })?;
 
     let client = Client::new();
-    let qr = xnd::get_qr_code(&client, secret_key, &external_ref)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    status: "error".to_string(),
-                    message: format!("Xendit get QR error: {}", e),
-                }),
-            )
-        })?;
+    let qr = xnd::get_qr_code(
+        &client,
+        &data.env.xendit_base_url,
+        secret_key,
+        &external_ref,
+    )
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                status: "error".to_string(),
+                message: format!("Xendit get QR error: {}", e),
+            }),
+        )
+    })?;
 
     let mut tx = data.db.begin().await.map_err(|e| {
         (
@@ -1037,7 +1482,7 @@ async fn get_qris_status_internal(
     })?;
 
     let payment_row = sqlx::query(
-        r#"SELECT uuid, paid_at FROM payments WHERE external_ref = $1 AND method = 'QRIS' AND deleted_at = 0 FOR UPDATE"#
+        r#"SELECT uuid, order_uuid, paid_at FROM payments WHERE external_ref = $1 AND method = 'QRIS' AND refund_uuid IS NULL AND deleted_at = 0"#
     )
     .bind(&external_ref)
     .fetch_optional(&mut *tx)
@@ -1048,27 +1493,15 @@ async fn get_qris_status_internal(
     ))?;
 
     let mut payment_uuid_opt: Option<Uuid> = None;
+    let mut consumed = Vec::new();
     if let Some(pr) = payment_row {
         let pr_uuid: Uuid = pr.get("uuid");
         let pr_paid_at: Option<i64> = pr.get("paid_at");
         payment_uuid_opt = Some(pr_uuid);
         if qr.status.to_uppercase() == "COMPLETED" && pr_paid_at.is_none() {
-            let now = chrono::Utc::now().timestamp_millis();
-            sqlx::query("UPDATE payments SET paid_at = $1, updated_at = $2 WHERE uuid = $3")
-                .bind(now)
-                .bind(now)
-                .bind(pr_uuid)
-                .execute(&mut *tx)
+            consumed = complete_qris_payment(&mut tx, pr_uuid, pr.get("order_uuid"))
                 .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ErrorResponse {
-                            status: "error".to_string(),
-                            message: format!("Database error: {}", e),
-                        }),
-                    )
-                })?;
+                .map_err(database_error)?;
         }
     }
 
@@ -1082,6 +1515,8 @@ async fn get_qris_status_internal(
         )
     })?;
 
+    sale_consumption::recompute_sale_stock(&data.db, &consumed).await;
+
     Ok(Json(ApiResponse {
         code: 200,
         status: "success".to_string(),
@@ -1169,7 +1604,7 @@ pub async fn xendit_webhook_handler(
     })?;
 
     let payment_row = sqlx::query(
-        r#"SELECT uuid, paid_at FROM payments WHERE external_ref = $1 AND method = 'QRIS' AND deleted_at = 0 FOR UPDATE"#
+        r#"SELECT uuid, order_uuid, paid_at FROM payments WHERE external_ref = $1 AND method = 'QRIS' AND refund_uuid IS NULL AND deleted_at = 0"#
     )
     .bind(qr_id)
     .fetch_optional(&mut *tx)
@@ -1179,26 +1614,14 @@ pub async fn xendit_webhook_handler(
         Json(ErrorResponse { status: "error".to_string(), message: format!("Database error: {}", e) }),
     ))?;
 
+    let mut consumed = Vec::new();
     if let Some(pr) = payment_row {
         let pr_uuid: Uuid = pr.get("uuid");
         let pr_paid_at: Option<i64> = pr.get("paid_at");
         if status.to_uppercase() == "COMPLETED" && pr_paid_at.is_none() {
-            let now = chrono::Utc::now().timestamp_millis();
-            sqlx::query("UPDATE payments SET paid_at = $1, updated_at = $2 WHERE uuid = $3")
-                .bind(now)
-                .bind(now)
-                .bind(pr_uuid)
-                .execute(&mut *tx)
+            consumed = complete_qris_payment(&mut tx, pr_uuid, pr.get("order_uuid"))
                 .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ErrorResponse {
-                            status: "error".to_string(),
-                            message: format!("Database error: {}", e),
-                        }),
-                    )
-                })?;
+                .map_err(database_error)?;
         }
     }
 
@@ -1212,6 +1635,8 @@ pub async fn xendit_webhook_handler(
         )
     })?;
 
+    sale_consumption::recompute_sale_stock(&data.db, &consumed).await;
+
     Ok(Json(ApiResponse {
         code: 200,
         status: "success".to_string(),
@@ -0,0 +1,528 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{AppendHeaders, IntoResponse},
+    Extension, Json,
+};
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        api::ApiResponse,
+        auth_token::{self, generate_action_token, hash_action_token},
+        pos_devices::{
+            is_valid_pin, EnrollPosDeviceRequest, PinSessionResponse, PinUnlockRequest,
+            PosDeviceEnrolledResponse, PosDeviceListQuery, PosDeviceResponse, SetPinRequest,
+        },
+    },
+    handlers::auth::{
+        client_metadata, db_error, error_response, remove_session_keys, save_token_data_to_redis,
+    },
+    handlers::auth_account::{hash_password, verify_password},
+    handlers::auth_sessions::current_session,
+    handlers::auth_two_factor::two_factor_in_use,
+    middleware::jwt::{JWTAuthMiddleware, DEVICE_TOKEN_HEADER},
+    models::auth_tokens::REVOKED_REASON_SIGNED_OUT,
+    models::pos_devices::{
+        PosDevice, EVENT_PIN_UNLOCK_FAILED, EVENT_POS_DEVICE_ENROLLED, EVENT_POS_DEVICE_REVOKED,
+        REVOKED_REASON_DEVICE_REVOKED,
+    },
+    models::roles::{SESSION_ADMIN_ROLE_NUMBERS, SUPER_ADMIN_ROLE_NUMBER},
+    repository::auth as auth_repository,
+    repository::auth_tokens::{self as auth_tokens_repository, SessionMetadata},
+    repository::pos_devices as pos_devices_repository,
+    services::login_throttle::{LoginThrottle, LoginThrottlePolicy},
+    AppState,
+};
+
+const MAX_DEVICE_NAME_LENGTH: usize = 100;
+
+// POST /api/v1/pos/devices: owner/admin mendaftarkan terminal POS bersama ke toko
+pub async fn enroll_device_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<EnrollPosDeviceRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let store_uuid = device_admin_store(&data, &jwt_auth, body.store_uuid).await?;
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_DEVICE_NAME_LENGTH {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "Nama perangkat wajib diisi (maksimal {} karakter)",
+                MAX_DEVICE_NAME_LENGTH
+            ),
+        ));
+    }
+
+    let (device_token, credential_hash) = generate_action_token();
+    let device = pos_devices_repository::create_device(
+        &data.db,
+        store_uuid,
+        name,
+        &credential_hash,
+        jwt_auth.user.uuid,
+    )
+    .await
+    .map_err(db_error)?;
+    record_pos_event(
+        &data,
+        Some(jwt_auth.user.uuid),
+        EVENT_POS_DEVICE_ENROLLED,
+        &request_headers,
+        json!({ "device_uuid": device.uuid, "store_uuid": store_uuid, "name": device.name }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "CREATED".to_string(),
+            message: "POS device enrolled successfully".to_string(),
+            data: PosDeviceEnrolledResponse {
+                device: device.into(),
+                device_token,
+            },
+            errors: json!({}),
+        }),
+    ))
+}
+
+// GET /api/v1/pos/devices
+pub async fn list_devices_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    Query(query): Query<PosDeviceListQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let store_uuid = device_admin_store(&data, &jwt_auth, query.store_uuid).await?;
+    let devices = pos_devices_repository::list_devices(&data.db, store_uuid)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Get POS devices successfully".to_string(),
+        data: devices
+            .into_iter()
+            .map(PosDeviceResponse::from)
+            .collect::<Vec<_>>(),
+        errors: json!({}),
+    }))
+}
+
+// DELETE /api/v1/pos/devices/:id: cabut perangkat (hilang/dicuri) beserta sesi PIN di dalamnya
+pub async fn revoke_device_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Path(device_uuid): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let not_found = || error_response(StatusCode::NOT_FOUND, "POS device not found");
+    let device = pos_devices_repository::find_device(&data.db, device_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(not_found)?;
+    device_admin_store(&data, &jwt_auth, Some(device.store_uuid)).await?;
+
+    let token_uuids =
+        pos_devices_repository::revoke_device(&data.db, device_uuid, REVOKED_REASON_DEVICE_REVOKED)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(not_found)?;
+    remove_session_keys(&data, &token_uuids).await;
+    record_pos_event(
+        &data,
+        Some(jwt_auth.user.uuid),
+        EVENT_POS_DEVICE_REVOKED,
+        &request_headers,
+        json!({ "device_uuid": device_uuid, "store_uuid": device.store_uuid }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "POS device revoked successfully".to_string(),
+        data: json!({}),
+        errors: json!({}),
+    }))
+}
+
+// PUT /api/v1/pos/pin: staf memasang / mengganti PIN kasirnya sendiri
+pub async fn set_pin_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<SetPinRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_password_session(&jwt_auth)?;
+    if !verify_password(&jwt_auth.user.password, &body.current_password) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "Password saat ini salah",
+        ));
+    }
+    if !is_valid_pin(&body.pin) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "PIN harus berupa 4-6 digit angka",
+        ));
+    }
+
+    let pin_hash = hash_password(&body.pin)?;
+    pos_devices_repository::upsert_pin(&data.db, jwt_auth.user.uuid, &pin_hash)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "PIN saved successfully".to_string(),
+        data: json!({}),
+        errors: json!({}),
+    }))
+}
+
+// DELETE /api/v1/pos/pin
+pub async fn delete_pin_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    ensure_password_session(&jwt_auth)?;
+    let deleted = pos_devices_repository::delete_pin(&data.db, jwt_auth.user.uuid)
+        .await
+        .map_err(db_error)?;
+    if !deleted {
+        return Err(error_response(StatusCode::NOT_FOUND, "PIN not set"));
+    }
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "PIN removed successfully".to_string(),
+        data: json!({}),
+        errors: json!({}),
+    }))
+}
+
+// GET /api/v1/pos/staff: daftar kasir untuk layar pilih kasir (autentikasi perangkat)
+pub async fn list_pos_staff_handler(
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let device = authenticate_device(&data, &request_headers).await?;
+    let staff = pos_devices_repository::list_staff_with_pin(&data.db, device.store_uuid)
+        .await
+        .map_err(db_error)?;
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Get POS staff successfully".to_string(),
+        data: staff,
+        errors: json!({}),
+    }))
+}
+
+/// ID: Buka terminal dengan PIN kasir. Menghasilkan access token berumur pendek yang terikat ke
+///     perangkat dan toko ini; sesi PIN sebelumnya di perangkat yang sama ditutup (ganti shift).
+/// EN: Unlock the terminal with a cashier PIN. Issues a short-lived access token bound to this
+///     device and store; the previous PIN session on the same device is closed (shift change).
+pub async fn pin_unlock_handler(
+    State(data): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Json(body): Json<PinUnlockRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let device = authenticate_device(&data, &request_headers).await?;
+    let (ip_address, user_agent) = client_metadata(&request_headers);
+    let throttle = LoginThrottle::new(
+        &data.redis_client,
+        &data.session_store,
+        LoginThrottlePolicy::from_config(&data.env),
+    );
+    let throttle_key = pin_throttle_key(body.user_uuid);
+    let device_key = format!("pos:{}", device.uuid);
+    if let Some(retry_after) = throttle
+        .locked_for(&throttle_key, Some(device_key.as_str()))
+        .await
+    {
+        return Err(pin_locked_response(retry_after));
+    }
+
+    let pin_hash =
+        pos_devices_repository::find_store_pin_hash(&data.db, body.user_uuid, device.store_uuid)
+            .await
+            .map_err(db_error)?;
+    let is_valid = pin_hash
+        .as_deref()
+        .is_some_and(|pin_hash| verify_password(pin_hash, &body.pin));
+    if !is_valid {
+        let failed = throttle
+            .record_failure(&throttle_key, Some(device_key.as_str()))
+            .await;
+        if let Err(e) = auth_tokens_repository::insert_security_event(
+            &data.db,
+            pin_hash.as_ref().map(|_| body.user_uuid),
+            None,
+            EVENT_PIN_UNLOCK_FAILED,
+            ip_address.as_deref(),
+            user_agent.as_deref(),
+            json!({
+                "device_uuid": device.uuid,
+                "user_failures": failed.username_failures,
+                "device_failures": failed.ip_failures,
+            }),
+        )
+        .await
+        {
+            tracing::error!("Failed to record security event: {:?}", e);
+        }
+        tokio::time::sleep(failed.delay).await;
+        if failed.username_locked || failed.ip_locked {
+            return Err(pin_locked_response(data.env.login_lockout_seconds));
+        }
+        return Err(error_response(StatusCode::BAD_REQUEST, "PIN salah"));
+    }
+    throttle.record_success(&throttle_key).await;
+
+    // Akun dengan 2FA wajib login password + kode; PIN tidak boleh melewati faktor kedua
+    if two_factor_in_use(&data, body.user_uuid).await? {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "PIN unlock is not available for accounts that sign in with two-factor authentication",
+        ));
+    }
+
+    let user = auth_repository::find_user_by_uuid(&data.db, body.user_uuid)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| error_response(StatusCode::NOT_FOUND, "User not found"))?;
+    let access_token_details = auth_token::generate_pos_jwt_token(
+        user.uuid,
+        device.uuid,
+        data.env.pos_pin_session_minutes,
+        data.env.access_token_private_key.to_owned(),
+    )
+    .map_err(|e| {
+        error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &format!("error generating token: {}", e),
+        )
+    })?;
+    save_token_data_to_redis(
+        &data,
+        &access_token_details,
+        data.env.pos_pin_session_minutes * 60,
+    )
+    .await?;
+
+    let expires_at =
+        chrono::Utc::now().timestamp_millis() + data.env.pos_pin_session_minutes * 60_000;
+    let family_uuid = auth_tokens_repository::create_pos_session(
+        &data.db,
+        user.uuid,
+        device.uuid,
+        access_token_details.token_uuid,
+        expires_at,
+        &SessionMetadata {
+            device_name: Some(device.name.clone()),
+            user_agent,
+            ip_address,
+        },
+    )
+    .await
+    .map_err(db_error)?;
+    let superseded = pos_devices_repository::revoke_device_sessions(
+        &data.db,
+        device.uuid,
+        Some(family_uuid),
+        REVOKED_REASON_SIGNED_OUT,
+    )
+    .await
+    .map_err(db_error)?;
+    remove_session_keys(&data, &superseded).await;
+
+    let access_token = access_token_details.token.unwrap_or_default();
+    // Terminal bersama: cookie sesi sebelumnya diganti agar request berikutnya atas nama kasir ini
+    let access_cookie = Cookie::build(("access_token", access_token.clone()))
+        .path("/")
+        .max_age(time::Duration::minutes(data.env.pos_pin_session_minutes))
+        .same_site(SameSite::Lax)
+        .http_only(true);
+    let refresh_cookie = Cookie::build(("refresh_token", ""))
+        .path("/")
+        .max_age(time::Duration::minutes(-1))
+        .same_site(SameSite::Lax)
+        .http_only(true);
+
+    Ok((
+        AppendHeaders([
+            (header::SET_COOKIE, access_cookie.to_string()),
+            (header::SET_COOKIE, refresh_cookie.to_string()),
+        ]),
+        Json(ApiResponse {
+            code: 200,
+            status: "OK".to_string(),
+            message: "POS unlocked".to_string(),
+            data: PinSessionResponse {
+                access_token,
+                expires_at,
+                user_uuid: user.uuid,
+                username: user.username,
+                store_uuid: device.store_uuid,
+                device_uuid: device.uuid,
+            },
+            errors: json!({}),
+        }),
+    ))
+}
+
+// POST /api/v1/pos/lock: kasir menutup sesi PIN-nya (terminal kembali ke layar PIN)
+pub async fn pin_lock_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if jwt_auth.pos_device.is_none() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            "This is not a cashier PIN session",
+        ));
+    }
+    let token_uuids = match current_session(&data, &jwt_auth).await? {
+        Some(family_uuid) => auth_tokens_repository::revoke_token_family(
+            &data.db,
+            family_uuid,
+            REVOKED_REASON_SIGNED_OUT,
+        )
+        .await
+        .map_err(db_error)?,
+        None => vec![jwt_auth.access_token_uuid],
+    };
+    remove_session_keys(&data, &token_uuids).await;
+
+    let access_cookie = Cookie::build(("access_token", ""))
+        .path("/")
+        .max_age(time::Duration::minutes(-1))
+        .same_site(SameSite::Lax)
+        .http_only(true);
+    Ok((
+        AppendHeaders([(header::SET_COOKIE, access_cookie.to_string())]),
+        Json(ApiResponse {
+            code: 200,
+            status: "OK".to_string(),
+            message: "POS locked".to_string(),
+            data: json!({}),
+            errors: json!({}),
+        }),
+    ))
+}
+
+/// ID: Kunci throttle PIN per staf, terpisah dari kuncian login password.
+/// EN: Per-staff PIN throttle key, separate from the password login lockout.
+pub(crate) fn pin_throttle_key(user_uuid: Uuid) -> String {
+    format!("pin:{}", user_uuid)
+}
+
+// Perangkat aktif dari header X-Device-Token
+async fn authenticate_device(
+    data: &Arc<AppState>,
+    request_headers: &HeaderMap,
+) -> Result<PosDevice, (StatusCode, Json<serde_json::Value>)> {
+    let unauthorized = || {
+        error_response(
+            StatusCode::UNAUTHORIZED,
+            "POS device is not enrolled or has been revoked",
+        )
+    };
+    let credential = request_headers
+        .get(DEVICE_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(unauthorized)?;
+    pos_devices_repository::authenticate_device(&data.db, &hash_action_token(credential))
+        .await
+        .map_err(db_error)?
+        .ok_or_else(unauthorized)
+}
+
+// ID: Toko yang perangkatnya boleh dikelola pemanggil. Super Admin wajib menyebut toko;
+//     Admin/Owner hanya toko profilnya sendiri. Sesi PIN tidak boleh mengelola perangkat.
+// EN: The store whose devices the caller may manage. Super Admin must name the store;
+//     Admin/Owner only their own profile store. PIN sessions cannot manage devices.
+async fn device_admin_store(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    requested_store: Option<Uuid>,
+) -> Result<Uuid, (StatusCode, Json<serde_json::Value>)> {
+    let forbidden = || {
+        error_response(
+            StatusCode::FORBIDDEN,
+            "You are not allowed to manage POS devices for this store",
+        )
+    };
+    ensure_password_session(jwt_auth)?;
+    let (caller_store, caller_role) =
+        auth_tokens_repository::find_profile_scope(&data.db, jwt_auth.user.uuid)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(forbidden)?;
+    let caller_role = caller_role.ok_or_else(forbidden)?;
+    if !SESSION_ADMIN_ROLE_NUMBERS.contains(&caller_role) {
+        return Err(forbidden());
+    }
+    if caller_role == SUPER_ADMIN_ROLE_NUMBER {
+        return requested_store
+            .or(caller_store)
+            .ok_or_else(|| error_response(StatusCode::BAD_REQUEST, "store_uuid wajib diisi"));
+    }
+    match (caller_store, requested_store) {
+        (Some(caller_store), None) => Ok(caller_store),
+        (Some(caller_store), Some(requested)) if caller_store == requested => Ok(caller_store),
+        _ => Err(forbidden()),
+    }
+}
+
+pub(crate) fn ensure_password_session(
+    jwt_auth: &JWTAuthMiddleware,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if jwt_auth.pos_device.is_some() {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "Sign in with your password to do this; a cashier PIN session is not enough",
+        ));
+    }
+    Ok(())
+}
+
+async fn record_pos_event(
+    data: &Arc<AppState>,
+    user_uuid: Option<Uuid>,
+    event_type: &str,
+    request_headers: &HeaderMap,
+    details: serde_json::Value,
+) {
+    let (ip_address, user_agent) = client_metadata(request_headers);
+    if let Err(e) = auth_tokens_repository::insert_security_event(
+        &data.db,
+        user_uuid,
+        None,
+        event_type,
+        ip_address.as_deref(),
+        user_agent.as_deref(),
+        details,
+    )
+    .await
+    {
+        tracing::error!("Failed to record security event: {:?}", e);
+    }
+}
+
+fn pin_locked_response(retry_after_seconds: u64) -> (StatusCode, Json<serde_json::Value>) {
+    let (status, Json(mut body)) = error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        "Terlalu banyak percobaan PIN salah, coba lagi nanti",
+    );
+    body["data"] = json!({ "retry_after_seconds": retry_after_seconds });
+    (status, Json(body))
+}
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::api::ApiResponse;
+use crate::dto::production_runs::{CreateProductionRunRequest, ProductionRunListRequest};
+use crate::middleware::jwt::JWTAuthMiddleware;
+use crate::models::production_runs::ProductionRunWithItems;
+use crate::repository::production_runs as production_runs_repo;
+use crate::repository::production_runs::NewProductionRun;
+use crate::repository::recipe_items as recipe_items_repository;
+use crate::repository::recipe_sets as recipe_sets_repository;
+use crate::repository::stores as stores_repository;
+use crate::services::recipe_graph::RecipeGraphError;
+use crate::AppState;
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({
+            "status": "error",
+            "message": format!("{:?}", e)
+        })),
+    )
+}
+
+fn validation_error(e: validator::ValidationErrors) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "fail",
+            "message": "Validation error",
+            "errors": e,
+        })),
+    )
+}
+
+fn fail(status: StatusCode, message: String) -> (StatusCode, Json<Value>) {
+    (
+        status,
+        Json(json!({
+            "status": "fail",
+            "message": message
+        })),
+    )
+}
+
+async fn load_production_run_with_items(
+    data: &AppState,
+    id: Uuid,
+) -> Result<ProductionRunWithItems, (StatusCode, Json<Value>)> {
+    let production_run = production_runs_repo::get_production_run(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            fail(
+                StatusCode::NOT_FOUND,
+                format!("Produksi dengan ID: {} tidak ditemukan", id),
+            )
+        })?;
+
+    let (recipe_name, output_ingredient_name) =
+        production_runs_repo::get_production_run_labels(&data.db, &production_run)
+            .await
+            .map_err(internal_error)?;
+    let items = production_runs_repo::get_production_run_items(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(ProductionRunWithItems {
+        production_run,
+        recipe_name,
+        output_ingredient_name,
+        items,
+    })
+}
+
+// ID: Handler untuk produksi batch: konsumsi bahan resep dan menambah stok bahan setengah jadi
+// EN: Handler for a batch production run: consumes recipe ingredients and stocks the semi-finished item
+pub async fn create_production_run_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<CreateProductionRunRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+
+    let recipe =
+        recipe_sets_repository::get_recipe_set_model_by_uuid(&data.db, body.recipe_sets_uuid)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| {
+                fail(
+                    StatusCode::NOT_FOUND,
+                    format!(
+                        "Recipe set dengan ID: {} tidak ditemukan",
+                        body.recipe_sets_uuid
+                    ),
+                )
+            })?;
+
+    let output_ingredient_catalog_uuid =
+        recipe.output_ingredient_catalog_uuid.ok_or_else(|| {
+            fail(
+                StatusCode::BAD_REQUEST,
+                "Recipe set belum memiliki output_ingredient_catalog_uuid (bahan setengah jadi)"
+                    .to_string(),
+            )
+        })?;
+
+    let output_quantity = body
+        .output_quantity
+        .or(recipe.yield_quantity)
+        .filter(|qty| *qty > Decimal::ZERO)
+        .unwrap_or(Decimal::ONE);
+
+    let graph = recipe_items_repository::load_recipe_graph(&data.db)
+        .await
+        .map_err(internal_error)?;
+    let consumption: Vec<(Uuid, Decimal)> = graph
+        .explode(recipe.uuid, output_quantity, true)
+        .map_err(|e| match e {
+            RecipeGraphError::Cycle(_) => fail(StatusCode::CONFLICT, e.to_string()),
+            RecipeGraphError::UnknownRecipe(_) => fail(StatusCode::NOT_FOUND, e.to_string()),
+        })?
+        .into_iter()
+        .map(|(uuid, qty)| (uuid, qty.round_dp(4)))
+        .filter(|(_, qty)| *qty > Decimal::ZERO)
+        .collect();
+
+    if consumption.is_empty() {
+        return Err(fail(
+            StatusCode::BAD_REQUEST,
+            "Recipe set belum memiliki bahan".to_string(),
+        ));
+    }
+
+    let user_uuid = jwt_auth.user.uuid;
+    let store_uuid = stores_repository::get_store_by_user_uuid(&data.db, user_uuid)
+        .await
+        .map_err(internal_error)?
+        .map(|store| store.uuid);
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let new_run = NewProductionRun {
+        recipe_sets_uuid: recipe.uuid,
+        recipe_name: recipe.name,
+        output_ingredient_catalog_uuid,
+        output_quantity,
+        store_uuid,
+        notes: body.notes,
+        produced_at: body.produced_at.unwrap_or(current_time),
+        produced_by: Some(user_uuid),
+        consumption,
+    };
+
+    let run_uuid = production_runs_repo::create_production_run(&data.db, &new_run, current_time)
+        .await
+        .map_err(internal_error)?;
+
+    let production_run = load_production_run_with_items(&data, run_uuid).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "success".to_string(),
+            message: "Produksi berhasil diposting".to_string(),
+            data: production_run,
+            errors: json!({}),
+        }),
+    ))
+}
+
+// ID: Handler untuk daftar produksi
+// EN: Handler to list production runs
+pub async fn get_production_runs_handler(
+    Query(opts): Query<ProductionRunListRequest>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let page = opts.page.unwrap_or(1).max(1);
+    let limit = opts.limit.unwrap_or(10).clamp(1, 100);
+
+    let (runs, total) = production_runs_repo::list_production_runs(&data.db, page, limit, &opts)
+        .await
+        .map_err(internal_error)?;
+
+    let total_pages = ((total + limit as i64 - 1) / limit as i64).max(1);
+    let has_prev = page > 1;
+    let has_next = (page as i64) < total_pages;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Daftar produksi berhasil diambil".to_string(),
+        data: json!({
+            "production_runs": runs,
+            "meta": {
+                "page": page,
+                "limit": limit,
+                "total_records": total,
+                "total_pages": total_pages,
+                "has_prev": has_prev,
+                "has_next": has_next,
+                "prev_page": if has_prev { Some(page - 1) } else { None },
+                "next_page": if has_next { Some(page + 1) } else { None }
+            }
+        }),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk detail produksi beserta bahan yang dikonsumsi
+// EN: Handler to get a production run with its consumed ingredients
+pub async fn get_production_run_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let production_run = load_production_run_with_items(&data, id).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Produksi berhasil diambil".to_string(),
+        data: production_run,
+        errors: json!({}),
+    }))
+}
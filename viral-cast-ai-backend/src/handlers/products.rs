@@ -12,6 +12,7 @@ use uuid::Uuid;
 use crate::dto::i18n::I18nEntity;
 use crate::repository::i18n as i18n_repository;
 use crate::repository::products as products_repository;
+use crate::services::image_pipeline;
 use crate::{
     dto::{
         api::ApiResponse,
@@ -50,8 +51,17 @@ pub async fn create_product_handler(
         return Err((StatusCode::BAD_REQUEST, Json(error_response)));
     }
 
-    let query_result =
-        products_repository::create_product(&data.db, product_uuid, body, current_time).await;
+    let image_variants =
+        image_pipeline::stored_variants_json(data.blob_store.as_ref(), body.image_url.as_deref())
+            .await;
+    let query_result = products_repository::create_product(
+        &data.db,
+        product_uuid,
+        body,
+        image_variants,
+        current_time,
+    )
+    .await;
 
     match query_result {
         Ok(product_response) => {
@@ -252,7 +262,11 @@ pub async fn update_product_handler(
         }
     }
 
-    let update_result = products_repository::update_product(&data.db, id, body, current_time).await;
+    let image_variants =
+        image_pipeline::stored_variants_json(data.blob_store.as_ref(), body.image_url.as_deref())
+            .await;
+    let update_result =
+        products_repository::update_product(&data.db, id, body, image_variants, current_time).await;
 
     match update_result {
         Ok(Some(updated_product)) => {
@@ -0,0 +1,503 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::api::ApiResponse;
+use crate::dto::purchase_orders::{
+    CreatePurchaseOrdersFromSuggestionsRequest, PurchaseOrderListRequest, ReorderSuggestionItem,
+    ReorderSuggestionQuery, ReorderSuggestionResponse, SupplierReorderSuggestion,
+    UpdatePurchaseOrderStatusRequest,
+};
+use crate::middleware::jwt::JWTAuthMiddleware;
+use crate::models::purchase_orders::PurchaseOrderStatus;
+use crate::repository::purchase_orders as purchase_orders_repo;
+use crate::repository::purchase_orders::{
+    NewPurchaseOrder, NewPurchaseOrderItem, PurchaseOrderTransition,
+};
+use crate::repository::recipe_items as recipe_items_repository;
+use crate::repository::stores as stores_repository;
+use crate::services::reorder::{compute_reorder, ReorderInput};
+use crate::AppState;
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+const DEFAULT_HORIZON_DAYS: i64 = 7;
+const DEFAULT_REVIEW_DAYS: i32 = 7;
+// ID: Lead time bawaan untuk bahan yang belum punya supplier
+// EN: Fallback lead time for ingredients without a linked supplier
+const DEFAULT_LEAD_TIME_DAYS: i32 = 1;
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({
+            "status": "error",
+            "message": format!("{:?}", e)
+        })),
+    )
+}
+
+fn validation_error(e: validator::ValidationErrors) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "fail",
+            "message": "Validation error",
+            "errors": e,
+        })),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UsageSource {
+    Forecast,
+    Prediction,
+    Auto,
+}
+
+impl UsageSource {
+    fn parse(value: Option<&str>) -> Option<Self> {
+        match value.map(|s| s.trim().to_ascii_uppercase()).as_deref() {
+            None | Some("") | Some("AUTO") => Some(Self::Auto),
+            Some("FORECAST") => Some(Self::Forecast),
+            Some("PREDICTION") => Some(Self::Prediction),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_source() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "fail",
+            "message": "Invalid source. Allowed: AUTO, FORECAST, PREDICTION"
+        })),
+    )
+}
+
+// ID: Gabungkan stok, pesanan berjalan, konsumsi (forecast/prediksi) dan aturan supplier menjadi saran reorder
+// EN: Combine stock, open orders, expected consumption (forecast/prediction) and supplier rules into suggestions
+async fn build_reorder_suggestions(
+    state: &AppState,
+    user_uuid: Uuid,
+    horizon_days: i64,
+    review_days: i32,
+    source: UsageSource,
+    include_zero: bool,
+) -> Result<ReorderSuggestionResponse, sqlx::Error> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let today_start = now - now.rem_euclid(MILLIS_PER_DAY);
+    let horizon = Decimal::from(horizon_days);
+
+    let mut forecast_usage: HashMap<Uuid, Decimal> = HashMap::new();
+    if source != UsageSource::Prediction {
+        let rows = purchase_orders_repo::fetch_forecast_recipe_usage(
+            &state.db,
+            today_start,
+            today_start + horizon_days * MILLIS_PER_DAY,
+        )
+        .await?;
+        if !rows.is_empty() {
+            // Pecah sampai bahan mentah: komponen setengah jadi tetap butuh bahan yang dibeli
+            let graph = recipe_items_repository::load_recipe_graph(&state.db).await?;
+            for row in rows {
+                let exploded =
+                    match graph.explode(row.recipe_sets_uuid, row.forecast_qty_total, false) {
+                        Ok(exploded) => exploded,
+                        Err(e) => {
+                            tracing::warn!(
+                                "Skipping forecast usage for recipe {}: {}",
+                                row.recipe_sets_uuid,
+                                e
+                            );
+                            continue;
+                        }
+                    };
+                for (ingredient_catalog_uuid, quantity) in exploded {
+                    *forecast_usage
+                        .entry(ingredient_catalog_uuid)
+                        .or_insert(Decimal::ZERO) += quantity / horizon;
+                }
+            }
+        }
+    }
+
+    let store_uuid = stores_repository::get_store_by_user_uuid(&state.db, user_uuid)
+        .await?
+        .map(|store| store.uuid);
+
+    // Prediksi bahan disimpan per store dan mewakili kebutuhan untuk satu hari
+    let mut prediction_usage: HashMap<Uuid, Decimal> = HashMap::new();
+    if source != UsageSource::Forecast {
+        if let Some(store_uuid) = store_uuid {
+            prediction_usage =
+                purchase_orders_repo::fetch_prediction_ingredient_usage(&state.db, store_uuid)
+                    .await?
+                    .into_iter()
+                    .collect();
+        }
+    }
+
+    let candidates = purchase_orders_repo::fetch_reorder_candidates(&state.db, store_uuid).await?;
+
+    let mut by_supplier: BTreeMap<Uuid, SupplierReorderSuggestion> = BTreeMap::new();
+    let mut unassigned = Vec::new();
+
+    for candidate in candidates {
+        let (daily_usage, usage_source) =
+            match forecast_usage.get(&candidate.ingredient_catalog_uuid) {
+                Some(usage) => (*usage, "FORECAST"),
+                None => match prediction_usage.get(&candidate.ingredient_catalog_uuid) {
+                    Some(usage) => (*usage, "PREDICTION"),
+                    None => (Decimal::ZERO, "NONE"),
+                },
+            };
+
+        let lead_time_days = candidate.lead_time_days.unwrap_or(DEFAULT_LEAD_TIME_DAYS);
+        let outcome = compute_reorder(&ReorderInput {
+            on_hand: candidate.on_hand_qty,
+            on_order: candidate.on_order_qty,
+            minimum_stock: candidate.minimum_stock,
+            daily_usage,
+            lead_time_days,
+            review_days,
+            min_order_qty: candidate.min_order_qty,
+            pack_size: candidate.pack_size,
+        });
+
+        if outcome.suggested_qty.is_zero() && !include_zero {
+            continue;
+        }
+
+        let unit_price = candidate.last_price.or(candidate.current_cost);
+        let item = ReorderSuggestionItem {
+            ingredient_catalog_uuid: candidate.ingredient_catalog_uuid,
+            ingredient_name: candidate.ingredient_name,
+            unit_of_measure_code: candidate.unit_of_measure_code,
+            unit_of_measure_name: candidate.unit_of_measure_name,
+            on_hand_qty: candidate.on_hand_qty,
+            on_order_qty: candidate.on_order_qty,
+            minimum_stock: candidate.minimum_stock,
+            daily_usage: daily_usage.round_dp(4),
+            usage_source: usage_source.to_string(),
+            lead_time_days,
+            reorder_point: outcome.reorder_point,
+            target_stock: outcome.target_stock,
+            suggested_qty: outcome.suggested_qty,
+            unit_price,
+            estimated_cost: unit_price.map(|p| (p * outcome.suggested_qty).round_dp(2)),
+        };
+
+        match candidate.supplier_uuid {
+            Some(supplier_uuid) => {
+                let entry =
+                    by_supplier
+                        .entry(supplier_uuid)
+                        .or_insert_with(|| SupplierReorderSuggestion {
+                            supplier_uuid: Some(supplier_uuid),
+                            supplier_name: candidate.supplier_name.clone(),
+                            lead_time_days: candidate.lead_time_days,
+                            items: Vec::new(),
+                            total_estimated: Decimal::ZERO,
+                        });
+                entry.total_estimated += item.estimated_cost.unwrap_or(Decimal::ZERO);
+                entry.lead_time_days = entry.lead_time_days.max(candidate.lead_time_days);
+                entry.items.push(item);
+            }
+            None => unassigned.push(item),
+        }
+    }
+
+    let mut suppliers: Vec<_> = by_supplier.into_values().collect();
+    suppliers.sort_by(|a, b| a.supplier_name.cmp(&b.supplier_name));
+
+    Ok(ReorderSuggestionResponse {
+        generated_at: now,
+        horizon_days,
+        review_days,
+        suppliers,
+        unassigned,
+    })
+}
+
+// ID: Handler untuk saran reorder per supplier
+// EN: Handler returning reorder suggestions grouped per supplier
+pub async fn get_reorder_suggestions_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Query(params): Query<ReorderSuggestionQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    params.validate().map_err(validation_error)?;
+    let source = UsageSource::parse(params.source.as_deref()).ok_or_else(invalid_source)?;
+
+    let mut suggestions = build_reorder_suggestions(
+        &data,
+        jwt_auth.user.uuid,
+        params.horizon_days.unwrap_or(DEFAULT_HORIZON_DAYS),
+        params.review_days.unwrap_or(DEFAULT_REVIEW_DAYS),
+        source,
+        params.include_zero.unwrap_or(false),
+    )
+    .await
+    .map_err(internal_error)?;
+
+    if let Some(supplier_uuid) = params.supplier_uuid {
+        suggestions
+            .suppliers
+            .retain(|s| s.supplier_uuid == Some(supplier_uuid));
+        suggestions.unassigned.clear();
+    }
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Reorder suggestions retrieved successfully".to_string(),
+        data: suggestions,
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk mengubah saran reorder menjadi draft PO (satu PO per supplier) dalam satu panggilan
+// EN: Handler converting reorder suggestions into draft purchase orders (one per supplier) in one call
+pub async fn create_purchase_orders_from_suggestions_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<CreatePurchaseOrdersFromSuggestionsRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+    let source = UsageSource::parse(body.source.as_deref()).ok_or_else(invalid_source)?;
+    let user_uuid = jwt_auth.user.uuid;
+
+    let suggestions = build_reorder_suggestions(
+        &data,
+        user_uuid,
+        body.horizon_days.unwrap_or(DEFAULT_HORIZON_DAYS),
+        body.review_days.unwrap_or(DEFAULT_REVIEW_DAYS),
+        source,
+        false,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    let store_uuid = stores_repository::get_store_by_user_uuid(&data.db, user_uuid)
+        .await
+        .map_err(internal_error)?
+        .map(|store| store.uuid);
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let supplier_filter = body.supplier_uuids.unwrap_or_default();
+
+    let drafts: Vec<NewPurchaseOrder> = suggestions
+        .suppliers
+        .into_iter()
+        .filter_map(|suggestion| {
+            let supplier_uuid = suggestion.supplier_uuid?;
+            if !supplier_filter.is_empty() && !supplier_filter.contains(&supplier_uuid) {
+                return None;
+            }
+            let lead_time_days = suggestion.lead_time_days.unwrap_or(DEFAULT_LEAD_TIME_DAYS);
+            Some(NewPurchaseOrder {
+                supplier_uuid,
+                store_uuid,
+                expected_at: Some(now + i64::from(lead_time_days) * MILLIS_PER_DAY),
+                notes: body.notes.clone(),
+                created_by: Some(user_uuid),
+                items: suggestion
+                    .items
+                    .into_iter()
+                    .map(|item| NewPurchaseOrderItem {
+                        ingredient_catalog_uuid: item.ingredient_catalog_uuid,
+                        quantity: item.suggested_qty,
+                        unit_price: item.unit_price,
+                        unit_of_measure_code: item.unit_of_measure_code,
+                        unit_of_measure_name: item.unit_of_measure_name,
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+
+    if drafts.is_empty() {
+        return Ok((
+            StatusCode::OK,
+            Json(ApiResponse {
+                code: 200,
+                status: "success".to_string(),
+                message: "No ingredients need reordering".to_string(),
+                data: json!({
+                    "purchase_orders": [],
+                    "unassigned": suggestions.unassigned,
+                }),
+                errors: json!({}),
+            }),
+        ));
+    }
+
+    let created = purchase_orders_repo::create_purchase_orders(&data.db, &drafts, now)
+        .await
+        .map_err(internal_error)?;
+
+    let mut purchase_orders = Vec::with_capacity(created.len());
+    for uuid in created {
+        if let Some(po) = purchase_orders_repo::get_purchase_order_with_items(&data.db, uuid)
+            .await
+            .map_err(internal_error)?
+        {
+            purchase_orders.push(po);
+        }
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "success".to_string(),
+            message: "Draft purchase orders created successfully".to_string(),
+            data: json!({
+                "purchase_orders": purchase_orders,
+                "unassigned": suggestions.unassigned,
+            }),
+            errors: json!({}),
+        }),
+    ))
+}
+
+pub async fn get_purchase_orders_handler(
+    Query(opts): Query<PurchaseOrderListRequest>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let page = opts.page.unwrap_or(1).max(1);
+    let limit = opts.limit.unwrap_or(10).clamp(1, 100);
+
+    let (purchase_orders, total) =
+        purchase_orders_repo::list_purchase_orders(&data.db, page, limit, &opts)
+            .await
+            .map_err(internal_error)?;
+
+    let total_pages = ((total + limit as i64 - 1) / limit as i64).max(1);
+    let has_prev = page > 1;
+    let has_next = (page as i64) < total_pages;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Purchase orders retrieved successfully".to_string(),
+        data: json!({
+            "purchase_orders": purchase_orders,
+            "meta": {
+                "page": page,
+                "limit": limit,
+                "total_records": total,
+                "total_pages": total_pages,
+                "has_prev": has_prev,
+                "has_next": has_next,
+                "prev_page": if has_prev { Some(page - 1) } else { None },
+                "next_page": if has_next { Some(page + 1) } else { None }
+            }
+        }),
+        errors: json!({}),
+    }))
+}
+
+pub async fn get_purchase_order_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let purchase_order = purchase_orders_repo::get_purchase_order_with_items(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "fail",
+                    "message": format!("Purchase order with ID: {} not found", id)
+                })),
+            )
+        })?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Purchase order retrieved successfully".to_string(),
+        data: purchase_order,
+        errors: json!({}),
+    }))
+}
+
+// ID: Ubah status PO; RECEIVED akan memposting pergerakan stok PURCHASE untuk setiap item
+// EN: Update PO status; RECEIVED posts a PURCHASE stock move for every item
+pub async fn update_purchase_order_status_handler(
+    State(data): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdatePurchaseOrderStatusRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+
+    let next_status = PurchaseOrderStatus::from_str(&body.status).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "fail",
+                "message": "Invalid status. Allowed: DRAFT, SUBMITTED, RECEIVED, CANCELLED"
+            })),
+        )
+    })?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    match purchase_orders_repo::transition_purchase_order_status(
+        &data.db,
+        id,
+        next_status.clone(),
+        now,
+    )
+    .await
+    .map_err(internal_error)?
+    {
+        PurchaseOrderTransition::Done => {}
+        PurchaseOrderTransition::NotFound => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "fail",
+                    "message": format!("Purchase order with ID: {} not found", id)
+                })),
+            ));
+        }
+        PurchaseOrderTransition::InvalidStatus(current_status) => {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "status": "fail",
+                    "message": format!(
+                        "Cannot change purchase order status from {} to {}",
+                        current_status,
+                        next_status.as_str()
+                    )
+                })),
+            ));
+        }
+    }
+
+    let updated = purchase_orders_repo::get_purchase_order_with_items(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Purchase order status updated successfully".to_string(),
+        data: updated,
+        errors: json!({}),
+    }))
+}
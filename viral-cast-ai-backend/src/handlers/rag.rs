@@ -3,6 +3,7 @@ use axum::{
     extract::{Multipart, Path, Query, State},
     http::StatusCode,
     response::Json,
+    Extension,
 };
 use calamine::{open_workbook_auto, DataType, Reader};
 use chrono::Utc;
@@ -11,8 +12,6 @@ use reqwest::Client;
 use serde_json::json;
 use sha2::{Digest, Sha256};
 use sqlx::{Postgres, QueryBuilder, Row};
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path as StdPath, PathBuf};
@@ -24,27 +23,42 @@ use zip::ZipArchive;
 const DEFAULT_CATEGORY_NAME: &str = "General";
 const DEFAULT_CATEGORY_DESCRIPTION: &str = "General business documents";
 const AUTO_CATEGORY_DESCRIPTION: &str = "Auto-created category";
+const NOT_IN_KB_ANSWER: &str =
+    "Maaf, informasi tersebut tidak ada di basis pengetahuan (not in knowledge base).";
 
 use crate::{
     dto::{
         ai::{GroqApiRequest, GroqApiResponse, GroqMessage},
         api::ApiResponse,
         rag::{
-            DocumentListRequest, DocumentListResponse, DocumentProcessingStatus, DocumentSource,
-            DocumentSummary, DocumentTextIngestRequest, DocumentTextIngestResponse,
-            DocumentUploadResponse, RagAnswerRequest, RagAnswerResponse, RagConfig,
-            RagQueryRequest, RagQueryResponse, UpdateRagConfigRequest,
+            AnswerCitation, DocumentListRequest, DocumentListResponse, DocumentProcessingStatus,
+            DocumentReindexResponse, DocumentSource, DocumentSummary, DocumentTextIngestRequest,
+            DocumentTextIngestResponse, DocumentUploadResponse, RagAnswerRequest,
+            RagAnswerResponse, RagConfig, RagQueryRequest, RagQueryResponse,
+            UpdateRagConfigRequest,
         },
     },
-    models::rag::{Document, DocumentProcessingJob, RagConfiguration},
+    middleware::jwt::JWTAuthMiddleware,
+    models::rag::{
+        Document, DocumentProcessingJob, RagConfiguration, DOCUMENT_VISIBILITY_PLATFORM,
+        DOCUMENT_VISIBILITY_STORE,
+    },
+    models::roles::SUPER_ADMIN_ROLE_NUMBER,
+    services::chunking::{self, ChunkOptions, ChunkingStrategy, SizeUnit},
+    services::document_extract::{self, ExtractedDocument},
+    services::embeddings::{Embedder, HASHING_MODEL},
+    services::rag_citations,
+    services::rag_retrieval::{self, DocumentAccess, RetrievalFilters, RetrievedChunk},
     AppState,
 };
 
 // Upload document endpoint
 pub async fn upload_document(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     mut multipart: Multipart,
 ) -> Result<Json<ApiResponse<DocumentUploadResponse>>, StatusCode> {
+    let caller = rag_caller(&data, &jwt_auth).await?;
     let mut title = String::new();
     let mut description: Option<String> = None;
     let mut category = String::new();
@@ -53,6 +67,7 @@ pub async fn upload_document(
     let mut file_name = String::new();
     let mut file_type = String::new();
     let mut mime_type = String::new();
+    let mut visibility: Option<String> = None;
 
     // Process multipart form data
     while let Some(field) = multipart
@@ -79,6 +94,9 @@ pub async fn upload_document(
                 let tags_str = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
                 tags = tags_str.split(',').map(|s| s.trim().to_string()).collect();
             }
+            "visibility" => {
+                visibility = Some(field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?);
+            }
             "file" => {
                 file_name = field.file_name().unwrap_or("unknown").to_string();
                 mime_type = field
@@ -124,6 +142,10 @@ pub async fn upload_document(
             file_type = "xlsx".to_string();
         } else if mt.contains("pdf") {
             file_type = "pdf".to_string();
+        } else if mt.contains("epub") {
+            file_type = "epub".to_string();
+        } else if mt.contains("html") {
+            file_type = "html".to_string();
         } else if mt.contains("csv") {
             file_type = "csv".to_string();
         } else if mt.starts_with("text/") {
@@ -140,6 +162,7 @@ pub async fn upload_document(
     if file_data.is_none() {
         return Err(StatusCode::BAD_REQUEST);
     }
+    let store_uuid = caller.document_owner(visibility.as_deref())?;
 
     let file_bytes = file_data.unwrap();
     let file_size = file_bytes.len() as i64;
@@ -165,6 +188,7 @@ pub async fn upload_document(
             SELECT *
             FROM documents
             WHERE lower(file_name) = lower($1)
+              AND store_uuid IS NOT DISTINCT FROM $2
               AND (status IS NULL OR status <> 'deleted')
               AND deleted_at = 0
             ORDER BY created_at DESC
@@ -172,6 +196,7 @@ pub async fn upload_document(
             "#,
         )
         .bind(&file_name)
+        .bind(store_uuid)
         .fetch_optional(&data.db)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -188,6 +213,8 @@ pub async fn upload_document(
                 file_type: existing_document.file_type,
                 status: existing_document.status,
                 uploaded_at: existing_document.created_at,
+                store_uuid: existing_document.store_uuid,
+                visibility: existing_document.visibility,
             };
 
             return Ok(Json(ApiResponse {
@@ -200,16 +227,25 @@ pub async fn upload_document(
         }
     }
 
-    // Generate unique file path
+    // Generate unique storage key; file_path menyimpan key blob store (local / S3)
     let document_id = Uuid::new_v4();
-    let file_path = format!("uploads/documents/{}/{}", document_id, file_name);
-
-    // Save file to disk (in production, use cloud storage)
-    if let Err(_) = tokio::fs::create_dir_all(format!("uploads/documents/{}", document_id)).await {
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
+    let file_path = format!(
+        "documents/{}/{}",
+        document_id,
+        sanitize_filename(&file_name)
+    );
 
-    if let Err(_) = tokio::fs::write(&file_path, &file_bytes).await {
+    let content_type = if mime_type.trim().is_empty() {
+        None
+    } else {
+        Some(mime_type.as_str())
+    };
+    if let Err(e) = data
+        .blob_store
+        .put(&file_path, file_bytes, content_type)
+        .await
+    {
+        tracing::error!("Failed to store document {}: {}", file_path, e);
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
 
@@ -218,8 +254,9 @@ pub async fn upload_document(
         r#"
         INSERT INTO documents (
             id, title, description, category, tags, file_path, file_name, 
-            file_size, file_type, mime_type, status, created_at, updated_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            file_size, file_type, mime_type, status, created_at, updated_at,
+            uploaded_by, store_uuid, visibility
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         RETURNING *
         "#,
     )
@@ -236,133 +273,21 @@ pub async fn upload_document(
     .bind("processing")
     .bind(Utc::now())
     .bind(Utc::now())
+    .bind(caller.user_uuid)
+    .bind(store_uuid)
+    .bind(document_visibility(store_uuid))
     .fetch_one(&data.db)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // Create processing job entry so status can be tracked
-    let job_row = sqlx::query(
-        r#"
-        INSERT INTO document_processing_jobs (
-            document_id, job_type, status, priority, current_step, created_at, updated_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7)
-        RETURNING id
-        "#,
-    )
-    .bind(document_id)
-    .bind("extract_text")
-    .bind("pending")
-    .bind(5)
-    .bind("Queued for processing")
-    .bind(Utc::now())
-    .bind(Utc::now())
-    .fetch_one(&data.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let job_id: Uuid = job_row.get("id");
-
-    let started_at = Utc::now();
-    let _ = sqlx::query(
-        r#"
-        UPDATE document_processing_jobs
-        SET status = 'processing',
-            current_step = $1,
-            started_at = $2,
-            updated_at = $2
-        WHERE id = $3
-        "#,
-    )
-    .bind("Extracting content")
-    .bind(started_at)
-    .bind(job_id)
-    .execute(&data.db)
-    .await;
-
-    let ingestion_result =
-        process_uploaded_document(&data, document.id, &file_path, &file_type, &config).await;
-
-    match ingestion_result {
-        Ok(chunk_count) => {
-            let completed_at = Utc::now();
-
-            let _ = sqlx::query(
-                r#"
-                UPDATE documents
-                SET status = 'ready',
-                    chunk_count = $1,
-                    processing_progress = 100,
-                    current_processing_step = 'Completed',
-                    error_message = NULL,
-                    updated_at = $2
-                WHERE id = $3
-                "#,
-            )
-            .bind(chunk_count as i32)
-            .bind(completed_at)
-            .bind(document.id)
-            .execute(&data.db)
-            .await;
-
-            let _ = sqlx::query(
-                r#"
-                UPDATE document_processing_jobs
-                SET status = 'completed',
-                    progress_percentage = 100,
-                    current_step = 'Completed',
-                    completed_at = $1,
-                    updated_at = $1
-                WHERE id = $2
-                "#,
-            )
-            .bind(completed_at)
-            .bind(job_id)
-            .execute(&data.db)
-            .await;
-        }
-        Err(err) => {
-            let failure_time = Utc::now();
-            let error_message = err.to_string();
-
-            let _ = sqlx::query(
-                r#"
-                UPDATE documents
-                SET status = 'error',
-                    error_message = $1,
-                    processing_progress = 0,
-                    current_processing_step = 'Failed',
-                    updated_at = $2
-                WHERE id = $3
-                "#,
-            )
-            .bind(&error_message)
-            .bind(failure_time)
-            .bind(document.id)
-            .execute(&data.db)
-            .await;
-
-            let _ = sqlx::query(
-                r#"
-                UPDATE document_processing_jobs
-                SET status = 'failed',
-                    current_step = 'Failed',
-                    error_message = $1,
-                    updated_at = $2
-                WHERE id = $3
-                "#,
-            )
-            .bind(&error_message)
-            .bind(failure_time)
-            .bind(job_id)
-            .execute(&data.db)
-            .await;
-
-            eprintln!(
-                "Failed to ingest document {} ({}): {}",
-                document.id, document.file_name, error_message
-            );
-
-            return Err(StatusCode::UNPROCESSABLE_ENTITY);
-        }
+    // Ekstraksi + embedding berjalan di worker antrian (workers::rag_ingest); status bisa dipantau
+    // lewat GET /documents/:id/status
+    if let Err(e) =
+        crate::workers::rag_ingest::enqueue_document_job(&data.db, document.id, "extract_text", 5)
+            .await
+    {
+        tracing::error!("Failed to enqueue document {}: {}", document.id, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
 
     let updated_document =
@@ -383,12 +308,14 @@ pub async fn upload_document(
         file_type: updated_document.file_type,
         status: updated_document.status,
         uploaded_at: updated_document.created_at,
+        store_uuid: updated_document.store_uuid,
+        visibility: updated_document.visibility,
     };
 
     Ok(Json(ApiResponse {
-        code: 201,
-        status: "CREATED".to_string(),
-        message: "Document uploaded successfully".to_string(),
+        code: 202,
+        status: "ACCEPTED".to_string(),
+        message: "Document uploaded and queued for processing".to_string(),
         data: response,
         errors: json!({}),
     }))
@@ -397,6 +324,7 @@ pub async fn upload_document(
 // Query RAG endpoint
 pub async fn query_rag(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Json(request): Json<RagQueryRequest>,
 ) -> Result<Json<ApiResponse<RagQueryResponse>>, StatusCode> {
     let start_time = std::time::Instant::now();
@@ -405,6 +333,7 @@ pub async fn query_rag(
     if request.query.trim().is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
+    let caller = rag_caller(&data, &jwt_auth).await?;
 
     // Get RAG configuration
     let config = get_rag_config(&data).await?;
@@ -414,228 +343,54 @@ pub async fn query_rag(
         .similarity_threshold
         .unwrap_or(config.similarity_threshold);
 
-    // Compute query embedding for similarity (use normalized query to be robust to typos)
-    let normalized_query_text = tokenize_and_normalize(&request.query).join(" ");
-    let query_embedding = embed_texts_local(
-        &vec![normalized_query_text.clone()],
-        config.embedding_dimensions as usize,
+    let filters = retrieval_filters(
+        caller.access(),
+        &request.category_filter,
+        &request.tags_filter,
+        &request.document_ids,
+    );
+    let retrieved = hybrid_retrieve(
+        &data,
+        &config,
+        &request.query,
+        &filters,
+        max_results as usize,
     )
-    .into_iter()
-    .next()
-    .unwrap_or_default();
-
-    // Try Milvus search when available and not in mock mode; otherwise fallback later
-    let milvus_client_opt = data.milvus_client.as_ref().cloned();
-    let mut milvus_ranked: Vec<(i64, f32)> = Vec::new();
-    if milvus_client_opt.is_some() && !data.env.allow_mock_dependencies {
-        let milvus_client = milvus_client_opt.unwrap();
-        let mut guard = milvus_client.lock().await;
-        crate::services::milvus::ensure_rag_collection(&mut guard, &data.milvus_collection)
-            .await
-            .map_err(|err| {
-                tracing::error!("Failed to ensure Milvus collection: {:?}", err);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-        let results = crate::services::milvus::search_top_k(
-            &mut guard,
-            &data.milvus_collection,
-            &query_embedding,
-            max_results as usize,
-        )
-        .await
-        .map_err(|err| {
-            tracing::error!("Milvus search_top_k failed: {:?}", err);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-        milvus_ranked = results;
-    }
-
-    let vector_filter_ids: Vec<i64> = milvus_ranked.iter().map(|(id, _)| *id).collect();
-
-    #[derive(Clone)]
-    struct ChunkRecord {
-        chunk_id: Uuid,
-        document_id: Uuid,
-        document_title: String,
-        content: String,
-        chunk_index: i32,
-        page_number: Option<i32>,
-    }
-
-    let mut chunk_map: HashMap<i64, ChunkRecord> = HashMap::new();
-
-    if !vector_filter_ids.is_empty() {
-        let mut query_builder = QueryBuilder::<Postgres>::new(
-            "SELECT dc.id, dc.document_id, dc.chunk_index, dc.content, dc.page_number, \
-             dc.milvus_vector_id, d.title as document_title \
-             FROM document_chunks dc \
-             JOIN documents d ON dc.document_id = d.id \
-             WHERE d.status = 'ready' AND dc.deleted_at = 0 AND d.deleted_at = 0",
-        );
-
-        if let Some(category) = &request.category_filter {
-            query_builder.push(" AND d.category = ");
-            query_builder.push_bind(category);
-        }
-
-        if let Some(doc_ids) = &request.document_ids {
-            if !doc_ids.is_empty() {
-                query_builder.push(" AND d.id = ANY(");
-                query_builder.push_bind(doc_ids);
-                query_builder.push(")");
-            }
-        }
-
-        query_builder.push(" AND dc.milvus_vector_id = ANY(");
-        query_builder.push_bind(&vector_filter_ids);
-        query_builder.push(")");
-
-        let rows = query_builder
-            .build()
-            .fetch_all(&data.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        for row in rows {
-            if let Ok(vector_id_opt) = row.try_get::<Option<i64>, _>("milvus_vector_id") {
-                if let Some(vector_id) = vector_id_opt {
-                    chunk_map.insert(
-                        vector_id,
-                        ChunkRecord {
-                            chunk_id: row.get("id"),
-                            document_id: row.get("document_id"),
-                            document_title: row.get("document_title"),
-                            content: row.get("content"),
-                            chunk_index: row.get("chunk_index"),
-                            page_number: row.get("page_number"),
-                        },
-                    );
-                }
-            }
-        }
-    }
-
-    // Build RAG response, rank strictly by Milvus similarity
-    let mut sources = Vec::new();
-    let mut answer = String::from("Based on the available documents, here's what I found:\n\n");
-
-    for (vector_id, similarity_score) in milvus_ranked.iter() {
-        if sources.len() >= max_results as usize {
-            break;
-        }
+    .await?;
+    let retrieved = rerank_candidates(&data, &config, &request.query, retrieved).await;
 
-        if *similarity_score < similarity_threshold {
-            continue;
-        }
-
-        if let Some(chunk) = chunk_map.get(vector_id) {
-            sources.push(DocumentSource {
-                document_id: chunk.document_id,
-                document_title: chunk.document_title.clone(),
-                chunk_text: chunk.content.chars().take(200).collect::<String>() + "...",
-                similarity_score: *similarity_score,
-                page_number: chunk.page_number,
-                chunk_index: chunk.chunk_index,
-            });
+    // Urutan mengikuti skor RRF; threshold hanya menyaring chunk yang murni hasil vektor
+    let sources: Vec<DocumentSource> = retrieved
+        .iter()
+        .filter(|chunk| passes_similarity_threshold(chunk, similarity_threshold))
+        .take(max_results as usize)
+        .map(|chunk| DocumentSource {
+            document_id: chunk.document_id,
+            document_title: chunk.document_title.clone(),
+            chunk_text: chunk.content.chars().take(200).collect::<String>() + "...",
+            similarity_score: chunk.similarity(),
+            page_number: chunk.page_number,
+            chunk_index: chunk.chunk_index,
+            retrieval_score: chunk.fused_score,
+            rerank_score: chunk.rerank_score,
+        })
+        .collect();
 
+    let answer = if sources.is_empty() {
+        "Tidak ditemukan konteks relevan di dokumen untuk pertanyaan ini.".to_string()
+    } else {
+        let mut answer =
+            String::from("Berdasarkan dokumen yang tersedia, berikut rangkuman temuan:\n\n");
+        for (i, s) in sources.iter().enumerate() {
             answer.push_str(&format!(
-                "{}. From '{}': {}\n\n",
-                sources.len(),
-                chunk.document_title,
-                chunk.content.chars().take(150).collect::<String>() + "..."
+                "{}. Dari '{}': {}\n\n",
+                i + 1,
+                s.document_title,
+                s.chunk_text.chars().take(150).collect::<String>()
             ));
         }
-    }
-
-    // Fallback: local embedding similarity search from DB when Milvus yields no sources
-    if sources.is_empty() {
-        let mut qb = QueryBuilder::<Postgres>::new(
-            "SELECT dc.id, dc.document_id, dc.chunk_index, dc.content, dc.page_number, dc.embedding, d.title as document_title \n             FROM document_chunks dc \n             JOIN documents d ON dc.document_id = d.id \n             WHERE d.status = 'ready' AND dc.deleted_at = 0 AND d.deleted_at = 0",
-        );
-
-        if let Some(category) = &request.category_filter {
-            qb.push(" AND d.category = ");
-            qb.push_bind(category);
-        }
-
-        if let Some(doc_ids) = &request.document_ids {
-            if !doc_ids.is_empty() {
-                qb.push(" AND dc.document_id IN (");
-                let mut separated = qb.separated(", ");
-                for id in doc_ids {
-                    separated.push_bind(id);
-                }
-                separated.push_unseparated(")");
-            }
-        }
-
-        // Prevent scanning too many rows
-        qb.push(" ORDER BY dc.created_at DESC LIMIT ");
-        qb.push_bind(2000i64);
-
-        let rows = qb
-            .build()
-            .fetch_all(&data.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        // Rank by cosine similarity (embeddings are L2-normalized), fallback to text similarity if dim mismatch
-        let mut ranked: Vec<(f32, DocumentSource)> = Vec::new();
-        for row in rows {
-            let emb: Vec<f32> = row.get("embedding");
-            let sim = if emb.len() == query_embedding.len() {
-                emb.iter()
-                    .zip(query_embedding.iter())
-                    .map(|(a, b)| a * b)
-                    .sum::<f32>()
-            } else {
-                let content: String = row.get("content");
-                calculate_text_similarity(&request.query, &content)
-            };
-
-            ranked.push((
-                sim,
-                DocumentSource {
-                    document_id: row.get("document_id"),
-                    document_title: row.get("document_title"),
-                    chunk_text: {
-                        let c: String = row.get("content");
-                        c.chars().take(200).collect::<String>() + "..."
-                    },
-                    similarity_score: sim,
-                    page_number: row.try_get("page_number").ok(),
-                    chunk_index: row.get("chunk_index"),
-                },
-            ));
-        }
-
-        ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-        for (_sim, src) in ranked
-            .into_iter()
-            .filter(|(s, _)| *s >= similarity_threshold)
-            .take(max_results as usize)
-        {
-            sources.push(src);
-        }
-
-        // Compose answer from sources
-        if sources.is_empty() {
-            answer = "Tidak ditemukan konteks relevan di dokumen untuk pertanyaan ini.".to_string();
-        } else {
-            answer.clear();
-            answer.push_str("Berdasarkan dokumen yang tersedia, berikut rangkuman temuan:\n\n");
-            for (i, s) in sources.iter().enumerate() {
-                answer.push_str(&format!(
-                    "{}. Dari '{}': {}\n\n",
-                    i + 1,
-                    s.document_title,
-                    s.chunk_text.chars().take(150).collect::<String>()
-                ));
-            }
-        }
-    }
+        answer
+    };
 
     let processing_time = start_time.elapsed().as_millis() as i64;
     let confidence_score = if sources.is_empty() {
@@ -692,6 +447,7 @@ pub async fn query_rag(
 // RAG + LLM answer endpoint
 pub async fn answer_with_rag_and_llm(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Json(request): Json<RagAnswerRequest>,
 ) -> Result<Json<ApiResponse<RagAnswerResponse>>, StatusCode> {
     let start_time = std::time::Instant::now();
@@ -699,6 +455,7 @@ pub async fn answer_with_rag_and_llm(
     if request.query.trim().is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
+    let caller = rag_caller(&data, &jwt_auth).await?;
 
     // Get RAG configuration
     let config = get_rag_config(&data).await?;
@@ -708,267 +465,109 @@ pub async fn answer_with_rag_and_llm(
         .similarity_threshold
         .unwrap_or(config.similarity_threshold);
 
-    // Compute query embedding (normalized query for typo robustness)
-    let normalized_query_text = tokenize_and_normalize(&request.query).join(" ");
-    let query_embedding = embed_texts_local(
-        &vec![normalized_query_text.clone()],
-        config.embedding_dimensions as usize,
+    let filters = retrieval_filters(
+        caller.access(),
+        &request.category_filter,
+        &request.tags_filter,
+        &request.document_ids,
+    );
+    let candidates = hybrid_retrieve(
+        &data,
+        &config,
+        &request.query,
+        &filters,
+        max_results as usize,
     )
-    .into_iter()
-    .next()
-    .unwrap_or_default();
-
-    // Optional Milvus search; allow fallback when not available or mock mode
-    let milvus_client_opt = data.milvus_client.as_ref().cloned();
-    let mut milvus_ranked: Vec<(i64, f32)> = Vec::new();
-    let mut candidate_map: HashMap<(Uuid, i32), DocumentSource> = HashMap::new();
-
-    // ID: Hindari meminjam mutable `candidate_map` dalam penutupan yang menangkap lingkungan.
-    //     Ini memperbaiki error borrow (E0502) dengan menjadikan peta sebagai parameter eksplisit.
-    // EN: Avoid mutably borrowing `candidate_map` via a capturing closure.
-    //     This fixes the borrow checker error (E0502) by making the map an explicit parameter.
-    let mut upsert_candidate = |map: &mut HashMap<(Uuid, i32), DocumentSource>, candidate: DocumentSource| {
-        let key = (candidate.document_id, candidate.chunk_index);
-        match map.entry(key) {
-            Entry::Vacant(entry) => {
-                entry.insert(candidate);
-            }
-            Entry::Occupied(mut entry) => {
-                if candidate.similarity_score > entry.get().similarity_score {
-                    entry.insert(candidate);
-                }
-            }
-        }
-    };
-
-    if milvus_client_opt.is_some() && !data.env.allow_mock_dependencies {
-        let milvus_client = milvus_client_opt.unwrap();
-        let mut guard = milvus_client.lock().await;
-        crate::services::milvus::ensure_rag_collection(&mut guard, &data.milvus_collection)
-            .await
-            .map_err(|err| {
-                tracing::error!("Failed to ensure Milvus collection: {:?}", err);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-        let results = crate::services::milvus::search_top_k(
-            &mut guard,
-            &data.milvus_collection,
-            &query_embedding,
-            max_results as usize,
-        )
-        .await
-        .map_err(|err| {
-            tracing::error!("Milvus search_top_k failed: {:?}", err);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-        milvus_ranked = results;
-    }
-
-    let vector_filter_ids: Vec<i64> = milvus_ranked.iter().map(|(id, _)| *id).collect();
-
-    #[derive(Clone)]
-    struct ChunkRecord {
-        chunk_id: Uuid,
-        document_id: Uuid,
-        document_title: String,
-        content: String,
-        chunk_index: i32,
-        page_number: Option<i32>,
-    }
-
-    let mut chunk_map: HashMap<i64, ChunkRecord> = HashMap::new();
-
-    if !vector_filter_ids.is_empty() {
-        let mut query_builder = QueryBuilder::<Postgres>::new(
-            "SELECT dc.id, dc.document_id, dc.chunk_index, dc.content, dc.page_number, \
-             dc.milvus_vector_id, d.title as document_title \
-             FROM document_chunks dc \
-             JOIN documents d ON dc.document_id = d.id \
-             WHERE d.status = 'ready' AND dc.deleted_at = 0 AND d.deleted_at = 0",
-        );
-
-        if let Some(category) = &request.category_filter {
-            query_builder.push(" AND d.category = ");
-            query_builder.push_bind(category);
-        }
-
-        if let Some(doc_ids) = &request.document_ids {
-            if !doc_ids.is_empty() {
-                query_builder.push(" AND d.id = ANY(");
-                query_builder.push_bind(doc_ids);
-                query_builder.push(")");
-            }
-        }
+    .await?;
+    let candidates = rerank_candidates(&data, &config, &request.query, candidates).await;
 
-        query_builder.push(" AND dc.milvus_vector_id = ANY(");
-        query_builder.push_bind(&vector_filter_ids);
-        query_builder.push(")");
-
-        let rows = query_builder
-            .build()
-            .fetch_all(&data.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        for row in rows {
-            if let Ok(vector_id_opt) = row.try_get::<Option<i64>, _>("milvus_vector_id") {
-                if let Some(vector_id) = vector_id_opt {
-                    chunk_map.insert(
-                        vector_id,
-                        ChunkRecord {
-                            chunk_id: row.get("id"),
-                            document_id: row.get("document_id"),
-                            document_title: row.get("document_title"),
-                            content: row.get("content"),
-                            chunk_index: row.get("chunk_index"),
-                            page_number: row.get("page_number"),
-                        },
-                    );
-                }
-            }
-        }
-    }
-
-    for (vector_id, similarity_score) in milvus_ranked.iter() {
-        if let Some(chunk) = chunk_map.get(vector_id) {
-            let candidate = DocumentSource {
-                document_id: chunk.document_id,
-                document_title: chunk.document_title.clone(),
-                chunk_text: chunk.content.clone(),
-                similarity_score: *similarity_score,
-                page_number: chunk.page_number,
-                chunk_index: chunk.chunk_index,
-            };
-            upsert_candidate(&mut candidate_map, candidate);
-        }
-    }
-
-    // If no sources from Milvus, fallback ke pencarian lokal berbasis embedding
-    if candidate_map.is_empty() {
-        let mut qb = QueryBuilder::<Postgres>::new(
-            "SELECT dc.id, dc.document_id, dc.chunk_index, dc.content, dc.page_number, dc.embedding, d.title as document_title \n             FROM document_chunks dc \n             JOIN documents d ON dc.document_id = d.id \n             WHERE d.status = 'ready' AND dc.deleted_at = 0 AND d.deleted_at = 0",
-        );
-        if let Some(category) = &request.category_filter {
-            qb.push(" AND d.category = ");
-            qb.push_bind(category);
-        }
-        if let Some(doc_ids) = &request.document_ids {
-            if !doc_ids.is_empty() {
-                qb.push(" AND dc.document_id IN (");
-                let mut separated = qb.separated(", ");
-                for id in doc_ids {
-                    separated.push_bind(id);
-                }
-                separated.push_unseparated(")");
-            }
-        }
-        qb.push(" ORDER BY dc.created_at DESC LIMIT ");
-        qb.push_bind(2000i64);
-
-        let rows = qb
-            .build()
-            .fetch_all(&data.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        for row in rows {
-            let emb: Vec<f32> = row.get("embedding");
-            let sim = if emb.len() == query_embedding.len() {
-                emb.iter()
-                    .zip(query_embedding.iter())
-                    .map(|(a, b)| a * b)
-                    .sum::<f32>()
-            } else {
-                let content: String = row.get("content");
-                calculate_text_similarity(&request.query, &content)
-            };
-            let candidate = DocumentSource {
-                document_id: row.get("document_id"),
-                document_title: row.get("document_title"),
-                chunk_text: row.get("content"),
-                similarity_score: sim,
-                page_number: row.try_get("page_number").ok(),
-                chunk_index: row.get("chunk_index"),
-            };
-            upsert_candidate(&mut candidate_map, candidate);
-        }
-    }
-
-    let mut candidates: Vec<DocumentSource> = candidate_map.into_values().collect();
-    candidates.sort_by(|a, b| {
-        b.similarity_score
-            .partial_cmp(&a.similarity_score)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-
-    let mut sources: Vec<DocumentSource> = candidates
+    // Retrieval lemah: tolak daripada membiarkan LLM mengarang jawaban
+    let top_relevance = candidates
         .iter()
-        .filter(|s| s.similarity_score >= similarity_threshold)
-        .take(max_results as usize)
-        .cloned()
-        .collect();
-
-    if sources.is_empty() && !candidates.is_empty() {
-        let top_score = candidates[0].similarity_score;
-        let adaptive_threshold = (top_score * 0.75).max(0.25);
-        sources = candidates
-            .iter()
-            .filter(|s| s.similarity_score >= adaptive_threshold)
-            .take(max_results as usize)
-            .cloned()
-            .collect();
-    }
-
-    if sources.is_empty() && !candidates.is_empty() {
-        sources = candidates
-            .iter()
-            .take(max_results as usize)
-            .cloned()
-            .collect();
-    }
-
-    if sources.is_empty() {
-        let processing_time = start_time.elapsed().as_millis() as i64;
+        .map(RetrievedChunk::relevance)
+        .fold(0.0, f32::max);
+    if candidates.is_empty() || top_relevance < similarity_threshold {
         let response = RagAnswerResponse {
-            answer: "Tidak ditemukan konteks relevan di dokumen untuk pertanyaan ini.".to_string(),
-            sources,
-            confidence_score: 0.0,
-            processing_time_ms: processing_time,
+            answer: NOT_IN_KB_ANSWER.to_string(),
+            sources: Vec::new(),
+            confidence_score: top_relevance,
+            processing_time_ms: start_time.elapsed().as_millis() as i64,
             llm_model: None,
             tokens_used: None,
+            citations: Vec::new(),
+            groundedness_score: 0.0,
+            refused: true,
         };
         return Ok(Json(ApiResponse {
             code: 200,
             status: "OK".to_string(),
-            message: "RAG tidak menemukan sumber yang relevan".to_string(),
+            message: "Not in knowledge base".to_string(),
             data: response,
             errors: json!({}),
         }));
     }
 
+    let selected: Vec<&RetrievedChunk> = candidates
+        .iter()
+        .filter(|chunk| {
+            chunk.relevance() >= similarity_threshold
+                || passes_similarity_threshold(chunk, similarity_threshold)
+        })
+        .take(max_results as usize)
+        .collect();
+
+    // Batasi konteks ke anggaran token; sumber teratas selalu ikut
+    let token_budget = config.context_token_budget.max(1) as usize;
+    let mut used_tokens = 0usize;
+    let selected: Vec<&RetrievedChunk> = selected
+        .into_iter()
+        .enumerate()
+        .take_while(|(i, chunk)| {
+            used_tokens += chunking::estimate_tokens(&chunk.content);
+            *i == 0 || used_tokens <= token_budget
+        })
+        .map(|(_, chunk)| chunk)
+        .collect();
+
+    let sources: Vec<DocumentSource> = selected
+        .into_iter()
+        .map(|chunk| DocumentSource {
+            document_id: chunk.document_id,
+            document_title: chunk.document_title.clone(),
+            chunk_text: chunk.content.clone(),
+            similarity_score: chunk.similarity(),
+            page_number: chunk.page_number,
+            chunk_index: chunk.chunk_index,
+            retrieval_score: chunk.fused_score,
+            rerank_score: chunk.rerank_score,
+        })
+        .collect();
+
     let confidence_score =
         sources.iter().map(|s| s.similarity_score).sum::<f32>() / sources.len() as f32;
 
     // Construct prompts
-    let default_system_prompt = "Anda adalah asisten AI untuk bisnis retail.
-Jawab hanya berdasarkan KONTEN yang diberikan.
-Jika informasi tidak ada di konteks, katakan dengan jujur.
-Cantumkan rujukan sumber hanya dalam format: [Rujukan: Judul_Dokumen].
+    let default_system_prompt = format!(
+        "Anda adalah asisten AI untuk bisnis retail.
+Jawab hanya berdasarkan KONTEN yang diberikan; jangan menambah fakta dari luar KONTEN.
+Setiap kalimat yang berisi fakta wajib diakhiri penanda sitasi nomor sumber, mis. [1] atau [1, 2].
+Jika jawaban tidak ada di KONTEN, balas persis: {}
 Jangan buat bagian terpisah bernama 'Sumber:' dan jangan tampilkan ID, chunk, atau halaman.
-Gunakan bahasa Indonesia yang jelas dan ringkas.";
+Gunakan bahasa Indonesia yang jelas dan ringkas.",
+        rag_citations::NOT_IN_KB_MARKER
+    );
 
     let system_prompt = request
         .prompt_instructions
         .as_ref()
         .map(|s| format!("{}\n\nInstruksi tambahan: {}", default_system_prompt, s))
-        .unwrap_or_else(|| default_system_prompt.to_string());
+        .unwrap_or(default_system_prompt);
 
     // Build context JSON with sources
     let context_json = serde_json::json!({
         "question": request.query,
         "sources": sources.iter().enumerate().map(|(i, s)| serde_json::json!({
-            "index": i,
+            "citation": format!("[{}]", i + 1),
             "title": s.document_title,
             "similarity": s.similarity_score,
             "text": s.chunk_text,
@@ -976,7 +575,7 @@ Gunakan bahasa Indonesia yang jelas dan ringkas.";
     });
 
     let user_prompt = format!(
-        "Pertanyaan pengguna:\n{}\n\nKONTEN untuk menjawab (JSON):\n{}\n\nInstruksi:\n- Jawab ringkas, tepat, dan berbasis KONTEN.\n- Cantumkan SATU baris rujukan di akhir jawaban dengan format: [Rujukan: Judul_Dokumen].\n- Jangan menulis bagian 'Sumber:' terpisah dan jangan tampilkan ID, chunk, atau halaman.\n- Jika konteks tidak memadai, jelaskan keterbatasan.\n",
+        "Pertanyaan pengguna:\n{}\n\nKONTEN untuk menjawab (JSON):\n{}\n\nInstruksi:\n- Jawab ringkas, tepat, dan berbasis KONTEN.\n- Akhiri setiap kalimat faktual dengan penanda sitasi sesuai field \"citation\" sumbernya, mis. [1].\n- Jangan menulis bagian 'Sumber:' terpisah dan jangan tampilkan ID, chunk, atau halaman.\n- Jika KONTEN hanya menjawab sebagian, jawab bagian itu dan sebutkan keterbatasannya.\n",
         request.query, context_json
     );
 
@@ -1025,13 +624,53 @@ Gunakan bahasa Indonesia yang jelas dan ringkas.";
         .unwrap_or_else(|| "".to_string());
 
     let processing_time = start_time.elapsed().as_millis() as i64;
+    if rag_citations::is_refusal(&answer) {
+        let response = RagAnswerResponse {
+            answer: NOT_IN_KB_ANSWER.to_string(),
+            sources,
+            confidence_score,
+            processing_time_ms: processing_time,
+            llm_model: Some(groq_resp.model.clone()),
+            tokens_used: Some(groq_resp.usage.total_tokens),
+            citations: Vec::new(),
+            groundedness_score: 0.0,
+            refused: true,
+        };
+        return Ok(Json(ApiResponse {
+            code: 200,
+            status: "OK".to_string(),
+            message: "Not in knowledge base".to_string(),
+            data: response,
+            errors: json!({}),
+        }));
+    }
+
+    let source_texts: Vec<&str> = sources.iter().map(|s| s.chunk_text.as_str()).collect();
+    let grounded = rag_citations::ground_answer(&answer, &source_texts);
+    let citations = grounded
+        .claims
+        .iter()
+        .flat_map(|claim| {
+            claim.markers.iter().map(|marker| AnswerCitation {
+                marker: *marker,
+                source_index: marker - 1,
+                document_id: sources[marker - 1].document_id,
+                claim: claim.text.clone(),
+                support: claim.support,
+            })
+        })
+        .collect();
+
     let response = RagAnswerResponse {
-        answer,
+        answer: grounded.answer,
         sources,
         confidence_score,
         processing_time_ms: processing_time,
         llm_model: Some(groq_resp.model.clone()),
         tokens_used: Some(groq_resp.usage.total_tokens),
+        citations,
+        groundedness_score: grounded.groundedness,
+        refused: false,
     };
 
     Ok(Json(ApiResponse {
@@ -1049,6 +688,7 @@ Gunakan bahasa Indonesia yang jelas dan ringkas.";
 //     This handler prepares sensible defaults tailored for specific queries like "Andi".
 pub async fn answer_simple_rag(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Json(request): Json<crate::dto::rag::SimpleRagRequest>,
 ) -> Result<Json<ApiResponse<RagAnswerResponse>>, StatusCode> {
     if request.query.trim().is_empty() {
@@ -1061,10 +701,11 @@ pub async fn answer_simple_rag(
         query: request.query.clone(),
         document_ids: None,            // dapat dibatasi kemudian jika diperlukan
         category_filter: None,         // tidak membatasi kategori
+        tags_filter: None,
         max_results: Some(8),          // ambil hingga 8 sumber
         similarity_threshold: Some(0.20), // turunkan threshold untuk recall lebih tinggi
         prompt_instructions: Some(
-            "Ringkas kinerja karyawan bernama Andi selama satu tahun berdasarkan KONTEN. Fokuskan pada KPI utama, pencapaian, kekuatan, area perbaikan, dan rekomendasi singkat. Tambahkan SATU rujukan di akhir dalam format: [Rujukan: Judul_Dokumen]."
+            "Ringkas kinerja karyawan bernama Andi selama satu tahun berdasarkan KONTEN. Fokuskan pada KPI utama, pencapaian, kekuatan, area perbaikan, dan rekomendasi singkat. Akhiri setiap kalimat faktual dengan penanda sitasi sumber, mis. [1]."
                 .to_string(),
         ),
         max_tokens: Some(512),
@@ -1073,41 +714,49 @@ pub async fn answer_simple_rag(
 
     // ID: Delegasikan ke handler utama RAG+LLM untuk pemrosesan lengkap.
     // EN: Delegate to the main RAG+LLM handler for full processing.
-    answer_with_rag_and_llm(State(data), Json(rag_req)).await
+    answer_with_rag_and_llm(State(data), Extension(jwt_auth), Json(rag_req)).await
 }
 
 // List documents endpoint
 pub async fn list_documents(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Query(params): Query<DocumentListRequest>,
 ) -> Result<Json<ApiResponse<DocumentListResponse>>, StatusCode> {
     let page = params.page.unwrap_or(1).max(1);
     let limit = params.limit.unwrap_or(20).min(100);
     let offset = (page - 1) * limit;
+    // Aturan akses sama dengan retrieval: dokumen platform + dokumen toko pemanggil
+    let access = rag_caller(&data, &jwt_auth).await?.access();
 
-    // Simple query without dynamic building for now
-    let documents_query = r#"
-        SELECT d.*, COALESCE(COUNT(dc.id), 0) as chunk_count 
-        FROM documents d 
-        LEFT JOIN document_chunks dc ON d.id = dc.document_id AND dc.deleted_at = 0
-        WHERE d.status != 'deleted' AND d.deleted_at = 0
-        GROUP BY d.id 
-        ORDER BY d.created_at DESC
-        LIMIT $1 OFFSET $2
-    "#;
-
-    let count_query = "SELECT COUNT(*) FROM documents WHERE status != 'deleted' AND deleted_at = 0";
+    let mut count_query = QueryBuilder::<Postgres>::new(
+        "SELECT COUNT(*) FROM documents d WHERE d.status != 'deleted' AND d.deleted_at = 0",
+    );
+    access.push_condition(&mut count_query);
 
     // Get total count
-    let total_count: i64 = sqlx::query_scalar(count_query)
+    let total_count: i64 = count_query
+        .build_query_scalar()
         .fetch_one(&data.db)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    let mut documents_query = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT d.*, COALESCE(COUNT(dc.id), 0) as chunk_count 
+        FROM documents d 
+        LEFT JOIN document_chunks dc ON d.id = dc.document_id AND dc.deleted_at = 0
+        WHERE d.status != 'deleted' AND d.deleted_at = 0"#,
+    );
+    access.push_condition(&mut documents_query);
+    documents_query.push(" GROUP BY d.id ORDER BY d.created_at DESC LIMIT ");
+    documents_query.push_bind(limit);
+    documents_query.push(" OFFSET ");
+    documents_query.push_bind(offset);
+
     // Get documents
-    let rows = sqlx::query(documents_query)
-        .bind(limit)
-        .bind(offset)
+    let rows = documents_query
+        .build()
         .fetch_all(&data.db)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -1126,6 +775,8 @@ pub async fn list_documents(
             chunk_count: row.get::<i64, _>("chunk_count") as i32,
             uploaded_at: row.get("created_at"),
             last_accessed: row.get("last_accessed"),
+            store_uuid: row.get("store_uuid"),
+            visibility: row.get("visibility"),
         });
     }
 
@@ -1151,15 +802,11 @@ pub async fn list_documents(
 // Get document processing status
 pub async fn get_document_status(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Path(document_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<DocumentProcessingStatus>>, StatusCode> {
-    let document =
-        sqlx::query_as::<_, Document>("SELECT * FROM documents WHERE id = $1 AND deleted_at = 0")
-            .bind(document_id)
-            .fetch_optional(&data.db)
-            .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-            .ok_or(StatusCode::NOT_FOUND)?;
+    let caller = rag_caller(&data, &jwt_auth).await?;
+    let document = find_document_for(&data, &caller, document_id, false).await?;
 
     let job = sqlx::query_as::<_, DocumentProcessingJob>(
         "SELECT * FROM document_processing_jobs WHERE document_id = $1 AND deleted_at = 0 ORDER BY created_at DESC LIMIT 1"
@@ -1169,24 +816,84 @@ pub async fn get_document_status(
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let status = DocumentProcessingStatus {
-        document_id,
-        status: document.status.clone(),
-        progress_percentage: document.processing_progress,
-        current_step: document
-            .current_processing_step
-            .unwrap_or("Unknown".to_string()),
-        error_message: document.error_message,
-        chunks_processed: document.chunk_count,
-        total_chunks: document.chunk_count, // Simplified
-        estimated_completion: job.and_then(|j| j.completed_at),
-    };
+    let status = DocumentProcessingStatus {
+        document_id,
+        status: document.status.clone(),
+        progress_percentage: document.processing_progress,
+        current_step: document
+            .current_processing_step
+            .unwrap_or("Unknown".to_string()),
+        error_message: document.error_message,
+        chunks_processed: document.chunk_count,
+        total_chunks: document.chunk_count, // Simplified
+        estimated_completion: job.as_ref().and_then(|j| j.completed_at),
+        job_status: job.as_ref().map(|j| j.status.clone()),
+        retry_count: job.as_ref().map(|j| j.retry_count).unwrap_or(0),
+        max_retries: job.as_ref().map(|j| j.max_retries).unwrap_or(0),
+        next_retry_at: job
+            .as_ref()
+            .filter(|j| j.status == "pending" && j.retry_count > 0)
+            .and_then(|j| j.next_attempt_at),
+    };
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Document status retrieved successfully".to_string(),
+        data: status,
+        errors: json!({}),
+    }))
+}
+
+// Reindex document: re-run extraction, chunking and embedding through the ingestion queue
+pub async fn reindex_document(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<DocumentReindexResponse>>, StatusCode> {
+    let caller = rag_caller(&data, &jwt_auth).await?;
+    let document = find_document_for(&data, &caller, document_id, true).await?;
+
+    // Dokumen teks lama (inline://) tidak menyimpan sumber aslinya
+    if document.file_path.starts_with("inline://") {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let active_job: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM document_processing_jobs
+        WHERE document_id = $1
+          AND status IN ('pending', 'processing')
+          AND deleted_at = 0
+        LIMIT 1
+        "#,
+    )
+    .bind(document_id)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if active_job.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let job_id =
+        crate::workers::rag_ingest::enqueue_document_job(&data.db, document_id, "reprocess", 3)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to enqueue reindex for {}: {}", document_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
 
     Ok(Json(ApiResponse {
-        code: 200,
-        status: "OK".to_string(),
-        message: "Document status retrieved successfully".to_string(),
-        data: status,
+        code: 202,
+        status: "ACCEPTED".to_string(),
+        message: "Document queued for reindexing".to_string(),
+        data: DocumentReindexResponse {
+            document_id,
+            job_id,
+            status: "pending".to_string(),
+        },
         errors: json!({}),
     }))
 }
@@ -1194,15 +901,24 @@ pub async fn get_document_status(
 // Delete document
 pub async fn delete_document(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Path(document_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    let caller = rag_caller(&data, &jwt_auth).await?;
+    find_document_for(&data, &caller, document_id, true).await?;
+
+    let mut tx = data
+        .db
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     let result = sqlx::query(
-        "UPDATE documents SET status = 'deleted', updated_at = $1, deleted_at = $2 WHERE id = $3",
+        "UPDATE documents SET status = 'deleted', updated_at = $1, deleted_at = $2 WHERE id = $3 AND deleted_at = 0",
     )
     .bind(Utc::now())
     .bind(Utc::now().timestamp_millis())
     .bind(document_id)
-    .execute(&data.db)
+    .execute(&mut *tx)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -1210,6 +926,15 @@ pub async fn delete_document(
         return Err(StatusCode::NOT_FOUND);
     }
 
+    let vector_ids = retire_document_chunks(&mut tx, document_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    delete_milvus_vectors(&data, &vector_ids).await;
+
     Ok(Json(ApiResponse {
         code: 200,
         status: "OK".to_string(),
@@ -1230,9 +955,14 @@ pub async fn get_rag_configuration(
         chunk_size: config.chunk_size,
         chunk_overlap: config.chunk_overlap,
         embedding_model: config.embedding_model,
+        embedding_dimensions: config.embedding_dimensions,
         similarity_threshold: config.similarity_threshold,
         max_results: config.max_results,
         enable_reranking: config.enable_reranking,
+        reranking_model: config.reranking_model,
+        chunking_strategy: config.chunking_strategy,
+        chunk_size_unit: config.chunk_size_unit,
+        context_token_budget: config.context_token_budget,
         created_at: config.created_at,
         updated_at: config.updated_at,
     };
@@ -1251,6 +981,30 @@ pub async fn update_rag_configuration(
     State(data): State<Arc<AppState>>,
     Json(request): Json<UpdateRagConfigRequest>,
 ) -> Result<Json<ApiResponse<RagConfig>>, StatusCode> {
+    if matches!(request.embedding_dimensions, Some(dims) if dims < 8)
+        || matches!(request.context_token_budget, Some(budget) if budget < 1)
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    // Simpan nilai kanonik (mis. "paragraph" -> "sentence")
+    let chunking_strategy = match request.chunking_strategy.as_deref() {
+        Some(value) => Some(
+            ChunkingStrategy::parse(value)
+                .ok_or(StatusCode::BAD_REQUEST)?
+                .as_str(),
+        ),
+        None => None,
+    };
+    let chunk_size_unit = match request.chunk_size_unit.as_deref() {
+        Some(value) => Some(
+            SizeUnit::parse(value)
+                .ok_or(StatusCode::BAD_REQUEST)?
+                .as_str(),
+        ),
+        None => None,
+    };
+    let previous = get_rag_config(&data).await?;
+
     // For simplicity, let's update all fields at once
     let query = r#"
         UPDATE rag_configurations 
@@ -1261,7 +1015,12 @@ pub async fn update_rag_configuration(
             similarity_threshold = COALESCE($4, similarity_threshold),
             max_results = COALESCE($5, max_results),
             enable_reranking = COALESCE($6, enable_reranking),
-            updated_at = $7
+            updated_at = $7,
+            embedding_dimensions = COALESCE($8, embedding_dimensions),
+            reranking_model = COALESCE($9, reranking_model),
+            chunking_strategy = COALESCE($10, chunking_strategy),
+            chunk_size_unit = COALESCE($11, chunk_size_unit),
+            context_token_budget = COALESCE($12, context_token_budget)
         WHERE id = (SELECT id FROM rag_configurations WHERE deleted_at = 0 ORDER BY created_at DESC LIMIT 1) 
         RETURNING *
     "#;
@@ -1274,18 +1033,35 @@ pub async fn update_rag_configuration(
         .bind(request.max_results)
         .bind(request.enable_reranking)
         .bind(Utc::now())
+        .bind(request.embedding_dimensions)
+        .bind(request.reranking_model)
+        .bind(chunking_strategy)
+        .bind(chunk_size_unit)
+        .bind(request.context_token_budget)
         .fetch_one(&data.db)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    // Model/dimensi embedding berubah: chunk lama di-embed ulang di background
+    let reembedding = config.embedding_model != previous.embedding_model
+        || config.embedding_dimensions != previous.embedding_dimensions;
+    if reembedding {
+        crate::workers::rag_reembed::spawn_reembed(data.clone());
+    }
+
     let response = RagConfig {
         id: config.id,
         chunk_size: config.chunk_size,
         chunk_overlap: config.chunk_overlap,
         embedding_model: config.embedding_model,
+        embedding_dimensions: config.embedding_dimensions,
         similarity_threshold: config.similarity_threshold,
         max_results: config.max_results,
         enable_reranking: config.enable_reranking,
+        reranking_model: config.reranking_model,
+        chunking_strategy: config.chunking_strategy,
+        chunk_size_unit: config.chunk_size_unit,
+        context_token_budget: config.context_token_budget,
         created_at: config.created_at,
         updated_at: config.updated_at,
     };
@@ -1293,7 +1069,11 @@ pub async fn update_rag_configuration(
     Ok(Json(ApiResponse {
         code: 200,
         status: "OK".to_string(),
-        message: "Configuration updated successfully".to_string(),
+        message: if reembedding {
+            "Configuration updated successfully; re-embedding existing chunks".to_string()
+        } else {
+            "Configuration updated successfully".to_string()
+        },
         data: response,
         errors: json!({}),
     }))
@@ -1301,31 +1081,36 @@ pub async fn update_rag_configuration(
 
 async fn process_uploaded_document(
     data: &Arc<AppState>,
-    document_id: Uuid,
-    file_path: &str,
-    file_type: &str,
+    document: &Document,
+    file_bytes: &[u8],
     config: &RagConfiguration,
+    job_id: Option<Uuid>,
 ) -> anyhow::Result<usize> {
-    let resolved_path = PathBuf::from(file_path);
-    if !resolved_path.exists() {
-        return Err(anyhow!("Uploaded file is missing on disk"));
-    }
-
-    let file_type_lower = file_type.to_lowercase();
-    let content = match file_type_lower.as_str() {
-        "docx" => {
-            let path_clone = resolved_path.clone();
-            task::spawn_blocking(move || extract_text_from_docx(&path_clone))
-                .await
-                .map_err(|e| anyhow!("Failed to join docx extraction task: {}", e))??
+    let document_id = document.id;
+    let file_type_lower = document.file_type.to_lowercase();
+    report_progress(data, document_id, job_id, 10, "Extracting content").await;
+    let extracted = match file_type_lower.as_str() {
+        "docx" => ExtractedDocument::plain(
+            extract_text_via_temp_file(document_id, "docx", file_bytes, extract_text_from_docx)
+                .await?,
+        ),
+        "xlsx" | "xls" | "xlsm" => ExtractedDocument::plain(
+            extract_text_via_temp_file(
+                document_id,
+                &file_type_lower,
+                file_bytes,
+                extract_text_from_xlsx,
+            )
+            .await?,
+        ),
+        "pdf" => extract_in_background("pdf", file_bytes, document_extract::extract_pdf).await?,
+        "html" | "htm" | "xhtml" => {
+            extract_in_background("html", file_bytes, document_extract::extract_html).await?
         }
-        "xlsx" | "xls" | "xlsm" => {
-            let path_clone = resolved_path.clone();
-            task::spawn_blocking(move || extract_text_from_xlsx(&path_clone))
-                .await
-                .map_err(|e| anyhow!("Failed to join xlsx extraction task: {}", e))??
+        "epub" => extract_in_background("epub", file_bytes, document_extract::extract_epub).await?,
+        "csv" | "txt" | "md" | "json" => {
+            ExtractedDocument::plain(String::from_utf8_lossy(file_bytes).into_owned())
         }
-        "csv" | "txt" | "md" | "json" => read_plain_text_file(&resolved_path).await?,
         other => {
             return Err(anyhow!(
                 "Unsupported file type '{}' for RAG ingestion",
@@ -1334,104 +1119,271 @@ async fn process_uploaded_document(
         }
     };
 
-    if content.trim().is_empty() {
+    if extracted.text.trim().is_empty() {
         return Err(anyhow!("Document does not contain extractable text"));
     }
 
     let base_metadata = json!({
         "source": "upload",
         "file_type": file_type_lower,
-        "file_path": file_path,
+        "file_path": document.file_path,
     });
 
-    ingest_document_content(data, document_id, &content, config, base_metadata).await
+    ingest_document_content(
+        data,
+        document,
+        &extracted,
+        &file_type_lower,
+        config,
+        base_metadata,
+        job_id,
+    )
+    .await
 }
 
-async fn ingest_document_content(
+/// ID: Jalankan satu job dari antrian ingest: ambil file dari blob store lalu ekstrak, chunk,
+/// embed dan simpan. Status akhir dokumen/job dicatat oleh worker.
+/// EN: Run one ingestion queue job: load the file from the blob store, then extract, chunk,
+/// embed and store. The worker records the final document/job status.
+pub(crate) async fn run_ingestion_job(
+    data: &Arc<AppState>,
+    document: &Document,
+    job_id: Uuid,
+) -> anyhow::Result<usize> {
+    let config = get_rag_config(data)
+        .await
+        .map_err(|_| anyhow!("Failed to load RAG configuration"))?;
+
+    report_progress(data, document.id, Some(job_id), 5, "Loading file").await;
+    let file_bytes = data
+        .blob_store
+        .get(&document.file_path)
+        .await
+        .map_err(|e| anyhow!("Failed to load {}: {}", document.file_path, e))?;
+
+    process_uploaded_document(data, document, &file_bytes, &config, Some(job_id)).await
+}
+
+// Helper: catat progres per langkah ke dokumen (dan job antrian bila ada); updated_at job
+// sekaligus menjadi heartbeat lease worker
+async fn report_progress(
     data: &Arc<AppState>,
     document_id: Uuid,
-    content: &str,
+    job_id: Option<Uuid>,
+    progress: i32,
+    step: &str,
+) {
+    let now = Utc::now();
+    let _ = sqlx::query(
+        "UPDATE documents SET processing_progress = $1, current_processing_step = $2, updated_at = $3 WHERE id = $4",
+    )
+    .bind(progress)
+    .bind(step)
+    .bind(now)
+    .bind(document_id)
+    .execute(&data.db)
+    .await;
+
+    if let Some(job_id) = job_id {
+        let _ = sqlx::query(
+            "UPDATE document_processing_jobs SET progress_percentage = $1, current_step = $2, updated_at = $3 WHERE id = $4",
+        )
+        .bind(progress)
+        .bind(step)
+        .bind(now)
+        .bind(job_id)
+        .execute(&data.db)
+        .await;
+    }
+}
+
+/// ID: Jalankan extractor CPU-bound (PDF/HTML/EPUB) di thread blocking.
+/// EN: Run a CPU-bound extractor (PDF/HTML/EPUB) on a blocking thread.
+async fn extract_in_background(
+    kind: &str,
+    file_bytes: &[u8],
+    extract: fn(&[u8]) -> anyhow::Result<ExtractedDocument>,
+) -> anyhow::Result<ExtractedDocument> {
+    let bytes = file_bytes.to_vec();
+    task::spawn_blocking(move || extract(&bytes))
+        .await
+        .map_err(|e| anyhow!("Failed to join {} extraction task: {}", kind, e))?
+}
+
+async fn ingest_document_content(
+    data: &Arc<AppState>,
+    document: &Document,
+    extracted: &ExtractedDocument,
+    file_type: &str,
     config: &RagConfiguration,
     base_metadata: serde_json::Value,
+    job_id: Option<Uuid>,
 ) -> anyhow::Result<usize> {
-    let chunk_size = config.chunk_size.max(1) as usize;
-    let chunk_overlap = config.chunk_overlap.max(0) as usize;
-    let embed_dim = config.embedding_dimensions.max(8) as usize;
-
-    let chunks = chunk_text(content, chunk_size, chunk_overlap);
+    let document_id = document.id;
+    report_progress(data, document_id, job_id, 25, "Chunking content").await;
+    let chunks = chunking::chunk_document(&extracted.text, &chunk_options(config, file_type));
     if chunks.is_empty() {
         return Err(anyhow!("No chunks generated from document content"));
     }
 
-    let texts: Vec<String> = chunks.iter().map(|c| c.0.clone()).collect();
-    let embeddings = embed_texts_local(&texts, embed_dim);
+    report_progress(data, document_id, job_id, 40, "Generating embeddings").await;
+    let texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+    let embedder = rag_embedder(data, config);
+    let embeddings = embedder
+        .embed(&texts)
+        .await
+        .context("Embedding document chunks failed")?;
+    let chunk_ids: Vec<Uuid> = chunks.iter().map(|_| Uuid::new_v4()).collect();
+
+    // Vektor dikirim ke Milvus lebih dulu: bila gagal, chunk lama di Postgres belum tersentuh
+    // sehingga job bisa diulang dengan aman. In mock dependency mode, skip Milvus operations.
+    if !data.env.allow_mock_dependencies {
+        report_progress(data, document_id, job_id, 65, "Indexing vectors").await;
+        let milvus_client = data
+            .milvus_client
+            .as_ref()
+            .ok_or_else(|| {
+                anyhow!("Milvus client not configured; set MILVUS_URI before ingesting documents")
+            })?
+            .clone();
+
+        let mut guard = milvus_client.lock().await;
+        crate::workers::rag_reembed::sync_collection_dimensions(
+            data,
+            &mut guard,
+            embedder.dimensions() as i32,
+        )
+        .await
+        .context("Ensuring Milvus collection failed")?;
+        // Partisi basis pengetahuan pemilik dokumen (toko atau platform)
+        crate::services::milvus::upsert_chunk_embeddings(
+            &mut guard,
+            &data.milvus_collection,
+            &crate::services::milvus::document_partition(document.store_uuid),
+            &chunk_ids,
+            &embeddings,
+        )
+        .await
+        .context("Failed to upsert embeddings to Milvus")?;
+    }
 
-    let mut chunk_ids = Vec::with_capacity(chunks.len());
+    report_progress(data, document_id, job_id, 85, "Storing chunks").await;
     let inserted_at = Utc::now();
+    let mut tx = data.db.begin().await?;
 
-    for (idx, (chunk_text, start_char, end_char)) in chunks.iter().enumerate() {
-        let chunk_id = Uuid::new_v4();
-        chunk_ids.push(chunk_id);
+    // Reindex / retry: chunk versi sebelumnya diganti dalam transaksi yang sama
+    let retired_vector_ids = retire_document_chunks(&mut tx, document_id)
+        .await
+        .context("Failed to retire previous document chunks")?;
 
-        let content_hash = format!("{:x}", Sha256::digest(chunk_text.as_bytes()));
-        let vector_id = crate::services::milvus::uuid_to_i64(&chunk_id);
+    for (idx, (chunk, chunk_id)) in chunks.iter().zip(chunk_ids.iter()).enumerate() {
+        let content_hash = format!("{:x}", Sha256::digest(chunk.text.as_bytes()));
+        let vector_id = crate::services::milvus::uuid_to_i64(chunk_id);
 
+        let page_number = extracted.page_for_offset(chunk.start_char);
         let mut metadata = base_metadata.clone();
         if let Some(map) = metadata.as_object_mut() {
             map.insert("chunk_index".to_string(), json!(idx));
+            if let Some(page) = page_number {
+                map.insert("page_number".to_string(), json!(page));
+            }
+            if let Some(heading) = &chunk.heading {
+                map.insert("section".to_string(), json!(heading));
+            }
+            if let Some(sheet) = &chunk.sheet {
+                map.insert("sheet".to_string(), json!(sheet));
+            }
+            if let Some((first_row, last_row)) = chunk.rows {
+                map.insert("rows".to_string(), json!([first_row, last_row]));
+            }
         }
 
         sqlx::query(
             r#"
             INSERT INTO document_chunks (
                 id, document_id, chunk_index, content, content_hash, embedding,
-                page_number, start_char, end_char, metadata, created_at, milvus_vector_id
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                page_number, start_char, end_char, metadata, created_at, milvus_vector_id,
+                embedding_model
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             "#,
         )
         .bind(chunk_id)
         .bind(document_id)
         .bind(idx as i32)
-        .bind(chunk_text)
+        .bind(&chunk.text)
         .bind(content_hash)
         .bind(&embeddings[idx])
-        .bind(None::<i32>)
-        .bind(*start_char as i32)
-        .bind(*end_char as i32)
+        .bind(page_number)
+        .bind(chunk.start_char as i32)
+        .bind(chunk.end_char as i32)
         .bind(metadata)
         .bind(inserted_at)
         .bind(vector_id)
-        .execute(&data.db)
+        .bind(embedder.model())
+        .execute(&mut *tx)
         .await
         .context("Failed to insert document chunk")?;
     }
-    // In mock dependency mode, skip Milvus operations and finish successfully
-    if data.env.allow_mock_dependencies {
-        return Ok(chunks.len());
-    }
+    tx.commit()
+        .await
+        .context("Failed to commit document chunks")?;
 
-    let milvus_client = data
-        .milvus_client
-        .as_ref()
-        .ok_or_else(|| {
-            anyhow!("Milvus client not configured; set MILVUS_URI before ingesting documents")
-        })?
-        .clone();
+    delete_milvus_vectors(data, &retired_vector_ids).await;
+    Ok(chunks.len())
+}
 
-    let mut guard = milvus_client.lock().await;
-    crate::services::milvus::ensure_rag_collection(&mut guard, &data.milvus_collection)
-        .await
-        .context("Ensuring Milvus collection failed")?;
-    crate::services::milvus::upsert_chunk_embeddings(
-        &mut guard,
-        &data.milvus_collection,
-        &chunk_ids,
-        &embeddings,
+// Helper: soft-delete chunk aktif sebuah dokumen, kembalikan PK Milvus-nya
+async fn retire_document_chunks(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    document_id: Uuid,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        UPDATE document_chunks
+        SET deleted_at = $1
+        WHERE document_id = $2 AND deleted_at = 0
+        RETURNING id, milvus_vector_id
+        "#,
     )
-    .await
-    .context("Failed to upsert embeddings to Milvus")?;
+    .bind(Utc::now().timestamp_millis())
+    .bind(document_id)
+    .fetch_all(&mut **tx)
+    .await?;
 
-    Ok(chunks.len())
+    Ok(rows
+        .iter()
+        .map(|row| {
+            row.get::<Option<i64>, _>("milvus_vector_id")
+                .unwrap_or_else(|| crate::services::milvus::uuid_to_i64(&row.get("id")))
+        })
+        .collect())
+}
+
+// Helper: hapus vektor chunk lama dari Milvus. Kegagalan hanya dicatat; sisa vektor yatim
+// dibereskan oleh `cargo run --bin reconcile_rag_vectors`
+async fn delete_milvus_vectors(data: &Arc<AppState>, vector_ids: &[i64]) {
+    if vector_ids.is_empty() || data.env.allow_mock_dependencies {
+        return;
+    }
+    let Some(client) = data.milvus_client.as_ref() else {
+        tracing::warn!(
+            count = vector_ids.len(),
+            "Milvus client not configured; stale vectors left for reconciliation"
+        );
+        return;
+    };
+
+    let mut guard = client.lock().await;
+    if let Err(e) =
+        crate::services::milvus::delete_vectors(&mut guard, &data.milvus_collection, vector_ids)
+            .await
+    {
+        tracing::warn!(
+            count = vector_ids.len(),
+            error = %e,
+            "Failed to delete stale vectors from Milvus; run reconcile_rag_vectors"
+        );
+    }
 }
 
 async fn resolve_document_category(
@@ -1638,14 +1590,26 @@ fn extract_text_from_xlsx(path: &StdPath) -> anyhow::Result<String> {
     Ok(output.trim().to_string())
 }
 
-async fn read_plain_text_file(path: &StdPath) -> anyhow::Result<String> {
-    let bytes = tokio::fs::read(path)
+/// ID: Extractor DOCX/XLSX membaca dari path, jadi bytes dari blob store ditulis ke file sementara.
+/// EN: DOCX/XLSX extractors read from a path, so blob bytes are written to a temp file first.
+async fn extract_text_via_temp_file(
+    document_id: Uuid,
+    extension: &str,
+    file_bytes: &[u8],
+    extract: fn(&StdPath) -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    let temp_path: PathBuf =
+        std::env::temp_dir().join(format!("rag-{}.{}", document_id, extension));
+    tokio::fs::write(&temp_path, file_bytes)
+        .await
+        .with_context(|| format!("Writing temp file {:?}", temp_path))?;
+
+    let path_clone = temp_path.clone();
+    let result = task::spawn_blocking(move || extract(&path_clone))
         .await
-        .with_context(|| format!("Reading text file {:?}", path))?;
-    Ok(match String::from_utf8(bytes) {
-        Ok(s) => s,
-        Err(err) => String::from_utf8_lossy(&err.into_bytes()).into_owned(),
-    })
+        .map_err(|e| anyhow!("Failed to join {} extraction task: {}", extension, e));
+    let _ = tokio::fs::remove_file(&temp_path).await;
+    result?
 }
 
 fn data_type_to_string(value: &DataType) -> String {
@@ -1663,7 +1627,7 @@ fn data_type_to_string(value: &DataType) -> String {
 }
 
 // Helper functions
-async fn get_rag_config(data: &Arc<AppState>) -> Result<RagConfiguration, StatusCode> {
+pub(crate) async fn get_rag_config(data: &Arc<AppState>) -> Result<RagConfiguration, StatusCode> {
     sqlx::query_as::<_, RagConfiguration>(
         "SELECT * FROM rag_configurations WHERE deleted_at = 0 ORDER BY created_at DESC LIMIT 1",
     )
@@ -1674,53 +1638,294 @@ async fn get_rag_config(data: &Arc<AppState>) -> Result<RagConfiguration, Status
     .pipe(Ok)
 }
 
-fn calculate_text_similarity(query: &str, content: &str) -> f32 {
-    // Robust text similarity with Indonesian-friendly normalization and fuzzy matching
-    let q_tokens = tokenize_and_normalize(query);
-    let c_tokens = tokenize_and_normalize(content);
+/// ID: Identitas pemanggil untuk ACL basis pengetahuan: toko di profil dan peran Super Admin.
+/// EN: Caller identity for the knowledge-base ACL: profile store and Super Admin role.
+#[derive(Debug, Clone)]
+pub(crate) struct RagCaller {
+    pub user_uuid: Uuid,
+    pub store_uuid: Option<Uuid>,
+    pub is_super_admin: bool,
+}
+
+impl RagCaller {
+    pub fn access(&self) -> DocumentAccess {
+        if self.is_super_admin {
+            DocumentAccess::All
+        } else {
+            DocumentAccess::Store(self.store_uuid)
+        }
+    }
+
+    pub fn can_read(&self, document: &Document) -> bool {
+        document.visibility == DOCUMENT_VISIBILITY_PLATFORM || self.can_manage(document)
+    }
 
-    if q_tokens.is_empty() || c_tokens.is_empty() {
-        return 0.0;
+    // Hapus/reindex: hanya toko pemilik atau Super Admin (termasuk dokumen platform)
+    fn can_manage(&self, document: &Document) -> bool {
+        self.is_super_admin
+            || (document.store_uuid.is_some() && document.store_uuid == self.store_uuid)
     }
 
-    // Recall-style match: for each query token, check exact/fuzzy match in content tokens
-    let mut matched = 0usize;
-    for q in &q_tokens {
-        if c_tokens.contains(q) {
-            matched += 1;
-            continue;
+    /// ID: Pemilik dokumen baru. Default toko pemanggil; `platform` hanya untuk Super Admin
+    /// (juga default bagi Super Admin tanpa toko).
+    /// EN: Owner of a new document. Defaults to the caller's store; `platform` is Super Admin
+    /// only (and the default for a Super Admin without a store).
+    fn document_owner(&self, requested: Option<&str>) -> Result<Option<Uuid>, StatusCode> {
+        let requested = requested.map(str::trim).filter(|v| !v.is_empty());
+        match requested {
+            Some(DOCUMENT_VISIBILITY_PLATFORM) if self.is_super_admin => Ok(None),
+            Some(DOCUMENT_VISIBILITY_PLATFORM) => Err(StatusCode::FORBIDDEN),
+            Some(DOCUMENT_VISIBILITY_STORE) | None => match self.store_uuid {
+                Some(store_uuid) => Ok(Some(store_uuid)),
+                None if self.is_super_admin && requested.is_none() => Ok(None),
+                None => Err(StatusCode::FORBIDDEN),
+            },
+            Some(_) => Err(StatusCode::BAD_REQUEST),
         }
-        let found_fuzzy = c_tokens.iter().any(|c| {
-            if c == q {
-                return true;
+    }
+}
+
+pub(crate) async fn rag_caller(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+) -> Result<RagCaller, StatusCode> {
+    let profile = sqlx::query(
+        "SELECT store_uuid, roles_number FROM profiles WHERE user_uuid = $1 AND deleted_at = 0 LIMIT 1",
+    )
+    .bind(jwt_auth.user.uuid)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let (store_uuid, roles_number) = profile
+        .map(|row| {
+            (
+                row.get::<Option<Uuid>, _>("store_uuid"),
+                row.get::<Option<i32>, _>("roles_number"),
+            )
+        })
+        .unwrap_or((None, None));
+    Ok(RagCaller {
+        user_uuid: jwt_auth.user.uuid,
+        store_uuid,
+        is_super_admin: roles_number == Some(SUPER_ADMIN_ROLE_NUMBER),
+    })
+}
+
+fn document_visibility(store_uuid: Option<Uuid>) -> &'static str {
+    if store_uuid.is_some() {
+        DOCUMENT_VISIBILITY_STORE
+    } else {
+        DOCUMENT_VISIBILITY_PLATFORM
+    }
+}
+
+// Dokumen di luar cakupan pemanggil dilaporkan 404 agar keberadaannya tidak bocor;
+// dokumen yang terlihat tapi bukan milik toko pemanggil ditolak 403 saat `manage`
+async fn find_document_for(
+    data: &Arc<AppState>,
+    caller: &RagCaller,
+    document_id: Uuid,
+    manage: bool,
+) -> Result<Document, StatusCode> {
+    let document =
+        sqlx::query_as::<_, Document>("SELECT * FROM documents WHERE id = $1 AND deleted_at = 0")
+            .bind(document_id)
+            .fetch_optional(&data.db)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .filter(|document| caller.can_read(document))
+            .ok_or(StatusCode::NOT_FOUND)?;
+    if manage && !caller.can_manage(&document) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(document)
+}
+
+// Partisi Milvus yang boleh dicari; partisi lama `_default` berisi vektor campuran sehingga
+// hasilnya tetap disaring ulang oleh filter akses di Postgres
+fn milvus_partitions(access: &DocumentAccess) -> Vec<String> {
+    match access {
+        DocumentAccess::All => Vec::new(),
+        DocumentAccess::Store(store_uuid) => {
+            let mut partitions = vec![
+                crate::services::milvus::LEGACY_PARTITION.to_string(),
+                crate::services::milvus::document_partition(None),
+            ];
+            if store_uuid.is_some() {
+                partitions.push(crate::services::milvus::document_partition(*store_uuid));
             }
-            // substring containment (handles inflectional variants)
-            if c.contains(q) || q.contains(c) {
-                return true;
+            partitions
+        }
+    }
+}
+
+fn rag_embedder(data: &Arc<AppState>, config: &RagConfiguration) -> Arc<dyn Embedder> {
+    data.embedding_settings.embedder_for(
+        &config.embedding_model,
+        config.embedding_dimensions.max(8) as usize,
+    )
+}
+
+fn retrieval_filters(
+    access: DocumentAccess,
+    category: &Option<String>,
+    tags: &Option<Vec<String>>,
+    document_ids: &Option<Vec<Uuid>>,
+) -> RetrievalFilters {
+    RetrievalFilters {
+        access,
+        category: category.clone(),
+        tags: tags.clone(),
+        document_ids: document_ids.clone(),
+    }
+}
+
+// Chunk yang cocok secara keyword selalu lolos; chunk hanya-vektor harus >= threshold
+pub(crate) fn passes_similarity_threshold(chunk: &RetrievedChunk, threshold: f32) -> bool {
+    chunk.keyword_score.is_some() || chunk.vector_score.is_some_and(|score| score >= threshold)
+}
+
+/// ID: Rerank N kandidat teratas bila enable_reranking aktif; sisanya tetap urutan retrieval.
+///     Kegagalan reranker hanya dicatat agar query tetap dijawab dengan urutan retrieval.
+/// EN: Rerank the top N candidates when enable_reranking is on; the rest keep retrieval order.
+///     Reranker failures are only logged so the query is still answered in retrieval order.
+pub(crate) async fn rerank_candidates(
+    data: &Arc<AppState>,
+    config: &RagConfiguration,
+    query: &str,
+    mut candidates: Vec<RetrievedChunk>,
+) -> Vec<RetrievedChunk> {
+    if !config.enable_reranking || candidates.is_empty() {
+        return candidates;
+    }
+    let Some(reranker) = data
+        .rerank_settings
+        .reranker_for(config.reranking_model.as_deref())
+    else {
+        tracing::warn!("Reranking enabled but neither RERANK_API_URL nor GROQ_API_KEY is set");
+        return candidates;
+    };
+
+    let top_n = data.rerank_settings.top_n.min(candidates.len());
+    let passages: Vec<String> = candidates[..top_n]
+        .iter()
+        .map(|chunk| chunk.content.clone())
+        .collect();
+    match reranker.rerank(query, &passages).await {
+        Ok(scores) => {
+            for (chunk, score) in candidates.iter_mut().zip(scores) {
+                chunk.rerank_score = Some(score);
             }
-            // small edit distance tolerates typos like "meleyani" vs "melayani"
-            levenshtein_distance(q, c) <= 1
-        });
-        if found_fuzzy {
-            matched += 1;
+            candidates[..top_n].sort_by(|a, b| {
+                b.rerank_score
+                    .partial_cmp(&a.rerank_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        Err(err) => {
+            tracing::warn!(
+                "Reranker {} failed, keeping retrieval order: {:?}",
+                reranker.model(),
+                err
+            );
         }
     }
-    let recall = matched as f32 / (q_tokens.len() as f32);
-
-    // Jaccard over sets for stability
-    use std::collections::HashSet;
-    let q_set: HashSet<&String> = q_tokens.iter().collect();
-    let c_set: HashSet<&String> = c_tokens.iter().collect();
-    let intersection = q_set.intersection(&c_set).count();
-    let union = q_set.union(&c_set).count();
-    let jaccard = if union == 0 {
-        0.0
+    candidates
+}
+
+/// ID: Retrieval hybrid: vektor (Milvus, fallback Postgres) + keyword full-text, digabung RRF.
+/// EN: Hybrid retrieval: vector (Milvus, Postgres fallback) + full-text keyword, fused with RRF.
+pub(crate) async fn hybrid_retrieve(
+    data: &Arc<AppState>,
+    config: &RagConfiguration,
+    query: &str,
+    filters: &RetrievalFilters,
+    max_results: usize,
+) -> Result<Vec<RetrievedChunk>, StatusCode> {
+    // Ambil kandidat lebih banyak dari hasil akhir agar fusion & threshold punya ruang
+    let candidate_limit = (max_results * 4).max(20);
+    let db_error = |err: sqlx::Error| {
+        tracing::error!("RAG retrieval query failed: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    // Embedder hashing memakai teks ternormalisasi agar lebih tahan typo; model semantik
+    // menerima query mentah seperti chunk yang di-embed dari teks aslinya
+    let embedder = rag_embedder(data, config);
+    let query_text = if embedder.model() == HASHING_MODEL {
+        tokenize_and_normalize(query).join(" ")
     } else {
-        intersection as f32 / union as f32
+        query.to_string()
     };
+    let query_embedding = embedder
+        .embed(&[query_text])
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to embed RAG query: {:?}", err);
+            StatusCode::BAD_GATEWAY
+        })?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+
+    // Koleksi Milvus tidak menyimpan metadata dokumen: dengan filter metadata, PK chunk yang lolos
+    // dihitung di Postgres lalu dikirim sebagai ekspresi `id in [...]` agar Milvus memfilter
+    // sebelum perankingan. Tanpa Milvus, retrieval hanya memakai keyword (tidak ada scan penuh).
+    let mut vector_hits = Vec::new();
+    if let Some(milvus_client) = data
+        .milvus_client
+        .as_ref()
+        .filter(|_| !data.env.allow_mock_dependencies)
+    {
+        let candidate_ids = if filters.has_metadata_filters() {
+            Some(
+                rag_retrieval::filtered_vector_ids(&data.db, filters)
+                    .await
+                    .map_err(db_error)?,
+            )
+        } else {
+            None
+        };
+        let mut guard = milvus_client.lock().await;
+        crate::services::milvus::ensure_rag_collection(
+            &mut guard,
+            &data.milvus_collection,
+            embedder.dimensions() as i64,
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!("Failed to ensure Milvus collection: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let hits = crate::services::milvus::search_top_k(
+            &mut guard,
+            &data.milvus_collection,
+            &milvus_partitions(&filters.access),
+            candidate_ids.as_deref(),
+            &query_embedding,
+            candidate_limit,
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!("Milvus search_top_k failed: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        drop(guard);
+        // Sisa filter (status, cakupan akses) tetap dicek ulang lewat Postgres
+        vector_hits = rag_retrieval::chunks_for_vector_ids(&data.db, &hits, filters)
+            .await
+            .map_err(db_error)?;
+    }
 
-    // Weighted combo; emphasize recall to ensure retrieval under typos/synonyms
-    0.7 * recall + 0.3 * jaccard
+    let keyword_hits =
+        rag_retrieval::keyword_search(&data.db, query, filters, candidate_limit as i64)
+            .await
+            .map_err(db_error)?;
+
+    rag_retrieval::fuse_and_load(&data.db, &vector_hits, &keyword_hits, candidate_limit)
+        .await
+        .map_err(db_error)
 }
 
 fn tokenize_and_normalize(text: &str) -> Vec<String> {
@@ -1777,29 +1982,6 @@ fn is_stopword_id(token: &str) -> bool {
     )
 }
 
-fn levenshtein_distance(a: &str, b: &str) -> usize {
-    // Classic DP implementation; optimized for small tokens
-    let (m, n) = (a.len(), b.len());
-    if m == 0 {
-        return n;
-    }
-    if n == 0 {
-        return m;
-    }
-    let mut prev: Vec<usize> = (0..=n).collect();
-    let mut curr: Vec<usize> = vec![0; n + 1];
-    for (i, ca) in a.chars().enumerate() {
-        curr[0] = i + 1;
-        for (j, cb) in b.chars().enumerate() {
-            let cost = if ca == cb { 0 } else { 1 };
-            curr[j + 1] =
-                std::cmp::min(std::cmp::min(curr[j] + 1, prev[j + 1] + 1), prev[j] + cost);
-        }
-        prev.copy_from_slice(&curr);
-    }
-    prev[n]
-}
-
 // Extension trait for pipe operation
 trait Pipe<T> {
     fn pipe<U, F>(self, f: F) -> U
@@ -1819,6 +2001,7 @@ impl<T> Pipe<T> for T {
 // Ingest a text document without file upload: chunk, embed, store
 pub async fn ingest_text_document(
     State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
     Json(body): Json<DocumentTextIngestRequest>,
 ) -> Result<Json<ApiResponse<DocumentTextIngestResponse>>, StatusCode> {
     if body.title.trim().is_empty()
@@ -1827,6 +2010,8 @@ pub async fn ingest_text_document(
     {
         return Err(StatusCode::BAD_REQUEST);
     }
+    let caller = rag_caller(&data, &jwt_auth).await?;
+    let store_uuid = caller.document_owner(body.visibility.as_deref())?;
 
     // Load configuration for chunking and embeddings
     let rag = get_rag_config(&data).await?;
@@ -1834,15 +2019,26 @@ pub async fn ingest_text_document(
     // Create document record
     let document_id = Uuid::new_v4();
     let file_name = format!("{}.txt", sanitize_filename(&body.title));
-    let file_path = format!("inline://{}", document_id);
     let file_bytes = body.content.as_bytes();
 
+    // Simpan teks sumber di blob store agar dokumen bisa di-reindex
+    let file_path = format!("documents/{}/{}", document_id, file_name);
+    if let Err(e) = data
+        .blob_store
+        .put(&file_path, file_bytes.to_vec(), Some("text/plain"))
+        .await
+    {
+        tracing::error!("Failed to store document {}: {}", file_path, e);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
     let document = sqlx::query_as::<_, Document>(
         r#"
         INSERT INTO documents (
             id, title, description, category, tags, file_path, file_name,
-            file_size, file_type, mime_type, status, created_at, updated_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            file_size, file_type, mime_type, status, created_at, updated_at,
+            uploaded_by, store_uuid, visibility
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
         RETURNING *
         "#,
     )
@@ -1859,13 +2055,24 @@ pub async fn ingest_text_document(
     .bind("processing")
     .bind(Utc::now())
     .bind(Utc::now())
+    .bind(caller.user_uuid)
+    .bind(store_uuid)
+    .bind(document_visibility(store_uuid))
     .fetch_one(&data.db)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let metadata = json!({"source": "inline"});
-    let ingest_result =
-        ingest_document_content(&data, document_id, &body.content, &rag, metadata).await;
+    let ingest_result = ingest_document_content(
+        &data,
+        &document,
+        &ExtractedDocument::plain(body.content.clone()),
+        "txt",
+        &rag,
+        metadata,
+        None,
+    )
+    .await;
 
     let chunk_count = match ingest_result {
         Ok(count) => count,
@@ -1934,56 +2141,16 @@ pub async fn ingest_text_document(
     }))
 }
 
-// Helper: naive char-based chunking with overlap
-fn chunk_text(content: &str, chunk_size: usize, chunk_overlap: usize) -> Vec<(String, i32, i32)> {
-    if chunk_size == 0 {
-        return vec![];
-    }
-    let chars: Vec<char> = content.chars().collect();
-    let mut start = 0usize;
-    let mut result = Vec::new();
-    let len = chars.len();
-    while start < len {
-        let end = (start + chunk_size).min(len);
-        let slice: String = chars[start..end].iter().collect();
-        result.push((slice, start as i32, end as i32));
-        if end == len {
-            break;
-        }
-        let next_start = end.saturating_sub(chunk_overlap);
-        if next_start <= start {
-            break;
-        }
-        start = next_start;
+// Helper: opsi chunking dari konfigurasi aktif; tabular hanya untuk csv/xlsx/xls
+fn chunk_options(config: &RagConfiguration, file_type: &str) -> ChunkOptions {
+    ChunkOptions {
+        strategy: ChunkingStrategy::parse(&config.chunking_strategy)
+            .unwrap_or(ChunkingStrategy::Sentence),
+        unit: SizeUnit::parse(&config.chunk_size_unit).unwrap_or(SizeUnit::Chars),
+        size: config.chunk_size.max(1) as usize,
+        overlap: config.chunk_overlap.max(0) as usize,
+        tabular: chunking::is_tabular_file_type(file_type),
     }
-    result
-}
-
-// Helper: simple hashing-based embedding generator as local fallback
-fn embed_texts_local(texts: &[String], dim: usize) -> Vec<Vec<f32>> {
-    let dim = dim.max(8); // safety
-    texts
-        .iter()
-        .map(|t| {
-            let mut vec = vec![0f32; dim];
-            for token in t.split_whitespace() {
-                let mut hasher = sha2::Sha256::new();
-                hasher.update(token.as_bytes());
-                let hash = hasher.finalize();
-                // Use first 8 bytes to index and weight
-                let idx = (u64::from_be_bytes([
-                    hash[0], hash[1], hash[2], hash[3], hash[4], hash[5], hash[6], hash[7],
-                ]) % dim as u64) as usize;
-                vec[idx] += 1.0;
-            }
-            // L2 normalize
-            let norm = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
-            if norm > 0.0 {
-                vec.iter_mut().for_each(|x| *x /= norm);
-            }
-            vec
-        })
-        .collect()
 }
 
 fn sanitize_filename(name: &str) -> String {
@@ -0,0 +1,434 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::Row;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        api::ApiResponse,
+        rag::{
+            CreateRagEvalSetRequest, RagAnswerRequest, RagEvalRunResponse, RagEvalSetResponse,
+            RagEvalSetSummary, RunRagEvalRequest,
+        },
+    },
+    handlers::rag::{
+        answer_with_rag_and_llm, get_rag_config, hybrid_retrieve, passes_similarity_threshold,
+        rag_caller, rerank_candidates,
+    },
+    middleware::jwt::JWTAuthMiddleware,
+    models::rag::{RagConfiguration, RagEvalQuestion, RagEvalRun, RagEvalSet},
+    services::rag_eval::{self, EvalSummary, QuestionOutcome},
+    services::rag_retrieval::RetrievalFilters,
+    AppState,
+};
+
+const MAX_EVAL_K: i32 = 50;
+
+// Create evaluation set with its golden questions
+pub async fn create_eval_set(
+    State(data): State<Arc<AppState>>,
+    Json(request): Json<CreateRagEvalSetRequest>,
+) -> Result<Json<ApiResponse<RagEvalSetResponse>>, StatusCode> {
+    if request.name.trim().is_empty() || request.questions.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    // Setiap pertanyaan wajib punya ekspektasi dokumen atau chunk
+    let has_expectation = |ids: &Option<Vec<Uuid>>| ids.as_ref().is_some_and(|v| !v.is_empty());
+    if request.questions.iter().any(|q| {
+        q.question.trim().is_empty()
+            || !(has_expectation(&q.expected_document_ids)
+                || has_expectation(&q.expected_chunk_ids))
+    }) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let db_error = |err: sqlx::Error| {
+        tracing::error!("Failed to create RAG eval set: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    let mut tx = data.db.begin().await.map_err(db_error)?;
+    let set = sqlx::query_as::<_, RagEvalSet>(
+        r#"
+        INSERT INTO rag_eval_sets (name, description)
+        VALUES ($1, $2)
+        RETURNING id, name, description, created_at, updated_at
+        "#,
+    )
+    .bind(request.name.trim())
+    .bind(&request.description)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let mut questions = Vec::with_capacity(request.questions.len());
+    for input in &request.questions {
+        let question = sqlx::query_as::<_, RagEvalQuestion>(
+            r#"
+            INSERT INTO rag_eval_questions (
+                eval_set_id, question, expected_document_ids, expected_chunk_ids, reference_answer
+            ) VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, eval_set_id, question, expected_document_ids, expected_chunk_ids,
+                      reference_answer, created_at
+            "#,
+        )
+        .bind(set.id)
+        .bind(input.question.trim())
+        .bind(input.expected_document_ids.clone().unwrap_or_default())
+        .bind(input.expected_chunk_ids.clone().unwrap_or_default())
+        .bind(
+            input
+                .reference_answer
+                .as_deref()
+                .map(str::trim)
+                .filter(|a| !a.is_empty()),
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        questions.push(question);
+    }
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(Json(ApiResponse {
+        code: 201,
+        status: "CREATED".to_string(),
+        message: "Evaluation set created successfully".to_string(),
+        data: eval_set_response(set, questions),
+        errors: json!({}),
+    }))
+}
+
+// List evaluation sets with question count and last run time
+pub async fn list_eval_sets(
+    State(data): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<Vec<RagEvalSetSummary>>>, StatusCode> {
+    let rows = sqlx::query(
+        r#"
+        SELECT s.id, s.name, s.description, s.created_at,
+               (SELECT COUNT(*) FROM rag_eval_questions q
+                WHERE q.eval_set_id = s.id AND q.deleted_at = 0) AS question_count,
+               (SELECT MAX(r.created_at) FROM rag_eval_runs r
+                WHERE r.eval_set_id = s.id) AS last_run_at
+        FROM rag_eval_sets s
+        WHERE s.deleted_at = 0
+        ORDER BY s.created_at DESC
+        "#,
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to list RAG eval sets: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let sets = rows
+        .iter()
+        .map(|row| RagEvalSetSummary {
+            id: row.get("id"),
+            name: row.get("name"),
+            description: row.get("description"),
+            question_count: row.get("question_count"),
+            last_run_at: row.get::<Option<DateTime<Utc>>, _>("last_run_at"),
+            created_at: row.get("created_at"),
+        })
+        .collect();
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Evaluation sets retrieved successfully".to_string(),
+        data: sets,
+        errors: json!({}),
+    }))
+}
+
+// Get evaluation set with its questions
+pub async fn get_eval_set(
+    State(data): State<Arc<AppState>>,
+    Path(eval_set_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<RagEvalSetResponse>>, StatusCode> {
+    let set = load_eval_set(&data, eval_set_id).await?;
+    let questions = load_eval_questions(&data, eval_set_id).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Evaluation set retrieved successfully".to_string(),
+        data: eval_set_response(set, questions),
+        errors: json!({}),
+    }))
+}
+
+// List previous runs of an evaluation set, newest first
+pub async fn list_eval_runs(
+    State(data): State<Arc<AppState>>,
+    Path(eval_set_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<RagEvalRun>>>, StatusCode> {
+    load_eval_set(&data, eval_set_id).await?;
+    let runs = sqlx::query_as::<_, RagEvalRun>(
+        "SELECT * FROM rag_eval_runs WHERE eval_set_id = $1 ORDER BY created_at DESC LIMIT 50",
+    )
+    .bind(eval_set_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to list RAG eval runs: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Evaluation runs retrieved successfully".to_string(),
+        data: runs,
+        errors: json!({}),
+    }))
+}
+
+/// ID: Jalankan set evaluasi terhadap rag_configuration aktif. Retrieval mengikuti jalur
+///     /query (hybrid + rerank + threshold); hasil dibandingkan dengan run sebelumnya.
+/// EN: Run an evaluation set against the active rag_configuration. Retrieval follows the
+///     /query path (hybrid + rerank + threshold); results are compared to the previous run.
+pub async fn run_eval_set(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(eval_set_id): Path<Uuid>,
+    request: Option<Json<RunRagEvalRequest>>,
+) -> Result<Json<ApiResponse<RagEvalRunResponse>>, StatusCode> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    load_eval_set(&data, eval_set_id).await?;
+    let questions = load_eval_questions(&data, eval_set_id).await?;
+    if questions.is_empty() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let config = get_rag_config(&data).await?;
+    // Retrieval memakai cakupan dokumen pemanggil, sama seperti /query
+    let filters = RetrievalFilters {
+        access: rag_caller(&data, &jwt_auth).await?.access(),
+        ..Default::default()
+    };
+    let k = request.k.unwrap_or(config.max_results).clamp(1, MAX_EVAL_K);
+    let include_answers = request.include_answers.unwrap_or(false);
+
+    let mut outcomes = Vec::with_capacity(questions.len());
+    for question in &questions {
+        let outcome = evaluate_question(
+            &data,
+            &jwt_auth,
+            &config,
+            &filters,
+            question,
+            k as usize,
+            include_answers,
+        )
+        .await?;
+        outcomes.push(outcome);
+    }
+    let summary = rag_eval::summarize(&outcomes);
+
+    let previous = sqlx::query_as::<_, RagEvalRun>(
+        "SELECT * FROM rag_eval_runs WHERE eval_set_id = $1 ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(eval_set_id)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let comparison = previous.as_ref().map(|prev| {
+        let prev_summary = EvalSummary {
+            question_count: prev.question_count.max(0) as usize,
+            recall_at_k: prev.recall_at_k,
+            mrr: prev.mrr,
+            answer_similarity: prev.answer_similarity,
+        };
+        // Hasil run lama yang tidak bisa dibaca hanya menghilangkan perbandingan per pertanyaan
+        let prev_outcomes: Vec<QuestionOutcome> =
+            serde_json::from_value(prev.results.clone()).unwrap_or_default();
+        rag_eval::compare(&prev_summary, &prev_outcomes, &summary, &outcomes)
+    });
+
+    let config_snapshot = json!({
+        "k": k,
+        "chunk_size": config.chunk_size,
+        "chunk_overlap": config.chunk_overlap,
+        "chunk_size_unit": config.chunk_size_unit,
+        "chunking_strategy": config.chunking_strategy,
+        "similarity_threshold": config.similarity_threshold,
+        "embedding_model": config.embedding_model,
+        "embedding_dimensions": config.embedding_dimensions,
+        "enable_reranking": config.enable_reranking,
+        "reranking_model": config.reranking_model,
+        "include_answers": include_answers,
+    });
+    let results = serde_json::to_value(&outcomes).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let run = sqlx::query_as::<_, RagEvalRun>(
+        r#"
+        INSERT INTO rag_eval_runs (
+            eval_set_id, k, question_count, recall_at_k, mrr, answer_similarity,
+            config_snapshot, results
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+    .bind(eval_set_id)
+    .bind(k)
+    .bind(summary.question_count as i32)
+    .bind(summary.recall_at_k)
+    .bind(summary.mrr)
+    .bind(summary.answer_similarity)
+    .bind(&config_snapshot)
+    .bind(&results)
+    .fetch_one(&data.db)
+    .await
+    .map_err(|err| {
+        tracing::error!("Failed to store RAG eval run: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let response = RagEvalRunResponse {
+        run_id: run.id,
+        eval_set_id,
+        k,
+        summary,
+        previous_run_id: previous.map(|prev| prev.id),
+        comparison,
+        config_snapshot,
+        results: outcomes,
+        created_at: run.created_at,
+    };
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Evaluation run completed".to_string(),
+        data: response,
+        errors: json!({}),
+    }))
+}
+
+async fn evaluate_question(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    config: &RagConfiguration,
+    filters: &RetrievalFilters,
+    question: &RagEvalQuestion,
+    k: usize,
+    include_answers: bool,
+) -> Result<QuestionOutcome, StatusCode> {
+    let retrieved = hybrid_retrieve(data, config, &question.question, filters, k).await?;
+    let retrieved = rerank_candidates(data, config, &question.question, retrieved).await;
+    let ranked = retrieved
+        .iter()
+        .filter(|chunk| passes_similarity_threshold(chunk, config.similarity_threshold));
+
+    // Ekspektasi chunk lebih spesifik; bila kosong, nilai per dokumen (urutan pertama muncul)
+    let (expected, retrieved_ids) = if question.expected_chunk_ids.is_empty() {
+        let documents = rag_eval::dedup_in_order(ranked.map(|chunk| chunk.document_id));
+        (&question.expected_document_ids, documents)
+    } else {
+        let chunks = ranked.map(|chunk| chunk.chunk_id).collect();
+        (&question.expected_chunk_ids, chunks)
+    };
+    let retrieved_ids: Vec<Uuid> = retrieved_ids.into_iter().take(k).collect();
+
+    let answer_similarity = match (&question.reference_answer, include_answers) {
+        (Some(reference), true) => generate_answer(data, jwt_auth, &question.question, k)
+            .await
+            .map(|answer| rag_eval::answer_similarity(reference, &answer)),
+        _ => None,
+    };
+
+    Ok(QuestionOutcome {
+        question_id: question.id,
+        recall: rag_eval::recall_at_k(expected, &retrieved_ids, k),
+        reciprocal_rank: rag_eval::reciprocal_rank(expected, &retrieved_ids),
+        answer_similarity,
+        retrieved_ids,
+    })
+}
+
+// Jawaban LLM yang gagal tidak menggagalkan run; pertanyaan itu tidak ikut skor jawaban
+async fn generate_answer(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    query: &str,
+    k: usize,
+) -> Option<String> {
+    let request = RagAnswerRequest {
+        query: query.to_string(),
+        document_ids: None,
+        category_filter: None,
+        tags_filter: None,
+        max_results: Some(k as i32),
+        similarity_threshold: None,
+        prompt_instructions: None,
+        max_tokens: None,
+        temperature: Some(0.0),
+    };
+    match answer_with_rag_and_llm(
+        State(data.clone()),
+        Extension(jwt_auth.clone()),
+        Json(request),
+    )
+    .await
+    {
+        Ok(Json(response)) => Some(response.data.answer),
+        Err(status) => {
+            tracing::warn!("RAG eval answer generation failed: {}", status);
+            None
+        }
+    }
+}
+
+async fn load_eval_set(data: &Arc<AppState>, eval_set_id: Uuid) -> Result<RagEvalSet, StatusCode> {
+    sqlx::query_as::<_, RagEvalSet>(
+        r#"
+        SELECT id, name, description, created_at, updated_at
+        FROM rag_eval_sets
+        WHERE id = $1 AND deleted_at = 0
+        "#,
+    )
+    .bind(eval_set_id)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn load_eval_questions(
+    data: &Arc<AppState>,
+    eval_set_id: Uuid,
+) -> Result<Vec<RagEvalQuestion>, StatusCode> {
+    sqlx::query_as::<_, RagEvalQuestion>(
+        r#"
+        SELECT id, eval_set_id, question, expected_document_ids, expected_chunk_ids,
+               reference_answer, created_at
+        FROM rag_eval_questions
+        WHERE eval_set_id = $1 AND deleted_at = 0
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(eval_set_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn eval_set_response(set: RagEvalSet, questions: Vec<RagEvalQuestion>) -> RagEvalSetResponse {
+    RagEvalSetResponse {
+        id: set.id,
+        name: set.name,
+        description: set.description,
+        questions,
+        created_at: set.created_at,
+        updated_at: set.updated_at,
+    }
+}
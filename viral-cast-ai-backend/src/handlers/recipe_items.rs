@@ -10,6 +10,7 @@ use serde_json::{json, Value};
 use sqlx::query_as;
 use uuid::Uuid;
 
+use crate::handlers::recipe_sets::ensure_recipe_version_editable;
 use crate::repository::recipe_items as recipe_items_repository;
 use crate::{
     dto::{
@@ -22,6 +23,63 @@ use crate::{
     AppState,
 };
 
+fn database_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    let error_response = serde_json::json!({
+        "status": "error",
+        "message": format!("Database error: {}", e)
+    });
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+}
+
+// ID: Komponen harus recipe set yang ada dan tidak boleh membentuk siklus
+// EN: A component must be an existing recipe set and must not create a cycle
+async fn ensure_component_allowed(
+    data: &AppState,
+    recipe_sets_uuid: Uuid,
+    component_uuid: Uuid,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    let graph = recipe_items_repository::load_recipe_graph(&data.db)
+        .await
+        .map_err(database_error)?;
+
+    for uuid in [recipe_sets_uuid, component_uuid] {
+        if !graph.nodes.contains_key(&uuid) {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("Recipe set with ID: {} not found", uuid)
+            });
+            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        }
+    }
+
+    if graph.would_create_cycle(recipe_sets_uuid, component_uuid) {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "Adding this component would create a recipe cycle"
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+async fn find_recipe_item(
+    data: &AppState,
+    id: Uuid,
+) -> Result<ProcessedRecipeItemSchema, (StatusCode, Json<Value>)> {
+    match recipe_items_repository::get_recipe_item_by_uuid(&data.db, id).await {
+        Ok(Some(recipe_item)) => Ok(recipe_item),
+        Ok(None) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("Recipe item with ID: {} not found", id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e) => Err(database_error(e)),
+    }
+}
+
 pub async fn create_recipe_item_handler(
     State(data): State<Arc<AppState>>,
     Json(body): Json<CreateRecipeItemSchema>,
@@ -47,6 +105,22 @@ pub async fn create_recipe_item_handler(
         }
     }
 
+    ensure_recipe_version_editable(&data, body.recipe_sets_uuid).await?;
+
+    match (body.ingredient_stocks_uuid, body.component_recipe_sets_uuid) {
+        (Some(_), None) => {}
+        (None, Some(component_uuid)) => {
+            ensure_component_allowed(&data, body.recipe_sets_uuid, component_uuid).await?
+        }
+        _ => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": "Provide exactly one of ingredient_stocks_uuid or component_recipe_sets_uuid"
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    }
+
     match recipe_items_repository::create_recipe_item(&data.db, item_uuid, body, current_time).await
     {
         Ok(recipe_item_response) => {
@@ -65,7 +139,7 @@ pub async fn create_recipe_item_handler(
             {
                 let error_response = serde_json::json!({
                     "status": "fail",
-                    "message": "Recipe item with that ingredient already exists for this recipe",
+                    "message": "Recipe item with that ingredient or component already exists for this recipe",
                 });
                 return Err((StatusCode::CONFLICT, Json(error_response)));
             }
@@ -190,6 +264,9 @@ pub async fn update_recipe_item_handler(
         }
     }
 
+    let existing = find_recipe_item(&data, id).await?;
+    ensure_recipe_version_editable(&data, existing.recipe_sets_uuid).await?;
+
     match recipe_items_repository::update_recipe_item(&data.db, id, body, current_time).await {
         Ok(Some(updated_recipe_item)) => {
             let json_response = ApiResponse {
@@ -224,6 +301,9 @@ pub async fn delete_recipe_item_handler(
 ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
     let current_time = chrono::Utc::now().timestamp_millis();
 
+    let existing = find_recipe_item(&data, id).await?;
+    ensure_recipe_version_editable(&data, existing.recipe_sets_uuid).await?;
+
     match recipe_items_repository::soft_delete_recipe_item(&data.db, id, current_time).await {
         Ok(true) => Ok((StatusCode::NO_CONTENT, Json(json!({})))),
         Ok(false) => {
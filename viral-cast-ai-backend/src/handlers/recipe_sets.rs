@@ -9,18 +9,55 @@ use rust_decimal::Decimal;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
+use crate::repository::ingredient_stocks as ingredient_stocks_repository;
+use crate::repository::recipe_items as recipe_items_repository;
 use crate::repository::recipe_sets as recipe_sets_repository;
+use crate::services::recipe_graph::RecipeGraphError;
 use crate::{
     dto::{
         api::ApiResponse,
         recipe_sets::{
-            CreateRecipeSetSchema, GetRecipeSetSchema, ProcessedRecipeSetSchema,
-            UpdateRecipeSetSchema,
+            CreateRecipeSetSchema, CreateRecipeVersionSchema, GetRecipeSetSchema,
+            ProcessedRecipeSetSchema, RecipeVersionDiffQuery, UpdateRecipeSetSchema,
         },
     },
     AppState,
 };
 
+fn database_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    let error_response = serde_json::json!({
+        "status": "error",
+        "message": format!("Database error: {}", e)
+    });
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+}
+
+// ID: Versi resep yang sudah dipatok di pesanan (langsung atau sebagai sub-resep) tidak boleh
+//     diubah karena konsumsi stok pesanan memakai versi tersebut; buat versi baru.
+// EN: A recipe version pinned on orders (directly or as a sub-recipe) must not change because
+//     order stock consumption resolves against it; create a new version instead.
+pub(crate) async fn ensure_recipe_version_editable(
+    data: &AppState,
+    recipe_sets_uuid: Uuid,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    let used = recipe_sets_repository::is_recipe_set_used_by_orders(&data.db, recipe_sets_uuid)
+        .await
+        .map_err(database_error)?;
+
+    if used {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!(
+                "Recipe set {} is already used by orders; create a new version via POST /api/recipe-sets/:id/versions",
+                recipe_sets_uuid
+            )
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    Ok(())
+}
+
 pub async fn create_recipe_set_handler(
     State(data): State<Arc<AppState>>,
     Json(body): Json<CreateRecipeSetSchema>,
@@ -195,6 +232,17 @@ pub async fn update_recipe_set_handler(
         return Err((StatusCode::NOT_FOUND, Json(error_response)));
     };
 
+    // Nama, status aktif, dan rentang berlaku boleh diubah; yield dan output mengubah konsumsi
+    let changes_consumption = body
+        .yield_quantity
+        .is_some_and(|value| Some(value) != existing.yield_quantity)
+        || body
+            .output_ingredient_catalog_uuid
+            .is_some_and(|value| Some(value) != existing.output_ingredient_catalog_uuid);
+    if changes_consumption {
+        ensure_recipe_version_editable(&data, id).await?;
+    }
+
     let effective_from = body.effective_from.or(existing.effective_from);
     let effective_to = body.effective_to.or(existing.effective_to);
 
@@ -242,6 +290,8 @@ pub async fn delete_recipe_set_handler(
 ) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
     let current_time = chrono::Utc::now().timestamp_millis();
 
+    ensure_recipe_version_editable(&data, id).await?;
+
     match recipe_sets_repository::soft_delete_recipe_set(&data.db, id, current_time).await {
         Ok(true) => Ok((StatusCode::NO_CONTENT, Json(json!({})))),
         Ok(false) => {
@@ -260,3 +310,212 @@ pub async fn delete_recipe_set_handler(
         }
     }
 }
+
+// ID: Roll-up biaya resep bertingkat sampai bahan mentah
+// EN: Multi-level recipe cost roll-up down to raw ingredients
+pub async fn get_recipe_set_cost_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let graph = recipe_items_repository::load_recipe_graph(&data.db)
+        .await
+        .map_err(database_error)?;
+    let prices = ingredient_stocks_repository::fetch_current_unit_costs(&data.db)
+        .await
+        .map_err(database_error)?;
+
+    match graph.roll_up_cost(id, &prices) {
+        Ok(breakdown) => {
+            // Biaya aktual bahan setengah jadi (dari produksi) sebagai pembanding
+            let stocked_unit_cost = graph
+                .nodes
+                .get(&id)
+                .and_then(|node| node.output_ingredient_catalog_uuid)
+                .and_then(|uuid| prices.get(&uuid).copied());
+
+            let json_response = ApiResponse {
+                code: 200,
+                status: "success".to_string(),
+                message: "Recipe set cost retrieved successfully".to_string(),
+                data: json!({
+                    "cost": breakdown,
+                    "stocked_unit_cost": stocked_unit_cost
+                }),
+                errors: json!(null),
+            };
+            Ok((StatusCode::OK, Json(json_response)))
+        }
+        Err(RecipeGraphError::UnknownRecipe(_)) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("Recipe set with ID: {} not found", id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e @ RecipeGraphError::Cycle(_)) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": e.to_string()
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+    }
+}
+
+// ID: Buat versi baru resep produk; item disalin dan versi lama ditutup pada effective_from
+// EN: Create a new version of a product recipe; items are copied and older versions are
+// closed at effective_from
+pub async fn create_recipe_version_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateRecipeVersionSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let bad_request = |message: String| {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": message
+        });
+        (StatusCode::BAD_REQUEST, Json(error_response))
+    };
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+
+    if let Some(yield_quantity) = body.yield_quantity {
+        if yield_quantity <= Decimal::ZERO {
+            return Err(bad_request(
+                "Yield quantity must be greater than 0".to_string(),
+            ));
+        }
+    }
+
+    let Some(source) = recipe_sets_repository::get_recipe_set_model_by_uuid(&data.db, id)
+        .await
+        .map_err(database_error)?
+    else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Recipe set with ID: {} not found", id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let Some(product_uuid) = source.product_uuid else {
+        return Err(bad_request(format!(
+            "Recipe set with ID: {} is not linked to a product",
+            id
+        )));
+    };
+
+    let effective_from = body.effective_from.unwrap_or(current_time);
+    let latest_from = recipe_sets_repository::get_latest_effective_from(&data.db, product_uuid)
+        .await
+        .map_err(database_error)?;
+    if let Some(latest_from) = latest_from {
+        if effective_from <= latest_from {
+            return Err(bad_request(format!(
+                "Effective_from must be after the latest version's effective_from ({})",
+                latest_from
+            )));
+        }
+    }
+
+    match recipe_sets_repository::create_recipe_version(
+        &data.db,
+        &source,
+        product_uuid,
+        Uuid::new_v4(),
+        body,
+        effective_from,
+        current_time,
+    )
+    .await
+    {
+        Ok(recipe_set_response) => {
+            let json_response = ApiResponse {
+                code: 201,
+                status: "success".to_string(),
+                message: "Recipe version created successfully".to_string(),
+                data: json!({"recipe_set": recipe_set_response}),
+                errors: json!(null),
+            };
+            Ok((StatusCode::CREATED, Json(json_response)))
+        }
+        Err(e) => Err(database_error(e)),
+    }
+}
+
+// ID: Daftar semua versi resep dari produk yang sama
+// EN: List every recipe version of the same product
+pub async fn get_recipe_versions_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let Some(recipe_set) = recipe_sets_repository::get_recipe_set_by_uuid(&data.db, id)
+        .await
+        .map_err(database_error)?
+    else {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": format!("Recipe set with ID: {} not found", id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let versions: Vec<ProcessedRecipeSetSchema> = match recipe_set.product_uuid {
+        Some(product_uuid) => recipe_sets_repository::list_recipe_versions(&data.db, product_uuid)
+            .await
+            .map_err(database_error)?,
+        None => vec![recipe_set],
+    };
+
+    let json_response = ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Recipe versions retrieved successfully".to_string(),
+        data: json!({"versions": versions}),
+        errors: json!(null),
+    };
+    Ok((StatusCode::OK, Json(json_response)))
+}
+
+// ID: Bandingkan item dan biaya dua versi resep (?against=<uuid versi pembanding>)
+// EN: Compare items and cost of two recipe versions (?against=<uuid of the other version>)
+pub async fn get_recipe_set_diff_handler(
+    Path(id): Path<Uuid>,
+    Query(query): Query<RecipeVersionDiffQuery>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let graph = recipe_items_repository::load_recipe_graph(&data.db)
+        .await
+        .map_err(database_error)?;
+    let prices = ingredient_stocks_repository::fetch_current_unit_costs(&data.db)
+        .await
+        .map_err(database_error)?;
+
+    match graph.diff_versions(query.against, id, &prices) {
+        Ok(diff) => {
+            let json_response = ApiResponse {
+                code: 200,
+                status: "success".to_string(),
+                message: "Recipe version diff retrieved successfully".to_string(),
+                data: json!({"diff": diff}),
+                errors: json!(null),
+            };
+            Ok((StatusCode::OK, Json(json_response)))
+        }
+        Err(RecipeGraphError::UnknownRecipe(missing)) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": format!("Recipe set with ID: {} not found", missing)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e @ RecipeGraphError::Cycle(_)) => {
+            let error_response = serde_json::json!({
+                "status": "fail",
+                "message": e.to_string()
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+    }
+}
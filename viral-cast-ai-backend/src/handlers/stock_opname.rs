@@ -0,0 +1,290 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::api::ApiResponse;
+use crate::dto::stock_opname::{
+    CreateStockOpnameSessionRequest, StockOpnameDetailQuery, StockOpnameListRequest,
+    SubmitStockOpnameCountsRequest,
+};
+use crate::middleware::jwt::JWTAuthMiddleware;
+use crate::models::stock_opname::StockOpnameSessionWithItems;
+use crate::repository::stock_opname as stock_opname_repo;
+use crate::repository::stock_opname::SessionTransition;
+use crate::repository::stores as stores_repository;
+use crate::AppState;
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({
+            "status": "error",
+            "message": format!("{:?}", e)
+        })),
+    )
+}
+
+fn validation_error(e: validator::ValidationErrors) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "fail",
+            "message": "Validation error",
+            "errors": e,
+        })),
+    )
+}
+
+fn session_not_found(id: Uuid) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "status": "fail",
+            "message": format!("Sesi stock opname dengan ID: {} tidak ditemukan", id)
+        })),
+    )
+}
+
+fn session_not_open(status: &str) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::CONFLICT,
+        Json(json!({
+            "status": "fail",
+            "message": format!("Sesi stock opname berstatus {} dan tidak dapat diubah", status)
+        })),
+    )
+}
+
+async fn load_session_with_items(
+    data: &AppState,
+    id: Uuid,
+    only_variance: bool,
+) -> Result<StockOpnameSessionWithItems, (StatusCode, Json<Value>)> {
+    let session = stock_opname_repo::get_session(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| session_not_found(id))?;
+
+    let all_items = stock_opname_repo::get_session_items(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+    let summary = stock_opname_repo::summarize_items(&all_items);
+    let items = if only_variance {
+        all_items
+            .into_iter()
+            .filter(|item| item.variance_qty.map(|v| !v.is_zero()).unwrap_or(false))
+            .collect()
+    } else {
+        all_items
+    };
+
+    Ok(StockOpnameSessionWithItems {
+        session,
+        summary,
+        items,
+    })
+}
+
+// ID: Handler untuk membuka sesi stock opname (snapshot qty sistem)
+// EN: Handler to open a stock opname session (snapshots system quantities)
+pub async fn create_stock_opname_session_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<CreateStockOpnameSessionRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+
+    let user_uuid = jwt_auth.user.uuid;
+    let store_uuid = stores_repository::get_store_by_user_uuid(&data.db, user_uuid)
+        .await
+        .map_err(internal_error)?
+        .map(|store| store.uuid);
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let session_uuid = stock_opname_repo::create_session(
+        &data.db,
+        store_uuid,
+        user_uuid,
+        body.notes,
+        body.ingredient_catalog_uuids,
+        current_time,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    let session = load_session_with_items(&data, session_uuid, false).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "success".to_string(),
+            message: "Sesi stock opname berhasil dibuat".to_string(),
+            data: session,
+            errors: json!({}),
+        }),
+    ))
+}
+
+// ID: Handler untuk daftar sesi stock opname
+// EN: Handler to list stock opname sessions
+pub async fn get_stock_opname_sessions_handler(
+    Query(opts): Query<StockOpnameListRequest>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let page = opts.page.unwrap_or(1).max(1);
+    let limit = opts.limit.unwrap_or(10).clamp(1, 100);
+
+    let (sessions, total) = stock_opname_repo::list_sessions(&data.db, page, limit, &opts)
+        .await
+        .map_err(internal_error)?;
+
+    let total_pages = ((total + limit as i64 - 1) / limit as i64).max(1);
+    let has_prev = page > 1;
+    let has_next = (page as i64) < total_pages;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Daftar sesi stock opname berhasil diambil".to_string(),
+        data: json!({
+            "stock_opnames": sessions,
+            "meta": {
+                "page": page,
+                "limit": limit,
+                "total_records": total,
+                "total_pages": total_pages,
+                "has_prev": has_prev,
+                "has_next": has_next,
+                "prev_page": if has_prev { Some(page - 1) } else { None },
+                "next_page": if has_next { Some(page + 1) } else { None }
+            }
+        }),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk detail sesi beserta selisih per bahan
+// EN: Handler to get session details with per-ingredient variance
+pub async fn get_stock_opname_session_handler(
+    Path(id): Path<Uuid>,
+    Query(params): Query<StockOpnameDetailQuery>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let session = load_session_with_items(&data, id, params.only_variance.unwrap_or(false)).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Sesi stock opname berhasil diambil".to_string(),
+        data: session,
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk input hasil hitung fisik (parsial, multi perangkat)
+// EN: Handler to submit counted quantities (partial, multi-device)
+pub async fn submit_stock_opname_counts_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<SubmitStockOpnameCountsRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+    for count in &body.counts {
+        count.validate().map_err(validation_error)?;
+    }
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let unknown = match stock_opname_repo::record_counts(
+        &data.db,
+        id,
+        &body.counts,
+        jwt_auth.user.uuid,
+        current_time,
+    )
+    .await
+    .map_err(internal_error)?
+    {
+        SessionTransition::Done(unknown) => unknown,
+        SessionTransition::NotFound => return Err(session_not_found(id)),
+        SessionTransition::InvalidStatus(status) => return Err(session_not_open(&status)),
+    };
+
+    let session = load_session_with_items(&data, id, false).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Hasil hitung berhasil disimpan".to_string(),
+        data: session,
+        errors: if unknown.is_empty() {
+            json!({})
+        } else {
+            json!({ "unknown_ingredient_catalog_uuids": unknown })
+        },
+    }))
+}
+
+// ID: Handler untuk approve sesi; selisih diposting sebagai ADJUSTMENT
+// EN: Handler to approve a session; variances are posted as ADJUSTMENT moves
+pub async fn approve_stock_opname_session_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let adjusted =
+        match stock_opname_repo::approve_session(&data.db, id, jwt_auth.user.uuid, current_time)
+            .await
+            .map_err(internal_error)?
+        {
+            SessionTransition::Done(adjusted) => adjusted,
+            SessionTransition::NotFound => return Err(session_not_found(id)),
+            SessionTransition::InvalidStatus(status) => return Err(session_not_open(&status)),
+        };
+
+    let session = load_session_with_items(&data, id, false).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: format!(
+            "Sesi stock opname disetujui, {} penyesuaian stok diposting",
+            adjusted.len()
+        ),
+        data: session,
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk membatalkan sesi yang masih OPEN
+// EN: Handler to cancel a session that is still OPEN
+pub async fn cancel_stock_opname_session_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let current_time = chrono::Utc::now().timestamp_millis();
+    match stock_opname_repo::cancel_session(&data.db, id, jwt_auth.user.uuid, current_time)
+        .await
+        .map_err(internal_error)?
+    {
+        SessionTransition::Done(_) => {}
+        SessionTransition::NotFound => return Err(session_not_found(id)),
+        SessionTransition::InvalidStatus(status) => return Err(session_not_open(&status)),
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Sesi stock opname berhasil dibatalkan"
+    })))
+}
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    dto::api::ApiResponse,
+    handlers::rag::rag_caller,
+    middleware::jwt::JWTAuthMiddleware,
+    models::rag::Document,
+    services::blob_store::{
+        content_type_for_key, is_private_key, BlobStoreError, DEFAULT_SIGNED_URL_TTL_SECS,
+        MAX_SIGNED_URL_TTL_SECS,
+    },
+    AppState,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SignedUrlQuery {
+    pub key: Option<String>,
+    pub url: Option<String>,
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignedBlobQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+fn fail(status: StatusCode, message: String) -> (StatusCode, Json<Value>) {
+    let status_text = if status.is_server_error() {
+        "error"
+    } else {
+        "fail"
+    };
+    (
+        status,
+        Json(json!({
+            "code": status.as_u16(),
+            "status": status_text,
+            "message": message,
+            "data": {},
+            "errors": {}
+        })),
+    )
+}
+
+fn blob_error(e: BlobStoreError) -> (StatusCode, Json<Value>) {
+    let status = match e {
+        BlobStoreError::InvalidKey(_) => StatusCode::BAD_REQUEST,
+        BlobStoreError::NotFound(_) => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    fail(status, e.to_string())
+}
+
+// ID: Key privat hanya boleh ditandatangani bila pemanggil boleh membaca dokumen pemiliknya
+//     (toko yang sama, dokumen platform, atau Super Admin). Key tanpa dokumen dianggap tidak ada.
+// EN: A private key may only be signed when the caller can read its owning document (same
+//     store, a platform document, or Super Admin). Keys without a document are treated as missing.
+async fn ensure_blob_readable(
+    data: &Arc<AppState>,
+    jwt_auth: &JWTAuthMiddleware,
+    key: &str,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    if !is_private_key(key) {
+        return Ok(());
+    }
+
+    let document = sqlx::query_as::<_, Document>(
+        "SELECT * FROM documents WHERE file_path = $1 AND deleted_at = 0 LIMIT 1",
+    )
+    .bind(key)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(|e| {
+        fail(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Database error: {}", e),
+        )
+    })?
+    .ok_or_else(|| fail(StatusCode::NOT_FOUND, format!("Blob not found: {}", key)))?;
+
+    let caller = rag_caller(data, jwt_auth)
+        .await
+        .map_err(|status| fail(status, "Failed to resolve caller".to_string()))?;
+    if !caller.can_read(&document) {
+        // 404 agar keberadaan dokumen toko lain tidak bocor
+        return Err(fail(
+            StatusCode::NOT_FOUND,
+            format!("Blob not found: {}", key),
+        ));
+    }
+    Ok(())
+}
+
+/// ID: Buat URL bertanda tangan (berbatas waktu) untuk sebuah blob, berdasarkan key atau URL publik.
+/// Key privat (dokumen RAG) dicek terhadap toko pemanggil.
+/// EN: Create a time-limited signed URL for a blob, by key or by public URL.
+/// Private keys (RAG documents) are checked against the caller's store.
+pub async fn get_signed_url_handler(
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    State(data): State<Arc<AppState>>,
+    Query(query): Query<SignedUrlQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let key = match (query.key.as_deref(), query.url.as_deref()) {
+        (Some(key), _) => key.trim_start_matches('/').to_string(),
+        (None, Some(url)) => data.blob_store.key_from_url(url).ok_or_else(|| {
+            fail(
+                StatusCode::BAD_REQUEST,
+                "URL does not belong to the configured storage".to_string(),
+            )
+        })?,
+        (None, None) => {
+            return Err(fail(
+                StatusCode::BAD_REQUEST,
+                "Either 'key' or 'url' is required".to_string(),
+            ))
+        }
+    };
+
+    let expires_in = query
+        .expires_in
+        .unwrap_or(DEFAULT_SIGNED_URL_TTL_SECS)
+        .clamp(1, MAX_SIGNED_URL_TTL_SECS);
+
+    ensure_blob_readable(&data, &jwt_auth, &key).await?;
+
+    if !data.blob_store.exists(&key).await.map_err(blob_error)? {
+        return Err(fail(
+            StatusCode::NOT_FOUND,
+            format!("Blob not found: {}", key),
+        ));
+    }
+
+    let signed_url = data
+        .blob_store
+        .signed_url(&key, expires_in)
+        .map_err(blob_error)?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "OK".to_string(),
+        message: "Signed URL created".to_string(),
+        data: json!({
+            "key": key,
+            "url": signed_url,
+            "expires_in": expires_in,
+            "backend": data.blob_store.backend(),
+        }),
+        errors: json!({}),
+    }))
+}
+
+/// ID: Sajikan blob dari backend local lewat URL bertanda tangan.
+/// EN: Serve a blob from the local backend through a signed URL.
+pub async fn get_signed_blob_handler(
+    State(data): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    Query(query): Query<SignedBlobQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    if !data
+        .blob_store
+        .verify_signed(&key, query.expires, &query.signature)
+    {
+        return Err(fail(
+            StatusCode::FORBIDDEN,
+            "Signed URL is invalid or has expired".to_string(),
+        ));
+    }
+
+    let bytes = data.blob_store.get(&key).await.map_err(blob_error)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type_for_key(&key)),
+            (header::CACHE_CONTROL, "private, max-age=60"),
+        ],
+        bytes,
+    ))
+}
@@ -10,6 +10,7 @@ use uuid::Uuid;
 
 use crate::middleware::jwt::JWTAuthMiddleware;
 use crate::repository::stores as stores_repository;
+use crate::services::image_pipeline;
 use crate::{
     dto::{
         api::ApiResponse,
@@ -50,7 +51,10 @@ pub async fn create_store_handler(
         }
     }
 
-    match stores_repository::create_store(&state.db, body).await {
+    let brand_variants =
+        image_pipeline::stored_variants_json(state.blob_store.as_ref(), body.brand_url.as_deref())
+            .await;
+    match stores_repository::create_store(&state.db, body, brand_variants).await {
         Ok(processed) => {
             // Update user profile dengan store_uuid baru
             if let Err(e) =
@@ -166,7 +170,10 @@ pub async fn update_store_handler_put(
     Path(store_uuid): Path<Uuid>,
     Json(body): Json<UpdateStoreSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    match stores_repository::update_store(&state.db, store_uuid, body).await {
+    let brand_variants =
+        image_pipeline::stored_variants_json(state.blob_store.as_ref(), body.brand_url.as_deref())
+            .await;
+    match stores_repository::update_store(&state.db, store_uuid, body, brand_variants).await {
         Ok(processed) => Ok(Json(ApiResponse::<ProcessedStore> {
             code: 200,
             status: "success".to_string(),
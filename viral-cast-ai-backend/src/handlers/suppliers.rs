@@ -0,0 +1,268 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::api::ApiResponse;
+use crate::dto::suppliers::{
+    CreateSupplierSchema, GetSuppliersSchema, UpdateSupplierSchema, UpsertIngredientSupplierSchema,
+};
+use crate::repository::suppliers as suppliers_repo;
+use crate::AppState;
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({
+            "status": "error",
+            "message": format!("{:?}", e)
+        })),
+    )
+}
+
+fn supplier_not_found(id: Uuid) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({
+            "status": "fail",
+            "message": format!("Supplier dengan ID: {} tidak ditemukan", id)
+        })),
+    )
+}
+
+// ID: Handler untuk membuat supplier baru
+// EN: Handler to create a new supplier
+pub async fn create_supplier_handler(
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateSupplierSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    if let Err(e) = body.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "fail",
+                "message": "Validation error",
+                "errors": e,
+            })),
+        ));
+    }
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let supplier = suppliers_repo::create_supplier(&data.db, body, current_time)
+        .await
+        .map_err(internal_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "success".to_string(),
+            message: "Supplier berhasil dibuat".to_string(),
+            data: Some(supplier),
+            errors: json!({}),
+        }),
+    ))
+}
+
+// ID: Handler untuk daftar supplier dengan paginasi
+// EN: Handler to list suppliers with pagination
+pub async fn get_suppliers_handler(
+    Query(opts): Query<GetSuppliersSchema>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let page = opts.page.unwrap_or(1).max(1);
+    let limit = opts.limit.unwrap_or(10).clamp(1, 100);
+
+    let (suppliers, total) = suppliers_repo::list_suppliers(&data.db, page, limit, &opts)
+        .await
+        .map_err(internal_error)?;
+
+    let total_pages = ((total + limit as i64 - 1) / limit as i64).max(1);
+    let has_prev = page > 1;
+    let has_next = (page as i64) < total_pages;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Daftar supplier berhasil diambil".to_string(),
+        data: json!({
+            "suppliers": suppliers,
+            "meta": {
+                "page": page,
+                "limit": limit,
+                "total_records": total,
+                "total_pages": total_pages,
+                "has_prev": has_prev,
+                "has_next": has_next,
+                "prev_page": if has_prev { Some(page - 1) } else { None },
+                "next_page": if has_next { Some(page + 1) } else { None }
+            }
+        }),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk detail supplier beserta bahan yang disediakan
+// EN: Handler to get supplier details with the ingredients it supplies
+pub async fn get_supplier_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let supplier = suppliers_repo::get_supplier_by_uuid(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| supplier_not_found(id))?;
+
+    let ingredients = suppliers_repo::list_supplier_ingredients(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Supplier berhasil diambil".to_string(),
+        data: json!({
+            "supplier": supplier,
+            "ingredients": ingredients,
+        }),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk memperbarui supplier
+// EN: Handler to update a supplier
+pub async fn update_supplier_handler(
+    State(data): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpdateSupplierSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    if let Err(e) = body.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "fail",
+                "message": "Validation error",
+                "errors": e,
+            })),
+        ));
+    }
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let supplier = suppliers_repo::update_supplier(&data.db, id, body, current_time)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| supplier_not_found(id))?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Supplier berhasil diperbarui".to_string(),
+        data: Some(supplier),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk menghapus (soft delete) supplier
+// EN: Handler to soft delete a supplier
+pub async fn delete_supplier_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let rows_affected = suppliers_repo::soft_delete_supplier(&data.db, id, current_time)
+        .await
+        .map_err(internal_error)?;
+
+    if rows_affected == 0 {
+        return Err(supplier_not_found(id));
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Supplier berhasil dihapus"
+    })))
+}
+
+// ID: Handler untuk menghubungkan bahan ke supplier (lead time, minimal order, ukuran kemasan)
+// EN: Handler to link an ingredient to a supplier (lead time, minimum order, pack size)
+pub async fn upsert_supplier_ingredient_handler(
+    State(data): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(body): Json<UpsertIngredientSupplierSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    if let Err(e) = body.validate() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "fail",
+                "message": "Validation error",
+                "errors": e,
+            })),
+        ));
+    }
+
+    suppliers_repo::get_supplier_by_uuid(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| supplier_not_found(id))?;
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let link = suppliers_repo::upsert_ingredient_supplier(&data.db, id, body, current_time)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err) if db_err.is_foreign_key_violation() => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "fail",
+                    "message": "Bahan tidak ditemukan"
+                })),
+            ),
+            other => internal_error(other),
+        })?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Bahan supplier berhasil disimpan".to_string(),
+        data: Some(link),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk melepas bahan dari supplier
+// EN: Handler to unlink an ingredient from a supplier
+pub async fn delete_supplier_ingredient_handler(
+    Path((id, ingredient_catalog_uuid)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let rows_affected = suppliers_repo::remove_ingredient_supplier(
+        &data.db,
+        id,
+        ingredient_catalog_uuid,
+        current_time,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    if rows_affected == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "fail",
+                "message": "Bahan tidak terhubung dengan supplier ini"
+            })),
+        ));
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Bahan berhasil dilepas dari supplier"
+    })))
+}
@@ -0,0 +1,351 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::api::ApiResponse;
+use crate::dto::waste_logs::{CreateWasteLogRequest, WasteLogListRequest, WasteReportQuery};
+use crate::middleware::jwt::JWTAuthMiddleware;
+use crate::models::waste_logs::WasteLogWithItems;
+use crate::repository::recipe_items as recipe_items_repository;
+use crate::repository::recipe_sets as recipe_sets_repository;
+use crate::repository::stores as stores_repository;
+use crate::repository::waste_logs as waste_logs_repo;
+use crate::repository::waste_logs::NewWasteLog;
+use crate::AppState;
+
+const DEFAULT_REPORT_TIMEZONE: &str = "Asia/Jakarta";
+
+fn internal_error(e: sqlx::Error) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({
+            "status": "error",
+            "message": format!("{:?}", e)
+        })),
+    )
+}
+
+fn validation_error(e: validator::ValidationErrors) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "fail",
+            "message": "Validation error",
+            "errors": e,
+        })),
+    )
+}
+
+fn bad_request(message: String) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "fail",
+            "message": message
+        })),
+    )
+}
+
+fn report_timezone(query: &WasteReportQuery) -> Result<String, (StatusCode, Json<Value>)> {
+    let name = query
+        .timezone
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .unwrap_or(DEFAULT_REPORT_TIMEZONE);
+    Tz::from_str(name)
+        .map(|tz| tz.name().to_string())
+        .map_err(|_| bad_request(format!("Timezone tidak valid: {}", name)))
+}
+
+// Gabungkan qty per bahan dengan urutan kemunculan pertama
+fn add_line(lines: &mut Vec<(Uuid, Decimal)>, ingredient_catalog_uuid: Uuid, quantity: Decimal) {
+    match lines
+        .iter_mut()
+        .find(|(uuid, _)| *uuid == ingredient_catalog_uuid)
+    {
+        Some((_, total)) => *total += quantity,
+        None => lines.push((ingredient_catalog_uuid, quantity)),
+    }
+}
+
+// ID: Handler untuk mencatat waste bahan dan/atau produk (dipecah lewat resep)
+// EN: Handler to log ingredient and/or product waste (exploded through the recipe)
+pub async fn create_waste_log_handler(
+    State(data): State<Arc<AppState>>,
+    Extension(jwt_auth): Extension<JWTAuthMiddleware>,
+    Json(body): Json<CreateWasteLogRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    body.validate().map_err(validation_error)?;
+    let items = body.items.unwrap_or_default();
+    for item in &items {
+        item.validate().map_err(validation_error)?;
+    }
+
+    let product = match (body.product_uuid, body.product_qty) {
+        (Some(product_uuid), Some(product_qty)) => Some((product_uuid, product_qty)),
+        (None, None) => None,
+        _ => {
+            return Err(bad_request(
+                "product_uuid dan product_qty harus diisi bersamaan".to_string(),
+            ))
+        }
+    };
+    if product.is_none() && items.is_empty() {
+        return Err(bad_request(
+            "Isi minimal satu bahan (items) atau produk (product_uuid)".to_string(),
+        ));
+    }
+
+    let current_time = chrono::Utc::now().timestamp_millis();
+    let wasted_at = body.wasted_at.unwrap_or(current_time);
+
+    let mut lines: Vec<(Uuid, Decimal)> = Vec::new();
+    for item in &items {
+        add_line(&mut lines, item.ingredient_catalog_uuid, item.quantity);
+    }
+
+    if let Some((product_uuid, product_qty)) = product {
+        if waste_logs_repo::get_product_name(&data.db, product_uuid)
+            .await
+            .map_err(internal_error)?
+            .is_none()
+        {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "fail",
+                    "message": format!("Produk dengan ID: {} tidak ditemukan", product_uuid)
+                })),
+            ));
+        }
+
+        // Versi resep yang berlaku saat waste terjadi
+        let recipe_sets_uuid =
+            recipe_sets_repository::resolve_product_recipe_set(&data.db, product_uuid, wasted_at)
+                .await
+                .map_err(internal_error)?
+                .ok_or_else(|| {
+                    bad_request(format!(
+                        "Produk dengan ID: {} belum memiliki resep",
+                        product_uuid
+                    ))
+                })?;
+
+        // Komponen setengah jadi yang distok diambil dari stoknya, bukan dipecah lagi
+        let graph = recipe_items_repository::load_recipe_graph(&data.db)
+            .await
+            .map_err(internal_error)?;
+        let exploded = graph
+            .explode(recipe_sets_uuid, product_qty, true)
+            .map_err(|e| bad_request(e.to_string()))?;
+        if exploded.is_empty() {
+            return Err(bad_request(format!(
+                "Produk dengan ID: {} belum memiliki resep",
+                product_uuid
+            )));
+        }
+
+        for (ingredient_catalog_uuid, quantity) in exploded {
+            add_line(&mut lines, ingredient_catalog_uuid, quantity.round_dp(4));
+        }
+    }
+
+    lines.retain(|(_, quantity)| *quantity > Decimal::ZERO);
+
+    let ingredient_uuids: Vec<Uuid> = lines.iter().map(|(uuid, _)| *uuid).collect();
+    let missing = waste_logs_repo::find_missing_ingredients(&data.db, &ingredient_uuids)
+        .await
+        .map_err(internal_error)?;
+    if !missing.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "fail",
+                "message": "Bahan tidak ditemukan di katalog",
+                "errors": { "unknown_ingredient_catalog_uuids": missing }
+            })),
+        ));
+    }
+
+    let user_uuid = jwt_auth.user.uuid;
+    let store_uuid = stores_repository::get_store_by_user_uuid(&data.db, user_uuid)
+        .await
+        .map_err(internal_error)?
+        .map(|store| store.uuid);
+
+    let new_log = NewWasteLog {
+        store_uuid,
+        reason_code: body.reason_code.trim().to_ascii_uppercase(),
+        product_uuid: product.map(|(uuid, _)| uuid),
+        product_qty: product.map(|(_, qty)| qty),
+        notes: body.notes,
+        wasted_at,
+        logged_by: Some(user_uuid),
+        lines,
+    };
+
+    let log_uuid = waste_logs_repo::create_waste_log(&data.db, &new_log, current_time)
+        .await
+        .map_err(internal_error)?;
+
+    let waste_log = load_waste_log_with_items(&data, log_uuid).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            code: 201,
+            status: "success".to_string(),
+            message: "Waste berhasil dicatat".to_string(),
+            data: waste_log,
+            errors: json!({}),
+        }),
+    ))
+}
+
+async fn load_waste_log_with_items(
+    data: &AppState,
+    id: Uuid,
+) -> Result<WasteLogWithItems, (StatusCode, Json<Value>)> {
+    let waste_log = waste_logs_repo::get_waste_log(&data.db, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "fail",
+                    "message": format!("Waste log dengan ID: {} tidak ditemukan", id)
+                })),
+            )
+        })?;
+
+    let product_name = match waste_log.product_uuid {
+        Some(product_uuid) => waste_logs_repo::get_product_name(&data.db, product_uuid)
+            .await
+            .map_err(internal_error)?,
+        None => None,
+    };
+
+    let items = waste_logs_repo::get_waste_log_items(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(WasteLogWithItems {
+        waste_log,
+        product_name,
+        items,
+    })
+}
+
+// ID: Handler untuk daftar waste log
+// EN: Handler to list waste logs
+pub async fn get_waste_logs_handler(
+    Query(opts): Query<WasteLogListRequest>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let page = opts.page.unwrap_or(1).max(1);
+    let limit = opts.limit.unwrap_or(10).clamp(1, 100);
+
+    let (logs, total) = waste_logs_repo::list_waste_logs(&data.db, page, limit, &opts)
+        .await
+        .map_err(internal_error)?;
+
+    let total_pages = ((total + limit as i64 - 1) / limit as i64).max(1);
+    let has_prev = page > 1;
+    let has_next = (page as i64) < total_pages;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Daftar waste log berhasil diambil".to_string(),
+        data: json!({
+            "waste_logs": logs,
+            "meta": {
+                "page": page,
+                "limit": limit,
+                "total_records": total,
+                "total_pages": total_pages,
+                "has_prev": has_prev,
+                "has_next": has_next,
+                "prev_page": if has_prev { Some(page - 1) } else { None },
+                "next_page": if has_next { Some(page + 1) } else { None }
+            }
+        }),
+        errors: json!({}),
+    }))
+}
+
+// ID: Handler untuk detail waste log beserta bahan yang dibuang
+// EN: Handler to get a waste log with its wasted ingredients
+pub async fn get_waste_log_handler(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let waste_log = load_waste_log_with_items(&data, id).await?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Waste log berhasil diambil".to_string(),
+        data: waste_log,
+        errors: json!({}),
+    }))
+}
+
+// ID: Laporan waste per bulan dan alasan
+// EN: Waste report per month and reason
+pub async fn get_waste_by_reason_report_handler(
+    Query(query): Query<WasteReportQuery>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let timezone = report_timezone(&query)?;
+    let rows = waste_logs_repo::report_by_reason(&data.db, &timezone, &query)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Laporan waste per alasan berhasil diambil".to_string(),
+        data: json!({
+            "timezone": timezone,
+            "rows": rows
+        }),
+        errors: json!({}),
+    }))
+}
+
+// ID: Laporan waste aktual vs waste_percent teoretis per bahan per bulan
+// EN: Actual vs theoretical (waste_percent) waste per ingredient per month
+pub async fn get_waste_vs_theoretical_report_handler(
+    Query(query): Query<WasteReportQuery>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<Value>)> {
+    let timezone = report_timezone(&query)?;
+    let rows = waste_logs_repo::report_vs_theoretical(&data.db, &timezone, &query)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(ApiResponse {
+        code: 200,
+        status: "success".to_string(),
+        message: "Laporan waste aktual vs teoretis berhasil diambil".to_string(),
+        data: json!({
+            "timezone": timezone,
+            "rows": rows
+        }),
+        errors: json!({}),
+    }))
+}
@@ -3,11 +3,14 @@ mod config {
 }
 
 mod middleware {
+    pub mod client_ip;
     pub mod jwt;
     pub mod logging;
+    pub mod uploads;
 }
 
 mod models {
+    pub mod auth_tokens;
     pub mod categories;
     pub mod ingredient_catalog;
     pub mod profiles;
@@ -16,8 +19,10 @@ mod models {
     pub mod user;
     // pub mod stock_moves; // removed
     pub mod ai_config;
+    pub mod order_refunds;
     pub mod orders;
     pub mod payments;
+    pub mod pos_devices;
     pub mod products;
     pub mod rag;
     pub mod recipe_items;
@@ -33,7 +38,13 @@ mod models {
     pub mod store_product_predictions;
     pub mod stores;
     pub mod trend_news;
+    pub mod two_factor;
     pub mod weather_bmkg;
+    pub mod production_runs;
+    pub mod purchase_orders;
+    pub mod stock_opname;
+    pub mod suppliers;
+    pub mod waste_logs;
 }
 mod data {
     pub mod master {
@@ -53,8 +64,10 @@ mod dto {
     pub mod users;
     // pub mod stock_moves; // removed
     pub mod ai;
+    pub mod order_refunds;
     pub mod orders;
     pub mod payments;
+    pub mod pos_devices;
     pub mod products;
     pub mod rag;
     pub mod recipe_items;
@@ -74,9 +87,18 @@ mod dto {
     pub mod i18n;
     pub mod store_product_predictions;
     pub mod trend_news;
+    pub mod two_factor;
+    pub mod production_runs;
+    pub mod purchase_orders;
+    pub mod stock_opname;
+    pub mod suppliers;
+    pub mod waste_logs;
 }
 mod handlers {
     pub mod auth;
+    pub mod auth_account;
+    pub mod auth_sessions;
+    pub mod auth_two_factor;
     pub mod categories;
     pub mod ingredient_catalog;
     pub mod profiles;
@@ -85,10 +107,13 @@ mod handlers {
     // pub mod stock_moves; // removed
     pub mod ai;
     pub mod images;
+    pub mod order_refunds;
     pub mod orders;
     pub mod payments;
+    pub mod pos_devices;
     pub mod products;
     pub mod rag;
+    pub mod rag_eval;
     pub mod recipe_items;
     pub mod recipe_sets;
     // pub mod sales_daily; // removed
@@ -106,6 +131,12 @@ mod handlers {
     pub mod i18n;
     pub mod store_product_predictions;
     pub mod trend_news;
+    pub mod production_runs;
+    pub mod purchase_orders;
+    pub mod stock_opname;
+    pub mod suppliers;
+    pub mod storage;
+    pub mod waste_logs;
 }
 
 mod routes {
@@ -118,8 +149,10 @@ mod routes {
     // pub mod stock_moves; // removed
     pub mod ai;
     pub mod images;
+    pub mod order_refunds;
     pub mod orders;
     pub mod payments;
+    pub mod pos_devices;
     pub mod products;
     pub mod rag;
     pub mod recipe_items;
@@ -137,18 +170,28 @@ mod routes {
     // Added i18n routes module
     pub mod i18n;
     pub mod trend_news;
+    pub mod production_runs;
+    pub mod purchase_orders;
+    pub mod stock_opname;
+    pub mod suppliers;
+    pub mod storage;
+    pub mod waste_logs;
 }
 
 mod repository {
     pub mod ai;
     pub mod auth;
+    pub mod auth_tokens;
     pub mod categories;
     pub mod ingredient_catalog;
+    pub mod order_refunds;
+    pub mod pos_devices;
     pub mod products;
     pub mod recipe_items;
     pub mod recipe_sets;
     pub mod regions;
     pub mod roles;
+    pub mod sale_consumption;
     pub mod stores;
     pub mod units_of_measure;
     pub mod weather_bmkg;
@@ -161,6 +204,12 @@ mod repository {
     pub mod store_ingredient_predictions;
     pub mod store_product_predictions;
     pub mod trend_news;
+    pub mod two_factor;
+    pub mod production_runs;
+    pub mod purchase_orders;
+    pub mod stock_opname;
+    pub mod suppliers;
+    pub mod waste_logs;
 }
 
 mod services {
@@ -171,8 +220,24 @@ mod services {
     // ID: Tambahkan modul services baru untuk rate limiting, batching, dan scheduler
     // EN: Add new services modules for rate limiting, batching, and scheduler
     pub mod batch_processor;
+    pub mod blob_store;
+    pub mod chunking;
+    pub mod document_extract;
+    pub mod embeddings;
+    pub mod image_pipeline;
     pub mod job_scheduler;
+    pub mod login_throttle;
+    pub mod mailer;
+    pub mod payment_allocation;
+    pub mod rag_citations;
+    pub mod rag_eval;
+    pub mod rag_retrieval;
     pub mod rate_limiter;
+    pub mod recipe_graph;
+    pub mod refunds;
+    pub mod reorder;
+    pub mod reranker;
+    pub mod totp;
     // ID: Nonaktifkan modul yang belum siap untuk produksi agar kompilasi sukses
     // EN: Disable not-ready modules to keep compilation successful
     // ID: Aktifkan kembali modul layanan untuk kompilasi penuh.
@@ -185,6 +250,8 @@ mod services {
 
 mod workers {
     pub mod bmkg_scheduler;
+    pub mod rag_ingest;
+    pub mod rag_reembed;
 }
 
 use config::config::Config;
@@ -200,8 +267,10 @@ use redis::Client;
 use routes::auth::create_auth_router;
 use routes::categories::create_categories_router;
 use routes::ingredient_catalog::create_ingredients_router;
+use routes::order_refunds::create_order_refunds_router;
 use routes::orders::create_orders_router;
 use routes::payments::create_payments_router;
+use routes::pos_devices::create_pos_devices_router;
 use routes::products::create_products_router;
 use routes::profiles::create_profiles_router;
 use routes::recipe_items::create_recipe_items_router;
@@ -225,6 +294,12 @@ use routes::ingredient_stock_moves::create_ingredient_stock_moves_router;
 use routes::ingredient_stocks::create_ingredient_stocks_router;
 use routes::regions::create_regions_routes;
 use routes::stores::create_stores_router;
+use routes::production_runs::create_production_runs_router;
+use routes::purchase_orders::create_purchase_orders_router;
+use routes::stock_opname::create_stock_opname_router;
+use routes::storage::create_storage_router;
+use routes::suppliers::create_suppliers_router;
+use routes::waste_logs::create_waste_logs_router;
 use routes::trend_news::create_trend_news_router;
 use routes::weather_bmkg::weather_bmkg_routes;
 use sqlx::{
@@ -255,6 +330,14 @@ pub struct AppState {
     // Milvus
     milvus_client: Option<Arc<tokio::sync::Mutex<MilvusClient>>>,
     milvus_collection: String,
+    // Endpoint embeddings untuk RAG; model & dimensi dari rag_configurations
+    embedding_settings: services::embeddings::EmbeddingSettings,
+    // Reranker RAG (cross-encoder / LLM); aktif bila rag_configurations.enable_reranking
+    rerank_settings: services::reranker::RerankSettings,
+    // Object storage untuk upload gambar dan dokumen RAG (local / S3-compatible)
+    blob_store: Arc<dyn services::blob_store::BlobStore>,
+    // Pengirim email transaksional (verifikasi email, reset password): log / SMTP
+    mailer: Arc<dyn services::mailer::Mailer>,
 }
 
 #[tokio::main]
@@ -363,6 +446,29 @@ async fn main() {
             HeaderName::from_static("x-csrf-token"),
         ]);
 
+    let blob_store_config = services::blob_store::BlobStoreConfig::from_env();
+    let blob_store = match blob_store_config.build() {
+        Ok(store) => {
+            println!("✅ Blob storage backend: {}", store.backend());
+            store
+        }
+        Err(e) => {
+            println!("🔥 Failed to configure blob storage: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mailer = match services::mailer::MailerConfig::from_env().build() {
+        Ok(mailer) => {
+            println!("✅ Mail backend: {}", mailer.backend());
+            mailer
+        }
+        Err(e) => {
+            println!("🔥 Failed to configure mailer: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     let app_state = Arc::new(AppState {
         db: pool.clone(),
         env: config.clone(),
@@ -390,13 +496,29 @@ async fn main() {
             }
         },
         milvus_collection: config.milvus_collection.clone(),
+        embedding_settings: services::embeddings::EmbeddingSettings::new(
+            config.embedding_api_url.clone(),
+            config.embedding_api_key.clone(),
+            config.embedding_batch_size,
+        ),
+        rerank_settings: services::reranker::RerankSettings::new(
+            config.rerank_api_url.clone(),
+            config.rerank_api_key.clone(),
+            config.rerank_top_n,
+            std::env::var("GROQ_API_URL")
+                .unwrap_or_else(|_| "https://api.groq.com/openai/v1/chat/completions".to_string()),
+            std::env::var("GROQ_API_KEY").ok(),
+            std::env::var("GROQ_MODEL").unwrap_or_else(|_| "llama-3.1-8b-instant".to_string()),
+        ),
+        blob_store,
+        mailer,
     });
 
     if config.allow_mock_dependencies {
         println!("⚠️ [startup] Skipping Milvus collection checks (mock dependency mode)");
     } else if let Some(ref client) = app_state.milvus_client {
         // Read embedding dimension from configuration table, fallback to 1536
-        let _embedding_dim = match sqlx::query_scalar::<_, i32>(
+        let embedding_dim = match sqlx::query_scalar::<_, i32>(
             "SELECT embedding_dimensions FROM rag_configurations WHERE deleted_at = 0 ORDER BY created_at DESC LIMIT 1"
         )
         .fetch_optional(&app_state.db)
@@ -410,8 +532,12 @@ async fn main() {
         };
 
         let mut guard = client.lock().await;
-        if let Err(e) =
-            services::milvus::ensure_rag_collection(&mut guard, &app_state.milvus_collection).await
+        if let Err(e) = services::milvus::ensure_rag_collection(
+            &mut guard,
+            &app_state.milvus_collection,
+            embedding_dim as i64,
+        )
+        .await
         {
             println!(
                 "⚠️ Failed to ensure Milvus collection '{}': {:?}",
@@ -424,7 +550,9 @@ async fn main() {
     let categories_router = create_categories_router(app_state.clone());
     let ingredients_router = create_ingredients_router(app_state.clone());
     let orders_router = create_orders_router(app_state.clone());
+    let order_refunds_router = create_order_refunds_router(app_state.clone());
     let payments_router = create_payments_router(app_state.clone());
+    let pos_devices_router = create_pos_devices_router(app_state.clone());
     let products_router = create_products_router(app_state.clone());
     let profiles_router = create_profiles_router(app_state.clone());
     let stores_router = create_stores_router(app_state.clone());
@@ -439,7 +567,7 @@ async fn main() {
     let uoms_router = create_units_of_measure_router(app_state.clone());
     let images_router = create_images_router(app_state.clone());
     let ai_router = create_ai_router(app_state.clone());
-    let rag_router = create_rag_router();
+    let rag_router = create_rag_router(app_state.clone());
     // let sales_daily_router = create_sales_daily_router(app_state.clone()); // removed
     let forecast_daily_router = create_forecast_daily_router(app_state.clone());
 
@@ -449,13 +577,21 @@ async fn main() {
     // New: create i18n router
     let i18n_router = create_i18n_router(app_state.clone());
     let trend_news_router = create_trend_news_router(app_state.clone());
+    let suppliers_router = create_suppliers_router(app_state.clone());
+    let purchase_orders_router = create_purchase_orders_router(app_state.clone());
+    let stock_opname_router = create_stock_opname_router(app_state.clone());
+    let waste_logs_router = create_waste_logs_router(app_state.clone());
+    let production_runs_router = create_production_runs_router(app_state.clone());
+    let storage_router = create_storage_router(app_state.clone());
 
     let app = Router::new()
         .nest("/", auth_router)
         .nest("/", categories_router)
         .nest("/", ingredients_router)
         .nest("/", orders_router)
+        .nest("/", order_refunds_router)
         .nest("/", payments_router)
+        .nest("/", pos_devices_router)
         .nest("/", products_router)
         .nest("/", profiles_router)
         .nest("/", stores_router)
@@ -470,7 +606,7 @@ async fn main() {
         .nest("/", images_router)
         .nest("/", ai_router)
         .nest("/", ingredient_stocks_router)
-        .nest("/api/rag", rag_router.with_state(app_state.clone()))
+        .nest("/api/rag", rag_router)
         // .nest("/api/sales-daily", sales_daily_router) // removed
         .nest("/api/forecast-daily", forecast_daily_router)
         .nest("/", weather_bmkg_router.with_state((*app_state).clone()))
@@ -479,11 +615,28 @@ async fn main() {
         // New: nest i18n routes
         .nest("/", i18n_router)
         .nest("/", trend_news_router)
-        .nest_service("/uploads", ServeDir::new("uploads"))
+        .nest("/", suppliers_router)
+        .nest("/", purchase_orders_router)
+        .nest("/", stock_opname_router)
+        .nest("/", waste_logs_router)
+        .nest("/", production_runs_router)
+        .nest("/", storage_router)
+        // File lama & backend local tetap dilayani langsung dari disk (kecuali key privat)
+        .merge(
+            Router::new()
+                .nest_service("/uploads", ServeDir::new(&blob_store_config.local_root))
+                .layer(axum::middleware::from_fn(
+                    crate::middleware::uploads::block_private_uploads,
+                )),
+        )
         .layer(RequestBodyLimitLayer::new(10 * 1024 * 1024)) // 10MB limit
         .layer(axum::middleware::from_fn(
             crate::middleware::logging::api_logger,
         ))
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::new(config.trusted_proxies.clone()),
+            crate::middleware::client_ip::resolve_client_ip,
+        ))
         .layer(TraceLayer::new_for_http())
         .layer(cors);
 
@@ -494,6 +647,18 @@ async fn main() {
     );
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
 
+    // RAG ingestion queue workers (document_processing_jobs, Postgres-backed)
+    workers::rag_ingest::spawn_ingest_workers(app_state.clone(), config.rag_ingest_workers);
+    println!(
+        "🧵 RAG ingestion workers started with concurrency {}",
+        config.rag_ingest_workers
+    );
+
+    // Re-embed RAG chunks left over from a previous embedding model (e.g. after EMBEDDING_API_URL changes)
+    if !config.allow_mock_dependencies {
+        workers::rag_reembed::spawn_reembed(app_state.clone());
+    }
+
     // Start BMKG scheduler in background (50 hits/hour ~ every 72 seconds)
     if config.bmkg_scheduler_enabled {
         let scheduler_state = app_state.clone();
@@ -519,5 +684,11 @@ async fn main() {
         println!("⚠️ BMKG scheduler disabled by config");
     }
 
-    axum::serve(listener, app).await.unwrap()
+    // ConnectInfo dipakai middleware::client_ip sebagai alamat klien
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap()
 }
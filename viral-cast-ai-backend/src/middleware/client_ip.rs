@@ -0,0 +1,121 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+const FORWARDED_FOR: &str = "x-forwarded-for";
+const REAL_IP: &str = "x-real-ip";
+
+// ID: Tentukan IP klien sekali di tepi aplikasi. X-Forwarded-For / X-Real-IP dari klien dibuang;
+//     hanya bila koneksi datang dari TRUSTED_PROXIES header tersebut dipakai. Hasilnya ditulis
+//     ulang ke X-Real-IP, satu-satunya header yang dibaca client_metadata.
+// EN: Resolve the client IP once at the edge of the app. Client-sent X-Forwarded-For / X-Real-IP
+//     are dropped; they are only honoured when the connection comes from TRUSTED_PROXIES. The
+//     result is rewritten into X-Real-IP, the only header client_metadata reads.
+pub async fn resolve_client_ip(
+    State(trusted_proxies): State<Arc<Vec<IpAddr>>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let client_ip = {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        client_ip(
+            peer,
+            header(FORWARDED_FOR),
+            header(REAL_IP),
+            &trusted_proxies,
+        )
+    };
+
+    let headers = req.headers_mut();
+    headers.remove(FORWARDED_FOR);
+    headers.remove(REAL_IP);
+    if let Some(value) = client_ip.and_then(|ip| HeaderValue::from_str(&ip.to_string()).ok()) {
+        headers.insert(REAL_IP, value);
+    }
+    next.run(req).await
+}
+
+/// ID: Alamat koneksi, kecuali koneksi dari proxy tepercaya: ambil entri X-Forwarded-For paling
+/// kanan yang bukan proxy tepercaya (entri di kirinya bisa dipalsukan klien), lalu X-Real-IP.
+/// EN: The peer address, unless the peer is a trusted proxy: take the right-most
+/// X-Forwarded-For entry that is not a trusted proxy (entries to its left are client-supplied),
+/// then X-Real-IP.
+pub fn client_ip(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    real_ip: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+    if let Some(forwarded_for) = forwarded_for {
+        for hop in forwarded_for.rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) if trusted_proxies.contains(&ip) => continue,
+                Ok(ip) => return Some(ip),
+                Err(_) => return Some(peer),
+            }
+        }
+    }
+    real_ip
+        .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+        .or(Some(peer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forwarded_headers_only_count_behind_trusted_proxies() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let proxy = ip("10.0.0.2");
+        let trusted = [proxy];
+
+        // Klien langsung: header palsu diabaikan
+        assert_eq!(
+            client_ip(
+                Some(ip("203.0.113.9")),
+                Some("1.2.3.4"),
+                Some("5.6.7.8"),
+                &trusted
+            ),
+            Some(ip("203.0.113.9"))
+        );
+        // Lewat proxy: entri paling kanan yang bukan proxy, bukan entri pertama yang dikirim klien
+        assert_eq!(
+            client_ip(
+                Some(proxy),
+                Some("1.2.3.4, 198.51.100.7, 10.0.0.2"),
+                None,
+                &trusted
+            ),
+            Some(ip("198.51.100.7"))
+        );
+        assert_eq!(
+            client_ip(Some(proxy), None, Some("198.51.100.7"), &trusted),
+            Some(ip("198.51.100.7"))
+        );
+        assert_eq!(
+            client_ip(Some(proxy), Some("garbage"), None, &trusted),
+            Some(proxy)
+        );
+        assert_eq!(client_ip(None, Some("1.2.3.4"), None, &trusted), None);
+    }
+}
@@ -1,4 +1,5 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
 
 use axum::{
     body::Body,
@@ -12,7 +13,12 @@ use axum::{
 use axum_extra::extract::cookie::CookieJar;
 use serde::{Deserialize, Serialize};
 
-use crate::{dto::auth_token, models::user::User, AppState};
+use crate::{
+    dto::auth_token,
+    models::{pos_devices::PosDeviceScope, user::User},
+    repository::pos_devices as pos_devices_repository,
+    AppState,
+};
 use redis::AsyncCommands;
 
 #[derive(Debug, Serialize)]
@@ -26,8 +32,42 @@ pub struct ErrorResponse {
 pub struct JWTAuthMiddleware {
     pub user: User,
     pub access_token_uuid: uuid::Uuid,
+    /// Terisi bila token berasal dari sesi PIN kasir di terminal POS
+    pub pos_device: Option<PosDeviceScope>,
 }
 
+// Token yang last_seen_at-nya baru disentuh proses ini (access_token_uuid -> epoch ms)
+static LAST_SEEN_TOUCHED: OnceLock<Mutex<HashMap<uuid::Uuid, i64>>> = OnceLock::new();
+// Di atas jumlah ini entri yang sudah lewat jeda dibuang
+const LAST_SEEN_TOUCHED_MAX_ENTRIES: usize = 10_000;
+
+// ID: true bila token ini belum menyentuh last_seen_at dalam LAST_SEEN_THROTTLE_MS terakhir,
+//     sehingga setiap request tidak memicu UPDATE ke database.
+// EN: true when this token has not touched last_seen_at within LAST_SEEN_THROTTLE_MS, so not
+//     every request spawns a database UPDATE.
+fn should_touch_last_seen(access_token_uuid: uuid::Uuid) -> bool {
+    let now = chrono::Utc::now().timestamp_millis();
+    let throttle = crate::repository::auth_tokens::LAST_SEEN_THROTTLE_MS;
+    let mut touched = LAST_SEEN_TOUCHED
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if touched
+        .get(&access_token_uuid)
+        .is_some_and(|last| now - last < throttle)
+    {
+        return false;
+    }
+    if touched.len() >= LAST_SEEN_TOUCHED_MAX_ENTRIES {
+        touched.retain(|_, last| now - *last < throttle);
+    }
+    touched.insert(access_token_uuid, now);
+    true
+}
+
+// Header berisi kredensial perangkat POS, wajib untuk setiap request dengan token sesi PIN
+pub const DEVICE_TOKEN_HEADER: &str = "x-device-token";
+
 pub async fn auth(
     cookie_jar: CookieJar,
     State(data): State<Arc<AppState>>,
@@ -84,6 +124,46 @@ pub async fn auth(
             (StatusCode::UNAUTHORIZED, Json(error_response))
         })?;
 
+    // Token sesi PIN hanya berlaku bersama kredensial perangkat yang membukanya
+    let pos_device = match access_token_details.pos_device_uuid {
+        Some(device_uuid) => {
+            let device_error = || {
+                let error_response = ErrorResponse {
+                    code: 401,
+                    status: "UNAUTHORIZED",
+                    message: "This cashier session is only valid on the POS device that opened it"
+                        .to_string(),
+                };
+                (StatusCode::UNAUTHORIZED, Json(error_response))
+            };
+            let credential = req
+                .headers()
+                .get(DEVICE_TOKEN_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(device_error)?;
+            let store_uuid = pos_devices_repository::find_active_device_store(
+                &data.db,
+                device_uuid,
+                &auth_token::hash_action_token(credential),
+            )
+            .await
+            .map_err(|e| {
+                let error_response = ErrorResponse {
+                    code: 500,
+                    status: "INTERNAL_SERVER_ERROR",
+                    message: format!("Error fetching POS device from database: {}", e),
+                };
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+            })?
+            .ok_or_else(device_error)?;
+            Some(PosDeviceScope {
+                device_uuid,
+                store_uuid,
+            })
+        }
+        None => None,
+    };
+
     // Try Redis, fallback to in-memory session store
     let redis_token_user_uuid_opt = match data.redis_client.get_multiplexed_async_connection().await
     {
@@ -149,9 +229,24 @@ pub async fn auth(
         (StatusCode::UNAUTHORIZED, Json(error_response))
     })?;
 
+    // Perbarui last_seen_at sesi di background, paling sering sekali per menit per token;
+    // query juga membatasi per menit agar beberapa instance tidak saling menimpa
+    if should_touch_last_seen(access_token_uuid) {
+        let db = data.db.clone();
+        tokio::spawn(async move {
+            if let Err(e) =
+                crate::repository::auth_tokens::touch_session_last_seen(&db, access_token_uuid)
+                    .await
+            {
+                tracing::warn!("Failed to update session last_seen_at: {:?}", e);
+            }
+        });
+    }
+
     req.extensions_mut().insert(JWTAuthMiddleware {
         user,
         access_token_uuid,
+        pos_device,
     });
     Ok(next.run(req).await)
 }
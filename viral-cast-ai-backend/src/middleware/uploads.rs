@@ -0,0 +1,22 @@
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::services::blob_store::{is_private_key, percent_decode_path};
+
+// ID: /uploads hanya untuk aset publik (gambar). Key privat (sumber dokumen RAG) ditolak
+//     sebelum sampai ke ServeDir; aksesnya lewat /api/v1/storage/signed-url.
+// EN: /uploads only serves public assets (images). Private keys (RAG document sources) are
+//     rejected before they reach ServeDir; access them through /api/v1/storage/signed-url.
+pub async fn block_private_uploads(req: Request, next: Next) -> Response {
+    let path = req.uri().path();
+    let key = path.strip_prefix("/uploads").unwrap_or(path);
+    match percent_decode_path(key) {
+        Some(decoded) if !is_private_key(&decoded) => next.run(req).await,
+        // Path yang tidak bisa di-decode juga akan ditolak ServeDir
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
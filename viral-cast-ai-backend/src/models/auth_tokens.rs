@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// auth_security_events.event_type
+pub const EVENT_REFRESH_TOKEN_REUSE: &str = "refresh_token_reuse";
+pub const EVENT_SESSIONS_REVOKED_BY_ADMIN: &str = "sessions_revoked_by_admin";
+pub const EVENT_LOGIN_FAILED: &str = "login_failed";
+pub const EVENT_ACCOUNT_LOCKED: &str = "account_locked";
+pub const EVENT_ACCOUNT_UNLOCKED: &str = "account_unlocked";
+pub const EVENT_PASSWORD_CHANGED: &str = "password_changed";
+pub const EVENT_PASSWORD_RESET: &str = "password_reset";
+pub const EVENT_EMAIL_VERIFIED: &str = "email_verified";
+
+// auth_token_families.revoked_reason
+pub const REVOKED_REASON_LOGOUT: &str = "logout";
+pub const REVOKED_REASON_REUSE: &str = "refresh_token_reuse";
+pub const REVOKED_REASON_SIGNED_OUT: &str = "signed_out";
+pub const REVOKED_REASON_ADMIN: &str = "admin_revoked";
+pub const REVOKED_REASON_PASSWORD_CHANGED: &str = "password_changed";
+pub const REVOKED_REASON_TWO_FACTOR_ENABLED: &str = "two_factor_enabled";
+
+// auth_action_tokens.purpose
+pub const PURPOSE_VERIFY_EMAIL: &str = "verify_email";
+pub const PURPOSE_RESET_PASSWORD: &str = "reset_password";
+// Langkah kedua login (kode 2FA) setelah password benar
+pub const PURPOSE_TWO_FACTOR_LOGIN: &str = "two_factor_login";
+
+/// ID: Refresh token yang tercatat beserta status keluarganya.
+/// EN: A recorded refresh token together with its family state.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct RefreshTokenRecord {
+    pub token_uuid: Uuid,
+    pub family_uuid: Uuid,
+    pub user_uuid: Uuid,
+    pub access_token_uuid: Option<Uuid>,
+    pub rotated_at: Option<i64>,
+    pub family_revoked_at: Option<i64>,
+}
+
+/// ID: Sesi login aktif (satu keluarga token) untuk daftar sesi pengguna.
+/// EN: An active sign-in session (one token family) for the user's session list.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct AuthSession {
+    pub uuid: Uuid,
+    pub user_uuid: Uuid,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: i64,
+    pub last_seen_at: Option<i64>,
+}
+
+/// ID: Token sekali pakai (verifikasi email / reset password) yang berhasil dipakai.
+/// EN: A single-use token (email verification / password reset) that was just consumed.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct AuthActionToken {
+    pub uuid: Uuid,
+    pub user_uuid: Uuid,
+    pub purpose: String,
+    pub email: Option<String>,
+    pub expires_at: i64,
+    pub used_at: Option<i64>,
+    pub created_at: i64,
+}
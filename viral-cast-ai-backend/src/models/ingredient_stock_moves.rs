@@ -12,7 +12,7 @@ pub struct IngredientStockMoveModel {
     pub price_updated_at: Option<i64>,
     pub effective_at: i64,        // tanggal masuk stok
     pub expiry_at: Option<i64>,   // tanggal kadaluarsa stok
-    pub ref_type: Option<String>, // 'PURCHASE' | 'PRODUCTION' | 'ADJUSTMENT' | 'WASTE' | 'RETURN'
+    pub ref_type: Option<String>, // 'PURCHASE' | 'PRODUCTION' | 'ADJUSTMENT' | 'WASTE' | 'RETURN' | 'SALE'
     pub ref_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// REFUND = pengembalian penuh/sebagian atas pesanan PAID; VOID = pembatalan pesanan
+pub const REFUND_KIND_REFUND: &str = "REFUND";
+pub const REFUND_KIND_VOID: &str = "VOID";
+
+pub const REFUND_STATUS_PENDING: &str = "PENDING";
+// Disetujui, pembayaran negatif dicadangkan; menunggu refund QRIS di Xendit
+pub const REFUND_STATUS_PROCESSING: &str = "PROCESSING";
+pub const REFUND_STATUS_APPROVED: &str = "APPROVED";
+pub const REFUND_STATUS_REJECTED: &str = "REJECTED";
+
+// Masa klaim gateway refund PROCESSING; lebih lama dari semua panggilan Xendit satu approval
+pub const REFUND_GATEWAY_CLAIM_TTL_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct OrderRefund {
+    pub uuid: Uuid,
+    pub refund_no: String,
+    pub order_uuid: Uuid,
+    pub kind: String,
+    pub status: String,
+    pub reason: String,
+    pub amount: rust_decimal::Decimal,
+    pub cost_amount: rust_decimal::Decimal,
+    pub profit_reversed: rust_decimal::Decimal,
+    pub restock: bool,
+    pub qris_sandbox: bool,
+    pub requested_by: Option<Uuid>,
+    pub approved_by: Option<Uuid>,
+    pub approved_at: Option<i64>,
+    pub rejected_reason: Option<String>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct OrderRefundItemWithProduct {
+    pub uuid: Uuid,
+    pub refund_uuid: Uuid,
+    pub order_item_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub qty: rust_decimal::Decimal,
+    pub unit_price: rust_decimal::Decimal,
+    pub amount: rust_decimal::Decimal,
+    pub unit_cost: Option<rust_decimal::Decimal>,
+    pub cost_amount: rust_decimal::Decimal,
+    pub created_at: Option<i64>,
+    // Product details
+    pub product_name: Option<String>,
+}
+
+// Pembayaran negatif yang dibukukan saat refund disetujui
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct RefundPayment {
+    pub uuid: Uuid,
+    pub refunded_payment_uuid: Option<Uuid>,
+    pub method: String,
+    pub amount: rust_decimal::Decimal,
+    pub external_ref: Option<String>,
+    pub paid_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderRefundWithItems {
+    #[serde(flatten)]
+    pub refund: OrderRefund,
+    pub order_no: Option<String>,
+    pub items: Vec<OrderRefundItemWithProduct>,
+    pub payments: Vec<RefundPayment>,
+}
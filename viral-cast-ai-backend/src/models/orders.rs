@@ -7,6 +7,7 @@ pub struct Order {
     pub uuid: Uuid,
     pub order_no: String,
     pub cashier_uuid: Option<Uuid>,
+    pub store_uuid: Option<Uuid>,
     pub status: String,
     pub subtotal: rust_decimal::Decimal,
     pub discount: rust_decimal::Decimal,
@@ -27,6 +28,7 @@ pub struct OrderItem {
     pub unit_price: rust_decimal::Decimal,
     pub unit_cost: Option<rust_decimal::Decimal>,
     pub line_total: rust_decimal::Decimal,
+    pub recipe_sets_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
     pub deleted_at: Option<i64>,
@@ -48,6 +50,7 @@ pub struct OrderItemWithProduct {
     pub unit_price: rust_decimal::Decimal,
     pub unit_cost: Option<rust_decimal::Decimal>,
     pub line_total: rust_decimal::Decimal,
+    pub recipe_sets_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
     pub deleted_at: Option<i64>,
@@ -8,8 +8,12 @@ pub struct Payment {
     pub order_uuid: Uuid,
     pub method: String,
     pub amount: rust_decimal::Decimal,
+    pub tendered_amount: Option<rust_decimal::Decimal>,
+    pub change_amount: rust_decimal::Decimal,
     pub paid_at: Option<i64>,
     pub external_ref: Option<String>,
+    pub refund_uuid: Option<Uuid>,
+    pub refunded_payment_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
     pub deleted_at: Option<i64>,
@@ -51,8 +55,12 @@ pub struct PaymentWithOrder {
     pub order_uuid: Uuid,
     pub method: String,
     pub amount: rust_decimal::Decimal,
+    pub tendered_amount: Option<rust_decimal::Decimal>,
+    pub change_amount: rust_decimal::Decimal,
     pub paid_at: Option<i64>,
     pub external_ref: Option<String>,
+    pub refund_uuid: Option<Uuid>,
+    pub refunded_payment_uuid: Option<Uuid>,
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
     pub deleted_at: Option<i64>,
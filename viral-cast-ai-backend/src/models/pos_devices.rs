@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// auth_security_events.event_type
+pub const EVENT_POS_DEVICE_ENROLLED: &str = "pos_device_enrolled";
+pub const EVENT_POS_DEVICE_REVOKED: &str = "pos_device_revoked";
+pub const EVENT_PIN_UNLOCK_FAILED: &str = "pin_unlock_failed";
+
+// auth_token_families.revoked_reason
+pub const REVOKED_REASON_DEVICE_REVOKED: &str = "pos_device_revoked";
+
+/// ID: Terminal POS bersama yang terdaftar di satu toko.
+/// EN: A shared POS terminal enrolled in one store.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct PosDevice {
+    pub uuid: Uuid,
+    pub store_uuid: Uuid,
+    pub name: String,
+    pub enrolled_by: Option<Uuid>,
+    pub last_used_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// ID: Staf toko yang sudah memasang PIN, untuk layar pilih kasir di terminal.
+/// EN: Store staff with a PIN set, for the cashier picker on the terminal.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct PosStaffMember {
+    pub user_uuid: Uuid,
+    pub username: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub roles_number: Option<i32>,
+}
+
+/// ID: Cakupan sesi PIN: perangkat dan toko tempat kasir membuka terminal.
+/// EN: Scope of a PIN session: the device and store the cashier unlocked.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct PosDeviceScope {
+    pub device_uuid: Uuid,
+    pub store_uuid: Uuid,
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct ProductionRun {
+    pub uuid: Uuid,
+    pub run_no: String,
+    pub recipe_sets_uuid: Uuid,
+    pub output_ingredient_catalog_uuid: Uuid,
+    pub output_quantity: rust_decimal::Decimal,
+    pub unit_cost: Option<rust_decimal::Decimal>, // total_cost / output_quantity
+    pub total_cost: rust_decimal::Decimal,
+    pub output_move_uuid: Option<Uuid>,
+    pub store_uuid: Option<Uuid>,
+    pub notes: Option<String>,
+    pub produced_at: i64,
+    pub produced_by: Option<Uuid>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct ProductionRunItemWithIngredient {
+    pub uuid: Uuid,
+    pub production_run_uuid: Uuid,
+    pub ingredient_catalog_uuid: Uuid,
+    pub quantity: rust_decimal::Decimal,
+    pub unit_cost: Option<rust_decimal::Decimal>,
+    pub total_cost: rust_decimal::Decimal,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+    pub stock_move_uuid: Option<Uuid>,
+    pub created_at: Option<i64>,
+    // Ingredient details
+    pub ingredient_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductionRunWithItems {
+    #[serde(flatten)]
+    pub production_run: ProductionRun,
+    pub recipe_name: Option<String>,
+    pub output_ingredient_name: Option<String>,
+    pub items: Vec<ProductionRunItemWithIngredient>,
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct PurchaseOrder {
+    pub uuid: Uuid,
+    pub po_no: String,
+    pub supplier_uuid: Uuid,
+    pub store_uuid: Option<Uuid>,
+    pub status: String,
+    pub expected_at: Option<i64>,
+    pub notes: Option<String>,
+    pub total_estimated: rust_decimal::Decimal,
+    pub created_by: Option<Uuid>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct PurchaseOrderItemWithIngredient {
+    pub uuid: Uuid,
+    pub purchase_order_uuid: Uuid,
+    pub ingredient_catalog_uuid: Uuid,
+    pub quantity: rust_decimal::Decimal,
+    pub unit_price: Option<rust_decimal::Decimal>,
+    pub line_total: rust_decimal::Decimal,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    // Ingredient details
+    pub ingredient_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderWithItems {
+    #[serde(flatten)]
+    pub purchase_order: PurchaseOrder,
+    pub supplier_name: Option<String>,
+    pub items: Vec<PurchaseOrderItemWithIngredient>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Submitted,
+    Received,
+    Cancelled,
+}
+
+impl PurchaseOrderStatus {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "DRAFT" => Some(Self::Draft),
+            "SUBMITTED" => Some(Self::Submitted),
+            "RECEIVED" => Some(Self::Received),
+            "CANCELLED" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "DRAFT",
+            Self::Submitted => "SUBMITTED",
+            Self::Received => "RECEIVED",
+            Self::Cancelled => "CANCELLED",
+        }
+    }
+
+    // Transisi status yang diizinkan: DRAFT -> SUBMITTED -> RECEIVED, dan DRAFT/SUBMITTED -> CANCELLED
+    pub fn can_transition_to(&self, next: &Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Draft, Self::Submitted)
+                | (Self::Draft, Self::Cancelled)
+                | (Self::Submitted, Self::Received)
+                | (Self::Submitted, Self::Cancelled)
+        )
+    }
+}
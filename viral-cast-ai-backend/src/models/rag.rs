@@ -26,8 +26,14 @@ pub struct Document {
     pub last_accessed: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub store_uuid: Option<Uuid>, // Owning store; None for platform documents
+    pub visibility: String,       // store, platform
 }
 
+// Document visibility: store-owned knowledge base or shared platform-wide (Super Admin)
+pub const DOCUMENT_VISIBILITY_STORE: &str = "store";
+pub const DOCUMENT_VISIBILITY_PLATFORM: &str = "platform";
+
 // Document chunk model for vector storage
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct DocumentChunk {
@@ -62,6 +68,9 @@ pub struct RagConfiguration {
     pub retention_days: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub chunking_strategy: String, // fixed | sentence | heading
+    pub chunk_size_unit: String,   // chars | tokens
+    pub context_token_budget: i32, // token budget for sources sent to the LLM
 }
 
 // Document access log for analytics
@@ -91,6 +100,8 @@ pub struct DocumentProcessingJob {
     pub error_message: Option<String>,
     pub retry_count: i32,
     pub max_retries: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub locked_by: Option<String>, // worker yang sedang memproses
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -224,12 +235,18 @@ impl RagConfiguration {
                 "xlsx".to_string(),
                 "xls".to_string(),
                 "xlsm".to_string(),
+                "html".to_string(),
+                "htm".to_string(),
+                "epub".to_string(),
             ],
             max_file_size_mb: 50,
             max_documents_per_user: Some(100),
             retention_days: Some(365),
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            chunking_strategy: "sentence".to_string(),
+            chunk_size_unit: "chars".to_string(),
+            context_token_budget: 3000,
         }
     }
 
@@ -257,6 +274,8 @@ impl DocumentProcessingJob {
             error_message: None,
             retry_count: 0,
             max_retries: 3,
+            next_attempt_at: Some(Utc::now()),
+            locked_by: None,
             started_at: None,
             completed_at: None,
             created_at: Utc::now(),
@@ -288,3 +307,38 @@ impl DocumentProcessingJob {
         self.retry_count < self.max_retries && self.status == "failed"
     }
 }
+
+// RAG evaluation set (golden questions)
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RagEvalSet {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RagEvalQuestion {
+    pub id: Uuid,
+    pub eval_set_id: Uuid,
+    pub question: String,
+    pub expected_document_ids: Vec<Uuid>,
+    pub expected_chunk_ids: Vec<Uuid>, // bila diisi, metrik dihitung per chunk
+    pub reference_answer: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RagEvalRun {
+    pub id: Uuid,
+    pub eval_set_id: Uuid,
+    pub k: i32,
+    pub question_count: i32,
+    pub recall_at_k: f32,
+    pub mrr: f32,
+    pub answer_similarity: Option<f32>,
+    pub config_snapshot: serde_json::Value,
+    pub results: serde_json::Value, // Vec<rag_eval::QuestionOutcome>
+    pub created_at: DateTime<Utc>,
+}
@@ -7,7 +7,8 @@ use uuid::Uuid;
 pub struct RecipeItemsModel {
     pub uuid: Uuid,
     pub recipe_sets_uuid: Uuid,
-    pub ingredient_stocks_uuid: Uuid,
+    pub ingredient_stocks_uuid: Option<Uuid>,
+    pub component_recipe_sets_uuid: Option<Uuid>, // recipe set lain sebagai komponen (resep bertingkat)
     pub quantity: Decimal,
     pub waste_percent: Option<Decimal>,
     pub created_at: Option<i64>,
@@ -11,6 +11,10 @@ pub struct RecipeSetsModel {
     pub effective_from: Option<i64>,
     pub effective_to: Option<i64>,
     pub is_active: bool,
+    pub output_ingredient_catalog_uuid: Option<Uuid>, // bahan setengah jadi yang dihasilkan resep ini
+    pub product_uuid: Option<Uuid>,                   // produk pemilik versi resep ini
+    pub version_no: i32,
+    pub previous_version_uuid: Option<Uuid>, // versi yang digantikan
     pub created_at: Option<i64>,
     pub updated_at: Option<i64>,
     pub deleted_at: Option<i64>,
@@ -1,6 +1,25 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+// roles.number untuk Super Admin (lihat data/master/roles.rs)
+pub const SUPER_ADMIN_ROLE_NUMBER: i32 = 1;
+// Super Admin, Admin dan Owner boleh mencabut sesi staf di tokonya
+pub const SESSION_ADMIN_ROLE_NUMBERS: [i32; 3] = [SUPER_ADMIN_ROLE_NUMBER, 2, 7];
+// Peringkat untuk mengelola sesi/2FA akun lain: Super Admin > Owner > Admin > staf lain.
+// Selain Super Admin, pemanggil hanya boleh mengelola akun dengan peringkat lebih rendah.
+pub fn session_admin_rank(roles_number: Option<i32>) -> u8 {
+    match roles_number {
+        Some(SUPER_ADMIN_ROLE_NUMBER) => 3,
+        Some(7) => 2,
+        Some(2) => 1,
+        _ => 0,
+    }
+}
+// Supervisor ke atas (Super Admin, Admin, COO, Supervisor, Manager, Owner) boleh menyetujui refund/void
+pub const REFUND_APPROVER_ROLE_NUMBERS: [i32; 6] = [SUPER_ADMIN_ROLE_NUMBER, 2, 3, 4, 5, 7];
+// Supervisor ke atas boleh mencatat pesanan atas nama kasir lain di tokonya
+pub const CASHIER_OVERRIDE_ROLE_NUMBERS: [i32; 6] = [SUPER_ADMIN_ROLE_NUMBER, 2, 3, 4, 5, 7];
+
 #[derive(Serialize, Deserialize)]
 pub struct RolesModel {
     pub uuid: Uuid,
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct StockOpnameSession {
+    pub uuid: Uuid,
+    pub session_no: String,
+    pub store_uuid: Option<Uuid>,
+    pub status: String, // 'OPEN' | 'APPROVED' | 'CANCELLED'
+    pub notes: Option<String>,
+    pub snapshot_at: i64, // waktu snapshot qty sistem
+    pub created_by: Option<Uuid>,
+    pub approved_by: Option<Uuid>,
+    pub approved_at: Option<i64>,
+    pub cancelled_by: Option<Uuid>,
+    pub cancelled_at: Option<i64>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct StockOpnameItem {
+    pub uuid: Uuid,
+    pub session_uuid: Uuid,
+    pub ingredient_catalog_uuid: Uuid,
+    pub ingredient_name: Option<String>,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+    pub system_qty: rust_decimal::Decimal,
+    pub avg_cost: Option<rust_decimal::Decimal>,
+    pub counted_qty: Option<rust_decimal::Decimal>,
+    // Selisih dihitung saat query: counted_qty - system_qty dan selisih * avg_cost
+    pub variance_qty: Option<rust_decimal::Decimal>,
+    pub variance_value: Option<rust_decimal::Decimal>,
+    pub counted_by: Option<Uuid>,
+    pub counted_at: Option<i64>,
+    pub notes: Option<String>,
+    pub adjustment_move_uuid: Option<Uuid>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct StockOpnameSummary {
+    pub total_items: i64,
+    pub counted_items: i64,
+    pub uncounted_items: i64,
+    pub items_with_variance: i64,
+    pub variance_qty_gain: rust_decimal::Decimal,
+    pub variance_qty_loss: rust_decimal::Decimal,
+    pub variance_value_gain: rust_decimal::Decimal,
+    pub variance_value_loss: rust_decimal::Decimal,
+    pub variance_value_net: rust_decimal::Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockOpnameSessionWithItems {
+    #[serde(flatten)]
+    pub session: StockOpnameSession,
+    pub summary: StockOpnameSummary,
+    pub items: Vec<StockOpnameItem>,
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct SupplierModel {
+    pub uuid: Uuid,
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub telp: Option<String>,
+    pub email: Option<String>,
+    pub address: Option<String>,
+    pub lead_time_days: i32, // default lead time bila tidak di-override per bahan
+    pub notes: Option<String>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct IngredientSupplierModel {
+    pub uuid: Uuid,
+    pub ingredient_catalog_uuid: Uuid,
+    pub supplier_uuid: Uuid,
+    pub lead_time_days: Option<i32>, // override lead time supplier
+    pub min_order_qty: Option<rust_decimal::Decimal>,
+    pub pack_size: Option<rust_decimal::Decimal>,
+    pub last_price: Option<rust_decimal::Decimal>,
+    pub is_preferred: bool,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct IngredientSupplierWithIngredient {
+    pub uuid: Uuid,
+    pub ingredient_catalog_uuid: Uuid,
+    pub supplier_uuid: Uuid,
+    pub lead_time_days: Option<i32>,
+    pub min_order_qty: Option<rust_decimal::Decimal>,
+    pub pack_size: Option<rust_decimal::Decimal>,
+    pub last_price: Option<rust_decimal::Decimal>,
+    pub is_preferred: bool,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    // Ingredient details
+    pub ingredient_name: Option<String>,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+}
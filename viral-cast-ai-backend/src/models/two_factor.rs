@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// auth_security_events.event_type
+pub const EVENT_TWO_FACTOR_ENABLED: &str = "two_factor_enabled";
+pub const EVENT_TWO_FACTOR_DISABLED: &str = "two_factor_disabled";
+pub const EVENT_TWO_FACTOR_FAILED: &str = "two_factor_failed";
+pub const EVENT_RECOVERY_CODE_USED: &str = "recovery_code_used";
+pub const EVENT_RECOVERY_CODES_REGENERATED: &str = "recovery_codes_regenerated";
+pub const EVENT_TWO_FACTOR_RESET_BY_ADMIN: &str = "two_factor_reset_by_admin";
+
+// Jumlah kode pemulihan per pembuatan
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// ID: Secret TOTP pengguna; `confirmed_at` kosong selama pendaftaran belum dikonfirmasi.
+/// EN: A user's TOTP secret; `confirmed_at` stays empty until enrolment is confirmed.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct TotpSecret {
+    pub user_uuid: Uuid,
+    pub secret: String,
+    pub confirmed_at: Option<i64>,
+    pub last_used_step: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl TotpSecret {
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+/// ID: Kode pemulihan yang belum dipakai (hash Argon2).
+/// EN: An unused recovery code (Argon2 hash).
+#[derive(Debug, FromRow, Clone)]
+pub struct RecoveryCode {
+    pub uuid: Uuid,
+    pub code_hash: String,
+}
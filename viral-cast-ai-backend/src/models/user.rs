@@ -26,4 +26,29 @@ pub struct RegisterUserSchema {
 pub struct LoginUserSchema {
     pub username: String,
     pub password: String,
+    // Nama perangkat untuk daftar sesi (mis. "Tablet Kasir 1"); fallback header X-Device-Name
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailSchema {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordSchema {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordSchema {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordSchema {
+    pub current_password: String,
+    pub new_password: String,
 }
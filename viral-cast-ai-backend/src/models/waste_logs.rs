@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const WASTE_REASON_CODES: [&str; 7] = [
+    "EXPIRED",
+    "SPOILED",
+    "SPILLED",
+    "CUSTOMER_RETURN",
+    "OVERPRODUCTION",
+    "DAMAGED",
+    "OTHER",
+];
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct WasteLog {
+    pub uuid: Uuid,
+    pub store_uuid: Option<Uuid>,
+    pub reason_code: String,
+    pub product_uuid: Option<Uuid>, // terisi bila yang dibuang adalah produk jadi
+    pub product_qty: Option<rust_decimal::Decimal>,
+    pub notes: Option<String>,
+    pub wasted_at: i64,
+    pub total_cost: rust_decimal::Decimal,
+    pub logged_by: Option<Uuid>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub deleted_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct WasteLogItemWithIngredient {
+    pub uuid: Uuid,
+    pub waste_log_uuid: Uuid,
+    pub ingredient_catalog_uuid: Uuid,
+    pub quantity: rust_decimal::Decimal,
+    pub unit_cost: Option<rust_decimal::Decimal>,
+    pub total_cost: rust_decimal::Decimal,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+    pub stock_move_uuid: Option<Uuid>,
+    pub created_at: Option<i64>,
+    // Ingredient details
+    pub ingredient_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WasteLogWithItems {
+    #[serde(flatten)]
+    pub waste_log: WasteLog,
+    pub product_name: Option<String>,
+    pub items: Vec<WasteLogItemWithIngredient>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct WasteByReasonRow {
+    pub month: String, // YYYY-MM sesuai timezone laporan
+    pub reason_code: String,
+    pub logs_count: i64,
+    pub total_cost: rust_decimal::Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct WasteVsTheoreticalRow {
+    pub month: String,
+    pub ingredient_catalog_uuid: Uuid,
+    pub ingredient_name: Option<String>,
+    pub unit_of_measure_code: Option<String>,
+    pub actual_waste_qty: rust_decimal::Decimal,
+    pub actual_waste_cost: rust_decimal::Decimal,
+    // Waste teoretis = qty terjual * qty resep * waste_percent / yield
+    pub theoretical_waste_qty: rust_decimal::Decimal,
+    pub theoretical_usage_qty: rust_decimal::Decimal,
+    pub variance_qty: rust_decimal::Decimal,
+    // Rasio aktual vs teoretis terhadap pemakaian (dalam persen)
+    pub actual_waste_pct: Option<rust_decimal::Decimal>,
+    pub theoretical_waste_pct: Option<rust_decimal::Decimal>,
+}
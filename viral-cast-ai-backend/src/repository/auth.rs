@@ -84,3 +84,58 @@ pub async fn find_user_by_uuid(db: &Pool<Postgres>, user_uuid: Uuid) -> sqlx::Re
     .fetch_optional(db)
     .await
 }
+
+pub async fn find_user_by_email(db: &Pool<Postgres>, email: &str) -> sqlx::Result<Option<User>> {
+    sqlx::query_as::<_, User>(
+        "SELECT uuid, username, email, password, access_token, refresh_token, created_at, updated_at, deleted_at FROM users WHERE email = $1 AND deleted_at = 0",
+    )
+    .bind(email)
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn email_verified_at(db: &Pool<Postgres>, user_uuid: Uuid) -> sqlx::Result<Option<i64>> {
+    sqlx::query_scalar("SELECT email_verified_at FROM users WHERE uuid = $1")
+        .bind(user_uuid)
+        .fetch_optional(db)
+        .await
+        .map(Option::flatten)
+}
+
+// Hanya menandai bila email pengguna masih sama dengan email tujuan token
+pub async fn mark_email_verified(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    email: &str,
+) -> sqlx::Result<Option<i64>> {
+    let now = chrono::Utc::now().timestamp_millis();
+    sqlx::query_scalar(
+        r#"
+        UPDATE users
+        SET email_verified_at = COALESCE(email_verified_at, $3), updated_at = $3
+        WHERE uuid = $1 AND email = $2
+        RETURNING email_verified_at
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(email)
+    .bind(now)
+    .fetch_optional(db)
+    .await
+    .map(Option::flatten)
+}
+
+pub async fn update_password(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    hashed_password: &str,
+) -> sqlx::Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    sqlx::query("UPDATE users SET password = $2, updated_at = $3 WHERE uuid = $1")
+        .bind(user_uuid)
+        .bind(hashed_password)
+        .bind(now)
+        .execute(db)
+        .await
+        .map(|_| ())
+}
@@ -0,0 +1,478 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::models::auth_tokens::{AuthActionToken, AuthSession, RefreshTokenRecord};
+
+const SESSION_COLUMNS: &str =
+    "uuid, user_uuid, device_name, user_agent, ip_address, created_at, last_seen_at";
+
+// Jeda minimum antar pembaruan last_seen_at dari middleware auth
+pub const LAST_SEEN_THROTTLE_MS: i64 = 60_000;
+
+#[derive(Debug, Clone, Default)]
+pub struct SessionMetadata {
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+pub async fn create_token_family(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    metadata: &SessionMetadata,
+) -> sqlx::Result<Uuid> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO auth_token_families (
+            user_uuid, device_name, user_agent, ip_address, last_seen_at
+        ) VALUES ($1, $2, $3, $4, $5)
+        RETURNING uuid
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(&metadata.device_name)
+    .bind(&metadata.user_agent)
+    .bind(&metadata.ip_address)
+    .bind(now)
+    .fetch_one(db)
+    .await
+}
+
+// ID: Sesi PIN kasir di terminal POS: keluarga terikat perangkat yang berakhir pada `expires_at`.
+//     Sesi ini tidak punya refresh token; baris auth_refresh_tokens hanya menautkan access token
+//     ke keluarganya (token_uuid = access_token_uuid, langsung ditandai dirotasi) agar daftar
+//     sesi, sign-out jarak jauh dan pencabutan tetap bekerja seperti sesi biasa.
+// EN: Cashier PIN session on a POS terminal: a device-bound family that ends at `expires_at`.
+//     It has no refresh token; the auth_refresh_tokens row only links the access token to its
+//     family (token_uuid = access_token_uuid, already marked rotated) so session listing,
+//     remote sign-out and revocation work as for regular sessions.
+pub async fn create_pos_session(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    pos_device_uuid: Uuid,
+    access_token_uuid: Uuid,
+    expires_at: i64,
+    metadata: &SessionMetadata,
+) -> sqlx::Result<Uuid> {
+    let now = Utc::now().timestamp_millis();
+    let mut tx = db.begin().await?;
+    let family_uuid: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO auth_token_families (
+            user_uuid, device_name, user_agent, ip_address, last_seen_at,
+            pos_device_uuid, expires_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING uuid
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(&metadata.device_name)
+    .bind(&metadata.user_agent)
+    .bind(&metadata.ip_address)
+    .bind(now)
+    .bind(pos_device_uuid)
+    .bind(expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO auth_refresh_tokens (token_uuid, family_uuid, access_token_uuid, rotated_at)
+        VALUES ($1, $2, $1, $3)
+        "#,
+    )
+    .bind(access_token_uuid)
+    .bind(family_uuid)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(family_uuid)
+}
+
+pub async fn insert_refresh_token(
+    db: &Pool<Postgres>,
+    family_uuid: Uuid,
+    token_uuid: Uuid,
+    access_token_uuid: Uuid,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO auth_refresh_tokens (token_uuid, family_uuid, access_token_uuid)
+        VALUES ($1, $2, $3)
+        "#,
+    )
+    .bind(token_uuid)
+    .bind(family_uuid)
+    .bind(access_token_uuid)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+pub async fn find_refresh_token(
+    db: &Pool<Postgres>,
+    token_uuid: Uuid,
+) -> sqlx::Result<Option<RefreshTokenRecord>> {
+    sqlx::query_as::<_, RefreshTokenRecord>(
+        r#"
+        SELECT rt.token_uuid, rt.family_uuid, f.user_uuid, rt.access_token_uuid,
+               rt.rotated_at, f.revoked_at AS family_revoked_at
+        FROM auth_refresh_tokens rt
+        JOIN auth_token_families f ON f.uuid = rt.family_uuid
+        WHERE rt.token_uuid = $1
+        "#,
+    )
+    .bind(token_uuid)
+    .fetch_optional(db)
+    .await
+}
+
+// Klaim atomik: hanya satu request yang berhasil merotasi token yang sama. `false` berarti
+// token sudah dirotasi oleh request lain (diperlakukan sebagai pemakaian ulang).
+pub async fn mark_refresh_token_rotated(
+    db: &Pool<Postgres>,
+    token_uuid: Uuid,
+) -> sqlx::Result<bool> {
+    let now = Utc::now().timestamp_millis();
+    let mut tx = db.begin().await?;
+    let family_uuid: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE auth_refresh_tokens
+        SET rotated_at = $2
+        WHERE token_uuid = $1 AND rotated_at IS NULL
+        RETURNING family_uuid
+        "#,
+    )
+    .bind(token_uuid)
+    .bind(now)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(family_uuid) = family_uuid else {
+        return Ok(false);
+    };
+    sqlx::query(
+        r#"
+        UPDATE auth_token_families
+        SET rotation_count = rotation_count + 1, updated_at = $2, last_seen_at = $2
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(family_uuid)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+// ID: Cabut keluarga token dan kembalikan semua token_uuid (refresh + access) yang pernah
+// diterbitkan di dalamnya agar key Redis / session store ikut dihapus.
+// EN: Revoke a token family and return every token_uuid (refresh + access) issued in it so
+// the Redis / session store keys can be removed as well.
+pub async fn revoke_token_family(
+    db: &Pool<Postgres>,
+    family_uuid: Uuid,
+    reason: &str,
+) -> sqlx::Result<Vec<Uuid>> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query(
+        r#"
+        UPDATE auth_token_families
+        SET revoked_at = COALESCE(revoked_at, $2),
+            revoked_reason = COALESCE(revoked_reason, $3),
+            updated_at = $2
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(family_uuid)
+    .bind(now)
+    .bind(reason)
+    .execute(db)
+    .await?;
+
+    sqlx::query_scalar(
+        r#"
+        SELECT token_uuid FROM auth_refresh_tokens WHERE family_uuid = $1
+        UNION
+        SELECT access_token_uuid FROM auth_refresh_tokens
+        WHERE family_uuid = $1 AND access_token_uuid IS NOT NULL
+        "#,
+    )
+    .bind(family_uuid)
+    .fetch_all(db)
+    .await
+}
+
+// ID: Sesi aktif = keluarga belum dicabut yang refresh token terakhirnya belum kedaluwarsa
+//     (updated_at hanya berubah saat login/rotasi/pencabutan); sesi PIN juga dibatasi expires_at.
+// EN: Active session = a non-revoked family whose latest refresh token has not expired yet
+//     (updated_at only changes on sign-in, rotation and revocation); PIN sessions also end at
+//     expires_at.
+pub async fn list_active_sessions(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    refresh_ttl_ms: i64,
+) -> sqlx::Result<Vec<AuthSession>> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query_as::<_, AuthSession>(&format!(
+        r#"
+        SELECT {}
+        FROM auth_token_families
+        WHERE user_uuid = $1 AND revoked_at IS NULL AND updated_at >= $2
+          AND (expires_at IS NULL OR expires_at > $3)
+        ORDER BY COALESCE(last_seen_at, created_at) DESC
+        "#,
+        SESSION_COLUMNS
+    ))
+    .bind(user_uuid)
+    .bind(now - refresh_ttl_ms)
+    .bind(now)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn find_family_by_access_token(
+    db: &Pool<Postgres>,
+    access_token_uuid: Uuid,
+) -> sqlx::Result<Option<Uuid>> {
+    sqlx::query_scalar(
+        "SELECT family_uuid FROM auth_refresh_tokens WHERE access_token_uuid = $1 LIMIT 1",
+    )
+    .bind(access_token_uuid)
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn touch_session_last_seen(
+    db: &Pool<Postgres>,
+    access_token_uuid: Uuid,
+) -> sqlx::Result<()> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query(
+        r#"
+        UPDATE auth_token_families f
+        SET last_seen_at = $2
+        FROM auth_refresh_tokens rt
+        WHERE rt.access_token_uuid = $1
+          AND f.uuid = rt.family_uuid
+          AND f.revoked_at IS NULL
+          AND (f.last_seen_at IS NULL OR f.last_seen_at < $2 - $3)
+        "#,
+    )
+    .bind(access_token_uuid)
+    .bind(now)
+    .bind(LAST_SEEN_THROTTLE_MS)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+// Cabut satu sesi milik pengguna; None bila sesi tidak ada, milik orang lain, atau sudah dicabut
+pub async fn revoke_user_session(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    family_uuid: Uuid,
+    reason: &str,
+) -> sqlx::Result<Option<Vec<Uuid>>> {
+    let owned: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM auth_token_families
+            WHERE uuid = $1 AND user_uuid = $2 AND revoked_at IS NULL
+        )
+        "#,
+    )
+    .bind(family_uuid)
+    .bind(user_uuid)
+    .fetch_one(db)
+    .await?;
+    if !owned {
+        return Ok(None);
+    }
+    revoke_token_family(db, family_uuid, reason).await.map(Some)
+}
+
+// Cabut semua sesi pengguna (kecuali `keep_family` bila diisi); mengembalikan jumlah sesi
+// dan token_uuid yang harus dihapus dari Redis / session store
+pub async fn revoke_user_sessions(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    keep_family: Option<Uuid>,
+    reason: &str,
+) -> sqlx::Result<(usize, Vec<Uuid>)> {
+    let family_uuids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT uuid FROM auth_token_families
+        WHERE user_uuid = $1 AND revoked_at IS NULL
+          AND ($2::UUID IS NULL OR uuid <> $2)
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(keep_family)
+    .fetch_all(db)
+    .await?;
+
+    let mut token_uuids = Vec::new();
+    for family_uuid in &family_uuids {
+        token_uuids.extend(revoke_token_family(db, *family_uuid, reason).await?);
+    }
+    Ok((family_uuids.len(), token_uuids))
+}
+
+// (store_uuid, roles_number) dari profil aktif pengguna
+pub async fn find_profile_scope(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+) -> sqlx::Result<Option<(Option<Uuid>, Option<i32>)>> {
+    sqlx::query_as(
+        "SELECT store_uuid, roles_number FROM profiles WHERE user_uuid = $1 AND deleted_at = 0 LIMIT 1",
+    )
+    .bind(user_uuid)
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn insert_security_event(
+    db: &Pool<Postgres>,
+    user_uuid: Option<Uuid>,
+    family_uuid: Option<Uuid>,
+    event_type: &str,
+    ip_address: Option<&str>,
+    user_agent: Option<&str>,
+    details: serde_json::Value,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO auth_security_events (
+            user_uuid, family_uuid, event_type, ip_address, user_agent, details
+        ) VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(family_uuid)
+    .bind(event_type)
+    .bind(ip_address)
+    .bind(user_agent)
+    .bind(details)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+// ID: Terbitkan token aksi baru; token lama yang belum dipakai untuk tujuan yang sama dibatalkan.
+// EN: Issue a new action token; older unused tokens for the same purpose are invalidated.
+pub async fn create_action_token(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    purpose: &str,
+    token_hash: &str,
+    email: Option<&str>,
+    expires_at: i64,
+) -> sqlx::Result<()> {
+    let now = Utc::now().timestamp_millis();
+    let mut tx = db.begin().await?;
+    sqlx::query(
+        r#"
+        UPDATE auth_action_tokens SET used_at = $3
+        WHERE user_uuid = $1 AND purpose = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(purpose)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO auth_action_tokens (user_uuid, purpose, token_hash, email, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(purpose)
+    .bind(token_hash)
+    .bind(email)
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+pub async fn invalidate_action_tokens(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    purpose: &str,
+) -> sqlx::Result<()> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query(
+        r#"
+        UPDATE auth_action_tokens SET used_at = $3
+        WHERE user_uuid = $1 AND purpose = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(purpose)
+    .bind(now)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+pub async fn last_action_token_created_at(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    purpose: &str,
+) -> sqlx::Result<Option<i64>> {
+    sqlx::query_scalar(
+        "SELECT MAX(created_at) FROM auth_action_tokens WHERE user_uuid = $1 AND purpose = $2",
+    )
+    .bind(user_uuid)
+    .bind(purpose)
+    .fetch_one(db)
+    .await
+}
+
+// Token yang masih berlaku tanpa memakainya (tantangan 2FA boleh dicoba beberapa kali)
+pub async fn find_action_token(
+    db: &Pool<Postgres>,
+    purpose: &str,
+    token_hash: &str,
+) -> sqlx::Result<Option<AuthActionToken>> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query_as::<_, AuthActionToken>(
+        r#"
+        SELECT uuid, user_uuid, purpose, email, expires_at, used_at, created_at
+        FROM auth_action_tokens
+        WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > $3
+        "#,
+    )
+    .bind(token_hash)
+    .bind(purpose)
+    .bind(now)
+    .fetch_optional(db)
+    .await
+}
+
+// ID: Pakai token secara atomik; None bila tidak dikenal, sudah dipakai, atau kedaluwarsa.
+// EN: Consume a token atomically; None when unknown, already used or expired.
+pub async fn consume_action_token(
+    db: &Pool<Postgres>,
+    purpose: &str,
+    token_hash: &str,
+) -> sqlx::Result<Option<AuthActionToken>> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query_as::<_, AuthActionToken>(
+        r#"
+        UPDATE auth_action_tokens SET used_at = $3
+        WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > $3
+        RETURNING uuid, user_uuid, purpose, email, expires_at, used_at, created_at
+        "#,
+    )
+    .bind(token_hash)
+    .bind(purpose)
+    .bind(now)
+    .fetch_optional(db)
+    .await
+}
@@ -581,3 +581,52 @@ async fn derive_expiry_at(
 
     Ok(calculate_expiry_at(effective_at, shelf_life_days))
 }
+
+// Pergerakan stok yang diposting oleh workflow lain (opname, waste, produksi) di dalam transaksi
+#[derive(Debug, Clone)]
+pub struct LedgerStockMove {
+    pub name: Option<String>,
+    pub ingredient_catalog_uuid: Uuid,
+    pub quantity: Decimal,
+    pub price: Option<Decimal>,
+    pub ref_type: &'static str,
+    pub ref_uuid: Uuid,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+}
+
+// ID: Insert pergerakan stok di dalam transaksi pemanggil. Pemanggil wajib memanggil
+// recompute_stock_for_ingredient setelah commit.
+// EN: Insert a stock move inside the caller's transaction. The caller must call
+// recompute_stock_for_ingredient after commit.
+pub async fn insert_ledger_move(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    stock_move: &LedgerStockMove,
+    timestamp_ms: i64,
+) -> Result<Uuid, sqlx::Error> {
+    let move_uuid = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO ingredient_stock_moves (
+            uuid, name, ingredient_catalog_uuid, quantity, price, price_updated_at,
+            effective_at, expiry_at, ref_type, ref_uuid, unit_of_measure_code,
+            unit_of_measure_name, created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, $4, $5, NULL, $6, NULL, $7, $8, $9, $10, $6, $6, 0)
+        "#,
+    )
+    .bind(move_uuid)
+    .bind(&stock_move.name)
+    .bind(stock_move.ingredient_catalog_uuid)
+    .bind(stock_move.quantity)
+    .bind(stock_move.price)
+    .bind(timestamp_ms)
+    .bind(stock_move.ref_type)
+    .bind(stock_move.ref_uuid)
+    .bind(&stock_move.unit_of_measure_code)
+    .bind(&stock_move.unit_of_measure_name)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(move_uuid)
+}
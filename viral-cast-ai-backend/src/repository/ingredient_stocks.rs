@@ -360,19 +360,35 @@ pub async fn delete_ingredient_stock(db: &Pool<Postgres>, id: Uuid) -> Result<()
     Ok(())
 }
 
+#[derive(Debug, sqlx::FromRow)]
+struct LedgerMoveRow {
+    uuid: Uuid,
+    quantity: Decimal,
+    price: Option<Decimal>,
+    ref_type: Option<String>,
+    unit_of_measure_code: Option<String>,
+    unit_of_measure_name: Option<String>,
+    is_production_output: bool,
+}
+
 pub async fn recompute_stock_for_ingredient(
     db: &Pool<Postgres>,
     ingredient_catalog_uuid: Uuid,
 ) -> Result<(), sqlx::Error> {
-    let moves = sqlx::query!(
+    // Move PRODUCTION hasil produksi (output_move_uuid) adalah barang masuk
+    let moves = sqlx::query_as::<_, LedgerMoveRow>(
         r#"
-        SELECT uuid, quantity, price, ref_type, unit_of_measure_code, unit_of_measure_name
-        FROM ingredient_stock_moves
-        WHERE ingredient_catalog_uuid = $1 AND deleted_at = 0
-        ORDER BY effective_at ASC, created_at ASC
+        SELECT m.uuid, m.quantity, m.price, m.ref_type, m.unit_of_measure_code, m.unit_of_measure_name,
+               EXISTS (
+                   SELECT 1 FROM production_runs pr
+                   WHERE pr.output_move_uuid = m.uuid AND pr.deleted_at = 0
+               ) AS is_production_output
+        FROM ingredient_stock_moves m
+        WHERE m.ingredient_catalog_uuid = $1 AND m.deleted_at = 0
+        ORDER BY m.effective_at ASC, m.created_at ASC
         "#,
-        ingredient_catalog_uuid
     )
+    .bind(ingredient_catalog_uuid)
     .fetch_all(db)
     .await?;
 
@@ -390,7 +406,11 @@ pub async fn recompute_stock_for_ingredient(
             continue;
         }
 
-        let delta = signed_quantity(record.ref_type.as_deref(), quantity);
+        let delta = if record.is_production_output {
+            quantity.abs()
+        } else {
+            signed_quantity(record.ref_type.as_deref(), quantity)
+        };
         if delta.is_zero() {
             continue;
         }
@@ -584,9 +604,70 @@ pub async fn recompute_stock_for_ingredient(
 
 fn signed_quantity(ref_type: Option<&str>, quantity: Decimal) -> Decimal {
     match ref_type.map(|s| s.to_ascii_uppercase()).as_deref() {
-        Some("PRODUCTION") | Some("WASTE") => -quantity.abs(),
+        Some("PRODUCTION") | Some("WASTE") | Some("SALE") => -quantity.abs(),
         Some("ADJUSTMENT") => quantity,
         Some("RETURN") | Some("PURCHASE") => quantity.abs(),
         _ => quantity,
     }
 }
+
+// ID: Biaya per unit terkini per bahan (avg_cost, fallback current_cost) untuk roll-up biaya resep
+// EN: Current unit cost per ingredient (avg_cost, falling back to current_cost) for recipe cost roll-up
+pub async fn fetch_current_unit_costs(
+    db: &Pool<Postgres>,
+) -> Result<std::collections::HashMap<Uuid, Decimal>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, Option<Decimal>)>(
+        r#"
+        SELECT DISTINCT ON (m.ingredient_catalog_uuid)
+               m.ingredient_catalog_uuid,
+               COALESCE(s.avg_cost, s.current_cost) AS unit_cost
+        FROM ingredient_stocks s
+        JOIN ingredient_stock_moves m ON s.ingredient_stock_moves_uuid = m.uuid
+        WHERE s.deleted_at = 0
+        ORDER BY m.ingredient_catalog_uuid, s.updated_at DESC
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(uuid, cost)| cost.map(|cost| (uuid, cost)))
+        .collect())
+}
+
+// Snapshot biaya & satuan bahan saat ini, dibaca di dalam transaksi pemanggil
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StockCostSnapshot {
+    pub avg_cost: Option<Decimal>,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+}
+
+pub async fn fetch_stock_cost_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    ingredient_catalog_uuid: Uuid,
+) -> Result<StockCostSnapshot, sqlx::Error> {
+    sqlx::query_as::<_, StockCostSnapshot>(
+        r#"
+        SELECT st.avg_cost,
+               COALESCE(st.unit_of_measure_code, uom.code) AS unit_of_measure_code,
+               COALESCE(st.unit_of_measure_name, uom.name) AS unit_of_measure_name
+        FROM ingredient_catalog ic
+        LEFT JOIN units_of_measure uom ON ic.unit_of_measure_uuid = uom.uuid
+        LEFT JOIN LATERAL (
+            SELECT s.avg_cost, s.unit_of_measure_code, s.unit_of_measure_name
+            FROM ingredient_stocks s
+            JOIN ingredient_stock_moves m ON s.ingredient_stock_moves_uuid = m.uuid
+            WHERE m.ingredient_catalog_uuid = ic.uuid
+              AND s.deleted_at = 0
+            ORDER BY s.updated_at DESC
+            LIMIT 1
+        ) st ON TRUE
+        WHERE ic.uuid = $1
+        "#,
+    )
+    .bind(ingredient_catalog_uuid)
+    .fetch_one(&mut **tx)
+    .await
+}
@@ -0,0 +1,752 @@
+use rust_decimal::Decimal;
+use sqlx::{FromRow, Pool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::models::order_refunds::{
+    OrderRefund, OrderRefundItemWithProduct, RefundPayment, REFUND_GATEWAY_CLAIM_TTL_MS,
+    REFUND_STATUS_APPROVED, REFUND_STATUS_PENDING, REFUND_STATUS_PROCESSING,
+    REFUND_STATUS_REJECTED,
+};
+use crate::repository::ingredient_stock_moves::{self, LedgerStockMove};
+use crate::repository::ingredient_stocks;
+
+const ORDER_REFUND_COLUMNS: &str = "uuid, refund_no, order_uuid, kind, status, reason, amount, \
+     cost_amount, profit_reversed, restock, qris_sandbox, requested_by, approved_by, approved_at, \
+     rejected_reason, created_at, updated_at, deleted_at";
+
+// Hasil transisi status refund (reject)
+#[derive(Debug)]
+pub enum RefundTransition {
+    Done,
+    NotFound,
+    InvalidStatus(String),
+    // Refund PROCESSING yang sebagian porsi QRIS-nya sudah diterima Xendit
+    GatewayRefunded,
+    // Refund PROCESSING yang sedang diklaim approver untuk memanggil Xendit
+    GatewayClaimed,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OrderForRefund {
+    pub uuid: Uuid,
+    pub order_no: String,
+    pub status: String,
+    pub subtotal: Decimal,
+    pub total: Decimal,
+    pub created_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RefundableLine {
+    pub order_item_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub qty: Decimal,
+    pub unit_price: Decimal,
+    pub unit_cost: Option<Decimal>,
+    pub line_total: Decimal,
+    // Qty yang sudah direfund atau sedang menunggu approval
+    pub refunded_qty: Decimal,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RefundableTender {
+    pub uuid: Uuid,
+    pub method: String,
+    pub amount: Decimal,
+    pub external_ref: Option<String>,
+    pub refunded: Decimal,
+}
+
+// Cadangan refund QRIS yang belum dikirim ke Xendit
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingGatewayRefund {
+    pub payment_uuid: Uuid,
+    pub tender_uuid: Uuid,
+    pub qr_id: Option<String>,
+    pub amount: Decimal,
+    pub tender_amount: Decimal,
+    // Bagian tender yang sudah dikembalikan oleh refund lain
+    pub refunded_elsewhere: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewOrderRefundItem {
+    pub order_item_uuid: Uuid,
+    pub product_uuid: Uuid,
+    pub qty: Decimal,
+    pub unit_price: Decimal,
+    pub amount: Decimal,
+    pub unit_cost: Option<Decimal>,
+    pub cost_amount: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewOrderRefund {
+    pub order_uuid: Uuid,
+    pub kind: &'static str,
+    pub reason: String,
+    pub amount: Decimal,
+    pub cost_amount: Decimal,
+    pub restock: bool,
+    pub qris_sandbox: bool,
+    pub requested_by: Option<Uuid>,
+    pub items: Vec<NewOrderRefundItem>,
+}
+
+fn generate_refund_no(uuid: Uuid, timestamp_ms: i64) -> String {
+    let date = chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .unwrap_or_default()
+        .format("%Y%m%d");
+    let suffix: String = uuid.simple().to_string().chars().rev().take(6).collect();
+    format!("RF-{}-{}", date, suffix.to_uppercase())
+}
+
+// Pesanan dikunci lebih dulu (urutan kunci sama dengan pembayaran)
+pub async fn lock_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+) -> Result<Option<OrderForRefund>, sqlx::Error> {
+    sqlx::query_as::<_, OrderForRefund>(
+        r#"
+        SELECT uuid, order_no, status, subtotal, total, created_at
+        FROM orders
+        WHERE uuid = $1 AND deleted_at = 0
+        FOR UPDATE
+        "#,
+    )
+    .bind(order_uuid)
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+// Toko pesanan untuk membatasi approver refund
+pub async fn find_order_store(
+    db: &Pool<Postgres>,
+    order_uuid: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar("SELECT store_uuid FROM orders WHERE uuid = $1")
+        .bind(order_uuid)
+        .fetch_optional(db)
+        .await
+        .map(Option::flatten)
+}
+
+pub async fn order_exists(db: &Pool<Postgres>, order_uuid: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM orders WHERE uuid = $1 AND deleted_at = 0)")
+        .bind(order_uuid)
+        .fetch_one(db)
+        .await
+}
+
+// ID: Baris pesanan beserta qty yang sudah direfund/sedang diproses/menunggu approval (selain
+//     `exclude_refund`).
+// EN: Order lines with the qty already refunded, processing or awaiting approval (except
+//     `exclude_refund`).
+pub async fn load_refundable_lines(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    exclude_refund: Option<Uuid>,
+) -> Result<Vec<RefundableLine>, sqlx::Error> {
+    sqlx::query_as::<_, RefundableLine>(
+        r#"
+        SELECT oi.uuid AS order_item_uuid,
+               oi.product_uuid,
+               oi.qty,
+               oi.unit_price,
+               oi.unit_cost,
+               oi.line_total,
+               COALESCE((
+                   SELECT SUM(ri.qty)
+                   FROM order_refund_items ri
+                   JOIN order_refunds r ON r.uuid = ri.refund_uuid
+                   WHERE ri.order_item_uuid = oi.uuid
+                     AND r.deleted_at = 0
+                     AND r.status IN ($2, $3, $4)
+                     AND ($5::uuid IS NULL OR r.uuid <> $5)
+               ), 0) AS refunded_qty
+        FROM order_items oi
+        WHERE oi.order_uuid = $1 AND oi.deleted_at = 0
+        ORDER BY oi.created_at ASC
+        "#,
+    )
+    .bind(order_uuid)
+    .bind(REFUND_STATUS_PENDING)
+    .bind(REFUND_STATUS_PROCESSING)
+    .bind(REFUND_STATUS_APPROVED)
+    .bind(exclude_refund)
+    .fetch_all(&mut **tx)
+    .await
+}
+
+// ID: Nominal yang masih bisa dikembalikan = pembayaran selesai - refund yang sudah dibukukan
+//     - refund yang masih PENDING/PROCESSING (selain `exclude_refund`).
+// EN: Amount still refundable = completed payments - refunds already booked
+//     - refunds still PENDING/PROCESSING (except `exclude_refund`).
+pub async fn refundable_amount(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    exclude_refund: Option<Uuid>,
+) -> Result<Decimal, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT COALESCE((
+                   SELECT SUM(amount)
+                   FROM payments
+                   WHERE order_uuid = $1 AND deleted_at = 0 AND paid_at IS NOT NULL
+               ), 0)
+             - COALESCE((
+                   SELECT SUM(amount)
+                   FROM order_refunds
+                   WHERE order_uuid = $1 AND deleted_at = 0 AND status IN ($2, $3)
+                     AND ($4::uuid IS NULL OR uuid <> $4)
+               ), 0)
+        "#,
+    )
+    .bind(order_uuid)
+    .bind(REFUND_STATUS_PENDING)
+    .bind(REFUND_STATUS_PROCESSING)
+    .bind(exclude_refund)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+// Tender asal yang sudah selesai, pembayaran terbaru lebih dulu
+pub async fn load_refundable_tenders(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+) -> Result<Vec<RefundableTender>, sqlx::Error> {
+    sqlx::query_as::<_, RefundableTender>(
+        r#"
+        SELECT p.uuid,
+               p.method,
+               p.amount,
+               p.external_ref,
+               COALESCE((
+                   SELECT -SUM(r.amount)
+                   FROM payments r
+                   WHERE r.refunded_payment_uuid = p.uuid AND r.deleted_at = 0
+               ), 0) AS refunded
+        FROM payments p
+        WHERE p.order_uuid = $1
+          AND p.deleted_at = 0
+          AND p.refund_uuid IS NULL
+          AND p.paid_at IS NOT NULL
+        ORDER BY p.created_at DESC
+        "#,
+    )
+    .bind(order_uuid)
+    .fetch_all(&mut **tx)
+    .await
+}
+
+pub async fn insert_refund(
+    tx: &mut Transaction<'_, Postgres>,
+    new_refund: &NewOrderRefund,
+    timestamp_ms: i64,
+) -> Result<Uuid, sqlx::Error> {
+    let refund_uuid = Uuid::new_v4();
+
+    sqlx::query(
+        r#"
+        INSERT INTO order_refunds (
+            uuid, refund_no, order_uuid, kind, status, reason, amount, cost_amount,
+            profit_reversed, restock, qris_sandbox, requested_by, created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 0, $9, $10, $11, $12, $12, 0)
+        "#,
+    )
+    .bind(refund_uuid)
+    .bind(generate_refund_no(refund_uuid, timestamp_ms))
+    .bind(new_refund.order_uuid)
+    .bind(new_refund.kind)
+    .bind(REFUND_STATUS_PENDING)
+    .bind(&new_refund.reason)
+    .bind(new_refund.amount)
+    .bind(new_refund.cost_amount)
+    .bind(new_refund.restock)
+    .bind(new_refund.qris_sandbox)
+    .bind(new_refund.requested_by)
+    .bind(timestamp_ms)
+    .execute(&mut **tx)
+    .await?;
+
+    for item in &new_refund.items {
+        sqlx::query(
+            r#"
+            INSERT INTO order_refund_items (
+                uuid, refund_uuid, order_item_uuid, product_uuid, qty, unit_price, amount,
+                unit_cost, cost_amount, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(refund_uuid)
+        .bind(item.order_item_uuid)
+        .bind(item.product_uuid)
+        .bind(item.qty)
+        .bind(item.unit_price)
+        .bind(item.amount)
+        .bind(item.unit_cost)
+        .bind(item.cost_amount)
+        .bind(timestamp_ms)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(refund_uuid)
+}
+
+pub async fn get_refund(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+) -> Result<Option<OrderRefund>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM order_refunds WHERE uuid = $1 AND deleted_at = 0",
+        ORDER_REFUND_COLUMNS
+    );
+    sqlx::query_as::<_, OrderRefund>(&query)
+        .bind(uuid)
+        .fetch_optional(db)
+        .await
+}
+
+pub async fn lock_refund(
+    tx: &mut Transaction<'_, Postgres>,
+    uuid: Uuid,
+) -> Result<Option<OrderRefund>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM order_refunds WHERE uuid = $1 AND deleted_at = 0 FOR UPDATE",
+        ORDER_REFUND_COLUMNS
+    );
+    sqlx::query_as::<_, OrderRefund>(&query)
+        .bind(uuid)
+        .fetch_optional(&mut **tx)
+        .await
+}
+
+pub async fn list_refunds_for_order(
+    db: &Pool<Postgres>,
+    order_uuid: Uuid,
+) -> Result<Vec<OrderRefund>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM order_refunds WHERE order_uuid = $1 AND deleted_at = 0 ORDER BY created_at DESC",
+        ORDER_REFUND_COLUMNS
+    );
+    sqlx::query_as::<_, OrderRefund>(&query)
+        .bind(order_uuid)
+        .fetch_all(db)
+        .await
+}
+
+pub async fn get_order_no(
+    db: &Pool<Postgres>,
+    order_uuid: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT order_no FROM orders WHERE uuid = $1")
+        .bind(order_uuid)
+        .fetch_optional(db)
+        .await
+}
+
+pub async fn get_refund_items(
+    db: &Pool<Postgres>,
+    refund_uuid: Uuid,
+) -> Result<Vec<OrderRefundItemWithProduct>, sqlx::Error> {
+    sqlx::query_as::<_, OrderRefundItemWithProduct>(
+        r#"
+        SELECT ri.uuid,
+               ri.refund_uuid,
+               ri.order_item_uuid,
+               ri.product_uuid,
+               ri.qty,
+               ri.unit_price,
+               ri.amount,
+               ri.unit_cost,
+               ri.cost_amount,
+               ri.created_at,
+               p.name AS product_name
+        FROM order_refund_items ri
+        LEFT JOIN products p ON p.uuid = ri.product_uuid
+        WHERE ri.refund_uuid = $1
+        ORDER BY ri.created_at ASC, p.name ASC
+        "#,
+    )
+    .bind(refund_uuid)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn get_refund_payments(
+    db: &Pool<Postgres>,
+    refund_uuid: Uuid,
+) -> Result<Vec<RefundPayment>, sqlx::Error> {
+    sqlx::query_as::<_, RefundPayment>(
+        r#"
+        SELECT uuid, refunded_payment_uuid, method, amount, external_ref, paid_at
+        FROM payments
+        WHERE refund_uuid = $1 AND deleted_at = 0
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(refund_uuid)
+    .fetch_all(db)
+    .await
+}
+
+// ID: Cadangkan pengembalian dana sebagai pembayaran negatif atas tender asal. paid_at baru
+//     diisi complete_refund_payments setelah gateway selesai.
+// EN: Reserve a refund as a negative payment against the original tender. paid_at is only
+//     set by complete_refund_payments once the gateway is done.
+pub async fn insert_refund_payment(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    refund_uuid: Uuid,
+    refunded_payment_uuid: Uuid,
+    method: &str,
+    amount: Decimal,
+    timestamp_ms: i64,
+) -> Result<Uuid, sqlx::Error> {
+    let payment_uuid = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO payments (
+            uuid, order_uuid, method, amount, tendered_amount, change_amount, paid_at,
+            external_ref, refund_uuid, refunded_payment_uuid, created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, $4, NULL, 0, NULL, NULL, $5, $6, $7, $7, 0)
+        "#,
+    )
+    .bind(payment_uuid)
+    .bind(order_uuid)
+    .bind(method)
+    .bind(-amount.abs())
+    .bind(refund_uuid)
+    .bind(refunded_payment_uuid)
+    .bind(timestamp_ms)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(payment_uuid)
+}
+
+// ID: Cadangan QRIS milik refund yang belum punya ID refund Xendit, beserta tender asalnya.
+// EN: QRIS reservations of a refund that have no Xendit refund ID yet, with their tender.
+pub async fn pending_gateway_refunds(
+    db: &Pool<Postgres>,
+    refund_uuid: Uuid,
+) -> Result<Vec<PendingGatewayRefund>, sqlx::Error> {
+    sqlx::query_as::<_, PendingGatewayRefund>(
+        r#"
+        SELECT r.uuid AS payment_uuid,
+               t.uuid AS tender_uuid,
+               t.external_ref AS qr_id,
+               -r.amount AS amount,
+               t.amount AS tender_amount,
+               COALESCE((
+                   SELECT -SUM(o.amount)
+                   FROM payments o
+                   WHERE o.refunded_payment_uuid = t.uuid AND o.deleted_at = 0
+                     AND o.refund_uuid <> r.refund_uuid
+               ), 0) AS refunded_elsewhere
+        FROM payments r
+        JOIN payments t ON t.uuid = r.refunded_payment_uuid
+        WHERE r.refund_uuid = $1 AND r.deleted_at = 0
+          AND r.method = 'QRIS' AND r.external_ref IS NULL
+        ORDER BY r.created_at ASC
+        "#,
+    )
+    .bind(refund_uuid)
+    .fetch_all(db)
+    .await
+}
+
+// ID: Klaim refund PROCESSING untuk memanggil Xendit. Klaim langsung di-commit (tanpa kunci
+//     baris yang ditahan selama HTTP); klaim lain yang belum kedaluwarsa membuatnya gagal.
+//     Mengembalikan waktu klaim bila berhasil.
+// EN: Claim a PROCESSING refund to call Xendit. The claim commits right away (no row lock is
+//     held over HTTP); another unexpired claim makes it fail. Returns the claim time on success.
+pub async fn claim_refund_gateway(
+    db: &Pool<Postgres>,
+    refund_uuid: Uuid,
+    timestamp_ms: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        UPDATE order_refunds
+        SET gateway_started_at = $3
+        WHERE uuid = $1 AND deleted_at = 0 AND status = $2
+          AND (gateway_started_at IS NULL OR gateway_started_at <= $4)
+        RETURNING gateway_started_at
+        "#,
+    )
+    .bind(refund_uuid)
+    .bind(REFUND_STATUS_PROCESSING)
+    .bind(timestamp_ms)
+    .bind(timestamp_ms - REFUND_GATEWAY_CLAIM_TTL_MS)
+    .fetch_optional(db)
+    .await
+}
+
+// Lepas klaim milik approval ini saja (klaim yang sudah diambil alih dibiarkan)
+pub async fn release_refund_gateway(
+    db: &Pool<Postgres>,
+    refund_uuid: Uuid,
+    claimed_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE order_refunds SET gateway_started_at = NULL WHERE uuid = $1 AND gateway_started_at = $2",
+    )
+    .bind(refund_uuid)
+    .bind(claimed_at)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+// Dicatat segera setelah Xendit menerima refund agar approval ulang tidak mengirimnya lagi
+pub async fn set_refund_payment_external_ref(
+    db: &Pool<Postgres>,
+    payment_uuid: Uuid,
+    external_ref: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE payments SET external_ref = $2 WHERE uuid = $1 AND refund_uuid IS NOT NULL",
+    )
+    .bind(payment_uuid)
+    .bind(external_ref)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn complete_refund_payments(
+    tx: &mut Transaction<'_, Postgres>,
+    refund_uuid: Uuid,
+    timestamp_ms: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE payments
+        SET paid_at = $2, updated_at = $2
+        WHERE refund_uuid = $1 AND deleted_at = 0 AND paid_at IS NULL
+        "#,
+    )
+    .bind(refund_uuid)
+    .bind(timestamp_ms)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+// ID: Bahan yang masih bisa dikembalikan ke stok untuk pesanan: move SALE pesanan dikurangi
+//     move RETURN yang sudah diposting refund-refundnya.
+// EN: Ingredients an order can still return to stock: the order's SALE moves minus the
+//     RETURN moves already posted by its refunds.
+pub async fn restockable_ingredients(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+) -> Result<Vec<(Uuid, Decimal)>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT ingredient_catalog_uuid, -SUM(quantity) AS quantity
+        FROM ingredient_stock_moves
+        WHERE deleted_at = 0
+          AND (
+            (ref_type = 'SALE' AND ref_uuid = $1)
+            OR (
+              ref_type = 'RETURN'
+              AND ref_uuid IN (SELECT uuid FROM order_refunds WHERE order_uuid = $1)
+            )
+          )
+        GROUP BY ingredient_catalog_uuid
+        HAVING -SUM(quantity) > 0
+        "#,
+    )
+    .bind(order_uuid)
+    .fetch_all(&mut **tx)
+    .await
+}
+
+// ID: Posting move RETURN per bahan (dinilai dengan avg_cost saat ini). Pemanggil wajib
+// memanggil recompute_stock_for_ingredient setelah commit.
+// EN: Post one RETURN move per ingredient (valued at the current avg_cost). The caller must
+// call recompute_stock_for_ingredient after commit.
+pub async fn post_restock_moves(
+    tx: &mut Transaction<'_, Postgres>,
+    refund_uuid: Uuid,
+    refund_no: &str,
+    lines: &[(Uuid, Decimal)],
+    timestamp_ms: i64,
+) -> Result<(), sqlx::Error> {
+    let move_name = format!("Refund {}", refund_no);
+    for (ingredient_catalog_uuid, quantity) in lines {
+        let snapshot =
+            ingredient_stocks::fetch_stock_cost_snapshot(tx, *ingredient_catalog_uuid).await?;
+        ingredient_stock_moves::insert_ledger_move(
+            tx,
+            &LedgerStockMove {
+                name: Some(move_name.clone()),
+                ingredient_catalog_uuid: *ingredient_catalog_uuid,
+                quantity: *quantity,
+                price: snapshot.avg_cost,
+                ref_type: "RETURN",
+                ref_uuid: refund_uuid,
+                unit_of_measure_code: snapshot.unit_of_measure_code,
+                unit_of_measure_name: snapshot.unit_of_measure_name,
+            },
+            timestamp_ms,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn mark_refund_processing(
+    tx: &mut Transaction<'_, Postgres>,
+    refund_uuid: Uuid,
+    approved_by: Uuid,
+    amount: Decimal,
+    timestamp_ms: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE order_refunds
+        SET status = $2, approved_by = $3, amount = $4, updated_at = $5
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(refund_uuid)
+    .bind(REFUND_STATUS_PROCESSING)
+    .bind(approved_by)
+    .bind(amount)
+    .bind(timestamp_ms)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_refund_approved(
+    tx: &mut Transaction<'_, Postgres>,
+    refund_uuid: Uuid,
+    profit_reversed: Decimal,
+    timestamp_ms: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE order_refunds
+        SET status = $2, approved_at = $3, profit_reversed = $4, updated_at = $3
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(refund_uuid)
+    .bind(REFUND_STATUS_APPROVED)
+    .bind(timestamp_ms)
+    .bind(profit_reversed)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+pub async fn set_order_status(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    status: &str,
+    timestamp_ms: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE orders SET status = $2, updated_at = $3 WHERE uuid = $1")
+        .bind(order_uuid)
+        .bind(status)
+        .bind(timestamp_ms)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// ID: Tolak refund PENDING, atau PROCESSING yang belum satu pun diterima Xendit; cadangan
+//     pembayaran negatifnya dihapus.
+// EN: Reject a PENDING refund, or a PROCESSING one Xendit has not accepted any part of yet;
+//     its reserved negative payments are removed.
+pub async fn reject_refund(
+    db: &Pool<Postgres>,
+    refund_uuid: Uuid,
+    rejected_by: Uuid,
+    reason: &str,
+    timestamp_ms: i64,
+) -> Result<RefundTransition, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let status: Option<String> = sqlx::query_scalar(
+        "SELECT status FROM order_refunds WHERE uuid = $1 AND deleted_at = 0 FOR UPDATE",
+    )
+    .bind(refund_uuid)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    match status.as_deref() {
+        None => return Ok(RefundTransition::NotFound),
+        Some(REFUND_STATUS_PENDING) => {}
+        Some(REFUND_STATUS_PROCESSING) => {
+            // Approver yang sedang memanggil Xendit tidak boleh disusul penolakan
+            let gateway_claimed: bool = sqlx::query_scalar(
+                "SELECT COALESCE(gateway_started_at > $2, FALSE) FROM order_refunds WHERE uuid = $1",
+            )
+            .bind(refund_uuid)
+            .bind(timestamp_ms - REFUND_GATEWAY_CLAIM_TTL_MS)
+            .fetch_one(&mut *tx)
+            .await?;
+            if gateway_claimed {
+                return Ok(RefundTransition::GatewayClaimed);
+            }
+
+            // Uang yang sudah dikembalikan Xendit tidak bisa ditarik lagi; refund harus
+            // diselesaikan lewat approval ulang
+            let gateway_refunded: bool = sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM payments
+                    WHERE refund_uuid = $1 AND deleted_at = 0 AND external_ref IS NOT NULL
+                )
+                "#,
+            )
+            .bind(refund_uuid)
+            .fetch_one(&mut *tx)
+            .await?;
+            if gateway_refunded {
+                return Ok(RefundTransition::GatewayRefunded);
+            }
+
+            // Cadangan pembayaran negatif dilepas agar tendernya bisa direfund lagi
+            sqlx::query(
+                r#"
+                UPDATE payments
+                SET deleted_at = $2, updated_at = $2
+                WHERE refund_uuid = $1 AND deleted_at = 0 AND paid_at IS NULL
+                "#,
+            )
+            .bind(refund_uuid)
+            .bind(timestamp_ms)
+            .execute(&mut *tx)
+            .await?;
+        }
+        Some(other) => return Ok(RefundTransition::InvalidStatus(other.to_string())),
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE order_refunds
+        SET status = $2, approved_by = $3, rejected_reason = $4, updated_at = $5
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(refund_uuid)
+    .bind(REFUND_STATUS_REJECTED)
+    .bind(rejected_by)
+    .bind(reason)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(RefundTransition::Done)
+}
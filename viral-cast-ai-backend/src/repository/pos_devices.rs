@@ -0,0 +1,223 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::models::pos_devices::{PosDevice, PosStaffMember};
+use crate::repository::auth_tokens as auth_tokens_repository;
+
+const DEVICE_COLUMNS: &str =
+    "uuid, store_uuid, name, enrolled_by, last_used_at, revoked_at, created_at, updated_at";
+
+pub async fn create_device(
+    db: &Pool<Postgres>,
+    store_uuid: Uuid,
+    name: &str,
+    credential_hash: &str,
+    enrolled_by: Uuid,
+) -> sqlx::Result<PosDevice> {
+    sqlx::query_as::<_, PosDevice>(&format!(
+        r#"
+        INSERT INTO pos_devices (store_uuid, name, credential_hash, enrolled_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {}
+        "#,
+        DEVICE_COLUMNS
+    ))
+    .bind(store_uuid)
+    .bind(name)
+    .bind(credential_hash)
+    .bind(enrolled_by)
+    .fetch_one(db)
+    .await
+}
+
+pub async fn list_devices(db: &Pool<Postgres>, store_uuid: Uuid) -> sqlx::Result<Vec<PosDevice>> {
+    sqlx::query_as::<_, PosDevice>(&format!(
+        r#"
+        SELECT {}
+        FROM pos_devices
+        WHERE store_uuid = $1 AND revoked_at IS NULL
+        ORDER BY created_at
+        "#,
+        DEVICE_COLUMNS
+    ))
+    .bind(store_uuid)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn find_device(
+    db: &Pool<Postgres>,
+    device_uuid: Uuid,
+) -> sqlx::Result<Option<PosDevice>> {
+    sqlx::query_as::<_, PosDevice>(&format!(
+        "SELECT {} FROM pos_devices WHERE uuid = $1",
+        DEVICE_COLUMNS
+    ))
+    .bind(device_uuid)
+    .fetch_optional(db)
+    .await
+}
+
+// Perangkat aktif pemilik kredensial; last_used_at ikut diperbarui
+pub async fn authenticate_device(
+    db: &Pool<Postgres>,
+    credential_hash: &str,
+) -> sqlx::Result<Option<PosDevice>> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query_as::<_, PosDevice>(&format!(
+        r#"
+        UPDATE pos_devices
+        SET last_used_at = $2
+        WHERE credential_hash = $1 AND revoked_at IS NULL
+        RETURNING {}
+        "#,
+        DEVICE_COLUMNS
+    ))
+    .bind(credential_hash)
+    .bind(now)
+    .fetch_optional(db)
+    .await
+}
+
+// Toko perangkat bila kredensial cocok dengan perangkat itu dan belum dicabut (middleware auth)
+pub async fn find_active_device_store(
+    db: &Pool<Postgres>,
+    device_uuid: Uuid,
+    credential_hash: &str,
+) -> sqlx::Result<Option<Uuid>> {
+    sqlx::query_scalar(
+        r#"
+        SELECT store_uuid FROM pos_devices
+        WHERE uuid = $1 AND credential_hash = $2 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(device_uuid)
+    .bind(credential_hash)
+    .fetch_optional(db)
+    .await
+}
+
+// ID: Cabut perangkat beserta semua sesi PIN di dalamnya; mengembalikan token_uuid yang harus
+//     dihapus dari Redis / session store, atau None bila perangkat sudah dicabut.
+// EN: Revoke a device and every PIN session on it; returns the token_uuids to remove from
+//     Redis / the session store, or None when the device was already revoked.
+pub async fn revoke_device(
+    db: &Pool<Postgres>,
+    device_uuid: Uuid,
+    reason: &str,
+) -> sqlx::Result<Option<Vec<Uuid>>> {
+    let now = Utc::now().timestamp_millis();
+    let revoked = sqlx::query(
+        r#"
+        UPDATE pos_devices
+        SET revoked_at = $2, updated_at = $2
+        WHERE uuid = $1 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(device_uuid)
+    .bind(now)
+    .execute(db)
+    .await?
+    .rows_affected();
+    if revoked == 0 {
+        return Ok(None);
+    }
+    revoke_device_sessions(db, device_uuid, None, reason)
+        .await
+        .map(Some)
+}
+
+// Cabut sesi PIN aktif di perangkat (kecuali `keep_family` bila diisi)
+pub async fn revoke_device_sessions(
+    db: &Pool<Postgres>,
+    device_uuid: Uuid,
+    keep_family: Option<Uuid>,
+    reason: &str,
+) -> sqlx::Result<Vec<Uuid>> {
+    let family_uuids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT uuid FROM auth_token_families
+        WHERE pos_device_uuid = $1 AND revoked_at IS NULL
+          AND ($2::UUID IS NULL OR uuid <> $2)
+        "#,
+    )
+    .bind(device_uuid)
+    .bind(keep_family)
+    .fetch_all(db)
+    .await?;
+
+    let mut token_uuids = Vec::new();
+    for family_uuid in &family_uuids {
+        token_uuids
+            .extend(auth_tokens_repository::revoke_token_family(db, *family_uuid, reason).await?);
+    }
+    Ok(token_uuids)
+}
+
+pub async fn upsert_pin(db: &Pool<Postgres>, user_uuid: Uuid, pin_hash: &str) -> sqlx::Result<()> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query(
+        r#"
+        INSERT INTO cashier_pins (user_uuid, pin_hash, created_at, updated_at)
+        VALUES ($1, $2, $3, $3)
+        ON CONFLICT (user_uuid) DO UPDATE
+        SET pin_hash = EXCLUDED.pin_hash, updated_at = EXCLUDED.updated_at
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(pin_hash)
+    .bind(now)
+    .execute(db)
+    .await
+    .map(|_| ())
+}
+
+pub async fn delete_pin(db: &Pool<Postgres>, user_uuid: Uuid) -> sqlx::Result<bool> {
+    sqlx::query("DELETE FROM cashier_pins WHERE user_uuid = $1")
+        .bind(user_uuid)
+        .execute(db)
+        .await
+        .map(|result| result.rows_affected() > 0)
+}
+
+// Hash PIN staf, hanya bila profil aktifnya ada di toko perangkat
+pub async fn find_store_pin_hash(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    store_uuid: Uuid,
+) -> sqlx::Result<Option<String>> {
+    sqlx::query_scalar(
+        r#"
+        SELECT cp.pin_hash
+        FROM cashier_pins cp
+        JOIN profiles p ON p.user_uuid = cp.user_uuid AND p.deleted_at = 0
+        JOIN users u ON u.uuid = cp.user_uuid AND u.deleted_at = 0
+        WHERE cp.user_uuid = $1 AND p.store_uuid = $2
+        LIMIT 1
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(store_uuid)
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn list_staff_with_pin(
+    db: &Pool<Postgres>,
+    store_uuid: Uuid,
+) -> sqlx::Result<Vec<PosStaffMember>> {
+    sqlx::query_as::<_, PosStaffMember>(
+        r#"
+        SELECT u.uuid AS user_uuid, u.username, p.first_name, p.last_name, p.roles_number
+        FROM cashier_pins cp
+        JOIN users u ON u.uuid = cp.user_uuid AND u.deleted_at = 0
+        JOIN profiles p ON p.user_uuid = u.uuid AND p.deleted_at = 0
+        WHERE p.store_uuid = $1
+        ORDER BY u.username
+        "#,
+    )
+    .bind(store_uuid)
+    .fetch_all(db)
+    .await
+}
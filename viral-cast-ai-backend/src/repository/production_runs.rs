@@ -0,0 +1,280 @@
+use rust_decimal::Decimal;
+use sqlx::{Pool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::dto::production_runs::ProductionRunListRequest;
+use crate::models::production_runs::{ProductionRun, ProductionRunItemWithIngredient};
+use crate::repository::ingredient_stock_moves::{self, LedgerStockMove};
+use crate::repository::ingredient_stocks;
+
+const PRODUCTION_RUN_COLUMNS: &str =
+    "uuid, run_no, recipe_sets_uuid, output_ingredient_catalog_uuid, \
+     output_quantity, unit_cost, total_cost, output_move_uuid, store_uuid, notes, produced_at, \
+     produced_by, created_at, updated_at, deleted_at";
+
+#[derive(Debug, Clone)]
+pub struct NewProductionRun {
+    pub recipe_sets_uuid: Uuid,
+    pub recipe_name: String,
+    pub output_ingredient_catalog_uuid: Uuid,
+    pub output_quantity: Decimal,
+    pub store_uuid: Option<Uuid>,
+    pub notes: Option<String>,
+    pub produced_at: i64,
+    pub produced_by: Option<Uuid>,
+    // (ingredient_catalog_uuid, quantity) hasil explode resep
+    pub consumption: Vec<(Uuid, Decimal)>,
+}
+
+fn generate_run_no(uuid: Uuid, timestamp_ms: i64) -> String {
+    let date = chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .unwrap_or_default()
+        .format("%Y%m%d");
+    let suffix: String = uuid.simple().to_string().chars().rev().take(6).collect();
+    format!("PR-{}-{}", date, suffix.to_uppercase())
+}
+
+// ID: Posting produksi dalam satu transaksi: konsumsi bahan sebagai move PRODUCTION negatif
+// (dinilai avg_cost), lalu hasil sebagai move PRODUCTION positif dengan harga = total biaya / qty.
+// EN: Post a production run in one transaction: ingredient consumption as negative PRODUCTION
+// moves (valued at avg_cost), then the output as a positive PRODUCTION move priced at
+// total cost / quantity.
+pub async fn create_production_run(
+    db: &Pool<Postgres>,
+    run: &NewProductionRun,
+    timestamp_ms: i64,
+) -> Result<Uuid, sqlx::Error> {
+    let run_uuid = Uuid::new_v4();
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO production_runs (
+            uuid, run_no, recipe_sets_uuid, output_ingredient_catalog_uuid, output_quantity,
+            unit_cost, total_cost, store_uuid, notes, produced_at, produced_by,
+            created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, $4, $5, NULL, 0, $6, $7, $8, $9, $10, $10, 0)
+        "#,
+    )
+    .bind(run_uuid)
+    .bind(generate_run_no(run_uuid, timestamp_ms))
+    .bind(run.recipe_sets_uuid)
+    .bind(run.output_ingredient_catalog_uuid)
+    .bind(run.output_quantity)
+    .bind(run.store_uuid)
+    .bind(&run.notes)
+    .bind(run.produced_at)
+    .bind(run.produced_by)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    let move_name = format!("Produksi {}", run.recipe_name);
+    let mut total_cost = Decimal::ZERO;
+
+    for (ingredient_catalog_uuid, quantity) in &run.consumption {
+        let snapshot =
+            ingredient_stocks::fetch_stock_cost_snapshot(&mut tx, *ingredient_catalog_uuid).await?;
+        let line_cost = *quantity * snapshot.avg_cost.unwrap_or(Decimal::ZERO);
+
+        let move_uuid = ingredient_stock_moves::insert_ledger_move(
+            &mut tx,
+            &LedgerStockMove {
+                name: Some(move_name.clone()),
+                ingredient_catalog_uuid: *ingredient_catalog_uuid,
+                quantity: -*quantity,
+                price: snapshot.avg_cost,
+                ref_type: "PRODUCTION",
+                ref_uuid: run_uuid,
+                unit_of_measure_code: snapshot.unit_of_measure_code.clone(),
+                unit_of_measure_name: snapshot.unit_of_measure_name.clone(),
+            },
+            run.produced_at,
+        )
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO production_run_items (
+                uuid, production_run_uuid, ingredient_catalog_uuid, quantity, unit_cost,
+                total_cost, unit_of_measure_code, unit_of_measure_name, stock_move_uuid,
+                created_at, updated_at, deleted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10, 0)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(run_uuid)
+        .bind(ingredient_catalog_uuid)
+        .bind(quantity)
+        .bind(snapshot.avg_cost)
+        .bind(line_cost.round_dp(2))
+        .bind(snapshot.unit_of_measure_code)
+        .bind(snapshot.unit_of_measure_name)
+        .bind(move_uuid)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        total_cost += line_cost;
+    }
+
+    let unit_cost = (total_cost / run.output_quantity).round_dp(4);
+    let output =
+        ingredient_stocks::fetch_stock_cost_snapshot(&mut tx, run.output_ingredient_catalog_uuid)
+            .await?;
+    let output_move_uuid = ingredient_stock_moves::insert_ledger_move(
+        &mut tx,
+        &LedgerStockMove {
+            name: Some(move_name),
+            ingredient_catalog_uuid: run.output_ingredient_catalog_uuid,
+            quantity: run.output_quantity,
+            price: Some(unit_cost),
+            ref_type: "PRODUCTION",
+            ref_uuid: run_uuid,
+            unit_of_measure_code: output.unit_of_measure_code,
+            unit_of_measure_name: output.unit_of_measure_name,
+        },
+        run.produced_at,
+    )
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE production_runs
+        SET unit_cost = $2, total_cost = $3, output_move_uuid = $4
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(run_uuid)
+    .bind(unit_cost)
+    .bind(total_cost.round_dp(2))
+    .bind(output_move_uuid)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    for (ingredient_catalog_uuid, _) in &run.consumption {
+        ingredient_stocks::recompute_stock_for_ingredient(db, *ingredient_catalog_uuid).await?;
+    }
+    ingredient_stocks::recompute_stock_for_ingredient(db, run.output_ingredient_catalog_uuid)
+        .await?;
+
+    Ok(run_uuid)
+}
+
+pub async fn get_production_run(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+) -> Result<Option<ProductionRun>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM production_runs WHERE uuid = $1 AND deleted_at = 0",
+        PRODUCTION_RUN_COLUMNS
+    );
+    sqlx::query_as::<_, ProductionRun>(&query)
+        .bind(uuid)
+        .fetch_optional(db)
+        .await
+}
+
+// Nama resep dan nama bahan output untuk tampilan detail
+pub async fn get_production_run_labels(
+    db: &Pool<Postgres>,
+    run: &ProductionRun,
+) -> Result<(Option<String>, Option<String>), sqlx::Error> {
+    sqlx::query_as::<_, (Option<String>, Option<String>)>(
+        r#"
+        SELECT (SELECT name FROM recipe_sets WHERE uuid = $1),
+               (SELECT name FROM ingredient_catalog WHERE uuid = $2)
+        "#,
+    )
+    .bind(run.recipe_sets_uuid)
+    .bind(run.output_ingredient_catalog_uuid)
+    .fetch_one(db)
+    .await
+}
+
+pub async fn get_production_run_items(
+    db: &Pool<Postgres>,
+    production_run_uuid: Uuid,
+) -> Result<Vec<ProductionRunItemWithIngredient>, sqlx::Error> {
+    sqlx::query_as::<_, ProductionRunItemWithIngredient>(
+        r#"
+        SELECT pri.uuid,
+               pri.production_run_uuid,
+               pri.ingredient_catalog_uuid,
+               pri.quantity,
+               pri.unit_cost,
+               pri.total_cost,
+               pri.unit_of_measure_code,
+               pri.unit_of_measure_name,
+               pri.stock_move_uuid,
+               pri.created_at,
+               ic.name AS ingredient_name
+        FROM production_run_items pri
+        LEFT JOIN ingredient_catalog ic ON ic.uuid = pri.ingredient_catalog_uuid
+        WHERE pri.production_run_uuid = $1
+          AND pri.deleted_at = 0
+        ORDER BY ic.name ASC
+        "#,
+    )
+    .bind(production_run_uuid)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn list_production_runs(
+    db: &Pool<Postgres>,
+    page: usize,
+    limit: usize,
+    filters: &ProductionRunListRequest,
+) -> Result<(Vec<ProductionRun>, i64), sqlx::Error> {
+    let offset = (page.saturating_sub(1) * limit) as i64;
+
+    fn push_filters<'a>(
+        builder: &mut QueryBuilder<'a, Postgres>,
+        filters: &'a ProductionRunListRequest,
+    ) {
+        builder.push(" WHERE deleted_at = 0");
+        if let Some(recipe_sets_uuid) = filters.recipe_sets_uuid {
+            builder
+                .push(" AND recipe_sets_uuid = ")
+                .push_bind(recipe_sets_uuid);
+        }
+        if let Some(output_uuid) = filters.output_ingredient_catalog_uuid {
+            builder
+                .push(" AND output_ingredient_catalog_uuid = ")
+                .push_bind(output_uuid);
+        }
+        if let Some(date_from) = filters.date_from {
+            builder.push(" AND produced_at >= ").push_bind(date_from);
+        }
+        if let Some(date_to) = filters.date_to {
+            builder.push(" AND produced_at <= ").push_bind(date_to);
+        }
+    }
+
+    let mut list_builder = QueryBuilder::<Postgres>::new(format!(
+        "SELECT {} FROM production_runs",
+        PRODUCTION_RUN_COLUMNS
+    ));
+    push_filters(&mut list_builder, filters);
+    list_builder
+        .push(" ORDER BY produced_at DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let runs = list_builder
+        .build_query_as::<ProductionRun>()
+        .fetch_all(db)
+        .await?;
+
+    let mut count_builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM production_runs");
+    push_filters(&mut count_builder, filters);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(db).await?;
+
+    Ok((runs, total))
+}
@@ -4,6 +4,7 @@ use uuid::Uuid;
 
 use crate::dto::products::{CreateProductSchema, ProcessedProductSchema, UpdateProductSchema};
 use crate::models::products::ProductsModel;
+use crate::repository::recipe_sets;
 
 fn sanitize_sort_column(sort_by: Option<&str>) -> &'static str {
     match sort_by.map(|s| s.trim().to_lowercase()).as_deref() {
@@ -26,6 +27,7 @@ pub async fn create_product(
     db: &Pool<Postgres>,
     product_uuid: Uuid,
     body: CreateProductSchema,
+    image_variants: Option<serde_json::Value>,
     timestamp_ms: i64,
 ) -> sqlx::Result<ProcessedProductSchema> {
     sqlx::query!(
@@ -46,6 +48,19 @@ pub async fn create_product(
     .execute(db)
     .await?;
 
+    // Recipe set yang belum dimiliki produk lain menjadi versi resep produk ini
+    if let Some(recipe_sets_uuid) = body.recipe_sets_uuid {
+        recipe_sets::link_recipe_set_to_product(db, recipe_sets_uuid, product_uuid).await?;
+    }
+
+    if image_variants.is_some() {
+        sqlx::query("UPDATE products SET image_variants = $1 WHERE uuid = $2")
+            .bind(&image_variants)
+            .bind(product_uuid)
+            .execute(db)
+            .await?;
+    }
+
     Ok(ProcessedProductSchema {
         uuid: product_uuid,
         category_uuid: body.category_uuid,
@@ -57,6 +72,7 @@ pub async fn create_product(
         current_recipe_yield_qty: None,
         status: body.status.unwrap_or_else(|| "ACTIVE".to_string()),
         image_url: body.image_url,
+        image_variants,
         created_at: Some(timestamp_ms),
         updated_at: Some(timestamp_ms),
     })
@@ -92,6 +108,7 @@ pub async fn list_products(
             p.recipe_sets_uuid, 
             p.status, 
             p.image_url, 
+            p.image_variants, 
             p.created_at, 
             p.updated_at, 
             p.deleted_at, 
@@ -135,6 +152,7 @@ pub async fn list_products(
             current_recipe_yield_qty: row.get::<Option<Decimal>, _>("recipe_yield_qty"),
             status: row.get("status"),
             image_url: row.get("image_url"),
+            image_variants: row.get("image_variants"),
             created_at: row.get::<Option<i64>, _>("created_at").or(Some(0)),
             updated_at: row.get::<Option<i64>, _>("updated_at").or(Some(0)),
         })
@@ -147,7 +165,7 @@ pub async fn get_product_by_uuid(
     db: &Pool<Postgres>,
     id: Uuid,
 ) -> sqlx::Result<Option<ProcessedProductSchema>> {
-    let row = sqlx::query!(
+    let row = sqlx::query(
         r#"SELECT 
             p.uuid, 
             p.category_uuid, 
@@ -157,32 +175,33 @@ pub async fn get_product_by_uuid(
             p.recipe_sets_uuid, 
             p.status, 
             p.image_url, 
+            p.image_variants, 
             p.created_at, 
             p.updated_at, 
-            p.deleted_at, 
-            rs.name as "recipe_name: Option<String>", 
-            rs.yield_quantity as "recipe_yield_qty: Option<Decimal>" 
+            rs.name as recipe_name, 
+            rs.yield_quantity as recipe_yield_qty 
         FROM products p 
         LEFT JOIN recipe_sets rs ON p.recipe_sets_uuid = rs.uuid AND rs.deleted_at = 0 
         WHERE p.uuid = $1 AND p.deleted_at = 0"#,
-        id
     )
+    .bind(id)
     .fetch_optional(db)
     .await?;
 
-    Ok(row.map(|r| ProcessedProductSchema {
-        uuid: r.uuid,
-        category_uuid: r.category_uuid,
-        name: r.name,
-        sku: r.sku,
-        price: r.price,
-        recipe_sets_uuid: r.recipe_sets_uuid,
-        current_recipe_name: r.recipe_name,
-        current_recipe_yield_qty: r.recipe_yield_qty.flatten(),
-        status: r.status,
-        image_url: r.image_url,
-        created_at: r.created_at.or(Some(0)),
-        updated_at: r.updated_at.or(Some(0)),
+    Ok(row.map(|row| ProcessedProductSchema {
+        uuid: row.get("uuid"),
+        category_uuid: row.get("category_uuid"),
+        name: row.get("name"),
+        sku: row.get("sku"),
+        price: row.get("price"),
+        recipe_sets_uuid: row.get("recipe_sets_uuid"),
+        current_recipe_name: row.get::<Option<String>, _>("recipe_name"),
+        current_recipe_yield_qty: row.get::<Option<Decimal>, _>("recipe_yield_qty"),
+        status: row.get("status"),
+        image_url: row.get("image_url"),
+        image_variants: row.get("image_variants"),
+        created_at: row.get::<Option<i64>, _>("created_at").or(Some(0)),
+        updated_at: row.get::<Option<i64>, _>("updated_at").or(Some(0)),
     }))
 }
 
@@ -190,6 +209,7 @@ pub async fn update_product(
     db: &Pool<Postgres>,
     id: Uuid,
     body: UpdateProductSchema,
+    image_variants: Option<serde_json::Value>,
     timestamp_ms: i64,
 ) -> sqlx::Result<Option<ProcessedProductSchema>> {
     // Fetch existing product
@@ -212,6 +232,8 @@ pub async fn update_product(
     let new_price = body.price.unwrap_or(current.price);
     let new_recipe_uuid = body.recipe_sets_uuid.or(current.recipe_sets_uuid);
     let new_status = body.status.unwrap_or(current.status);
+    // Varian hanya dipertahankan selama image_url tidak berubah
+    let image_url_changed = body.image_url.is_some() && body.image_url != current.image_url;
     let new_image_url = body.image_url.or(current.image_url);
 
     let res = sqlx::query!(
@@ -242,6 +264,22 @@ pub async fn update_product(
         return Ok(None);
     }
 
+    if let Some(recipe_sets_uuid) = new_recipe_uuid {
+        recipe_sets::link_recipe_set_to_product(db, recipe_sets_uuid, id).await?;
+    }
+
+    let image_variants = sqlx::query_scalar::<_, Option<serde_json::Value>>(
+        r#"UPDATE products
+           SET image_variants = CASE WHEN $2 THEN $3 ELSE image_variants END
+           WHERE uuid = $1
+           RETURNING image_variants"#,
+    )
+    .bind(id)
+    .bind(image_url_changed)
+    .bind(image_variants)
+    .fetch_one(db)
+    .await?;
+
     Ok(Some(ProcessedProductSchema {
         uuid: id,
         category_uuid: new_category_uuid,
@@ -253,6 +291,7 @@ pub async fn update_product(
         current_recipe_yield_qty: None,
         status: new_status,
         image_url: new_image_url,
+        image_variants,
         created_at: current.created_at.or(Some(0)),
         updated_at: Some(timestamp_ms),
     }))
@@ -273,3 +312,27 @@ pub async fn soft_delete_product(
 
     Ok(res.rows_affected())
 }
+
+/// ID: Simpan URL gambar produk beserta varian hasil pipeline gambar.
+/// EN: Store the product image URL together with its image pipeline variants.
+pub async fn update_product_image(
+    db: &Pool<Postgres>,
+    id: Uuid,
+    image_url: &str,
+    image_variants: &serde_json::Value,
+    timestamp_ms: i64,
+) -> sqlx::Result<u64> {
+    let res = sqlx::query(
+        r#"UPDATE products
+           SET image_url = $1, image_variants = $2, updated_at = $3
+           WHERE uuid = $4 AND deleted_at = 0"#,
+    )
+    .bind(image_url)
+    .bind(image_variants)
+    .bind(timestamp_ms)
+    .bind(id)
+    .execute(db)
+    .await?;
+
+    Ok(res.rows_affected())
+}
@@ -0,0 +1,472 @@
+use rust_decimal::Decimal;
+use sqlx::{FromRow, Pool, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::dto::purchase_orders::PurchaseOrderListRequest;
+use crate::models::purchase_orders::{
+    PurchaseOrder, PurchaseOrderItemWithIngredient, PurchaseOrderStatus, PurchaseOrderWithItems,
+};
+use crate::repository::ingredient_stocks;
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+// Hasil transisi status PO
+#[derive(Debug)]
+pub enum PurchaseOrderTransition {
+    Done,
+    NotFound,
+    InvalidStatus(String),
+}
+
+// Satu baris kandidat reorder per bahan, lengkap dengan stok, pesanan berjalan dan supplier terpilih
+#[derive(Debug, Clone, FromRow)]
+pub struct ReorderCandidateRow {
+    pub ingredient_catalog_uuid: Uuid,
+    pub ingredient_name: Option<String>,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+    pub minimum_stock: Decimal,
+    pub on_hand_qty: Decimal,
+    pub on_order_qty: Decimal,
+    pub current_cost: Option<Decimal>,
+    pub supplier_uuid: Option<Uuid>,
+    pub supplier_name: Option<String>,
+    pub lead_time_days: Option<i32>,
+    pub min_order_qty: Option<Decimal>,
+    pub pack_size: Option<Decimal>,
+    pub last_price: Option<Decimal>,
+}
+
+// Total forecast dalam horizon per versi resep (dipecah ke bahan lewat RecipeGraph)
+#[derive(Debug, Clone, FromRow)]
+pub struct ForecastRecipeUsageRow {
+    pub recipe_sets_uuid: Uuid,
+    pub forecast_qty_total: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPurchaseOrderItem {
+    pub ingredient_catalog_uuid: Uuid,
+    pub quantity: Decimal,
+    pub unit_price: Option<Decimal>,
+    pub unit_of_measure_code: Option<String>,
+    pub unit_of_measure_name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPurchaseOrder {
+    pub supplier_uuid: Uuid,
+    pub store_uuid: Option<Uuid>,
+    pub expected_at: Option<i64>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub items: Vec<NewPurchaseOrderItem>,
+}
+
+// ID: `store_uuid` membatasi PO berjalan (on_order) ke store pemanggil
+// EN: `store_uuid` limits open POs (on_order) to the caller's store
+pub async fn fetch_reorder_candidates(
+    db: &Pool<Postgres>,
+    store_uuid: Option<Uuid>,
+) -> Result<Vec<ReorderCandidateRow>, sqlx::Error> {
+    sqlx::query_as::<_, ReorderCandidateRow>(
+        r#"
+        SELECT
+            ic.uuid AS ingredient_catalog_uuid,
+            ic.name AS ingredient_name,
+            uom.code AS unit_of_measure_code,
+            uom.name AS unit_of_measure_name,
+            COALESCE(ic.minimum_stock, 0) AS minimum_stock,
+            COALESCE(st.total_quantity, 0) AS on_hand_qty,
+            COALESCE(open_po.on_order_qty, 0) AS on_order_qty,
+            st.current_cost,
+            sup.supplier_uuid,
+            sup.supplier_name,
+            sup.lead_time_days,
+            sup.min_order_qty,
+            sup.pack_size,
+            sup.last_price
+        FROM ingredient_catalog ic
+        LEFT JOIN units_of_measure uom ON ic.unit_of_measure_uuid = uom.uuid
+        LEFT JOIN LATERAL (
+            SELECT s.total_quantity, s.current_cost
+            FROM ingredient_stocks s
+            JOIN ingredient_stock_moves m ON s.ingredient_stock_moves_uuid = m.uuid
+            WHERE m.ingredient_catalog_uuid = ic.uuid
+              AND s.deleted_at = 0
+            ORDER BY s.updated_at DESC
+            LIMIT 1
+        ) st ON TRUE
+        LEFT JOIN LATERAL (
+            SELECT SUM(poi.quantity) AS on_order_qty
+            FROM purchase_order_items poi
+            JOIN purchase_orders po ON poi.purchase_order_uuid = po.uuid
+            WHERE poi.ingredient_catalog_uuid = ic.uuid
+              AND poi.deleted_at = 0
+              AND po.deleted_at = 0
+              AND po.status IN ('DRAFT', 'SUBMITTED')
+              AND po.store_uuid IS NOT DISTINCT FROM $1
+        ) open_po ON TRUE
+        LEFT JOIN LATERAL (
+            SELECT isup.supplier_uuid,
+                   su.name AS supplier_name,
+                   COALESCE(isup.lead_time_days, su.lead_time_days) AS lead_time_days,
+                   isup.min_order_qty,
+                   isup.pack_size,
+                   isup.last_price
+            FROM ingredient_suppliers isup
+            JOIN suppliers su ON su.uuid = isup.supplier_uuid AND su.deleted_at = 0
+            WHERE isup.ingredient_catalog_uuid = ic.uuid
+              AND isup.deleted_at = 0
+            ORDER BY isup.is_preferred DESC,
+                     COALESCE(isup.lead_time_days, su.lead_time_days) ASC,
+                     isup.created_at ASC
+            LIMIT 1
+        ) sup ON TRUE
+        WHERE (ic.deleted_at IS NULL OR ic.deleted_at = 0)
+        ORDER BY ic.name ASC
+        "#,
+    )
+    .bind(store_uuid)
+    .fetch_all(db)
+    .await
+}
+
+// ID: Ambil forecast terbaru per (produk, tanggal) di rentang [from, to), dijumlah per versi resep
+//     yang berlaku pada tanggal forecast. Pemanggil memecahnya ke bahan lewat RecipeGraph.
+// EN: Take the latest forecast per (product, day) in [from, to), summed per recipe version
+//     effective on the forecast day. The caller explodes it into ingredients via RecipeGraph.
+pub async fn fetch_forecast_recipe_usage(
+    db: &Pool<Postgres>,
+    from_ts: i64,
+    to_ts: i64,
+) -> Result<Vec<ForecastRecipeUsageRow>, sqlx::Error> {
+    sqlx::query_as::<_, ForecastRecipeUsageRow>(
+        r#"
+        WITH latest AS (
+            SELECT DISTINCT ON (product_uuid, date_ts) product_uuid, date_ts, forecast_qty
+            FROM forecast_daily
+            WHERE (deleted_at IS NULL OR deleted_at = 0)
+              AND date_ts >= $1
+              AND date_ts < $2
+            ORDER BY product_uuid, date_ts, created_at DESC
+        ),
+        per_recipe AS (
+            SELECT resolve_product_recipe_set(l.product_uuid, l.date_ts) AS recipe_sets_uuid,
+                   l.forecast_qty
+            FROM latest l
+            JOIN products p ON p.uuid = l.product_uuid AND p.deleted_at = 0
+        )
+        SELECT recipe_sets_uuid, SUM(forecast_qty) AS forecast_qty_total
+        FROM per_recipe
+        WHERE recipe_sets_uuid IS NOT NULL
+        GROUP BY recipe_sets_uuid
+        "#,
+    )
+    .bind(from_ts)
+    .bind(to_ts)
+    .fetch_all(db)
+    .await
+}
+
+// Prediksi bahan per store (kebutuhan restock untuk hari berikutnya)
+pub async fn fetch_prediction_ingredient_usage(
+    db: &Pool<Postgres>,
+    store_uuid: Uuid,
+) -> Result<Vec<(Uuid, Decimal)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, Decimal)>(
+        r#"
+        SELECT ingredient_catalog_uuid, recommended_restock_qty
+        FROM store_ingredient_predictions
+        WHERE store_uuid = $1
+          AND (deleted_at IS NULL OR deleted_at = 0)
+        "#,
+    )
+    .bind(store_uuid)
+    .fetch_all(db)
+    .await
+}
+
+fn generate_po_no(uuid: Uuid, timestamp_ms: i64) -> String {
+    let date = chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .unwrap_or_default()
+        .format("%Y%m%d");
+    let suffix: String = uuid.simple().to_string().chars().rev().take(6).collect();
+    format!("PO-{}-{}", date, suffix.to_uppercase())
+}
+
+// Membuat satu atau beberapa draft PO sekaligus dalam satu transaksi
+pub async fn create_purchase_orders(
+    db: &Pool<Postgres>,
+    drafts: &[NewPurchaseOrder],
+    timestamp_ms: i64,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let mut created = Vec::with_capacity(drafts.len());
+
+    for draft in drafts {
+        let po_uuid = Uuid::new_v4();
+        let total_estimated: Decimal = draft
+            .items
+            .iter()
+            .map(|item| (item.quantity * item.unit_price.unwrap_or(Decimal::ZERO)).round_dp(2))
+            .sum();
+
+        sqlx::query(
+            r#"
+            INSERT INTO purchase_orders (
+                uuid, po_no, supplier_uuid, store_uuid, status, expected_at, notes,
+                total_estimated, created_by, created_at, updated_at, deleted_at
+            )
+            VALUES ($1, $2, $3, $4, 'DRAFT', $5, $6, $7, $8, $9, $9, 0)
+            "#,
+        )
+        .bind(po_uuid)
+        .bind(generate_po_no(po_uuid, timestamp_ms))
+        .bind(draft.supplier_uuid)
+        .bind(draft.store_uuid)
+        .bind(draft.expected_at)
+        .bind(&draft.notes)
+        .bind(total_estimated)
+        .bind(draft.created_by)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        for item in &draft.items {
+            let line_total = (item.quantity * item.unit_price.unwrap_or(Decimal::ZERO)).round_dp(2);
+            sqlx::query(
+                r#"
+                INSERT INTO purchase_order_items (
+                    uuid, purchase_order_uuid, ingredient_catalog_uuid, quantity, unit_price,
+                    line_total, unit_of_measure_code, unit_of_measure_name,
+                    created_at, updated_at, deleted_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, 0)
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(po_uuid)
+            .bind(item.ingredient_catalog_uuid)
+            .bind(item.quantity)
+            .bind(item.unit_price)
+            .bind(line_total)
+            .bind(&item.unit_of_measure_code)
+            .bind(&item.unit_of_measure_name)
+            .bind(timestamp_ms)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        created.push(po_uuid);
+    }
+
+    tx.commit().await?;
+    Ok(created)
+}
+
+pub async fn get_purchase_order_with_items(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+) -> Result<Option<PurchaseOrderWithItems>, sqlx::Error> {
+    let purchase_order = sqlx::query_as::<_, PurchaseOrder>(
+        r#"
+        SELECT uuid, po_no, supplier_uuid, store_uuid, status, expected_at, notes,
+               total_estimated, created_by, created_at, updated_at, deleted_at
+        FROM purchase_orders
+        WHERE uuid = $1 AND deleted_at = 0
+        "#,
+    )
+    .bind(uuid)
+    .fetch_optional(db)
+    .await?;
+
+    let purchase_order = match purchase_order {
+        Some(po) => po,
+        None => return Ok(None),
+    };
+
+    let supplier_name: Option<String> =
+        sqlx::query_scalar("SELECT name FROM suppliers WHERE uuid = $1")
+            .bind(purchase_order.supplier_uuid)
+            .fetch_optional(db)
+            .await?;
+
+    let items = sqlx::query_as::<_, PurchaseOrderItemWithIngredient>(
+        r#"
+        SELECT poi.uuid,
+               poi.purchase_order_uuid,
+               poi.ingredient_catalog_uuid,
+               poi.quantity,
+               poi.unit_price,
+               poi.line_total,
+               poi.unit_of_measure_code,
+               poi.unit_of_measure_name,
+               poi.created_at,
+               poi.updated_at,
+               ic.name AS ingredient_name
+        FROM purchase_order_items poi
+        LEFT JOIN ingredient_catalog ic ON poi.ingredient_catalog_uuid = ic.uuid
+        WHERE poi.purchase_order_uuid = $1 AND poi.deleted_at = 0
+        ORDER BY ic.name ASC
+        "#,
+    )
+    .bind(uuid)
+    .fetch_all(db)
+    .await?;
+
+    Ok(Some(PurchaseOrderWithItems {
+        purchase_order,
+        supplier_name,
+        items,
+    }))
+}
+
+pub async fn list_purchase_orders(
+    db: &Pool<Postgres>,
+    page: usize,
+    limit: usize,
+    filters: &PurchaseOrderListRequest,
+) -> Result<(Vec<PurchaseOrder>, i64), sqlx::Error> {
+    let offset = (page.saturating_sub(1) * limit) as i64;
+
+    fn push_filters<'a>(
+        builder: &mut QueryBuilder<'a, Postgres>,
+        filters: &'a PurchaseOrderListRequest,
+    ) {
+        builder.push(" WHERE deleted_at = 0");
+        if let Some(status) = filters.status.as_deref().filter(|s| !s.trim().is_empty()) {
+            builder
+                .push(" AND status = ")
+                .push_bind(status.trim().to_uppercase());
+        }
+        if let Some(supplier_uuid) = filters.supplier_uuid {
+            builder
+                .push(" AND supplier_uuid = ")
+                .push_bind(supplier_uuid);
+        }
+        if let Some(date_from) = filters.date_from {
+            builder.push(" AND created_at >= ").push_bind(date_from);
+        }
+        if let Some(date_to) = filters.date_to {
+            builder.push(" AND created_at <= ").push_bind(date_to);
+        }
+        if let Some(search) = filters.search.as_deref().filter(|s| !s.trim().is_empty()) {
+            builder
+                .push(" AND po_no ILIKE ")
+                .push_bind(format!("%{}%", search.trim()));
+        }
+    }
+
+    let mut list_builder = QueryBuilder::<Postgres>::new(
+        "SELECT uuid, po_no, supplier_uuid, store_uuid, status, expected_at, notes, \
+         total_estimated, created_by, created_at, updated_at, deleted_at FROM purchase_orders",
+    );
+    push_filters(&mut list_builder, filters);
+    list_builder
+        .push(" ORDER BY created_at DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let purchase_orders = list_builder
+        .build_query_as::<PurchaseOrder>()
+        .fetch_all(db)
+        .await?;
+
+    let mut count_builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM purchase_orders");
+    push_filters(&mut count_builder, filters);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(db).await?;
+
+    Ok((purchase_orders, total))
+}
+
+// ID: Ubah status PO dalam satu transaksi. Baris PO dikunci (FOR UPDATE) sehingga dua RECEIVED
+// bersamaan tidak memposting stok dua kali; pergerakan PURCHASE dan status ikut rollback bila gagal.
+// EN: Change a PO status in one transaction. The PO row is locked (FOR UPDATE) so two concurrent
+// RECEIVED calls cannot post stock twice; PURCHASE moves and the status roll back together.
+pub async fn transition_purchase_order_status(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+    next_status: PurchaseOrderStatus,
+    timestamp_ms: i64,
+) -> Result<PurchaseOrderTransition, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let purchase_order = sqlx::query(
+        "SELECT status, po_no FROM purchase_orders WHERE uuid = $1 AND deleted_at = 0 FOR UPDATE",
+    )
+    .bind(uuid)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(purchase_order) = purchase_order else {
+        return Ok(PurchaseOrderTransition::NotFound);
+    };
+    let status: String = purchase_order.try_get("status")?;
+    let po_no: String = purchase_order.try_get("po_no")?;
+    let current_status =
+        PurchaseOrderStatus::from_str(&status).unwrap_or(PurchaseOrderStatus::Draft);
+    if !current_status.can_transition_to(&next_status) {
+        return Ok(PurchaseOrderTransition::InvalidStatus(status));
+    }
+
+    let mut received = Vec::new();
+    if next_status == PurchaseOrderStatus::Received {
+        // Kedaluwarsa mengikuti shelf_life_days katalog, sama seperti pergerakan stok manual
+        received = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO ingredient_stock_moves (
+                uuid, name, ingredient_catalog_uuid, quantity, price, price_updated_at,
+                effective_at, expiry_at, ref_type, ref_uuid, unit_of_measure_code,
+                unit_of_measure_name, created_at, updated_at, deleted_at
+            )
+            SELECT gen_uuid_v7(),
+                   $2,
+                   poi.ingredient_catalog_uuid,
+                   poi.quantity,
+                   poi.unit_price,
+                   CASE WHEN poi.unit_price IS NULL THEN NULL ELSE $3 END,
+                   $3,
+                   CASE WHEN ic.shelf_life_days IS NULL THEN NULL
+                        ELSE $3 + ic.shelf_life_days::BIGINT * $4 END,
+                   'PURCHASE',
+                   $1,
+                   COALESCE(poi.unit_of_measure_code, uom.code),
+                   COALESCE(poi.unit_of_measure_name, uom.name),
+                   $3,
+                   $3,
+                   0
+            FROM purchase_order_items poi
+            LEFT JOIN ingredient_catalog ic ON poi.ingredient_catalog_uuid = ic.uuid
+            LEFT JOIN units_of_measure uom ON ic.unit_of_measure_uuid = uom.uuid
+            WHERE poi.purchase_order_uuid = $1
+              AND poi.deleted_at = 0
+            RETURNING ingredient_catalog_uuid
+            "#,
+        )
+        .bind(uuid)
+        .bind(format!("PO {}", po_no))
+        .bind(timestamp_ms)
+        .bind(MILLIS_PER_DAY)
+        .fetch_all(&mut *tx)
+        .await?;
+        received.sort();
+        received.dedup();
+    }
+
+    sqlx::query("UPDATE purchase_orders SET status = $2, updated_at = $3 WHERE uuid = $1")
+        .bind(uuid)
+        .bind(next_status.as_str())
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    for ingredient_catalog_uuid in &received {
+        ingredient_stocks::recompute_stock_for_ingredient(db, *ingredient_catalog_uuid).await?;
+    }
+
+    Ok(PurchaseOrderTransition::Done)
+}
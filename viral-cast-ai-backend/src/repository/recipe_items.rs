@@ -1,11 +1,29 @@
 use rust_decimal::Decimal;
-use sqlx::{Pool, Postgres};
+use sqlx::{FromRow, PgConnection, Pool, Postgres};
 use uuid::Uuid;
 
 use crate::dto::recipe_items::{
     CreateRecipeItemSchema, GetRecipeItemSchema, ProcessedRecipeItemSchema, UpdateRecipeItemSchema,
 };
 use crate::models::recipe_items::RecipeItemsModel;
+use crate::services::recipe_graph::{RecipeComponent, RecipeGraph, RecipeLine, RecipeNode};
+
+const RECIPE_ITEM_COLUMNS: &str =
+    "uuid, recipe_sets_uuid, ingredient_stocks_uuid, component_recipe_sets_uuid, \
+     quantity, waste_percent, created_at, updated_at, deleted_at";
+
+fn map_model_to_processed(ri: RecipeItemsModel) -> ProcessedRecipeItemSchema {
+    ProcessedRecipeItemSchema {
+        uuid: ri.uuid,
+        recipe_sets_uuid: ri.recipe_sets_uuid,
+        ingredient_stocks_uuid: ri.ingredient_stocks_uuid,
+        component_recipe_sets_uuid: ri.component_recipe_sets_uuid,
+        quantity: ri.quantity,
+        waste_percent: ri.waste_percent,
+        created_at: ri.created_at.or(Some(0)),
+        updated_at: ri.updated_at.or(Some(0)),
+    }
+}
 
 // Create recipe item and return processed schema
 pub async fn create_recipe_item(
@@ -16,18 +34,17 @@ pub async fn create_recipe_item(
 ) -> Result<ProcessedRecipeItemSchema, sqlx::Error> {
     let waste_percent = body.waste_percent.unwrap_or(Decimal::ZERO);
 
-    sqlx::query!(
-        r#"INSERT INTO recipe_items (uuid, recipe_sets_uuid, ingredient_stocks_uuid, quantity, waste_percent, created_at, updated_at, deleted_at)
-           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
-        recipe_item_uuid,
-        body.recipe_sets_uuid,
-        body.ingredient_stocks_uuid,
-        body.quantity,
-        waste_percent,
-        timestamp_ms,
-        timestamp_ms,
-        0i64
+    sqlx::query(
+        r#"INSERT INTO recipe_items (uuid, recipe_sets_uuid, ingredient_stocks_uuid, component_recipe_sets_uuid, quantity, waste_percent, created_at, updated_at, deleted_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $7, 0)"#,
     )
+    .bind(recipe_item_uuid)
+    .bind(body.recipe_sets_uuid)
+    .bind(body.ingredient_stocks_uuid)
+    .bind(body.component_recipe_sets_uuid)
+    .bind(body.quantity)
+    .bind(waste_percent)
+    .bind(timestamp_ms)
     .execute(db)
     .await?;
 
@@ -35,6 +52,7 @@ pub async fn create_recipe_item(
         uuid: recipe_item_uuid,
         recipe_sets_uuid: body.recipe_sets_uuid,
         ingredient_stocks_uuid: body.ingredient_stocks_uuid,
+        component_recipe_sets_uuid: body.component_recipe_sets_uuid,
         quantity: body.quantity,
         waste_percent: Some(waste_percent),
         created_at: Some(timestamp_ms),
@@ -42,7 +60,7 @@ pub async fn create_recipe_item(
     })
 }
 
-// List recipe items with optional filtering by recipe_sets_uuid and/or ingredient_stocks_uuid
+// List recipe items with optional filtering by recipe set, ingredient stock and/or component recipe set
 pub async fn list_recipe_items(
     db: &Pool<Postgres>,
     opts: GetRecipeItemSchema,
@@ -50,94 +68,59 @@ pub async fn list_recipe_items(
     let limit = opts.limit.unwrap_or(10);
     let offset = (opts.page.unwrap_or(1) - 1) * limit;
 
-    let rows = if let (Some(recipe_sets_uuid), Some(ingredient_stocks_uuid)) =
-        (opts.recipe_sets_uuid, opts.ingredient_stocks_uuid)
-    {
-        sqlx::query_as!(
-            RecipeItemsModel,
-            r#"SELECT uuid, recipe_sets_uuid, ingredient_stocks_uuid, quantity, waste_percent, created_at, updated_at, deleted_at
-               FROM recipe_items WHERE deleted_at = 0 AND recipe_sets_uuid = $1 AND ingredient_stocks_uuid = $2
-               ORDER BY created_at DESC LIMIT $3 OFFSET $4"#,
-            recipe_sets_uuid,
-            ingredient_stocks_uuid,
-            limit as i64,
-            offset as i64
-        )
-        .fetch_all(db)
-        .await?
-    } else if let Some(recipe_sets_uuid) = opts.recipe_sets_uuid {
-        sqlx::query_as!(
-            RecipeItemsModel,
-            r#"SELECT uuid, recipe_sets_uuid, ingredient_stocks_uuid, quantity, waste_percent, created_at, updated_at, deleted_at
-               FROM recipe_items WHERE deleted_at = 0 AND recipe_sets_uuid = $1
-               ORDER BY created_at DESC LIMIT $2 OFFSET $3"#,
-            recipe_sets_uuid,
-            limit as i64,
-            offset as i64
-        )
-        .fetch_all(db)
-        .await?
-    } else if let Some(ingredient_stocks_uuid) = opts.ingredient_stocks_uuid {
-        sqlx::query_as!(
-            RecipeItemsModel,
-            r#"SELECT uuid, recipe_sets_uuid, ingredient_stocks_uuid, quantity, waste_percent, created_at, updated_at, deleted_at
-               FROM recipe_items WHERE deleted_at = 0 AND ingredient_stocks_uuid = $1
-               ORDER BY created_at DESC LIMIT $2 OFFSET $3"#,
-            ingredient_stocks_uuid,
-            limit as i64,
-            offset as i64
-        )
-        .fetch_all(db)
-        .await?
-    } else {
-        sqlx::query_as!(
-            RecipeItemsModel,
-            r#"SELECT uuid, recipe_sets_uuid, ingredient_stocks_uuid, quantity, waste_percent, created_at, updated_at, deleted_at
-               FROM recipe_items WHERE deleted_at = 0
-               ORDER BY created_at DESC LIMIT $1 OFFSET $2"#,
-            limit as i64,
-            offset as i64
-        )
+    let mut builder = sqlx::QueryBuilder::<Postgres>::new(format!(
+        "SELECT {} FROM recipe_items WHERE deleted_at = 0",
+        RECIPE_ITEM_COLUMNS
+    ));
+    if let Some(recipe_sets_uuid) = opts.recipe_sets_uuid {
+        builder
+            .push(" AND recipe_sets_uuid = ")
+            .push_bind(recipe_sets_uuid);
+    }
+    if let Some(ingredient_stocks_uuid) = opts.ingredient_stocks_uuid {
+        builder
+            .push(" AND ingredient_stocks_uuid = ")
+            .push_bind(ingredient_stocks_uuid);
+    }
+    if let Some(component_recipe_sets_uuid) = opts.component_recipe_sets_uuid {
+        builder
+            .push(" AND component_recipe_sets_uuid = ")
+            .push_bind(component_recipe_sets_uuid);
+    }
+    builder
+        .push(" ORDER BY created_at DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset as i64);
+
+    let rows = builder
+        .build_query_as::<RecipeItemsModel>()
         .fetch_all(db)
-        .await?
-    };
+        .await?;
 
-    Ok(rows
-        .into_iter()
-        .map(|ri| ProcessedRecipeItemSchema {
-            uuid: ri.uuid,
-            recipe_sets_uuid: ri.recipe_sets_uuid,
-            ingredient_stocks_uuid: ri.ingredient_stocks_uuid,
-            quantity: ri.quantity,
-            waste_percent: ri.waste_percent,
-            created_at: ri.created_at.or(Some(0)),
-            updated_at: ri.updated_at.or(Some(0)),
-        })
-        .collect())
+    Ok(rows.into_iter().map(map_model_to_processed).collect())
+}
+
+async fn get_recipe_item_model_by_uuid(
+    db: &Pool<Postgres>,
+    id: Uuid,
+) -> Result<Option<RecipeItemsModel>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM recipe_items WHERE uuid = $1 AND deleted_at = 0",
+        RECIPE_ITEM_COLUMNS
+    );
+    sqlx::query_as::<_, RecipeItemsModel>(&query)
+        .bind(id)
+        .fetch_optional(db)
+        .await
 }
 
 pub async fn get_recipe_item_by_uuid(
     db: &Pool<Postgres>,
     id: Uuid,
 ) -> Result<Option<ProcessedRecipeItemSchema>, sqlx::Error> {
-    let row = sqlx::query_as!(
-        RecipeItemsModel,
-        r#"SELECT uuid, recipe_sets_uuid, ingredient_stocks_uuid, quantity, waste_percent, created_at, updated_at, deleted_at
-           FROM recipe_items WHERE uuid = $1 AND deleted_at = 0"#,
-        id
-    )
-    .fetch_optional(db)
-    .await?;
-
-    Ok(row.map(|ri| ProcessedRecipeItemSchema {
-        uuid: ri.uuid,
-        recipe_sets_uuid: ri.recipe_sets_uuid,
-        ingredient_stocks_uuid: ri.ingredient_stocks_uuid,
-        quantity: ri.quantity,
-        waste_percent: ri.waste_percent,
-        created_at: ri.created_at.or(Some(0)),
-        updated_at: ri.updated_at.or(Some(0)),
-    }))
+    let row = get_recipe_item_model_by_uuid(db, id).await?;
+    Ok(row.map(map_model_to_processed))
 }
 
 // Update recipe item and return processed schema
@@ -148,14 +131,7 @@ pub async fn update_recipe_item(
     timestamp_ms: i64,
 ) -> Result<Option<ProcessedRecipeItemSchema>, sqlx::Error> {
     // Fetch current
-    let current = sqlx::query_as!(
-        RecipeItemsModel,
-        r#"SELECT uuid, recipe_sets_uuid, ingredient_stocks_uuid, quantity, waste_percent, created_at, updated_at, deleted_at
-           FROM recipe_items WHERE uuid = $1 AND deleted_at = 0"#,
-        id
-    )
-    .fetch_optional(db)
-    .await?;
+    let current = get_recipe_item_model_by_uuid(db, id).await?;
 
     let Some(existing) = current else {
         return Ok(None);
@@ -164,13 +140,13 @@ pub async fn update_recipe_item(
     let new_quantity = body.quantity.unwrap_or(existing.quantity);
     let new_waste_percent = body.waste_percent.or(existing.waste_percent);
 
-    let res = sqlx::query!(
+    let res = sqlx::query(
         r#"UPDATE recipe_items SET quantity = $1, waste_percent = $2, updated_at = $3 WHERE uuid = $4 AND deleted_at = 0"#,
-        new_quantity,
-        new_waste_percent,
-        timestamp_ms,
-        id
     )
+    .bind(new_quantity)
+    .bind(new_waste_percent)
+    .bind(timestamp_ms)
+    .bind(id)
     .execute(db)
     .await?;
 
@@ -179,13 +155,10 @@ pub async fn update_recipe_item(
     }
 
     Ok(Some(ProcessedRecipeItemSchema {
-        uuid: id,
-        recipe_sets_uuid: existing.recipe_sets_uuid,
-        ingredient_stocks_uuid: existing.ingredient_stocks_uuid,
         quantity: new_quantity,
         waste_percent: new_waste_percent,
-        created_at: existing.created_at.or(Some(0)),
         updated_at: Some(timestamp_ms),
+        ..map_model_to_processed(existing)
     }))
 }
 
@@ -195,13 +168,98 @@ pub async fn soft_delete_recipe_item(
     id: Uuid,
     timestamp_ms: i64,
 ) -> Result<bool, sqlx::Error> {
-    let res = sqlx::query!(
+    let res = sqlx::query(
         r#"UPDATE recipe_items SET deleted_at = $1 WHERE uuid = $2 AND deleted_at = 0"#,
-        timestamp_ms,
-        id
     )
+    .bind(timestamp_ms)
+    .bind(id)
     .execute(db)
     .await?;
 
     Ok(res.rows_affected() > 0)
 }
+
+#[derive(Debug, FromRow)]
+struct RecipeGraphSetRow {
+    uuid: Uuid,
+    yield_quantity: Option<Decimal>,
+    output_ingredient_catalog_uuid: Option<Uuid>,
+}
+
+#[derive(Debug, FromRow)]
+struct RecipeGraphItemRow {
+    recipe_sets_uuid: Uuid,
+    ingredient_catalog_uuid: Option<Uuid>,
+    component_recipe_sets_uuid: Option<Uuid>,
+    quantity: Decimal,
+    waste_percent: Option<Decimal>,
+}
+
+// ID: Muat seluruh graf resep aktif (bahan diresolusikan ke ingredient_catalog_uuid)
+// EN: Load the whole active recipe graph (ingredients resolved to ingredient_catalog_uuid)
+pub async fn load_recipe_graph(db: &Pool<Postgres>) -> Result<RecipeGraph, sqlx::Error> {
+    let mut conn = db.acquire().await?;
+    load_recipe_graph_in(&mut conn).await
+}
+
+// Sama seperti load_recipe_graph, tetapi di koneksi/transaksi pemanggil
+pub async fn load_recipe_graph_in(conn: &mut PgConnection) -> Result<RecipeGraph, sqlx::Error> {
+    let sets = sqlx::query_as::<_, RecipeGraphSetRow>(
+        r#"
+        SELECT uuid, yield_quantity, output_ingredient_catalog_uuid
+        FROM recipe_sets
+        WHERE deleted_at IS NULL OR deleted_at = 0
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let items = sqlx::query_as::<_, RecipeGraphItemRow>(
+        r#"
+        SELECT ri.recipe_sets_uuid,
+               sm.ingredient_catalog_uuid,
+               ri.component_recipe_sets_uuid,
+               ri.quantity,
+               ri.waste_percent
+        FROM recipe_items ri
+        LEFT JOIN ingredient_stocks s ON ri.ingredient_stocks_uuid = s.uuid
+        LEFT JOIN ingredient_stock_moves sm ON s.ingredient_stock_moves_uuid = sm.uuid
+        WHERE (ri.deleted_at IS NULL OR ri.deleted_at = 0)
+        ORDER BY ri.created_at ASC
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut graph = RecipeGraph::default();
+    for set in sets {
+        graph.nodes.insert(
+            set.uuid,
+            RecipeNode {
+                yield_quantity: set.yield_quantity,
+                output_ingredient_catalog_uuid: set.output_ingredient_catalog_uuid,
+                lines: Vec::new(),
+            },
+        );
+    }
+
+    for item in items {
+        let component = match (
+            item.component_recipe_sets_uuid,
+            item.ingredient_catalog_uuid,
+        ) {
+            (Some(recipe), _) => RecipeComponent::Recipe(recipe),
+            (None, Some(ingredient)) => RecipeComponent::Ingredient(ingredient),
+            (None, None) => continue,
+        };
+        if let Some(node) = graph.nodes.get_mut(&item.recipe_sets_uuid) {
+            node.lines.push(RecipeLine {
+                component,
+                quantity: item.quantity,
+                waste_percent: item.waste_percent,
+            });
+        }
+    }
+
+    Ok(graph)
+}
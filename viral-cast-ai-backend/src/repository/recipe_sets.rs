@@ -3,14 +3,41 @@ use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 
 use crate::dto::recipe_sets::{
-    CreateRecipeSetSchema, GetRecipeSetSchema, ProcessedRecipeSetSchema, UpdateRecipeSetSchema,
+    CreateRecipeSetSchema, CreateRecipeVersionSchema, GetRecipeSetSchema, ProcessedRecipeSetSchema,
+    UpdateRecipeSetSchema,
 };
 use crate::models::recipe_sets::RecipeSetsModel;
 
+const RECIPE_SET_COLUMNS: &str =
+    "uuid, name, yield_quantity, effective_from, effective_to, is_active, \
+     output_ingredient_catalog_uuid, product_uuid, version_no, previous_version_uuid, \
+     created_at, updated_at, deleted_at";
+
+// Next version number for a product (1 when the product has no versions yet)
+const NEXT_VERSION_NO_SQL: &str = "SELECT COALESCE(MAX(version_no), 0) + 1 FROM recipe_sets \
+     WHERE product_uuid = $1 AND deleted_at = 0";
+
 fn resolve_yield_quantity(value: Option<Decimal>) -> Decimal {
     value.unwrap_or_else(|| Decimal::from(1))
 }
 
+fn map_model_to_processed(r: RecipeSetsModel) -> ProcessedRecipeSetSchema {
+    ProcessedRecipeSetSchema {
+        uuid: r.uuid,
+        name: r.name,
+        yield_quantity: resolve_yield_quantity(r.yield_quantity),
+        effective_from: r.effective_from,
+        effective_to: r.effective_to,
+        is_active: r.is_active,
+        output_ingredient_catalog_uuid: r.output_ingredient_catalog_uuid,
+        product_uuid: r.product_uuid,
+        version_no: r.version_no,
+        previous_version_uuid: r.previous_version_uuid,
+        created_at: r.created_at.or(Some(0)),
+        updated_at: r.updated_at.or(Some(0)),
+    }
+}
+
 fn sanitize_sort_column(sort_by: Option<&str>) -> &'static str {
     match sort_by.map(|s| s.trim().to_lowercase()).as_deref() {
         Some("name") => "name",
@@ -37,19 +64,30 @@ pub async fn create_recipe_set(
     let yield_quantity = body.yield_quantity.unwrap_or(Decimal::from(1));
     let is_active = body.is_active.unwrap_or(true);
 
-    sqlx::query!(
-        r#"INSERT INTO recipe_sets (uuid, name, yield_quantity, effective_from, effective_to, is_active, created_at, updated_at, deleted_at)
-           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
-        recipe_set_uuid,
-        body.name,
-        yield_quantity,
-        body.effective_from,
-        body.effective_to,
-        is_active,
-        timestamp_ms,
-        timestamp_ms,
-        0i64
+    let version_no: i32 = match body.product_uuid {
+        Some(product_uuid) => {
+            sqlx::query_scalar(NEXT_VERSION_NO_SQL)
+                .bind(product_uuid)
+                .fetch_one(db)
+                .await?
+        }
+        None => 1,
+    };
+
+    sqlx::query(
+        r#"INSERT INTO recipe_sets (uuid, name, yield_quantity, effective_from, effective_to, is_active, output_ingredient_catalog_uuid, product_uuid, version_no, created_at, updated_at, deleted_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10, 0)"#,
     )
+    .bind(recipe_set_uuid)
+    .bind(&body.name)
+    .bind(yield_quantity)
+    .bind(body.effective_from)
+    .bind(body.effective_to)
+    .bind(is_active)
+    .bind(body.output_ingredient_catalog_uuid)
+    .bind(body.product_uuid)
+    .bind(version_no)
+    .bind(timestamp_ms)
     .execute(db)
     .await?;
 
@@ -60,6 +98,10 @@ pub async fn create_recipe_set(
         effective_from: body.effective_from,
         effective_to: body.effective_to,
         is_active,
+        output_ingredient_catalog_uuid: body.output_ingredient_catalog_uuid,
+        product_uuid: body.product_uuid,
+        version_no,
+        previous_version_uuid: None,
         created_at: Some(timestamp_ms),
         updated_at: Some(timestamp_ms),
     })
@@ -89,11 +131,10 @@ pub async fn list_recipe_sets(
     let search_effective = name_term.or(search_term);
     let search_pattern = search_effective.map(|s| format!("%{}%", s));
 
-    let mut data_builder = sqlx::QueryBuilder::<Postgres>::new(
-        r#"SELECT uuid, name, yield_quantity, effective_from, effective_to, is_active, created_at, updated_at, deleted_at
-           FROM recipe_sets
-           WHERE deleted_at = 0"#,
-    );
+    let mut data_builder = sqlx::QueryBuilder::<Postgres>::new(format!(
+        "SELECT {} FROM recipe_sets WHERE deleted_at = 0",
+        RECIPE_SET_COLUMNS
+    ));
     let mut count_builder = sqlx::QueryBuilder::<Postgres>::new(
         r#"SELECT COUNT(*) FROM recipe_sets WHERE deleted_at = 0"#,
     );
@@ -132,19 +173,7 @@ pub async fn list_recipe_sets(
 
     let total: i64 = count_builder.build_query_scalar().fetch_one(db).await?;
 
-    let recipe_sets = rows
-        .into_iter()
-        .map(|row| ProcessedRecipeSetSchema {
-            uuid: row.uuid,
-            name: row.name,
-            yield_quantity: resolve_yield_quantity(row.yield_quantity),
-            effective_from: row.effective_from,
-            effective_to: row.effective_to,
-            is_active: row.is_active,
-            created_at: row.created_at.or(Some(0)),
-            updated_at: row.updated_at.or(Some(0)),
-        })
-        .collect();
+    let recipe_sets = rows.into_iter().map(map_model_to_processed).collect();
 
     Ok((recipe_sets, total))
 }
@@ -153,24 +182,9 @@ pub async fn get_recipe_set_by_uuid(
     db: &Pool<Postgres>,
     id: Uuid,
 ) -> Result<Option<ProcessedRecipeSetSchema>, sqlx::Error> {
-    let row = sqlx::query!(
-        r#"SELECT uuid, name, yield_quantity, effective_from, effective_to, is_active, created_at, updated_at, deleted_at
-           FROM recipe_sets WHERE uuid = $1 AND deleted_at = 0"#,
-        id
-    )
-    .fetch_optional(db)
-    .await?;
+    let row = get_recipe_set_model_by_uuid(db, id).await?;
 
-    Ok(row.map(|r| ProcessedRecipeSetSchema {
-        uuid: r.uuid,
-        name: r.name,
-        yield_quantity: resolve_yield_quantity(r.yield_quantity),
-        effective_from: r.effective_from,
-        effective_to: r.effective_to,
-        is_active: r.is_active,
-        created_at: r.created_at.or(Some(0)),
-        updated_at: r.updated_at.or(Some(0)),
-    }))
+    Ok(row.map(map_model_to_processed))
 }
 
 // Helper to fetch raw model for advanced validations if needed
@@ -178,14 +192,14 @@ pub async fn get_recipe_set_model_by_uuid(
     db: &Pool<Postgres>,
     id: Uuid,
 ) -> Result<Option<RecipeSetsModel>, sqlx::Error> {
-    let row = sqlx::query_as!(
-        RecipeSetsModel,
-        r#"SELECT uuid, name, yield_quantity, effective_from, effective_to, is_active, created_at, updated_at, deleted_at
-           FROM recipe_sets WHERE uuid = $1 AND deleted_at = 0"#,
-        id
-    )
-    .fetch_optional(db)
-    .await?;
+    let query = format!(
+        "SELECT {} FROM recipe_sets WHERE uuid = $1 AND deleted_at = 0",
+        RECIPE_SET_COLUMNS
+    );
+    let row = sqlx::query_as::<_, RecipeSetsModel>(&query)
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
 
     Ok(row)
 }
@@ -198,14 +212,7 @@ pub async fn update_recipe_set(
     timestamp_ms: i64,
 ) -> Result<Option<ProcessedRecipeSetSchema>, sqlx::Error> {
     // Fetch current
-    let current = sqlx::query_as!(
-        RecipeSetsModel,
-        r#"SELECT uuid, name, yield_quantity, effective_from, effective_to, is_active, created_at, updated_at, deleted_at
-           FROM recipe_sets WHERE uuid = $1 AND deleted_at = 0"#,
-        id
-    )
-    .fetch_optional(db)
-    .await?;
+    let current = get_recipe_set_model_by_uuid(db, id).await?;
 
     let Some(existing) = current else {
         return Ok(None);
@@ -217,18 +224,22 @@ pub async fn update_recipe_set(
     let new_effective_from = body.effective_from.or(existing.effective_from);
     let new_effective_to = body.effective_to.or(existing.effective_to);
     let new_is_active = body.is_active.unwrap_or(existing.is_active);
+    let new_output_ingredient = body
+        .output_ingredient_catalog_uuid
+        .or(existing.output_ingredient_catalog_uuid);
 
-    let res = sqlx::query!(
-        r#"UPDATE recipe_sets SET name = $1, yield_quantity = $2, effective_from = $3, effective_to = $4, is_active = $5, updated_at = $6
-           WHERE uuid = $7 AND deleted_at = 0"#,
-        new_name,
-        new_yield_quantity,
-        new_effective_from,
-        new_effective_to,
-        new_is_active,
-        timestamp_ms,
-        id
+    let res = sqlx::query(
+        r#"UPDATE recipe_sets SET name = $1, yield_quantity = $2, effective_from = $3, effective_to = $4, is_active = $5, output_ingredient_catalog_uuid = $6, updated_at = $7
+           WHERE uuid = $8 AND deleted_at = 0"#,
     )
+    .bind(&new_name)
+    .bind(new_yield_quantity)
+    .bind(new_effective_from)
+    .bind(new_effective_to)
+    .bind(new_is_active)
+    .bind(new_output_ingredient)
+    .bind(timestamp_ms)
+    .bind(id)
     .execute(db)
     .await?;
 
@@ -237,14 +248,14 @@ pub async fn update_recipe_set(
     }
 
     Ok(Some(ProcessedRecipeSetSchema {
-        uuid: id,
         name: new_name,
         yield_quantity: new_yield_quantity,
         effective_from: new_effective_from,
         effective_to: new_effective_to,
         is_active: new_is_active,
-        created_at: existing.created_at.or(Some(0)),
+        output_ingredient_catalog_uuid: new_output_ingredient,
         updated_at: Some(timestamp_ms),
+        ..map_model_to_processed(existing)
     }))
 }
 
@@ -264,3 +275,225 @@ pub async fn soft_delete_recipe_set(
 
     Ok(res.rows_affected() > 0)
 }
+
+// Attach an unowned recipe set to a product as its next version (used when a product
+// starts pointing at a recipe set)
+pub async fn link_recipe_set_to_product(
+    db: &Pool<Postgres>,
+    recipe_sets_uuid: Uuid,
+    product_uuid: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE recipe_sets
+           SET product_uuid = $2,
+               version_no = (SELECT COALESCE(MAX(version_no), 0) + 1 FROM recipe_sets WHERE product_uuid = $2 AND deleted_at = 0)
+           WHERE uuid = $1 AND product_uuid IS NULL AND deleted_at = 0"#,
+    )
+    .bind(recipe_sets_uuid)
+    .bind(product_uuid)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+// Latest effective_from among a product's versions (new versions must start after it)
+pub async fn get_latest_effective_from(
+    db: &Pool<Postgres>,
+    product_uuid: Uuid,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"SELECT MAX(effective_from) FROM recipe_sets WHERE product_uuid = $1 AND deleted_at = 0"#,
+    )
+    .bind(product_uuid)
+    .fetch_one(db)
+    .await
+}
+
+// ID: Buat versi baru dari recipe set dalam satu transaksi: salin set + item, tutup versi
+// sebelumnya pada effective_from, dan arahkan products.recipe_sets_uuid ke versi terbaru.
+// EN: Create a new version of a recipe set in one transaction: copy the set and its items,
+// close earlier versions at effective_from and point products.recipe_sets_uuid at the newest.
+pub async fn create_recipe_version(
+    db: &Pool<Postgres>,
+    source: &RecipeSetsModel,
+    product_uuid: Uuid,
+    new_uuid: Uuid,
+    body: CreateRecipeVersionSchema,
+    effective_from: i64,
+    timestamp_ms: i64,
+) -> Result<ProcessedRecipeSetSchema, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let version_no: i32 = sqlx::query_scalar(NEXT_VERSION_NO_SQL)
+        .bind(product_uuid)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    // Output bahan setengah jadi unik per resep aktif, jadi dipindahkan ke versi baru
+    if source.output_ingredient_catalog_uuid.is_some() {
+        sqlx::query(
+            r#"UPDATE recipe_sets SET output_ingredient_catalog_uuid = NULL, updated_at = $2 WHERE uuid = $1"#,
+        )
+        .bind(source.uuid)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(
+        r#"UPDATE recipe_sets SET effective_to = $2, updated_at = $3
+           WHERE product_uuid = $1 AND deleted_at = 0
+             AND (effective_to IS NULL OR effective_to > $2)"#,
+    )
+    .bind(product_uuid)
+    .bind(effective_from)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    let name = body.name.unwrap_or_else(|| source.name.clone());
+    let yield_quantity = body
+        .yield_quantity
+        .unwrap_or_else(|| resolve_yield_quantity(source.yield_quantity));
+
+    sqlx::query(
+        r#"INSERT INTO recipe_sets (uuid, name, yield_quantity, effective_from, effective_to, is_active, output_ingredient_catalog_uuid, product_uuid, version_no, previous_version_uuid, created_at, updated_at, deleted_at)
+           VALUES ($1, $2, $3, $4, NULL, TRUE, $5, $6, $7, $8, $9, $9, 0)"#,
+    )
+    .bind(new_uuid)
+    .bind(&name)
+    .bind(yield_quantity)
+    .bind(effective_from)
+    .bind(source.output_ingredient_catalog_uuid)
+    .bind(product_uuid)
+    .bind(version_no)
+    .bind(source.uuid)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"INSERT INTO recipe_items (uuid, recipe_sets_uuid, ingredient_stocks_uuid, component_recipe_sets_uuid, quantity, waste_percent, created_at, updated_at, deleted_at)
+           SELECT gen_uuid_v7(), $2, ingredient_stocks_uuid, component_recipe_sets_uuid, quantity, waste_percent, $3, $3, 0
+           FROM recipe_items
+           WHERE recipe_sets_uuid = $1 AND deleted_at = 0"#,
+    )
+    .bind(source.uuid)
+    .bind(new_uuid)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"UPDATE products SET recipe_sets_uuid = $2, updated_at = $3 WHERE uuid = $1 AND deleted_at = 0"#,
+    )
+    .bind(product_uuid)
+    .bind(new_uuid)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ProcessedRecipeSetSchema {
+        uuid: new_uuid,
+        name,
+        yield_quantity,
+        effective_from: Some(effective_from),
+        effective_to: None,
+        is_active: true,
+        output_ingredient_catalog_uuid: source.output_ingredient_catalog_uuid,
+        product_uuid: Some(product_uuid),
+        version_no,
+        previous_version_uuid: Some(source.uuid),
+        created_at: Some(timestamp_ms),
+        updated_at: Some(timestamp_ms),
+    })
+}
+
+// All versions of a product's recipe, oldest first
+pub async fn list_recipe_versions(
+    db: &Pool<Postgres>,
+    product_uuid: Uuid,
+) -> Result<Vec<ProcessedRecipeSetSchema>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM recipe_sets WHERE product_uuid = $1 AND deleted_at = 0 ORDER BY version_no ASC",
+        RECIPE_SET_COLUMNS
+    );
+    let rows = sqlx::query_as::<_, RecipeSetsModel>(&query)
+        .bind(product_uuid)
+        .fetch_all(db)
+        .await?;
+
+    Ok(rows.into_iter().map(map_model_to_processed).collect())
+}
+
+// ID: Versi resep produk yang berlaku pada `at_ms` (lihat fungsi SQL resolve_product_recipe_set)
+// EN: Product recipe version effective at `at_ms` (see the resolve_product_recipe_set SQL function)
+pub async fn resolve_product_recipe_set(
+    db: &Pool<Postgres>,
+    product_uuid: Uuid,
+    at_ms: i64,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar("SELECT resolve_product_recipe_set($1, $2)")
+        .bind(product_uuid)
+        .bind(at_ms)
+        .fetch_one(db)
+        .await
+}
+
+// ID: Versi resep item pesanan: yang dipatok di order_items, fallback versi yang berlaku saat
+//     pesanan dibuat (sama seperti posting move SALE)
+// EN: Recipe version of an order item: the one pinned on order_items, falling back to the version
+//     effective when the order was placed (same as SALE move posting)
+pub async fn resolve_order_item_recipe_set(
+    db: &Pool<Postgres>,
+    order_item_uuid: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let recipe_sets_uuid: Option<Option<Uuid>> = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(
+                   oi.recipe_sets_uuid,
+                   resolve_product_recipe_set(oi.product_uuid, o.created_at)
+               )
+        FROM order_items oi
+        JOIN orders o ON o.uuid = oi.order_uuid
+        WHERE oi.uuid = $1
+        "#,
+    )
+    .bind(order_item_uuid)
+    .fetch_optional(db)
+    .await?;
+    Ok(recipe_sets_uuid.flatten())
+}
+
+// ID: Apakah versi resep ini (langsung atau sebagai sub-resep) sudah dipatok di item pesanan.
+//     Versi yang sudah dipakai tidak boleh diubah; buat versi baru.
+// EN: Whether this recipe version is pinned on any order item, directly or as a sub-recipe.
+//     Versions in use must not be edited; create a new version instead.
+pub async fn is_recipe_set_used_by_orders(
+    db: &Pool<Postgres>,
+    recipe_sets_uuid: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        WITH RECURSIVE dependents(uuid) AS (
+            SELECT $1::uuid
+            UNION
+            SELECT ri.recipe_sets_uuid
+            FROM recipe_items ri
+            JOIN dependents d ON ri.component_recipe_sets_uuid = d.uuid
+            WHERE ri.deleted_at IS NULL OR ri.deleted_at = 0
+        )
+        SELECT EXISTS (
+            SELECT 1 FROM order_items oi
+            JOIN dependents d ON oi.recipe_sets_uuid = d.uuid
+            WHERE oi.deleted_at = 0
+        )
+        "#,
+    )
+    .bind(recipe_sets_uuid)
+    .fetch_one(db)
+    .await
+}
@@ -0,0 +1,138 @@
+use rust_decimal::Decimal;
+use sqlx::{Pool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::repository::ingredient_stock_moves::{self, LedgerStockMove};
+use crate::repository::ingredient_stocks;
+use crate::repository::recipe_items;
+
+// ID: Posting konsumsi bahan (move SALE) untuk pesanan yang baru lunas, memakai versi resep
+//     yang dipatok di order_items (fallback: versi yang berlaku saat pesanan dibuat).
+//     Idempoten per pesanan. Mengembalikan bahan yang tersentuh; pemanggil wajib memanggil
+//     recompute_sale_stock setelah commit.
+// EN: Post ingredient consumption (SALE moves) for a freshly paid order, using the recipe
+//     version pinned on order_items (falling back to the version effective when the order was
+//     placed). Idempotent per order. Returns the touched ingredients; the caller must call
+//     recompute_sale_stock after commit.
+pub async fn post_sale_consumption(
+    tx: &mut Transaction<'_, Postgres>,
+    order_uuid: Uuid,
+    timestamp_ms: i64,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let already_posted: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM ingredient_stock_moves
+            WHERE ref_type = 'SALE' AND ref_uuid = $1 AND deleted_at = 0
+        )
+        "#,
+    )
+    .bind(order_uuid)
+    .fetch_one(&mut **tx)
+    .await?;
+    if already_posted {
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT o.order_no,
+               oi.qty,
+               COALESCE(
+                   oi.recipe_sets_uuid,
+                   resolve_product_recipe_set(oi.product_uuid, o.created_at)
+               ) AS recipe_sets_uuid
+        FROM order_items oi
+        JOIN orders o ON o.uuid = oi.order_uuid
+        WHERE oi.order_uuid = $1 AND oi.deleted_at = 0
+        ORDER BY oi.created_at ASC
+        "#,
+    )
+    .bind(order_uuid)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let recipes: Vec<(String, Uuid, Decimal)> = rows
+        .iter()
+        .filter_map(|row| {
+            let recipe_sets_uuid: Option<Uuid> = row.get("recipe_sets_uuid");
+            recipe_sets_uuid.map(|recipe| (row.get("order_no"), recipe, row.get("qty")))
+        })
+        .collect();
+    if recipes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let graph = recipe_items::load_recipe_graph_in(tx).await?;
+
+    let mut lines: Vec<(Uuid, Decimal)> = Vec::new();
+    for (_, recipe_sets_uuid, qty) in &recipes {
+        // Resep rusak tidak boleh menggagalkan pembayaran; catat dan lewati
+        let exploded = match graph.explode(*recipe_sets_uuid, *qty, true) {
+            Ok(exploded) => exploded,
+            Err(e) => {
+                tracing::warn!(
+                    "Skipping sale consumption for order {} recipe {}: {}",
+                    order_uuid,
+                    recipe_sets_uuid,
+                    e
+                );
+                continue;
+            }
+        };
+        for (ingredient_catalog_uuid, quantity) in exploded {
+            match lines
+                .iter_mut()
+                .find(|(uuid, _)| *uuid == ingredient_catalog_uuid)
+            {
+                Some((_, total)) => *total += quantity,
+                None => lines.push((ingredient_catalog_uuid, quantity)),
+            }
+        }
+    }
+
+    let move_name = format!("Penjualan {}", recipes[0].0);
+    let mut touched = Vec::new();
+    for (ingredient_catalog_uuid, quantity) in lines {
+        let quantity = quantity.round_dp(4);
+        if quantity <= Decimal::ZERO {
+            continue;
+        }
+        let snapshot =
+            ingredient_stocks::fetch_stock_cost_snapshot(tx, ingredient_catalog_uuid).await?;
+        ingredient_stock_moves::insert_ledger_move(
+            tx,
+            &LedgerStockMove {
+                name: Some(move_name.clone()),
+                ingredient_catalog_uuid,
+                quantity: -quantity,
+                price: snapshot.avg_cost,
+                ref_type: "SALE",
+                ref_uuid: order_uuid,
+                unit_of_measure_code: snapshot.unit_of_measure_code,
+                unit_of_measure_name: snapshot.unit_of_measure_name,
+            },
+            timestamp_ms,
+        )
+        .await?;
+        touched.push(ingredient_catalog_uuid);
+    }
+
+    Ok(touched)
+}
+
+// ID: Hitung ulang stok setelah commit. Pesanan sudah lunas, jadi kegagalan hanya dicatat.
+// EN: Recompute stock after commit. The order is already paid, so failures are only logged.
+pub async fn recompute_sale_stock(db: &Pool<Postgres>, ingredient_catalog_uuids: &[Uuid]) {
+    for ingredient_catalog_uuid in ingredient_catalog_uuids {
+        if let Err(e) =
+            ingredient_stocks::recompute_stock_for_ingredient(db, *ingredient_catalog_uuid).await
+        {
+            tracing::error!(
+                "Failed to recompute stock for ingredient {} after sale: {}",
+                ingredient_catalog_uuid,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,481 @@
+use rust_decimal::Decimal;
+use sqlx::{Pool, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::dto::stock_opname::{StockOpnameCountInput, StockOpnameListRequest};
+use crate::models::stock_opname::{StockOpnameItem, StockOpnameSession, StockOpnameSummary};
+use crate::repository::ingredient_stock_moves::{self, LedgerStockMove};
+use crate::repository::ingredient_stocks;
+
+const SESSION_COLUMNS: &str = "uuid, session_no, store_uuid, status, notes, snapshot_at, \
+     created_by, approved_by, approved_at, cancelled_by, cancelled_at, created_at, updated_at, deleted_at";
+
+// Hasil transisi status sesi (approve / cancel)
+#[derive(Debug)]
+pub enum SessionTransition {
+    Done(Vec<Uuid>),
+    NotFound,
+    InvalidStatus(String),
+}
+
+fn generate_session_no(uuid: Uuid, timestamp_ms: i64) -> String {
+    let date = chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .unwrap_or_default()
+        .format("%Y%m%d");
+    let suffix: String = uuid.simple().to_string().chars().rev().take(6).collect();
+    format!("SO-{}-{}", date, suffix.to_uppercase())
+}
+
+// ID: Buka sesi baru dan snapshot qty sistem + avg_cost dari ingredient_stocks
+// EN: Open a new session and snapshot system qty + avg_cost from ingredient_stocks
+pub async fn create_session(
+    db: &Pool<Postgres>,
+    store_uuid: Option<Uuid>,
+    created_by: Uuid,
+    notes: Option<String>,
+    ingredient_catalog_uuids: Option<Vec<Uuid>>,
+    timestamp_ms: i64,
+) -> Result<Uuid, sqlx::Error> {
+    let session_uuid = Uuid::new_v4();
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO stock_opname_sessions (
+            uuid, session_no, store_uuid, status, notes, snapshot_at, created_by,
+            created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, 'OPEN', $4, $5, $6, $5, $5, 0)
+        "#,
+    )
+    .bind(session_uuid)
+    .bind(generate_session_no(session_uuid, timestamp_ms))
+    .bind(store_uuid)
+    .bind(notes)
+    .bind(timestamp_ms)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await?;
+
+    let filter = ingredient_catalog_uuids.filter(|ids| !ids.is_empty());
+    insert_snapshot_items(&mut tx, session_uuid, filter.as_deref(), timestamp_ms).await?;
+
+    tx.commit().await?;
+    Ok(session_uuid)
+}
+
+async fn insert_snapshot_items(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    session_uuid: Uuid,
+    ingredient_catalog_uuids: Option<&[Uuid]>,
+    timestamp_ms: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO stock_opname_items (
+            uuid, session_uuid, ingredient_catalog_uuid, ingredient_name,
+            unit_of_measure_code, unit_of_measure_name, system_qty, avg_cost,
+            created_at, updated_at, deleted_at
+        )
+        SELECT gen_uuid_v7(),
+               $1,
+               ic.uuid,
+               ic.name,
+               COALESCE(st.unit_of_measure_code, uom.code),
+               COALESCE(st.unit_of_measure_name, uom.name),
+               COALESCE(st.total_quantity, 0),
+               st.avg_cost,
+               $3,
+               $3,
+               0
+        FROM ingredient_catalog ic
+        LEFT JOIN units_of_measure uom ON ic.unit_of_measure_uuid = uom.uuid
+        LEFT JOIN LATERAL (
+            SELECT s.total_quantity, s.avg_cost, s.unit_of_measure_code, s.unit_of_measure_name
+            FROM ingredient_stocks s
+            JOIN ingredient_stock_moves m ON s.ingredient_stock_moves_uuid = m.uuid
+            WHERE m.ingredient_catalog_uuid = ic.uuid
+              AND s.deleted_at = 0
+            ORDER BY s.updated_at DESC
+            LIMIT 1
+        ) st ON TRUE
+        WHERE (ic.deleted_at IS NULL OR ic.deleted_at = 0)
+          AND ($2::UUID[] IS NULL OR ic.uuid = ANY($2))
+        ON CONFLICT (session_uuid, ingredient_catalog_uuid) DO NOTHING
+        "#,
+    )
+    .bind(session_uuid)
+    .bind(ingredient_catalog_uuids)
+    .bind(timestamp_ms)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn get_session(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+) -> Result<Option<StockOpnameSession>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM stock_opname_sessions WHERE uuid = $1 AND deleted_at = 0",
+        SESSION_COLUMNS
+    );
+    sqlx::query_as::<_, StockOpnameSession>(&query)
+        .bind(uuid)
+        .fetch_optional(db)
+        .await
+}
+
+pub async fn get_session_items(
+    db: &Pool<Postgres>,
+    session_uuid: Uuid,
+) -> Result<Vec<StockOpnameItem>, sqlx::Error> {
+    sqlx::query_as::<_, StockOpnameItem>(
+        r#"
+        SELECT uuid,
+               session_uuid,
+               ingredient_catalog_uuid,
+               ingredient_name,
+               unit_of_measure_code,
+               unit_of_measure_name,
+               system_qty,
+               avg_cost,
+               counted_qty,
+               counted_qty - system_qty AS variance_qty,
+               ROUND((counted_qty - system_qty) * COALESCE(avg_cost, 0), 2) AS variance_value,
+               counted_by,
+               counted_at,
+               notes,
+               adjustment_move_uuid,
+               created_at,
+               updated_at
+        FROM stock_opname_items
+        WHERE session_uuid = $1
+          AND deleted_at = 0
+        ORDER BY ingredient_name ASC
+        "#,
+    )
+    .bind(session_uuid)
+    .fetch_all(db)
+    .await
+}
+
+// Ringkasan selisih per sesi (qty dan nilai berdasarkan avg_cost snapshot)
+pub fn summarize_items(items: &[StockOpnameItem]) -> StockOpnameSummary {
+    let mut summary = StockOpnameSummary {
+        total_items: items.len() as i64,
+        ..Default::default()
+    };
+
+    for item in items {
+        let (Some(variance_qty), Some(variance_value)) = (item.variance_qty, item.variance_value)
+        else {
+            continue;
+        };
+        summary.counted_items += 1;
+        if variance_qty.is_zero() {
+            continue;
+        }
+        summary.items_with_variance += 1;
+        if variance_qty > Decimal::ZERO {
+            summary.variance_qty_gain += variance_qty;
+            summary.variance_value_gain += variance_value;
+        } else {
+            summary.variance_qty_loss += variance_qty.abs();
+            summary.variance_value_loss += variance_value.abs();
+        }
+    }
+
+    summary.uncounted_items = summary.total_items - summary.counted_items;
+    summary.variance_value_net = summary.variance_value_gain - summary.variance_value_loss;
+    summary
+}
+
+pub async fn list_sessions(
+    db: &Pool<Postgres>,
+    page: usize,
+    limit: usize,
+    filters: &StockOpnameListRequest,
+) -> Result<(Vec<StockOpnameSession>, i64), sqlx::Error> {
+    let offset = (page.saturating_sub(1) * limit) as i64;
+
+    fn push_filters<'a>(
+        builder: &mut QueryBuilder<'a, Postgres>,
+        filters: &'a StockOpnameListRequest,
+    ) {
+        builder.push(" WHERE deleted_at = 0");
+        if let Some(status) = filters.status.as_deref().filter(|s| !s.trim().is_empty()) {
+            builder
+                .push(" AND status = ")
+                .push_bind(status.trim().to_uppercase());
+        }
+        if let Some(date_from) = filters.date_from {
+            builder.push(" AND created_at >= ").push_bind(date_from);
+        }
+        if let Some(date_to) = filters.date_to {
+            builder.push(" AND created_at <= ").push_bind(date_to);
+        }
+    }
+
+    let mut list_builder = QueryBuilder::<Postgres>::new(format!(
+        "SELECT {} FROM stock_opname_sessions",
+        SESSION_COLUMNS
+    ));
+    push_filters(&mut list_builder, filters);
+    list_builder
+        .push(" ORDER BY created_at DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let sessions = list_builder
+        .build_query_as::<StockOpnameSession>()
+        .fetch_all(db)
+        .await?;
+
+    let mut count_builder =
+        QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM stock_opname_sessions");
+    push_filters(&mut count_builder, filters);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(db).await?;
+
+    Ok((sessions, total))
+}
+
+// ID: Simpan hasil hitung. Bahan yang belum ada di sesi ditambahkan dengan snapshot qty saat ini.
+// Mengembalikan UUID bahan yang tidak ditemukan di katalog.
+// EN: Store counts. Ingredients missing from the session are added with a current snapshot.
+// Returns the ingredient UUIDs that do not exist in the catalog.
+pub async fn record_counts(
+    db: &Pool<Postgres>,
+    session_uuid: Uuid,
+    counts: &[StockOpnameCountInput],
+    counted_by: Uuid,
+    timestamp_ms: i64,
+) -> Result<SessionTransition, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    // FOR SHARE: beberapa perangkat boleh input bersamaan, tetapi approve menunggu
+    let status: Option<String> = sqlx::query_scalar(
+        "SELECT status FROM stock_opname_sessions WHERE uuid = $1 AND deleted_at = 0 FOR SHARE",
+    )
+    .bind(session_uuid)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    match status.as_deref() {
+        None => return Ok(SessionTransition::NotFound),
+        Some("OPEN") => {}
+        Some(other) => return Ok(SessionTransition::InvalidStatus(other.to_string())),
+    }
+
+    let mut unknown = Vec::new();
+    for count in counts {
+        let missing = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM stock_opname_items WHERE session_uuid = $1 AND ingredient_catalog_uuid = $2",
+        )
+        .bind(session_uuid)
+        .bind(count.ingredient_catalog_uuid)
+        .fetch_one(&mut *tx)
+        .await?
+            == 0;
+
+        if missing {
+            let inserted = insert_snapshot_items(
+                &mut tx,
+                session_uuid,
+                Some(std::slice::from_ref(&count.ingredient_catalog_uuid)),
+                timestamp_ms,
+            )
+            .await?;
+            if inserted == 0 {
+                unknown.push(count.ingredient_catalog_uuid);
+                continue;
+            }
+        }
+
+        let add_mode = count
+            .mode
+            .as_deref()
+            .map(|m| m.eq_ignore_ascii_case("ADD"))
+            .unwrap_or(false);
+
+        // ID: system_qty/avg_cost di-snapshot ulang saat dihitung, sehingga pembelian/waste di antara
+        //     pembukaan sesi dan penghitungan tidak dihitung ganda atau hilang saat approve.
+        // EN: system_qty/avg_cost are re-snapshotted at count time, so purchases/waste between
+        //     opening the session and counting are neither double-counted nor lost on approval.
+        sqlx::query(
+            r#"
+            UPDATE stock_opname_items soi
+            SET counted_qty = CASE WHEN $3 THEN COALESCE(soi.counted_qty, 0) + $4 ELSE $4 END,
+                system_qty = COALESCE(st.total_quantity, 0),
+                avg_cost = COALESCE(st.avg_cost, soi.avg_cost),
+                notes = COALESCE($5, soi.notes),
+                counted_by = $6,
+                counted_at = $7,
+                updated_at = $7
+            FROM (SELECT 1) AS one
+            LEFT JOIN LATERAL (
+                SELECT s.total_quantity, s.avg_cost
+                FROM ingredient_stocks s
+                JOIN ingredient_stock_moves m ON s.ingredient_stock_moves_uuid = m.uuid
+                WHERE m.ingredient_catalog_uuid = $2
+                  AND s.deleted_at = 0
+                ORDER BY s.updated_at DESC
+                LIMIT 1
+            ) st ON TRUE
+            WHERE soi.session_uuid = $1
+              AND soi.ingredient_catalog_uuid = $2
+            "#,
+        )
+        .bind(session_uuid)
+        .bind(count.ingredient_catalog_uuid)
+        .bind(add_mode)
+        .bind(count.counted_qty)
+        .bind(&count.notes)
+        .bind(counted_by)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("UPDATE stock_opname_sessions SET updated_at = $2 WHERE uuid = $1")
+        .bind(session_uuid)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(SessionTransition::Done(unknown))
+}
+
+// ID: Approve sesi: posting selisih (counted - system_qty saat dihitung) sebagai pergerakan ADJUSTMENT
+// (ref_uuid = sesi), lalu hitung ulang stok. Item yang belum dihitung dilewati.
+// Mengembalikan UUID bahan yang disesuaikan.
+// EN: Approve a session: post variances (counted - system_qty at count time) as ADJUSTMENT moves
+// (ref_uuid = session), then recompute stock. Uncounted items are skipped.
+// Returns the adjusted ingredient UUIDs.
+pub async fn approve_session(
+    db: &Pool<Postgres>,
+    session_uuid: Uuid,
+    approved_by: Uuid,
+    timestamp_ms: i64,
+) -> Result<SessionTransition, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let session = sqlx::query(
+        "SELECT status, session_no FROM stock_opname_sessions WHERE uuid = $1 AND deleted_at = 0 FOR UPDATE",
+    )
+    .bind(session_uuid)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(session) = session else {
+        return Ok(SessionTransition::NotFound);
+    };
+    let status: String = session.try_get("status")?;
+    let session_no: String = session.try_get("session_no")?;
+    if status != "OPEN" {
+        return Ok(SessionTransition::InvalidStatus(status));
+    }
+
+    let variances = sqlx::query(
+        r#"
+        SELECT uuid, ingredient_catalog_uuid, counted_qty - system_qty AS variance_qty,
+               unit_of_measure_code, unit_of_measure_name
+        FROM stock_opname_items
+        WHERE session_uuid = $1
+          AND deleted_at = 0
+          AND counted_qty IS NOT NULL
+          AND counted_qty <> system_qty
+          AND adjustment_move_uuid IS NULL
+        "#,
+    )
+    .bind(session_uuid)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut adjusted = Vec::with_capacity(variances.len());
+    for row in variances {
+        let item_uuid: Uuid = row.try_get("uuid")?;
+        let ingredient_catalog_uuid: Uuid = row.try_get("ingredient_catalog_uuid")?;
+        let variance_qty: Decimal = row.try_get("variance_qty")?;
+        // price NULL: nilai penyesuaian mengikuti avg_cost saat recompute
+        let move_uuid = ingredient_stock_moves::insert_ledger_move(
+            &mut tx,
+            &LedgerStockMove {
+                name: Some(format!("Stock opname {}", session_no)),
+                ingredient_catalog_uuid,
+                quantity: variance_qty,
+                price: None,
+                ref_type: "ADJUSTMENT",
+                ref_uuid: session_uuid,
+                unit_of_measure_code: row.try_get("unit_of_measure_code")?,
+                unit_of_measure_name: row.try_get("unit_of_measure_name")?,
+            },
+            timestamp_ms,
+        )
+        .await?;
+
+        sqlx::query(
+            "UPDATE stock_opname_items SET adjustment_move_uuid = $2, updated_at = $3 WHERE uuid = $1",
+        )
+        .bind(item_uuid)
+        .bind(move_uuid)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        adjusted.push(ingredient_catalog_uuid);
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE stock_opname_sessions
+        SET status = 'APPROVED', approved_by = $2, approved_at = $3, updated_at = $3
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(session_uuid)
+    .bind(approved_by)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    for ingredient_catalog_uuid in &adjusted {
+        ingredient_stocks::recompute_stock_for_ingredient(db, *ingredient_catalog_uuid).await?;
+    }
+
+    Ok(SessionTransition::Done(adjusted))
+}
+
+pub async fn cancel_session(
+    db: &Pool<Postgres>,
+    session_uuid: Uuid,
+    cancelled_by: Uuid,
+    timestamp_ms: i64,
+) -> Result<SessionTransition, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE stock_opname_sessions
+        SET status = 'CANCELLED', cancelled_by = $2, cancelled_at = $3, updated_at = $3
+        WHERE uuid = $1 AND deleted_at = 0 AND status = 'OPEN'
+        "#,
+    )
+    .bind(session_uuid)
+    .bind(cancelled_by)
+    .bind(timestamp_ms)
+    .execute(db)
+    .await?;
+
+    if result.rows_affected() > 0 {
+        return Ok(SessionTransition::Done(Vec::new()));
+    }
+
+    match get_session(db, session_uuid).await? {
+        Some(session) => Ok(SessionTransition::InvalidStatus(session.status)),
+        None => Ok(SessionTransition::NotFound),
+    }
+}
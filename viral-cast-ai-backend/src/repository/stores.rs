@@ -37,17 +37,18 @@ pub async fn update_user_profile_store(
 pub async fn create_store(
     db: &Pool<Postgres>,
     body: CreateStoreSchema,
+    brand_variants: Option<serde_json::Value>,
 ) -> Result<ProcessedStore, sqlx::Error> {
     let name = body.name.unwrap_or_default();
 
     let query = r#"
         INSERT INTO stores (
-            name, brand_url, province_code, regency_code, district_code, village_code,
-            rt, rw, postal_code, telp, whatsapp, instagram
+            name, brand_url, brand_variants, province_code, regency_code, district_code,
+            village_code, rt, rw, postal_code, telp, whatsapp, instagram
         ) VALUES (
-            $1, $2, $3, $4, $5, $6,
-            $7, $8, $9, $10, $11, $12
-        ) RETURNING uuid, name, brand_url, province_code, regency_code, district_code, village_code,
+            $1, $2, $13, $3, $4, $5,
+            $6, $7, $8, $9, $10, $11, $12
+        ) RETURNING uuid, name, brand_url, brand_variants, province_code, regency_code, district_code, village_code,
                   rt, rw, postal_code, telp, whatsapp, instagram,
                   opening_time, middle_closing_time, closing_time,
                   created_at, updated_at
@@ -66,6 +67,7 @@ pub async fn create_store(
         .bind(body.telp)
         .bind(body.whatsapp)
         .bind(body.instagram)
+        .bind(brand_variants)
         .fetch_one(db)
         .await?;
 
@@ -73,6 +75,7 @@ pub async fn create_store(
         uuid: store.uuid,
         name: store.name,
         brand_url: store.brand_url,
+        brand_variants: store.brand_variants,
         province_code: store.province_code,
         regency_code: store.regency_code,
         district_code: store.district_code,
@@ -96,7 +99,7 @@ pub async fn get_store_by_uuid(
     store_uuid: Uuid,
 ) -> Result<Option<ProcessedStore>, sqlx::Error> {
     let query = r#"
-        SELECT uuid, name, brand_url, province_code, regency_code, district_code, village_code,
+        SELECT uuid, name, brand_url, brand_variants, province_code, regency_code, district_code, village_code,
                rt, rw, postal_code, telp, whatsapp, instagram,
                opening_time, middle_closing_time, closing_time,
                created_at, updated_at
@@ -112,6 +115,7 @@ pub async fn get_store_by_uuid(
         uuid: store.uuid,
         name: store.name,
         brand_url: store.brand_url,
+        brand_variants: store.brand_variants,
         province_code: store.province_code,
         regency_code: store.regency_code,
         district_code: store.district_code,
@@ -139,6 +143,7 @@ pub async fn get_store_by_user_uuid(
             s.uuid,
             s.name,
             s.brand_url,
+            s.brand_variants,
             s.province_code,
             s.regency_code,
             s.district_code,
@@ -173,6 +178,7 @@ pub async fn get_store_by_user_uuid(
         uuid: store.uuid,
         name: store.name,
         brand_url: store.brand_url,
+        brand_variants: store.brand_variants,
         province_code: store.province_code,
         regency_code: store.regency_code,
         district_code: store.district_code,
@@ -195,10 +201,11 @@ pub async fn update_store(
     db: &Pool<Postgres>,
     store_uuid: Uuid,
     body: UpdateStoreSchema,
+    brand_variants: Option<serde_json::Value>,
 ) -> Result<ProcessedStore, sqlx::Error> {
     // Fetch current store
     let current = sqlx::query_as::<_, GetStoreSchema>(
-        r#"SELECT uuid, name, brand_url, province_code, regency_code, district_code, village_code,
+        r#"SELECT uuid, name, brand_url, brand_variants, province_code, regency_code, district_code, village_code,
             rt, rw, postal_code, telp, whatsapp, instagram,
             opening_time, middle_closing_time, closing_time,
             created_at, updated_at
@@ -209,6 +216,13 @@ pub async fn update_store(
     .await?;
 
     let name = body.name.unwrap_or(current.name);
+    // Varian hanya dipertahankan selama brand_url tidak berubah
+    let brand_url = body.brand_url.or(current.brand_url.clone());
+    let brand_variants = if brand_url == current.brand_url {
+        current.brand_variants
+    } else {
+        brand_variants
+    };
     // Gunakan Unix timestamp (ms) langsung; jika tidak dikirim, pertahankan nilai lama
     let opening_time: Option<i64> = match &body.opening_time {
         Some(v) if *v > 0 => Some(*v),
@@ -242,9 +256,10 @@ pub async fn update_store(
             opening_time = $13,
             middle_closing_time = $14,
             closing_time = $15,
+            brand_variants = $17,
             updated_at = (EXTRACT(EPOCH FROM NOW()) * 1000)::bigint
         WHERE uuid = $16 AND deleted_at = 0
-        RETURNING uuid, name, brand_url, province_code, regency_code, district_code, village_code,
+        RETURNING uuid, name, brand_url, brand_variants, province_code, regency_code, district_code, village_code,
                   rt, rw, postal_code, telp, whatsapp, instagram,
                   opening_time, middle_closing_time, closing_time,
                   created_at, updated_at
@@ -252,7 +267,7 @@ pub async fn update_store(
 
     let updated = sqlx::query_as::<_, GetStoreSchema>(query)
         .bind(name)
-        .bind(body.brand_url.or(current.brand_url))
+        .bind(brand_url)
         .bind(body.province_code.or(current.province_code))
         .bind(body.regency_code.or(current.regency_code))
         .bind(body.district_code.or(current.district_code))
@@ -267,6 +282,7 @@ pub async fn update_store(
         .bind(middle_closing_time)
         .bind(closing_time)
         .bind(store_uuid)
+        .bind(brand_variants)
         .fetch_one(db)
         .await?;
 
@@ -274,6 +290,7 @@ pub async fn update_store(
         uuid: updated.uuid,
         name: updated.name,
         brand_url: updated.brand_url,
+        brand_variants: updated.brand_variants,
         province_code: updated.province_code,
         regency_code: updated.regency_code,
         district_code: updated.district_code,
@@ -291,3 +308,27 @@ pub async fn update_store(
         updated_at: updated.updated_at,
     })
 }
+
+/// ID: Simpan URL brand beserta varian hasil pipeline gambar.
+/// EN: Store the brand URL together with its image pipeline variants.
+pub async fn update_store_brand_image(
+    db: &Pool<Postgres>,
+    store_uuid: Uuid,
+    brand_url: &str,
+    brand_variants: &serde_json::Value,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"UPDATE stores
+           SET brand_url = $1,
+               brand_variants = $2,
+               updated_at = (EXTRACT(EPOCH FROM NOW()) * 1000)::bigint
+           WHERE uuid = $3 AND deleted_at = 0"#,
+    )
+    .bind(brand_url)
+    .bind(brand_variants)
+    .bind(store_uuid)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
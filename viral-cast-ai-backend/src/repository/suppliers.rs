@@ -0,0 +1,301 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::dto::suppliers::{
+    CreateSupplierSchema, GetSuppliersSchema, UpdateSupplierSchema, UpsertIngredientSupplierSchema,
+};
+use crate::models::suppliers::{IngredientSupplierWithIngredient, SupplierModel};
+
+const SUPPLIER_COLUMNS: &str = "uuid, name, contact_name, telp, email, address, lead_time_days, \
+     notes, created_at, updated_at, deleted_at";
+
+// Membuat supplier baru
+pub async fn create_supplier(
+    db: &Pool<Postgres>,
+    body: CreateSupplierSchema,
+    timestamp_ms: i64,
+) -> Result<SupplierModel, sqlx::Error> {
+    let query = format!(
+        r#"
+        INSERT INTO suppliers (
+            uuid, name, contact_name, telp, email, address, lead_time_days, notes,
+            created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9, 0)
+        RETURNING {}
+        "#,
+        SUPPLIER_COLUMNS
+    );
+
+    sqlx::query_as::<_, SupplierModel>(&query)
+        .bind(Uuid::new_v4())
+        .bind(body.name.trim())
+        .bind(body.contact_name)
+        .bind(body.telp)
+        .bind(body.email)
+        .bind(body.address)
+        .bind(body.lead_time_days.unwrap_or(1))
+        .bind(body.notes)
+        .bind(timestamp_ms)
+        .fetch_one(db)
+        .await
+}
+
+// Mendapatkan daftar supplier dengan paginasi dan pencarian nama
+pub async fn list_suppliers(
+    db: &Pool<Postgres>,
+    page: usize,
+    limit: usize,
+    filters: &GetSuppliersSchema,
+) -> Result<(Vec<SupplierModel>, i64), sqlx::Error> {
+    let offset = (page.saturating_sub(1) * limit) as i64;
+    let search_pattern = filters
+        .search
+        .as_ref()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("%{}%", s));
+
+    let list_query = format!(
+        r#"
+        SELECT {}
+        FROM suppliers
+        WHERE deleted_at = 0
+          AND ($1::TEXT IS NULL OR name ILIKE $1 OR contact_name ILIKE $1)
+        ORDER BY name ASC
+        LIMIT $2 OFFSET $3
+        "#,
+        SUPPLIER_COLUMNS
+    );
+
+    let suppliers = sqlx::query_as::<_, SupplierModel>(&list_query)
+        .bind(search_pattern.as_deref())
+        .bind(limit as i64)
+        .bind(offset)
+        .fetch_all(db)
+        .await?;
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM suppliers
+        WHERE deleted_at = 0
+          AND ($1::TEXT IS NULL OR name ILIKE $1 OR contact_name ILIKE $1)
+        "#,
+    )
+    .bind(search_pattern.as_deref())
+    .fetch_one(db)
+    .await?;
+
+    Ok((suppliers, total))
+}
+
+// Mendapatkan supplier berdasarkan UUID
+pub async fn get_supplier_by_uuid(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+) -> Result<Option<SupplierModel>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM suppliers WHERE uuid = $1 AND deleted_at = 0",
+        SUPPLIER_COLUMNS
+    );
+
+    sqlx::query_as::<_, SupplierModel>(&query)
+        .bind(uuid)
+        .fetch_optional(db)
+        .await
+}
+
+// Memperbarui supplier (hanya field yang dikirim)
+pub async fn update_supplier(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+    body: UpdateSupplierSchema,
+    timestamp_ms: i64,
+) -> Result<Option<SupplierModel>, sqlx::Error> {
+    let query = format!(
+        r#"
+        UPDATE suppliers
+        SET name = COALESCE($2, name),
+            contact_name = COALESCE($3, contact_name),
+            telp = COALESCE($4, telp),
+            email = COALESCE($5, email),
+            address = COALESCE($6, address),
+            lead_time_days = COALESCE($7, lead_time_days),
+            notes = COALESCE($8, notes),
+            updated_at = $9
+        WHERE uuid = $1 AND deleted_at = 0
+        RETURNING {}
+        "#,
+        SUPPLIER_COLUMNS
+    );
+
+    sqlx::query_as::<_, SupplierModel>(&query)
+        .bind(uuid)
+        .bind(body.name.map(|n| n.trim().to_string()))
+        .bind(body.contact_name)
+        .bind(body.telp)
+        .bind(body.email)
+        .bind(body.address)
+        .bind(body.lead_time_days)
+        .bind(body.notes)
+        .bind(timestamp_ms)
+        .fetch_optional(db)
+        .await
+}
+
+// Soft delete supplier beserta relasi bahannya
+pub async fn soft_delete_supplier(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+    timestamp_ms: i64,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let result = sqlx::query(
+        "UPDATE suppliers SET deleted_at = $2, updated_at = $2 WHERE uuid = $1 AND deleted_at = 0",
+    )
+    .bind(uuid)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE ingredient_suppliers SET deleted_at = $2, updated_at = $2 WHERE supplier_uuid = $1 AND deleted_at = 0",
+    )
+    .bind(uuid)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
+
+// Menghubungkan bahan ke supplier; jika sudah ada, perbarui aturan pembeliannya
+pub async fn upsert_ingredient_supplier(
+    db: &Pool<Postgres>,
+    supplier_uuid: Uuid,
+    body: UpsertIngredientSupplierSchema,
+    timestamp_ms: i64,
+) -> Result<IngredientSupplierWithIngredient, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    // Hanya satu supplier utama (preferred) per bahan
+    if body.is_preferred.unwrap_or(false) {
+        sqlx::query(
+            r#"
+            UPDATE ingredient_suppliers
+            SET is_preferred = FALSE, updated_at = $3
+            WHERE ingredient_catalog_uuid = $1
+              AND supplier_uuid <> $2
+              AND deleted_at = 0
+            "#,
+        )
+        .bind(body.ingredient_catalog_uuid)
+        .bind(supplier_uuid)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let link_uuid: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO ingredient_suppliers (
+            uuid, ingredient_catalog_uuid, supplier_uuid, lead_time_days, min_order_qty,
+            pack_size, last_price, is_preferred, created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, FALSE), $9, $9, 0)
+        ON CONFLICT (ingredient_catalog_uuid, supplier_uuid) WHERE deleted_at = 0
+        DO UPDATE SET
+            lead_time_days = COALESCE(EXCLUDED.lead_time_days, ingredient_suppliers.lead_time_days),
+            min_order_qty = COALESCE(EXCLUDED.min_order_qty, ingredient_suppliers.min_order_qty),
+            pack_size = COALESCE(EXCLUDED.pack_size, ingredient_suppliers.pack_size),
+            last_price = COALESCE(EXCLUDED.last_price, ingredient_suppliers.last_price),
+            is_preferred = COALESCE($8, ingredient_suppliers.is_preferred),
+            updated_at = EXCLUDED.updated_at
+        RETURNING uuid
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(body.ingredient_catalog_uuid)
+    .bind(supplier_uuid)
+    .bind(body.lead_time_days)
+    .bind(body.min_order_qty)
+    .bind(body.pack_size)
+    .bind(body.last_price)
+    .bind(body.is_preferred)
+    .bind(timestamp_ms)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let link = sqlx::query_as::<_, IngredientSupplierWithIngredient>(&format!(
+        "{} WHERE isup.uuid = $1",
+        INGREDIENT_SUPPLIER_SELECT
+    ))
+    .bind(link_uuid)
+    .fetch_one(db)
+    .await?;
+
+    Ok(link)
+}
+
+const INGREDIENT_SUPPLIER_SELECT: &str = r#"
+    SELECT isup.uuid,
+           isup.ingredient_catalog_uuid,
+           isup.supplier_uuid,
+           isup.lead_time_days,
+           isup.min_order_qty,
+           isup.pack_size,
+           isup.last_price,
+           isup.is_preferred,
+           isup.created_at,
+           isup.updated_at,
+           ic.name AS ingredient_name,
+           uom.code AS unit_of_measure_code,
+           uom.name AS unit_of_measure_name
+    FROM ingredient_suppliers isup
+    LEFT JOIN ingredient_catalog ic ON isup.ingredient_catalog_uuid = ic.uuid
+    LEFT JOIN units_of_measure uom ON ic.unit_of_measure_uuid = uom.uuid
+"#;
+
+// Daftar bahan yang disediakan oleh supplier
+pub async fn list_supplier_ingredients(
+    db: &Pool<Postgres>,
+    supplier_uuid: Uuid,
+) -> Result<Vec<IngredientSupplierWithIngredient>, sqlx::Error> {
+    sqlx::query_as::<_, IngredientSupplierWithIngredient>(&format!(
+        "{} WHERE isup.supplier_uuid = $1 AND isup.deleted_at = 0 ORDER BY ic.name ASC",
+        INGREDIENT_SUPPLIER_SELECT
+    ))
+    .bind(supplier_uuid)
+    .fetch_all(db)
+    .await
+}
+
+// Melepas bahan dari supplier (soft delete)
+pub async fn remove_ingredient_supplier(
+    db: &Pool<Postgres>,
+    supplier_uuid: Uuid,
+    ingredient_catalog_uuid: Uuid,
+    timestamp_ms: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE ingredient_suppliers
+        SET deleted_at = $3, updated_at = $3
+        WHERE supplier_uuid = $1
+          AND ingredient_catalog_uuid = $2
+          AND deleted_at = 0
+        "#,
+    )
+    .bind(supplier_uuid)
+    .bind(ingredient_catalog_uuid)
+    .bind(timestamp_ms)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,149 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::models::two_factor::{RecoveryCode, TotpSecret};
+
+pub async fn find_totp_secret(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+) -> sqlx::Result<Option<TotpSecret>> {
+    sqlx::query_as::<_, TotpSecret>(
+        r#"
+        SELECT user_uuid, secret, confirmed_at, last_used_step, created_at, updated_at
+        FROM auth_totp_secrets
+        WHERE user_uuid = $1
+        "#,
+    )
+    .bind(user_uuid)
+    .fetch_optional(db)
+    .await
+}
+
+// ID: Simpan secret baru yang belum dikonfirmasi; secret yang sudah aktif tidak ditimpa.
+//     Mengembalikan false bila 2FA pengguna sudah aktif.
+// EN: Store a new unconfirmed secret; an active secret is never overwritten.
+//     Returns false when the user's 2FA is already active.
+pub async fn upsert_pending_secret(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    secret: &str,
+) -> sqlx::Result<bool> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query(
+        r#"
+        INSERT INTO auth_totp_secrets (user_uuid, secret, created_at, updated_at)
+        VALUES ($1, $2, $3, $3)
+        ON CONFLICT (user_uuid) DO UPDATE
+        SET secret = EXCLUDED.secret, last_used_step = NULL, updated_at = EXCLUDED.updated_at
+        WHERE auth_totp_secrets.confirmed_at IS NULL
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(secret)
+    .bind(now)
+    .execute(db)
+    .await
+    .map(|result| result.rows_affected() > 0)
+}
+
+// ID: Catat langkah TOTP yang dipakai (sekaligus mengaktifkan 2FA bila `confirm`). False bila
+//     langkah itu sudah dipakai request lain (replay).
+// EN: Record the TOTP step that was used (and activate 2FA when `confirm`). False when that step
+//     was already used by another request (replay).
+pub async fn mark_step_used(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    step: i64,
+    confirm: bool,
+) -> sqlx::Result<bool> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query(
+        r#"
+        UPDATE auth_totp_secrets
+        SET last_used_step = $2,
+            confirmed_at = CASE WHEN $3 THEN COALESCE(confirmed_at, $4) ELSE confirmed_at END,
+            updated_at = $4
+        WHERE user_uuid = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(user_uuid)
+    .bind(step)
+    .bind(confirm)
+    .bind(now)
+    .execute(db)
+    .await
+    .map(|result| result.rows_affected() > 0)
+}
+
+// Matikan 2FA: secret dan semua kode pemulihan dihapus
+pub async fn delete_two_factor(db: &Pool<Postgres>, user_uuid: Uuid) -> sqlx::Result<bool> {
+    let mut tx = db.begin().await?;
+    sqlx::query("DELETE FROM auth_recovery_codes WHERE user_uuid = $1")
+        .bind(user_uuid)
+        .execute(&mut *tx)
+        .await?;
+    let deleted = sqlx::query("DELETE FROM auth_totp_secrets WHERE user_uuid = $1")
+        .bind(user_uuid)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+    Ok(deleted > 0)
+}
+
+// Ganti seluruh kode pemulihan; kode lama (terpakai maupun belum) tidak berlaku lagi
+pub async fn replace_recovery_codes(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+    code_hashes: &[String],
+) -> sqlx::Result<()> {
+    let mut tx = db.begin().await?;
+    sqlx::query("DELETE FROM auth_recovery_codes WHERE user_uuid = $1")
+        .bind(user_uuid)
+        .execute(&mut *tx)
+        .await?;
+    for code_hash in code_hashes {
+        sqlx::query("INSERT INTO auth_recovery_codes (user_uuid, code_hash) VALUES ($1, $2)")
+            .bind(user_uuid)
+            .bind(code_hash)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+pub async fn list_unused_recovery_codes(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+) -> sqlx::Result<Vec<RecoveryCode>> {
+    sqlx::query_as::<_, RecoveryCode>(
+        "SELECT uuid, code_hash FROM auth_recovery_codes WHERE user_uuid = $1 AND used_at IS NULL",
+    )
+    .bind(user_uuid)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn count_unused_recovery_codes(
+    db: &Pool<Postgres>,
+    user_uuid: Uuid,
+) -> sqlx::Result<i64> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM auth_recovery_codes WHERE user_uuid = $1 AND used_at IS NULL",
+    )
+    .bind(user_uuid)
+    .fetch_one(db)
+    .await
+}
+
+// Pakai satu kode secara atomik; false bila kode itu baru saja dipakai request lain
+pub async fn mark_recovery_code_used(db: &Pool<Postgres>, code_uuid: Uuid) -> sqlx::Result<bool> {
+    let now = Utc::now().timestamp_millis();
+    sqlx::query("UPDATE auth_recovery_codes SET used_at = $2 WHERE uuid = $1 AND used_at IS NULL")
+        .bind(code_uuid)
+        .bind(now)
+        .execute(db)
+        .await
+        .map(|result| result.rows_affected() > 0)
+}
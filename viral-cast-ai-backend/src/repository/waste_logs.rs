@@ -0,0 +1,457 @@
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use sqlx::{Pool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::dto::waste_logs::{WasteLogListRequest, WasteReportQuery};
+use crate::models::waste_logs::{
+    WasteByReasonRow, WasteLog, WasteLogItemWithIngredient, WasteVsTheoreticalRow,
+};
+use crate::repository::ingredient_stock_moves::{self, LedgerStockMove};
+use crate::repository::ingredient_stocks;
+use crate::repository::recipe_items;
+
+const WASTE_LOG_COLUMNS: &str = "uuid, store_uuid, reason_code, product_uuid, product_qty, notes, \
+     wasted_at, total_cost, logged_by, created_at, updated_at, deleted_at";
+
+#[derive(Debug, Clone)]
+pub struct NewWasteLog {
+    pub store_uuid: Option<Uuid>,
+    pub reason_code: String,
+    pub product_uuid: Option<Uuid>,
+    pub product_qty: Option<Decimal>,
+    pub notes: Option<String>,
+    pub wasted_at: i64,
+    pub logged_by: Option<Uuid>,
+    // (ingredient_catalog_uuid, quantity) yang sudah digabung per bahan
+    pub lines: Vec<(Uuid, Decimal)>,
+}
+
+pub async fn get_product_name(
+    db: &Pool<Postgres>,
+    product_uuid: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT name FROM products WHERE uuid = $1 AND deleted_at = 0")
+        .bind(product_uuid)
+        .fetch_optional(db)
+        .await
+}
+
+// Mengembalikan UUID bahan yang tidak ada di katalog
+pub async fn find_missing_ingredients(
+    db: &Pool<Postgres>,
+    ingredient_catalog_uuids: &[Uuid],
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM UNNEST($1::UUID[]) AS id
+        WHERE NOT EXISTS (
+            SELECT 1 FROM ingredient_catalog ic
+            WHERE ic.uuid = id
+              AND (ic.deleted_at IS NULL OR ic.deleted_at = 0)
+        )
+        "#,
+    )
+    .bind(ingredient_catalog_uuids)
+    .fetch_all(db)
+    .await
+}
+
+// ID: Simpan waste log dan posting move WASTE per bahan (dinilai dengan avg_cost saat ini),
+// lalu hitung ulang stok setelah commit.
+// EN: Store a waste log and post one WASTE move per ingredient (valued at the current
+// avg_cost), then recompute stock after commit.
+pub async fn create_waste_log(
+    db: &Pool<Postgres>,
+    new_log: &NewWasteLog,
+    timestamp_ms: i64,
+) -> Result<Uuid, sqlx::Error> {
+    let log_uuid = Uuid::new_v4();
+    let mut tx = db.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO waste_logs (
+            uuid, store_uuid, reason_code, product_uuid, product_qty, notes, wasted_at,
+            total_cost, logged_by, created_at, updated_at, deleted_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 0, $8, $9, $9, 0)
+        "#,
+    )
+    .bind(log_uuid)
+    .bind(new_log.store_uuid)
+    .bind(&new_log.reason_code)
+    .bind(new_log.product_uuid)
+    .bind(new_log.product_qty)
+    .bind(&new_log.notes)
+    .bind(new_log.wasted_at)
+    .bind(new_log.logged_by)
+    .bind(timestamp_ms)
+    .execute(&mut *tx)
+    .await?;
+
+    let move_name = format!("Waste {}", new_log.reason_code);
+    let mut total_cost = Decimal::ZERO;
+
+    for (ingredient_catalog_uuid, quantity) in &new_log.lines {
+        let snapshot =
+            ingredient_stocks::fetch_stock_cost_snapshot(&mut tx, *ingredient_catalog_uuid).await?;
+        let unit_cost = snapshot.avg_cost;
+        let unit_of_measure_code = snapshot.unit_of_measure_code;
+        let unit_of_measure_name = snapshot.unit_of_measure_name;
+        let line_cost = (*quantity * unit_cost.unwrap_or(Decimal::ZERO)).round_dp(2);
+
+        let move_uuid = ingredient_stock_moves::insert_ledger_move(
+            &mut tx,
+            &LedgerStockMove {
+                name: Some(move_name.clone()),
+                ingredient_catalog_uuid: *ingredient_catalog_uuid,
+                quantity: *quantity,
+                price: unit_cost,
+                ref_type: "WASTE",
+                ref_uuid: log_uuid,
+                unit_of_measure_code: unit_of_measure_code.clone(),
+                unit_of_measure_name: unit_of_measure_name.clone(),
+            },
+            new_log.wasted_at,
+        )
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO waste_log_items (
+                uuid, waste_log_uuid, ingredient_catalog_uuid, quantity, unit_cost, total_cost,
+                unit_of_measure_code, unit_of_measure_name, stock_move_uuid,
+                created_at, updated_at, deleted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10, 0)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(log_uuid)
+        .bind(ingredient_catalog_uuid)
+        .bind(quantity)
+        .bind(unit_cost)
+        .bind(line_cost)
+        .bind(unit_of_measure_code)
+        .bind(unit_of_measure_name)
+        .bind(move_uuid)
+        .bind(timestamp_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        total_cost += line_cost;
+    }
+
+    sqlx::query("UPDATE waste_logs SET total_cost = $2 WHERE uuid = $1")
+        .bind(log_uuid)
+        .bind(total_cost)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    for (ingredient_catalog_uuid, _) in &new_log.lines {
+        ingredient_stocks::recompute_stock_for_ingredient(db, *ingredient_catalog_uuid).await?;
+    }
+
+    Ok(log_uuid)
+}
+
+pub async fn get_waste_log(
+    db: &Pool<Postgres>,
+    uuid: Uuid,
+) -> Result<Option<WasteLog>, sqlx::Error> {
+    let query = format!(
+        "SELECT {} FROM waste_logs WHERE uuid = $1 AND deleted_at = 0",
+        WASTE_LOG_COLUMNS
+    );
+    sqlx::query_as::<_, WasteLog>(&query)
+        .bind(uuid)
+        .fetch_optional(db)
+        .await
+}
+
+pub async fn get_waste_log_items(
+    db: &Pool<Postgres>,
+    waste_log_uuid: Uuid,
+) -> Result<Vec<WasteLogItemWithIngredient>, sqlx::Error> {
+    sqlx::query_as::<_, WasteLogItemWithIngredient>(
+        r#"
+        SELECT wli.uuid,
+               wli.waste_log_uuid,
+               wli.ingredient_catalog_uuid,
+               wli.quantity,
+               wli.unit_cost,
+               wli.total_cost,
+               wli.unit_of_measure_code,
+               wli.unit_of_measure_name,
+               wli.stock_move_uuid,
+               wli.created_at,
+               ic.name AS ingredient_name
+        FROM waste_log_items wli
+        LEFT JOIN ingredient_catalog ic ON ic.uuid = wli.ingredient_catalog_uuid
+        WHERE wli.waste_log_uuid = $1
+          AND wli.deleted_at = 0
+        ORDER BY ic.name ASC
+        "#,
+    )
+    .bind(waste_log_uuid)
+    .fetch_all(db)
+    .await
+}
+
+pub async fn list_waste_logs(
+    db: &Pool<Postgres>,
+    page: usize,
+    limit: usize,
+    filters: &WasteLogListRequest,
+) -> Result<(Vec<WasteLog>, i64), sqlx::Error> {
+    let offset = (page.saturating_sub(1) * limit) as i64;
+
+    fn push_filters<'a>(
+        builder: &mut QueryBuilder<'a, Postgres>,
+        filters: &'a WasteLogListRequest,
+    ) {
+        builder.push(" WHERE deleted_at = 0");
+        if let Some(reason) = filters
+            .reason_code
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+        {
+            builder
+                .push(" AND reason_code = ")
+                .push_bind(reason.trim().to_uppercase());
+        }
+        if let Some(product_uuid) = filters.product_uuid {
+            builder.push(" AND product_uuid = ").push_bind(product_uuid);
+        }
+        if let Some(ingredient_uuid) = filters.ingredient_catalog_uuid {
+            builder
+                .push(
+                    " AND uuid IN (SELECT waste_log_uuid FROM waste_log_items \
+                     WHERE deleted_at = 0 AND ingredient_catalog_uuid = ",
+                )
+                .push_bind(ingredient_uuid)
+                .push(")");
+        }
+        if let Some(date_from) = filters.date_from {
+            builder.push(" AND wasted_at >= ").push_bind(date_from);
+        }
+        if let Some(date_to) = filters.date_to {
+            builder.push(" AND wasted_at <= ").push_bind(date_to);
+        }
+    }
+
+    let mut list_builder =
+        QueryBuilder::<Postgres>::new(format!("SELECT {} FROM waste_logs", WASTE_LOG_COLUMNS));
+    push_filters(&mut list_builder, filters);
+    list_builder
+        .push(" ORDER BY wasted_at DESC LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let logs = list_builder
+        .build_query_as::<WasteLog>()
+        .fetch_all(db)
+        .await?;
+
+    let mut count_builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM waste_logs");
+    push_filters(&mut count_builder, filters);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(db).await?;
+
+    Ok((logs, total))
+}
+
+// Total waste per bulan dan alasan
+pub async fn report_by_reason(
+    db: &Pool<Postgres>,
+    timezone: &str,
+    query: &WasteReportQuery,
+) -> Result<Vec<WasteByReasonRow>, sqlx::Error> {
+    sqlx::query_as::<_, WasteByReasonRow>(
+        r#"
+        SELECT to_char(to_timestamp(wl.wasted_at / 1000.0) AT TIME ZONE $1, 'YYYY-MM') AS month,
+               wl.reason_code,
+               COUNT(*) AS logs_count,
+               COALESCE(SUM(wl.total_cost), 0) AS total_cost
+        FROM waste_logs wl
+        WHERE wl.deleted_at = 0
+          AND ($2::BIGINT IS NULL OR wl.wasted_at >= $2)
+          AND ($3::BIGINT IS NULL OR wl.wasted_at <= $3)
+          AND (
+              $4::UUID IS NULL OR EXISTS (
+                  SELECT 1 FROM waste_log_items wli
+                  WHERE wli.waste_log_uuid = wl.uuid
+                    AND wli.deleted_at = 0
+                    AND wli.ingredient_catalog_uuid = $4
+              )
+          )
+        GROUP BY 1, 2
+        ORDER BY 1 ASC, total_cost DESC
+        "#,
+    )
+    .bind(timezone)
+    .bind(query.date_from)
+    .bind(query.date_to)
+    .bind(query.ingredient_catalog_uuid)
+    .fetch_all(db)
+    .await
+}
+
+// ID: Waste aktual (waste_log_items) vs waste teoretis dari penjualan PAID. Qty terjual per
+// versi resep dipecah lewat RecipeGraph (komponen resep ikut, berhenti di bahan setengah jadi yang
+// distok seperti move SALE); waste teoretis = pemakaian dengan waste_percent - pemakaian bersih.
+// EN: Actual waste (waste_log_items) vs theoretical waste from PAID sales. Sold quantity per
+// recipe version is exploded through RecipeGraph (component recipes included, stopping at stocked
+// semi-finished items like SALE moves); theoretical waste = usage with waste_percent - net usage.
+pub async fn report_vs_theoretical(
+    db: &Pool<Postgres>,
+    timezone: &str,
+    query: &WasteReportQuery,
+) -> Result<Vec<WasteVsTheoreticalRow>, sqlx::Error> {
+    let sold: Vec<(String, Uuid, Decimal)> = sqlx::query_as(
+        r#"
+        SELECT to_char(to_timestamp(o.created_at / 1000.0) AT TIME ZONE $1, 'YYYY-MM') AS month,
+               sold.recipe_sets_uuid,
+               SUM(oi.qty) AS qty
+        FROM orders o
+        JOIN order_items oi ON oi.order_uuid = o.uuid AND oi.deleted_at = 0
+        -- Versi resep yang dipatok di item pesanan (fallback: yang berlaku saat order dibuat)
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(
+                       oi.recipe_sets_uuid,
+                       resolve_product_recipe_set(oi.product_uuid, o.created_at)
+                   ) AS recipe_sets_uuid
+        ) sold
+        WHERE o.status = 'PAID'
+          AND o.deleted_at = 0
+          AND sold.recipe_sets_uuid IS NOT NULL
+          AND ($2::BIGINT IS NULL OR o.created_at >= $2)
+          AND ($3::BIGINT IS NULL OR o.created_at <= $3)
+        GROUP BY 1, 2
+        "#,
+    )
+    .bind(timezone)
+    .bind(query.date_from)
+    .bind(query.date_to)
+    .fetch_all(db)
+    .await?;
+
+    // (bulan, bahan) -> (pemakaian bersih, waste)
+    let mut theoretical: BTreeMap<(String, Uuid), (Decimal, Decimal)> = BTreeMap::new();
+    if !sold.is_empty() {
+        let graph = recipe_items::load_recipe_graph(db).await?;
+        for (month, recipe_sets_uuid, qty) in &sold {
+            let (gross, net) = match (
+                graph.explode(*recipe_sets_uuid, *qty, true),
+                graph.explode_net(*recipe_sets_uuid, *qty, true),
+            ) {
+                (Ok(gross), Ok(net)) => (gross, net),
+                (Err(e), _) | (_, Err(e)) => {
+                    tracing::warn!(
+                        "Skipping theoretical waste for recipe {}: {}",
+                        recipe_sets_uuid,
+                        e
+                    );
+                    continue;
+                }
+            };
+            for (ingredient_catalog_uuid, net_qty) in net {
+                if query
+                    .ingredient_catalog_uuid
+                    .is_some_and(|filter| filter != ingredient_catalog_uuid)
+                {
+                    continue;
+                }
+                let gross_qty = gross
+                    .iter()
+                    .find(|(uuid, _)| *uuid == ingredient_catalog_uuid)
+                    .map(|(_, quantity)| *quantity)
+                    .unwrap_or(net_qty);
+                let entry = theoretical
+                    .entry((month.clone(), ingredient_catalog_uuid))
+                    .or_insert((Decimal::ZERO, Decimal::ZERO));
+                entry.0 += net_qty;
+                entry.1 += gross_qty - net_qty;
+            }
+        }
+    }
+    let mut months = Vec::with_capacity(theoretical.len());
+    let mut ingredients = Vec::with_capacity(theoretical.len());
+    let mut usage_qtys = Vec::with_capacity(theoretical.len());
+    let mut waste_qtys = Vec::with_capacity(theoretical.len());
+    for ((month, ingredient_catalog_uuid), (usage_qty, waste_qty)) in theoretical {
+        months.push(month);
+        ingredients.push(ingredient_catalog_uuid);
+        usage_qtys.push(usage_qty);
+        waste_qtys.push(waste_qty);
+    }
+
+    sqlx::query_as::<_, WasteVsTheoreticalRow>(
+        r#"
+        WITH actual AS (
+            SELECT to_char(to_timestamp(wl.wasted_at / 1000.0) AT TIME ZONE $1, 'YYYY-MM') AS month,
+                   wli.ingredient_catalog_uuid,
+                   SUM(wli.quantity) AS waste_qty,
+                   SUM(wli.total_cost) AS waste_cost
+            FROM waste_log_items wli
+            JOIN waste_logs wl ON wl.uuid = wli.waste_log_uuid AND wl.deleted_at = 0
+            WHERE wli.deleted_at = 0
+              AND ($2::BIGINT IS NULL OR wl.wasted_at >= $2)
+              AND ($3::BIGINT IS NULL OR wl.wasted_at <= $3)
+              AND ($4::UUID IS NULL OR wli.ingredient_catalog_uuid = $4)
+            GROUP BY 1, 2
+        ),
+        theoretical AS (
+            SELECT *
+            FROM UNNEST($5::TEXT[], $6::UUID[], $7::NUMERIC[], $8::NUMERIC[])
+                AS t(month, ingredient_catalog_uuid, usage_qty, waste_qty)
+        ),
+        merged AS (
+            SELECT COALESCE(a.month, t.month) AS month,
+                   COALESCE(a.ingredient_catalog_uuid, t.ingredient_catalog_uuid) AS ingredient_catalog_uuid,
+                   COALESCE(a.waste_qty, 0) AS actual_waste_qty,
+                   COALESCE(a.waste_cost, 0) AS actual_waste_cost,
+                   COALESCE(t.waste_qty, 0) AS theoretical_waste_qty,
+                   COALESCE(t.usage_qty, 0) AS theoretical_usage_qty
+            FROM actual a
+            FULL OUTER JOIN theoretical t
+                ON t.month = a.month
+                AND t.ingredient_catalog_uuid = a.ingredient_catalog_uuid
+        )
+        SELECT m.month,
+               m.ingredient_catalog_uuid,
+               ic.name AS ingredient_name,
+               uom.code AS unit_of_measure_code,
+               ROUND(m.actual_waste_qty, 4) AS actual_waste_qty,
+               ROUND(m.actual_waste_cost, 2) AS actual_waste_cost,
+               ROUND(m.theoretical_waste_qty, 4) AS theoretical_waste_qty,
+               ROUND(m.theoretical_usage_qty, 4) AS theoretical_usage_qty,
+               ROUND(m.actual_waste_qty - m.theoretical_waste_qty, 4) AS variance_qty,
+               CASE WHEN m.theoretical_usage_qty > 0
+                    THEN ROUND(m.actual_waste_qty / m.theoretical_usage_qty * 100, 2)
+               END AS actual_waste_pct,
+               CASE WHEN m.theoretical_usage_qty > 0
+                    THEN ROUND(m.theoretical_waste_qty / m.theoretical_usage_qty * 100, 2)
+               END AS theoretical_waste_pct
+        FROM merged m
+        LEFT JOIN ingredient_catalog ic ON ic.uuid = m.ingredient_catalog_uuid
+        LEFT JOIN units_of_measure uom ON ic.unit_of_measure_uuid = uom.uuid
+        ORDER BY m.month ASC, ic.name ASC
+        "#,
+    )
+    .bind(timezone)
+    .bind(query.date_from)
+    .bind(query.date_to)
+    .bind(query.ingredient_catalog_uuid)
+    .bind(&months)
+    .bind(&ingredients)
+    .bind(&usage_qtys)
+    .bind(&waste_qtys)
+    .fetch_all(db)
+    .await
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 
@@ -11,6 +11,20 @@ use crate::{
         get_me_handler, health_checker_handler, login_user_handler, logout_handler,
         refresh_access_token_handler, register_user_handler,
     },
+    handlers::auth_account::{
+        change_password_handler, forgot_password_handler, request_email_verification_handler,
+        reset_password_handler, verify_email_handler,
+    },
+    handlers::auth_sessions::{
+        list_sessions_handler, list_user_sessions_handler, revoke_all_sessions_handler,
+        revoke_session_handler, revoke_user_session_handler, revoke_user_sessions_handler,
+        unlock_user_handler,
+    },
+    handlers::auth_two_factor::{
+        disable_two_factor_handler, login_two_factor_handler, login_two_factor_setup_handler,
+        regenerate_recovery_codes_handler, reset_user_two_factor_handler,
+        two_factor_confirm_handler, two_factor_setup_handler, two_factor_status_handler,
+    },
     middleware::jwt::auth,
     AppState,
 };
@@ -20,12 +34,91 @@ pub fn create_auth_router(app_state: Arc<AppState>) -> Router {
         .route("/api/v1/healthchecker", get(health_checker_handler))
         .route("/api/v1/auth/register", post(register_user_handler))
         .route("/api/v1/auth/login", post(login_user_handler))
+        // Langkah kedua login untuk akun dengan 2FA (challenge_token dari /auth/login)
+        .route("/api/v1/auth/login/2fa", post(login_two_factor_handler))
+        .route(
+            "/api/v1/auth/login/2fa/setup",
+            post(login_two_factor_setup_handler),
+        )
         .route("/api/v1/auth/refresh", get(refresh_access_token_handler))
         .route(
             "/api/v1/auth/logout",
             post(logout_handler)
                 .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
         )
+        // Verifikasi email dan reset / ganti password
+        .route("/api/v1/auth/verify-email", post(verify_email_handler))
+        .route(
+            "/api/v1/auth/verify-email/request",
+            post(request_email_verification_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/forgot-password",
+            post(forgot_password_handler),
+        )
+        .route("/api/v1/auth/reset-password", post(reset_password_handler))
+        .route(
+            "/api/v1/auth/change-password",
+            post(change_password_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        // Sesi login aktif dan sign-out jarak jauh
+        .route(
+            "/api/v1/auth/sessions",
+            get(list_sessions_handler)
+                .delete(revoke_all_sessions_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/sessions/:id",
+            delete(revoke_session_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/users/:user_uuid/sessions",
+            get(list_user_sessions_handler)
+                .delete(revoke_user_sessions_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/users/:user_uuid/sessions/:id",
+            delete(revoke_user_session_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        // Buka kunci akun setelah terlalu banyak login gagal
+        .route(
+            "/api/v1/auth/users/:user_uuid/unlock",
+            post(unlock_user_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        // Autentikasi dua faktor (TOTP) milik sendiri
+        .route(
+            "/api/v1/auth/2fa",
+            get(two_factor_status_handler)
+                .delete(disable_two_factor_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/2fa/setup",
+            post(two_factor_setup_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/2fa/confirm",
+            post(two_factor_confirm_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/2fa/recovery-codes",
+            post(regenerate_recovery_codes_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/auth/users/:user_uuid/2fa",
+            delete(reset_user_two_factor_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
         .route(
             "/api/v1/users/me",
             get(get_me_handler)
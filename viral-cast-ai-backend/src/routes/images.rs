@@ -10,7 +10,8 @@ use crate::{
     handlers::images::{
         delete_product_image_handler, upload_product_image_handler,
         upload_product_image_handler_v2, upload_store_brand_image_handler,
-        upload_user_image_handler,
+        upload_user_background_handler, upload_user_image_handler,
+        upload_user_profile_photo_handler,
     },
     middleware::jwt::auth,
     AppState,
@@ -31,12 +32,12 @@ pub fn create_images_router(app_state: Arc<AppState>) -> Router {
         // NEW: separate endpoints for profile photo and background uploads
         .route(
             "/api/v1/images/upload/user/profile-photo",
-            post(upload_user_image_handler)
+            post(upload_user_profile_photo_handler)
                 .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
         )
         .route(
             "/api/v1/images/upload/user/background",
-            post(upload_user_image_handler)
+            post(upload_user_background_handler)
                 .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
         )
         .route(
@@ -0,0 +1,36 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    handlers::order_refunds::{
+        approve_order_refund_handler, create_order_refund_handler, get_order_refund_handler,
+        get_order_refunds_handler, reject_order_refund_handler, void_order_handler,
+    },
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_order_refunds_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/orders/:id/refunds",
+            post(create_order_refund_handler),
+        )
+        .route("/api/v1/orders/:id/refunds", get(get_order_refunds_handler))
+        .route("/api/v1/orders/:id/void", post(void_order_handler))
+        .route("/api/v1/refunds/:id", get(get_order_refund_handler))
+        .route(
+            "/api/v1/refunds/:id/approve",
+            post(approve_order_refund_handler),
+        )
+        .route(
+            "/api/v1/refunds/:id/reject",
+            post(reject_order_refund_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth))
+        .with_state(app_state)
+}
@@ -1,21 +1,38 @@
 use axum::{
+    middleware,
     routing::{delete, get, patch, post, put},
     Router,
 };
 use std::sync::Arc;
 
-use crate::{handlers::orders::*, AppState};
+use crate::{handlers::orders::*, middleware::jwt::auth, AppState};
 
 pub fn create_orders_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         // CRUD operations
-        .route("/api/v1/orders", post(create_order))
+        // Pembuatan pesanan butuh login agar cashier_uuid tercatat atas nama yang benar
+        .route(
+            "/api/v1/orders",
+            post(create_order).route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
         .route("/api/v1/orders", get(get_orders))
         .route("/api/v1/orders/:id", get(get_order_by_id))
-        .route("/api/v1/orders/:id", put(update_order))
-        .route("/api/v1/orders/:id", delete(delete_order))
+        // Perubahan pesanan juga butuh login: kasir dan status tidak boleh diubah anonim
+        .route(
+            "/api/v1/orders/:id",
+            put(update_order).route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/orders/:id",
+            delete(delete_order)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
         // Status management
-        .route("/api/v1/orders/:id/status", patch(update_order_status))
+        .route(
+            "/api/v1/orders/:id/status",
+            patch(update_order_status)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
         // Statistics and analytics
         .route("/api/v1/orders/stats", get(get_order_stats))
         .with_state(app_state)
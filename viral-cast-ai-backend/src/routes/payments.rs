@@ -1,18 +1,28 @@
 use axum::{
+    middleware,
     routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
 
-use crate::{handlers::payments::*, AppState};
+use crate::{handlers::payments::*, middleware::jwt::auth, AppState};
 
 pub fn create_payments_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/api/v1/payments", post(create_payment))
         .route("/api/v1/payments", get(get_payments))
         .route("/api/v1/payments/:id", get(get_payment_by_id))
-        .route("/api/v1/payments/:id", put(update_payment))
-        .route("/api/v1/payments/:id", delete(delete_payment))
+        // Mengubah atau menghapus pembayaran butuh login
+        .route(
+            "/api/v1/payments/:id",
+            put(update_payment)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/payments/:id",
+            delete(delete_payment)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
         .route("/api/v1/payments/stats", get(get_payment_stats))
         .route(
             "/api/v1/payments/order/:order_uuid",
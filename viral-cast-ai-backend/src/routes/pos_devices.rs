@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use axum::{
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
+
+use crate::{
+    handlers::pos_devices::{
+        delete_pin_handler, enroll_device_handler, list_devices_handler, list_pos_staff_handler,
+        pin_lock_handler, pin_unlock_handler, revoke_device_handler, set_pin_handler,
+    },
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_pos_devices_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        // Pendaftaran terminal POS bersama (owner/admin)
+        .route(
+            "/api/v1/pos/devices",
+            post(enroll_device_handler)
+                .get(list_devices_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .route(
+            "/api/v1/pos/devices/:id",
+            delete(revoke_device_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        // PIN kasir milik sendiri
+        .route(
+            "/api/v1/pos/pin",
+            put(set_pin_handler)
+                .delete(delete_pin_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        // Dipanggil terminal dengan header X-Device-Token
+        .route("/api/v1/pos/staff", get(list_pos_staff_handler))
+        .route("/api/v1/pos/unlock", post(pin_unlock_handler))
+        .route(
+            "/api/v1/pos/lock",
+            post(pin_lock_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        .with_state(app_state)
+}
@@ -0,0 +1,29 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    handlers::production_runs::{
+        create_production_run_handler, get_production_run_handler, get_production_runs_handler,
+    },
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_production_runs_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/production-runs",
+            post(create_production_run_handler),
+        )
+        .route("/api/v1/production-runs", get(get_production_runs_handler))
+        .route(
+            "/api/v1/production-runs/:id",
+            get(get_production_run_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth))
+        .with_state(app_state)
+}
@@ -0,0 +1,39 @@
+use axum::{
+    middleware,
+    routing::{get, patch, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    handlers::purchase_orders::{
+        create_purchase_orders_from_suggestions_handler, get_purchase_order_handler,
+        get_purchase_orders_handler, get_reorder_suggestions_handler,
+        update_purchase_order_status_handler,
+    },
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_purchase_orders_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/reorder-suggestions",
+            get(get_reorder_suggestions_handler),
+        )
+        .route(
+            "/api/v1/reorder-suggestions/purchase-orders",
+            post(create_purchase_orders_from_suggestions_handler),
+        )
+        .route("/api/v1/purchase-orders", get(get_purchase_orders_handler))
+        .route(
+            "/api/v1/purchase-orders/:id",
+            get(get_purchase_order_handler),
+        )
+        .route(
+            "/api/v1/purchase-orders/:id/status",
+            patch(update_purchase_order_status_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth))
+        .with_state(app_state)
+}
@@ -1,4 +1,5 @@
 use axum::{
+    middleware,
     routing::{delete, get, post, put},
     Router,
 };
@@ -7,19 +8,24 @@ use std::sync::Arc;
 use crate::{
     handlers::rag::{
         answer_simple_rag, answer_with_rag_and_llm, delete_document, get_document_status,
-        get_rag_configuration, ingest_text_document, list_documents, query_rag,
+        get_rag_configuration, ingest_text_document, list_documents, query_rag, reindex_document,
         update_rag_configuration, upload_document,
     },
+    handlers::rag_eval::{
+        create_eval_set, get_eval_set, list_eval_runs, list_eval_sets, run_eval_set,
+    },
+    middleware::jwt::auth,
     AppState,
 };
 
-pub fn create_rag_router() -> Router<Arc<AppState>> {
+pub fn create_rag_router(app_state: Arc<AppState>) -> Router {
     Router::new()
         // Document management endpoints
         .route("/documents/upload-document", post(ingest_text_document))
         .route("/documents/upload", post(upload_document))
         .route("/documents", get(list_documents))
         .route("/documents/:id/status", get(get_document_status))
+        .route("/documents/:id/reindex", post(reindex_document))
         .route("/documents/:id", delete(delete_document))
         // RAG query endpoint
         .route("/query", post(query_rag))
@@ -28,4 +34,13 @@ pub fn create_rag_router() -> Router<Arc<AppState>> {
         // Configuration endpoints
         .route("/config", get(get_rag_configuration))
         .route("/config", put(update_rag_configuration))
+        // Evaluation (golden question sets)
+        .route("/eval/sets", get(list_eval_sets).post(create_eval_set))
+        .route("/eval/sets/:id", get(get_eval_set))
+        .route(
+            "/eval/sets/:id/runs",
+            get(list_eval_runs).post(run_eval_set),
+        )
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth))
+        .with_state(app_state)
 }
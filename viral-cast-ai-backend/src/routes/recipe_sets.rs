@@ -7,8 +7,9 @@ use axum::{
 
 use crate::{
     handlers::recipe_sets::{
-        create_recipe_set_handler, delete_recipe_set_handler, get_recipe_set_handler,
-        get_recipe_sets_handler, update_recipe_set_handler,
+        create_recipe_set_handler, create_recipe_version_handler, delete_recipe_set_handler,
+        get_recipe_set_cost_handler, get_recipe_set_diff_handler, get_recipe_set_handler,
+        get_recipe_sets_handler, get_recipe_versions_handler, update_recipe_set_handler,
     },
     AppState,
 };
@@ -20,5 +21,21 @@ pub fn create_recipe_sets_router(app_state: Arc<AppState>) -> Router {
         .route("/api/recipe-sets/:id", get(get_recipe_set_handler))
         .route("/api/recipe-sets/:id", put(update_recipe_set_handler))
         .route("/api/recipe-sets/:id", delete(delete_recipe_set_handler))
+        .route(
+            "/api/recipe-sets/:id/cost",
+            get(get_recipe_set_cost_handler),
+        )
+        .route(
+            "/api/recipe-sets/:id/versions",
+            post(create_recipe_version_handler),
+        )
+        .route(
+            "/api/recipe-sets/:id/versions",
+            get(get_recipe_versions_handler),
+        )
+        .route(
+            "/api/recipe-sets/:id/diff",
+            get(get_recipe_set_diff_handler),
+        )
         .with_state(app_state)
 }
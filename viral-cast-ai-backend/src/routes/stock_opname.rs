@@ -0,0 +1,46 @@
+use axum::{
+    middleware,
+    routing::{get, patch, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    handlers::stock_opname::{
+        approve_stock_opname_session_handler, cancel_stock_opname_session_handler,
+        create_stock_opname_session_handler, get_stock_opname_session_handler,
+        get_stock_opname_sessions_handler, submit_stock_opname_counts_handler,
+    },
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_stock_opname_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/stock-opnames",
+            post(create_stock_opname_session_handler),
+        )
+        .route(
+            "/api/v1/stock-opnames",
+            get(get_stock_opname_sessions_handler),
+        )
+        .route(
+            "/api/v1/stock-opnames/:id",
+            get(get_stock_opname_session_handler),
+        )
+        .route(
+            "/api/v1/stock-opnames/:id/counts",
+            patch(submit_stock_opname_counts_handler),
+        )
+        .route(
+            "/api/v1/stock-opnames/:id/approve",
+            post(approve_stock_opname_session_handler),
+        )
+        .route(
+            "/api/v1/stock-opnames/:id/cancel",
+            post(cancel_stock_opname_session_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth))
+        .with_state(app_state)
+}
@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use axum::{middleware, routing::get, Router};
+
+use crate::{
+    handlers::storage::{get_signed_blob_handler, get_signed_url_handler},
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_storage_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/storage/signed-url",
+            get(get_signed_url_handler)
+                .route_layer(middleware::from_fn_with_state(app_state.clone(), auth)),
+        )
+        // Signed URL membawa otorisasinya sendiri (expires + signature), tanpa JWT
+        .route("/api/v1/storage/blobs/*key", get(get_signed_blob_handler))
+        .with_state(app_state)
+}
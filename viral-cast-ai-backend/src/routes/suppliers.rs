@@ -0,0 +1,35 @@
+use axum::{
+    middleware,
+    routing::{delete, get, patch, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    handlers::suppliers::{
+        create_supplier_handler, delete_supplier_handler, delete_supplier_ingredient_handler,
+        get_supplier_handler, get_suppliers_handler, update_supplier_handler,
+        upsert_supplier_ingredient_handler,
+    },
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_suppliers_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/v1/suppliers", post(create_supplier_handler))
+        .route("/api/v1/suppliers", get(get_suppliers_handler))
+        .route("/api/v1/suppliers/:id", get(get_supplier_handler))
+        .route("/api/v1/suppliers/:id", patch(update_supplier_handler))
+        .route("/api/v1/suppliers/:id", delete(delete_supplier_handler))
+        .route(
+            "/api/v1/suppliers/:id/ingredients",
+            post(upsert_supplier_ingredient_handler),
+        )
+        .route(
+            "/api/v1/suppliers/:id/ingredients/:ingredient_catalog_uuid",
+            delete(delete_supplier_ingredient_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth))
+        .with_state(app_state)
+}
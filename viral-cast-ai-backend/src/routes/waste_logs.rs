@@ -0,0 +1,32 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::{
+    handlers::waste_logs::{
+        create_waste_log_handler, get_waste_by_reason_report_handler, get_waste_log_handler,
+        get_waste_logs_handler, get_waste_vs_theoretical_report_handler,
+    },
+    middleware::jwt::auth,
+    AppState,
+};
+
+pub fn create_waste_logs_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/v1/waste-logs", post(create_waste_log_handler))
+        .route("/api/v1/waste-logs", get(get_waste_logs_handler))
+        .route(
+            "/api/v1/waste-logs/reports/by-reason",
+            get(get_waste_by_reason_report_handler),
+        )
+        .route(
+            "/api/v1/waste-logs/reports/vs-theoretical",
+            get(get_waste_vs_theoretical_report_handler),
+        )
+        .route("/api/v1/waste-logs/:id", get(get_waste_log_handler))
+        .route_layer(middleware::from_fn_with_state(app_state.clone(), auth))
+        .with_state(app_state)
+}
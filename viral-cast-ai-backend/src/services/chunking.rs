@@ -0,0 +1,503 @@
+// ID: Chunking dokumen RAG yang sadar struktur: kalimat/paragraf, heading (judul bagian disimpan
+// sebagai metadata), dan kelompok baris tabel (xlsx/csv) yang mengulang header kolom.
+// EN: Structure-aware chunking for RAG documents: sentence/paragraph, heading (section titles kept
+// as metadata), and table row groups (xlsx/csv) that repeat the column header.
+
+/// Prefix baris nama sheet dari extractor xlsx
+pub const SHEET_MARKER: &str = "Sheet: ";
+/// Rata-rata karakter per token BPE, dipakai untuk estimasi tanpa tokenizer
+const CHARS_PER_TOKEN: usize = 4;
+const MAX_HEADING_CHARS: usize = 80;
+const MAX_HEADING_WORDS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkingStrategy {
+    /// Potongan karakter tetap (perilaku lama)
+    Fixed,
+    Sentence,
+    Heading,
+}
+
+impl ChunkingStrategy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "fixed" => Some(Self::Fixed),
+            "sentence" | "paragraph" => Some(Self::Sentence),
+            "heading" => Some(Self::Heading),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fixed => "fixed",
+            Self::Sentence => "sentence",
+            Self::Heading => "heading",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnit {
+    Chars,
+    Tokens,
+}
+
+impl SizeUnit {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "chars" | "characters" => Some(Self::Chars),
+            "tokens" => Some(Self::Tokens),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Chars => "chars",
+            Self::Tokens => "tokens",
+        }
+    }
+
+    pub fn measure(&self, text: &str) -> usize {
+        match self {
+            Self::Chars => text.chars().count(),
+            Self::Tokens => estimate_tokens(text),
+        }
+    }
+
+    fn to_chars(self, size: usize) -> usize {
+        match self {
+            Self::Chars => size,
+            Self::Tokens => size * CHARS_PER_TOKEN,
+        }
+    }
+}
+
+/// ID: Estimasi jumlah token LLM: tiap kata ≈ ceil(panjang / 4), minimal 1.
+/// EN: Estimated LLM token count: each word ≈ ceil(length / 4), at least 1.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.split_whitespace()
+        .map(|word| word.chars().count().div_ceil(CHARS_PER_TOKEN).max(1))
+        .sum()
+}
+
+pub fn is_tabular_file_type(file_type: &str) -> bool {
+    matches!(
+        file_type.to_lowercase().as_str(),
+        "csv" | "xlsx" | "xls" | "xlsm"
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkOptions {
+    pub strategy: ChunkingStrategy,
+    pub unit: SizeUnit,
+    pub size: usize,
+    pub overlap: usize,
+    /// Teks berasal dari csv/xlsx: satu baris = satu record
+    pub tabular: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub text: String,
+    /// Offset karakter di teks asli (untuk nomor halaman)
+    pub start_char: usize,
+    pub end_char: usize,
+    /// Jalur heading, mis. "SOP Dapur > Penyimpanan"
+    pub heading: Option<String>,
+    pub sheet: Option<String>,
+    /// Nomor baris data pertama & terakhir (1-based, tanpa header)
+    pub rows: Option<(usize, usize)>,
+}
+
+impl Chunk {
+    fn span(chars: &[char], start: usize, end: usize) -> Self {
+        Self {
+            text: chars[start..end].iter().collect(),
+            start_char: start,
+            end_char: end,
+            heading: None,
+            sheet: None,
+            rows: None,
+        }
+    }
+}
+
+pub fn chunk_document(text: &str, options: &ChunkOptions) -> Vec<Chunk> {
+    if options.size == 0 || text.trim().is_empty() {
+        return Vec::new();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    match options.strategy {
+        ChunkingStrategy::Fixed => fixed_windows(&chars, 0, chars.len(), options),
+        _ if options.tabular => chunk_tabular(&chars, options),
+        ChunkingStrategy::Sentence => {
+            let units = sentence_units(&chars, 0, chars.len());
+            pack_units(&chars, &units, options)
+        }
+        ChunkingStrategy::Heading => chunk_by_heading(&chars, options),
+    }
+}
+
+// =============== FIXED =================
+
+fn fixed_windows(chars: &[char], from: usize, to: usize, options: &ChunkOptions) -> Vec<Chunk> {
+    let size = options.unit.to_chars(options.size).max(1);
+    let overlap = options.unit.to_chars(options.overlap);
+    let mut chunks = Vec::new();
+    let mut start = from;
+    while start < to {
+        let end = (start + size).min(to);
+        chunks.push(Chunk::span(chars, start, end));
+        if end == to {
+            break;
+        }
+        let next_start = end.saturating_sub(overlap);
+        if next_start <= start {
+            break;
+        }
+        start = next_start;
+    }
+    chunks
+}
+
+// =============== SENTENCE / PARAGRAPH =================
+
+/// Rentang [start, end) tiap baris, tanpa karakter '\n'
+fn line_spans(chars: &[char], from: usize, to: usize) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = from;
+    for (i, c) in chars.iter().enumerate().take(to).skip(from) {
+        if *c == '\n' {
+            spans.push((start, i));
+            start = i + 1;
+        }
+    }
+    spans.push((start, to));
+    spans
+}
+
+fn trim_span(chars: &[char], mut start: usize, mut end: usize) -> Option<(usize, usize)> {
+    while start < end && chars[start].is_whitespace() {
+        start += 1;
+    }
+    while end > start && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    (start < end).then_some((start, end))
+}
+
+/// ID: Unit terkecil yang tidak boleh dipotong: kalimat di dalam baris (baris = paragraf/list item).
+/// EN: Smallest units that must not be cut: sentences within a line (line = paragraph/list item).
+fn sentence_units(chars: &[char], from: usize, to: usize) -> Vec<(usize, usize)> {
+    let mut units = Vec::new();
+    for (line_start, line_end) in line_spans(chars, from, to) {
+        let mut start = line_start;
+        for i in line_start..line_end {
+            let terminator = matches!(chars[i], '.' | '!' | '?' | '。');
+            if terminator && (i + 1 == line_end || chars[i + 1].is_whitespace()) {
+                units.extend(trim_span(chars, start, i + 1));
+                start = i + 1;
+            }
+        }
+        units.extend(trim_span(chars, start, line_end));
+    }
+    units
+}
+
+fn measure_span(chars: &[char], start: usize, end: usize, unit: SizeUnit) -> usize {
+    match unit {
+        SizeUnit::Chars => end - start,
+        SizeUnit::Tokens => estimate_tokens(&chars[start..end].iter().collect::<String>()),
+    }
+}
+
+/// ID: Susun unit berurutan menjadi chunk <= size; overlap memakai unit utuh dari akhir chunk
+/// sebelumnya. Unit yang lebih besar dari size dipotong dengan jendela tetap.
+/// EN: Greedily pack consecutive units into chunks <= size; overlap reuses whole units from the
+/// end of the previous chunk. Units larger than size are cut with fixed windows.
+fn pack_units(chars: &[char], units: &[(usize, usize)], options: &ChunkOptions) -> Vec<Chunk> {
+    let measure = |start: usize, end: usize| measure_span(chars, start, end, options.unit);
+    let mut chunks = Vec::new();
+    let mut i = 0;
+    while i < units.len() {
+        let (start, end) = units[i];
+        if measure(start, end) > options.size {
+            chunks.extend(fixed_windows(chars, start, end, options));
+            i += 1;
+            continue;
+        }
+
+        let mut j = i;
+        while j + 1 < units.len() && measure(start, units[j + 1].1) <= options.size {
+            j += 1;
+        }
+        chunks.push(Chunk::span(chars, start, units[j].1));
+        if j + 1 >= units.len() {
+            break;
+        }
+
+        let mut next = j + 1;
+        while next > i + 1 && measure(units[next - 1].0, units[j].1) <= options.overlap {
+            next -= 1;
+        }
+        i = next;
+    }
+    chunks
+}
+
+// =============== HEADING =================
+
+fn markdown_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    let title = trimmed[level..].trim();
+    ((1..=6).contains(&level) && trimmed[level..].starts_with(' ') && !title.is_empty())
+        .then(|| (level, title.trim_end_matches('#').trim().to_string()))
+}
+
+/// Baris pendek yang berdiri sendiri (diapit baris kosong) tanpa tanda baca akhir
+fn looks_like_heading(line: &str) -> bool {
+    let line = line.trim();
+    let starts_upper = line
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_uppercase() || c.is_ascii_digit());
+    starts_upper
+        && line.chars().count() <= MAX_HEADING_CHARS
+        && line.split_whitespace().count() <= MAX_HEADING_WORDS
+        && !line.ends_with(['.', ',', ';', ':', '!', '?'])
+}
+
+fn chunk_by_heading(chars: &[char], options: &ChunkOptions) -> Vec<Chunk> {
+    let lines = line_spans(chars, 0, chars.len());
+    let line_text = |idx: usize| -> String {
+        let (start, end) = lines[idx];
+        chars[start..end].iter().collect()
+    };
+    let is_blank = |idx: usize| line_text(idx).trim().is_empty();
+
+    // (level, judul) yang aktif; section = (jalur heading, awal, akhir)
+    let mut path: Vec<(usize, String)> = Vec::new();
+    let mut sections: Vec<(Option<String>, usize, usize)> = Vec::new();
+    let mut section_start = 0;
+    let mut current_heading: Option<String> = None;
+    for idx in 0..lines.len() {
+        let text = line_text(idx);
+        let heading = markdown_heading(&text).or_else(|| {
+            let standalone =
+                (idx == 0 || is_blank(idx - 1)) && (idx + 1 == lines.len() || is_blank(idx + 1));
+            (standalone && !text.trim().is_empty() && looks_like_heading(&text))
+                .then(|| (1, text.trim().to_string()))
+        });
+        if let Some((level, title)) = heading {
+            sections.push((current_heading.take(), section_start, lines[idx].0));
+            path.retain(|(l, _)| *l < level);
+            path.push((level, title));
+            current_heading = Some(
+                path.iter()
+                    .map(|(_, t)| t.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" > "),
+            );
+            section_start = lines[idx].0;
+        }
+    }
+    sections.push((current_heading, section_start, chars.len()));
+
+    sections
+        .into_iter()
+        .flat_map(|(heading, start, end)| {
+            let units = sentence_units(chars, start, end);
+            pack_units(chars, &units, options)
+                .into_iter()
+                .map(move |chunk| Chunk {
+                    heading: heading.clone(),
+                    ..chunk
+                })
+        })
+        .collect()
+}
+
+// =============== TABULAR =================
+
+/// ID: Kelompokkan baris tabel per sheet; setiap chunk diawali nama sheet dan header kolom.
+/// EN: Group table rows per sheet; each chunk starts with the sheet name and column header.
+fn chunk_tabular(chars: &[char], options: &ChunkOptions) -> Vec<Chunk> {
+    let lines: Vec<(usize, usize)> = line_spans(chars, 0, chars.len())
+        .into_iter()
+        .filter_map(|(start, end)| trim_span(chars, start, end))
+        .collect();
+    let text_of = |(start, end): (usize, usize)| chars[start..end].iter().collect::<String>();
+
+    let mut chunks = Vec::new();
+    let mut idx = 0;
+    while idx < lines.len() {
+        let mut sheet = None;
+        let first = text_of(lines[idx]);
+        if let Some(name) = first.strip_prefix(SHEET_MARKER) {
+            sheet = Some(name.trim().to_string());
+            idx += 1;
+        }
+        let section_end = lines[idx..]
+            .iter()
+            .position(|span| text_of(*span).starts_with(SHEET_MARKER))
+            .map(|offset| idx + offset)
+            .unwrap_or(lines.len());
+        if idx >= section_end {
+            continue;
+        }
+
+        let header = text_of(lines[idx]);
+        let prefix = match &sheet {
+            Some(name) => format!("{}{}\n{}", SHEET_MARKER, name, header),
+            None => header,
+        };
+        let rows = &lines[idx + 1..section_end];
+        if rows.is_empty() {
+            let mut chunk = Chunk::span(chars, lines[idx].0, lines[idx].1);
+            chunk.text = prefix.clone();
+            chunk.sheet = sheet.clone();
+            chunks.push(chunk);
+        }
+
+        let mut row = 0;
+        while row < rows.len() {
+            let mut text = format!("{}\n{}", prefix, text_of(rows[row]));
+            let mut last = row;
+            while last + 1 < rows.len() {
+                let candidate = format!("{}\n{}", text, text_of(rows[last + 1]));
+                if options.unit.measure(&candidate) > options.size {
+                    break;
+                }
+                text = candidate;
+                last += 1;
+            }
+            chunks.push(Chunk {
+                text,
+                start_char: rows[row].0,
+                end_char: rows[last].1,
+                heading: None,
+                sheet: sheet.clone(),
+                rows: Some((row + 1, last + 1)),
+            });
+            row = last + 1;
+        }
+        idx = section_end;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(strategy: ChunkingStrategy, size: usize, overlap: usize) -> ChunkOptions {
+        ChunkOptions {
+            strategy,
+            unit: SizeUnit::Chars,
+            size,
+            overlap,
+            tabular: false,
+        }
+    }
+
+    #[test]
+    fn sentence_chunks_never_cut_sentences() {
+        let text = "Kopi disimpan kering. Susu masuk kulkas! Gula di rak atas?\nCatatan akhir.";
+        let chunks = chunk_document(text, &options(ChunkingStrategy::Sentence, 45, 20));
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Kopi disimpan kering. Susu masuk kulkas!",
+                "Susu masuk kulkas! Gula di rak atas?",
+                "Gula di rak atas?\nCatatan akhir."
+            ]
+        );
+        let first = &chunks[0];
+        assert_eq!(
+            text.chars()
+                .skip(first.start_char)
+                .take(first.end_char - first.start_char)
+                .collect::<String>(),
+            first.text
+        );
+    }
+
+    #[test]
+    fn oversized_sentence_falls_back_to_fixed_windows() {
+        let chunks = chunk_document("abcdefghij", &options(ChunkingStrategy::Sentence, 4, 0));
+        let texts: Vec<&str> = chunks.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn heading_chunks_keep_section_path() {
+        let text = "# SOP Dapur\nPanduan umum.\n\n## Penyimpanan\nSimpan susu di kulkas.\n\nBahan Kering\n\nGula di rak atas.";
+        let chunks = chunk_document(text, &options(ChunkingStrategy::Heading, 200, 0));
+        let summary: Vec<(Option<&str>, &str)> = chunks
+            .iter()
+            .map(|c| (c.heading.as_deref(), c.text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Some("SOP Dapur"), "# SOP Dapur\nPanduan umum."),
+                (
+                    Some("SOP Dapur > Penyimpanan"),
+                    "## Penyimpanan\nSimpan susu di kulkas."
+                ),
+                (Some("Bahan Kering"), "Bahan Kering\n\nGula di rak atas."),
+            ]
+        );
+    }
+
+    #[test]
+    fn tabular_chunks_repeat_header() {
+        let text = "Sheet: Stok\nBahan\tQty\nSusu\t10\nGula\t5\nKopi\t7\n\nSheet: Kosong\nA\tB";
+        let chunks = chunk_document(
+            text,
+            &ChunkOptions {
+                tabular: true,
+                ..options(ChunkingStrategy::Sentence, 36, 0)
+            },
+        );
+        let summary: Vec<(&str, Option<(usize, usize)>)> =
+            chunks.iter().map(|c| (c.text.as_str(), c.rows)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Sheet: Stok\nBahan\tQty\nSusu\t10\nGula\t5", Some((1, 2))),
+                ("Sheet: Stok\nBahan\tQty\nKopi\t7", Some((3, 3))),
+                ("Sheet: Kosong\nA\tB", None),
+            ]
+        );
+        assert_eq!(chunks[1].sheet.as_deref(), Some("Stok"));
+
+        let csv = chunk_document(
+            "nama,harga\nlatte,25000\nmocha,28000",
+            &ChunkOptions {
+                tabular: true,
+                ..options(ChunkingStrategy::Heading, 25, 0)
+            },
+        );
+        assert_eq!(csv[1].text, "nama,harga\nmocha,28000");
+    }
+
+    #[test]
+    fn token_sizing_uses_estimate() {
+        assert_eq!(estimate_tokens("kopi susu gula-aren"), 1 + 1 + 3);
+        let chunks = chunk_document(
+            "Satu dua. Tiga empat. Lima enam.",
+            &ChunkOptions {
+                unit: SizeUnit::Tokens,
+                ..options(ChunkingStrategy::Sentence, 5, 0)
+            },
+        );
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].text, "Satu dua. Tiga empat.");
+    }
+}
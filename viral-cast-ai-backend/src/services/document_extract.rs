@@ -0,0 +1,538 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use anyhow::{anyhow, Context};
+use quick_xml::{events::Event as XmlEvent, Reader as XmlReader};
+use zip::ZipArchive;
+
+// ID: Ekstraksi teks untuk ingestion RAG dari PDF, HTML dan EPUB (pure Rust, tanpa binary
+// eksternal). Fungsi di sini sinkron/CPU-bound, panggil lewat spawn_blocking.
+// EN: Text extraction for RAG ingestion from PDF, HTML and EPUB (pure Rust, no external
+// binaries). Functions here are synchronous/CPU-bound, call them through spawn_blocking.
+
+/// Elemen yang isinya bukan teks dokumen
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "head", "noscript", "template", "svg"];
+
+/// Elemen blok: dipisahkan baris baru agar paragraf tidak menempel
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// Penutup elemen ini memberi baris kosong (pemisah paragraf)
+const PARAGRAPH_ELEMENTS: &[&str] = &[
+    "article",
+    "blockquote",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ol",
+    "p",
+    "section",
+    "table",
+    "ul",
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedDocument {
+    pub text: String,
+    /// Offset karakter (bukan byte) awal tiap halaman beserta nomor halaman (mulai 1)
+    pub page_starts: Vec<(usize, i32)>,
+}
+
+impl ExtractedDocument {
+    pub fn plain(text: String) -> Self {
+        Self {
+            text,
+            page_starts: Vec::new(),
+        }
+    }
+
+    /// ID: Gabungkan teks per halaman; halaman kosong dilewati tapi penomoran tetap.
+    /// EN: Join per-page text; empty pages are skipped but numbering is preserved.
+    pub fn from_pages(pages: Vec<String>) -> Self {
+        let mut text = String::new();
+        let mut char_len = 0usize;
+        let mut page_starts = Vec::new();
+        for (idx, page) in pages.iter().enumerate() {
+            let page = page.trim();
+            if page.is_empty() {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push_str("\n\n");
+                char_len += 2;
+            }
+            page_starts.push((char_len, idx as i32 + 1));
+            text.push_str(page);
+            char_len += page.chars().count();
+        }
+        Self { text, page_starts }
+    }
+
+    /// Nomor halaman untuk chunk yang dimulai pada offset karakter tersebut
+    pub fn page_for_offset(&self, char_offset: usize) -> Option<i32> {
+        self.page_starts
+            .iter()
+            .take_while(|(start, _)| *start <= char_offset)
+            .last()
+            .map(|(_, page)| *page)
+    }
+}
+
+pub fn extract_pdf(bytes: &[u8]) -> anyhow::Result<ExtractedDocument> {
+    let pages = pdf_extract::extract_text_from_mem_by_pages(bytes)
+        .map_err(|e| anyhow!("Failed to extract PDF text: {}", e))?;
+    let document = ExtractedDocument::from_pages(pages);
+    if document.text.trim().is_empty() {
+        return Err(anyhow!(
+            "PDF has no text layer (scanned documents need OCR before upload)"
+        ));
+    }
+    Ok(document)
+}
+
+pub fn extract_html(bytes: &[u8]) -> anyhow::Result<ExtractedDocument> {
+    Ok(ExtractedDocument::plain(html_to_text(
+        &String::from_utf8_lossy(bytes),
+    )))
+}
+
+/// ID: EPUB = zip berisi XHTML; urutan bab mengikuti spine pada file OPF.
+/// EN: EPUB = zip of XHTML files; chapter order follows the spine in the OPF file.
+pub fn extract_epub(bytes: &[u8]) -> anyhow::Result<ExtractedDocument> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).context("Reading EPUB zip archive")?;
+
+    let chapters = match epub_spine(&mut archive) {
+        Ok(chapters) if !chapters.is_empty() => chapters,
+        // Fallback untuk EPUB tanpa OPF yang valid: semua file (X)HTML berurutan nama
+        _ => {
+            let mut names: Vec<String> = archive
+                .file_names()
+                .filter(|name| {
+                    let lower = name.to_lowercase();
+                    lower.ends_with(".xhtml") || lower.ends_with(".html") || lower.ends_with(".htm")
+                })
+                .map(str::to_string)
+                .collect();
+            names.sort();
+            names
+        }
+    };
+
+    let mut sections = Vec::new();
+    for name in chapters {
+        let Ok(mut entry) = archive.by_name(&name) else {
+            continue;
+        };
+        let mut raw = Vec::new();
+        entry
+            .read_to_end(&mut raw)
+            .with_context(|| format!("Reading EPUB entry {}", name))?;
+        let text = html_to_text(&String::from_utf8_lossy(&raw));
+        if !text.is_empty() {
+            sections.push(text);
+        }
+    }
+
+    Ok(ExtractedDocument::plain(sections.join("\n\n")))
+}
+
+fn read_zip_text(archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> anyhow::Result<String> {
+    let mut entry = archive
+        .by_name(name)
+        .with_context(|| format!("EPUB missing {}", name))?;
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .with_context(|| format!("Reading EPUB entry {}", name))?;
+    Ok(content)
+}
+
+fn epub_spine(archive: &mut ZipArchive<Cursor<&[u8]>>) -> anyhow::Result<Vec<String>> {
+    let container = read_zip_text(archive, "META-INF/container.xml")?;
+    let opf_path = xml_attribute_values(&container, b"rootfile", b"full-path")?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("EPUB container has no rootfile"))?;
+    let opf = read_zip_text(archive, &opf_path)?;
+    let base_dir = opf_path
+        .rsplit_once('/')
+        .map(|(dir, _)| format!("{}/", dir))
+        .unwrap_or_default();
+
+    let mut manifest: HashMap<String, String> = HashMap::new();
+    let mut spine: Vec<String> = Vec::new();
+    let mut reader = XmlReader::from_str(&opf);
+    reader.trim_text(true);
+    loop {
+        match reader.read_event() {
+            Ok(XmlEvent::Start(ref e)) | Ok(XmlEvent::Empty(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"item" => {
+                        let id = attribute(e, b"id");
+                        let href = attribute(e, b"href");
+                        if let (Some(id), Some(href)) = (id, href) {
+                            manifest.insert(id, href);
+                        }
+                    }
+                    b"itemref" => {
+                        if let Some(idref) = attribute(e, b"idref") {
+                            spine.push(idref);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(XmlEvent::Eof) => break,
+            Ok(_) => {}
+            Err(err) => return Err(anyhow!("Failed to parse EPUB OPF: {}", err)),
+        }
+    }
+
+    Ok(spine
+        .iter()
+        .filter_map(|idref| manifest.get(idref))
+        .map(|href| {
+            let href = href.split('#').next().unwrap_or(href);
+            format!("{}{}", base_dir, percent_decode(href))
+        })
+        .collect())
+}
+
+fn attribute(element: &quick_xml::events::BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name)
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+fn xml_attribute_values(xml: &str, element: &[u8], name: &[u8]) -> anyhow::Result<Vec<String>> {
+    let mut values = Vec::new();
+    let mut reader = XmlReader::from_str(xml);
+    reader.trim_text(true);
+    loop {
+        match reader.read_event() {
+            Ok(XmlEvent::Start(ref e)) | Ok(XmlEvent::Empty(ref e))
+                if e.local_name().as_ref() == element =>
+            {
+                if let Some(value) = attribute(e, name) {
+                    values.push(value);
+                }
+            }
+            Ok(XmlEvent::Eof) => break,
+            Ok(_) => {}
+            Err(err) => return Err(anyhow!("Failed to parse EPUB XML: {}", err)),
+        }
+    }
+    Ok(values)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// ID: Ubah HTML menjadi teks polos: buang tag, script/style dan komentar, decode entity,
+/// pisahkan elemen blok dengan baris baru dan rapikan spasi.
+/// EN: Convert HTML to plain text: drop tags, script/style and comments, decode entities,
+/// separate block elements with newlines and normalize whitespace.
+pub fn html_to_text(html: &str) -> String {
+    let mut out = String::with_capacity(html.len() / 2);
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map(|i| &rest[i + 3..]).unwrap_or("");
+            continue;
+        }
+
+        if rest.starts_with('<') && starts_tag(&rest[1..]) {
+            let Some(end) = rest.find('>') else {
+                break;
+            };
+            let inner = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            let closing = inner.starts_with('/');
+            let name = inner
+                .trim_start_matches('/')
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+                .to_ascii_lowercase();
+
+            if !closing && !inner.ends_with('/') && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+                let close_tag = format!("</{}", name);
+                rest = find_ascii_ci(rest, &close_tag)
+                    .and_then(|i| rest[i..].find('>').map(|j| &rest[i + j + 1..]))
+                    .unwrap_or("");
+                continue;
+            }
+
+            if closing && PARAGRAPH_ELEMENTS.contains(&name.as_str()) {
+                push_breaks(&mut out, 2);
+            } else if BLOCK_ELEMENTS.contains(&name.as_str()) {
+                push_breaks(&mut out, 1);
+            } else if closing && (name == "td" || name == "th") {
+                out.push('\t');
+            }
+            continue;
+        }
+
+        // Karakter pertama selalu teks (termasuk '<' yang bukan awal tag)
+        let first_len = rest.chars().next().map(char::len_utf8).unwrap_or(1);
+        let end = rest[first_len..]
+            .find('<')
+            .map(|i| i + first_len)
+            .unwrap_or(rest.len());
+        for c in decode_entities(&rest[..end]).chars() {
+            // Whitespace di dalam teks HTML tidak bermakna, kecuali lewat elemen blok
+            out.push(if c.is_whitespace() && c != '\u{a0}' {
+                ' '
+            } else {
+                c
+            });
+        }
+        rest = &rest[end..];
+    }
+
+    normalize_whitespace(&out)
+}
+
+/// Pastikan teks diakhiri minimal `count` baris baru (tanpa spasi menggantung)
+fn push_breaks(out: &mut String, count: usize) {
+    while out.ends_with(' ') {
+        out.pop();
+    }
+    let existing = out.chars().rev().take_while(|c| *c == '\n').count();
+    for _ in existing..count {
+        out.push('\n');
+    }
+}
+
+fn starts_tag(after_lt: &str) -> bool {
+    after_lt
+        .chars()
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?')
+        .unwrap_or(false)
+}
+
+fn find_ascii_ci(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(needle)
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|semi| *semi <= 10)
+            .and_then(|semi| decode_entity(&rest[1..semi + 1]).map(|c| (c, semi + 2)));
+        match decoded {
+            Some((c, consumed)) => {
+                out.push(c);
+                rest = &rest[consumed..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse::<u32>().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    let c = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "times" => '×',
+        "euro" => '€',
+        _ => return None,
+    };
+    Some(c)
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut blank_run = 0;
+    for line in text.lines() {
+        let collapsed = line
+            .split('\t')
+            .map(|cell| cell.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\t");
+        let collapsed = collapsed.trim_matches('\t').to_string();
+        if collapsed.is_empty() {
+            blank_run += 1;
+            // Maksimal satu baris kosong sebagai pemisah paragraf
+            if blank_run == 1 && !lines.is_empty() {
+                lines.push(String::new());
+            }
+        } else {
+            blank_run = 0;
+            lines.push(collapsed);
+        }
+    }
+    while lines.last().map(|l| l.is_empty()).unwrap_or(false) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn html_to_text_strips_markup_scripts_and_entities() {
+        let html = r#"<!DOCTYPE html><html><head><title>SOP</title>
+            <style>p { color: red; }</style></head>
+            <body><h1>SOP Dapur</h1><!-- internal note -->
+            <p>Simpan susu &lt; 4&deg;C &amp; catat suhu&#33;</p>
+            <script>alert("x < y")</script>
+            <ul><li>Cuci   tangan</li><li>Pakai&nbsp;sarung tangan</li></ul>
+            <table><tr><td>Susu</td><td>2 L</td></tr></table>
+            <p>Harga 3 < 5 tetap teks</p></body></html>"#;
+        assert_eq!(
+            html_to_text(html),
+            "SOP Dapur\n\nSimpan susu < 4°C & catat suhu!\n\nCuci tangan\nPakai sarung tangan\n\nSusu\t2 L\n\nHarga 3 < 5 tetap teks"
+        );
+    }
+
+    #[test]
+    fn page_offsets_follow_joined_text() {
+        let document = ExtractedDocument::from_pages(vec![
+            "Halaman satu".to_string(),
+            "   ".to_string(),
+            "Halaman tiga".to_string(),
+        ]);
+        assert_eq!(document.text, "Halaman satu\n\nHalaman tiga");
+        assert_eq!(document.page_starts, vec![(0, 1), (14, 3)]);
+        assert_eq!(document.page_for_offset(0), Some(1));
+        assert_eq!(document.page_for_offset(13), Some(1));
+        assert_eq!(document.page_for_offset(14), Some(3));
+        assert_eq!(
+            ExtractedDocument::plain("x".into()).page_for_offset(0),
+            None
+        );
+    }
+
+    #[test]
+    fn epub_chapters_follow_spine_order() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            let options = zip::write::FileOptions::default();
+            let files = [
+                ("mimetype", "application/epub+zip"),
+                (
+                    "META-INF/container.xml",
+                    r#"<?xml version="1.0"?><container xmlns="urn:oasis:names:tc:opendocument:xmlns:container"><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#,
+                ),
+                (
+                    "OEBPS/content.opf",
+                    r#"<?xml version="1.0"?><package xmlns="http://www.idpf.org/2007/opf"><manifest><item id="c1" href="chapter%201.xhtml" media-type="application/xhtml+xml"/><item id="c2" href="a-second.xhtml" media-type="application/xhtml+xml"/></manifest><spine><itemref idref="c2"/><itemref idref="c1"/></spine></package>"#,
+                ),
+                (
+                    "OEBPS/chapter 1.xhtml",
+                    "<html><body><p>Bab penutup</p></body></html>",
+                ),
+                (
+                    "OEBPS/a-second.xhtml",
+                    "<html><head><title>x</title></head><body><h1>Bab pembuka</h1></body></html>",
+                ),
+            ];
+            for (name, content) in files {
+                zip.start_file(name, options).unwrap();
+                zip.write_all(content.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+
+        let document = extract_epub(buffer.get_ref()).unwrap();
+        assert_eq!(document.text, "Bab pembuka\n\nBab penutup");
+        assert!(document.page_starts.is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// ID: Embedder untuk RAG. Model semantik dipanggil lewat endpoint embeddings OpenAI-compatible
+// (OpenAI, atau server lokal CPU seperti text-embeddings-inference / Ollama yang menjalankan
+// model ONNX sentence-transformers). Hashing embedder hanya fallback tanpa makna semantik.
+// EN: Embedders for RAG. Semantic models are called through an OpenAI-compatible embeddings
+// endpoint (OpenAI, or a local CPU server such as text-embeddings-inference / Ollama running
+// ONNX sentence-transformer models). The hashing embedder is a non-semantic fallback only.
+
+/// Nama model untuk hashing embedder; dipakai juga di rag_configurations.embedding_model
+pub const HASHING_MODEL: &str = "local-hash";
+const DEFAULT_BATCH_SIZE: usize = 64;
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Model yang benar-benar menghasilkan vektor (disimpan per chunk)
+    fn model(&self) -> &str;
+
+    fn dimensions(&self) -> usize;
+
+    /// Satu vektor L2-normalized per teks, urutan sama dengan input
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+// =============== HASHING (FALLBACK) =================
+
+/// ID: Embedding berbasis hash token (tanpa model); hanya cocok untuk token yang sama persis.
+/// EN: Token-hash embedding (no model); only matches identical tokens.
+pub struct HashingEmbedder {
+    dimensions: usize,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(8),
+        }
+    }
+
+    pub fn embed_sync(&self, texts: &[String]) -> Vec<Vec<f32>> {
+        texts
+            .iter()
+            .map(|t| {
+                let mut vec = vec![0f32; self.dimensions];
+                for token in t.split_whitespace() {
+                    let hash = Sha256::digest(token.as_bytes());
+                    // Use first 8 bytes to index and weight
+                    let idx = (u64::from_be_bytes([
+                        hash[0], hash[1], hash[2], hash[3], hash[4], hash[5], hash[6], hash[7],
+                    ]) % self.dimensions as u64) as usize;
+                    vec[idx] += 1.0;
+                }
+                l2_normalize(&mut vec);
+                vec
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Embedder for HashingEmbedder {
+    fn model(&self) -> &str {
+        HASHING_MODEL
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(self.embed_sync(texts))
+    }
+}
+
+// =============== OPENAI-COMPATIBLE =================
+
+#[derive(Serialize)]
+struct EmbeddingsRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingItem>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingItem {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+pub struct OpenAiCompatibleEmbedder {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    dimensions: usize,
+    batch_size: usize,
+}
+
+impl OpenAiCompatibleEmbedder {
+    pub fn new(
+        client: reqwest::Client,
+        base_url: &str,
+        api_key: Option<String>,
+        model: &str,
+        dimensions: usize,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            client,
+            endpoint: format!("{}/embeddings", base_url.trim_end_matches('/')),
+            api_key,
+            model: model.to_string(),
+            dimensions,
+            batch_size: batch_size.max(1),
+        }
+    }
+
+    async fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut request = self.client.post(&self.endpoint).json(&EmbeddingsRequest {
+            model: &self.model,
+            input: batch,
+        });
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Calling embeddings endpoint {}", self.endpoint))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Embeddings endpoint returned {}: {}",
+                status,
+                body.chars().take(300).collect::<String>()
+            ));
+        }
+
+        let mut parsed: EmbeddingsResponse = response
+            .json()
+            .await
+            .context("Parsing embeddings response")?;
+        if parsed.data.len() != batch.len() {
+            return Err(anyhow!(
+                "Embeddings endpoint returned {} vectors for {} inputs",
+                parsed.data.len(),
+                batch.len()
+            ));
+        }
+        parsed.data.sort_by_key(|item| item.index);
+
+        parsed
+            .data
+            .into_iter()
+            .map(|item| {
+                let mut embedding = item.embedding;
+                if embedding.len() != self.dimensions {
+                    return Err(anyhow!(
+                        "Model '{}' returned {} dimensions but embedding_dimensions is {}",
+                        self.model,
+                        embedding.len(),
+                        self.dimensions
+                    ));
+                }
+                l2_normalize(&mut embedding);
+                Ok(embedding)
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAiCompatibleEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            vectors.extend(self.embed_batch(batch).await?);
+        }
+        Ok(vectors)
+    }
+}
+
+// =============== SETTINGS =================
+
+/// ID: Pengaturan endpoint dari env; model & dimensi diambil dari rag_configurations.
+/// EN: Endpoint settings from env; model & dimensions come from rag_configurations.
+#[derive(Clone)]
+pub struct EmbeddingSettings {
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    pub batch_size: usize,
+    client: reqwest::Client,
+}
+
+impl EmbeddingSettings {
+    pub fn new(
+        api_url: Option<String>,
+        api_key: Option<String>,
+        batch_size: Option<usize>,
+    ) -> Self {
+        Self {
+            api_url: api_url.filter(|url| !url.trim().is_empty()),
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            batch_size: batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// ID: Pilih embedder untuk model terkonfigurasi. Tanpa EMBEDDING_API_URL (atau model
+    /// "local-hash") dipakai hashing embedder.
+    /// EN: Pick the embedder for the configured model. Without EMBEDDING_API_URL (or with the
+    /// "local-hash" model) the hashing embedder is used.
+    pub fn embedder_for(&self, model: &str, dimensions: usize) -> Arc<dyn Embedder> {
+        match &self.api_url {
+            Some(url) if model != HASHING_MODEL => Arc::new(OpenAiCompatibleEmbedder::new(
+                self.client.clone(),
+                url,
+                self.api_key.clone(),
+                model,
+                dimensions,
+                self.batch_size,
+            )),
+            _ => Arc::new(HashingEmbedder::new(dimensions)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashing_embedder_is_normalized_and_deterministic() {
+        let embedder = HashingEmbedder::new(16);
+        let texts = vec!["stok susu habis".to_string(), String::new()];
+        let first = embedder.embed_sync(&texts);
+        assert_eq!(first, embedder.embed_sync(&texts));
+        assert_eq!(first[0].len(), 16);
+        let norm: f32 = first[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert!(first[1].iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn falls_back_to_hashing_without_endpoint() {
+        let settings = EmbeddingSettings::new(None, None, None);
+        let embedder = settings.embedder_for("text-embedding-3-small", 384);
+        assert_eq!(embedder.model(), HASHING_MODEL);
+        assert_eq!(embedder.dimensions(), 384);
+
+        let settings = EmbeddingSettings::new(Some("http://localhost:8080/v1".into()), None, None);
+        assert_eq!(
+            settings.embedder_for("bge-small-en-v1.5", 384).model(),
+            "bge-small-en-v1.5"
+        );
+        assert_eq!(
+            settings.embedder_for(HASHING_MODEL, 384).model(),
+            HASHING_MODEL
+        );
+    }
+
+    #[tokio::test]
+    async fn openai_compatible_embedder_orders_and_normalizes() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            // Urutan sengaja dibalik; embedder harus mengurutkan berdasarkan index
+            let body =
+                r#"{"data":[{"index":1,"embedding":[0.0,2.0]},{"index":0,"embedding":[3.0,4.0]}]}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let settings =
+            EmbeddingSettings::new(Some(format!("http://{}/v1", addr)), Some("k".into()), None);
+        let embedder = settings.embedder_for("mini-lm", 2);
+        let vectors = embedder
+            .embed(&["a".to_string(), "b".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![0.6, 0.8], vec![0.0, 1.0]]);
+    }
+}
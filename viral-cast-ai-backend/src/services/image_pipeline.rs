@@ -0,0 +1,396 @@
+use std::io::Cursor;
+
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{ColorType, DynamicImage, ImageFormat, ImageOutputFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::services::blob_store::BlobStore;
+
+// ID: Pipeline gambar upload: verifikasi isi (magic bytes), koreksi orientasi EXIF, buang
+// metadata (EXIF/GPS) dengan decode + encode ulang, perkecil original, buat versi WebP dan
+// thumbnail persegi berukuran tetap.
+// EN: Upload image pipeline: verify real content (magic bytes), apply EXIF orientation, strip
+// metadata (EXIF/GPS) by decoding and re-encoding, downscale the original, and produce WebP
+// versions plus fixed-size square thumbnails.
+
+pub const MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+pub const ORIGINAL_MAX_DIMENSION: u32 = 1600;
+pub const THUMBNAIL_SIZES: [u32; 2] = [160, 480];
+// Tolak gambar raksasa sebelum decode (decompression bomb)
+const MAX_SOURCE_PIXELS: u64 = 40_000_000;
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImagePipelineError {
+    UnsupportedFormat,
+    TooLarge { width: u32, height: u32 },
+    Decode(String),
+    Encode(String),
+}
+
+impl std::fmt::Display for ImagePipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImagePipelineError::UnsupportedFormat => write!(
+                f,
+                "File content is not a valid image. Only JPG, PNG, GIF, and WebP are allowed"
+            ),
+            ImagePipelineError::TooLarge { width, height } => {
+                write!(f, "Image dimensions too large: {}x{}", width, height)
+            }
+            ImagePipelineError::Decode(e) => write!(f, "Failed to decode image: {}", e),
+            ImagePipelineError::Encode(e) => write!(f, "Failed to encode image: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EncodedImage {
+    pub variant: String, // 'original' | 'thumb_160' | ...
+    pub extension: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub source_format: &'static str,
+    pub files: Vec<EncodedImage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailVariant {
+    pub size: u32,
+    pub url: String,
+    pub webp_url: String,
+}
+
+// Disimpan sebagai JSONB pada entitas (products.image_variants, dst.)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageVariants {
+    pub original_url: String,
+    pub webp_url: String,
+    pub thumbnails: Vec<ThumbnailVariant>,
+}
+
+fn format_name(format: ImageFormat) -> Option<&'static str> {
+    match format {
+        ImageFormat::Jpeg => Some("jpeg"),
+        ImageFormat::Png => Some("png"),
+        ImageFormat::Gif => Some("gif"),
+        ImageFormat::WebP => Some("webp"),
+        _ => None,
+    }
+}
+
+/// ID: Baca tag Orientation (0x0112) dari segmen APP1/EXIF sebuah JPEG.
+/// EN: Read the Orientation tag (0x0112) from a JPEG's APP1/EXIF segment.
+pub fn jpeg_exif_orientation(data: &[u8]) -> Option<u16> {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // SOS / EOI: metadata sudah lewat
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment_end = (pos + 2 + length).min(data.len());
+        let segment = &data[(pos + 4).min(segment_end)..segment_end];
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return tiff_orientation(&segment[6..]);
+        }
+        pos = pos + 2 + length;
+    }
+    None
+}
+
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?];
+        Some(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes = [
+            *tiff.get(offset)?,
+            *tiff.get(offset + 1)?,
+            *tiff.get(offset + 2)?,
+            *tiff.get(offset + 3)?,
+        ];
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    let ifd0 = read_u32(4)? as usize;
+    let entries = read_u16(ifd0)? as usize;
+    (0..entries)
+        .map(|i| ifd0 + 2 + i * 12)
+        .find(|entry| read_u16(*entry) == Some(0x0112))
+        .and_then(|entry| read_u16(entry + 8))
+}
+
+fn apply_orientation(img: DynamicImage, orientation: u16) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+fn encode_raster(img: &DynamicImage) -> Result<(&'static str, Vec<u8>), ImagePipelineError> {
+    let mut buffer = Cursor::new(Vec::new());
+    // Alpha dipertahankan sebagai PNG, selain itu JPEG
+    let extension = if img.color().has_alpha() {
+        img.write_to(&mut buffer, ImageOutputFormat::Png)
+            .map_err(|e| ImagePipelineError::Encode(e.to_string()))?;
+        "png"
+    } else {
+        DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_to(&mut buffer, ImageOutputFormat::Jpeg(JPEG_QUALITY))
+            .map_err(|e| ImagePipelineError::Encode(e.to_string()))?;
+        "jpg"
+    };
+    Ok((extension, buffer.into_inner()))
+}
+
+fn encode_webp(img: &DynamicImage) -> Result<Vec<u8>, ImagePipelineError> {
+    let rgba = img.to_rgba8();
+    let mut buffer = Vec::new();
+    WebPEncoder::new_lossless(&mut buffer)
+        .encode(rgba.as_raw(), rgba.width(), rgba.height(), ColorType::Rgba8)
+        .map_err(|e| ImagePipelineError::Encode(e.to_string()))?;
+    Ok(buffer)
+}
+
+fn push_variant(
+    files: &mut Vec<EncodedImage>,
+    variant: &str,
+    img: &DynamicImage,
+) -> Result<(), ImagePipelineError> {
+    let (extension, bytes) = encode_raster(img)?;
+    files.push(EncodedImage {
+        variant: variant.to_string(),
+        extension,
+        width: img.width(),
+        height: img.height(),
+        bytes,
+    });
+    files.push(EncodedImage {
+        variant: variant.to_string(),
+        extension: "webp",
+        width: img.width(),
+        height: img.height(),
+        bytes: encode_webp(img)?,
+    });
+    Ok(())
+}
+
+/// ID: Proses bytes upload menjadi original yang diperkecil + thumbnail, masing-masing dalam
+/// format raster (JPEG/PNG) dan WebP. Operasi CPU-bound: panggil lewat spawn_blocking.
+/// EN: Process uploaded bytes into a downscaled original plus thumbnails, each as a raster
+/// (JPEG/PNG) and a WebP file. CPU-bound: call it through spawn_blocking.
+pub fn process_image(data: &[u8]) -> Result<ProcessedImage, ImagePipelineError> {
+    let format = image::guess_format(data).map_err(|_| ImagePipelineError::UnsupportedFormat)?;
+    let source_format = format_name(format).ok_or(ImagePipelineError::UnsupportedFormat)?;
+
+    let (width, height) = image::io::Reader::with_format(Cursor::new(data), format)
+        .into_dimensions()
+        .map_err(|e| ImagePipelineError::Decode(e.to_string()))?;
+    if width as u64 * height as u64 > MAX_SOURCE_PIXELS {
+        return Err(ImagePipelineError::TooLarge { width, height });
+    }
+
+    let decoded = image::load_from_memory_with_format(data, format)
+        .map_err(|e| ImagePipelineError::Decode(e.to_string()))?;
+    let oriented = match jpeg_exif_orientation(data) {
+        Some(orientation) if format == ImageFormat::Jpeg => apply_orientation(decoded, orientation),
+        _ => decoded,
+    };
+
+    let original = if oriented.width() > ORIGINAL_MAX_DIMENSION
+        || oriented.height() > ORIGINAL_MAX_DIMENSION
+    {
+        oriented.resize(
+            ORIGINAL_MAX_DIMENSION,
+            ORIGINAL_MAX_DIMENSION,
+            FilterType::Lanczos3,
+        )
+    } else {
+        oriented
+    };
+
+    let mut files = Vec::with_capacity(2 + THUMBNAIL_SIZES.len() * 2);
+    push_variant(&mut files, "original", &original)?;
+    for size in THUMBNAIL_SIZES {
+        let thumbnail = original.resize_to_fill(size, size, FilterType::Triangle);
+        push_variant(&mut files, &format!("thumb_{}", size), &thumbnail)?;
+    }
+
+    Ok(ProcessedImage {
+        source_format,
+        files,
+    })
+}
+
+impl EncodedImage {
+    pub fn file_name(&self, stem: &str) -> String {
+        if self.variant == "original" {
+            format!("{}.{}", stem, self.extension)
+        } else {
+            format!("{}_{}.{}", stem, self.variant, self.extension)
+        }
+    }
+}
+
+impl ProcessedImage {
+    /// URL semua varian dengan prefix publik, mis. "/uploads/products"
+    pub fn variants(&self, url_prefix: &str, stem: &str) -> Option<ImageVariants> {
+        let original = self
+            .files
+            .iter()
+            .find(|f| f.variant == "original" && f.extension != "webp")?;
+        variants_for_url(&format!("{}/{}", url_prefix, original.file_name(stem)))
+    }
+}
+
+/// ID: Turunkan URL varian dari URL original hasil pipeline (penamaan deterministik).
+/// EN: Derive the variant URLs from a pipeline original URL (naming is deterministic).
+pub fn variants_for_url(original_url: &str) -> Option<ImageVariants> {
+    let (base, extension) = original_url.rsplit_once('.')?;
+    if extension != "jpg" && extension != "png" {
+        return None;
+    }
+
+    Some(ImageVariants {
+        original_url: original_url.to_string(),
+        webp_url: format!("{}.webp", base),
+        thumbnails: THUMBNAIL_SIZES
+            .iter()
+            .map(|size| ThumbnailVariant {
+                size: *size,
+                url: format!("{}_thumb_{}.{}", base, size, extension),
+                webp_url: format!("{}_thumb_{}.webp", base, size),
+            })
+            .collect(),
+    })
+}
+
+/// ID: Varian untuk URL upload di blob store, hanya jika file thumbnail memang ada (upload lama
+/// sebelum pipeline tidak punya varian).
+/// EN: Variants for an upload URL in the blob store, only when the thumbnail files exist (legacy
+/// uploads made before the pipeline have none).
+pub async fn stored_variants_for_url(
+    blob_store: &dyn BlobStore,
+    original_url: &str,
+) -> Option<ImageVariants> {
+    let variants = variants_for_url(original_url)?;
+    let thumbnail = variants.thumbnails.first()?;
+    let key = blob_store.key_from_url(&thumbnail.webp_url)?;
+    match blob_store.exists(&key).await {
+        Ok(true) => Some(variants),
+        _ => None,
+    }
+}
+
+/// Seperti stored_variants_for_url, dalam bentuk JSON untuk kolom *_variants
+pub async fn stored_variants_json(
+    blob_store: &dyn BlobStore,
+    original_url: Option<&str>,
+) -> Option<serde_json::Value> {
+    let variants = stored_variants_for_url(blob_store, original_url?).await?;
+    serde_json::to_value(variants).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(img: &DynamicImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut buffer = Cursor::new(Vec::new());
+        img.write_to(&mut buffer, format).unwrap();
+        buffer.into_inner()
+    }
+
+    // JPEG minimal: SOI + APP1 (Exif, TIFF big-endian, IFD0 dengan satu tag Orientation)
+    fn jpeg_with_orientation(orientation: u16) -> Vec<u8> {
+        let mut tiff = b"MM\0\x2a\0\0\0\x08".to_vec();
+        tiff.extend_from_slice(&1u16.to_be_bytes());
+        tiff.extend_from_slice(&0x0112u16.to_be_bytes());
+        tiff.extend_from_slice(&3u16.to_be_bytes());
+        tiff.extend_from_slice(&1u32.to_be_bytes());
+        tiff.extend_from_slice(&orientation.to_be_bytes());
+        tiff.extend_from_slice(&[0, 0]);
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&tiff);
+
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(&((app1.len() + 2) as u16).to_be_bytes());
+        data.extend_from_slice(&app1);
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn reads_exif_orientation() {
+        assert_eq!(jpeg_exif_orientation(&jpeg_with_orientation(6)), Some(6));
+        assert_eq!(jpeg_exif_orientation(b"not a jpeg"), None);
+    }
+
+    #[test]
+    fn rejects_non_image_content() {
+        assert_eq!(
+            process_image(b"<?php echo 'hi'; ?>").unwrap_err(),
+            ImagePipelineError::UnsupportedFormat
+        );
+    }
+
+    #[test]
+    fn downscales_and_builds_all_variants() {
+        let png = encode(&DynamicImage::new_rgb8(3200, 1600), ImageOutputFormat::Png);
+        let processed = process_image(&png).unwrap();
+        assert_eq!(processed.source_format, "png");
+
+        let original = &processed.files[0];
+        assert_eq!((original.width, original.height), (1600, 800));
+        assert_eq!(original.extension, "jpg");
+        assert_eq!(processed.files.len(), 2 + THUMBNAIL_SIZES.len() * 2);
+
+        let variants = processed.variants("/uploads/products", "abc").unwrap();
+        assert_eq!(variants.original_url, "/uploads/products/abc.jpg");
+        assert_eq!(variants.webp_url, "/uploads/products/abc.webp");
+        assert_eq!(
+            variants.thumbnails[0].url,
+            "/uploads/products/abc_thumb_160.jpg"
+        );
+        let thumb = processed
+            .files
+            .iter()
+            .find(|f| f.variant == "thumb_160")
+            .unwrap();
+        assert_eq!((thumb.width, thumb.height), (160, 160));
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use redis::AsyncCommands;
+use tokio::sync::Mutex;
+
+use crate::config::config::Config;
+
+// ID: Pembatasan percobaan login. Kegagalan dihitung per username dan per IP dalam jendela
+// waktu tetap di Redis (fallback ke session store in-memory), respons gagal diperlambat
+// secara progresif, dan username/IP dikunci sementara setelah ambang kegagalan tercapai.
+// EN: Login attempt throttling. Failures are counted per username and per IP in a fixed
+// window in Redis (falling back to the in-memory session store), failed responses are
+// slowed down progressively, and the username/IP is locked temporarily once the failure
+// threshold is reached.
+
+const FAILURES_PREFIX: &str = "login_failures";
+const LOCK_PREFIX: &str = "login_lock";
+// Dua kegagalan pertama tanpa jeda agar salah ketik tidak terasa lambat
+const FREE_FAILURES: u32 = 2;
+const BASE_DELAY_MS: u64 = 500;
+const MAX_DELAY_MS: u64 = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoginThrottlePolicy {
+    /// Kegagalan per username sebelum akun dikunci
+    pub max_failures: u32,
+    /// Kegagalan per IP sebelum IP dikunci (lebih longgar: satu outlet bisa berbagi IP)
+    pub ip_max_failures: u32,
+    pub failure_window_secs: u64,
+    pub lockout_secs: u64,
+}
+
+impl LoginThrottlePolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_failures: config.login_max_failures,
+            ip_max_failures: config.login_ip_max_failures,
+            failure_window_secs: config.login_failure_window_seconds,
+            lockout_secs: config.login_lockout_seconds,
+        }
+    }
+
+    /// ID: Jeda sebelum membalas kegagalan ke-`failures`: 0, 0, 0.5s, 1s, 2s, ... maks 5s.
+    /// EN: Delay before answering the `failures`-th failure: 0, 0, 0.5s, 1s, 2s, ... capped at 5s.
+    pub fn delay_for(&self, failures: u32) -> Duration {
+        if failures <= FREE_FAILURES {
+            return Duration::ZERO;
+        }
+        let exponent = (failures - FREE_FAILURES - 1).min(16);
+        Duration::from_millis((BASE_DELAY_MS << exponent).min(MAX_DELAY_MS))
+    }
+}
+
+/// ID: Hasil pencatatan satu login gagal.
+/// EN: Outcome of recording one failed login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailedLogin {
+    pub username_failures: u32,
+    pub ip_failures: u32,
+    pub delay: Duration,
+    pub username_locked: bool,
+    pub ip_locked: bool,
+}
+
+pub struct LoginThrottle<'a> {
+    redis_client: &'a redis::Client,
+    fallback: &'a Mutex<HashMap<String, String>>,
+    policy: LoginThrottlePolicy,
+}
+
+impl<'a> LoginThrottle<'a> {
+    pub fn new(
+        redis_client: &'a redis::Client,
+        fallback: &'a Mutex<HashMap<String, String>>,
+        policy: LoginThrottlePolicy,
+    ) -> Self {
+        Self {
+            redis_client,
+            fallback,
+            policy,
+        }
+    }
+
+    /// ID: Sisa detik kuncian untuk username atau IP ini; `None` bila boleh mencoba login.
+    /// EN: Remaining lockout seconds for this username or IP; `None` when login may proceed.
+    pub async fn locked_for(&self, username: &str, ip_address: Option<&str>) -> Option<u64> {
+        let mut remaining = self
+            .lock_remaining(&username_key(LOCK_PREFIX, username))
+            .await;
+        if let Some(ip) = ip_address {
+            let ip_remaining = self.lock_remaining(&ip_key(LOCK_PREFIX, ip)).await;
+            remaining = remaining.max(ip_remaining);
+        }
+        remaining
+    }
+
+    pub async fn record_failure(&self, username: &str, ip_address: Option<&str>) -> FailedLogin {
+        let username_failures = self
+            .increment(&username_key(FAILURES_PREFIX, username))
+            .await;
+        let ip_failures = match ip_address {
+            Some(ip) => self.increment(&ip_key(FAILURES_PREFIX, ip)).await,
+            None => 0,
+        };
+
+        let username_locked = username_failures >= self.policy.max_failures;
+        if username_locked {
+            self.lock(&username_key(LOCK_PREFIX, username)).await;
+        }
+        let ip_locked = ip_address.is_some() && ip_failures >= self.policy.ip_max_failures;
+        if let (true, Some(ip)) = (ip_locked, ip_address) {
+            self.lock(&ip_key(LOCK_PREFIX, ip)).await;
+        }
+
+        FailedLogin {
+            username_failures,
+            ip_failures,
+            delay: self.policy.delay_for(username_failures.max(ip_failures)),
+            username_locked,
+            ip_locked,
+        }
+    }
+
+    /// ID: Login berhasil: reset hitungan username (hitungan IP tetap berjalan).
+    /// EN: Successful login: reset the username counter (the IP counter keeps running).
+    pub async fn record_success(&self, username: &str) {
+        self.remove(&[username_key(FAILURES_PREFIX, username)])
+            .await;
+    }
+
+    /// ID: Buka kunci akun oleh admin: hapus kuncian dan hitungan kegagalan username.
+    /// EN: Admin unlock: drop the username's lockout and failure counter.
+    pub async fn unlock(&self, username: &str) {
+        self.remove(&[
+            username_key(LOCK_PREFIX, username),
+            username_key(FAILURES_PREFIX, username),
+        ])
+        .await;
+    }
+
+    async fn lock_remaining(&self, key: &str) -> Option<u64> {
+        if let Ok(mut conn) = self.redis_client.get_multiplexed_async_connection().await {
+            match conn.ttl::<_, i64>(key).await {
+                Ok(ttl) if ttl > 0 => return Some(ttl as u64),
+                Ok(_) => return None,
+                Err(e) => tracing::warn!("Redis ttl failed, using in-memory store: {:?}", e),
+            }
+        }
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let store = self.fallback.lock().await;
+        store
+            .get(key)
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|locked_until| *locked_until > now_ms)
+            .map(|locked_until| ((locked_until - now_ms) as u64).div_ceil(1000))
+    }
+
+    async fn increment(&self, key: &str) -> u32 {
+        if let Ok(mut conn) = self.redis_client.get_multiplexed_async_connection().await {
+            // Jendela tetap: SET NX EX membuat counter ber-TTL hanya bila belum ada, lalu INCR,
+            // dalam satu MULTI/EXEC sehingga counter tidak pernah tertinggal tanpa TTL
+            let result = redis::pipe()
+                .atomic()
+                .cmd("SET")
+                .arg(key)
+                .arg(0)
+                .arg("NX")
+                .arg("EX")
+                .arg(self.policy.failure_window_secs)
+                .ignore()
+                .cmd("INCR")
+                .arg(key)
+                .query_async::<_, (u32,)>(&mut conn)
+                .await;
+            match result {
+                Ok((count,)) => return count,
+                Err(e) => tracing::warn!("Redis incr failed, using in-memory store: {:?}", e),
+            }
+        }
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let window_ms = (self.policy.failure_window_secs * 1000) as i64;
+        let mut store = self.fallback.lock().await;
+        let (count, expires_at) = next_fallback_count(store.get(key), now_ms, window_ms);
+        store.insert(key.to_string(), format!("{}:{}", count, expires_at));
+        count
+    }
+
+    async fn lock(&self, key: &str) {
+        if let Ok(mut conn) = self.redis_client.get_multiplexed_async_connection().await {
+            match conn
+                .set_ex::<_, _, ()>(key, "1", self.policy.lockout_secs)
+                .await
+            {
+                Ok(()) => return,
+                Err(e) => tracing::warn!("Redis set_ex failed, using in-memory store: {:?}", e),
+            }
+        }
+        let locked_until =
+            chrono::Utc::now().timestamp_millis() + (self.policy.lockout_secs * 1000) as i64;
+        let mut store = self.fallback.lock().await;
+        store.insert(key.to_string(), locked_until.to_string());
+    }
+
+    async fn remove(&self, keys: &[String]) {
+        if let Ok(mut conn) = self.redis_client.get_multiplexed_async_connection().await {
+            if let Err(e) = conn.del::<_, ()>(keys).await {
+                tracing::warn!("Redis del failed for login throttle keys: {:?}", e);
+            }
+        }
+        let mut store = self.fallback.lock().await;
+        for key in keys {
+            store.remove(key);
+        }
+    }
+}
+
+// Username dinormalisasi agar "Kasir1" dan "kasir1" berbagi hitungan
+fn username_key(prefix: &str, username: &str) -> String {
+    format!("{}:user:{}", prefix, username.trim().to_lowercase())
+}
+
+fn ip_key(prefix: &str, ip_address: &str) -> String {
+    format!("{}:ip:{}", prefix, ip_address)
+}
+
+// Nilai fallback in-memory: "<jumlah>:<kedaluwarsa_ms>"
+fn next_fallback_count(current: Option<&String>, now_ms: i64, window_ms: i64) -> (u32, i64) {
+    let active = current.and_then(|value| {
+        let (count, expires_at) = value.split_once(':')?;
+        let count = count.parse::<u32>().ok()?;
+        let expires_at = expires_at.parse::<i64>().ok()?;
+        (expires_at > now_ms).then_some((count, expires_at))
+    });
+    match active {
+        Some((count, expires_at)) => (count.saturating_add(1), expires_at),
+        None => (1, now_ms + window_ms),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> LoginThrottlePolicy {
+        LoginThrottlePolicy {
+            max_failures: 5,
+            ip_max_failures: 20,
+            failure_window_secs: 900,
+            lockout_secs: 900,
+        }
+    }
+
+    #[test]
+    fn delay_grows_after_free_failures_and_is_capped() {
+        let policy = policy();
+        assert_eq!(policy.delay_for(1), Duration::ZERO);
+        assert_eq!(policy.delay_for(2), Duration::ZERO);
+        assert_eq!(policy.delay_for(3), Duration::from_millis(500));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(1_000));
+        assert_eq!(policy.delay_for(5), Duration::from_millis(2_000));
+        assert_eq!(policy.delay_for(7), Duration::from_millis(5_000));
+        assert_eq!(policy.delay_for(u32::MAX), Duration::from_millis(5_000));
+    }
+
+    #[test]
+    fn username_keys_are_case_insensitive() {
+        assert_eq!(
+            username_key(FAILURES_PREFIX, " Kasir1 "),
+            username_key(FAILURES_PREFIX, "kasir1")
+        );
+        assert_ne!(
+            username_key(FAILURES_PREFIX, "kasir1"),
+            ip_key(FAILURES_PREFIX, "kasir1")
+        );
+    }
+
+    #[test]
+    fn fallback_count_restarts_after_window() {
+        let now = 1_000_000;
+        assert_eq!(next_fallback_count(None, now, 60_000), (1, now + 60_000));
+
+        let current = format!("3:{}", now + 10);
+        assert_eq!(
+            next_fallback_count(Some(&current), now, 60_000),
+            (4, now + 10)
+        );
+
+        let expired = format!("3:{}", now);
+        assert_eq!(
+            next_fallback_count(Some(&expired), now, 60_000),
+            (1, now + 60_000)
+        );
+
+        let garbage = "not-a-counter".to_string();
+        assert_eq!(
+            next_fallback_count(Some(&garbage), now, 60_000),
+            (1, now + 60_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn fallback_locks_username_after_max_failures() {
+        // Port 1 tidak pernah menerima koneksi Redis, jadi semua operasi memakai fallback
+        let redis_client = redis::Client::open("redis://127.0.0.1:1/").unwrap();
+        let fallback = Mutex::new(HashMap::new());
+        let throttle = LoginThrottle::new(&redis_client, &fallback, policy());
+
+        for attempt in 1..5 {
+            let failed = throttle.record_failure("kasir1", Some("10.0.0.7")).await;
+            assert_eq!(failed.username_failures, attempt);
+            assert!(!failed.username_locked);
+        }
+        assert_eq!(throttle.locked_for("kasir1", Some("10.0.0.7")).await, None);
+
+        let failed = throttle.record_failure("Kasir1", Some("10.0.0.7")).await;
+        assert!(failed.username_locked);
+        assert!(!failed.ip_locked);
+        assert_eq!(throttle.locked_for("kasir1", None).await, Some(900));
+        // IP lain tetap terkunci untuk username yang sama
+        assert_eq!(
+            throttle.locked_for("kasir1", Some("10.0.0.8")).await,
+            Some(900)
+        );
+        assert_eq!(throttle.locked_for("kasir2", Some("10.0.0.7")).await, None);
+
+        throttle.unlock("KASIR1").await;
+        assert_eq!(throttle.locked_for("kasir1", None).await, None);
+        let failed = throttle.record_failure("kasir1", None).await;
+        assert_eq!(failed.username_failures, 1);
+    }
+}
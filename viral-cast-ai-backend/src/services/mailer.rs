@@ -0,0 +1,324 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+
+// ID: Abstraksi pengiriman email transaksional (verifikasi email, reset password).
+// Backend "smtp" mengirim lewat server SMTP; backend "log" (default) menulis email ke
+// direktori sebagai file .eml dan ke log, sehingga alur bisa diuji lokal tanpa server email.
+// EN: Transactional email abstraction (email verification, password reset).
+// The "smtp" backend sends through an SMTP server; the "log" backend (default) writes each
+// email to a directory as an .eml file and to the log so flows can be tested locally without
+// a mail server.
+
+const DEFAULT_FROM: &str = "Viral Cast AI <no-reply@localhost>";
+const DEFAULT_MAIL_DIR: &str = "logs/mail";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MailerError {
+    InvalidAddress(String),
+    Send(String),
+    Io(String),
+    Config(String),
+}
+
+impl std::fmt::Display for MailerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MailerError::InvalidAddress(e) => write!(f, "Invalid email address: {}", e),
+            MailerError::Send(e) => write!(f, "Failed to send email: {}", e),
+            MailerError::Io(e) => write!(f, "Mail file I/O error: {}", e),
+            MailerError::Config(e) => write!(f, "Mailer misconfigured: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MailerError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Nama backend: "log" | "smtp"
+    fn backend(&self) -> &'static str;
+
+    async fn send(&self, message: &MailMessage) -> Result<(), MailerError>;
+}
+
+fn build_message(from: &Mailbox, message: &MailMessage) -> Result<Message, MailerError> {
+    let to: Mailbox = message
+        .to
+        .parse()
+        .map_err(|e| MailerError::InvalidAddress(format!("{}: {}", message.to, e)))?;
+    Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(message.subject.clone())
+        .body(message.text_body.clone())
+        .map_err(|e| MailerError::InvalidAddress(e.to_string()))
+}
+
+// =============== LOG / FILE =================
+
+pub struct LogMailer {
+    from: Mailbox,
+    dir: PathBuf,
+}
+
+impl LogMailer {
+    pub fn new(from: Mailbox, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            from,
+            dir: dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    fn backend(&self) -> &'static str {
+        "log"
+    }
+
+    async fn send(&self, message: &MailMessage) -> Result<(), MailerError> {
+        let email = build_message(&self.from, message)?;
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| MailerError::Io(e.to_string()))?;
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%3f"),
+            uuid::Uuid::new_v4().simple()
+        ));
+        tokio::fs::write(&path, email.formatted())
+            .await
+            .map_err(|e| MailerError::Io(e.to_string()))?;
+        tracing::info!(
+            "📧 [mail:log] to={} subject=\"{}\" file={}\n{}",
+            message.to,
+            message.subject,
+            path.display(),
+            message.text_body
+        );
+        Ok(())
+    }
+}
+
+// =============== SMTP =================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Koneksi biasa lalu STARTTLS (port 587)
+    StartTls,
+    /// TLS sejak awal (port 465)
+    Tls,
+    /// Tanpa TLS, hanya untuk relay lokal (mis. MailHog / Mailpit)
+    None,
+}
+
+impl SmtpSecurity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "starttls" => Some(Self::StartTls),
+            "tls" | "ssl" => Some(Self::Tls),
+            "none" | "plain" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            Self::StartTls => 587,
+            Self::Tls => 465,
+            Self::None => 25,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub security: SmtpSecurity,
+}
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(from: Mailbox, config: SmtpConfig) -> Result<Self, MailerError> {
+        let builder = match config.security {
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.host,
+            )),
+        }
+        .map_err(|e| MailerError::Config(e.to_string()))?;
+        let mut builder = builder.port(config.port.unwrap_or(config.security.default_port()));
+        if let (Some(username), Some(password)) = (config.username, config.password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+        Ok(Self {
+            from,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    fn backend(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn send(&self, message: &MailMessage) -> Result<(), MailerError> {
+        let email = build_message(&self.from, message)?;
+        self.transport
+            .send(email)
+            .await
+            .map(|_| ())
+            .map_err(|e| MailerError::Send(e.to_string()))
+    }
+}
+
+// =============== CONFIG =================
+
+#[derive(Debug, Clone)]
+pub struct MailerConfig {
+    pub backend: String,
+    pub from: String,
+    pub file_dir: String,
+    pub smtp: Option<SmtpConfig>,
+}
+
+impl MailerConfig {
+    /// ID: Baca konfigurasi dari env. MAIL_BACKEND=log (default) | smtp.
+    /// EN: Read configuration from env. MAIL_BACKEND=log (default) | smtp.
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let smtp = var("SMTP_HOST").map(|host| SmtpConfig {
+            host,
+            port: var("SMTP_PORT").and_then(|v| v.parse::<u16>().ok()),
+            username: var("SMTP_USERNAME"),
+            password: var("SMTP_PASSWORD"),
+            security: var("SMTP_SECURITY")
+                .and_then(|v| SmtpSecurity::parse(&v))
+                .unwrap_or(SmtpSecurity::StartTls),
+        });
+
+        Self {
+            backend: var("MAIL_BACKEND")
+                .unwrap_or_else(|| "log".to_string())
+                .to_lowercase(),
+            from: var("MAIL_FROM").unwrap_or_else(|| DEFAULT_FROM.to_string()),
+            file_dir: var("MAIL_FILE_DIR").unwrap_or_else(|| DEFAULT_MAIL_DIR.to_string()),
+            smtp,
+        }
+    }
+
+    pub fn build(&self) -> Result<Arc<dyn Mailer>, MailerError> {
+        let from: Mailbox = self
+            .from
+            .parse()
+            .map_err(|e| MailerError::Config(format!("MAIL_FROM '{}': {}", self.from, e)))?;
+        match self.backend.as_str() {
+            "log" => Ok(Arc::new(LogMailer::new(from, &self.file_dir))),
+            "smtp" => {
+                let config = self.smtp.clone().ok_or_else(|| {
+                    MailerError::Config("SMTP_HOST must be set for MAIL_BACKEND=smtp".to_string())
+                })?;
+                Ok(Arc::new(SmtpMailer::new(from, config)?))
+            }
+            other => Err(MailerError::Config(format!(
+                "Unknown MAIL_BACKEND '{}', expected 'log' or 'smtp'",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(to: &str) -> MailMessage {
+        MailMessage {
+            to: to.to_string(),
+            subject: "Reset password".to_string(),
+            text_body: "Buka tautan berikut untuk reset password.".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn log_mailer_writes_eml_file() {
+        let dir = std::env::temp_dir().join(format!("vcai-mail-{}", uuid::Uuid::new_v4()));
+        let mailer = LogMailer::new(DEFAULT_FROM.parse().unwrap(), &dir);
+
+        mailer.send(&message("owner@example.com")).await.unwrap();
+
+        let mut entries = std::fs::read_dir(&dir).unwrap();
+        let path = entries.next().unwrap().unwrap().path();
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some("eml"));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("To: owner@example.com"));
+        assert!(content.contains("Subject: Reset password"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalid_recipient_is_rejected() {
+        let dir = std::env::temp_dir().join(format!("vcai-mail-{}", uuid::Uuid::new_v4()));
+        let mailer = LogMailer::new(DEFAULT_FROM.parse().unwrap(), &dir);
+        let err = mailer.send(&message("not-an-email")).await.unwrap_err();
+        assert!(matches!(err, MailerError::InvalidAddress(_)));
+    }
+
+    #[test]
+    fn smtp_security_parses_aliases() {
+        assert_eq!(
+            SmtpSecurity::parse("STARTTLS"),
+            Some(SmtpSecurity::StartTls)
+        );
+        assert_eq!(SmtpSecurity::parse("ssl"), Some(SmtpSecurity::Tls));
+        assert_eq!(SmtpSecurity::parse("none"), Some(SmtpSecurity::None));
+        assert_eq!(SmtpSecurity::parse("smtps?"), None);
+        assert_eq!(SmtpSecurity::Tls.default_port(), 465);
+    }
+
+    #[test]
+    fn build_rejects_unknown_backend_and_missing_smtp_host() {
+        let mut config = MailerConfig {
+            backend: "pigeon".to_string(),
+            from: DEFAULT_FROM.to_string(),
+            file_dir: DEFAULT_MAIL_DIR.to_string(),
+            smtp: None,
+        };
+        assert!(matches!(config.build(), Err(MailerError::Config(_))));
+        config.backend = "smtp".to_string();
+        assert!(matches!(config.build(), Err(MailerError::Config(_))));
+        config.backend = "log".to_string();
+        assert_eq!(config.build().unwrap().backend(), "log");
+    }
+}
@@ -1,10 +1,19 @@
 use anyhow::Result;
+use milvus::collection::SearchOption;
+use milvus::index::{IndexParams, IndexType, MetricType};
+use milvus::proto::common::KeyValuePair;
 use milvus::schema;
+use milvus::value::Value;
 use milvus::Client as MilvusClient;
+use std::collections::HashMap;
 use uuid::Uuid;
 
-// Ensure collection for RAG chunks exists with INT64 PK and FLOAT_VECTOR field
-pub async fn ensure_rag_collection(client: &mut MilvusClient, collection_name: &str) -> Result<()> {
+// Ensure collection for RAG chunks exists with INT64 PK and FLOAT_VECTOR field of `dim`
+pub async fn ensure_rag_collection(
+    client: &mut MilvusClient,
+    collection_name: &str,
+    dim: i64,
+) -> Result<()> {
     // Build schema: id (INT64, primary), embedding (FLOAT_VECTOR)
     let id_field = schema::FieldSchema {
         field_id: 1,
@@ -23,7 +32,10 @@ pub async fn ensure_rag_collection(client: &mut MilvusClient, collection_name: &
         is_primary_key: false,
         description: "embedding vector".to_string(),
         data_type: schema::DataType::FloatVector as i32,
-        type_params: Vec::new(),
+        type_params: vec![KeyValuePair {
+            key: "dim".to_string(),
+            value: dim.to_string(),
+        }],
         index_params: Vec::new(),
         auto_id: false,
     };
@@ -46,6 +58,68 @@ pub async fn ensure_rag_collection(client: &mut MilvusClient, collection_name: &
         }
     }
 
+    // Search butuh index + koleksi ter-load; index yang sudah ada juga diabaikan
+    let index_params = IndexParams::new(
+        "embedding_ip".to_string(),
+        IndexType::IvfFlat,
+        MetricType::IP,
+        HashMap::from([("nlist".to_string(), "1024".to_string())]),
+    );
+    if let Err(_e) = client
+        .create_index(collection_name, "embedding", index_params)
+        .await
+    {
+        // Assume already indexed; proceed
+    }
+    client.load_collection(collection_name, None).await?;
+
+    Ok(())
+}
+
+/// ID: Buang lalu buat ulang koleksi dengan dimensi baru; vektor berdimensi lama tidak bisa
+/// dicampur. Partisi dibuat ulang saat insert berikutnya.
+/// EN: Drop and recreate the collection with a new dimension; vectors of the old dimension
+/// cannot be mixed in. Partitions are recreated by the next insert.
+pub async fn recreate_rag_collection(
+    client: &mut MilvusClient,
+    collection_name: &str,
+    dim: i64,
+) -> Result<()> {
+    if client.has_collection(collection_name).await? {
+        client.drop_collection(collection_name).await?;
+    }
+    ensure_rag_collection(client, collection_name, dim).await
+}
+
+// Partisi Milvus per basis pengetahuan: dokumen platform di `platform`, dokumen toko di
+// `store_<uuid>`. Vektor sebelum scoping per toko masih ada di partisi bawaan `_default`.
+pub const PLATFORM_PARTITION: &str = "platform";
+pub const LEGACY_PARTITION: &str = "_default";
+
+/// ID: Nama partisi untuk dokumen milik `store_uuid` (None = dokumen platform).
+/// EN: Partition name for a document owned by `store_uuid` (None = platform document).
+pub fn document_partition(store_uuid: Option<Uuid>) -> String {
+    match store_uuid {
+        Some(store_uuid) => format!("store_{}", store_uuid.simple()),
+        None => PLATFORM_PARTITION.to_string(),
+    }
+}
+
+// Create partition if missing; like ensure_rag_collection, "already exists" errors are ignored
+async fn ensure_partition(
+    client: &mut MilvusClient,
+    collection_name: &str,
+    partition_name: &str,
+) -> Result<()> {
+    if partition_name == LEGACY_PARTITION {
+        return Ok(());
+    }
+    if let Err(_e) = client
+        .create_partition(collection_name.to_string(), partition_name.to_string())
+        .await
+    {
+        // Assume already exists; proceed
+    }
     Ok(())
 }
 
@@ -56,10 +130,11 @@ pub fn uuid_to_i64(uuid: &Uuid) -> i64 {
     (b & 0xFFFF_FFFF_FFFF_FFFF) as i64
 }
 
-// Upsert chunk embeddings into Milvus collection
+// Upsert chunk embeddings into a partition of the Milvus collection
 pub async fn upsert_chunk_embeddings(
     client: &mut MilvusClient,
     collection_name: &str,
+    partition_name: &str,
     chunk_ids: &[Uuid],
     embeddings: &[Vec<f32>],
 ) -> Result<()> {
@@ -97,10 +172,11 @@ pub async fn upsert_chunk_embeddings(
         field_id: 2,
     };
 
+    ensure_partition(client, collection_name, partition_name).await?;
     client
         .insert(
             collection_name,
-            Option::<String>::None,
+            Some(partition_name.to_string()),
             vec![id_field, embed_field],
         )
         .await?;
@@ -108,14 +184,163 @@ pub async fn upsert_chunk_embeddings(
     Ok(())
 }
 
-// Stub search: return empty to allow compilation
+// Batas jumlah PK per ekspresi `id in [...]` agar request Milvus tetap kecil
+const DELETE_BATCH_SIZE: usize = 1000;
+
+// Delete chunk embeddings by Milvus primary key (milvus_vector_id / uuid_to_i64)
+pub async fn delete_vectors(
+    client: &mut MilvusClient,
+    collection_name: &str,
+    vector_ids: &[i64],
+) -> Result<()> {
+    for batch in vector_ids.chunks(DELETE_BATCH_SIZE) {
+        client
+            .delete(collection_name, Option::<String>::None, &id_in_expr(batch))
+            .await?;
+    }
+    Ok(())
+}
+
+// Primary keys matching a boolean expression, e.g. `id >= 0 && id < 1000`
+pub async fn query_vector_ids(
+    client: &mut MilvusClient,
+    collection_name: &str,
+    expr: &str,
+) -> Result<Vec<i64>> {
+    let results = client
+        .query(
+            collection_name,
+            expr,
+            vec!["id".to_string()],
+            Option::<String>::None,
+        )
+        .await?;
+
+    let ids = results
+        .fields_data
+        .into_iter()
+        .filter(|field| field.field_name == "id")
+        .filter_map(|field| match field.field {
+            Some(schema::field_data::Field::Scalars(schema::ScalarField {
+                data: Some(schema::scalar_field::Data::LongData(longs)),
+            })) => Some(longs.data),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    Ok(ids)
+}
+
+/// ID: Bagi rentang PK i64 menjadi `buckets` ekspresi `id >= a && id < b` yang berurutan.
+/// PK berasal dari 64 bit bawah UUID v4 sehingga tersebar merata antar bucket; dipakai untuk
+/// memindai seluruh koleksi tanpa melewati batas hasil query Milvus.
+/// EN: Split the i64 PK range into `buckets` consecutive `id >= a && id < b` expressions.
+/// PKs come from the low 64 bits of UUID v4 so they spread evenly; used to scan the whole
+/// collection without hitting Milvus' query result limit.
+pub fn id_range_exprs(buckets: u32) -> Vec<String> {
+    let buckets = buckets.max(1) as i128;
+    let span = (i64::MAX as i128 - i64::MIN as i128 + 1) / buckets;
+    (0..buckets)
+        .map(|i| {
+            let lower = i64::MIN as i128 + i * span;
+            if i == buckets - 1 {
+                format!("id >= {}", lower)
+            } else {
+                format!("id >= {} && id < {}", lower, lower + span)
+            }
+        })
+        .collect()
+}
+
+fn id_in_expr(ids: &[i64]) -> String {
+    let list: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    format!("id in [{}]", list.join(","))
+}
+
+// Batas jumlah PK kandidat per pencarian terfilter; batch digabung lalu dipotong ke `top_k`
+const SEARCH_FILTER_BATCH_SIZE: usize = 5000;
+
+/// ID: Cari `top_k` vektor terdekat (inner product; embedding sudah L2-normalized) hanya di
+/// `partitions`. `partitions` kosong = seluruh koleksi (Super Admin). Partisi yang belum ada
+/// (toko tanpa dokumen) dilewati; bila tidak ada satu pun, hasilnya kosong, bukan seluruh koleksi.
+/// `candidate_ids` membatasi pencarian ANN ke PK tersebut lewat ekspresi `id in [...]` di Milvus
+/// (filter metadata diterapkan sebelum perankingan); daftar kosong = hasil kosong.
+/// EN: Search the `top_k` nearest vectors (inner product; embeddings are L2-normalized) only in
+/// `partitions`. Empty `partitions` = whole collection (Super Admin). Missing partitions (a store
+/// without documents) are skipped; when none exist the result is empty, never the whole collection.
+/// `candidate_ids` restricts the ANN search to those primary keys with an `id in [...]` expression
+/// inside Milvus (metadata filters apply before ranking); an empty list yields no hits.
 pub async fn search_top_k(
-    _client: &mut MilvusClient,
-    _collection_name: &str,
-    _query_vec: &[f32],
+    client: &mut MilvusClient,
+    collection_name: &str,
+    partitions: &[String],
+    candidate_ids: Option<&[i64]>,
+    query_vec: &[f32],
     top_k: usize,
 ) -> Result<Vec<(i64, f32)>> {
-    Ok(Vec::with_capacity(top_k))
+    if query_vec.is_empty() || top_k == 0 || candidate_ids.is_some_and(|ids| ids.is_empty()) {
+        return Ok(Vec::new());
+    }
+
+    let mut searched_partitions = Vec::new();
+    for partition in partitions {
+        let present = partition == LEGACY_PARTITION
+            || client
+                .has_partition(collection_name, partition.as_str())
+                .await?;
+        if present {
+            searched_partitions.push(partition);
+        }
+    }
+    if !partitions.is_empty() && searched_partitions.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let exprs: Vec<Option<String>> = match candidate_ids {
+        Some(ids) => ids
+            .chunks(SEARCH_FILTER_BATCH_SIZE)
+            .map(|batch| Some(id_in_expr(batch)))
+            .collect(),
+        None => vec![None],
+    };
+
+    let mut hits = Vec::new();
+    for expr in exprs {
+        let mut option = SearchOption::with_limit(top_k);
+        option.metric_type(MetricType::IP);
+        for partition in &searched_partitions {
+            option.add_partition(partition);
+        }
+        if let Some(expr) = expr {
+            option.expr(expr);
+        }
+
+        let results = client
+            .search(
+                collection_name,
+                vec![Value::from(query_vec.to_vec())],
+                "embedding",
+                &option,
+            )
+            .await?;
+
+        if let Some(result) = results.into_iter().next() {
+            hits.extend(result.id.into_iter().zip(result.score).filter_map(
+                |(id, score)| match id {
+                    Value::Long(id) => Some((id, score)),
+                    _ => None,
+                },
+            ));
+        }
+        keep_top(&mut hits, top_k);
+    }
+    Ok(hits)
+}
+
+// Urutkan menurun berdasarkan skor lalu sisakan `limit` teratas
+fn keep_top(ranked: &mut Vec<(i64, f32)>, limit: usize) {
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ranked.truncate(limit);
 }
 
 fn embedding_dim_from_list(embeddings: &[Vec<f32>]) -> usize {
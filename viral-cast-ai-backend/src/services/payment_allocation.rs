@@ -0,0 +1,132 @@
+use rust_decimal::Decimal;
+use std::fmt;
+
+use crate::models::payments::PaymentMethod;
+
+// ID: Alokasi pembayaran (split tender) ke tagihan pesanan yang murni, tanpa akses DB.
+//     Satu pesanan boleh dibayar dengan beberapa metode; CASH boleh lebih (ada kembalian),
+//     metode non-tunai tidak boleh melebihi sisa tagihan.
+// EN: Pure split-tender allocation against an order bill, no database access.
+//     An order may be paid with several methods; CASH may overpay (change is due),
+//     non-cash methods may not exceed the remaining balance.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Allocation {
+    /// Porsi yang dicatat sebagai `payments.amount`
+    pub applied: Decimal,
+    pub tendered: Decimal,
+    pub change: Decimal,
+    pub remaining_after: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AllocationError {
+    NonPositiveAmount,
+    AlreadySettled,
+    Overpayment { remaining: Decimal },
+}
+
+impl fmt::Display for AllocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocationError::NonPositiveAmount => write!(f, "Payment amount must be positive"),
+            AllocationError::AlreadySettled => write!(f, "Order is already fully paid"),
+            AllocationError::Overpayment { remaining } => write!(
+                f,
+                "Amount exceeds the remaining balance of {}; only CASH may be overpaid",
+                remaining
+            ),
+        }
+    }
+}
+
+/// ID: Sisa tagihan = total pesanan - pembayaran yang sudah dialokasikan (tidak pernah negatif).
+/// EN: Remaining balance = order total - payments already allocated (never negative).
+pub fn remaining_balance(order_total: Decimal, allocated: Decimal) -> Decimal {
+    (order_total - allocated).max(Decimal::ZERO).round_dp(2)
+}
+
+/// ID: Alokasikan uang yang diserahkan ke sisa tagihan.
+/// EN: Allocate a tendered amount against the remaining balance.
+pub fn allocate(
+    method: &PaymentMethod,
+    tendered: Decimal,
+    remaining: Decimal,
+) -> Result<Allocation, AllocationError> {
+    let tendered = tendered.round_dp(2);
+    if tendered <= Decimal::ZERO {
+        return Err(AllocationError::NonPositiveAmount);
+    }
+    if remaining <= Decimal::ZERO {
+        return Err(AllocationError::AlreadySettled);
+    }
+    if tendered > remaining && !matches!(method, PaymentMethod::Cash) {
+        return Err(AllocationError::Overpayment { remaining });
+    }
+
+    let applied = tendered.min(remaining);
+    Ok(Allocation {
+        applied,
+        tendered,
+        change: tendered - applied,
+        remaining_after: remaining - applied,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn cash_overpayment_gives_change_and_settles() {
+        let allocation = allocate(&PaymentMethod::Cash, dec("100000"), dec("87500")).unwrap();
+        assert_eq!(allocation.applied, dec("87500"));
+        assert_eq!(allocation.change, dec("12500"));
+        assert_eq!(allocation.remaining_after, dec("0"));
+    }
+
+    #[test]
+    fn split_tender_leaves_remaining_balance() {
+        let remaining = remaining_balance(dec("150000"), dec("0"));
+        let card = allocate(&PaymentMethod::Card, dec("100000"), remaining).unwrap();
+        assert_eq!(card.remaining_after, dec("50000"));
+
+        let cash = allocate(&PaymentMethod::Cash, dec("50000"), card.remaining_after).unwrap();
+        assert_eq!(cash.change, dec("0"));
+        assert_eq!(cash.remaining_after, dec("0"));
+    }
+
+    #[test]
+    fn non_cash_overpayment_is_rejected() {
+        for method in [
+            PaymentMethod::Card,
+            PaymentMethod::Qris,
+            PaymentMethod::Transfer,
+        ] {
+            assert_eq!(
+                allocate(&method, dec("60000"), dec("50000")),
+                Err(AllocationError::Overpayment {
+                    remaining: dec("50000")
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn settled_orders_and_empty_amounts_are_rejected() {
+        assert_eq!(remaining_balance(dec("100"), dec("120")), dec("0"));
+        assert_eq!(
+            allocate(&PaymentMethod::Cash, dec("10"), dec("0")),
+            Err(AllocationError::AlreadySettled)
+        );
+        assert_eq!(
+            allocate(&PaymentMethod::Cash, dec("0"), dec("10")),
+            Err(AllocationError::NonPositiveAmount)
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+
+// Jawaban LLM yang persis berisi penanda ini dianggap penolakan "tidak ada di basis pengetahuan"
+pub const NOT_IN_KB_MARKER: &str = "NOT_IN_KNOWLEDGE_BASE";
+
+// Klaim dengan token konten lebih sedikit dari ini (mis. "Ringkasan:") tidak dinilai
+const MIN_CLAIM_TOKENS: usize = 3;
+// Klaim dengan tumpang-tindih token di bawah ini dianggap tidak didukung sumber
+const SUPPORT_THRESHOLD: f32 = 0.5;
+// Klaim tanpa sitasi hanya mendapat separuh skor dukungan
+const UNCITED_PENALTY: f32 = 0.5;
+
+const STOPWORDS: &[&str] = &[
+    "yang", "dan", "atau", "dari", "untuk", "dengan", "pada", "ini", "itu", "adalah", "akan",
+    "dalam", "juga", "tidak", "bisa", "dapat", "the", "and", "for", "with", "that", "this", "are",
+    "was", "were", "has", "have", "from", "not",
+];
+
+/// ID: Satu klaim (kalimat) jawaban beserta nomor sitasi [n] yang valid (1-based).
+/// EN: One answer claim (sentence) with its valid [n] citation markers (1-based).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Claim {
+    pub text: String,
+    pub markers: Vec<usize>,
+    pub support: Option<f32>, // None = terlalu pendek untuk dinilai
+}
+
+#[derive(Debug, Clone)]
+pub struct GroundedAnswer {
+    /// Jawaban dengan penanda sitasi di luar jangkauan sumber dihapus
+    pub answer: String,
+    pub claims: Vec<Claim>,
+    /// Rata-rata dukungan klaim yang dinilai, 0..1
+    pub groundedness: f32,
+    pub unsupported_claims: usize,
+}
+
+pub fn is_refusal(answer: &str) -> bool {
+    answer.contains(NOT_IN_KB_MARKER)
+}
+
+/// ID: Pecah jawaban menjadi klaim, cocokkan penanda [n] dengan sumber, dan hitung skor
+/// groundedness dari tumpang-tindih token klaim terhadap sumber yang dirujuk.
+/// EN: Split the answer into claims, map [n] markers to sources, and compute a groundedness
+/// score from the token overlap between each claim and the sources it cites.
+pub fn ground_answer(answer: &str, sources: &[&str]) -> GroundedAnswer {
+    let cleaned = strip_invalid_markers(answer, sources.len());
+    let source_tokens: Vec<HashSet<String>> = sources.iter().map(|s| content_tokens(s)).collect();
+
+    let claims: Vec<Claim> = split_claims(&cleaned)
+        .into_iter()
+        .map(|(text, markers)| {
+            let tokens = content_tokens(&text);
+            let support = (tokens.len() >= MIN_CLAIM_TOKENS)
+                .then(|| claim_support(&tokens, &markers, &source_tokens));
+            Claim {
+                text,
+                markers,
+                support,
+            }
+        })
+        .collect();
+
+    let scored: Vec<f32> = claims.iter().filter_map(|c| c.support).collect();
+    let groundedness = if scored.is_empty() {
+        0.0
+    } else {
+        scored.iter().sum::<f32>() / scored.len() as f32
+    };
+    let unsupported_claims = scored.iter().filter(|s| **s < SUPPORT_THRESHOLD).count();
+
+    GroundedAnswer {
+        answer: cleaned,
+        claims,
+        groundedness,
+        unsupported_claims,
+    }
+}
+
+fn claim_support(
+    tokens: &HashSet<String>,
+    markers: &[usize],
+    source_tokens: &[HashSet<String>],
+) -> f32 {
+    let overlap = |source: &HashSet<String>| {
+        tokens.iter().filter(|t| source.contains(*t)).count() as f32 / tokens.len() as f32
+    };
+    if markers.is_empty() {
+        let best = source_tokens.iter().map(overlap).fold(0.0, f32::max);
+        return best * UNCITED_PENALTY;
+    }
+    markers
+        .iter()
+        .filter_map(|m| source_tokens.get(m - 1))
+        .map(overlap)
+        .fold(0.0, f32::max)
+}
+
+/// ID: Hapus penanda [n] yang tidak menunjuk sumber mana pun (sitasi karangan model).
+/// EN: Drop [n] markers that do not point at any source (citations invented by the model).
+fn strip_invalid_markers(answer: &str, source_count: usize) -> String {
+    let mut out = String::with_capacity(answer.len());
+    let mut rest = answer;
+    while let Some(open) = rest.find('[') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open..];
+        match parse_marker_group(after) {
+            Some((numbers, len)) => {
+                let valid: Vec<String> = numbers
+                    .into_iter()
+                    .filter(|n| (1..=source_count).contains(n))
+                    .map(|n| n.to_string())
+                    .collect();
+                if valid.is_empty() {
+                    // Buang juga spasi sebelum penanda yang dihapus
+                    let trimmed = out.trim_end().len();
+                    out.truncate(trimmed);
+                } else {
+                    out.push_str(&format!("[{}]", valid.join(", ")));
+                }
+                rest = &after[len..];
+            }
+            None => {
+                out.push('[');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `[1]`, `[1, 2]` atau `[1,2]` di awal teks -> (nomor, panjang byte)
+fn parse_marker_group(text: &str) -> Option<(Vec<usize>, usize)> {
+    let close = text.find(']')?;
+    let inner = &text[1..close];
+    if inner.trim().is_empty() {
+        return None;
+    }
+    let numbers: Option<Vec<usize>> = inner
+        .split(',')
+        .map(|part| part.trim().parse::<usize>().ok())
+        .collect();
+    numbers.map(|n| (n, close + 1))
+}
+
+/// ID: Kalimat berakhir di . ! ? atau baris baru; penanda [n] tepat setelah tanda baca
+/// (mis. "… Rp20.000. [1]") tetap milik kalimat sebelumnya.
+/// EN: Sentences end at . ! ? or a newline; [n] markers right after the punctuation
+/// (e.g. "… Rp20.000. [1]") still belong to the preceding sentence.
+fn split_claims(answer: &str) -> Vec<(String, Vec<usize>)> {
+    let mut claims: Vec<(String, Vec<usize>)> = Vec::new();
+    let mut current = String::new();
+    let mut markers: Vec<usize> = Vec::new();
+    let chars: Vec<char> = answer.chars().collect();
+    let mut i = 0;
+
+    let flush =
+        |current: &mut String, markers: &mut Vec<usize>, claims: &mut Vec<(String, Vec<usize>)>| {
+            let text = current.trim().to_string();
+            if !text.is_empty() {
+                claims.push((text, std::mem::take(markers)));
+            } else if !markers.is_empty() {
+                // Penanda yang berdiri sendiri menempel ke klaim sebelumnya
+                if let Some(last) = claims.last_mut() {
+                    last.1.append(markers);
+                }
+            }
+            current.clear();
+        };
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '[' {
+            let rest: String = chars[i..].iter().collect();
+            if let Some((numbers, len)) = parse_marker_group(&rest) {
+                for n in numbers {
+                    if !markers.contains(&n) {
+                        markers.push(n);
+                    }
+                }
+                i += rest[..len].chars().count();
+                continue;
+            }
+        }
+
+        if c == '\n' {
+            flush(&mut current, &mut markers, &mut claims);
+            i += 1;
+            continue;
+        }
+
+        current.push(c);
+        let at_boundary = matches!(c, '.' | '!' | '?')
+            && chars.get(i + 1).copied().unwrap_or(' ').is_whitespace();
+        if at_boundary {
+            // Penanda setelah tanda baca: "kalimat. [1]"
+            let mut j = i + 1;
+            while chars.get(j).is_some_and(|ch| *ch == ' ') {
+                j += 1;
+            }
+            if chars.get(j) == Some(&'[') {
+                let rest: String = chars[j..].iter().collect();
+                if let Some((numbers, len)) = parse_marker_group(&rest) {
+                    for n in numbers {
+                        if !markers.contains(&n) {
+                            markers.push(n);
+                        }
+                    }
+                    i = j + rest[..len].chars().count();
+                    flush(&mut current, &mut markers, &mut claims);
+                    continue;
+                }
+            }
+            flush(&mut current, &mut markers, &mut claims);
+        }
+        i += 1;
+    }
+    flush(&mut current, &mut markers, &mut claims);
+    claims
+}
+
+fn content_tokens(text: &str) -> HashSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .filter(|t| t.chars().all(|c| c.is_ascii_digit()) || t.chars().count() >= 3)
+        .filter(|t| !STOPWORDS.contains(t))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCES: &[&str] = &[
+        "Harga kopi susu gula aren adalah Rp20.000 per gelas ukuran reguler.",
+        "Toko buka setiap hari pukul 07.00 sampai 22.00 kecuali hari raya.",
+    ];
+
+    #[test]
+    fn maps_markers_to_claims_including_after_punctuation() {
+        let grounded = ground_answer(
+            "Kopi susu gula aren harganya Rp20.000 per gelas [1]. Toko buka pukul 07.00. [2]",
+            SOURCES,
+        );
+        assert_eq!(grounded.claims.len(), 2);
+        assert_eq!(grounded.claims[0].markers, vec![1]);
+        assert_eq!(grounded.claims[1].markers, vec![2]);
+        assert!(grounded.groundedness > 0.7, "{}", grounded.groundedness);
+        assert_eq!(grounded.unsupported_claims, 0);
+    }
+
+    #[test]
+    fn strips_markers_pointing_at_missing_sources() {
+        let grounded = ground_answer("Harga kopi susu Rp20.000 [1, 7]. Diskon 50% [9].", SOURCES);
+        assert_eq!(grounded.answer, "Harga kopi susu Rp20.000 [1]. Diskon 50%.");
+        assert!(grounded
+            .claims
+            .iter()
+            .all(|c| c.markers.iter().all(|m| *m <= 2)));
+    }
+
+    #[test]
+    fn invented_claims_lower_the_score() {
+        let grounded = ground_answer(
+            "Kopi susu gula aren Rp20.000 per gelas [1]. Semua pelanggan mendapat voucher gratis setiap minggu [2].",
+            SOURCES,
+        );
+        assert_eq!(grounded.unsupported_claims, 1);
+        assert!(grounded.groundedness < 0.7, "{}", grounded.groundedness);
+    }
+
+    #[test]
+    fn uncited_claims_are_penalised() {
+        let cited = ground_answer("Toko buka setiap hari pukul 07.00 [2].", SOURCES);
+        let uncited = ground_answer("Toko buka setiap hari pukul 07.00.", SOURCES);
+        assert!(uncited.groundedness < cited.groundedness);
+    }
+
+    #[test]
+    fn detects_refusal_marker_and_keeps_plain_brackets() {
+        assert!(is_refusal("NOT_IN_KNOWLEDGE_BASE"));
+        assert!(!is_refusal("Harga Rp20.000 [1]."));
+        let grounded = ground_answer("Lihat [catatan] ini.", SOURCES);
+        assert_eq!(grounded.answer, "Lihat [catatan] ini.");
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+// Selisih skor per pertanyaan di bawah ini dianggap sama (hindari noise pembulatan)
+const CHANGE_EPSILON: f32 = 1e-4;
+
+/// ID: Hasil satu pertanyaan evaluasi; disimpan sebagai JSONB di rag_eval_runs.results.
+/// EN: Outcome of one evaluation question; stored as JSONB in rag_eval_runs.results.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuestionOutcome {
+    pub question_id: Uuid,
+    pub recall: f32,
+    pub reciprocal_rank: f32,
+    pub answer_similarity: Option<f32>,
+    pub retrieved_ids: Vec<Uuid>, // chunk atau dokumen, sesuai jenis ekspektasi
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalSummary {
+    pub question_count: usize,
+    pub recall_at_k: f32,
+    pub mrr: f32,
+    pub answer_similarity: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalComparison {
+    pub recall_delta: f32,
+    pub mrr_delta: f32,
+    pub answer_similarity_delta: Option<f32>,
+    pub improved_questions: Vec<Uuid>,
+    pub regressed_questions: Vec<Uuid>,
+}
+
+/// ID: Urutan pertama kemunculan, tanpa duplikat (mis. beberapa chunk dari dokumen yang sama).
+/// EN: First-occurrence order without duplicates (e.g. several chunks of the same document).
+pub fn dedup_in_order(ids: impl IntoIterator<Item = Uuid>) -> Vec<Uuid> {
+    let mut seen = HashSet::new();
+    ids.into_iter().filter(|id| seen.insert(*id)).collect()
+}
+
+/// Bagian ekspektasi yang muncul di k hasil teratas; 0 bila ekspektasi kosong
+pub fn recall_at_k(expected: &[Uuid], retrieved: &[Uuid], k: usize) -> f32 {
+    let expected: HashSet<&Uuid> = expected.iter().collect();
+    if expected.is_empty() {
+        return 0.0;
+    }
+    let hits = retrieved
+        .iter()
+        .take(k)
+        .filter(|id| expected.contains(id))
+        .collect::<HashSet<_>>()
+        .len();
+    hits as f32 / expected.len() as f32
+}
+
+/// 1 / peringkat hasil relevan pertama (1-based); 0 bila tidak ada
+pub fn reciprocal_rank(expected: &[Uuid], retrieved: &[Uuid]) -> f32 {
+    retrieved
+        .iter()
+        .position(|id| expected.contains(id))
+        .map(|pos| 1.0 / (pos + 1) as f32)
+        .unwrap_or(0.0)
+}
+
+/// ID: F1 token antara jawaban acuan dan jawaban model (huruf kecil, tanpa tanda baca
+/// dan penanda sitasi), 0..1.
+/// EN: Token F1 between the reference and the generated answer (lowercased, without
+/// punctuation and citation markers), 0..1.
+pub fn answer_similarity(reference: &str, answer: &str) -> f32 {
+    let reference = answer_tokens(reference);
+    let answer = answer_tokens(answer);
+    if reference.is_empty() || answer.is_empty() {
+        return 0.0;
+    }
+
+    // Hitung irisan sebagai multiset
+    let mut remaining = reference.clone();
+    let mut common = 0usize;
+    for token in &answer {
+        if let Some(pos) = remaining.iter().position(|t| t == token) {
+            remaining.swap_remove(pos);
+            common += 1;
+        }
+    }
+    if common == 0 {
+        return 0.0;
+    }
+    let precision = common as f32 / answer.len() as f32;
+    let recall = common as f32 / reference.len() as f32;
+    2.0 * precision * recall / (precision + recall)
+}
+
+fn answer_tokens(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        // Angka pendek (penanda sitasi [1], jam 07.00) tidak ikut dihitung
+        .filter(|t| t.len() > 2 || t.chars().any(|c| !c.is_ascii_digit()))
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn summarize(outcomes: &[QuestionOutcome]) -> EvalSummary {
+    let count = outcomes.len();
+    let mean = |values: Vec<f32>| {
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().sum::<f32>() / values.len() as f32)
+        }
+    };
+    EvalSummary {
+        question_count: count,
+        recall_at_k: mean(outcomes.iter().map(|o| o.recall).collect()).unwrap_or(0.0),
+        mrr: mean(outcomes.iter().map(|o| o.reciprocal_rank).collect()).unwrap_or(0.0),
+        answer_similarity: mean(
+            outcomes
+                .iter()
+                .filter_map(|o| o.answer_similarity)
+                .collect(),
+        ),
+    }
+}
+
+/// ID: Bandingkan run sekarang dengan run sebelumnya. Pertanyaan dibandingkan lewat
+/// (recall, reciprocal rank); pertanyaan baru/terhapus diabaikan.
+/// EN: Compare the current run with the previous one. Questions are compared on
+/// (recall, reciprocal rank); added/removed questions are ignored.
+pub fn compare(
+    previous: &EvalSummary,
+    previous_outcomes: &[QuestionOutcome],
+    current: &EvalSummary,
+    current_outcomes: &[QuestionOutcome],
+) -> EvalComparison {
+    let mut improved_questions = Vec::new();
+    let mut regressed_questions = Vec::new();
+    for outcome in current_outcomes {
+        let Some(before) = previous_outcomes
+            .iter()
+            .find(|p| p.question_id == outcome.question_id)
+        else {
+            continue;
+        };
+        let delta =
+            (outcome.recall - before.recall) + (outcome.reciprocal_rank - before.reciprocal_rank);
+        if delta > CHANGE_EPSILON {
+            improved_questions.push(outcome.question_id);
+        } else if delta < -CHANGE_EPSILON {
+            regressed_questions.push(outcome.question_id);
+        }
+    }
+
+    EvalComparison {
+        recall_delta: current.recall_at_k - previous.recall_at_k,
+        mrr_delta: current.mrr - previous.mrr,
+        answer_similarity_delta: match (current.answer_similarity, previous.answer_similarity) {
+            (Some(now), Some(before)) => Some(now - before),
+            _ => None,
+        },
+        improved_questions,
+        regressed_questions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: usize) -> Vec<Uuid> {
+        (0..n).map(|_| Uuid::new_v4()).collect()
+    }
+
+    #[test]
+    fn recall_and_reciprocal_rank() {
+        let docs = ids(5);
+        let expected = vec![docs[1], docs[4]];
+        let retrieved = vec![docs[0], docs[1], docs[2], docs[3], docs[4]];
+        assert_eq!(recall_at_k(&expected, &retrieved, 3), 0.5);
+        assert_eq!(recall_at_k(&expected, &retrieved, 5), 1.0);
+        assert_eq!(reciprocal_rank(&expected, &retrieved), 0.5);
+        assert_eq!(reciprocal_rank(&[docs[3]], &retrieved[..2]), 0.0);
+        assert_eq!(recall_at_k(&[], &retrieved, 5), 0.0);
+    }
+
+    #[test]
+    fn dedup_keeps_first_rank() {
+        let docs = ids(2);
+        assert_eq!(
+            dedup_in_order(vec![docs[0], docs[0], docs[1], docs[0]]),
+            vec![docs[0], docs[1]]
+        );
+    }
+
+    #[test]
+    fn answer_similarity_is_token_f1() {
+        assert_eq!(
+            answer_similarity("Toko buka pukul 07.00", "Toko buka pukul 07.00 [1]."),
+            1.0
+        );
+        let partial = answer_similarity("harga kopi susu 20000", "harga kopi 20000 per gelas");
+        assert!(partial > 0.5 && partial < 1.0, "{}", partial);
+        assert_eq!(answer_similarity("harga kopi", "jam buka toko"), 0.0);
+        assert_eq!(answer_similarity("", "apa saja"), 0.0);
+    }
+
+    #[test]
+    fn compare_reports_deltas_and_question_changes() {
+        let q = ids(3);
+        let outcome = |id: Uuid, recall: f32, rr: f32| QuestionOutcome {
+            question_id: id,
+            recall,
+            reciprocal_rank: rr,
+            answer_similarity: None,
+            retrieved_ids: Vec::new(),
+        };
+        let before = vec![
+            outcome(q[0], 1.0, 1.0),
+            outcome(q[1], 0.0, 0.0),
+            outcome(q[2], 1.0, 0.5),
+        ];
+        let after = vec![
+            outcome(q[0], 0.5, 0.5),
+            outcome(q[1], 1.0, 1.0),
+            outcome(q[2], 1.0, 0.5),
+        ];
+        let comparison = compare(&summarize(&before), &before, &summarize(&after), &after);
+        assert_eq!(comparison.improved_questions, vec![q[1]]);
+        assert_eq!(comparison.regressed_questions, vec![q[0]]);
+        assert!((comparison.recall_delta - 0.5 / 3.0).abs() < 1e-6);
+        assert_eq!(comparison.answer_similarity_delta, None);
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+// ID: Retrieval hybrid untuk RAG: keyword (full-text Postgres, skor ala BM25) + vektor,
+// digabung dengan reciprocal rank fusion. Filter metadata diterapkan di SQL sebelum ranking.
+// EN: Hybrid retrieval for RAG: keyword (Postgres full-text, BM25-style score) + vector,
+// merged with reciprocal rank fusion. Metadata filters are applied in SQL before ranking.
+
+/// Konstanta k RRF (nilai umum dari paper Cormack et al.)
+pub const RRF_K: f32 = 60.0;
+/// Normalisasi ts_rank_cd: 1 = bagi 1 + log(panjang dokumen), 32 = rank / (rank + 1)
+const TS_RANK_NORMALIZATION: i32 = 1 | 32;
+const MAX_KEYWORD_TERMS: usize = 16;
+
+/// ID: Cakupan basis pengetahuan yang boleh dibaca pemanggil. Default paling sempit
+/// (hanya dokumen platform) agar filter yang lupa diisi tidak membuka dokumen toko lain.
+/// EN: Knowledge-base scope the caller may read. Defaults to the narrowest scope
+/// (platform documents only) so a forgotten filter never exposes another store's documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentAccess {
+    /// Super Admin: seluruh dokumen
+    All,
+    /// Dokumen platform + dokumen milik toko ini (None = pengguna tanpa toko)
+    Store(Option<Uuid>),
+}
+
+impl Default for DocumentAccess {
+    fn default() -> Self {
+        DocumentAccess::Store(None)
+    }
+}
+
+impl DocumentAccess {
+    /// Kondisi akses untuk query dengan alias dokumen `d`
+    pub fn push_condition(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        match self {
+            DocumentAccess::All => {}
+            DocumentAccess::Store(Some(store_uuid)) => {
+                qb.push(" AND (d.visibility = 'platform' OR d.store_uuid = ");
+                qb.push_bind(*store_uuid);
+                qb.push(")");
+            }
+            DocumentAccess::Store(None) => {
+                qb.push(" AND d.visibility = 'platform'");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct RetrievalFilters {
+    pub access: DocumentAccess,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub document_ids: Option<Vec<Uuid>>,
+}
+
+impl RetrievalFilters {
+    /// ID: Apakah ada filter metadata dokumen (kategori, tag, id dokumen).
+    /// EN: Whether a document metadata filter (category, tags, document ids) is set.
+    pub fn has_metadata_filters(&self) -> bool {
+        self.category.is_some()
+            || self.tags.as_ref().is_some_and(|tags| !tags.is_empty())
+            || self
+                .document_ids
+                .as_ref()
+                .is_some_and(|ids| !ids.is_empty())
+    }
+
+    /// ID: Tambahkan kondisi filter; query harus memakai alias `dc` (chunk) dan `d` (dokumen).
+    /// EN: Append filter conditions; the query must alias chunks as `dc` and documents as `d`.
+    pub fn push_conditions(&self, qb: &mut QueryBuilder<'_, Postgres>) {
+        qb.push(" AND d.status = 'ready' AND dc.deleted_at = 0 AND d.deleted_at = 0");
+        self.access.push_condition(qb);
+        if let Some(category) = &self.category {
+            qb.push(" AND d.category = ");
+            qb.push_bind(category.clone());
+        }
+        if let Some(tags) = self.tags.as_ref().filter(|t| !t.is_empty()) {
+            qb.push(" AND d.tags && ");
+            qb.push_bind(tags.clone());
+        }
+        if let Some(ids) = self.document_ids.as_ref().filter(|ids| !ids.is_empty()) {
+            qb.push(" AND d.id = ANY(");
+            qb.push_bind(ids.clone());
+            qb.push(")");
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetrievedChunk {
+    pub chunk_id: Uuid,
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub content: String,
+    pub chunk_index: i32,
+    pub page_number: Option<i32>,
+    /// Cosine similarity dari pencarian vektor (jika chunk muncul di sana)
+    pub vector_score: Option<f32>,
+    /// Skor keyword BM25-style (jika chunk muncul di sana)
+    pub keyword_score: Option<f32>,
+    pub fused_score: f32,
+    /// Skor reranker (0..1) bila tahap rerank berjalan untuk chunk ini
+    pub rerank_score: Option<f32>,
+}
+
+impl RetrievedChunk {
+    /// Skor yang ditampilkan sebagai similarity_score: vektor bila ada, jika tidak skor keyword
+    pub fn similarity(&self) -> f32 {
+        self.vector_score.or(self.keyword_score).unwrap_or(0.0)
+    }
+
+    /// Bukti relevansi terkuat untuk gerbang "not in knowledge base": rerank, lalu vektor, lalu keyword
+    pub fn relevance(&self) -> f32 {
+        self.rerank_score
+            .or(self.vector_score)
+            .or(self.keyword_score)
+            .unwrap_or(0.0)
+    }
+}
+
+/// ID: Term pencarian keyword: huruf/angka saja (aman untuk to_tsquery), lowercase, unik.
+/// EN: Keyword search terms: alphanumeric only (safe for to_tsquery), lowercased, unique.
+pub fn keyword_terms(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2)
+        .filter(|t| seen.insert(t.to_string()))
+        .take(MAX_KEYWORD_TERMS)
+        .map(str::to_string)
+        .collect()
+}
+
+/// IDF BM25 (varian Lucene, selalu positif)
+pub fn bm25_idf(total_chunks: i64, document_frequency: i64) -> f32 {
+    let n = total_chunks.max(0) as f32;
+    let df = document_frequency.clamp(0, total_chunks.max(0)) as f32;
+    (1.0 + (n - df + 0.5) / (df + 0.5)).ln()
+}
+
+/// ID: Gabungkan beberapa daftar ranking: skor = Σ 1 / (k + rank), rank mulai dari 1.
+/// EN: Merge several ranked lists: score = Σ 1 / (k + rank), ranks start at 1.
+pub fn reciprocal_rank_fusion(rankings: &[Vec<Uuid>], k: f32) -> Vec<(Uuid, f32)> {
+    let mut scores: HashMap<Uuid, f32> = HashMap::new();
+    let mut first_seen: Vec<Uuid> = Vec::new();
+    for ranking in rankings {
+        for (rank, id) in ranking.iter().enumerate() {
+            let entry = scores.entry(*id).or_insert_with(|| {
+                first_seen.push(*id);
+                0.0
+            });
+            *entry += 1.0 / (k + rank as f32 + 1.0);
+        }
+    }
+    let mut fused: Vec<(Uuid, f32)> = first_seen.into_iter().map(|id| (id, scores[&id])).collect();
+    // sort stabil: skor sama mempertahankan urutan kemunculan pertama
+    fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    fused
+}
+
+/// ID: Pencarian keyword. Tiap term dicocokkan lewat konfigurasi indonesian + english,
+/// skor = Σ idf(term) · ts_rank_cd(term) dengan normalisasi panjang dan saturasi ala BM25.
+/// EN: Keyword search. Each term is matched through the indonesian + english configurations,
+/// score = Σ idf(term) · ts_rank_cd(term) with BM25-like length normalization and saturation.
+pub async fn keyword_search(
+    db: &PgPool,
+    query: &str,
+    filters: &RetrievalFilters,
+    limit: i64,
+) -> Result<Vec<(Uuid, f32)>, sqlx::Error> {
+    let terms = keyword_terms(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Statistik korpus global (tanpa filter), memakai index GIN content_tsv
+    let total_chunks: i64 =
+        sqlx::query_scalar("SELECT count(*) FROM document_chunks WHERE deleted_at = 0")
+            .fetch_one(db)
+            .await?;
+    let frequencies = sqlx::query(
+        r#"
+        SELECT t.term,
+               (SELECT count(*) FROM document_chunks dc
+                WHERE dc.deleted_at = 0
+                  AND dc.content_tsv @@ (to_tsquery('indonesian', t.term) || to_tsquery('english', t.term))
+               ) AS df
+        FROM unnest($1::text[]) AS t(term)
+        "#,
+    )
+    .bind(&terms)
+    .fetch_all(db)
+    .await?;
+
+    let mut weighted_terms = Vec::new();
+    let mut idfs = Vec::new();
+    for row in frequencies {
+        let df: i64 = row.get("df");
+        if df > 0 {
+            weighted_terms.push(row.get::<String, _>("term"));
+            idfs.push(bm25_idf(total_chunks, df));
+        }
+    }
+    if weighted_terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "WITH terms AS ( \
+            SELECT to_tsquery('indonesian', t.term) || to_tsquery('english', t.term) AS q, t.idf \
+            FROM unnest(",
+    );
+    qb.push_bind(weighted_terms);
+    qb.push("::text[], ");
+    qb.push_bind(idfs);
+    qb.push("::real[]) AS t(term, idf)) ");
+    qb.push("SELECT dc.id, SUM(terms.idf * ts_rank_cd(dc.content_tsv, terms.q, ");
+    qb.push_bind(TS_RANK_NORMALIZATION);
+    qb.push(
+        "))::real AS score \
+         FROM document_chunks dc \
+         JOIN documents d ON dc.document_id = d.id \
+         JOIN terms ON dc.content_tsv @@ terms.q \
+         WHERE TRUE",
+    );
+    filters.push_conditions(&mut qb);
+    qb.push(" GROUP BY dc.id ORDER BY score DESC LIMIT ");
+    qb.push_bind(limit);
+
+    let rows = qb.build().fetch_all(db).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("id"), row.get("score")))
+        .collect())
+}
+
+/// ID: PK Milvus (`milvus_vector_id`) seluruh chunk yang lolos filter, untuk membatasi pencarian
+/// ANN di Milvus sebelum perankingan. Hanya kolom id yang dibaca, bukan embedding.
+/// EN: Milvus primary keys (`milvus_vector_id`) of every chunk passing the filters, used to
+/// restrict the Milvus ANN search before ranking. Only ids are read, never embeddings.
+pub async fn filtered_vector_ids(
+    db: &PgPool,
+    filters: &RetrievalFilters,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT dc.milvus_vector_id \
+         FROM document_chunks dc \
+         JOIN documents d ON dc.document_id = d.id \
+         WHERE dc.milvus_vector_id IS NOT NULL",
+    );
+    filters.push_conditions(&mut qb);
+
+    let rows = qb.build().fetch_all(db).await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.get("milvus_vector_id"))
+        .collect())
+}
+
+/// ID: Petakan hasil Milvus (vector id) ke chunk id, sekaligus menerapkan filter metadata.
+/// EN: Map Milvus hits (vector ids) to chunk ids while applying the metadata filters.
+pub async fn chunks_for_vector_ids(
+    db: &PgPool,
+    hits: &[(i64, f32)],
+    filters: &RetrievalFilters,
+) -> Result<Vec<(Uuid, f32)>, sqlx::Error> {
+    if hits.is_empty() {
+        return Ok(Vec::new());
+    }
+    let vector_ids: Vec<i64> = hits.iter().map(|(id, _)| *id).collect();
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT dc.id, dc.milvus_vector_id \
+         FROM document_chunks dc \
+         JOIN documents d ON dc.document_id = d.id \
+         WHERE dc.milvus_vector_id = ANY(",
+    );
+    qb.push_bind(vector_ids);
+    qb.push(")");
+    filters.push_conditions(&mut qb);
+
+    let allowed: HashMap<i64, Uuid> = qb
+        .build()
+        .fetch_all(db)
+        .await?
+        .into_iter()
+        .map(|row| (row.get("milvus_vector_id"), row.get("id")))
+        .collect();
+    Ok(hits
+        .iter()
+        .filter_map(|(vector_id, score)| allowed.get(vector_id).map(|id| (*id, *score)))
+        .collect())
+}
+
+/// ID: Gabungkan hasil vektor + keyword dengan RRF lalu muat isi chunk untuk `limit` teratas.
+/// EN: Fuse vector + keyword results with RRF, then load chunk contents for the top `limit`.
+pub async fn fuse_and_load(
+    db: &PgPool,
+    vector_hits: &[(Uuid, f32)],
+    keyword_hits: &[(Uuid, f32)],
+    limit: usize,
+) -> Result<Vec<RetrievedChunk>, sqlx::Error> {
+    let fused = reciprocal_rank_fusion(
+        &[
+            vector_hits.iter().map(|(id, _)| *id).collect(),
+            keyword_hits.iter().map(|(id, _)| *id).collect(),
+        ],
+        RRF_K,
+    );
+    let top: Vec<(Uuid, f32)> = fused.into_iter().take(limit).collect();
+    if top.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ids: Vec<Uuid> = top.iter().map(|(id, _)| *id).collect();
+    let rows = sqlx::query(
+        r#"
+        SELECT dc.id, dc.document_id, dc.chunk_index, dc.content, dc.page_number,
+               d.title AS document_title
+        FROM document_chunks dc
+        JOIN documents d ON dc.document_id = d.id
+        WHERE dc.id = ANY($1)
+        "#,
+    )
+    .bind(&ids)
+    .fetch_all(db)
+    .await?;
+    let mut by_id: HashMap<Uuid, sqlx::postgres::PgRow> = rows
+        .into_iter()
+        .map(|row| (row.get::<Uuid, _>("id"), row))
+        .collect();
+
+    let vector_scores: HashMap<Uuid, f32> = vector_hits.iter().copied().collect();
+    let keyword_scores: HashMap<Uuid, f32> = keyword_hits.iter().copied().collect();
+    Ok(top
+        .into_iter()
+        .filter_map(|(id, fused_score)| {
+            let row = by_id.remove(&id)?;
+            Some(RetrievedChunk {
+                chunk_id: id,
+                document_id: row.get("document_id"),
+                document_title: row.get("document_title"),
+                content: row.get("content"),
+                chunk_index: row.get("chunk_index"),
+                page_number: row.get("page_number"),
+                vector_score: vector_scores.get(&id).copied(),
+                keyword_score: keyword_scores.get(&id).copied(),
+                fused_score,
+                rerank_score: None,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyword_terms_are_sanitized_and_unique() {
+        assert_eq!(
+            keyword_terms("Penjualan Q3: penjualan & stok 'kopi' a"),
+            vec!["penjualan", "q3", "stok", "kopi"]
+        );
+        assert!(keyword_terms("?! a").is_empty());
+    }
+
+    #[test]
+    fn rare_terms_get_higher_idf() {
+        assert!(bm25_idf(1000, 2) > bm25_idf(1000, 500));
+        assert!(bm25_idf(1000, 1000) > 0.0);
+        assert!(bm25_idf(0, 0) > 0.0);
+    }
+
+    #[test]
+    fn rrf_rewards_agreement_between_rankings() {
+        let (a, b, c, d) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let fused = reciprocal_rank_fusion(&[vec![a, b, c], vec![c, d, b]], RRF_K);
+        let order: Vec<Uuid> = fused.iter().map(|(id, _)| *id).collect();
+        // c & b muncul di kedua daftar sehingga mengalahkan a (hanya peringkat 1 di satu daftar)
+        assert_eq!(order, vec![c, b, a, d]);
+        assert!((fused[0].1 - (1.0 / 63.0 + 1.0 / 61.0)).abs() < 1e-6);
+
+        assert!(reciprocal_rank_fusion(&[vec![], vec![]], RRF_K).is_empty());
+        assert_eq!(reciprocal_rank_fusion(&[vec![a, b]], RRF_K)[0].0, a);
+    }
+
+    #[test]
+    fn metadata_filters_ignore_access_and_empty_lists() {
+        let mut filters = RetrievalFilters {
+            access: DocumentAccess::Store(Some(Uuid::new_v4())),
+            tags: Some(Vec::new()),
+            document_ids: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert!(!filters.has_metadata_filters());
+        filters.document_ids = Some(vec![Uuid::new_v4()]);
+        assert!(filters.has_metadata_filters());
+        filters.document_ids = None;
+        filters.category = Some("sop".to_string());
+        assert!(filters.has_metadata_filters());
+    }
+
+    #[test]
+    fn access_scope_limits_documents() {
+        let sql = |access: DocumentAccess| {
+            let mut qb = QueryBuilder::<Postgres>::new("WHERE TRUE");
+            RetrievalFilters {
+                access,
+                ..Default::default()
+            }
+            .push_conditions(&mut qb);
+            qb.sql().to_string()
+        };
+        assert!(!sql(DocumentAccess::All).contains("visibility"));
+        assert!(sql(DocumentAccess::Store(Some(Uuid::new_v4())))
+            .contains("(d.visibility = 'platform' OR d.store_uuid = $1)"));
+        // Filter kosong (default) hanya membuka dokumen platform
+        assert!(sql(DocumentAccess::default()).ends_with(" AND d.visibility = 'platform'"));
+    }
+}
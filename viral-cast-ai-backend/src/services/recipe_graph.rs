@@ -0,0 +1,592 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::services::reorder::ingredient_usage_per_unit;
+
+// ID: Graf resep bertingkat (recipe set -> bahan / recipe set komponen), murni tanpa akses DB
+// EN: Nested recipe graph (recipe set -> ingredient / component recipe set), pure, no database access
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecipeComponent {
+    Ingredient(Uuid), // ingredient_catalog_uuid
+    Recipe(Uuid),     // recipe_sets_uuid
+}
+
+#[derive(Debug, Clone)]
+pub struct RecipeLine {
+    pub component: RecipeComponent,
+    pub quantity: Decimal,
+    pub waste_percent: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RecipeNode {
+    pub yield_quantity: Option<Decimal>,
+    // Terisi bila resep menghasilkan bahan setengah jadi yang distok
+    pub output_ingredient_catalog_uuid: Option<Uuid>,
+    pub lines: Vec<RecipeLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipeGraphError {
+    UnknownRecipe(Uuid),
+    Cycle(Vec<Uuid>),
+}
+
+impl std::fmt::Display for RecipeGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecipeGraphError::UnknownRecipe(uuid) => write!(f, "Recipe set {} not found", uuid),
+            RecipeGraphError::Cycle(path) => {
+                let path: Vec<String> = path.iter().map(Uuid::to_string).collect();
+                write!(f, "Recipe cycle detected: {}", path.join(" -> "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeCostLine {
+    pub component_type: &'static str, // 'INGREDIENT' | 'RECIPE'
+    pub ingredient_catalog_uuid: Option<Uuid>,
+    pub recipe_sets_uuid: Option<Uuid>,
+    // Qty per batch termasuk waste
+    pub quantity: Decimal,
+    pub unit_cost: Option<Decimal>,
+    pub line_cost: Decimal,
+    pub breakdown: Option<Box<RecipeCostBreakdown>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeCostBreakdown {
+    pub recipe_sets_uuid: Uuid,
+    pub yield_quantity: Decimal,
+    pub batch_cost: Decimal,
+    pub unit_cost: Decimal,
+    // Bahan tanpa harga di level mana pun (dihitung 0)
+    pub missing_prices: Vec<Uuid>,
+    pub lines: Vec<RecipeCostLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeLineDiff {
+    pub component_type: &'static str, // 'INGREDIENT' | 'RECIPE'
+    pub ingredient_catalog_uuid: Option<Uuid>,
+    pub recipe_sets_uuid: Option<Uuid>,
+    pub change: &'static str, // 'ADDED' | 'REMOVED' | 'CHANGED' | 'UNCHANGED'
+    pub from_quantity: Option<Decimal>,
+    pub to_quantity: Option<Decimal>,
+    pub from_waste_percent: Option<Decimal>,
+    pub to_waste_percent: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeVersionDiff {
+    pub from_recipe_sets_uuid: Uuid,
+    pub to_recipe_sets_uuid: Uuid,
+    pub from_yield_quantity: Decimal,
+    pub to_yield_quantity: Decimal,
+    pub from_unit_cost: Decimal,
+    pub to_unit_cost: Decimal,
+    pub unit_cost_delta: Decimal,
+    pub lines: Vec<RecipeLineDiff>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RecipeGraph {
+    pub nodes: HashMap<Uuid, RecipeNode>,
+}
+
+fn effective_yield(yield_quantity: Option<Decimal>) -> Decimal {
+    match yield_quantity {
+        Some(y) if y > Decimal::ZERO => y,
+        _ => Decimal::ONE,
+    }
+}
+
+impl RecipeGraph {
+    /// True bila menambahkan `component` ke `parent` menimbulkan siklus,
+    /// yaitu `parent` sama dengan atau dapat dicapai dari `component`.
+    pub fn would_create_cycle(&self, parent: Uuid, component: Uuid) -> bool {
+        let mut stack = vec![component];
+        let mut seen = HashSet::new();
+        while let Some(current) = stack.pop() {
+            if current == parent {
+                return true;
+            }
+            if !seen.insert(current) {
+                continue;
+            }
+            if let Some(node) = self.nodes.get(&current) {
+                for line in &node.lines {
+                    if let RecipeComponent::Recipe(child) = line.component {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// ID: Pecah `output_qty` unit hasil resep menjadi kebutuhan bahan (termasuk waste).
+    /// Bila `stop_at_stocked`, komponen yang menghasilkan bahan setengah jadi diambil dari
+    /// stoknya; jika tidak, komponen selalu dipecah sampai bahan mentah.
+    /// EN: Explode `output_qty` units of a recipe into ingredient requirements (waste included).
+    /// With `stop_at_stocked`, components that produce a stocked semi-finished item are drawn
+    /// from that stock; otherwise they are always exploded down to raw ingredients.
+    pub fn explode(
+        &self,
+        recipe: Uuid,
+        output_qty: Decimal,
+        stop_at_stocked: bool,
+    ) -> Result<Vec<(Uuid, Decimal)>, RecipeGraphError> {
+        self.explode_with(recipe, output_qty, stop_at_stocked, true)
+    }
+
+    /// ID: Sama seperti `explode`, tetapi tanpa waste_percent di level mana pun (pemakaian bersih).
+    /// EN: Same as `explode`, but ignoring waste_percent at every level (net usage).
+    pub fn explode_net(
+        &self,
+        recipe: Uuid,
+        output_qty: Decimal,
+        stop_at_stocked: bool,
+    ) -> Result<Vec<(Uuid, Decimal)>, RecipeGraphError> {
+        self.explode_with(recipe, output_qty, stop_at_stocked, false)
+    }
+
+    fn explode_with(
+        &self,
+        recipe: Uuid,
+        output_qty: Decimal,
+        stop_at_stocked: bool,
+        include_waste: bool,
+    ) -> Result<Vec<(Uuid, Decimal)>, RecipeGraphError> {
+        let mut totals: Vec<(Uuid, Decimal)> = Vec::new();
+        let mut path = Vec::new();
+        self.explode_into(
+            recipe,
+            output_qty,
+            stop_at_stocked,
+            include_waste,
+            &mut path,
+            &mut totals,
+        )?;
+        Ok(totals)
+    }
+
+    fn explode_into(
+        &self,
+        recipe: Uuid,
+        output_qty: Decimal,
+        stop_at_stocked: bool,
+        include_waste: bool,
+        path: &mut Vec<Uuid>,
+        totals: &mut Vec<(Uuid, Decimal)>,
+    ) -> Result<(), RecipeGraphError> {
+        if path.contains(&recipe) {
+            let mut cycle = path.clone();
+            cycle.push(recipe);
+            return Err(RecipeGraphError::Cycle(cycle));
+        }
+        let node = self
+            .nodes
+            .get(&recipe)
+            .ok_or(RecipeGraphError::UnknownRecipe(recipe))?;
+        path.push(recipe);
+
+        for line in &node.lines {
+            let waste_percent = line.waste_percent.filter(|_| include_waste);
+            let needed =
+                ingredient_usage_per_unit(line.quantity, waste_percent, node.yield_quantity)
+                    * output_qty;
+            match line.component {
+                RecipeComponent::Ingredient(ingredient) => add_total(totals, ingredient, needed),
+                RecipeComponent::Recipe(child) => {
+                    let stocked_output = self
+                        .nodes
+                        .get(&child)
+                        .and_then(|n| n.output_ingredient_catalog_uuid)
+                        .filter(|_| stop_at_stocked);
+                    match stocked_output {
+                        Some(ingredient) => add_total(totals, ingredient, needed),
+                        None => self.explode_into(
+                            child,
+                            needed,
+                            stop_at_stocked,
+                            include_waste,
+                            path,
+                            totals,
+                        )?,
+                    }
+                }
+            }
+        }
+
+        path.pop();
+        Ok(())
+    }
+
+    /// ID: Roll-up biaya melalui semua level: komponen resep selalu dihitung dari harga bahan
+    /// mentahnya. `prices` = biaya per unit bahan (ingredient_catalog_uuid).
+    /// EN: Cost roll-up through every level: component recipes are always costed from their
+    /// raw ingredient prices. `prices` = unit cost per ingredient_catalog_uuid.
+    pub fn roll_up_cost(
+        &self,
+        recipe: Uuid,
+        prices: &HashMap<Uuid, Decimal>,
+    ) -> Result<RecipeCostBreakdown, RecipeGraphError> {
+        let mut path = Vec::new();
+        self.roll_up_inner(recipe, prices, &mut path)
+    }
+
+    fn roll_up_inner(
+        &self,
+        recipe: Uuid,
+        prices: &HashMap<Uuid, Decimal>,
+        path: &mut Vec<Uuid>,
+    ) -> Result<RecipeCostBreakdown, RecipeGraphError> {
+        if path.contains(&recipe) {
+            let mut cycle = path.clone();
+            cycle.push(recipe);
+            return Err(RecipeGraphError::Cycle(cycle));
+        }
+        let node = self
+            .nodes
+            .get(&recipe)
+            .ok_or(RecipeGraphError::UnknownRecipe(recipe))?;
+        path.push(recipe);
+
+        let mut lines = Vec::with_capacity(node.lines.len());
+        let mut missing_prices = Vec::new();
+        let mut batch_cost = Decimal::ZERO;
+
+        for line in &node.lines {
+            // Qty per batch termasuk waste (yield = 1 agar tidak dibagi)
+            let quantity = ingredient_usage_per_unit(line.quantity, line.waste_percent, None);
+            let cost_line = match line.component {
+                RecipeComponent::Ingredient(ingredient) => {
+                    let unit_cost = prices.get(&ingredient).copied();
+                    if unit_cost.is_none() && !missing_prices.contains(&ingredient) {
+                        missing_prices.push(ingredient);
+                    }
+                    RecipeCostLine {
+                        component_type: "INGREDIENT",
+                        ingredient_catalog_uuid: Some(ingredient),
+                        recipe_sets_uuid: None,
+                        quantity: quantity.round_dp(4),
+                        unit_cost,
+                        line_cost: quantity * unit_cost.unwrap_or(Decimal::ZERO),
+                        breakdown: None,
+                    }
+                }
+                RecipeComponent::Recipe(child) => {
+                    let child_breakdown = self.roll_up_inner(child, prices, path)?;
+                    for missing in &child_breakdown.missing_prices {
+                        if !missing_prices.contains(missing) {
+                            missing_prices.push(*missing);
+                        }
+                    }
+                    let unit_cost = child_breakdown.unit_cost;
+                    RecipeCostLine {
+                        component_type: "RECIPE",
+                        ingredient_catalog_uuid: self
+                            .nodes
+                            .get(&child)
+                            .and_then(|n| n.output_ingredient_catalog_uuid),
+                        recipe_sets_uuid: Some(child),
+                        quantity: quantity.round_dp(4),
+                        unit_cost: Some(unit_cost),
+                        line_cost: quantity * unit_cost,
+                        breakdown: Some(Box::new(child_breakdown)),
+                    }
+                }
+            };
+            batch_cost += cost_line.line_cost;
+            lines.push(RecipeCostLine {
+                line_cost: cost_line.line_cost.round_dp(4),
+                ..cost_line
+            });
+        }
+
+        path.pop();
+        let yield_quantity = effective_yield(node.yield_quantity);
+        Ok(RecipeCostBreakdown {
+            recipe_sets_uuid: recipe,
+            yield_quantity,
+            batch_cost: batch_cost.round_dp(4),
+            unit_cost: (batch_cost / yield_quantity).round_dp(4),
+            missing_prices,
+            lines,
+        })
+    }
+
+    /// ID: Bandingkan item dan biaya dua versi resep. Baris digabung per komponen
+    /// (bahan katalog atau recipe set komponen).
+    /// EN: Compare the items and cost of two recipe versions. Lines are merged per component
+    /// (catalog ingredient or component recipe set).
+    pub fn diff_versions(
+        &self,
+        from: Uuid,
+        to: Uuid,
+        prices: &HashMap<Uuid, Decimal>,
+    ) -> Result<RecipeVersionDiff, RecipeGraphError> {
+        let from_cost = self.roll_up_cost(from, prices)?;
+        let to_cost = self.roll_up_cost(to, prices)?;
+        let from_lines = self.merged_lines(from)?;
+        let to_lines = self.merged_lines(to)?;
+
+        let mut lines = Vec::new();
+        for (component, from_qty, from_waste) in &from_lines {
+            let matching = to_lines.iter().find(|(c, _, _)| c == component);
+            let change = match matching {
+                None => "REMOVED",
+                Some((_, to_qty, to_waste)) if to_qty == from_qty && to_waste == from_waste => {
+                    "UNCHANGED"
+                }
+                Some(_) => "CHANGED",
+            };
+            lines.push(line_diff(
+                *component,
+                change,
+                Some((*from_qty, *from_waste)),
+                matching.map(|(_, qty, waste)| (*qty, *waste)),
+            ));
+        }
+        for (component, to_qty, to_waste) in &to_lines {
+            if !from_lines.iter().any(|(c, _, _)| c == component) {
+                lines.push(line_diff(
+                    *component,
+                    "ADDED",
+                    None,
+                    Some((*to_qty, *to_waste)),
+                ));
+            }
+        }
+
+        Ok(RecipeVersionDiff {
+            from_recipe_sets_uuid: from,
+            to_recipe_sets_uuid: to,
+            from_yield_quantity: from_cost.yield_quantity,
+            to_yield_quantity: to_cost.yield_quantity,
+            from_unit_cost: from_cost.unit_cost,
+            to_unit_cost: to_cost.unit_cost,
+            unit_cost_delta: to_cost.unit_cost - from_cost.unit_cost,
+            lines,
+        })
+    }
+
+    fn merged_lines(
+        &self,
+        recipe: Uuid,
+    ) -> Result<Vec<(RecipeComponent, Decimal, Decimal)>, RecipeGraphError> {
+        let node = self
+            .nodes
+            .get(&recipe)
+            .ok_or(RecipeGraphError::UnknownRecipe(recipe))?;
+        let mut merged: Vec<(RecipeComponent, Decimal, Decimal)> = Vec::new();
+        for line in &node.lines {
+            let waste = line.waste_percent.unwrap_or(Decimal::ZERO);
+            match merged.iter_mut().find(|(c, _, _)| *c == line.component) {
+                Some((_, quantity, waste_percent)) => {
+                    *quantity += line.quantity;
+                    *waste_percent = waste;
+                }
+                None => merged.push((line.component, line.quantity, waste)),
+            }
+        }
+        Ok(merged)
+    }
+}
+
+fn line_diff(
+    component: RecipeComponent,
+    change: &'static str,
+    from: Option<(Decimal, Decimal)>,
+    to: Option<(Decimal, Decimal)>,
+) -> RecipeLineDiff {
+    let (component_type, ingredient_catalog_uuid, recipe_sets_uuid) = match component {
+        RecipeComponent::Ingredient(uuid) => ("INGREDIENT", Some(uuid), None),
+        RecipeComponent::Recipe(uuid) => ("RECIPE", None, Some(uuid)),
+    };
+    RecipeLineDiff {
+        component_type,
+        ingredient_catalog_uuid,
+        recipe_sets_uuid,
+        change,
+        from_quantity: from.map(|(qty, _)| qty),
+        to_quantity: to.map(|(qty, _)| qty),
+        from_waste_percent: from.map(|(_, waste)| waste),
+        to_waste_percent: to.map(|(_, waste)| waste),
+    }
+}
+
+fn add_total(totals: &mut Vec<(Uuid, Decimal)>, ingredient: Uuid, quantity: Decimal) {
+    match totals.iter_mut().find(|(uuid, _)| *uuid == ingredient) {
+        Some((_, total)) => *total += quantity,
+        None => totals.push((ingredient, quantity)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn line(component: RecipeComponent, quantity: &str) -> RecipeLine {
+        RecipeLine {
+            component,
+            quantity: dec(quantity),
+            waste_percent: None,
+        }
+    }
+
+    // syrup (yield 2): sugar 1, water 1 -> latte (yield 1): syrup 0.5, milk 0.2
+    fn sample_graph() -> (RecipeGraph, Uuid, Uuid, Uuid, Uuid, Uuid, Uuid) {
+        let (sugar, water, milk, syrup_stock) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let (syrup, latte) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut graph = RecipeGraph::default();
+        graph.nodes.insert(
+            syrup,
+            RecipeNode {
+                yield_quantity: Some(dec("2")),
+                output_ingredient_catalog_uuid: Some(syrup_stock),
+                lines: vec![
+                    line(RecipeComponent::Ingredient(sugar), "1"),
+                    line(RecipeComponent::Ingredient(water), "1"),
+                ],
+            },
+        );
+        graph.nodes.insert(
+            latte,
+            RecipeNode {
+                yield_quantity: Some(dec("1")),
+                output_ingredient_catalog_uuid: None,
+                lines: vec![
+                    line(RecipeComponent::Recipe(syrup), "0.5"),
+                    RecipeLine {
+                        component: RecipeComponent::Ingredient(milk),
+                        quantity: dec("0.2"),
+                        waste_percent: Some(dec("0.1")),
+                    },
+                ],
+            },
+        );
+        (graph, sugar, water, milk, syrup_stock, syrup, latte)
+    }
+
+    #[test]
+    fn detects_direct_and_indirect_cycles() {
+        let (graph, _, _, _, _, syrup, latte) = sample_graph();
+        assert!(graph.would_create_cycle(syrup, syrup));
+        // latte already uses syrup, so syrup may not use latte
+        assert!(graph.would_create_cycle(syrup, latte));
+        assert!(!graph.would_create_cycle(latte, syrup));
+    }
+
+    #[test]
+    fn explodes_through_levels_or_stops_at_stock() {
+        let (graph, sugar, water, milk, syrup_stock, _, latte) = sample_graph();
+
+        let raw = graph.explode(latte, dec("4"), false).unwrap();
+        // syrup needed = 2 -> sugar 1, water 1; milk = 0.2 * 1.1 * 4
+        assert!(raw.contains(&(sugar, dec("1"))));
+        assert!(raw.contains(&(water, dec("1"))));
+        assert!(raw.contains(&(milk, dec("0.88"))));
+
+        let stocked = graph.explode(latte, dec("4"), true).unwrap();
+        assert!(stocked.contains(&(syrup_stock, dec("2"))));
+        assert!(!stocked.iter().any(|(uuid, _)| *uuid == sugar));
+    }
+
+    #[test]
+    fn explode_net_ignores_waste_at_every_level() {
+        let (mut graph, sugar, _, milk, _, syrup, latte) = sample_graph();
+        graph.nodes.get_mut(&syrup).unwrap().lines[0].waste_percent = Some(dec("0.5"));
+
+        let gross = graph.explode(latte, dec("4"), false).unwrap();
+        let net = graph.explode_net(latte, dec("4"), false).unwrap();
+        assert!(gross.contains(&(sugar, dec("1.5"))));
+        assert!(net.contains(&(sugar, dec("1"))));
+        assert!(net.contains(&(milk, dec("0.8"))));
+    }
+
+    #[test]
+    fn explode_reports_cycles() {
+        let (mut graph, _, _, _, _, syrup, latte) = sample_graph();
+        graph
+            .nodes
+            .get_mut(&syrup)
+            .unwrap()
+            .lines
+            .push(line(RecipeComponent::Recipe(latte), "1"));
+        assert!(matches!(
+            graph.explode(latte, Decimal::ONE, false),
+            Err(RecipeGraphError::Cycle(_))
+        ));
+    }
+
+    #[test]
+    fn rolls_cost_up_through_every_level() {
+        let (graph, sugar, water, milk, _, _, latte) = sample_graph();
+        let prices = HashMap::from([(sugar, dec("10")), (milk, dec("20"))]);
+
+        let breakdown = graph.roll_up_cost(latte, &prices).unwrap();
+        // syrup batch = 10 + 0 (water unpriced) -> unit 5; latte = 0.5 * 5 + 0.22 * 20
+        assert_eq!(breakdown.lines[0].unit_cost, Some(dec("5")));
+        assert_eq!(breakdown.batch_cost, dec("6.9"));
+        assert_eq!(breakdown.unit_cost, dec("6.9"));
+        assert_eq!(breakdown.missing_prices, vec![water]);
+    }
+
+    #[test]
+    fn diffs_items_and_cost_between_versions() {
+        let (mut graph, sugar, _, milk, _, syrup, latte) = sample_graph();
+        let oat = Uuid::new_v4();
+        let latte_v2 = Uuid::new_v4();
+        graph.nodes.insert(
+            latte_v2,
+            RecipeNode {
+                yield_quantity: Some(dec("1")),
+                output_ingredient_catalog_uuid: None,
+                lines: vec![
+                    line(RecipeComponent::Recipe(syrup), "0.5"),
+                    line(RecipeComponent::Ingredient(oat), "0.2"),
+                ],
+            },
+        );
+        let prices = HashMap::from([(sugar, dec("10")), (milk, dec("20")), (oat, dec("30"))]);
+
+        let diff = graph.diff_versions(latte, latte_v2, &prices).unwrap();
+        let change_of = |component: RecipeComponent| {
+            let (ingredient, recipe) = match component {
+                RecipeComponent::Ingredient(uuid) => (Some(uuid), None),
+                RecipeComponent::Recipe(uuid) => (None, Some(uuid)),
+            };
+            diff.lines
+                .iter()
+                .find(|l| l.ingredient_catalog_uuid == ingredient && l.recipe_sets_uuid == recipe)
+                .map(|l| l.change)
+        };
+        assert_eq!(change_of(RecipeComponent::Recipe(syrup)), Some("UNCHANGED"));
+        assert_eq!(
+            change_of(RecipeComponent::Ingredient(milk)),
+            Some("REMOVED")
+        );
+        assert_eq!(change_of(RecipeComponent::Ingredient(oat)), Some("ADDED"));
+        // v1 = 6.9; v2 = 0.5 * 5 + 0.2 * 30 = 8.5
+        assert_eq!(diff.to_unit_cost, dec("8.5"));
+        assert_eq!(diff.unit_cost_delta, dec("1.6"));
+    }
+}
@@ -0,0 +1,270 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::fmt;
+use uuid::Uuid;
+
+// ID: Perhitungan refund yang murni, tanpa akses DB. Nominal refund per baris mengikuti porsi
+//     baris terhadap total pesanan (diskon/pajak ikut diprorata), lalu dibagi ke tender asal
+//     dari pembayaran terbaru.
+// EN: Pure refund maths, no database access. A line's refund follows its share of the order
+//     total (discount/tax prorated), then gets split across the original tenders starting
+//     from the most recent payment.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefundError {
+    NothingToRefund,
+    QtyExceedsRemaining { remaining: Decimal },
+    ExceedsRefundable { refundable: Decimal },
+}
+
+impl fmt::Display for RefundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefundError::NothingToRefund => write!(f, "Tidak ada yang bisa direfund"),
+            RefundError::QtyExceedsRemaining { remaining } => write!(
+                f,
+                "Qty refund melebihi sisa qty yang bisa direfund ({})",
+                remaining
+            ),
+            RefundError::ExceedsRefundable { refundable } => write!(
+                f,
+                "Nominal refund melebihi pembayaran yang bisa dikembalikan ({})",
+                refundable
+            ),
+        }
+    }
+}
+
+/// Saldo tender yang masih bisa direfund (nominal dibayar - refund sebelumnya)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TenderBalance {
+    pub payment_uuid: Uuid,
+    pub refundable: Decimal,
+    // Gateway hanya menerima rupiah bulat (QRIS Xendit)
+    pub whole_units: bool,
+}
+
+/// ID: Pastikan qty refund positif dan tidak melebihi qty baris dikurangi refund sebelumnya.
+/// EN: Ensure the refund qty is positive and within the line qty minus earlier refunds.
+pub fn check_line_qty(
+    qty: Decimal,
+    item_qty: Decimal,
+    already_refunded: Decimal,
+) -> Result<(), RefundError> {
+    let remaining = (item_qty - already_refunded).max(Decimal::ZERO);
+    if qty <= Decimal::ZERO {
+        return Err(RefundError::NothingToRefund);
+    }
+    if qty > remaining {
+        return Err(RefundError::QtyExceedsRemaining { remaining });
+    }
+    Ok(())
+}
+
+/// ID: Porsi total pesanan untuk `qty` dari sebuah baris: line_total * qty/item_qty * total/subtotal.
+/// EN: Share of the order total for `qty` of a line: line_total * qty/item_qty * total/subtotal.
+pub fn line_refund_amount(
+    qty: Decimal,
+    item_qty: Decimal,
+    line_total: Decimal,
+    order_subtotal: Decimal,
+    order_total: Decimal,
+) -> Decimal {
+    if item_qty <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    let line_share = line_total * qty / item_qty;
+    let prorated = if order_subtotal > Decimal::ZERO {
+        line_share * order_total / order_subtotal
+    } else {
+        line_share
+    };
+    prorated.max(Decimal::ZERO).round_dp(2)
+}
+
+/// ID: Laba yang dibatalkan. Bahan yang tidak kembali ke stok tetap menjadi biaya.
+/// EN: Profit reversed. Ingredients that don't go back to stock remain a cost.
+pub fn profit_reversed(amount: Decimal, cost_amount: Decimal, restock: bool) -> Decimal {
+    if restock {
+        (amount - cost_amount).round_dp(2)
+    } else {
+        amount.round_dp(2)
+    }
+}
+
+/// ID: Bagi nominal refund ke tender (urutan input = prioritas, biasanya pembayaran terbaru dulu).
+/// Porsi tender `whole_units` dibulatkan ke rupiah penuh; selisihnya diteruskan ke tender
+/// berikutnya, sehingga total yang dibagi bisa berbeda kurang dari 1 dari `amount`.
+/// EN: Split a refund across tenders (input order = priority, usually most recent payment first).
+/// Shares of `whole_units` tenders are rounded to whole rupiah; the difference carries over to
+/// the next tender, so the split total may differ from `amount` by less than 1.
+pub fn split_across_tenders(
+    amount: Decimal,
+    tenders: &[TenderBalance],
+) -> Result<Vec<(Uuid, Decimal)>, RefundError> {
+    let amount = amount.round_dp(2);
+    if amount <= Decimal::ZERO {
+        return Ok(Vec::new());
+    }
+    let refundable: Decimal = tenders
+        .iter()
+        .map(|t| t.refundable.max(Decimal::ZERO))
+        .sum();
+    if amount > refundable {
+        return Err(RefundError::ExceedsRefundable { refundable });
+    }
+
+    let mut left = amount;
+    let mut parts = Vec::new();
+    for tender in tenders {
+        if left <= Decimal::ZERO {
+            break;
+        }
+        let available = tender.refundable.max(Decimal::ZERO);
+        let mut part = available.min(left);
+        if tender.whole_units {
+            part = part
+                .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+                .min(available.floor());
+        }
+        if part > Decimal::ZERO {
+            parts.push((tender.payment_uuid, part));
+            left -= part;
+        }
+    }
+    Ok(parts)
+}
+
+/// ID: Batasi restock per bahan ke yang benar-benar dipotong penjualan pesanan (SALE dikurangi
+///     RETURN sebelumnya). Bahan yang tidak pernah dipotong tidak dikembalikan.
+/// EN: Cap restock per ingredient at what the order's sale actually deducted (SALE minus
+///     earlier RETURNs). Ingredients that were never deducted are not returned.
+pub fn cap_restock(
+    lines: &[(Uuid, Decimal)],
+    restockable: &[(Uuid, Decimal)],
+) -> Vec<(Uuid, Decimal)> {
+    lines
+        .iter()
+        .filter_map(|(ingredient_catalog_uuid, quantity)| {
+            let available = restockable
+                .iter()
+                .find(|(uuid, _)| uuid == ingredient_catalog_uuid)
+                .map(|(_, available)| *available)
+                .unwrap_or(Decimal::ZERO);
+            let capped = (*quantity).min(available);
+            (capped > Decimal::ZERO).then_some((*ingredient_catalog_uuid, capped))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn line_amount_is_prorated_by_discount_and_tax() {
+        // subtotal 200, diskon 20, pajak 10 -> total 190
+        let amount = line_refund_amount(dec("1"), dec("2"), dec("100"), dec("200"), dec("190"));
+        assert_eq!(amount, dec("47.50"));
+        let untaxed = line_refund_amount(dec("3"), dec("3"), dec("60"), dec("0"), dec("0"));
+        assert_eq!(untaxed, dec("60"));
+    }
+
+    #[test]
+    fn line_qty_cannot_exceed_what_is_left() {
+        assert!(check_line_qty(dec("1"), dec("3"), dec("2")).is_ok());
+        assert_eq!(
+            check_line_qty(dec("2"), dec("3"), dec("2")),
+            Err(RefundError::QtyExceedsRemaining {
+                remaining: dec("1")
+            })
+        );
+        assert_eq!(
+            check_line_qty(dec("0"), dec("3"), dec("0")),
+            Err(RefundError::NothingToRefund)
+        );
+    }
+
+    #[test]
+    fn refund_is_split_across_tenders_in_order() {
+        let card = Uuid::new_v4();
+        let cash = Uuid::new_v4();
+        let tenders = [
+            TenderBalance {
+                payment_uuid: card,
+                refundable: dec("60"),
+                whole_units: false,
+            },
+            TenderBalance {
+                payment_uuid: cash,
+                refundable: dec("40"),
+                whole_units: false,
+            },
+        ];
+        assert_eq!(
+            split_across_tenders(dec("75"), &tenders).unwrap(),
+            vec![(card, dec("60")), (cash, dec("15"))]
+        );
+        assert_eq!(
+            split_across_tenders(dec("120"), &tenders),
+            Err(RefundError::ExceedsRefundable {
+                refundable: dec("100")
+            })
+        );
+        assert!(split_across_tenders(dec("0"), &tenders).unwrap().is_empty());
+    }
+
+    #[test]
+    fn qris_share_is_whole_rupiah() {
+        let qris = Uuid::new_v4();
+        let cash = Uuid::new_v4();
+        let tender = |payment_uuid, refundable: &str, whole_units| TenderBalance {
+            payment_uuid,
+            refundable: dec(refundable),
+            whole_units,
+        };
+
+        // QRIS saja: 12 500,50 dibukukan dan dikirim sebagai 12 501
+        let only_qris = [tender(qris, "20000", true)];
+        assert_eq!(
+            split_across_tenders(dec("12500.50"), &only_qris).unwrap(),
+            vec![(qris, dec("12501"))]
+        );
+
+        // Selisih pembulatan QRIS diteruskan ke tunai
+        let mixed = [tender(qris, "10000", true), tender(cash, "5000", false)];
+        assert_eq!(
+            split_across_tenders(dec("9999.40"), &mixed).unwrap(),
+            vec![(qris, dec("9999")), (cash, dec("0.40"))]
+        );
+        // Pembulatan ke atas tidak melebihi saldo QRIS
+        assert_eq!(
+            split_across_tenders(dec("10000.60"), &mixed).unwrap(),
+            vec![(qris, dec("10000")), (cash, dec("0.60"))]
+        );
+    }
+
+    #[test]
+    fn restock_is_capped_by_what_the_sale_deducted() {
+        let flour = Uuid::new_v4();
+        let sugar = Uuid::new_v4();
+        let milk = Uuid::new_v4();
+        let lines = [(flour, dec("3")), (sugar, dec("1")), (milk, dec("2"))];
+        let restockable = [(flour, dec("2")), (sugar, dec("5"))];
+        assert_eq!(
+            cap_restock(&lines, &restockable),
+            vec![(flour, dec("2")), (sugar, dec("1"))]
+        );
+        assert!(cap_restock(&lines, &[]).is_empty());
+    }
+
+    #[test]
+    fn restock_keeps_ingredient_cost_out_of_reversed_profit() {
+        assert_eq!(profit_reversed(dec("50"), dec("20"), true), dec("30"));
+        assert_eq!(profit_reversed(dec("50"), dec("20"), false), dec("50"));
+    }
+}
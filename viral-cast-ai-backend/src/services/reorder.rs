@@ -0,0 +1,151 @@
+use rust_decimal::Decimal;
+
+// ID: Perhitungan saran pemesanan ulang (reorder) yang murni, tanpa akses DB
+// EN: Pure reorder suggestion math, no database access
+
+#[derive(Debug, Clone, Default)]
+pub struct ReorderInput {
+    pub on_hand: Decimal,
+    pub on_order: Decimal,
+    pub minimum_stock: Decimal,
+    pub daily_usage: Decimal,
+    pub lead_time_days: i32,
+    pub review_days: i32,
+    pub min_order_qty: Option<Decimal>,
+    pub pack_size: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReorderOutcome {
+    pub reorder_point: Decimal,
+    pub target_stock: Decimal,
+    pub shortfall: Decimal,
+    pub suggested_qty: Decimal,
+}
+
+/// Kebutuhan bahan per 1 unit produk: quantity * (1 + waste) / yield.
+pub fn ingredient_usage_per_unit(
+    quantity: Decimal,
+    waste_percent: Option<Decimal>,
+    yield_quantity: Option<Decimal>,
+) -> Decimal {
+    let waste = waste_percent.unwrap_or(Decimal::ZERO).max(Decimal::ZERO);
+    let yield_qty = match yield_quantity {
+        Some(y) if y > Decimal::ZERO => y,
+        _ => Decimal::ONE,
+    };
+    quantity * (Decimal::ONE + waste) / yield_qty
+}
+
+/// Membulatkan ke atas ke kelipatan pack_size (jika ada).
+pub fn round_up_to_pack(qty: Decimal, pack_size: Option<Decimal>) -> Decimal {
+    match pack_size {
+        Some(pack) if pack > Decimal::ZERO && qty > Decimal::ZERO => (qty / pack).ceil() * pack,
+        _ => qty,
+    }
+}
+
+/// ID: Titik reorder = pemakaian harian * lead time + minimum stok.
+/// Target stok = pemakaian harian * (lead time + periode review) + minimum stok.
+/// Saran = target - (stok + pesanan berjalan), dibulatkan ke pack dan minimal order.
+/// EN: Reorder point = daily usage * lead time + minimum stock. The suggestion tops the
+/// inventory position (on hand + on order) up to the target for lead time + review period.
+pub fn compute_reorder(input: &ReorderInput) -> ReorderOutcome {
+    let daily_usage = input.daily_usage.max(Decimal::ZERO);
+    let lead_time = Decimal::from(input.lead_time_days.max(0));
+    let review = Decimal::from(input.review_days.max(0));
+    let minimum_stock = input.minimum_stock.max(Decimal::ZERO);
+
+    let reorder_point = daily_usage * lead_time + minimum_stock;
+    let target_stock = daily_usage * (lead_time + review) + minimum_stock;
+    let position = input.on_hand.max(Decimal::ZERO) + input.on_order.max(Decimal::ZERO);
+
+    let shortfall = (target_stock - position).max(Decimal::ZERO);
+    let mut suggested_qty = if position <= reorder_point && shortfall > Decimal::ZERO {
+        round_up_to_pack(shortfall, input.pack_size)
+    } else {
+        Decimal::ZERO
+    };
+
+    if suggested_qty > Decimal::ZERO {
+        if let Some(min_qty) = input.min_order_qty {
+            if suggested_qty < min_qty {
+                suggested_qty = round_up_to_pack(min_qty, input.pack_size);
+            }
+        }
+    }
+
+    ReorderOutcome {
+        reorder_point: reorder_point.round_dp(4),
+        target_stock: target_stock.round_dp(4),
+        shortfall: shortfall.round_dp(4),
+        suggested_qty: suggested_qty.round_dp(4),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn usage_per_unit_applies_waste_and_yield() {
+        let usage = ingredient_usage_per_unit(dec("200"), Some(dec("0.1")), Some(dec("4")));
+        assert_eq!(usage, dec("55"));
+        let no_yield = ingredient_usage_per_unit(dec("10"), None, Some(Decimal::ZERO));
+        assert_eq!(no_yield, dec("10"));
+    }
+
+    #[test]
+    fn rounds_up_to_pack_size() {
+        assert_eq!(round_up_to_pack(dec("7"), Some(dec("5"))), dec("10"));
+        assert_eq!(round_up_to_pack(dec("10"), Some(dec("5"))), dec("10"));
+        assert_eq!(round_up_to_pack(dec("7"), None), dec("7"));
+    }
+
+    #[test]
+    fn suggests_nothing_above_reorder_point() {
+        let outcome = compute_reorder(&ReorderInput {
+            on_hand: dec("100"),
+            minimum_stock: dec("10"),
+            daily_usage: dec("5"),
+            lead_time_days: 2,
+            review_days: 7,
+            ..Default::default()
+        });
+        assert_eq!(outcome.reorder_point, dec("20"));
+        assert_eq!(outcome.suggested_qty, Decimal::ZERO);
+    }
+
+    #[test]
+    fn tops_up_to_target_with_pack_and_minimum() {
+        let outcome = compute_reorder(&ReorderInput {
+            on_hand: dec("12"),
+            on_order: dec("3"),
+            minimum_stock: dec("10"),
+            daily_usage: dec("5"),
+            lead_time_days: 2,
+            review_days: 2,
+            min_order_qty: None,
+            pack_size: Some(dec("4")),
+        });
+        // target = 5 * 4 + 10 = 30, position = 15, shortfall = 15 -> 16 (pack 4)
+        assert_eq!(outcome.shortfall, dec("15"));
+        assert_eq!(outcome.suggested_qty, dec("16"));
+
+        let with_min = compute_reorder(&ReorderInput {
+            on_hand: dec("0"),
+            minimum_stock: dec("1"),
+            daily_usage: dec("1"),
+            lead_time_days: 1,
+            min_order_qty: Some(dec("25")),
+            pack_size: Some(dec("10")),
+            ..Default::default()
+        });
+        assert_eq!(with_min.suggested_qty, dec("30"));
+    }
+}
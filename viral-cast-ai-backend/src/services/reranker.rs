@@ -0,0 +1,418 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::ai::{GroqApiRequest, GroqApiResponse, GroqMessage};
+
+// ID: Tahap rerank RAG. Cross-encoder dipanggil lewat endpoint `/rerank` ala
+// text-embeddings-inference (model seperti bge-reranker berjalan lokal di CPU); tanpa endpoint
+// itu, kandidat diberi skor oleh LLM lewat Groq chat completions.
+// EN: RAG rerank stage. The cross-encoder is called through a text-embeddings-inference style
+// `/rerank` endpoint (models such as bge-reranker run locally on CPU); without that endpoint
+// candidates are scored by an LLM through Groq chat completions.
+
+/// Prefix reranking_model untuk memaksa reranker LLM, mis. "llm" atau "llm:llama-3.1-8b-instant"
+pub const LLM_MODEL_PREFIX: &str = "llm";
+const DEFAULT_CROSS_ENCODER_MODEL: &str = "BAAI/bge-reranker-base";
+const DEFAULT_TOP_N: usize = 20;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+// Potong passage untuk prompt LLM agar token tetap kecil
+const LLM_PASSAGE_CHARS: usize = 800;
+
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    fn model(&self) -> &str;
+
+    /// Skor relevansi 0..1 per passage, urutan sama dengan input
+    async fn rerank(&self, query: &str, passages: &[String]) -> Result<Vec<f32>>;
+}
+
+// =============== CROSS-ENCODER (TEI /rerank) =================
+
+#[derive(Serialize)]
+struct RerankRequest<'a> {
+    query: &'a str,
+    texts: &'a [String],
+    truncate: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RerankResponse {
+    // text-embeddings-inference: [{"index":0,"score":0.9}]
+    Tei(Vec<RerankItem>),
+    // Jina / Cohere style: {"results":[{"index":0,"relevance_score":0.9}]}
+    Results { results: Vec<RerankItem> },
+}
+
+#[derive(Deserialize)]
+struct RerankItem {
+    index: usize,
+    #[serde(alias = "relevance_score")]
+    score: f32,
+}
+
+pub struct CrossEncoderReranker {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl CrossEncoderReranker {
+    pub fn new(client: reqwest::Client, url: &str, api_key: Option<String>, model: &str) -> Self {
+        let url = url.trim_end_matches('/');
+        let endpoint = if url.ends_with("/rerank") {
+            url.to_string()
+        } else {
+            format!("{}/rerank", url)
+        };
+        Self {
+            client,
+            endpoint,
+            api_key,
+            model: model.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Reranker for CrossEncoderReranker {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn rerank(&self, query: &str, passages: &[String]) -> Result<Vec<f32>> {
+        if passages.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut request = self.client.post(&self.endpoint).json(&RerankRequest {
+            query,
+            texts: passages,
+            truncate: true,
+        });
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Calling rerank endpoint {}", self.endpoint))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Rerank endpoint returned {}: {}",
+                status,
+                body.chars().take(300).collect::<String>()
+            ));
+        }
+
+        let items = match response
+            .json::<RerankResponse>()
+            .await
+            .context("Parsing rerank response")?
+        {
+            RerankResponse::Tei(items) => items,
+            RerankResponse::Results { results } => results,
+        };
+        let mut scores = vec![None; passages.len()];
+        for item in items {
+            if let Some(slot) = scores.get_mut(item.index) {
+                *slot = Some(item.score);
+            }
+        }
+        scores
+            .into_iter()
+            .enumerate()
+            .map(|(i, score)| score.ok_or_else(|| anyhow!("Rerank response missing index {}", i)))
+            .collect()
+    }
+}
+
+// =============== LLM-SCORED (GROQ) =================
+
+pub struct LlmReranker {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+    model: String,
+}
+
+impl LlmReranker {
+    pub fn new(client: reqwest::Client, api_url: &str, api_key: &str, model: &str) -> Self {
+        Self {
+            client,
+            api_url: api_url.to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+        }
+    }
+}
+
+/// ID: Ambil array skor 0-10 dari jawaban LLM (boleh dibungkus teks lain), lalu skala ke 0..1.
+/// EN: Extract the 0-10 score array from the LLM answer (may be wrapped in other text), scaled to 0..1.
+pub fn parse_llm_scores(answer: &str, expected: usize) -> Result<Vec<f32>> {
+    let start = answer
+        .find('[')
+        .ok_or_else(|| anyhow!("LLM rerank answer has no score array"))?;
+    let end = answer[start..]
+        .find(']')
+        .map(|i| start + i)
+        .ok_or_else(|| anyhow!("LLM rerank answer has an unterminated score array"))?;
+    let scores: Vec<f32> = serde_json::from_str(&answer[start..=end])
+        .with_context(|| format!("Parsing LLM rerank scores {:?}", &answer[start..=end]))?;
+    if scores.len() != expected {
+        return Err(anyhow!(
+            "LLM returned {} scores for {} passages",
+            scores.len(),
+            expected
+        ));
+    }
+    Ok(scores
+        .into_iter()
+        .map(|s| (s / 10.0).clamp(0.0, 1.0))
+        .collect())
+}
+
+#[async_trait]
+impl Reranker for LlmReranker {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn rerank(&self, query: &str, passages: &[String]) -> Result<Vec<f32>> {
+        if passages.is_empty() {
+            return Ok(Vec::new());
+        }
+        let numbered = passages
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                format!(
+                    "[{}] {}",
+                    i,
+                    p.chars().take(LLM_PASSAGE_CHARS).collect::<String>()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let user_prompt = format!(
+            "Pertanyaan:\n{}\n\nPassage:\n{}\n\nBeri skor relevansi 0-10 untuk setiap passage terhadap pertanyaan. \
+             Jawab HANYA dengan array JSON berisi {} angka sesuai urutan passage, contoh: [7, 0, 10].",
+            query,
+            numbered,
+            passages.len()
+        );
+        let body = GroqApiRequest {
+            messages: vec![
+                GroqMessage {
+                    role: "system".to_string(),
+                    content:
+                        "You are a search relevance judge. Output only a JSON array of numbers."
+                            .to_string(),
+                },
+                GroqMessage {
+                    role: "user".to_string(),
+                    content: user_prompt,
+                },
+            ],
+            model: self.model.clone(),
+            max_tokens: Some((passages.len() * 4 + 16) as u32),
+            temperature: Some(0.0),
+        };
+
+        let response = self
+            .client
+            .post(&self.api_url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .context("Calling Groq for LLM rerank")?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow!("Groq rerank request returned {}", status));
+        }
+        let parsed: GroqApiResponse = response
+            .json()
+            .await
+            .context("Parsing Groq rerank response")?;
+        let answer = parsed
+            .choices
+            .first()
+            .map(|c| c.message.content.as_str())
+            .unwrap_or_default();
+        parse_llm_scores(answer, passages.len())
+    }
+}
+
+// =============== SETTINGS =================
+
+/// ID: Endpoint reranker dari env; aktif/tidaknya & model dari rag_configurations.
+/// EN: Reranker endpoints from env; enablement & model come from rag_configurations.
+#[derive(Clone)]
+pub struct RerankSettings {
+    pub api_url: Option<String>,
+    pub api_key: Option<String>,
+    pub groq_api_url: String,
+    pub groq_api_key: Option<String>,
+    pub groq_model: String,
+    /// Jumlah kandidat teratas yang diberi skor ulang
+    pub top_n: usize,
+    client: reqwest::Client,
+}
+
+impl RerankSettings {
+    pub fn new(
+        api_url: Option<String>,
+        api_key: Option<String>,
+        top_n: Option<usize>,
+        groq_api_url: String,
+        groq_api_key: Option<String>,
+        groq_model: String,
+    ) -> Self {
+        Self {
+            api_url: api_url.filter(|url| !url.trim().is_empty()),
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            groq_api_url,
+            groq_api_key: groq_api_key.filter(|key| !key.trim().is_empty()),
+            groq_model,
+            top_n: top_n.unwrap_or(DEFAULT_TOP_N).max(1),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// ID: Pilih reranker: cross-encoder bila RERANK_API_URL diset (kecuali model diawali "llm"),
+    /// jika tidak LLM lewat Groq. None bila keduanya tidak tersedia.
+    /// EN: Pick the reranker: cross-encoder when RERANK_API_URL is set (unless the model starts
+    /// with "llm"), otherwise the Groq LLM. None when neither is available.
+    pub fn reranker_for(&self, reranking_model: Option<&str>) -> Option<Arc<dyn Reranker>> {
+        let model = reranking_model.map(str::trim).filter(|m| !m.is_empty());
+        let wants_llm = model.is_some_and(|m| m.starts_with(LLM_MODEL_PREFIX));
+
+        if let (Some(url), false) = (&self.api_url, wants_llm) {
+            return Some(Arc::new(CrossEncoderReranker::new(
+                self.client.clone(),
+                url,
+                self.api_key.clone(),
+                model.unwrap_or(DEFAULT_CROSS_ENCODER_MODEL),
+            )));
+        }
+
+        let key = self.groq_api_key.as_deref()?;
+        let llm_model = model
+            .filter(|_| wants_llm)
+            .and_then(|m| m.split_once(':'))
+            .map(|(_, name)| name.trim())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.groq_model);
+        Some(Arc::new(LlmReranker::new(
+            self.client.clone(),
+            &self.groq_api_url,
+            key,
+            llm_model,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(api_url: Option<&str>, groq_key: Option<&str>) -> RerankSettings {
+        RerankSettings::new(
+            api_url.map(str::to_string),
+            None,
+            None,
+            "https://api.groq.com/openai/v1/chat/completions".to_string(),
+            groq_key.map(str::to_string),
+            "llama-3.1-8b-instant".to_string(),
+        )
+    }
+
+    #[test]
+    fn picks_reranker_from_configuration() {
+        assert!(settings(None, None).reranker_for(None).is_none());
+
+        let cross = settings(Some("http://localhost:8081"), Some("k"));
+        assert_eq!(
+            cross.reranker_for(None).unwrap().model(),
+            DEFAULT_CROSS_ENCODER_MODEL
+        );
+        assert_eq!(
+            cross
+                .reranker_for(Some("BAAI/bge-reranker-v2-m3"))
+                .unwrap()
+                .model(),
+            "BAAI/bge-reranker-v2-m3"
+        );
+        assert_eq!(
+            cross.reranker_for(Some("llm")).unwrap().model(),
+            "llama-3.1-8b-instant"
+        );
+
+        let llm = settings(None, Some("k"));
+        assert_eq!(
+            llm.reranker_for(Some("llm:llama-3.3-70b-versatile"))
+                .unwrap()
+                .model(),
+            "llama-3.3-70b-versatile"
+        );
+        // Model cross-encoder tanpa endpoint jatuh ke LLM default
+        assert_eq!(
+            llm.reranker_for(Some("BAAI/bge-reranker-base"))
+                .unwrap()
+                .model(),
+            "llama-3.1-8b-instant"
+        );
+    }
+
+    #[test]
+    fn parses_llm_scores() {
+        assert_eq!(
+            parse_llm_scores("Skor: [10, 5, 0]", 3).unwrap(),
+            vec![1.0, 0.5, 0.0]
+        );
+        assert_eq!(parse_llm_scores("[12.5]", 1).unwrap(), vec![1.0]);
+        assert!(parse_llm_scores("[1, 2]", 3).is_err());
+        assert!(parse_llm_scores("tidak relevan", 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn cross_encoder_maps_scores_back_to_input_order() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let _ = socket.read(&mut buf).await.unwrap();
+            // TEI mengembalikan hasil terurut berdasarkan skor, bukan urutan input
+            let body = r#"[{"index":1,"score":0.9},{"index":0,"score":0.1}]"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let reranker = settings(Some(&format!("http://{}", addr)), None)
+            .reranker_for(None)
+            .unwrap();
+        let scores = reranker
+            .rerank("kopi", &["teh".to_string(), "kopi susu".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(scores, vec![0.1, 0.9]);
+    }
+}
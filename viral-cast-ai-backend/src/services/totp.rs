@@ -0,0 +1,232 @@
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+// ID: TOTP (RFC 6238) untuk autentikasi dua faktor: HMAC-SHA1, 6 digit, langkah 30 detik,
+// kompatibel dengan Google Authenticator, Authy, 1Password, dll. Secret disimpan sebagai
+// base32 (RFC 4648, tanpa padding) seperti yang diminta URI `otpauth://`.
+// EN: TOTP (RFC 6238) for two-factor authentication: HMAC-SHA1, 6 digits, 30-second steps,
+// compatible with Google Authenticator, Authy, 1Password, etc. Secrets are kept as unpadded
+// base32 (RFC 4648), the form the `otpauth://` URI expects.
+
+type HmacSha1 = Hmac<Sha1>;
+
+pub const STEP_SECONDS: u64 = 30;
+pub const DIGITS: u32 = 6;
+// Toleransi jam ponsel yang meleset: satu langkah sebelum dan sesudah
+const ALLOWED_DRIFT_STEPS: u64 = 1;
+const SECRET_BYTES: usize = 20;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+// Kode pemulihan: tanpa 0/1/o/l/i agar tidak tertukar saat diketik dari kertas
+const RECOVERY_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_CODE_GROUP: usize = 5;
+
+/// ID: Secret baru 160-bit dalam base32.
+/// EN: A new 160-bit secret in base32.
+pub fn generate_secret() -> String {
+    base32_encode(&rand::random::<[u8; SECRET_BYTES]>())
+}
+
+/// ID: URI `otpauth://totp/...` untuk dijadikan QR code oleh aplikasi klien.
+/// EN: `otpauth://totp/...` URI for the client app to render as a QR code.
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        STEP_SECONDS
+    )
+}
+
+pub fn current_step(unix_seconds: u64) -> u64 {
+    unix_seconds / STEP_SECONDS
+}
+
+/// ID: Kode TOTP untuk satu langkah waktu, atau None bila secret bukan base32 yang valid.
+/// EN: The TOTP code for one time step, or None when the secret is not valid base32.
+pub fn code_at(secret: &str, step: u64) -> Option<String> {
+    let key = base32_decode(secret)?;
+    let mut mac = HmacSha1::new_from_slice(&key).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    // Dynamic truncation (RFC 4226 5.3)
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    Some(format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    ))
+}
+
+/// ID: Cocokkan kode dalam jendela toleransi. Mengembalikan langkah yang cocok; langkah yang
+///     tidak lebih baru dari `last_used_step` ditolak agar kode yang sama tidak bisa diputar ulang.
+/// EN: Match a code within the drift window. Returns the matching step; steps not newer than
+///     `last_used_step` are rejected so the same code cannot be replayed.
+pub fn verify(
+    secret: &str,
+    code: &str,
+    unix_seconds: u64,
+    last_used_step: Option<u64>,
+) -> Option<u64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let now = current_step(unix_seconds);
+    (now.saturating_sub(ALLOWED_DRIFT_STEPS)..=now + ALLOWED_DRIFT_STEPS)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| code_at(secret, *step).is_some_and(|expected| expected == code))
+}
+
+/// ID: Kode pemulihan sekali pakai, format `xxxxx-xxxxx`.
+/// EN: Single-use recovery codes, formatted `xxxxx-xxxxx`.
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| {
+            let chars: Vec<char> = (0..RECOVERY_CODE_GROUP * 2)
+                .map(|_| {
+                    RECOVERY_ALPHABET[rand::random::<usize>() % RECOVERY_ALPHABET.len()] as char
+                })
+                .collect();
+            format!(
+                "{}-{}",
+                chars[..RECOVERY_CODE_GROUP].iter().collect::<String>(),
+                chars[RECOVERY_CODE_GROUP..].iter().collect::<String>()
+            )
+        })
+        .collect()
+}
+
+/// ID: Bentuk baku kode pemulihan sebelum di-hash (huruf kecil, tanpa spasi/tanda hubung).
+/// EN: Canonical form of a recovery code before hashing (lowercase, no spaces or dashes).
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    output
+}
+
+fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    if output.is_empty() {
+        return None;
+    }
+    Some(output)
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 Appendix B (SHA1), secret ASCII "12345678901234567890", 6 digit terakhir
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn matches_rfc_6238_vectors() {
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+        assert_eq!(code_at(RFC_SECRET, current_step(59)).unwrap(), "287082");
+        assert_eq!(
+            code_at(RFC_SECRET, current_step(1_111_111_109)).unwrap(),
+            "081804"
+        );
+        assert_eq!(
+            code_at(RFC_SECRET, current_step(1_234_567_890)).unwrap(),
+            "005924"
+        );
+    }
+
+    #[test]
+    fn verify_allows_drift_and_rejects_replay() {
+        let now = 1_234_567_890;
+        let step = current_step(now);
+        let previous = code_at(RFC_SECRET, step - 1).unwrap();
+        assert_eq!(verify(RFC_SECRET, &previous, now, None), Some(step - 1));
+        assert_eq!(verify(RFC_SECRET, "005 924", now, None), Some(step));
+        assert_eq!(verify(RFC_SECRET, "005924", now, Some(step)), None);
+        assert_eq!(verify(RFC_SECRET, &previous, now, Some(step - 1)), None);
+        let stale = code_at(RFC_SECRET, step - 2).unwrap();
+        assert_eq!(verify(RFC_SECRET, &stale, now, None), None);
+        assert_eq!(verify(RFC_SECRET, "12345", now, None), None);
+    }
+
+    #[test]
+    fn secrets_round_trip_through_base32() {
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(base32_decode(&secret).unwrap().len(), SECRET_BYTES);
+        assert!(base32_decode("not base32!").is_none());
+    }
+
+    #[test]
+    fn provisioning_uri_escapes_labels() {
+        let uri = provisioning_uri("Viral Cast AI", "owner@example.com", RFC_SECRET);
+        assert!(uri.starts_with("otpauth://totp/Viral%20Cast%20AI:owner%40example.com?"));
+        assert!(uri.contains(&format!("secret={}", RFC_SECRET)));
+        assert!(uri.contains("issuer=Viral%20Cast%20AI"));
+    }
+
+    #[test]
+    fn recovery_codes_are_unique_and_normalize() {
+        let codes = generate_recovery_codes(10);
+        assert_eq!(codes.len(), 10);
+        assert!(codes
+            .iter()
+            .all(|c| c.len() == 11 && c.as_bytes()[5] == b'-'));
+        let mut unique = codes.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 10);
+        assert_eq!(normalize_recovery_code(" ABCDE-fghjk "), "abcdefghjk");
+    }
+}
@@ -0,0 +1,361 @@
+use crate::models::rag::{Document, DocumentProcessingJob};
+use crate::AppState;
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+// Antrian ingest dokumen RAG di atas tabel document_processing_jobs.
+// Worker mengklaim job dengan FOR UPDATE SKIP LOCKED sehingga aman dijalankan di beberapa instance.
+const POLL_INTERVAL_SECS: u64 = 5;
+// Job 'processing' yang tidak memperbarui updated_at selama ini dianggap yatim (worker mati)
+const STALE_LEASE_SECS: f64 = 900.0;
+const RETRY_BASE_SECS: i64 = 30;
+const RETRY_MAX_SECS: i64 = 1800;
+
+// Dibangunkan saat job baru masuk agar worker tidak menunggu interval polling
+static QUEUE_SIGNAL: Notify = Notify::const_new();
+
+pub fn spawn_ingest_workers(state: Arc<AppState>, concurrency: usize) {
+    for worker_idx in 0..concurrency.max(1) {
+        let st = state.clone();
+        let worker_id = format!("ingest-{}-{}", std::process::id(), worker_idx);
+        tokio::spawn(async move {
+            loop {
+                match claim_next_job(&st.db, &worker_id).await {
+                    Ok(Some(job)) => run_job(&st, job).await,
+                    Ok(None) => {
+                        let _ = tokio::time::timeout(
+                            Duration::from_secs(POLL_INTERVAL_SECS),
+                            QUEUE_SIGNAL.notified(),
+                        )
+                        .await;
+                    }
+                    Err(e) => {
+                        tracing::warn!(worker = %worker_id, error = %e, "[rag_ingest] failed to claim job");
+                        tokio::time::sleep(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// ID: Masukkan job ingest ke antrian dan tandai dokumen sebagai antre. Dokumen yang sudah
+/// 'ready' tetap 'ready' (chunk lama tetap bisa dicari) sampai reindex selesai.
+/// EN: Enqueue an ingestion job and mark the document as queued. A 'ready' document stays
+/// 'ready' (old chunks remain searchable) until the reindex completes.
+pub async fn enqueue_document_job(
+    db: &Pool<Postgres>,
+    document_id: Uuid,
+    job_type: &str,
+    priority: i32,
+) -> Result<Uuid> {
+    let now = Utc::now();
+    let mut tx = db.begin().await?;
+    let job_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO document_processing_jobs (
+            document_id, job_type, status, priority, current_step, next_attempt_at,
+            created_at, updated_at
+        ) VALUES ($1, $2, 'pending', $3, 'Queued for processing', $4, $4, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(document_id)
+    .bind(job_type)
+    .bind(priority)
+    .bind(now)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE documents
+        SET status = CASE WHEN status = 'ready' THEN 'ready' ELSE 'processing' END,
+            processing_progress = 0,
+            current_processing_step = 'Queued for processing',
+            error_message = NULL,
+            updated_at = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(now)
+    .bind(document_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    QUEUE_SIGNAL.notify_one();
+    Ok(job_id)
+}
+
+/// ID: Jeda sebelum percobaan ke-(retry_count + 1): 30s, 60s, 120s, ... maksimal 30 menit.
+/// EN: Delay before attempt retry_count + 1: 30s, 60s, 120s, ... capped at 30 minutes.
+fn retry_delay_secs(retry_count: i32) -> i64 {
+    let exponent = (retry_count - 1).clamp(0, 10) as u32;
+    (RETRY_BASE_SECS << exponent).min(RETRY_MAX_SECS)
+}
+
+/// ID: Klaim satu job. Job 'processing' yang lease-nya kedaluwarsa diambil alih dan dihitung
+/// sebagai satu percobaan (retry_count + 1) agar dokumen yang selalu mematikan worker tidak
+/// diulang tanpa batas.
+/// EN: Claim one job. A 'processing' job whose lease expired is taken over and counted as an
+/// attempt (retry_count + 1) so a document that keeps killing workers is not retried forever.
+async fn claim_next_job(
+    db: &Pool<Postgres>,
+    worker_id: &str,
+) -> Result<Option<DocumentProcessingJob>> {
+    let job = sqlx::query_as::<_, DocumentProcessingJob>(
+        r#"
+        UPDATE document_processing_jobs
+        SET status = 'processing',
+            retry_count = retry_count + CASE WHEN status = 'processing' THEN 1 ELSE 0 END,
+            locked_by = $1,
+            current_step = 'Starting',
+            progress_percentage = 0,
+            started_at = NOW(),
+            updated_at = NOW()
+        WHERE id = (
+            SELECT id
+            FROM document_processing_jobs
+            WHERE deleted_at = 0
+              AND (
+                (status = 'pending' AND COALESCE(next_attempt_at, created_at) <= NOW())
+                OR (status = 'processing' AND updated_at < NOW() - make_interval(secs => $2))
+              )
+            ORDER BY priority DESC, next_attempt_at
+            FOR UPDATE SKIP LOCKED
+            LIMIT 1
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(worker_id)
+    .bind(STALE_LEASE_SECS)
+    .fetch_optional(db)
+    .await?;
+    Ok(job)
+}
+
+async fn run_job(state: &Arc<AppState>, job: DocumentProcessingJob) {
+    let document =
+        sqlx::query_as::<_, Document>("SELECT * FROM documents WHERE id = $1 AND deleted_at = 0")
+            .bind(job.document_id)
+            .fetch_optional(&state.db)
+            .await;
+    let document = match document {
+        Ok(Some(document)) => document,
+        Ok(None) => {
+            // Dokumen dihapus saat masih antre: tidak ada yang perlu diulang
+            let _ = mark_job_failed(&state.db, &job, "Document was deleted").await;
+            return;
+        }
+        Err(e) => {
+            schedule_retry_or_fail(state, &job, &e.to_string()).await;
+            return;
+        }
+    };
+
+    if job.retry_count > job.max_retries {
+        tracing::error!(job_id = %job.id, document_id = %job.document_id, "[rag_ingest] lease expired too many times");
+        if let Err(e) =
+            mark_job_failed(&state.db, &job, "Worker lease expired too many times").await
+        {
+            tracing::error!(job_id = %job.id, error = %e, "[rag_ingest] failed to record failure");
+        }
+        return;
+    }
+
+    tracing::info!(
+        job_id = %job.id,
+        document_id = %document.id,
+        attempt = job.retry_count + 1,
+        "[rag_ingest] processing document"
+    );
+    match crate::handlers::rag::run_ingestion_job(state, &document, job.id).await {
+        Ok(chunk_count) => {
+            if let Err(e) = mark_job_completed(&state.db, &job, chunk_count).await {
+                tracing::error!(job_id = %job.id, error = %e, "[rag_ingest] failed to record completion");
+            }
+        }
+        Err(e) => schedule_retry_or_fail(state, &job, &format!("{:#}", e)).await,
+    }
+}
+
+async fn mark_job_completed(
+    db: &Pool<Postgres>,
+    job: &DocumentProcessingJob,
+    chunk_count: usize,
+) -> Result<()> {
+    let completed_at = Utc::now();
+    let mut tx = db.begin().await?;
+    let updated = sqlx::query(
+        r#"
+        UPDATE document_processing_jobs
+        SET status = 'completed',
+            progress_percentage = 100,
+            current_step = 'Completed',
+            error_message = NULL,
+            locked_by = NULL,
+            completed_at = $1,
+            updated_at = $1
+        WHERE id = $2 AND status = 'processing' AND locked_by = $3
+        "#,
+    )
+    .bind(completed_at)
+    .bind(job.id)
+    .bind(&job.locked_by)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Err(lease_lost(job));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE documents
+        SET status = 'ready',
+            chunk_count = $1,
+            processing_progress = 100,
+            current_processing_step = 'Completed',
+            error_message = NULL,
+            updated_at = $2
+        WHERE id = $3
+        "#,
+    )
+    .bind(chunk_count as i32)
+    .bind(completed_at)
+    .bind(job.document_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+// Job sudah diambil alih worker lain (lease kedaluwarsa); hasil worker ini tidak dicatat
+fn lease_lost(job: &DocumentProcessingJob) -> anyhow::Error {
+    anyhow::anyhow!(
+        "job {} is no longer locked by {}",
+        job.id,
+        job.locked_by.as_deref().unwrap_or("-")
+    )
+}
+
+async fn schedule_retry_or_fail(state: &Arc<AppState>, job: &DocumentProcessingJob, error: &str) {
+    let retry_count = job.retry_count + 1;
+    if retry_count > job.max_retries {
+        tracing::error!(job_id = %job.id, document_id = %job.document_id, error, "[rag_ingest] job failed permanently");
+        if let Err(e) = mark_job_failed(&state.db, job, error).await {
+            tracing::error!(job_id = %job.id, error = %e, "[rag_ingest] failed to record failure");
+        }
+        return;
+    }
+
+    let delay = retry_delay_secs(retry_count);
+    let now = Utc::now();
+    let next_attempt_at = now + ChronoDuration::seconds(delay);
+    let step = format!(
+        "Retry {}/{} scheduled in {}s",
+        retry_count, job.max_retries, delay
+    );
+    tracing::warn!(job_id = %job.id, document_id = %job.document_id, error, delay, "[rag_ingest] job failed; retry scheduled");
+
+    let rescheduled = sqlx::query(
+        r#"
+        UPDATE document_processing_jobs
+        SET status = 'pending',
+            retry_count = $1,
+            next_attempt_at = $2,
+            current_step = $3,
+            error_message = $4,
+            locked_by = NULL,
+            updated_at = $5
+        WHERE id = $6 AND status = 'processing' AND locked_by = $7
+        "#,
+    )
+    .bind(retry_count)
+    .bind(next_attempt_at)
+    .bind(&step)
+    .bind(error)
+    .bind(now)
+    .bind(job.id)
+    .bind(&job.locked_by)
+    .execute(&state.db)
+    .await
+    .map(|result| result.rows_affected() > 0)
+    .unwrap_or(false);
+    if !rescheduled {
+        tracing::warn!(job_id = %job.id, error = %lease_lost(job), "[rag_ingest] retry not recorded");
+        return;
+    }
+
+    let _ = sqlx::query(
+        r#"
+        UPDATE documents
+        SET current_processing_step = $1,
+            error_message = $2,
+            updated_at = $3
+        WHERE id = $4
+        "#,
+    )
+    .bind(&step)
+    .bind(error)
+    .bind(now)
+    .bind(job.document_id)
+    .execute(&state.db)
+    .await;
+}
+
+/// ID: Gagal permanen. Dokumen 'ready' (reindex) tetap melayani chunk lama; selainnya 'error'.
+/// EN: Permanent failure. A 'ready' document (reindex) keeps serving old chunks; otherwise 'error'.
+async fn mark_job_failed(
+    db: &Pool<Postgres>,
+    job: &DocumentProcessingJob,
+    error: &str,
+) -> Result<()> {
+    let failed_at = Utc::now();
+    let updated = sqlx::query(
+        r#"
+        UPDATE document_processing_jobs
+        SET status = 'failed',
+            current_step = 'Failed',
+            error_message = $1,
+            locked_by = NULL,
+            updated_at = $2
+        WHERE id = $3 AND status = 'processing' AND locked_by = $4
+        "#,
+    )
+    .bind(error)
+    .bind(failed_at)
+    .bind(job.id)
+    .bind(&job.locked_by)
+    .execute(db)
+    .await?
+    .rows_affected();
+    if updated == 0 {
+        return Err(lease_lost(job));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE documents
+        SET status = CASE WHEN status = 'ready' THEN 'ready' ELSE 'error' END,
+            error_message = $1,
+            processing_progress = 0,
+            current_processing_step = 'Failed',
+            updated_at = $2
+        WHERE id = $3 AND deleted_at = 0
+        "#,
+    )
+    .bind(error)
+    .bind(failed_at)
+    .bind(job.document_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
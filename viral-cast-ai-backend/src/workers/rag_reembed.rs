@@ -0,0 +1,217 @@
+use crate::models::rag::RagConfiguration;
+use crate::AppState;
+use anyhow::{Context, Result};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+// Re-embedding chunk RAG setelah embedding_model / embedding_dimensions berubah
+// (atau EMBEDDING_API_URL baru diset sehingga hashing fallback diganti model semantik).
+const BATCH_SIZE: i64 = 64;
+
+// Hanya satu proses re-embed berjalan; pemicu berikutnya cukup ditangkap loop yang sedang jalan
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+pub fn spawn_reembed(state: Arc<AppState>) {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        tracing::info!("[rag_reembed] already running, trigger merged into current run");
+        return;
+    }
+    tokio::spawn(async move {
+        match reembed_stale_chunks(&state).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, "[rag_reembed] chunks re-embedded"),
+            Err(e) => tracing::error!(error = %e, "[rag_reembed] re-embedding stopped"),
+        }
+        RUNNING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// ID: Embed ulang chunk yang embedding_model atau dimensinya berbeda dari konfigurasi aktif,
+/// per batch. Konfigurasi dibaca ulang tiap batch sehingga perubahan model di tengah jalan ikut
+/// terpakai. Vektor ditulis ke Milvus lebih dulu; Postgres baru ditandai setelah Milvus sukses
+/// sehingga batch yang gagal tetap basi dan diulang pada run berikutnya.
+/// EN: Re-embed chunks whose embedding_model or dimension differs from the active
+/// configuration, batch by batch. Configuration is re-read per batch so a model change mid-run
+/// is picked up. Vectors are written to Milvus first; Postgres is only marked once Milvus
+/// succeeded, so a failed batch stays stale and is retried on the next run.
+pub async fn reembed_stale_chunks(state: &Arc<AppState>) -> Result<usize> {
+    let mut total = 0usize;
+    loop {
+        let config = sqlx::query_as::<_, RagConfiguration>(
+            "SELECT * FROM rag_configurations WHERE deleted_at = 0 ORDER BY created_at DESC LIMIT 1",
+        )
+        .fetch_optional(&state.db)
+        .await?
+        .unwrap_or_else(RagConfiguration::default);
+        let embedder = state.embedding_settings.embedder_for(
+            &config.embedding_model,
+            config.embedding_dimensions.max(8) as usize,
+        );
+        let dimensions = embedder.dimensions() as i32;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT dc.id, dc.content, dc.milvus_vector_id, d.store_uuid
+            FROM document_chunks dc
+            JOIN documents d ON d.id = dc.document_id
+            WHERE dc.deleted_at = 0
+              AND (dc.embedding_model IS DISTINCT FROM $1
+                   OR COALESCE(array_length(dc.embedding, 1), 0) <> $2)
+            ORDER BY dc.created_at, dc.chunk_index
+            LIMIT $3
+            "#,
+        )
+        .bind(embedder.model())
+        .bind(dimensions)
+        .bind(BATCH_SIZE)
+        .fetch_all(&state.db)
+        .await?;
+        if rows.is_empty() {
+            return Ok(total);
+        }
+
+        let chunk_ids: Vec<Uuid> = rows.iter().map(|r| r.get("id")).collect();
+        let texts: Vec<String> = rows.iter().map(|r| r.get("content")).collect();
+        let embeddings = embedder
+            .embed(&texts)
+            .await
+            .context("Embedding stale chunks failed")?;
+
+        if !state.env.allow_mock_dependencies {
+            if let Some(client) = state.milvus_client.as_ref() {
+                let mut guard = client.lock().await;
+                sync_collection_dimensions(state, &mut guard, dimensions).await?;
+
+                // Insert Milvus tidak menimpa PK yang sama, jadi vektor lama dihapus dulu
+                let old_vector_ids: Vec<i64> = rows
+                    .iter()
+                    .zip(&chunk_ids)
+                    .map(|(row, chunk_id)| {
+                        row.get::<Option<i64>, _>("milvus_vector_id")
+                            .unwrap_or_else(|| crate::services::milvus::uuid_to_i64(chunk_id))
+                    })
+                    .collect();
+                crate::services::milvus::delete_vectors(
+                    &mut guard,
+                    &state.milvus_collection,
+                    &old_vector_ids,
+                )
+                .await
+                .context("Failed to delete stale chunk vectors from Milvus")?;
+
+                // Vektor ditulis ke partisi basis pengetahuan pemilik dokumen
+                let mut by_partition: HashMap<String, (Vec<Uuid>, Vec<Vec<f32>>)> = HashMap::new();
+                for ((row, chunk_id), embedding) in rows.iter().zip(&chunk_ids).zip(&embeddings) {
+                    let partition = crate::services::milvus::document_partition(
+                        row.get::<Option<Uuid>, _>("store_uuid"),
+                    );
+                    let entry = by_partition.entry(partition).or_default();
+                    entry.0.push(*chunk_id);
+                    entry.1.push(embedding.clone());
+                }
+
+                for (partition, (ids, vectors)) in &by_partition {
+                    crate::services::milvus::upsert_chunk_embeddings(
+                        &mut guard,
+                        &state.milvus_collection,
+                        partition,
+                        ids,
+                        vectors,
+                    )
+                    .await
+                    .context("Failed to upsert re-embedded chunks to Milvus")?;
+                }
+            }
+        }
+
+        let mut tx = state.db.begin().await?;
+        for (chunk_id, embedding) in chunk_ids.iter().zip(embeddings.iter()) {
+            sqlx::query(
+                r#"
+                UPDATE document_chunks
+                SET embedding = $1, embedding_model = $2, milvus_vector_id = $3
+                WHERE id = $4
+                "#,
+            )
+            .bind(embedding)
+            .bind(embedder.model())
+            .bind(crate::services::milvus::uuid_to_i64(chunk_id))
+            .bind(chunk_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        total += chunk_ids.len();
+        tracing::info!(
+            model = embedder.model(),
+            batch = chunk_ids.len(),
+            total,
+            "[rag_reembed] batch re-embedded"
+        );
+    }
+}
+
+/// ID: Samakan dimensi koleksi Milvus dengan konfigurasi. Bila berbeda dari yang tercatat,
+/// koleksi dibuat ulang (vektor lama dibuang); semua chunk berdimensi lama tetap basi
+/// sehingga diisi ulang oleh re-embed. Dipakai juga oleh ingest sebelum menulis vektor.
+/// EN: Align the Milvus collection dimension with the configuration. When it differs from the
+/// recorded one, the collection is recreated (old vectors dropped); every chunk with the old
+/// dimension is still stale, so re-embedding refills it. Also used by ingest before writing.
+pub async fn sync_collection_dimensions(
+    state: &Arc<AppState>,
+    client: &mut milvus::Client,
+    dimensions: i32,
+) -> Result<()> {
+    let recorded: Option<i32> = sqlx::query_scalar(
+        "SELECT dimensions FROM rag_vector_collections WHERE collection_name = $1",
+    )
+    .bind(&state.milvus_collection)
+    .fetch_optional(&state.db)
+    .await?;
+
+    match recorded {
+        Some(recorded) if recorded == dimensions => return Ok(()),
+        Some(recorded) => {
+            tracing::warn!(
+                collection = %state.milvus_collection,
+                from = recorded,
+                to = dimensions,
+                "[rag_reembed] embedding dimension changed, recreating Milvus collection"
+            );
+            crate::services::milvus::recreate_rag_collection(
+                client,
+                &state.milvus_collection,
+                dimensions as i64,
+            )
+            .await
+            .context("Failed to recreate Milvus collection")?;
+        }
+        None => {
+            crate::services::milvus::ensure_rag_collection(
+                client,
+                &state.milvus_collection,
+                dimensions as i64,
+            )
+            .await
+            .context("Failed to ensure Milvus collection")?;
+        }
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO rag_vector_collections (collection_name, dimensions, updated_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (collection_name)
+        DO UPDATE SET dimensions = EXCLUDED.dimensions, updated_at = NOW()
+        "#,
+    )
+    .bind(&state.milvus_collection)
+    .bind(dimensions)
+    .execute(&state.db)
+    .await?;
+    Ok(())
+}
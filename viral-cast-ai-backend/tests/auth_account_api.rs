@@ -0,0 +1,132 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+async fn login(client: &Client, username: &str, password: &str) -> String {
+    let res = client
+        .post(format!("{}/api/v1/auth/login", common::base_url()))
+        .json(&json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .expect("login resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("login json");
+    json["data"]["access_token"]
+        .as_str()
+        .expect("access token")
+        .to_string()
+}
+
+async fn me_status(client: &Client, token: &str) -> StatusCode {
+    client
+        .get(format!("{}/api/v1/users/me", common::base_url()))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("me resp")
+        .status()
+}
+
+async fn change_password(client: &Client, token: &str, current: &str, new: &str) -> StatusCode {
+    client
+        .post(format!(
+            "{}/api/v1/auth/change-password",
+            common::base_url()
+        ))
+        .bearer_auth(token)
+        .json(&json!({ "current_password": current, "new_password": new }))
+        .send()
+        .await
+        .expect("change password resp")
+        .status()
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn change_password_revokes_other_sessions() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+
+    let username = format!("testuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let res = client
+        .post(format!("{}/api/v1/auth/register", common::base_url()))
+        .json(&json!({ "username": username, "password": "Passw0rd!" }))
+        .send()
+        .await
+        .expect("register resp");
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let laptop = login(&client, &username, "Passw0rd!").await;
+    let tablet = login(&client, &username, "Passw0rd!").await;
+
+    // Password saat ini salah atau password baru terlalu pendek ditolak
+    let status = change_password(&client, &laptop, "wrong-password", "N3wPassw0rd!").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let status = change_password(&client, &laptop, "Passw0rd!", "short").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let status = change_password(&client, &laptop, "Passw0rd!", "N3wPassw0rd!").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(me_status(&client, &laptop).await, StatusCode::OK);
+    assert_eq!(me_status(&client, &tablet).await, StatusCode::UNAUTHORIZED);
+
+    // Password lama tidak berlaku lagi
+    let res = client
+        .post(format!("{}/api/v1/auth/login", common::base_url()))
+        .json(&json!({ "username": username, "password": "Passw0rd!" }))
+        .send()
+        .await
+        .expect("old password login resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    login(&client, &username, "N3wPassw0rd!").await;
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn forgot_and_reset_password_do_not_leak_accounts() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+
+    // Email yang tidak terdaftar tetap mendapat respons sukses yang sama
+    let res = client
+        .post(format!(
+            "{}/api/v1/auth/forgot-password",
+            common::base_url()
+        ))
+        .json(&json!({ "email": format!("ghost_{}@example.com", Uuid::new_v4().simple()) }))
+        .send()
+        .await
+        .expect("forgot password resp");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .post(format!("{}/api/v1/auth/reset-password", common::base_url()))
+        .json(&json!({ "token": "not-a-real-token", "new_password": "N3wPassw0rd!" }))
+        .send()
+        .await
+        .expect("reset password resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = client
+        .post(format!("{}/api/v1/auth/verify-email", common::base_url()))
+        .json(&json!({ "token": "not-a-real-token" }))
+        .send()
+        .await
+        .expect("verify email resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -356,3 +356,81 @@ async fn register_duplicate_conflict() {
         .expect("register2");
     assert_eq!(res2.status(), StatusCode::CONFLICT);
 }
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn refresh_rotates_token_and_revokes_family_on_reuse() {
+    ensure_server_running().await;
+    let client = Client::new();
+    let uname = format!("testuser_{}", &Uuid::new_v4().to_string()[..8]);
+
+    let reg_res = client
+        .post(format!("{}/api/v1/auth/register", base_url()))
+        .json(&json!({ "username": uname, "password": "Passw0rd!" }))
+        .send()
+        .await
+        .expect("register response");
+    assert_eq!(reg_res.status(), StatusCode::CREATED);
+    let refresh_cookie =
+        extract_cookie_value(reg_res.headers(), "refresh_token").expect("refresh cookie");
+
+    let refresh_url = format!("{}/api/v1/auth/refresh", base_url());
+    let res = client
+        .get(&refresh_url)
+        .headers(cookie_header(&refresh_cookie, Some(true)))
+        .send()
+        .await
+        .expect("refresh response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let rotated_cookie =
+        extract_cookie_value(res.headers(), "refresh_token").expect("rotated refresh cookie");
+    assert_ne!(rotated_cookie, refresh_cookie, "refresh token must rotate");
+    let json: serde_json::Value = res.json().await.expect("refresh json");
+    assert_eq!(
+        json["data"]["refresh_token"].as_str(),
+        Some(rotated_cookie.as_str())
+    );
+    let access_token = json["data"]["access_token"]
+        .as_str()
+        .expect("access token")
+        .to_string();
+
+    // Rotated token keeps working once
+    let res = client
+        .get(&refresh_url)
+        .headers(cookie_header(&rotated_cookie, Some(true)))
+        .send()
+        .await
+        .expect("second refresh response");
+    assert_eq!(res.status(), StatusCode::OK);
+    let latest_cookie =
+        extract_cookie_value(res.headers(), "refresh_token").expect("latest refresh cookie");
+
+    // Replaying the first (already rotated) token revokes the whole family
+    let res = client
+        .get(&refresh_url)
+        .headers(cookie_header(&refresh_cookie, Some(true)))
+        .send()
+        .await
+        .expect("reuse response");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .get(&refresh_url)
+        .headers(cookie_header(&latest_cookie, Some(true)))
+        .send()
+        .await
+        .expect("latest after reuse response");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .get(format!("{}/api/v1/users/me", base_url()))
+        .bearer_auth(&access_token)
+        .send()
+        .await
+        .expect("me after reuse response");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
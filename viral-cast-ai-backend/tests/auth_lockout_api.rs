@@ -0,0 +1,94 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+// IP unik per run agar hitungan per-IP tidak menumpuk di 127.0.0.1 antar test
+async fn login_status(
+    client: &Client,
+    username: &str,
+    password: &str,
+    ip: &str,
+) -> (StatusCode, Value) {
+    let res = client
+        .post(format!("{}/api/v1/auth/login", common::base_url()))
+        .header("X-Forwarded-For", ip)
+        .json(&json!({ "username": username, "password": password }))
+        .send()
+        .await
+        .expect("login resp");
+    let status = res.status();
+    let json: Value = res.json().await.expect("login json");
+    (status, json)
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn repeated_failed_logins_lock_the_account() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+
+    let username = format!("testuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let res = client
+        .post(format!("{}/api/v1/auth/register", common::base_url()))
+        .json(&json!({ "username": username, "password": "Passw0rd!" }))
+        .send()
+        .await
+        .expect("register resp");
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let json: Value = res.json().await.expect("register json");
+    let user_uuid = json["data"]["user"]["uuid"]
+        .as_str()
+        .expect("user uuid")
+        .to_string();
+
+    let bytes = Uuid::new_v4().into_bytes();
+    let ip = format!("10.{}.{}.{}", bytes[0], bytes[1], bytes[2]);
+
+    for _ in 0..4 {
+        let (status, _) = login_status(&client, &username, "wrong-password", &ip).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (status, json) = login_status(&client, &username, "wrong-password", &ip).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(json["data"]["retry_after_seconds"].as_u64().unwrap_or(0) > 0);
+
+    // Password benar pun ditolak selama terkunci, juga dari IP lain
+    let (status, _) = login_status(&client, &username, "Passw0rd!", &ip).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _) = login_status(&client, &username, "Passw0rd!", "192.0.2.10").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // Username yang tidak ada diperlakukan sama (tidak membocorkan keberadaan akun)
+    let ghost = format!("ghost_{}", &Uuid::new_v4().to_string()[..8]);
+    for _ in 0..4 {
+        let (status, _) = login_status(&client, &ghost, "wrong-password", &ip).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (status, _) = login_status(&client, &ghost, "wrong-password", &ip).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // Staf tanpa peran admin tidak boleh membuka kunci akun
+    let other = common::register_and_login(&client).await;
+    let res = client
+        .post(format!(
+            "{}/api/v1/auth/users/{}/unlock",
+            common::base_url(),
+            user_uuid
+        ))
+        .bearer_auth(&other)
+        .send()
+        .await
+        .expect("unlock resp");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,142 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+async fn login(client: &Client, username: &str, device_name: &str) -> String {
+    let res = client
+        .post(format!("{}/api/v1/auth/login", common::base_url()))
+        .json(&json!({
+            "username": username,
+            "password": "Passw0rd!",
+            "device_name": device_name,
+        }))
+        .send()
+        .await
+        .expect("login resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("login json");
+    json["data"]["access_token"]
+        .as_str()
+        .expect("access token")
+        .to_string()
+}
+
+async fn list_sessions(client: &Client, token: &str) -> Vec<Value> {
+    let res = client
+        .get(format!("{}/api/v1/auth/sessions", common::base_url()))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("sessions resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("sessions json");
+    json["data"].as_array().cloned().expect("sessions")
+}
+
+async fn me_status(client: &Client, token: &str) -> StatusCode {
+    client
+        .get(format!("{}/api/v1/users/me", common::base_url()))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("me resp")
+        .status()
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn sessions_can_be_listed_and_revoked_remotely() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+
+    let username = format!("testuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let res = client
+        .post(format!("{}/api/v1/auth/register", common::base_url()))
+        .json(&json!({ "username": username, "password": "Passw0rd!" }))
+        .send()
+        .await
+        .expect("register resp");
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    let laptop = login(&client, &username, "Laptop Owner").await;
+    let tablet = login(&client, &username, "Tablet Kasir 1").await;
+    let phone = login(&client, &username, "HP Owner").await;
+
+    let sessions = list_sessions(&client, &laptop).await;
+    let current: Vec<&Value> = sessions.iter().filter(|s| s["current"] == true).collect();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0]["device_name"], "Laptop Owner");
+    let tablet_session = sessions
+        .iter()
+        .find(|s| s["device_name"] == "Tablet Kasir 1")
+        .expect("tablet session");
+    let tablet_session_id = tablet_session["uuid"].as_str().expect("session uuid");
+
+    // Kick the lost tablet
+    let res = client
+        .delete(format!(
+            "{}/api/v1/auth/sessions/{}",
+            common::base_url(),
+            tablet_session_id
+        ))
+        .bearer_auth(&laptop)
+        .send()
+        .await
+        .expect("revoke resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(me_status(&client, &tablet).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(me_status(&client, &laptop).await, StatusCode::OK);
+
+    let res = client
+        .delete(format!(
+            "{}/api/v1/auth/sessions/{}",
+            common::base_url(),
+            tablet_session_id
+        ))
+        .bearer_auth(&laptop)
+        .send()
+        .await
+        .expect("revoke again resp");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Sign out everywhere except this laptop
+    let res = client
+        .delete(format!(
+            "{}/api/v1/auth/sessions?keep_current=true",
+            common::base_url()
+        ))
+        .bearer_auth(&laptop)
+        .send()
+        .await
+        .expect("revoke all resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("revoke all json");
+    assert!(json["data"]["revoked_sessions"].as_u64().unwrap_or(0) >= 1);
+    assert_eq!(me_status(&client, &phone).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(list_sessions(&client, &laptop).await.len(), 1);
+
+    // Staff without an admin role cannot revoke someone else's sessions
+    let other = common::register_and_login(&client).await;
+    let res = client
+        .get(format!(
+            "{}/api/v1/auth/users/{}/sessions",
+            common::base_url(),
+            Uuid::new_v4()
+        ))
+        .bearer_auth(&other)
+        .send()
+        .await
+        .expect("admin list resp");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,242 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use sha1::Sha1;
+use uuid::Uuid;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+const PASSWORD: &str = "Passw0rd!";
+
+// Kode TOTP (RFC 6238, SHA1, 6 digit) seperti yang dihitung aplikasi authenticator
+fn totp_code(secret: &str) -> String {
+    let mut key = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in secret.chars() {
+        let value = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567"
+            .find(c)
+            .expect("base32 secret") as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            key.push((buffer >> bits) as u8);
+        }
+    }
+    let step = (chrono::Utc::now().timestamp() / 30) as u64;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).expect("hmac key");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[19] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!("{:06}", binary % 1_000_000)
+}
+
+async fn login(client: &Client, username: &str) -> Value {
+    let res = client
+        .post(format!("{}/api/v1/auth/login", common::base_url()))
+        .json(&json!({ "username": username, "password": PASSWORD }))
+        .send()
+        .await
+        .expect("login resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    res.json().await.expect("login json")
+}
+
+async fn second_step(client: &Client, body: Value) -> (StatusCode, Value) {
+    let res = client
+        .post(format!("{}/api/v1/auth/login/2fa", common::base_url()))
+        .json(&body)
+        .send()
+        .await
+        .expect("2fa login resp");
+    let status = res.status();
+    let json: Value = res.json().await.expect("2fa login json");
+    (status, json)
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn enabling_totp_adds_a_second_login_step() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+
+    let username = format!("testuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let res = client
+        .post(format!("{}/api/v1/auth/register", common::base_url()))
+        .json(&json!({ "username": username, "password": PASSWORD }))
+        .send()
+        .await
+        .expect("register resp");
+    assert_eq!(res.status(), StatusCode::CREATED);
+    let json = login(&client, &username).await;
+    let token = json["data"]["access_token"]
+        .as_str()
+        .expect("access token")
+        .to_string();
+    let json = login(&client, &username).await;
+    let other_token = json["data"]["access_token"]
+        .as_str()
+        .expect("other access token")
+        .to_string();
+
+    let res = client
+        .post(format!("{}/api/v1/auth/2fa/setup", common::base_url()))
+        .bearer_auth(&token)
+        .json(&json!({ "current_password": "wrong-password" }))
+        .send()
+        .await
+        .expect("setup wrong password resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = client
+        .post(format!("{}/api/v1/auth/2fa/setup", common::base_url()))
+        .bearer_auth(&token)
+        .json(&json!({ "current_password": PASSWORD }))
+        .send()
+        .await
+        .expect("setup resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("setup json");
+    let secret = json["data"]["secret"].as_str().expect("secret").to_string();
+    assert!(json["data"]["otpauth_uri"]
+        .as_str()
+        .unwrap_or_default()
+        .starts_with("otpauth://totp/"));
+
+    let confirm_code = totp_code(&secret);
+    let res = client
+        .post(format!("{}/api/v1/auth/2fa/confirm", common::base_url()))
+        .bearer_auth(&token)
+        .json(&json!({ "code": confirm_code }))
+        .send()
+        .await
+        .expect("confirm resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("confirm json");
+    let recovery_codes: Vec<String> = json["data"]["recovery_codes"]
+        .as_array()
+        .expect("recovery codes")
+        .iter()
+        .map(|code| code.as_str().unwrap_or_default().to_string())
+        .collect();
+    assert_eq!(recovery_codes.len(), 10);
+
+    // Sesi lain yang dibuat tanpa 2FA dicabut, sesi yang mengaktifkan tetap berlaku
+    let res = client
+        .get(format!("{}/api/v1/auth/2fa", common::base_url()))
+        .bearer_auth(&other_token)
+        .send()
+        .await
+        .expect("status with revoked session resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = client
+        .get(format!("{}/api/v1/auth/2fa", common::base_url()))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("status resp");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Password saja tidak lagi menghasilkan token
+    let json = login(&client, &username).await;
+    assert_eq!(json["data"]["two_factor_required"], true);
+    assert_eq!(json["data"]["setup_required"], false);
+    assert!(json["data"]["access_token"].is_null());
+    let challenge = json["data"]["challenge_token"]
+        .as_str()
+        .expect("challenge token")
+        .to_string();
+
+    // Kode yang sama dengan konfirmasi tidak bisa diputar ulang
+    let (status, _) = second_step(
+        &client,
+        json!({ "challenge_token": challenge, "code": confirm_code }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = second_step(
+        &client,
+        json!({ "challenge_token": challenge, "recovery_code": recovery_codes[0] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(json["data"]["access_token"].as_str().is_some());
+
+    // Tantangan dan kode pemulihan hanya berlaku sekali
+    let (status, _) = second_step(
+        &client,
+        json!({ "challenge_token": challenge, "recovery_code": recovery_codes[1] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let json = login(&client, &username).await;
+    let challenge = json["data"]["challenge_token"]
+        .as_str()
+        .expect("challenge token")
+        .to_string();
+    let (status, _) = second_step(
+        &client,
+        json!({ "challenge_token": challenge, "recovery_code": recovery_codes[0] }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn two_factor_login_rejects_unknown_challenges() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+
+    let (status, _) = second_step(
+        &client,
+        json!({ "challenge_token": "not-a-challenge", "code": "123456" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!(
+            "{}/api/v1/auth/login/2fa/setup",
+            common::base_url()
+        ))
+        .json(&json!({ "challenge_token": "not-a-challenge" }))
+        .send()
+        .await
+        .expect("2fa setup resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Staf tanpa peran admin tidak boleh mereset 2FA pengguna lain
+    let token = common::register_and_login(&client).await;
+    let res = client
+        .delete(format!(
+            "{}/api/v1/auth/users/{}/2fa",
+            common::base_url(),
+            Uuid::new_v4()
+        ))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("reset resp");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
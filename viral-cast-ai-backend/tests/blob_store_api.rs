@@ -0,0 +1,214 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::multipart::{Form, Part};
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::Value;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+#[allow(dead_code)]
+#[path = "../src/services/blob_store.rs"]
+mod blob_store;
+
+use blob_store::{BlobStore, BlobStoreConfig};
+
+// Requires MinIO from compose.yml and S3_* env vars (see .env.example)
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn s3_backend_round_trip_against_minio() {
+    dotenvy::dotenv().ok();
+    let store = BlobStoreConfig::from_env()
+        .build_s3()
+        .expect("S3_* env vars must point at MinIO");
+    let key = format!("integration/{}.txt", uuid::Uuid::new_v4());
+
+    store
+        .put(&key, b"hello minio".to_vec(), Some("text/plain"))
+        .await
+        .expect("put");
+    assert!(store.exists(&key).await.expect("exists"));
+    assert_eq!(store.get(&key).await.expect("get"), b"hello minio");
+
+    // Presigned GET works without credentials; a tampered signature does not
+    let signed = store.signed_url(&key, 60).expect("signed url");
+    let client = Client::new();
+    let res = client.get(&signed).send().await.expect("presigned get");
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text().await.expect("body"), "hello minio");
+    let tampered = format!("{}0", signed);
+    let res = client.get(&tampered).send().await.expect("tampered get");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    store.delete(&key).await.expect("delete");
+    assert!(!store.exists(&key).await.expect("exists after delete"));
+    // Deleting a missing key is not an error
+    store.delete(&key).await.expect("delete missing");
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn signed_url_endpoint_serves_uploaded_blob() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let image = tokio::fs::read(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("pexels-edwardeyer-1049620.jpg"),
+    )
+    .await
+    .expect("read sample image");
+    let part = Part::bytes(image)
+        .file_name("sample.jpg")
+        .mime_str("image/jpeg")
+        .expect("set mime");
+    let res = client
+        .post(format!(
+            "{}/api/v1/images/upload/product",
+            common::base_url()
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .multipart(Form::new().part("image", part))
+        .send()
+        .await
+        .expect("upload resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("upload json");
+    let image_url = json["data"]["image_url"].as_str().expect("image_url");
+
+    // Signed URL endpoint requires authentication
+    let signed_endpoint = format!("{}/api/v1/storage/signed-url", common::base_url());
+    let res = client
+        .get(&signed_endpoint)
+        .query(&[("url", image_url)])
+        .send()
+        .await
+        .expect("unauthenticated resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .get(&signed_endpoint)
+        .query(&[("url", image_url), ("expires_in", "120")])
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("signed url resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("signed url json");
+    assert_eq!(json["data"]["expires_in"], 120);
+    let signed_url = json["data"]["url"].as_str().expect("signed url");
+    let absolute = if signed_url.starts_with('/') {
+        format!("{}{}", common::base_url(), signed_url)
+    } else {
+        signed_url.to_string()
+    };
+
+    let res = client.get(&absolute).send().await.expect("signed get");
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok()),
+        Some("image/jpeg")
+    );
+
+    // Local backend rejects a tampered signature
+    if json["data"]["backend"] == "local" {
+        let res = client
+            .get(format!("{}0", absolute))
+            .send()
+            .await
+            .expect("tampered resp");
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    let res = client
+        .get(&signed_endpoint)
+        .query(&[("key", "products/does-not-exist.jpg")])
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("missing key resp");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn document_blobs_are_private_to_their_store() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token_a = common::register_and_login(&client).await;
+    helpers::create_store(&client, &token_a).await;
+    let token_b = common::register_and_login(&client).await;
+    helpers::create_store(&client, &token_b).await;
+
+    let res = client
+        .post(format!(
+            "{}/api/rag/documents/upload-document",
+            common::base_url()
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token_a))
+        .json(&serde_json::json!({
+            "title": "secret-recipe",
+            "category": "General",
+            "content": "Takaran sirup rahasia adalah dua pump.",
+        }))
+        .send()
+        .await
+        .expect("ingest resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("ingest json");
+    let document_id = json["data"]["document_id"].as_str().expect("document_id");
+    let key = format!("documents/{}/secret-recipe.txt", document_id);
+
+    // Sumber dokumen tidak pernah dilayani lewat /uploads publik
+    for path in [
+        format!("/uploads/{}", key),
+        format!("/uploads/%64ocuments/{}/secret-recipe.txt", document_id),
+        format!("/uploads//./{}", key),
+    ] {
+        let res = client
+            .get(format!("{}{}", common::base_url(), path))
+            .send()
+            .await
+            .expect("public uploads resp");
+        assert_eq!(
+            res.status(),
+            StatusCode::NOT_FOUND,
+            "{} must not be public",
+            path
+        );
+    }
+
+    let signed_endpoint = format!("{}/api/v1/storage/signed-url", common::base_url());
+    let res = client
+        .get(&signed_endpoint)
+        .query(&[("key", key.as_str())])
+        .header(AUTHORIZATION, format!("Bearer {}", token_b))
+        .send()
+        .await
+        .expect("other store signed url resp");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = client
+        .get(&signed_endpoint)
+        .query(&[("key", key.as_str())])
+        .header(AUTHORIZATION, format!("Bearer {}", token_a))
+        .send()
+        .await
+        .expect("owner signed url resp");
+    assert_eq!(res.status(), StatusCode::OK);
+}
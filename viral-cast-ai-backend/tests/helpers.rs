@@ -16,6 +16,28 @@ pub fn ensure_base_url() {
     }
 }
 
+// Decimal diserialisasi sebagai string ("40.00"); bandingkan sebagai angka
+pub fn as_f64(value: &Value) -> f64 {
+    value
+        .as_str()
+        .and_then(|v| v.parse().ok())
+        .or_else(|| value.as_f64())
+        .expect("numeric value")
+}
+
+pub async fn post(client: &Client, token: &str, path: &str, body: Value) -> (StatusCode, Value) {
+    let res = client
+        .post(format!("{}{}", common::base_url(), path))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&body)
+        .send()
+        .await
+        .expect("post resp");
+    let status = res.status();
+    let json: Value = res.json().await.unwrap_or(Value::Null);
+    (status, json)
+}
+
 pub async fn create_uom(client: &Client, token: &str) -> (String, String, String) {
     let code = format!("UOM{}", &Uuid::new_v4().to_string()[..6]);
     let name = format!("Unit {}", &Uuid::new_v4().to_string()[..6]);
@@ -255,6 +277,21 @@ pub async fn create_order(client: &Client, token: &str, product_uuid: &str) -> V
     res.json().await.expect("create order json")
 }
 
+// Pesanan DRAFT baru: total 100, satu baris qty 2; kembalikan (order_uuid, order_item_uuid)
+pub async fn new_order(client: &Client, token: &str) -> (String, String) {
+    let (category_uuid, _) = create_category(client, token).await;
+    let product = create_product(client, token, &category_uuid, None, 50.0).await;
+    let product_uuid = product["data"]["product"]["uuid"]
+        .as_str()
+        .expect("product uuid");
+    let order = create_order(client, token, product_uuid).await;
+    let order_uuid = order["data"]["uuid"].as_str().expect("order uuid");
+    let item_uuid = order["data"]["items"][0]["uuid"]
+        .as_str()
+        .expect("order item uuid");
+    (order_uuid.to_string(), item_uuid.to_string())
+}
+
 pub async fn create_payment(client: &Client, token: &str, order_uuid: &str) -> Value {
     let res = client
         .post(format!("{}/api/v1/payments", common::base_url()))
@@ -272,3 +309,61 @@ pub async fn create_payment(client: &Client, token: &str, order_uuid: &str) -> V
     assert_eq!(res.status(), StatusCode::CREATED, "create payment failed");
     res.json().await.expect("create payment json")
 }
+
+pub async fn create_supplier(client: &Client, token: &str) -> (String, String) {
+    let name = format!("Supplier {}", &Uuid::new_v4().to_string()[..8]);
+    let res = client
+        .post(format!("{}/api/v1/suppliers", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({
+            "name": name,
+            "contact_name": "Integration Tester",
+            "lead_time_days": 2
+        }))
+        .send()
+        .await
+        .expect("create supplier request");
+    assert_eq!(res.status(), StatusCode::CREATED, "create supplier failed");
+    let json: Value = res.json().await.expect("create supplier json");
+    let uuid = json["data"]["uuid"]
+        .as_str()
+        .expect("supplier uuid")
+        .to_string();
+    (uuid, name)
+}
+
+// Toko baru untuk pengguna uji; profil pengguna otomatis ditautkan ke toko ini
+pub async fn create_store(client: &Client, token: &str) -> String {
+    let name = format!("Store {}", &Uuid::new_v4().to_string()[..8]);
+    let res = client
+        .post(format!("{}/api/v1/stores", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "name": name }))
+        .send()
+        .await
+        .expect("create store request");
+    assert_eq!(res.status(), StatusCode::CREATED, "create store failed");
+    let json: Value = res.json().await.expect("create store json");
+    json["data"]["uuid"]
+        .as_str()
+        .expect("store uuid")
+        .to_string()
+}
+
+// Pengguna baru dengan peran Supervisor (4) di toko yang diberikan; kembalikan token-nya
+pub async fn create_supervisor(client: &Client, store_uuid: &str) -> String {
+    let token = common::register_and_login(client).await;
+    let res = client
+        .patch(format!("{}/api/v1/profiles", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "roles_number": 4, "store_uuid": store_uuid }))
+        .send()
+        .await
+        .expect("update supervisor profile request");
+    assert_eq!(
+        res.status(),
+        StatusCode::OK,
+        "update supervisor profile failed"
+    );
+    token
+}
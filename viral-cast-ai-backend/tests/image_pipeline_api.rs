@@ -0,0 +1,112 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::multipart::{Form, Part};
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::Value;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+async fn sample_image() -> Vec<u8> {
+    let mut path = std::path::PathBuf::from(
+        std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR set"),
+    );
+    path.push("pexels-edwardeyer-1049620.jpg");
+    tokio::fs::read(&path).await.expect("read sample image")
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn product_upload_stores_variants_and_rejects_fake_images() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let (category_uuid, _) = helpers::create_category(&client, &token).await;
+    let product_json =
+        helpers::create_product(&client, &token, &category_uuid, None, 15000.0).await;
+    let product_uuid = product_json["data"]["product"]["uuid"]
+        .as_str()
+        .expect("product uuid")
+        .to_string();
+
+    let upload_url = format!("{}/api/v1/images/upload/product", common::base_url());
+
+    // Text content with an image extension must be rejected by the content check
+    let fake = Part::bytes(b"<?php echo 'hi'; ?>".to_vec())
+        .file_name("avatar.png")
+        .mime_str("image/png")
+        .expect("set mime");
+    let res = client
+        .post(&upload_url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .multipart(Form::new().part("image", fake))
+        .send()
+        .await
+        .expect("fake upload resp");
+    assert_eq!(
+        res.status(),
+        StatusCode::BAD_REQUEST,
+        "fake image should be 400"
+    );
+
+    let part = Part::bytes(sample_image().await)
+        .file_name("sample.jpg")
+        .mime_str("image/jpeg")
+        .expect("set mime");
+    let form = Form::new()
+        .part("image", part)
+        .text("product_uuid", product_uuid.clone());
+    let res = client
+        .post(&upload_url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .multipart(form)
+        .send()
+        .await
+        .expect("upload resp");
+    assert_eq!(res.status(), StatusCode::OK, "product upload should be 200");
+    let json: Value = res.json().await.expect("upload json");
+    let image_url = json["data"]["image_url"].as_str().expect("image_url");
+    assert!(image_url.starts_with("/uploads/products/"));
+    assert!(image_url.ends_with(".jpg"), "original re-encoded as jpg");
+    let variants = &json["data"]["variants"];
+    assert!(variants["webp_url"]
+        .as_str()
+        .expect("webp_url")
+        .ends_with(".webp"));
+    let thumbnails = variants["thumbnails"].as_array().expect("thumbnails");
+    assert_eq!(thumbnails.len(), 2);
+    assert_eq!(thumbnails[0]["size"], 160);
+
+    // Thumbnail is served and the product carries the variants for the POS grid
+    let thumb_url = thumbnails[0]["webp_url"].as_str().expect("thumb webp");
+    let thumb = client
+        .get(format!("{}{}", common::base_url(), thumb_url))
+        .send()
+        .await
+        .expect("thumb resp");
+    assert_eq!(thumb.status(), StatusCode::OK);
+
+    let res = client
+        .get(format!(
+            "{}/api/v1/products/{}",
+            common::base_url(),
+            product_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("get product resp");
+    let product: Value = res.json().await.expect("product json");
+    assert_eq!(product["data"]["product"]["image_url"], image_url);
+    assert_eq!(
+        product["data"]["product"]["image_variants"]["thumbnails"][0]["url"],
+        thumbnails[0]["url"]
+    );
+}
@@ -183,24 +183,6 @@ async fn products_orders_and_payments_flow() {
         "get order should be 200"
     );
 
-    // Update order status
-    let status_res = client
-        .patch(format!(
-            "{}/api/v1/orders/{}/status",
-            common::base_url(),
-            order_uuid
-        ))
-        .header(AUTHORIZATION, format!("Bearer {}", token))
-        .json(&json!({ "status": "PAID" }))
-        .send()
-        .await
-        .expect("update order status");
-    assert_eq!(
-        status_res.status(),
-        StatusCode::OK,
-        "update order status should be 200"
-    );
-
     // List orders
     let orders_list = client
         .get(format!("{}/api/v1/orders", common::base_url()))
@@ -221,6 +203,24 @@ async fn products_orders_and_payments_flow() {
         .expect("payment uuid")
         .to_string();
 
+    // Update order status: PAID only once payments cover the total
+    let status_res = client
+        .patch(format!(
+            "{}/api/v1/orders/{}/status",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "status": "PAID" }))
+        .send()
+        .await
+        .expect("update order status");
+    assert_eq!(
+        status_res.status(),
+        StatusCode::OK,
+        "update order status should be 200"
+    );
+
     // Get payment by id
     let payment_get = client
         .get(format!(
@@ -0,0 +1,467 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod helpers;
+use helpers::{
+    as_f64, common, create_category, create_ingredient, create_ingredient_stock_move, create_order,
+    create_product, create_recipe_item, create_recipe_set, create_store, create_supervisor,
+    create_uom, ensure_base_url, new_order, post,
+};
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn refund_requests_are_validated_against_the_order() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    let (order_uuid, item_uuid) = new_order(&client, &token).await;
+    let refunds_path = format!("/api/v1/orders/{}/refunds", order_uuid);
+
+    // Alasan wajib diisi
+    let (status, _) = post(&client, &token, &refunds_path, json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = post(&client, &token, &refunds_path, json!({ "reason": "  " })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = post(
+        &client,
+        &token,
+        &format!("/api/v1/orders/{}/refunds", Uuid::new_v4()),
+        json!({ "reason": "Salah pesan" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Pesanan DRAFT belum dibayar: refund ditolak, gunakan void
+    let (status, _) = post(
+        &client,
+        &token,
+        &refunds_path,
+        json!({ "reason": "Salah pesan" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, json) = post(
+        &client,
+        &token,
+        "/api/v1/payments",
+        json!({ "order_uuid": order_uuid, "method": "CASH", "amount": 100.0 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let payment_uuid = json["data"]["uuid"]
+        .as_str()
+        .expect("payment uuid")
+        .to_string();
+
+    let (status, _) = post(
+        &client,
+        &token,
+        &refunds_path,
+        json!({
+            "reason": "Salah pesan",
+            "items": [{ "order_item_uuid": item_uuid, "qty": 3 }]
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = post(
+        &client,
+        &token,
+        &refunds_path,
+        json!({
+            "reason": "Salah pesan",
+            "items": [{ "order_item_uuid": item_uuid, "qty": 1 }],
+            "restock": true
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["data"]["status"], "PENDING");
+    assert_eq!(json["data"]["kind"], "REFUND");
+    assert_eq!(as_f64(&json["data"]["amount"]), 50.0);
+    assert_eq!(json["data"]["items"].as_array().map(Vec::len), Some(1));
+    let refund_uuid = json["data"]["uuid"].as_str().expect("refund uuid");
+
+    // Peminta tidak boleh menyetujui/menolak refund miliknya sendiri
+    let (status, _) = post(
+        &client,
+        &token,
+        &format!("/api/v1/refunds/{}/approve", refund_uuid),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = post(
+        &client,
+        &token,
+        &format!("/api/v1/refunds/{}/reject", refund_uuid),
+        json!({ "reason": "Tidak valid" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Selama ada refund, pembayaran dan status REFUNDED hanya berubah lewat alur refund
+    let res = client
+        .delete(format!(
+            "{}/api/v1/payments/{}",
+            common::base_url(),
+            payment_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("delete payment resp");
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let res = client
+        .patch(format!(
+            "{}/api/v1/orders/{}/status",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "status": "REFUNDED" }))
+        .send()
+        .await
+        .expect("mark refunded resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let res = client
+        .patch(format!(
+            "{}/api/v1/orders/{}/status",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "status": "CANCELLED" }))
+        .send()
+        .await
+        .expect("cancel refunded order resp");
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    let res = client
+        .get(format!("{}{}", common::base_url(), refunds_path))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("list refunds resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("list refunds json");
+    assert_eq!(json["data"]["refunds"].as_array().map(Vec::len), Some(1));
+
+    // Pesanan tetap PAID selama refund belum disetujui
+    let res = client
+        .get(format!(
+            "{}/api/v1/payments/order/{}",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("payments by order resp");
+    let json: Value = res.json().await.expect("payments by order json");
+    assert_eq!(json["data"]["order_status"], "PAID");
+    assert_eq!(as_f64(&json["data"]["total_refunded"]), 0.0);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn processing_refund_can_be_rejected_before_the_gateway_accepts_it() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    let store_uuid = create_store(&client, &token).await;
+    let supervisor = create_supervisor(&client, &store_uuid).await;
+    let (order_uuid, _) = new_order(&client, &token).await;
+    let refunds_path = format!("/api/v1/orders/{}/refunds", order_uuid);
+
+    // QR palsu: Xendit tidak akan menemukan pembayarannya (butuh XENDIT_SECRET_KEY_SANDBOX)
+    let (status, _) = post(
+        &client,
+        &token,
+        "/api/v1/payments",
+        json!({
+            "order_uuid": order_uuid,
+            "method": "QRIS",
+            "amount": 100.0,
+            "external_ref": format!("qr_{}", Uuid::new_v4())
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, json) = post(
+        &client,
+        &token,
+        &refunds_path,
+        json!({ "reason": "Salah pesan", "qris_sandbox": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let refund_uuid = json["data"]["uuid"]
+        .as_str()
+        .expect("refund uuid")
+        .to_string();
+
+    // Gateway gagal: refund tertahan PROCESSING dengan pembayaran negatif yang dicadangkan
+    let (status, _) = post(
+        &client,
+        &supervisor,
+        &format!("/api/v1/refunds/{}/approve", refund_uuid),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    let res = client
+        .get(format!(
+            "{}/api/v1/refunds/{}",
+            common::base_url(),
+            refund_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("get refund resp");
+    let json: Value = res.json().await.expect("get refund json");
+    assert_eq!(json["data"]["status"], "PROCESSING");
+    assert_eq!(json["data"]["payments"].as_array().map(Vec::len), Some(1));
+    assert!(json["data"]["payments"][0]["paid_at"].is_null());
+
+    // Supervisor menolaknya: cadangan dilepas dan pesanan bisa direfund lagi
+    let (status, json) = post(
+        &client,
+        &supervisor,
+        &format!("/api/v1/refunds/{}/reject", refund_uuid),
+        json!({ "reason": "QR tidak ditemukan" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["status"], "REJECTED");
+    assert_eq!(json["data"]["payments"].as_array().map(Vec::len), Some(0));
+
+    let res = client
+        .get(format!(
+            "{}/api/v1/payments/order/{}",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("payments by order resp");
+    let json: Value = res.json().await.expect("payments by order json");
+    assert_eq!(json["data"]["order_status"], "PAID");
+    assert_eq!(as_f64(&json["data"]["total_refunded"]), 0.0);
+
+    let (status, json) = post(
+        &client,
+        &token,
+        &refunds_path,
+        json!({ "reason": "Salah pesan" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(as_f64(&json["data"]["amount"]), 100.0);
+}
+
+// Jumlah quantity move bahan dengan ref_type tertentu (SALE negatif, RETURN positif)
+async fn move_total(client: &Client, token: &str, ingredient_uuid: &str, ref_type: &str) -> f64 {
+    let res = client
+        .get(format!(
+            "{}/api/v1/ingredient-stock-moves?ingredient_catalog_uuid={}&ref_type={}&limit=50",
+            common::base_url(),
+            ingredient_uuid,
+            ref_type
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("list stock moves resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("list stock moves json");
+    json["data"]["ingredient_stock_moves"]
+        .as_array()
+        .expect("stock moves array")
+        .iter()
+        .map(|m| as_f64(&m["quantity"]))
+        .sum()
+}
+
+async fn get_data(client: &Client, token: &str, path: &str) -> Value {
+    let res = client
+        .get(format!("{}{}", common::base_url(), path))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("get resp");
+    assert_eq!(res.status(), StatusCode::OK, "GET {} failed", path);
+    let json: Value = res.json().await.expect("get json");
+    json["data"].clone()
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn supervisor_approves_cash_refunds_with_restock() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    let store_uuid = create_store(&client, &token).await;
+    let supervisor = create_supervisor(&client, &store_uuid).await;
+
+    // Produk berresep agar pelunasan memposting move SALE
+    let (uom_uuid, _, _) = create_uom(&client, &token).await;
+    let (ingredient_uuid, _) = create_ingredient(&client, &token, &uom_uuid).await;
+    create_ingredient_stock_move(&client, &token, &ingredient_uuid).await;
+    let stocks = get_data(
+        &client,
+        &token,
+        &format!(
+            "/api/v1/ingredient-stocks?ingredient_catalog_uuid={}",
+            ingredient_uuid
+        ),
+    )
+    .await;
+    let stock_uuid = stocks[0]["uuid"].as_str().expect("ingredient stock uuid");
+    let (recipe_uuid, _) = create_recipe_set(&client, &token).await;
+    create_recipe_item(&client, &token, &recipe_uuid, stock_uuid).await;
+    let (category_uuid, _) = create_category(&client, &token).await;
+    let product = create_product(&client, &token, &category_uuid, Some(&recipe_uuid), 50.0).await;
+    let product_uuid = product["data"]["product"]["uuid"]
+        .as_str()
+        .expect("product uuid");
+    let order = create_order(&client, &token, product_uuid).await;
+    let order_uuid = order["data"]["uuid"].as_str().expect("order uuid");
+    let item_uuid = order["data"]["items"][0]["uuid"]
+        .as_str()
+        .expect("order item uuid");
+    let cashier_uuid = order["data"]["cashier_uuid"]
+        .as_str()
+        .expect("cashier uuid");
+    let refunds_path = format!("/api/v1/orders/{}/refunds", order_uuid);
+
+    let (status, _) = post(
+        &client,
+        &token,
+        "/api/v1/payments",
+        json!({ "order_uuid": order_uuid, "method": "CASH", "amount": 100.0 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let sold = -move_total(&client, &token, &ingredient_uuid, "SALE").await;
+    assert!(sold > 0.0, "paying the order should post SALE moves");
+
+    // Refund sebagian: satu dari dua porsi kembali sebagai pembayaran CASH negatif
+    let refund_line = json!({
+        "reason": "Salah pesan",
+        "items": [{ "order_item_uuid": item_uuid, "qty": 1 }],
+        "restock": true
+    });
+    let (status, json) = post(&client, &token, &refunds_path, refund_line.clone()).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let refund_uuid = json["data"]["uuid"].as_str().expect("refund uuid");
+    let (status, json) = post(
+        &client,
+        &supervisor,
+        &format!("/api/v1/refunds/{}/approve", refund_uuid),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["status"], "APPROVED");
+    let payments = json["data"]["payments"]
+        .as_array()
+        .expect("refund payments");
+    assert_eq!(payments.len(), 1);
+    assert_eq!(payments[0]["method"], "CASH");
+    assert_eq!(as_f64(&payments[0]["amount"]), -50.0);
+    assert!(!payments[0]["paid_at"].is_null());
+
+    let by_order = get_data(
+        &client,
+        &token,
+        &format!("/api/v1/payments/order/{}", order_uuid),
+    )
+    .await;
+    assert_eq!(by_order["order_status"], "PAID");
+    assert_eq!(as_f64(&by_order["total_refunded"]), 50.0);
+    let returned = move_total(&client, &token, &ingredient_uuid, "RETURN").await;
+    assert!((returned - sold / 2.0).abs() < 0.001);
+
+    let order_stats = get_data(
+        &client,
+        &token,
+        &format!("/api/v1/orders/stats?cashier_uuid={}", cashier_uuid),
+    )
+    .await;
+    assert_eq!(as_f64(&order_stats["gross_revenue"]), 100.0);
+    assert_eq!(as_f64(&order_stats["total_revenue"]), 50.0);
+    assert_eq!(as_f64(&order_stats["total_refunded"]), 50.0);
+    assert_eq!(order_stats["refund_count"], 1);
+    let payment_stats = get_data(
+        &client,
+        &token,
+        &format!("/api/v1/payments/stats?order_uuid={}", order_uuid),
+    )
+    .await;
+    assert_eq!(as_f64(&payment_stats["total_amount"]), 100.0);
+    assert_eq!(as_f64(&payment_stats["refunded_amount"]), 50.0);
+    assert_eq!(as_f64(&payment_stats["net_amount"]), 50.0);
+    assert_eq!(payment_stats["refund_count"], 1);
+
+    // Sisa porsi direfund: pesanan REFUNDED dan RETURN tidak melebihi SALE
+    let (status, json) = post(&client, &token, &refunds_path, refund_line).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let refund_uuid = json["data"]["uuid"].as_str().expect("refund uuid");
+    let (status, _) = post(
+        &client,
+        &supervisor,
+        &format!("/api/v1/refunds/{}/approve", refund_uuid),
+        json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let by_order = get_data(
+        &client,
+        &token,
+        &format!("/api/v1/payments/order/{}", order_uuid),
+    )
+    .await;
+    assert_eq!(by_order["order_status"], "REFUNDED");
+    assert_eq!(as_f64(&by_order["total_refunded"]), 100.0);
+    let returned = move_total(&client, &token, &ingredient_uuid, "RETURN").await;
+    assert!((returned - sold).abs() < 0.001);
+
+    // Pesanan REFUNDED tidak bisa diubah statusnya secara manual
+    let res = client
+        .patch(format!(
+            "{}/api/v1/orders/{}/status",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "status": "DRAFT" }))
+        .send()
+        .await
+        .expect("reopen refunded order resp");
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+}
@@ -0,0 +1,218 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod helpers;
+use helpers::{as_f64, common, ensure_base_url, new_order};
+
+async fn pay(
+    client: &Client,
+    token: &str,
+    order_uuid: &str,
+    method: &str,
+    amount: f64,
+) -> (StatusCode, Value) {
+    let res = client
+        .post(format!("{}/api/v1/payments", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "order_uuid": order_uuid, "method": method, "amount": amount }))
+        .send()
+        .await
+        .expect("create payment resp");
+    let status = res.status();
+    let json: Value = res.json().await.expect("create payment json");
+    (status, json)
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn split_tender_settles_order_and_gives_change() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    let (order_uuid, _) = new_order(&client, &token).await;
+
+    // Non-tunai tidak boleh melebihi tagihan
+    let (status, _) = pay(&client, &token, &order_uuid, "CARD", 150.0).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, json) = pay(&client, &token, &order_uuid, "CARD", 60.0).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(as_f64(&json["data"]["remaining_amount"]), 40.0);
+    assert_eq!(json["data"]["order_status"], "DRAFT");
+
+    // Masih ada sisa tagihan: status PAID manual ditolak
+    let res = client
+        .patch(format!(
+            "{}/api/v1/orders/{}/status",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "status": "PAID" }))
+        .send()
+        .await
+        .expect("mark paid resp");
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    let res = client
+        .put(format!(
+            "{}/api/v1/orders/{}",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "status": "PAID" }))
+        .send()
+        .await
+        .expect("update order to paid resp");
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    // CASH boleh lebih; kelebihannya menjadi kembalian dan pesanan lunas
+    let (status, json) = pay(&client, &token, &order_uuid, "cash", 50.0).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(as_f64(&json["data"]["amount"]), 40.0);
+    assert_eq!(as_f64(&json["data"]["tendered_amount"]), 50.0);
+    assert_eq!(as_f64(&json["data"]["change_amount"]), 10.0);
+    assert_eq!(as_f64(&json["data"]["remaining_amount"]), 0.0);
+    assert_eq!(json["data"]["order_status"], "PAID");
+
+    let (status, _) = pay(&client, &token, &order_uuid, "CASH", 10.0).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let res = client
+        .get(format!(
+            "{}/api/v1/payments/order/{}",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("payments by order resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("payments by order json");
+    assert_eq!(json["data"]["order_status"], "PAID");
+    assert_eq!(as_f64(&json["data"]["total_paid"]), 100.0);
+    assert_eq!(as_f64(&json["data"]["remaining_amount"]), 0.0);
+    assert_eq!(as_f64(&json["data"]["change_given"]), 10.0);
+    assert_eq!(json["data"]["payments"].as_array().map(Vec::len), Some(2));
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn payments_reject_bad_methods_and_closed_orders() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    let (order_uuid, _) = new_order(&client, &token).await;
+
+    let (status, _) = pay(&client, &token, &order_uuid, "VOUCHER", 10.0).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = pay(&client, &token, &order_uuid, "CASH", 0.0).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = pay(&client, &token, &Uuid::new_v4().to_string(), "CASH", 10.0).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Ubah nominal lewat update juga dialokasikan ulang
+    let (status, json) = pay(&client, &token, &order_uuid, "TRANSFER", 30.0).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let payment_uuid = json["data"]["uuid"].as_str().expect("payment uuid");
+    let res = client
+        .put(format!(
+            "{}/api/v1/payments/{}",
+            common::base_url(),
+            payment_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "amount": 120.0 }))
+        .send()
+        .await
+        .expect("update payment resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    // Tanpa login pembayaran tidak boleh diubah
+    let res = client
+        .put(format!(
+            "{}/api/v1/payments/{}",
+            common::base_url(),
+            payment_uuid
+        ))
+        .json(&json!({ "amount": 10.0 }))
+        .send()
+        .await
+        .expect("anonymous update payment resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .patch(format!(
+            "{}/api/v1/orders/{}/status",
+            common::base_url(),
+            order_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "status": "CANCELLED" }))
+        .send()
+        .await
+        .expect("cancel order resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let (status, _) = pay(&client, &token, &order_uuid, "CASH", 70.0).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn lowering_or_deleting_payments_reopens_a_paid_order() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    let (order_uuid, _) = new_order(&client, &token).await;
+
+    let (status, json) = pay(&client, &token, &order_uuid, "CARD", 100.0).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["data"]["order_status"], "PAID");
+    let payment_uuid = json["data"]["uuid"].as_str().expect("payment uuid");
+    let payment_url = format!("{}/api/v1/payments/{}", common::base_url(), payment_uuid);
+
+    // Nominal diturunkan: pesanan tidak lagi lunas
+    let res = client
+        .put(&payment_url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "amount": 70.0 }))
+        .send()
+        .await
+        .expect("lower payment resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("lower payment json");
+    assert_eq!(as_f64(&json["data"]["remaining_amount"]), 30.0);
+    assert_eq!(json["data"]["order_status"], "DRAFT");
+
+    let (status, json) = pay(&client, &token, &order_uuid, "CASH", 30.0).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(json["data"]["order_status"], "PAID");
+
+    let res = client
+        .delete(&payment_url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("delete payment resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("delete payment json");
+    assert_eq!(as_f64(&json["data"]["remaining_amount"]), 70.0);
+    assert_eq!(json["data"]["order_status"], "DRAFT");
+}
@@ -115,6 +115,7 @@ async fn roles_regions_weather_and_rag_endpoints() {
     // RAG configuration endpoints
     let rag_config = client
         .get(format!("{}/api/rag/config", common::base_url()))
+        .bearer_auth(&token)
         .send()
         .await
         .expect("get rag config");
@@ -124,6 +125,7 @@ async fn roles_regions_weather_and_rag_endpoints() {
 
     let rag_update = client
         .put(format!("{}/api/rag/config", common::base_url()))
+        .bearer_auth(&token)
         .json(&json!({
             "max_results": 15,
             "enable_reranking": true
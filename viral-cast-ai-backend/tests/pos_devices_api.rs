@@ -0,0 +1,134 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn staff_cannot_enrol_pos_devices() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let res = client
+        .post(format!("{}/api/v1/pos/devices", common::base_url()))
+        .json(&json!({ "name": "Counter 1" }))
+        .send()
+        .await
+        .expect("unauthenticated enrol resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!("{}/api/v1/pos/devices", common::base_url()))
+        .bearer_auth(&token)
+        .json(&json!({ "name": "Counter 1" }))
+        .send()
+        .await
+        .expect("enrol resp");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn pin_unlock_requires_an_enrolled_device() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let body = json!({ "user_uuid": Uuid::new_v4(), "pin": "1234" });
+
+    let res = client
+        .post(format!("{}/api/v1/pos/unlock", common::base_url()))
+        .json(&body)
+        .send()
+        .await
+        .expect("unlock without device resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .post(format!("{}/api/v1/pos/unlock", common::base_url()))
+        .header("X-Device-Token", "not-a-device-token")
+        .json(&body)
+        .send()
+        .await
+        .expect("unlock with bad device resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = client
+        .get(format!("{}/api/v1/pos/staff", common::base_url()))
+        .header("X-Device-Token", "not-a-device-token")
+        .send()
+        .await
+        .expect("staff resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn set_pin_checks_password_and_format() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    let url = format!("{}/api/v1/pos/pin", common::base_url());
+
+    let res = client
+        .put(&url)
+        .bearer_auth(&token)
+        .json(&json!({ "current_password": "wrong-password", "pin": "1234" }))
+        .send()
+        .await
+        .expect("wrong password resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = client
+        .put(&url)
+        .bearer_auth(&token)
+        .json(&json!({ "current_password": "Passw0rd!", "pin": "12ab" }))
+        .send()
+        .await
+        .expect("bad pin resp");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = client
+        .put(&url)
+        .bearer_auth(&token)
+        .json(&json!({ "current_password": "Passw0rd!", "pin": "0420" }))
+        .send()
+        .await
+        .expect("set pin resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("set pin json");
+    assert_eq!(json["code"], 200);
+
+    let res = client
+        .delete(&url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("delete pin resp");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .delete(&url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("delete missing pin resp");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
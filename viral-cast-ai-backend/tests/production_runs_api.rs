@@ -0,0 +1,132 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{as_f64, common, ensure_base_url};
+
+async fn first_stock_uuid(client: &Client, token: &str, ingredient_uuid: &str) -> String {
+    let res = client
+        .get(format!(
+            "{}/api/v1/ingredient-stocks?ingredient_catalog_uuid={}",
+            common::base_url(),
+            ingredient_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("list ingredient stocks");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("stock json");
+    json["data"][0]["uuid"]
+        .as_str()
+        .expect("ingredient stock uuid")
+        .to_string()
+}
+
+async fn add_component(
+    client: &Client,
+    token: &str,
+    recipe_set_uuid: &str,
+    component_uuid: &str,
+) -> reqwest::Response {
+    client
+        .post(format!("{}/api/recipe-items", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({
+            "recipe_sets_uuid": recipe_set_uuid,
+            "component_recipe_sets_uuid": component_uuid,
+            "quantity": 1.0
+        }))
+        .send()
+        .await
+        .expect("create component recipe item")
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn production_run_consumes_ingredients_and_stocks_output() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    // Raw ingredient with 4.5 units in stock at price 11
+    let (uom_uuid, _, _) = helpers::create_uom(&client, &token).await;
+    let (raw_uuid, _) = helpers::create_ingredient(&client, &token, &uom_uuid).await;
+    helpers::create_ingredient_stock_move(&client, &token, &raw_uuid).await;
+    let raw_stock_uuid = first_stock_uuid(&client, &token, &raw_uuid).await;
+
+    // Semi-finished ingredient produced by the recipe
+    let (syrup_uuid, _) = helpers::create_ingredient(&client, &token, &uom_uuid).await;
+    let (recipe_uuid, _) = helpers::create_recipe_set(&client, &token).await;
+    let update = client
+        .put(format!(
+            "{}/api/recipe-sets/{}",
+            common::base_url(),
+            recipe_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "output_ingredient_catalog_uuid": syrup_uuid }))
+        .send()
+        .await
+        .expect("set recipe output");
+    assert_eq!(update.status(), StatusCode::OK);
+    helpers::create_recipe_item(&client, &token, &recipe_uuid, &raw_stock_uuid).await;
+
+    let created = client
+        .post(format!("{}/api/v1/production-runs", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "recipe_sets_uuid": recipe_uuid, "output_quantity": 1.0 }))
+        .send()
+        .await
+        .expect("create production run");
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let created_json: Value = created.json().await.expect("created json");
+    let data = &created_json["data"];
+    assert_eq!(data["output_ingredient_catalog_uuid"], syrup_uuid.as_str());
+    assert!(data["output_move_uuid"].is_string());
+    let items = data["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["ingredient_catalog_uuid"], raw_uuid.as_str());
+    assert!(as_f64(&data["total_cost"]) > 0.0);
+
+    let cost = client
+        .get(format!(
+            "{}/api/recipe-sets/{}/cost",
+            common::base_url(),
+            recipe_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("recipe cost");
+    assert_eq!(cost.status(), StatusCode::OK);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn recipe_component_cycle_is_rejected() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let (parent_uuid, _) = helpers::create_recipe_set(&client, &token).await;
+    let (child_uuid, _) = helpers::create_recipe_set(&client, &token).await;
+
+    let first = add_component(&client, &token, &parent_uuid, &child_uuid).await;
+    assert_eq!(first.status(), StatusCode::CREATED);
+
+    let cycle = add_component(&client, &token, &child_uuid, &parent_uuid).await;
+    assert_eq!(cycle.status(), StatusCode::CONFLICT);
+}
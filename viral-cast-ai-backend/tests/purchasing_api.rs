@@ -0,0 +1,127 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn reorder_suggestions_to_purchase_order_flow() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    // Ingredient with minimum_stock 5 and no stock yet -> must be suggested
+    let (uom_uuid, _, _) = helpers::create_uom(&client, &token).await;
+    let (ingredient_uuid, _) = helpers::create_ingredient(&client, &token, &uom_uuid).await;
+    let (supplier_uuid, _) = helpers::create_supplier(&client, &token).await;
+
+    let link = client
+        .post(format!(
+            "{}/api/v1/suppliers/{}/ingredients",
+            common::base_url(),
+            supplier_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({
+            "ingredient_catalog_uuid": ingredient_uuid,
+            "pack_size": 4,
+            "last_price": 1000,
+            "is_preferred": true
+        }))
+        .send()
+        .await
+        .expect("link ingredient to supplier");
+    assert_eq!(link.status(), StatusCode::OK);
+
+    let suggestions = client
+        .get(format!(
+            "{}/api/v1/reorder-suggestions?supplier_uuid={}",
+            common::base_url(),
+            supplier_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("get reorder suggestions");
+    assert_eq!(suggestions.status(), StatusCode::OK);
+    let suggestions_json: Value = suggestions.json().await.expect("suggestions json");
+    let suppliers = suggestions_json["data"]["suppliers"]
+        .as_array()
+        .expect("suppliers array");
+    assert_eq!(suppliers.len(), 1, "only the filtered supplier is returned");
+    let item = suppliers[0]["items"]
+        .as_array()
+        .expect("items array")
+        .iter()
+        .find(|i| i["ingredient_catalog_uuid"] == ingredient_uuid.as_str())
+        .expect("ingredient suggested")
+        .clone();
+    // minimum_stock 5 rounded up to pack size 4
+    let suggested_qty: f64 = item["suggested_qty"]
+        .as_str()
+        .and_then(|v| v.parse().ok())
+        .expect("suggested qty");
+    assert_eq!(suggested_qty, 8.0);
+
+    let created = client
+        .post(format!(
+            "{}/api/v1/reorder-suggestions/purchase-orders",
+            common::base_url()
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "supplier_uuids": [supplier_uuid] }))
+        .send()
+        .await
+        .expect("create purchase orders from suggestions");
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let created_json: Value = created.json().await.expect("created json");
+    let po_uuid = created_json["data"]["purchase_orders"][0]["uuid"]
+        .as_str()
+        .expect("purchase order uuid")
+        .to_string();
+    assert_eq!(
+        created_json["data"]["purchase_orders"][0]["status"].as_str(),
+        Some("DRAFT")
+    );
+
+    for status in ["SUBMITTED", "RECEIVED"] {
+        let res = client
+            .patch(format!(
+                "{}/api/v1/purchase-orders/{}/status",
+                common::base_url(),
+                po_uuid
+            ))
+            .header(AUTHORIZATION, format!("Bearer {}", token))
+            .json(&json!({ "status": status }))
+            .send()
+            .await
+            .expect("update purchase order status");
+        assert_eq!(res.status(), StatusCode::OK, "transition to {}", status);
+    }
+
+    // Received PO is no longer open, so the ingredient is now covered
+    let after = client
+        .get(format!(
+            "{}/api/v1/reorder-suggestions?supplier_uuid={}",
+            common::base_url(),
+            supplier_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("get reorder suggestions after receive");
+    let after_json: Value = after.json().await.expect("after json");
+    assert!(after_json["data"]["suppliers"]
+        .as_array()
+        .expect("suppliers array")
+        .is_empty());
+}
@@ -0,0 +1,164 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+// Pengguna baru dengan tokonya sendiri
+async fn store_user(client: &Client) -> String {
+    let token = common::register_and_login(client).await;
+    helpers::create_store(client, &token).await;
+    token
+}
+
+async fn query_document_ids(client: &Client, token: &str, query: &str) -> Vec<String> {
+    let res = client
+        .post(format!("{}/api/rag/query", common::base_url()))
+        .bearer_auth(token)
+        .json(&json!({ "query": query, "similarity_threshold": 0.99 }))
+        .send()
+        .await
+        .expect("query resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("query json");
+    json["data"]["sources"]
+        .as_array()
+        .expect("sources")
+        .iter()
+        .map(|s| s["document_id"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+async fn listed_document_ids(client: &Client, token: &str) -> Vec<String> {
+    let res = client
+        .get(format!(
+            "{}/api/rag/documents?limit=100",
+            common::base_url()
+        ))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("list resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("list json");
+    json["data"]["documents"]
+        .as_array()
+        .expect("documents")
+        .iter()
+        .map(|d| d["id"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn store_documents_are_isolated_between_stores() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token_a = store_user(&client).await;
+    let token_b = store_user(&client).await;
+
+    let res = client
+        .get(format!("{}/api/rag/documents", common::base_url()))
+        .send()
+        .await
+        .expect("unauthenticated resp");
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let marker = format!("acl{}", &uuid::Uuid::new_v4().simple().to_string()[..10]);
+    let res = client
+        .post(format!(
+            "{}/api/rag/documents/upload-document",
+            common::base_url()
+        ))
+        .bearer_auth(&token_a)
+        .json(&json!({
+            "title": "Resep rahasia",
+            "category": "General",
+            "content": format!("Takaran rahasia sirup {} adalah dua pump.", marker),
+        }))
+        .send()
+        .await
+        .expect("ingest resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("ingest json");
+    let document_id = json["data"]["document_id"]
+        .as_str()
+        .expect("document_id")
+        .to_string();
+
+    assert_eq!(
+        query_document_ids(&client, &token_a, &marker).await,
+        vec![document_id.clone()]
+    );
+    assert!(query_document_ids(&client, &token_b, &marker)
+        .await
+        .is_empty());
+
+    let listed_a = listed_document_ids(&client, &token_a).await;
+    assert!(listed_a.contains(&document_id));
+    let listed_b = listed_document_ids(&client, &token_b).await;
+    assert!(!listed_b.contains(&document_id));
+
+    let status_url = format!(
+        "{}/api/rag/documents/{}/status",
+        common::base_url(),
+        document_id
+    );
+    let res = client
+        .get(&status_url)
+        .bearer_auth(&token_a)
+        .send()
+        .await
+        .expect("status a resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client
+        .get(&status_url)
+        .bearer_auth(&token_b)
+        .send()
+        .await
+        .expect("status b resp");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let delete_url = format!("{}/api/rag/documents/{}", common::base_url(), document_id);
+    let res = client
+        .delete(&delete_url)
+        .bearer_auth(&token_b)
+        .send()
+        .await
+        .expect("delete b resp");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Hanya Super Admin yang boleh membagikan dokumen ke seluruh platform
+    let res = client
+        .post(format!(
+            "{}/api/rag/documents/upload-document",
+            common::base_url()
+        ))
+        .bearer_auth(&token_a)
+        .json(&json!({
+            "title": "Pengumuman",
+            "category": "General",
+            "content": "Dokumen untuk semua toko.",
+            "visibility": "platform",
+        }))
+        .send()
+        .await
+        .expect("platform ingest resp");
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = client
+        .delete(&delete_url)
+        .bearer_auth(&token_a)
+        .send()
+        .await
+        .expect("delete a resp");
+    assert_eq!(res.status(), StatusCode::OK);
+}
@@ -0,0 +1,138 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+async fn ingest_text(client: &Client, token: &str, title: &str, content: &str) -> String {
+    let res = client
+        .post(format!(
+            "{}/api/rag/documents/upload-document",
+            common::base_url()
+        ))
+        .bearer_auth(token)
+        .json(&json!({
+            "title": title,
+            "content": content,
+            "category": "General",
+            "tags": ["eval-test"]
+        }))
+        .send()
+        .await
+        .expect("ingest resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("ingest json");
+    json["data"]["document_id"]
+        .as_str()
+        .expect("document id")
+        .to_string()
+}
+
+async fn run_eval(client: &Client, token: &str, set_id: &str) -> Value {
+    let res = client
+        .post(format!(
+            "{}/api/rag/eval/sets/{}/runs",
+            common::base_url(),
+            set_id
+        ))
+        .bearer_auth(token)
+        .json(&json!({ "k": 5 }))
+        .send()
+        .await
+        .expect("run resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("run json");
+    json["data"].clone()
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn eval_set_reports_metrics_and_compares_runs() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    helpers::create_store(&client, &token).await;
+
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    let document_id = ingest_text(
+        &client,
+        &token,
+        &format!("SOP espresso {}", marker),
+        &format!(
+            "Kalibrasi grinder espresso {} dilakukan setiap pagi sebelum toko buka pukul 07.00.",
+            marker
+        ),
+    )
+    .await;
+
+    let res = client
+        .post(format!("{}/api/rag/eval/sets", common::base_url()))
+        .bearer_auth(&token)
+        .json(&json!({
+            "name": format!("eval {}", marker),
+            "questions": [{
+                "question": format!("Kapan kalibrasi grinder espresso {}?", marker),
+                "expected_document_ids": [document_id],
+                "reference_answer": "Setiap pagi sebelum toko buka pukul 07.00."
+            }]
+        }))
+        .send()
+        .await
+        .expect("create set resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("create set json");
+    assert_eq!(json["code"], 201);
+    let set_id = json["data"]["id"].as_str().expect("set id").to_string();
+    assert_eq!(json["data"]["questions"].as_array().map(Vec::len), Some(1));
+
+    let first = run_eval(&client, &token, &set_id).await;
+    assert_eq!(first["k"], 5);
+    assert_eq!(first["summary"]["question_count"], 1);
+    assert_eq!(first["summary"]["recall_at_k"].as_f64(), Some(1.0));
+    assert!(first["summary"]["mrr"].as_f64().unwrap_or(0.0) > 0.0);
+    assert!(first["comparison"].is_null());
+
+    let second = run_eval(&client, &token, &set_id).await;
+    assert_eq!(second["previous_run_id"], first["run_id"]);
+    assert_eq!(second["comparison"]["recall_delta"].as_f64(), Some(0.0));
+    assert_eq!(
+        second["comparison"]["regressed_questions"]
+            .as_array()
+            .map(Vec::len),
+        Some(0)
+    );
+
+    let res = client
+        .get(format!(
+            "{}/api/rag/eval/sets/{}/runs",
+            common::base_url(),
+            set_id
+        ))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("runs resp");
+    let json: Value = res.json().await.expect("runs json");
+    assert_eq!(json["data"].as_array().map(Vec::len), Some(2));
+
+    // Pertanyaan tanpa ekspektasi ditolak
+    let res = client
+        .post(format!("{}/api/rag/eval/sets", common::base_url()))
+        .bearer_auth(&token)
+        .json(&json!({
+            "name": "invalid",
+            "questions": [{ "question": "Apa saja menu baru?" }]
+        }))
+        .send()
+        .await
+        .expect("invalid set resp");
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,113 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+async fn document_status(client: &Client, token: &str, document_id: &str) -> Value {
+    let res = client
+        .get(format!(
+            "{}/api/rag/documents/{}/status",
+            common::base_url(),
+            document_id
+        ))
+        .bearer_auth(token)
+        .send()
+        .await
+        .expect("status resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("status json");
+    json["data"].clone()
+}
+
+// Tunggu worker antrian menyelesaikan job terakhir dokumen
+async fn wait_for_job(client: &Client, token: &str, document_id: &str) -> Value {
+    for _ in 0..60 {
+        let status = document_status(client, token, document_id).await;
+        if matches!(status["job_status"].as_str(), Some("completed" | "failed")) {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    panic!("ingestion job for {} did not finish in time", document_id);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn upload_is_queued_processed_and_reindexable() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    helpers::create_store(&client, &token).await;
+
+    let file_name = format!("sop-{}.txt", uuid::Uuid::new_v4().simple());
+    let part = Part::bytes(
+        "SOP pembukaan toko.\n\nNyalakan mesin espresso dan kalibrasi grinder sebelum jam 7 pagi."
+            .as_bytes()
+            .to_vec(),
+    )
+    .file_name(file_name)
+    .mime_str("text/plain")
+    .expect("set mime");
+    let res = client
+        .post(format!("{}/api/rag/documents/upload", common::base_url()))
+        .bearer_auth(&token)
+        .multipart(Form::new().text("category", "General").part("file", part))
+        .send()
+        .await
+        .expect("upload resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("upload json");
+    assert_eq!(json["code"], 202);
+    let document_id = json["data"]["id"].as_str().expect("id").to_string();
+
+    let status = wait_for_job(&client, &token, &document_id).await;
+    assert_eq!(status["job_status"], "completed");
+    assert_eq!(status["status"], "ready");
+    assert_eq!(status["progress_percentage"], 100);
+    let chunk_count = status["chunks_processed"].as_i64().expect("chunks");
+    assert!(chunk_count > 0);
+
+    let reindex_url = format!(
+        "{}/api/rag/documents/{}/reindex",
+        common::base_url(),
+        document_id
+    );
+    let res = client
+        .post(&reindex_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("reindex resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("reindex json");
+    assert_eq!(json["code"], 202);
+    assert!(json["data"]["job_id"].is_string());
+
+    let status = wait_for_job(&client, &token, &document_id).await;
+    assert_eq!(status["job_status"], "completed");
+    assert_eq!(status["status"], "ready");
+    assert_eq!(status["chunks_processed"].as_i64(), Some(chunk_count));
+
+    let res = client
+        .post(format!(
+            "{}/api/rag/documents/{}/reindex",
+            common::base_url(),
+            uuid::Uuid::new_v4()
+        ))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("missing reindex resp");
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,108 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+async fn ingest(client: &Client, token: &str, title: &str, content: &str, tags: &[&str]) -> String {
+    let res = client
+        .post(format!(
+            "{}/api/rag/documents/upload-document",
+            common::base_url()
+        ))
+        .bearer_auth(token)
+        .json(&json!({
+            "title": title,
+            "category": "General",
+            "content": content,
+            "tags": tags,
+        }))
+        .send()
+        .await
+        .expect("ingest resp");
+    assert_eq!(res.status(), StatusCode::OK);
+    let json: Value = res.json().await.expect("ingest json");
+    json["data"]["document_id"]
+        .as_str()
+        .expect("document_id")
+        .to_string()
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn hybrid_query_finds_keyword_matches_and_applies_filters() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+    helpers::create_store(&client, &token).await;
+
+    // Kata unik supaya hanya dokumen uji yang cocok secara keyword
+    let marker = format!("zq{}", &uuid::Uuid::new_v4().simple().to_string()[..10]);
+    let tag_a = format!("tag-a-{}", marker);
+    let tag_b = format!("tag-b-{}", marker);
+    let doc_a = ingest(
+        &client,
+        &token,
+        "SOP Barista",
+        &format!(
+            "Resep kopi susu gula aren memakai sirup {} dua pump.",
+            marker
+        ),
+        &[tag_a.as_str()],
+    )
+    .await;
+    let doc_b = ingest(
+        &client,
+        &token,
+        "Catatan Gudang",
+        &format!(
+            "Stok sirup {} tersisa tiga botol di gudang belakang.",
+            marker
+        ),
+        &[tag_b.as_str()],
+    )
+    .await;
+
+    let query = |body: Value| {
+        let client = client.clone();
+        let token = token.clone();
+        async move {
+            let res = client
+                .post(format!("{}/api/rag/query", common::base_url()))
+                .bearer_auth(&token)
+                .json(&body)
+                .send()
+                .await
+                .expect("query resp");
+            assert_eq!(res.status(), StatusCode::OK);
+            let json: Value = res.json().await.expect("query json");
+            json["data"]["sources"]
+                .as_array()
+                .expect("sources")
+                .iter()
+                .map(|s| s["document_id"].as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // Keyword match lolos walau similarity_threshold tinggi
+    let ids = query(json!({ "query": marker, "similarity_threshold": 0.99 })).await;
+    assert!(ids.contains(&doc_a) && ids.contains(&doc_b), "{:?}", ids);
+
+    let ids = query(json!({ "query": marker, "tags_filter": [tag_b] })).await;
+    assert_eq!(ids, vec![doc_b.clone()]);
+
+    let ids = query(json!({ "query": marker, "document_ids": [doc_a] })).await;
+    assert_eq!(ids, vec![doc_a.clone()]);
+
+    let ids = query(json!({ "query": marker, "category_filter": "does-not-exist" })).await;
+    assert!(ids.is_empty());
+}
@@ -0,0 +1,188 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use chrono::Utc;
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{common, ensure_base_url};
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn recipe_versions_are_created_listed_and_diffed() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let (category_uuid, _) = helpers::create_category(&client, &token).await;
+    let (v1_uuid, _) = helpers::create_recipe_set(&client, &token).await;
+    let product_json =
+        helpers::create_product(&client, &token, &category_uuid, Some(&v1_uuid), 25000.0).await;
+    let product_uuid = product_json["data"]["product"]["uuid"]
+        .as_str()
+        .expect("product uuid")
+        .to_string();
+
+    let created = client
+        .post(format!(
+            "{}/api/recipe-sets/{}/versions",
+            common::base_url(),
+            v1_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "effective_from": Utc::now().timestamp_millis() }))
+        .send()
+        .await
+        .expect("create recipe version");
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let created_json: Value = created.json().await.expect("version json");
+    let v2 = &created_json["data"]["recipe_set"];
+    let v2_uuid = v2["uuid"].as_str().expect("version uuid").to_string();
+    assert_eq!(v2["version_no"], 2);
+    assert_eq!(v2["previous_version_uuid"], v1_uuid.as_str());
+    assert_eq!(v2["product_uuid"], product_uuid.as_str());
+
+    // Only the new version gets an extra ingredient
+    let (uom_uuid, _, _) = helpers::create_uom(&client, &token).await;
+    let (ingredient_uuid, _) = helpers::create_ingredient(&client, &token, &uom_uuid).await;
+    helpers::create_ingredient_stock_move(&client, &token, &ingredient_uuid).await;
+    let stocks: Value = client
+        .get(format!(
+            "{}/api/v1/ingredient-stocks?ingredient_catalog_uuid={}",
+            common::base_url(),
+            ingredient_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("list ingredient stocks")
+        .json()
+        .await
+        .expect("stock json");
+    let stock_uuid = stocks["data"][0]["uuid"].as_str().expect("stock uuid");
+    helpers::create_recipe_item(&client, &token, &v2_uuid, stock_uuid).await;
+
+    let versions = client
+        .get(format!(
+            "{}/api/recipe-sets/{}/versions",
+            common::base_url(),
+            v2_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("list recipe versions");
+    assert_eq!(versions.status(), StatusCode::OK);
+    let versions_json: Value = versions.json().await.expect("versions json");
+    let list = versions_json["data"]["versions"]
+        .as_array()
+        .expect("versions");
+    assert_eq!(list.len(), 2);
+    assert!(list[0]["effective_to"].is_i64(), "v1 is closed by v2");
+
+    let diff = client
+        .get(format!(
+            "{}/api/recipe-sets/{}/diff?against={}",
+            common::base_url(),
+            v2_uuid,
+            v1_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("diff recipe versions");
+    assert_eq!(diff.status(), StatusCode::OK);
+    let diff_json: Value = diff.json().await.expect("diff json");
+    let lines = diff_json["data"]["diff"]["lines"]
+        .as_array()
+        .expect("lines");
+    assert!(lines.iter().any(|line| line["change"] == "ADDED"
+        && line["ingredient_catalog_uuid"] == ingredient_uuid.as_str()));
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn recipe_version_requires_linked_product() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let (recipe_uuid, _) = helpers::create_recipe_set(&client, &token).await;
+    let res = client
+        .post(format!(
+            "{}/api/recipe-sets/{}/versions",
+            common::base_url(),
+            recipe_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("create recipe version");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn recipe_version_used_by_orders_is_locked() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let (category_uuid, _) = helpers::create_category(&client, &token).await;
+    let (recipe_uuid, _) = helpers::create_recipe_set(&client, &token).await;
+    let product_json =
+        helpers::create_product(&client, &token, &category_uuid, Some(&recipe_uuid), 50.0).await;
+    let product_uuid = product_json["data"]["product"]["uuid"]
+        .as_str()
+        .expect("product uuid");
+
+    // Item pesanan dipatok ke versi resep yang berlaku
+    let order = helpers::create_order(&client, &token, product_uuid).await;
+    assert_eq!(
+        order["data"]["items"][0]["recipe_sets_uuid"],
+        recipe_uuid.as_str()
+    );
+
+    let recipe_url = format!("{}/api/recipe-sets/{}", common::base_url(), recipe_uuid);
+    let res = client
+        .put(&recipe_url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "yield_quantity": 3 }))
+        .send()
+        .await
+        .expect("update recipe yield");
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    // Metadata tetap boleh diubah
+    let res = client
+        .put(&recipe_url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({ "name": "Renamed recipe" }))
+        .send()
+        .await
+        .expect("rename recipe");
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .delete(&recipe_url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("delete recipe");
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+}
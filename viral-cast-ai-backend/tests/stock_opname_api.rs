@@ -0,0 +1,127 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{as_f64, common, ensure_base_url};
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn stock_opname_count_and_approve_flow() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    // 4.5 units in stock at price 11
+    let (uom_uuid, _, _) = helpers::create_uom(&client, &token).await;
+    let (ingredient_uuid, _) = helpers::create_ingredient(&client, &token, &uom_uuid).await;
+    helpers::create_ingredient_stock_move(&client, &token, &ingredient_uuid).await;
+
+    let created = client
+        .post(format!("{}/api/v1/stock-opnames", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({
+            "notes": "Monthly count",
+            "ingredient_catalog_uuids": [ingredient_uuid]
+        }))
+        .send()
+        .await
+        .expect("create stock opname");
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let created_json: Value = created.json().await.expect("created json");
+    let session_uuid = created_json["data"]["uuid"]
+        .as_str()
+        .expect("session uuid")
+        .to_string();
+    assert_eq!(as_f64(&created_json["data"]["items"][0]["system_qty"]), 4.5);
+
+    // Two devices counting different shelves
+    for qty in [3.0, 1.0] {
+        let res = client
+            .patch(format!(
+                "{}/api/v1/stock-opnames/{}/counts",
+                common::base_url(),
+                session_uuid
+            ))
+            .header(AUTHORIZATION, format!("Bearer {}", token))
+            .json(&json!({
+                "counts": [{
+                    "ingredient_catalog_uuid": ingredient_uuid,
+                    "counted_qty": qty,
+                    "mode": "ADD"
+                }]
+            }))
+            .send()
+            .await
+            .expect("submit counts");
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    let detail = client
+        .get(format!(
+            "{}/api/v1/stock-opnames/{}?only_variance=true",
+            common::base_url(),
+            session_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("get stock opname");
+    assert_eq!(detail.status(), StatusCode::OK);
+    let detail_json: Value = detail.json().await.expect("detail json");
+    let item = &detail_json["data"]["items"][0];
+    assert_eq!(as_f64(&item["counted_qty"]), 4.0);
+    assert_eq!(as_f64(&item["variance_qty"]), -0.5);
+    assert_eq!(as_f64(&item["variance_value"]), -5.5);
+
+    let approved = client
+        .post(format!(
+            "{}/api/v1/stock-opnames/{}/approve",
+            common::base_url(),
+            session_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("approve stock opname");
+    assert_eq!(approved.status(), StatusCode::OK);
+    let approved_json: Value = approved.json().await.expect("approved json");
+    assert_eq!(approved_json["data"]["status"].as_str(), Some("APPROVED"));
+    assert!(approved_json["data"]["items"][0]["adjustment_move_uuid"].is_string());
+
+    // Approved sessions are frozen
+    let late_count = client
+        .patch(format!(
+            "{}/api/v1/stock-opnames/{}/counts",
+            common::base_url(),
+            session_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({
+            "counts": [{ "ingredient_catalog_uuid": ingredient_uuid, "counted_qty": 1 }]
+        }))
+        .send()
+        .await
+        .expect("late count");
+    assert_eq!(late_count.status(), StatusCode::CONFLICT);
+
+    let stock_list = client
+        .get(format!(
+            "{}/api/v1/ingredient-stocks?ingredient_catalog_uuid={}",
+            common::base_url(),
+            ingredient_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("list ingredient stocks");
+    let stock_json: Value = stock_list.json().await.expect("stock json");
+    assert_eq!(as_f64(&stock_json["data"][0]["total_quantity"]), 4.0);
+}
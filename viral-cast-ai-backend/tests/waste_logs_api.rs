@@ -0,0 +1,92 @@
+#![cfg_attr(
+    not(feature = "integration-tests"),
+    allow(dead_code, unused_imports, unused_variables)
+)]
+use reqwest::{header::AUTHORIZATION, Client, StatusCode};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{as_f64, common, ensure_base_url};
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn waste_log_posts_waste_move_and_reports_by_reason() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    // 4.5 units in stock at price 11
+    let (uom_uuid, _, _) = helpers::create_uom(&client, &token).await;
+    let (ingredient_uuid, _) = helpers::create_ingredient(&client, &token, &uom_uuid).await;
+    helpers::create_ingredient_stock_move(&client, &token, &ingredient_uuid).await;
+
+    let created = client
+        .post(format!("{}/api/v1/waste-logs", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({
+            "reason_code": "spoiled",
+            "notes": "Left out overnight",
+            "items": [
+                { "ingredient_catalog_uuid": ingredient_uuid, "quantity": 1.0 },
+                { "ingredient_catalog_uuid": ingredient_uuid, "quantity": 0.5 }
+            ]
+        }))
+        .send()
+        .await
+        .expect("create waste log");
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let created_json: Value = created.json().await.expect("created json");
+    let data = &created_json["data"];
+    assert_eq!(data["reason_code"], "SPOILED");
+    let items = data["items"].as_array().expect("items");
+    assert_eq!(items.len(), 1, "lines for the same ingredient are merged");
+    assert_eq!(as_f64(&items[0]["quantity"]), 1.5);
+    assert!(items[0]["stock_move_uuid"].is_string());
+    assert_eq!(as_f64(&data["total_cost"]), 16.5);
+
+    let report = client
+        .get(format!(
+            "{}/api/v1/waste-logs/reports/by-reason?ingredient_catalog_uuid={}",
+            common::base_url(),
+            ingredient_uuid
+        ))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .expect("waste report");
+    assert_eq!(report.status(), StatusCode::OK);
+    let report_json: Value = report.json().await.expect("report json");
+    let rows = report_json["data"]["rows"].as_array().expect("rows");
+    assert!(rows.iter().any(|row| row["reason_code"] == "SPOILED"));
+}
+
+#[cfg_attr(
+    not(feature = "integration-tests"),
+    ignore = "requires external services"
+)]
+#[tokio::test]
+async fn waste_log_rejects_unknown_reason() {
+    ensure_base_url();
+    common::ensure_server_running().await;
+    let client = Client::new();
+    let token = common::register_and_login(&client).await;
+
+    let (uom_uuid, _, _) = helpers::create_uom(&client, &token).await;
+    let (ingredient_uuid, _) = helpers::create_ingredient(&client, &token, &uom_uuid).await;
+
+    let res = client
+        .post(format!("{}/api/v1/waste-logs", common::base_url()))
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .json(&json!({
+            "reason_code": "LOST",
+            "items": [{ "ingredient_catalog_uuid": ingredient_uuid, "quantity": 1.0 }]
+        }))
+        .send()
+        .await
+        .expect("create waste log");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}